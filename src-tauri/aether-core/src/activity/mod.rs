@@ -0,0 +1,112 @@
+//! Résumé d'activité du coffre, pour le badge d'état de l'interface.
+//!
+//! Quatre compteurs : envois en attente, transferts échoués, conflits de synchronisation et
+//! avertissements d'intégrité. Les envois en attente et l'intégrité se relisent dans l'index
+//! (file hors ligne, dernier rapport, quarantaine) ; les échecs et les conflits, qui ne
+//! laissent pas de trace durable, sont comptés au fil de l'eau dans l'index jusqu'à ce que
+//! l'utilisateur les ait vus ([`acknowledge`]).
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::index::PendingKind;
+use crate::integrity;
+use crate::vault::VaultError;
+
+const COUNTERS_META_KEY: &str = "activity_counters";
+
+/// Événements comptés depuis le dernier acquittement.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct Counters {
+    failed_transfers: usize,
+    conflicts: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ActivitySummary {
+    /// Envois de la file hors ligne pas encore partis.
+    pub pending_uploads: usize,
+    /// Transferts échoués depuis le dernier acquittement.
+    pub failed_transfers: usize,
+    /// Conflits de synchronisation depuis le dernier acquittement.
+    pub conflicts: usize,
+    /// Anomalies du dernier rapport d'intégrité et fichiers en quarantaine.
+    pub integrity_warnings: usize,
+}
+
+impl ActivitySummary {
+    /// Vrai si rien ne mérite l'attention de l'utilisateur.
+    pub fn is_clear(&self) -> bool {
+        *self == ActivitySummary::default()
+    }
+}
+
+fn load_counters(index: &SqlCipherIndex) -> rusqlite::Result<Counters> {
+    Ok(index.get_meta_json(COUNTERS_META_KEY)?.unwrap_or_default())
+}
+
+fn update_counters(index: &mut SqlCipherIndex, update: impl FnOnce(&mut Counters)) -> rusqlite::Result<()> {
+    let mut counters = load_counters(index)?;
+    update(&mut counters);
+    index.put_meta_json(COUNTERS_META_KEY, &counters)
+}
+
+/// Compte un transfert échoué (un envoi reporté hors ligne n'en est pas un).
+pub fn record_failed_transfer(index: &mut SqlCipherIndex) -> rusqlite::Result<()> {
+    update_counters(index, |counters| counters.failed_transfers += 1)
+}
+
+/// Compte les conflits rencontrés en rejouant le journal d'un autre appareil.
+pub fn record_conflicts(index: &mut SqlCipherIndex, count: usize) -> rusqlite::Result<()> {
+    if count == 0 {
+        return Ok(());
+    }
+    update_counters(index, |counters| counters.conflicts += count)
+}
+
+/// Remet à zéro les échecs et conflits comptés, une fois vus par l'utilisateur.
+pub fn acknowledge(index: &mut SqlCipherIndex) -> rusqlite::Result<()> {
+    update_counters(index, |counters| *counters = Counters::default())
+}
+
+pub fn summary(index: &SqlCipherIndex) -> Result<ActivitySummary, VaultError> {
+    let counters = load_counters(index)?;
+    let pending_uploads = index
+        .list_pending()?
+        .iter()
+        .filter(|op| op.kind == PendingKind::Upload)
+        .count();
+    let report_warnings = integrity::last_report(index)?.map_or(0, |report| {
+        usize::from(!report.merkle_ok) + report.tampered_rows.len() + report.remote_drift.len()
+    });
+    Ok(ActivitySummary {
+        pending_uploads,
+        failed_transfers: counters.failed_transfers,
+        conflicts: counters.conflicts,
+        integrity_warnings: report_warnings + index.quarantined()?.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn counted_events_last_until_acknowledged() {
+        let dir = tempdir().unwrap();
+        let mut index = SqlCipherIndex::open(dir.path().join("index.db"), &[7u8; 32]).unwrap();
+        assert!(summary(&index).unwrap().is_clear());
+
+        record_failed_transfer(&mut index).unwrap();
+        record_conflicts(&mut index, 2).unwrap();
+        record_conflicts(&mut index, 0).unwrap();
+        index.quarantine(&"f1".to_string(), "Tag mismatch", "download").unwrap();
+        let counted = summary(&index).unwrap();
+        assert_eq!((counted.failed_transfers, counted.conflicts, counted.integrity_warnings), (1, 2, 1));
+
+        // L'acquittement ne touche pas à ce qui se relit dans l'index
+        acknowledge(&mut index).unwrap();
+        assert_eq!(summary(&index).unwrap(), ActivitySummary { integrity_warnings: 1, ..ActivitySummary::default() });
+    }
+}
@@ -0,0 +1,344 @@
+//! Sauvegardes automatiques de l'index vers le bucket.
+//!
+//! Une copie de la base SQLCipher (donc chiffrée avec la clé dérivée de la MasterKey) est
+//! envoyée sous [`BACKUP_PREFIX`] après chaque lot de modifications significatives. Seules
+//! les `retention` plus récentes sont conservées. Après la perte du disque local, la plus
+//! récente restaure l'index à quelques minutes près.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::clock::unix_now;
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::storj::{RemoteObject, StorjError};
+use crate::vault::{Vault, VaultError};
+
+/// Préfixe des sauvegardes dans le bucket (sous le préfixe réservé, ignoré des listes de fichiers).
+pub const BACKUP_PREFIX: &str = ".aether/index-backups/";
+
+/// Nombre de modifications qui déclenche une sauvegarde sans attendre.
+pub const BATCH_CHANGES: i64 = 50;
+
+/// Délai maximal entre une modification et sa sauvegarde.
+pub const MAX_DELAY_SECS: i64 = 5 * 60;
+
+pub(crate) const POLICY_META_KEY: &str = "index_backup_policy";
+const STATE_META_KEY: &str = "index_backup_state";
+
+#[derive(Debug)]
+pub enum BackupError {
+    /// Sauvegarde refusée (`reason` : code stable).
+    Invalid(&'static str),
+    Io(std::io::Error),
+    Vault(VaultError),
+}
+
+impl fmt::Display for BackupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackupError::Invalid(reason) => write!(f, "Invalid index backup: {}", reason),
+            BackupError::Io(e) => write!(f, "IO error: {}", e),
+            BackupError::Vault(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for BackupError {
+    fn from(e: std::io::Error) -> Self {
+        BackupError::Io(e)
+    }
+}
+
+impl From<VaultError> for BackupError {
+    fn from(e: VaultError) -> Self {
+        BackupError::Vault(e)
+    }
+}
+
+impl From<StorjError> for BackupError {
+    fn from(e: StorjError) -> Self {
+        BackupError::Vault(e.into())
+    }
+}
+
+impl From<rusqlite::Error> for BackupError {
+    fn from(e: rusqlite::Error) -> Self {
+        BackupError::Vault(e.into())
+    }
+}
+
+impl std::error::Error for BackupError {}
+
+/// Activation des sauvegardes automatiques et nombre de copies conservées.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupPolicy {
+    pub enabled: bool,
+    /// Nombre de sauvegardes conservées dans le bucket (au moins une).
+    pub retention: usize,
+}
+
+impl Default for BackupPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention: 10,
+        }
+    }
+}
+
+/// Progression des sauvegardes, conservée dans l'index.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupState {
+    /// Compteur de modifications de l'index au moment de la dernière sauvegarde.
+    pub backed_up_seq: i64,
+    /// Timestamp Unix de la dernière sauvegarde.
+    pub last_backup_at: Option<i64>,
+    /// Timestamp Unix de la première modification non sauvegardée.
+    pub pending_since: Option<i64>,
+}
+
+impl BackupState {
+    /// Prend en compte le compteur courant ; retourne `true` si une sauvegarde est due.
+    pub fn observe(&mut self, seq: i64, now: i64) -> bool {
+        let unsaved = seq - self.backed_up_seq;
+        if unsaved <= 0 {
+            self.pending_since = None;
+            return false;
+        }
+        let since = *self.pending_since.get_or_insert(now);
+        unsaved >= BATCH_CHANGES || now - since >= MAX_DELAY_SECS
+    }
+}
+
+/// Sauvegarde présente dans le bucket.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IndexBackup {
+    pub key: String,
+    /// Timestamp Unix de la sauvegarde.
+    pub created_at: i64,
+    pub size: u64,
+}
+
+impl IndexBackup {
+    fn from_remote(object: RemoteObject) -> Option<Self> {
+        let created_at = object
+            .key
+            .strip_prefix(BACKUP_PREFIX)?
+            .strip_prefix("index-")?
+            .strip_suffix(".db")?
+            .parse()
+            .ok()?;
+        Some(Self {
+            key: object.key,
+            created_at,
+            size: object.size,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupReport {
+    pub backup: IndexBackup,
+    /// Anciennes sauvegardes supprimées au-delà de la rétention.
+    pub pruned: usize,
+}
+
+/// Politique enregistrée, ou celle par défaut.
+pub fn load_policy(index: &SqlCipherIndex) -> rusqlite::Result<BackupPolicy> {
+    Ok(index.get_meta_json(POLICY_META_KEY)?.unwrap_or_default())
+}
+
+pub fn save_policy(index: &mut SqlCipherIndex, policy: &BackupPolicy) -> Result<(), BackupError> {
+    if policy.retention == 0 {
+        return Err(BackupError::Invalid("zero_retention"));
+    }
+    Ok(index.put_meta_json(POLICY_META_KEY, policy)?)
+}
+
+pub fn load_state(index: &SqlCipherIndex) -> rusqlite::Result<BackupState> {
+    Ok(index.get_meta_json(STATE_META_KEY)?.unwrap_or_default())
+}
+
+fn save_state(index: &mut SqlCipherIndex, state: &BackupState) -> rusqlite::Result<()> {
+    index.put_meta_json(STATE_META_KEY, state)
+}
+
+/// Clés à supprimer pour ne garder que les `retention` sauvegardes les plus récentes.
+pub fn retention_plan(backups: &[IndexBackup], retention: usize) -> Vec<String> {
+    let mut sorted: Vec<&IndexBackup> = backups.iter().collect();
+    sorted.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    sorted
+        .into_iter()
+        .skip(retention.max(1))
+        .map(|backup| backup.key.clone())
+        .collect()
+}
+
+/// Sauvegardes présentes dans le bucket, de la plus récente à la plus ancienne.
+pub async fn list(vault: &Vault) -> Result<Vec<IndexBackup>, BackupError> {
+    let mut backups: Vec<IndexBackup> = vault
+        .remote()
+        .list_prefix(BACKUP_PREFIX)
+        .await?
+        .into_iter()
+        .filter_map(IndexBackup::from_remote)
+        .collect();
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    Ok(backups)
+}
+
+/// Sauvegarde l'index si la politique est active et qu'un lot de modifications l'attend.
+pub async fn run_if_due(vault: &Vault) -> Result<Option<BackupReport>, BackupError> {
+    let now = unix_now();
+    let due = {
+        let mut index = vault.open_index()?;
+        if !load_policy(&index)?.enabled {
+            return Ok(None);
+        }
+        let mut state = load_state(&index)?;
+        let due = state.observe(index.change_seq()?, now);
+        save_state(&mut index, &state)?;
+        due
+    };
+    if due {
+        Ok(Some(run(vault).await?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Envoie une sauvegarde de l'index maintenant, puis applique la rétention.
+///
+/// L'échec de la suppression d'une ancienne sauvegarde est journalisé sans faire échouer
+/// la sauvegarde : elle sera retentée à la suivante.
+pub async fn run(vault: &Vault) -> Result<BackupReport, BackupError> {
+    let now = unix_now();
+    let snapshot = sibling(vault.index_path(), "snapshot");
+    let _ = fs::remove_file(&snapshot);
+    let (seq, policy) = {
+        let index = vault.open_index()?;
+        index.export_snapshot(&snapshot)?;
+        (index.change_seq()?, load_policy(&index)?)
+    };
+
+    let key = backup_key(now);
+    let size = fs::metadata(&snapshot)?.len();
+    let uploaded = vault.remote().upload_path(&key, &snapshot).await;
+    let _ = fs::remove_file(&snapshot);
+    uploaded?;
+    log::info!("Index backup uploaded: key={}, size={}", key, size);
+
+    {
+        let mut index = vault.open_index()?;
+        let mut state = load_state(&index)?;
+        state.backed_up_seq = seq;
+        state.last_backup_at = Some(now);
+        state.pending_since = None;
+        save_state(&mut index, &state)?;
+    }
+
+    let mut pruned = 0;
+    match list(vault).await {
+        Ok(backups) => {
+            for key in retention_plan(&backups, policy.retention) {
+                match vault.remote().delete_file(&key).await {
+                    Ok(()) => pruned += 1,
+                    Err(e) => log::warn!("Failed to prune index backup {}: {}", key, e),
+                }
+            }
+        }
+        Err(e) => log::warn!("Failed to list index backups for pruning: {}", e),
+    }
+
+    Ok(BackupReport {
+        backup: IndexBackup { key, created_at: now, size },
+        pruned,
+    })
+}
+
+/// Remplace l'index local par une sauvegarde du bucket ; retourne son nombre de fichiers.
+///
+/// La sauvegarde doit s'ouvrir avec la MasterKey du coffre. L'index remplacé est conservé
+/// à côté (`.before-restore`).
+pub async fn restore(vault: &Vault, key: &str) -> Result<usize, BackupError> {
+    if !key.starts_with(BACKUP_PREFIX) {
+        return Err(BackupError::Invalid("not_a_backup"));
+    }
+    let data = vault.remote().download_file(key).await?;
+    let downloaded = sibling(vault.index_path(), "restore");
+    fs::write(&downloaded, &data)?;
+
+    let files = match SqlCipherIndex::inspect_snapshot(&downloaded, vault.master_key().as_bytes()) {
+        Ok(files) => files,
+        Err(e) => {
+            log::warn!("Index backup {} does not open with this vault's key: {}", key, e);
+            let _ = fs::remove_file(&downloaded);
+            return Err(BackupError::Invalid("key_mismatch"));
+        }
+    };
+
+    let index_path = vault.index_path();
+    if index_path.exists() {
+        fs::copy(index_path, sibling(index_path, "before-restore"))?;
+    }
+    fs::rename(&downloaded, index_path)?;
+    log::info!("Index restored from backup {}: files={}", key, files);
+    Ok(files)
+}
+
+/// Clé d'une sauvegarde créée à `created_at` (timestamp Unix).
+pub(crate) fn backup_key(created_at: i64) -> String {
+    format!("{}index-{}.db", BACKUP_PREFIX, created_at)
+}
+
+/// `index.db` → `index.db.<suffix>`, dans le même dossier (renommage atomique possible).
+pub(crate) fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backup(created_at: i64) -> IndexBackup {
+        IndexBackup {
+            key: format!("{}index-{}.db", BACKUP_PREFIX, created_at),
+            created_at,
+            size: 1,
+        }
+    }
+
+    #[test]
+    fn backup_is_due_after_a_batch_or_a_delay() {
+        let mut state = BackupState { backed_up_seq: 10, ..BackupState::default() };
+        assert!(!state.observe(10, 1_000));
+
+        assert!(!state.observe(11, 1_000));
+        assert_eq!(state.pending_since, Some(1_000));
+        assert!(!state.observe(12, 1_000 + MAX_DELAY_SECS - 1));
+        assert!(state.observe(12, 1_000 + MAX_DELAY_SECS));
+
+        let mut burst = BackupState { backed_up_seq: 10, ..BackupState::default() };
+        assert!(burst.observe(10 + BATCH_CHANGES, 1_000));
+    }
+
+    #[test]
+    fn retention_keeps_the_most_recent_backups() {
+        let backups = vec![backup(300), backup(100), backup(400), backup(200)];
+        assert_eq!(retention_plan(&backups, 2), vec![backup(200).key, backup(100).key]);
+        assert_eq!(retention_plan(&backups, 0).len(), 3);
+        assert!(retention_plan(&backups, 10).is_empty());
+
+        let parsed = IndexBackup::from_remote(RemoteObject {
+            key: backup(300).key,
+            size: 1,
+            last_modified: None,
+        });
+        assert_eq!(parsed, Some(backup(300)));
+    }
+}
@@ -0,0 +1,116 @@
+//! Lit une archive chiffrée du coffre (voir `aether_core::export::archive`) sans l'application.
+//!
+//! Usage : `aether-archive <info|list|verify|extract> <ARCHIVE> [--out <DIR>]`
+//!
+//! Le mot de passe du coffre (ou celui choisi pour un lot de fichiers remis à un tiers, voir
+//! `archive::write_selection`) est lu dans `AETHER_PASSWORD`, sinon sur l'entrée standard
+//! (première ligne). `info` ne le demande pas. `verify` déchiffre chaque fichier sans rien
+//! écrire ; `extract` recrée l'arborescence en clair sous `--out`.
+
+use aether_core::crypto::{MasterKey, PasswordSecret};
+use aether_core::export::archive::{self, ArchiveReader};
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const PASSWORD_ENV: &str = "AETHER_PASSWORD";
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let (Some(command), Some(path)) = (args.next(), args.next()) else {
+        return usage();
+    };
+    let mut out: Option<PathBuf> = None;
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--out", Some(v)) => out = Some(PathBuf::from(v)),
+            _ => return usage(),
+        }
+    }
+
+    let mut reader = match ArchiveReader::open(&PathBuf::from(&path)) {
+        Ok(reader) => reader,
+        Err(e) => return fail(&format!("cannot open {}: {}", path, e)),
+    };
+    if command == "info" {
+        let header = reader.header();
+        println!("format: {}", header.format);
+        println!("vault: {}", header.vault_fingerprint);
+        println!("created_at: {}", header.created_at);
+        return ExitCode::SUCCESS;
+    }
+    if !matches!(command.as_str(), "list" | "verify" | "extract") || (command == "extract") != out.is_some() {
+        return usage();
+    }
+
+    let master_key = match read_password().map(|password| reader.unlock(&password)) {
+        Some(Ok(master_key)) => master_key,
+        Some(Err(e)) => return fail(&e.to_string()),
+        None => return fail("no password given"),
+    };
+    match command.as_str() {
+        "list" => list(&mut reader, &master_key),
+        "verify" => verify(&mut reader, &master_key),
+        _ => match archive::extract(&mut reader, &master_key, out.as_deref().unwrap_or(std::path::Path::new("."))) {
+            Ok(report) if report.failed.is_empty() => {
+                println!("{} file(s), {} folder(s) extracted", report.files, report.folders);
+                ExitCode::SUCCESS
+            }
+            Ok(report) => fail(&format!("{} file(s) could not be decrypted: {}", report.failed.len(), report.failed.join(", "))),
+            Err(e) => fail(&e.to_string()),
+        },
+    }
+}
+
+fn list(reader: &mut ArchiveReader, master_key: &MasterKey) -> ExitCode {
+    let manifest = match reader.manifest(master_key) {
+        Ok(manifest) => manifest,
+        Err(e) => return fail(&e.to_string()),
+    };
+    for folder in &manifest.folders {
+        println!("{}", folder);
+    }
+    for entry in &manifest.entries {
+        let size = manifest.objects.get(entry.object).map_or(0, |object| object.len);
+        println!("{}\t{}", entry.logical_path, size);
+    }
+    ExitCode::SUCCESS
+}
+
+fn verify(reader: &mut ArchiveReader, master_key: &MasterKey) -> ExitCode {
+    let manifest = match reader.manifest(master_key) {
+        Ok(manifest) => manifest,
+        Err(e) => return fail(&e.to_string()),
+    };
+    let failed: Vec<&str> = manifest
+        .entries
+        .iter()
+        .filter(|entry| reader.open_entry(master_key, &manifest, entry).is_err())
+        .map(|entry| entry.logical_path.as_str())
+        .collect();
+    if !failed.is_empty() {
+        return fail(&format!("{} file(s) could not be decrypted: {}", failed.len(), failed.join(", ")));
+    }
+    println!("{} file(s) verified", manifest.entries.len());
+    ExitCode::SUCCESS
+}
+
+fn read_password() -> Option<PasswordSecret> {
+    if let Ok(password) = std::env::var(PASSWORD_ENV) {
+        return Some(PasswordSecret::new(password));
+    }
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line).ok()?;
+    let password = line.trim_end_matches(['\r', '\n']);
+    (!password.is_empty()).then(|| PasswordSecret::new(password))
+}
+
+fn fail(message: &str) -> ExitCode {
+    eprintln!("aether-archive: {}", message);
+    ExitCode::FAILURE
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: aether-archive <info|list|verify|extract> <ARCHIVE> [--out <DIR>]");
+    ExitCode::from(2)
+}
@@ -0,0 +1,70 @@
+//! Daemon Aether Drive sans interface (NAS, sauvegarde permanente).
+//!
+//! Usage : `aether-daemon --data-dir <DIR> [--port <PORT>] [--sync-interval-secs <N>]`
+//!
+//! `<DIR>` est le dossier de données de l'application (celui qui contient `index.db`).
+//! Le daemon démarre verrouillé ; voir `aether_core::daemon` pour le protocole de contrôle.
+
+use aether_core::crash::{CrashLog, CRASH_DIR};
+use aether_core::daemon::Daemon;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+const DEFAULT_SYNC_INTERVAL_SECS: u64 = 300;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let mut data_dir: Option<PathBuf> = None;
+    let mut port: u16 = 0;
+    let mut interval = DEFAULT_SYNC_INTERVAL_SECS;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        match (arg.as_str(), value) {
+            ("--data-dir", Some(v)) => data_dir = Some(PathBuf::from(v)),
+            ("--port", Some(v)) => match v.parse() {
+                Ok(p) => port = p,
+                Err(_) => return usage(),
+            },
+            ("--sync-interval-secs", Some(v)) => match v.parse() {
+                Ok(n) if n > 0 => interval = n,
+                _ => return usage(),
+            },
+            _ => return usage(),
+        }
+    }
+    let Some(data_dir) = data_dir else {
+        return usage();
+    };
+
+    match CrashLog::open(data_dir.join(CRASH_DIR)) {
+        Ok(crash_log) => crash_log.install_panic_hook(),
+        Err(e) => eprintln!("aether-daemon: crash reports disabled: {}", e),
+    }
+
+    let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("aether-daemon: cannot bind control socket: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let daemon = Arc::new(Daemon::new(data_dir, Duration::from_secs(interval)));
+    match daemon.run(listener).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("aether-daemon: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: aether-daemon --data-dir <DIR> [--port <PORT>] [--sync-interval-secs <N>]");
+    ExitCode::from(2)
+}
@@ -0,0 +1,67 @@
+//! Génère les vecteurs de test du format Aether V1 et de la MKEK (voir `aether_core::vectors`).
+//!
+//! Usage : `aether-vectors [--out <FILE>] [--check <FILE>]`
+//!
+//! Sans option, la suite est écrite en JSON sur la sortie standard. `--check` compare la
+//! suite générée à un fichier existant et échoue au moindre écart (intégration continue).
+
+use aether_core::vectors::{self, TestVectors};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let mut out: Option<PathBuf> = None;
+    let mut check: Option<PathBuf> = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        match (arg.as_str(), value) {
+            ("--out", Some(v)) => out = Some(PathBuf::from(v)),
+            ("--check", Some(v)) => check = Some(PathBuf::from(v)),
+            _ => return usage(),
+        }
+    }
+
+    let suite = match vectors::generate() {
+        Ok(suite) => suite,
+        Err(e) => {
+            eprintln!("aether-vectors: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let json = serde_json::to_string_pretty(&suite).expect("test vectors serialize to JSON") + "\n";
+
+    if let Some(path) = check {
+        let expected: TestVectors = match std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()))
+        {
+            Ok(expected) => expected,
+            Err(e) => {
+                eprintln!("aether-vectors: cannot read {}: {}", path.display(), e);
+                return ExitCode::FAILURE;
+            }
+        };
+        if expected != suite {
+            eprintln!("aether-vectors: {} does not match the generated vectors", path.display());
+            return ExitCode::FAILURE;
+        }
+    }
+
+    match out {
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                eprintln!("aether-vectors: cannot write {}: {}", path.display(), e);
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{}", json),
+    }
+    ExitCode::SUCCESS
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: aether-vectors [--out <FILE>] [--check <FILE>]");
+    ExitCode::from(2)
+}
@@ -0,0 +1,224 @@
+//! Paquet de configuration d'un coffre, pour ajouter un appareil en deux étapes.
+//!
+//! L'appareil d'origine exporte un paquet scellé : sel du mot de passe, MKEK, empreinte du
+//! coffre, référence du bucket (sans identifiants : ils viennent du compte ou du trousseau)
+//! et réglages de l'index. Le paquet est chiffré sous une clé dérivée (Argon2id) d'un code
+//! de transfert aléatoire, affiché une seule fois et jamais écrit avec le paquet. Le nouvel
+//! appareil l'importe avec ce code, puis se déverrouille avec le mot de passe habituel :
+//! la MKEK seule n'ouvre rien.
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use rand::{rngs::OsRng, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use zeroize::Zeroizing;
+
+use crate::crypto::{mkek::MkekCiphertext, CryptoCore, CryptoError, PasswordSecret};
+use crate::index::sqlcipher::SqlCipherIndex;
+
+/// Extension proposée pour le fichier exporté.
+pub const FILE_EXTENSION: &str = "aethervault";
+
+const MAGIC: &[u8; 8] = b"AETHCFG1";
+const BUNDLE_AAD: &[u8] = b"aether-drive:config-bundle:v1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Alphabet du code de transfert : base32 sans caractères confondables (0/O, 1/I).
+const CODE_ALPHABET: &[u8; 32] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+/// Cinq groupes de quatre caractères : 100 bits, étirés par Argon2id.
+const CODE_GROUPS: usize = 5;
+const CODE_GROUP_LEN: usize = 4;
+
+/// Réglages de l'index transportés par le paquet ; toute autre clé est ignorée à l'import.
+const SETTINGS_META_KEYS: &[&str] = &[
+    crate::quota::QUOTA_META_KEY,
+    crate::quota::TRASH_SUGGEST_META_KEY,
+    crate::cache::policy::POLICY_META_KEY,
+    crate::backup::POLICY_META_KEY,
+    crate::search::ENABLED_META_KEY,
+];
+
+#[derive(Debug)]
+pub enum BundleError {
+    /// Fichier ou code illisible (`reason` : code stable).
+    Invalid(&'static str),
+    /// Code de transfert erroné ou paquet modifié.
+    Decrypt,
+    Crypto(CryptoError),
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BundleError::Invalid(reason) => write!(f, "Invalid vault bundle: {}", reason),
+            BundleError::Decrypt => write!(f, "Failed to open vault bundle (wrong transfer code or tampered file)"),
+            BundleError::Crypto(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<CryptoError> for BundleError {
+    fn from(e: CryptoError) -> Self {
+        BundleError::Crypto(e)
+    }
+}
+
+/// Bucket du coffre, sans identifiants d'accès.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorjReference {
+    pub endpoint: String,
+    pub bucket_name: String,
+}
+
+/// Contenu du paquet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultBundle {
+    /// Empreinte de la MasterKey : vérifiée au premier déverrouillage du nouvel appareil.
+    pub vault_fingerprint: String,
+    pub password_salt: [u8; 16],
+    pub mkek: MkekCiphertext,
+    pub storj: Option<StorjReference>,
+    /// Entrées `index_metadata` de réglage (voir [`settings`]).
+    pub settings: Vec<(String, Vec<u8>)>,
+    pub exported_at: i64,
+}
+
+/// Code de transfert neuf, en groupes séparés par des tirets (`ABCD-EFGH-…`).
+pub fn generate_code() -> Zeroizing<String> {
+    let mut code = Zeroizing::new(String::with_capacity(CODE_GROUPS * (CODE_GROUP_LEN + 1)));
+    for group in 0..CODE_GROUPS {
+        if group > 0 {
+            code.push('-');
+        }
+        for _ in 0..CODE_GROUP_LEN {
+            code.push(CODE_ALPHABET[OsRng.gen_range(0..CODE_ALPHABET.len())] as char);
+        }
+    }
+    code
+}
+
+/// Code saisi sans tirets ni espaces, en majuscules ; refusé s'il n'a pas la bonne forme.
+fn normalize_code(code: &str) -> Result<Zeroizing<String>, BundleError> {
+    let normalized: Zeroizing<String> = Zeroizing::new(
+        code.chars().filter(|c| !c.is_whitespace() && *c != '-').map(|c| c.to_ascii_uppercase()).collect(),
+    );
+    if normalized.len() != CODE_GROUPS * CODE_GROUP_LEN || !normalized.bytes().all(|b| CODE_ALPHABET.contains(&b)) {
+        return Err(BundleError::Invalid("bad_code"));
+    }
+    Ok(normalized)
+}
+
+fn cipher(code: &str, salt: &[u8; SALT_LEN]) -> Result<XChaCha20Poly1305, BundleError> {
+    let kek = CryptoCore::new().derive_kek(&PasswordSecret::new(normalize_code(code)?.as_str()), salt)?;
+    Ok(XChaCha20Poly1305::new(Key::from_slice(kek.as_bytes())))
+}
+
+/// Scelle le paquet sous le code de transfert : `MAGIC | sel | nonce | chiffré`.
+pub fn seal(bundle: &VaultBundle, code: &str) -> Result<Vec<u8>, BundleError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let json = Zeroizing::new(serde_json::to_vec(bundle).map_err(|_| BundleError::Invalid("encode"))?);
+    let ciphertext = cipher(code, &salt)?
+        .encrypt(XNonce::from_slice(&nonce), Payload { msg: &json, aad: BUNDLE_AAD })
+        .map_err(|_| BundleError::Decrypt)?;
+
+    let mut sealed = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(MAGIC);
+    sealed.extend_from_slice(&salt);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Ouvre un paquet avec le code de transfert affiché à l'export.
+pub fn open(sealed: &[u8], code: &str) -> Result<VaultBundle, BundleError> {
+    let body = sealed.strip_prefix(MAGIC).ok_or(BundleError::Invalid("not_a_bundle"))?;
+    if body.len() < SALT_LEN + NONCE_LEN {
+        return Err(BundleError::Invalid("truncated"));
+    }
+    let (salt, rest) = body.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let salt: [u8; SALT_LEN] = salt.try_into().map_err(|_| BundleError::Invalid("truncated"))?;
+    let json = Zeroizing::new(
+        cipher(code, &salt)?
+            .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: BUNDLE_AAD })
+            .map_err(|_| BundleError::Decrypt)?,
+    );
+    serde_json::from_slice(&json).map_err(|_| BundleError::Invalid("malformed"))
+}
+
+/// Réglages de l'index à transporter (seules les clés définies).
+pub fn settings(index: &SqlCipherIndex) -> rusqlite::Result<Vec<(String, Vec<u8>)>> {
+    let mut settings = Vec::new();
+    for key in SETTINGS_META_KEYS {
+        if let Some(value) = index.get_meta(key)? {
+            settings.push((key.to_string(), value));
+        }
+    }
+    Ok(settings)
+}
+
+/// Applique les réglages d'un paquet à l'index ; renvoie le nombre de réglages repris.
+pub fn apply_settings(index: &mut SqlCipherIndex, settings: &[(String, Vec<u8>)]) -> rusqlite::Result<usize> {
+    let mut applied = 0;
+    for (key, value) in settings.iter().filter(|(key, _)| SETTINGS_META_KEYS.contains(&key.as_str())) {
+        index.put_meta(key, value)?;
+        applied += 1;
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn bundle_round_trips_under_its_transfer_code_only() {
+        let dir = TempDir::new().unwrap();
+        let mut source = SqlCipherIndex::open(dir.path().join("a.db"), &[1u8; 32]).unwrap();
+        crate::quota::set_trash_suggest_percent(&mut source, Some(35)).unwrap();
+        source.put_meta("integrity_report", b"{}").unwrap();
+
+        let bundle = VaultBundle {
+            vault_fingerprint: "ab12".to_string(),
+            password_salt: [7u8; 16],
+            mkek: MkekCiphertext::new([3u8; 24], vec![1, 2, 3]),
+            storj: Some(StorjReference { endpoint: "https://gateway.storjshare.io".to_string(), bucket_name: "vault".to_string() }),
+            settings: settings(&source).unwrap(),
+            exported_at: 1_700_000_000,
+        };
+        assert_eq!(bundle.settings.len(), 1);
+
+        let code = generate_code();
+        assert_eq!(code.len(), 24);
+        let sealed = seal(&bundle, &code).unwrap();
+        assert!(!sealed.windows(4).any(|w| w == b"ab12"));
+
+        // Saisie en minuscules, sans tirets
+        let typed = code.replace('-', " ").to_lowercase();
+        let opened = open(&sealed, &typed).unwrap();
+        assert_eq!(opened.vault_fingerprint, "ab12");
+        assert_eq!(opened.mkek.payload, vec![1, 2, 3]);
+        assert_eq!(opened.storj, bundle.storj);
+
+        let other = generate_code();
+        assert!(matches!(open(&sealed, &other), Err(BundleError::Decrypt)));
+        assert!(matches!(open(&sealed, "trop-court"), Err(BundleError::Invalid("bad_code"))));
+        assert!(matches!(open(b"PK\x03\x04", &code), Err(BundleError::Invalid("not_a_bundle"))));
+
+        // Seules les clés de réglage connues sont reprises
+        let mut target = SqlCipherIndex::open(dir.path().join("b.db"), &[2u8; 32]).unwrap();
+        let mut settings = opened.settings.clone();
+        settings.push(("vault_fingerprint".to_string(), b"ff".to_vec()));
+        assert_eq!(apply_settings(&mut target, &settings).unwrap(), 1);
+        assert_eq!(crate::quota::trash_suggest_percent(&target).unwrap(), 35);
+        assert_eq!(target.get_meta("vault_fingerprint").unwrap(), None);
+    }
+}
@@ -0,0 +1,169 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::index::FileId;
+
+pub mod policy;
+
+/// Cache local des blobs Aether (toujours chiffrés).
+///
+/// Sert à la fois de cache de lecture (fichiers déjà téléchargés consultables hors
+/// ligne) et de zone de transit pour les envois différés. Rien n'y est jamais
+/// écrit en clair : un blob est exactement l'objet stocké sur Storj.
+#[derive(Debug, Clone)]
+pub struct BlobCache {
+    dir: PathBuf,
+}
+
+impl BlobCache {
+    pub fn new(dir: impl AsRef<Path>) -> io::Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    fn path_for(&self, file_id: &FileId) -> Option<PathBuf> {
+        // Les FileIds sont des UUID hex : tout autre caractère est refusé.
+        if file_id.is_empty() || !file_id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some(self.dir.join(file_id))
+    }
+
+    pub fn get(&self, file_id: &FileId) -> Option<Vec<u8>> {
+        fs::read(self.path_for(file_id)?).ok()
+    }
+
+    pub fn contains(&self, file_id: &FileId) -> bool {
+        self.path_for(file_id).is_some_and(|p| p.exists())
+    }
+
+    pub fn put(&self, file_id: &FileId, blob: &[u8]) -> io::Result<()> {
+        let path = self
+            .path_for(file_id)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid file id"))?;
+        let partial = path.with_extension("part");
+        let mut file = fs::File::create(&partial)?;
+        file.write_all(blob)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(partial, path)
+    }
+
+    /// Déplace un blob déjà écrit sur disque dans le cache (copie si le renommage échoue).
+    pub fn adopt(&self, file_id: &FileId, source: &Path) -> io::Result<()> {
+        let path = self
+            .path_for(file_id)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid file id"))?;
+        if fs::rename(source, &path).is_ok() {
+            return Ok(());
+        }
+        // Autre système de fichiers
+        self.copy_from(file_id, source)?;
+        fs::remove_file(source)
+    }
+
+    /// Copie dans le cache un blob écrit sur disque, sans le lire en mémoire ; la source
+    /// reste en place. La copie passe par un `.part` pour rester atomique.
+    pub fn copy_from(&self, file_id: &FileId, source: &Path) -> io::Result<()> {
+        let path = self
+            .path_for(file_id)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid file id"))?;
+        let partial = path.with_extension("part");
+        fs::copy(source, &partial)?;
+        fs::File::open(&partial)?.sync_all()?;
+        fs::rename(partial, path)
+    }
+
+    pub fn remove(&self, file_id: &FileId) {
+        if let Some(path) = self.path_for(file_id) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// Blobs présents et leur taille (écritures interrompues exclues).
+    pub fn entries(&self) -> io::Result<Vec<(FileId, u64)>> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            if self.path_for(&name).is_some() {
+                entries.push((name, entry.metadata()?.len()));
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Supprime les écritures interrompues (`.part`). Retourne le nombre de fichiers retirés.
+    pub fn sweep_partials(&self) -> io::Result<usize> {
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "part") {
+                fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn put_get_remove_roundtrip() {
+        let dir = tempdir().unwrap();
+        let cache = BlobCache::new(dir.path()).unwrap();
+        let id = "00ff".to_string();
+
+        cache.put(&id, b"blob").unwrap();
+        assert!(cache.contains(&id));
+        assert_eq!(cache.get(&id).unwrap(), b"blob");
+
+        cache.remove(&id);
+        assert!(cache.get(&id).is_none());
+
+        let source = dir.path().join("upload.aeth");
+        fs::write(&source, b"on disk").unwrap();
+        cache.copy_from(&id, &source).unwrap();
+        assert_eq!(cache.get(&id).unwrap(), b"on disk");
+        assert!(source.exists());
+    }
+
+    #[test]
+    fn sweep_removes_only_partials() {
+        let dir = tempdir().unwrap();
+        let cache = BlobCache::new(dir.path()).unwrap();
+        let id = "abcd".to_string();
+        cache.put(&id, b"blob").unwrap();
+        fs::write(dir.path().join("ef01.part"), b"half").unwrap();
+
+        assert_eq!(cache.sweep_partials().unwrap(), 1);
+        assert!(cache.contains(&id));
+    }
+
+    #[test]
+    fn entries_list_blobs_with_sizes() {
+        let dir = tempdir().unwrap();
+        let cache = BlobCache::new(dir.path()).unwrap();
+        cache.put(&"aa".to_string(), b"four").unwrap();
+        fs::write(dir.path().join("bb.part"), b"half").unwrap();
+
+        assert_eq!(cache.entries().unwrap(), vec![("aa".to_string(), 4)]);
+    }
+
+    #[test]
+    fn rejects_non_hex_ids() {
+        let dir = tempdir().unwrap();
+        let cache = BlobCache::new(dir.path()).unwrap();
+        assert!(cache.put(&"../escape".to_string(), b"x").is_err());
+        assert!(cache.get(&"../escape".to_string()).is_none());
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::index::FileId;
+
+/// Clé `index_metadata` sous laquelle la politique (JSON) est conservée.
+pub(crate) const POLICY_META_KEY: &str = "cache_policy";
+
+/// Un fichier doit avoir été ouvert au moins autant de fois pour être pré-chargé.
+pub const PREWARM_MIN_HITS: u32 = 3;
+
+/// Taille maximale du cache et pré-chargement des fichiers ouverts souvent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachePolicy {
+    /// Taille totale visée des blobs en cache, en octets.
+    pub max_bytes: u64,
+    /// Nombre de fichiers fréquents gardés en cache par anticipation (0 : désactivé).
+    pub prewarm_count: usize,
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self {
+            max_bytes: 2 * 1024 * 1024 * 1024,
+            prewarm_count: 10,
+        }
+    }
+}
+
+/// Ouvertures d'un fichier par l'utilisateur (aperçu, téléchargement).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccessStats {
+    pub hits: u32,
+    /// Timestamp Unix de la dernière ouverture.
+    pub last_access: i64,
+}
+
+/// Politique enregistrée, ou celle par défaut.
+pub fn load(index: &SqlCipherIndex) -> rusqlite::Result<CachePolicy> {
+    Ok(index.get_meta_json(POLICY_META_KEY)?.unwrap_or_default())
+}
+
+pub fn save(index: &mut SqlCipherIndex, policy: &CachePolicy) -> rusqlite::Result<()> {
+    index.put_meta_json(POLICY_META_KEY, policy)
+}
+
+/// Blobs à évincer pour revenir sous `max_bytes`, du plus froid au plus chaud.
+///
+/// Les blobs `pinned` (envois différés, dont le cache est la seule copie) ne sont jamais évincés.
+pub fn eviction_plan(
+    entries: &[(FileId, u64)],
+    stats: &HashMap<FileId, AccessStats>,
+    pinned: &HashSet<FileId>,
+    max_bytes: u64,
+) -> Vec<FileId> {
+    let mut total: u64 = entries.iter().map(|(_, size)| size).sum();
+    if total <= max_bytes {
+        return Vec::new();
+    }
+    let mut candidates: Vec<&(FileId, u64)> = entries.iter().filter(|(id, _)| !pinned.contains(id)).collect();
+    candidates.sort_by_key(|(id, _)| {
+        let stats = stats.get(id).copied().unwrap_or_default();
+        (stats.last_access, stats.hits)
+    });
+
+    let mut evicted = Vec::new();
+    for (id, size) in candidates {
+        if total <= max_bytes {
+            break;
+        }
+        total -= size;
+        evicted.push(id.clone());
+    }
+    evicted
+}
+
+/// Fichiers fréquents absents du cache à télécharger, dans la marge laissée par `max_bytes`.
+pub fn prewarm_candidates(
+    stats: &HashMap<FileId, AccessStats>,
+    indexed: &HashMap<FileId, u64>,
+    cached: &HashSet<FileId>,
+    cached_bytes: u64,
+    policy: &CachePolicy,
+) -> Vec<FileId> {
+    let mut frequent: Vec<(&FileId, &AccessStats)> = stats
+        .iter()
+        .filter(|(id, stats)| stats.hits >= PREWARM_MIN_HITS && indexed.contains_key(*id))
+        .collect();
+    frequent.sort_by_key(|(_, stats)| std::cmp::Reverse((stats.hits, stats.last_access)));
+
+    let mut budget = policy.max_bytes.saturating_sub(cached_bytes);
+    let mut selected = Vec::new();
+    // Les fichiers déjà en cache comptent dans les `prewarm_count` plus fréquents
+    for (id, _) in frequent.into_iter().take(policy.prewarm_count) {
+        if cached.contains(id) {
+            continue;
+        }
+        let size = indexed[id];
+        if size <= budget {
+            budget -= size;
+            selected.push(id.clone());
+        }
+    }
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(hits: u32, last_access: i64) -> AccessStats {
+        AccessStats { hits, last_access }
+    }
+
+    #[test]
+    fn evicts_coldest_unpinned_blobs_first() {
+        let entries = vec![("a".to_string(), 40), ("b".to_string(), 40), ("c".to_string(), 40), ("d".to_string(), 40)];
+        let stats = HashMap::from([
+            ("a".to_string(), stats(5, 300)),
+            ("b".to_string(), stats(1, 100)),
+            ("d".to_string(), stats(2, 200)),
+        ]);
+        let pinned = HashSet::from(["c".to_string()]);
+
+        assert!(eviction_plan(&entries, &stats, &pinned, 160).is_empty());
+        assert_eq!(eviction_plan(&entries, &stats, &pinned, 100), vec!["b".to_string(), "d".to_string()]);
+        // Même à 0, le blob épinglé reste
+        assert_eq!(eviction_plan(&entries, &stats, &pinned, 0).len(), 3);
+    }
+
+    #[test]
+    fn prewarms_frequent_files_within_budget() {
+        let policy = CachePolicy { max_bytes: 100, prewarm_count: 3 };
+        let stats = HashMap::from([
+            ("hot".to_string(), stats(9, 10)),
+            ("warm".to_string(), stats(4, 10)),
+            ("big".to_string(), stats(5, 10)),
+            ("rare".to_string(), stats(1, 10)),
+            ("gone".to_string(), stats(7, 10)),
+        ]);
+        let indexed = HashMap::from([
+            ("hot".to_string(), 30),
+            ("warm".to_string(), 30),
+            ("big".to_string(), 80),
+            ("rare".to_string(), 10),
+        ]);
+        let cached = HashSet::from(["hot".to_string()]);
+
+        assert_eq!(prewarm_candidates(&stats, &indexed, &cached, 30, &policy), vec!["warm".to_string()]);
+        let disabled = CachePolicy { prewarm_count: 0, ..policy };
+        assert!(prewarm_candidates(&stats, &indexed, &cached, 30, &disabled).is_empty());
+    }
+}
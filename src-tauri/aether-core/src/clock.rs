@@ -0,0 +1,10 @@
+//! Horloge murale partagée par les modules qui horodatent (secondes Unix).
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Secondes écoulées depuis l'epoch Unix ; 0 si l'horloge est antérieure.
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
@@ -0,0 +1,434 @@
+//! Clonage du coffre vers un second emplacement, pour répéter une restauration.
+//!
+//! Chaque objet chiffré du bucket et une copie de l'index sont copiés vers un dossier local
+//! ou un autre bucket, puis le clone est vérifié comme le ferait une vraie restauration :
+//! son index doit s'ouvrir avec la MasterKey et chaque fichier doit s'y déchiffrer depuis
+//! l'objet copié. Rien n'est déchiffré sur le disque, le clone reste aussi opaque que
+//! l'original.
+//!
+//! Dans un dossier, le clone est rangé sous [`OBJECTS_DIR`] avec l'index à la racine
+//! ([`INDEX_FILE`]) et le rapport ([`REPORT_FILE`]). Dans un bucket, l'index devient une
+//! sauvegarde (`crate::backup`) : le coffre cloné se restaure comme n'importe quel autre.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::backup::{self, BACKUP_PREFIX};
+use crate::clock::unix_now;
+use crate::export;
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::index::FileId;
+use crate::integrity;
+use crate::jobs::Job;
+use crate::storage::AetherFile;
+use crate::storj::{StorjClient, StorjError};
+use crate::vault::{self, Vault, VaultError};
+
+/// Étapes d'un clonage, annoncées via [`Job`].
+pub const CLONE_STEPS: &[&str] = &["snapshot", "copy", "verify"];
+
+/// Sous-dossier des objets chiffrés dans un clone local.
+pub const OBJECTS_DIR: &str = "objects";
+/// Copie de l'index (chiffrée par SQLCipher) dans un clone local.
+pub const INDEX_FILE: &str = "index.db";
+/// Rapport du clonage, écrit à la racine d'un clone local.
+pub const REPORT_FILE: &str = "clone.json";
+
+/// Clé `index_metadata` sous laquelle le dernier rapport est conservé.
+const REPORT_META_KEY: &str = "clone_report";
+
+#[derive(Debug)]
+pub enum CloneError {
+    /// Clonage refusé (`reason` : code stable).
+    Invalid(&'static str),
+    Io(io::Error),
+    Vault(VaultError),
+}
+
+impl fmt::Display for CloneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CloneError::Invalid(reason) => write!(f, "Invalid vault clone: {}", reason),
+            CloneError::Io(e) => write!(f, "IO error: {}", e),
+            CloneError::Vault(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<io::Error> for CloneError {
+    fn from(e: io::Error) -> Self {
+        CloneError::Io(e)
+    }
+}
+
+impl From<VaultError> for CloneError {
+    fn from(e: VaultError) -> Self {
+        CloneError::Vault(e)
+    }
+}
+
+impl From<StorjError> for CloneError {
+    fn from(e: StorjError) -> Self {
+        CloneError::Vault(e.into())
+    }
+}
+
+impl From<rusqlite::Error> for CloneError {
+    fn from(e: rusqlite::Error) -> Self {
+        CloneError::Vault(e.into())
+    }
+}
+
+impl std::error::Error for CloneError {}
+
+/// Emplacement du clone ; il doit être vide.
+pub enum CloneTarget {
+    Directory(PathBuf),
+    /// Autre bucket, ou autre préfixe du même bucket.
+    Bucket(Arc<StorjClient>),
+}
+
+impl CloneTarget {
+    /// Désignation lisible, pour le rapport et les journaux (sans identifiants).
+    pub fn describe(&self) -> String {
+        match self {
+            CloneTarget::Directory(dir) => dir.display().to_string(),
+            CloneTarget::Bucket(client) => {
+                format!("{}/{}/{}", client.endpoint().trim_end_matches('/'), client.bucket_name(), client.prefix())
+            }
+        }
+    }
+
+    async fn ensure_usable(&self, source: &StorjClient) -> Result<(), CloneError> {
+        match self {
+            CloneTarget::Directory(dir) => {
+                if dir.exists() && fs::read_dir(dir)?.next().is_some() {
+                    return Err(CloneError::Invalid("target_not_empty"));
+                }
+                fs::create_dir_all(dir)?;
+            }
+            CloneTarget::Bucket(client) => {
+                if client.endpoint() == source.endpoint()
+                    && client.bucket_name() == source.bucket_name()
+                    && client.prefix() == source.prefix()
+                {
+                    return Err(CloneError::Invalid("same_as_source"));
+                }
+                if !client.list_objects().await?.is_empty() || !client.list_prefix(BACKUP_PREFIX).await?.is_empty() {
+                    return Err(CloneError::Invalid("target_not_empty"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn put(&self, key: &str, data: &[u8]) -> Result<(), CloneError> {
+        match self {
+            CloneTarget::Directory(dir) => export::write_atomic(&dir.join(OBJECTS_DIR).join(key), data)
+                .map_err(|e| CloneError::Io(io::Error::other(e.to_string()))),
+            CloneTarget::Bucket(client) => Ok(client.upload_file(key, data).await.map(|_| ())?),
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, CloneError> {
+        match self {
+            CloneTarget::Directory(dir) => Ok(fs::read(dir.join(OBJECTS_DIR).join(key))?),
+            CloneTarget::Bucket(client) => Ok(client.download_file(key).await?),
+        }
+    }
+
+    /// Dépose la copie de l'index ; retourne l'endroit où la relire.
+    async fn put_index(&self, snapshot: &Path, now: i64) -> Result<String, CloneError> {
+        match self {
+            CloneTarget::Directory(dir) => {
+                fs::copy(snapshot, dir.join(INDEX_FILE))?;
+                Ok(INDEX_FILE.to_string())
+            }
+            CloneTarget::Bucket(client) => {
+                let key = backup::backup_key(now);
+                client.upload_path(&key, snapshot).await?;
+                Ok(key)
+            }
+        }
+    }
+
+    async fn get_index(&self, location: &str, dest: &Path) -> Result<(), CloneError> {
+        match self {
+            CloneTarget::Directory(dir) => {
+                fs::copy(dir.join(location), dest)?;
+            }
+            CloneTarget::Bucket(client) => fs::write(dest, client.download_file(location).await?)?,
+        }
+        Ok(())
+    }
+
+    /// Retire ce qui a été copié (clonage annulé). Les échecs sont journalisés.
+    async fn discard(&self, keys: &[String], index_location: Option<&str>) {
+        match self {
+            CloneTarget::Directory(dir) => {
+                // Le dossier était vide : tout ce qu'il contient vient du clonage
+                for entry in [dir.join(OBJECTS_DIR), dir.join(INDEX_FILE)] {
+                    let removed = if entry.is_dir() { fs::remove_dir_all(&entry) } else { fs::remove_file(&entry) };
+                    if let Err(e) = removed.or_else(|e| if e.kind() == io::ErrorKind::NotFound { Ok(()) } else { Err(e) }) {
+                        log::warn!("Failed to discard cancelled clone {}: {}", entry.display(), e);
+                    }
+                }
+            }
+            CloneTarget::Bucket(client) => {
+                for key in keys.iter().map(String::as_str).chain(index_location) {
+                    if let Err(e) = client.delete_file(key).await {
+                        log::warn!("Failed to discard cancelled clone object {}: {}", key, e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Élément du clone qui n'a pas passé la vérification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CloneFailure {
+    /// Clé d'objet, identifiant de fichier, ou [`INDEX_FILE`] pour l'index.
+    pub key: String,
+    pub reason: String,
+}
+
+/// Résultat d'un clonage (conservé dans l'index jusqu'au suivant).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CloneReport {
+    pub target: String,
+    /// Timestamp Unix (secondes) du clonage.
+    pub cloned_at: i64,
+    /// Objets chiffrés copiés, et leur taille totale.
+    pub objects: usize,
+    pub bytes: u64,
+    /// Fichiers de l'index cloné, et ceux qui s'y sont déchiffrés depuis le clone.
+    pub files: usize,
+    pub verified_files: usize,
+    /// Fichiers dont l'envoi est encore en attente : absents du bucket, donc du clone.
+    pub pending_files: usize,
+    pub failures: Vec<CloneFailure>,
+}
+
+impl CloneReport {
+    /// Le clone restaurerait chaque fichier du coffre.
+    pub fn is_verified(&self) -> bool {
+        self.failures.is_empty() && self.verified_files == self.files
+    }
+}
+
+/// Dernier rapport de clonage enregistré, s'il y en a un.
+pub fn last_report(index: &SqlCipherIndex) -> rusqlite::Result<Option<CloneReport>> {
+    index.get_meta_json(REPORT_META_KEY)
+}
+
+/// Clone le coffre vers `target`, étape par étape ([`CLONE_STEPS`]) et annulable.
+///
+/// Une annulation retire ce qui a déjà été copié. Un clone copié mais qui ne se vérifie pas
+/// n'est pas une erreur : le rapport en détaille les échecs.
+pub async fn clone_vault(vault: &Vault, target: &CloneTarget, job: &Job<'_>) -> Result<CloneReport, CloneError> {
+    let result = run(vault, target, job).await;
+    match &result {
+        Ok(_) => job.completed(),
+        Err(CloneError::Vault(VaultError::Cancelled)) => job.cancelled(),
+        Err(_) => job.failed(),
+    }
+    result
+}
+
+async fn run(vault: &Vault, target: &CloneTarget, job: &Job<'_>) -> Result<CloneReport, CloneError> {
+    if !job.step(0) {
+        return Err(VaultError::Cancelled.into());
+    }
+    target.ensure_usable(vault.remote()).await?;
+    let now = unix_now();
+    let snapshot = backup::sibling(vault.index_path(), "clone");
+    let _ = fs::remove_file(&snapshot);
+    vault.open_index()?.export_snapshot(&snapshot)?;
+
+    let copied = copy(vault, target, job, &snapshot, now).await;
+    let _ = fs::remove_file(&snapshot);
+    let (objects, index_location) = match copied {
+        Ok(copied) => copied,
+        Err((e, keys)) => {
+            if matches!(e, CloneError::Vault(VaultError::Cancelled)) {
+                target.discard(&keys, None).await;
+            }
+            return Err(e);
+        }
+    };
+
+    if !job.step(2) {
+        let keys: Vec<String> = objects.keys().cloned().collect();
+        target.discard(&keys, Some(&index_location)).await;
+        return Err(VaultError::Cancelled.into());
+    }
+    let mut report = CloneReport {
+        target: target.describe(),
+        cloned_at: now,
+        objects: objects.len(),
+        bytes: objects.values().sum(),
+        ..Default::default()
+    };
+    verify(vault, target, &objects, &index_location, &mut report).await?;
+
+    let raw = serde_json::to_vec(&report).map_err(|e| CloneError::Io(io::Error::other(e)))?;
+    if let CloneTarget::Directory(dir) = target {
+        export::write_atomic(&dir.join(REPORT_FILE), &raw).map_err(|e| CloneError::Io(io::Error::other(e.to_string())))?;
+    }
+    vault.open_index()?.put_meta(REPORT_META_KEY, &raw)?;
+    if report.is_verified() {
+        log::info!("Vault cloned and verified: target={}, objects={}, files={}", report.target, report.objects, report.files);
+    } else {
+        log::warn!("Vault clone failed verification: target={}, failures={}", report.target, report.failures.len());
+    }
+    Ok(report)
+}
+
+type CopyError = (CloneError, Vec<String>);
+
+/// Copie les objets chiffrés du bucket puis l'index. Retourne la taille de chaque objet
+/// copié et l'endroit de l'index ; en cas d'échec, ce qui avait déjà été copié.
+async fn copy(
+    vault: &Vault,
+    target: &CloneTarget,
+    job: &Job<'_>,
+    snapshot: &Path,
+    now: i64,
+) -> Result<(HashMap<String, u64>, String), CopyError> {
+    let mut copied: HashMap<String, u64> = HashMap::new();
+    let fail = |e: CloneError, copied: &HashMap<String, u64>| (e, copied.keys().cloned().collect());
+    if !job.step(1) {
+        return Err(fail(VaultError::Cancelled.into(), &copied));
+    }
+    let remote_objects = vault.remote().list_objects().await.map_err(|e| fail(e.into(), &copied))?;
+    for object in remote_objects {
+        if job.is_cancelled() {
+            return Err(fail(VaultError::Cancelled.into(), &copied));
+        }
+        // Les clés d'objet sont des identifiants hexadécimaux : rien d'autre n'est recopié
+        // dans un dossier, où la clé devient un nom de fichier
+        if object.key.is_empty() || !object.key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            log::warn!("Vault clone skipped unexpected object key: {}", object.key);
+            continue;
+        }
+        let data = vault.remote().download_file(&object.key).await.map_err(|e| fail(e.into(), &copied))?;
+        target.put(&object.key, &data).await.map_err(|e| fail(e, &copied))?;
+        copied.insert(object.key, data.len() as u64);
+    }
+    if job.is_cancelled() {
+        return Err(fail(VaultError::Cancelled.into(), &copied));
+    }
+    let index_location = target.put_index(snapshot, now).await.map_err(|e| fail(e, &copied))?;
+    Ok((copied, index_location))
+}
+
+/// Relit l'index du clone avec la MasterKey, vérifie l'en-tête de chaque objet copié et
+/// déchiffre chaque fichier depuis le clone.
+async fn verify(
+    vault: &Vault,
+    target: &CloneTarget,
+    objects: &HashMap<String, u64>,
+    index_location: &str,
+    report: &mut CloneReport,
+) -> Result<(), CloneError> {
+    let copy = backup::sibling(vault.index_path(), "clone-verify");
+    let _ = fs::remove_file(&copy);
+    target.get_index(index_location, &copy).await?;
+    let opened = SqlCipherIndex::open(&copy, vault.master_key().as_bytes());
+    let result = match opened {
+        Ok(index) => verify_files(vault, target, objects, &index, report).await,
+        Err(e) => {
+            report.failures.push(CloneFailure {
+                key: INDEX_FILE.to_string(),
+                reason: format!("index does not open with this vault's key: {}", e),
+            });
+            Ok(())
+        }
+    };
+    for suffix in ["", "-wal", "-shm"] {
+        let mut path = copy.clone().into_os_string();
+        path.push(suffix);
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+async fn verify_files(
+    vault: &Vault,
+    target: &CloneTarget,
+    objects: &HashMap<String, u64>,
+    index: &SqlCipherIndex,
+    report: &mut CloneReport,
+) -> Result<(), CloneError> {
+    let tampered = index.tampered_rows()?;
+    if !tampered.is_empty() || !index.verify_integrity()? {
+        report.failures.push(CloneFailure {
+            key: INDEX_FILE.to_string(),
+            reason: format!("index integrity check failed ({} tampered rows)", tampered.len()),
+        });
+        return Ok(());
+    }
+
+    // Fichiers à déchiffrer, regroupés par objet (une entrée dédupliquée partage le sien)
+    let pending = index.pending_upload_ids()?;
+    let mut by_object: HashMap<String, Vec<(FileId, String)>> = HashMap::new();
+    for (file_id, meta) in index.list_all()? {
+        if meta.logical_path.ends_with('/') || meta.encrypted_size == 0 {
+            continue;
+        }
+        if pending.contains(&file_id) {
+            report.pending_files += 1;
+            continue;
+        }
+        report.files += 1;
+        by_object.entry(index.object_key(&file_id)?).or_default().push((file_id, meta.logical_path));
+    }
+
+    for (object_key, expected_len) in objects {
+        // L'échec d'un objet vaut pour les fichiers qui le lisent
+        let files = by_object.remove(object_key).unwrap_or_default();
+        let data = match target.get(object_key).await {
+            Ok(data) => data,
+            Err(e) => {
+                report.failures.push(CloneFailure { key: object_key.clone(), reason: format!("unreadable copy: {}", e) });
+                continue;
+            }
+        };
+        let reason = (data.len() as u64 != *expected_len)
+            .then(|| format!("copy is {} bytes instead of {}", data.len(), expected_len))
+            .or_else(|| integrity::object_drift(vault.master_key(), object_key, &data, data.len() as u64));
+        if let Some(reason) = reason {
+            report.failures.push(CloneFailure { key: object_key.clone(), reason });
+            continue;
+        }
+        if files.is_empty() {
+            continue;
+        }
+        let aether_file = match AetherFile::from_bytes(&data) {
+            Ok(aether_file) => aether_file,
+            Err(e) => {
+                report.failures.push(CloneFailure { key: object_key.clone(), reason: e.to_string() });
+                continue;
+            }
+        };
+        for (file_id, logical_path) in files {
+            match vault::decrypt_with_index(index, vault.master_key(), &file_id, &aether_file, &logical_path) {
+                Ok(_) => report.verified_files += 1,
+                Err(e) => report.failures.push(CloneFailure { key: file_id, reason: e.to_string() }),
+            }
+        }
+    }
+    for files in by_object.into_values() {
+        for (file_id, _) in files {
+            report.failures.push(CloneFailure { key: file_id, reason: "object missing from the clone".to_string() });
+        }
+    }
+    Ok(())
+}
+
@@ -0,0 +1,226 @@
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use rand::{rngs::OsRng, RngCore};
+use std::backtrace::Backtrace;
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::Zeroizing;
+
+use crate::keychain::{KeychainError, KeychainStore};
+
+/// Dossier (dans le dossier de données) contenant les rapports de plantage chiffrés.
+pub const CRASH_DIR: &str = "crashes";
+const CRASH_EXTENSION: &str = "crash";
+const CRASH_AAD: &[u8] = b"aether-drive:crash-log:v1";
+const NONCE_LEN: usize = 24;
+/// En dessous de cette longueur, une suite alphanumérique n'est pas traitée comme un secret.
+const SECRET_MIN_LEN: usize = 24;
+
+/// Journal de plantages chiffré avec une clé propre à l'appareil.
+///
+/// La MasterKey n'est pas utilisable ici (un plantage peut survenir coffre verrouillé) :
+/// la clé vit dans le trousseau de l'OS. Les rapports sont en outre expurgés avant
+/// chiffrement, l'export pour un rapport de bug ne révèle donc ni secret ni chemin.
+pub struct CrashLog {
+    dir: PathBuf,
+    key: Zeroizing<[u8; 32]>,
+}
+
+impl CrashLog {
+    pub fn new(dir: impl AsRef<Path>, key: [u8; 32]) -> io::Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+            key: Zeroizing::new(key),
+        })
+    }
+
+    /// Ouvre le journal avec la clé de l'appareil (créée au premier usage).
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, KeychainError> {
+        let key = KeychainStore::crash_log()?.load_or_create_key()?;
+        Self::new(dir, key).map_err(|e| KeychainError::Backend(e.to_string()))
+    }
+
+    /// Installe le hook de panique : le hook par défaut reste appelé ensuite.
+    pub fn install_panic_hook(self) {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let message = info
+                .payload()
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| info.payload().downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "<non-string panic payload>".to_string());
+            let location = info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+                .unwrap_or_default();
+            if let Err(e) = self.write(&report_for(&message, &location)) {
+                eprintln!("Failed to write crash report: {}", e);
+            }
+            default_hook(info);
+        }));
+    }
+
+    /// Expurge, chiffre et enregistre un rapport.
+    pub fn write(&self, report: &str) -> io::Result<PathBuf> {
+        let redacted = redact(report);
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher()
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: redacted.as_bytes(), aad: CRASH_AAD })
+            .map_err(|_| io::Error::other("crash report encryption failed"))?;
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let path = self.dir.join(format!("{}.{}", millis, CRASH_EXTENSION));
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        fs::write(&path, sealed)?;
+        Ok(path)
+    }
+
+    fn entries(&self) -> io::Result<Vec<PathBuf>> {
+        let mut entries: Vec<PathBuf> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == CRASH_EXTENSION))
+            .collect();
+        entries.sort();
+        Ok(entries)
+    }
+
+    pub fn count(&self) -> io::Result<usize> {
+        Ok(self.entries()?.len())
+    }
+
+    /// Rapports déchiffrés, du plus ancien au plus récent (les illisibles sont ignorés).
+    pub fn read_all(&self) -> io::Result<Vec<String>> {
+        let mut reports = Vec::new();
+        for path in self.entries()? {
+            let sealed = fs::read(&path)?;
+            if sealed.len() <= NONCE_LEN {
+                continue;
+            }
+            let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+            match self
+                .cipher()
+                .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: CRASH_AAD })
+            {
+                Ok(plaintext) => reports.push(String::from_utf8_lossy(&plaintext).into_owned()),
+                Err(_) => log::warn!("Skipping unreadable crash report {:?}", path.file_name()),
+            }
+        }
+        Ok(reports)
+    }
+
+    /// Écrit tous les rapports (expurgés) dans un fichier texte pour un rapport de bug.
+    pub fn export(&self, destination: &Path) -> io::Result<usize> {
+        let reports = self.read_all()?;
+        fs::write(destination, reports.join("\n\n----------------------------------------\n\n"))?;
+        Ok(reports.len())
+    }
+
+    pub fn clear(&self) -> io::Result<()> {
+        for path in self.entries()? {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(Key::from_slice(self.key.as_ref()))
+    }
+}
+
+fn report_for(message: &str, location: &str) -> String {
+    format!(
+        "Aether Drive {} panicked at {}\n{}\n\nBacktrace:\n{}",
+        env!("CARGO_PKG_VERSION"),
+        location,
+        message,
+        Backtrace::force_capture()
+    )
+}
+
+/// Retire chemins et secrets d'un texte libre.
+///
+/// - tout chemin absolu devient `<path>` ; pour un fichier source Rust (`.rs`), le nom
+///   du fichier est conservé car il est indispensable au diagnostic ;
+/// - toute longue suite alphanumérique (clé hex, FileId, jeton base64) devient `<redacted>`.
+pub fn redact(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut word = String::new();
+    for c in text.chars() {
+        if c.is_whitespace() || matches!(c, '"' | '\'' | '(' | ')' | '[' | ']' | '{' | '}' | ',' | '=' | '`') {
+            out.push_str(&redact_word(&word));
+            word.clear();
+            out.push(c);
+        } else {
+            word.push(c);
+        }
+    }
+    out.push_str(&redact_word(&word));
+    out
+}
+
+fn redact_word(word: &str) -> String {
+    let is_path = word.starts_with('/') || word.starts_with('~') || word.contains('\\');
+    if is_path && word.len() > 1 {
+        let file = word.rsplit(['/', '\\']).next().unwrap_or("");
+        return match file.split(':').next() {
+            Some(name) if name.ends_with(".rs") => format!("<path>/{}", file),
+            _ => "<path>".to_string(),
+        };
+    }
+
+    let secret_like = word
+        .split(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '_' | '-')))
+        .any(|part| part.len() >= SECRET_MIN_LEN && part.chars().any(|c| c.is_ascii_digit()));
+    if secret_like {
+        return "<redacted>".to_string();
+    }
+    word.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn redacts_paths_and_secrets_but_keeps_source_files() {
+        let text = "failed to open \"/home/alice/Documents/taxes.pdf\" with key=0123456789abcdef0123456789abcdef\n  at /home/alice/.cargo/registry/src/vault/mod.rs:42:7";
+        let redacted = redact(text);
+        assert!(!redacted.contains("alice"));
+        assert!(!redacted.contains("taxes.pdf"));
+        assert!(!redacted.contains("0123456789abcdef"));
+        assert!(redacted.contains("<path>/mod.rs:42:7"));
+        assert!(redacted.contains("failed to open"));
+    }
+
+    #[test]
+    fn reports_are_encrypted_at_rest() {
+        let dir = tempdir().unwrap();
+        let log = CrashLog::new(dir.path(), [7u8; 32]).unwrap();
+        let path = log.write("boom at /Users/bob/secret.txt").unwrap();
+
+        let raw = fs::read(path).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("boom"));
+        assert_eq!(log.read_all().unwrap(), vec!["boom at <path>".to_string()]);
+
+        // Une autre clé ne relit rien
+        let other = CrashLog::new(dir.path(), [8u8; 32]).unwrap();
+        assert!(other.read_all().unwrap().is_empty());
+
+        log.clear().unwrap();
+        assert_eq!(log.count().unwrap(), 0);
+    }
+}
@@ -0,0 +1,98 @@
+//! Fichier clé : second facteur « ce que je possède » (32 octets aléatoires, sur une clé USB
+//! par exemple), mélangé à la KEK dérivée du mot de passe.
+//!
+//! La KEK d'un coffre à fichier clé est `HKDF-SHA256(sel = fichier, ikm = KEK Argon2id)` : le
+//! mot de passe seul ne suffit plus, le fichier seul non plus. La MKEK scellée sous cette KEK
+//! porte une marque lisible sans déchiffrement (voir [`MkekCiphertext::requires_keyfile`]).
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use hkdf::Hkdf;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use super::{CryptoError, Kek, KEK_LEN};
+#[cfg(doc)]
+use super::MkekCiphertext;
+
+/// Taille d'un fichier clé, en octets.
+pub const KEYFILE_LEN: usize = 32;
+const KEYFILE_KEK_INFO: &[u8] = b"aether-drive:keyfile-kek";
+
+#[derive(Clone)]
+pub struct Keyfile(Zeroizing<[u8; KEYFILE_LEN]>);
+
+impl Keyfile {
+    /// Nouveau fichier clé aléatoire.
+    pub fn generate() -> Self {
+        let mut bytes = Zeroizing::new([0u8; KEYFILE_LEN]);
+        OsRng.fill_bytes(bytes.as_mut());
+        Self(bytes)
+    }
+
+    /// `None` si `bytes` n'a pas exactement [`KEYFILE_LEN`] octets.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let array: [u8; KEYFILE_LEN] = bytes.try_into().ok()?;
+        Some(Self(Zeroizing::new(array)))
+    }
+
+    /// Lit un fichier clé ; voir [`Self::read_from`].
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::read_from(File::open(path)?)
+    }
+
+    /// Lit un fichier clé ; un fichier d'une autre taille (mauvais fichier choisi) est refusé
+    /// avec [`io::ErrorKind::InvalidData`] sans être lu en entier.
+    pub fn read_from(reader: impl Read) -> io::Result<Self> {
+        let mut buffer = Zeroizing::new(Vec::with_capacity(KEYFILE_LEN + 1));
+        reader.take(KEYFILE_LEN as u64 + 1).read_to_end(&mut buffer)?;
+        Self::from_bytes(&buffer)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "keyfile must be exactly 32 bytes"))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+
+    /// KEK du coffre à fichier clé, à partir de la KEK dérivée du mot de passe.
+    pub fn mix(&self, kek: &Kek) -> Result<Kek, CryptoError> {
+        let hkdf = Hkdf::<Sha256>::new(Some(self.as_bytes()), kek.as_bytes());
+        let mut okm = vec![0u8; KEK_LEN];
+        hkdf.expand(KEYFILE_KEK_INFO, &mut okm).map_err(|_| CryptoError::HkdfLength)?;
+        Ok(Kek::from_vec(okm))
+    }
+}
+
+impl std::fmt::Debug for Keyfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Keyfile").field(&"<redacted>").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyfile_loads_exact_length_and_changes_the_kek() {
+        let dir = tempfile::tempdir().unwrap();
+        let keyfile = Keyfile::generate();
+        let path = dir.path().join("vault.key");
+        std::fs::write(&path, keyfile.as_bytes()).unwrap();
+        let loaded = Keyfile::load(&path).unwrap();
+        assert_eq!(loaded.as_bytes(), keyfile.as_bytes());
+
+        std::fs::write(&path, [0u8; KEYFILE_LEN + 1]).unwrap();
+        assert_eq!(Keyfile::load(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let kek = Kek::from_bytes(&[5u8; KEK_LEN]);
+        let mixed = keyfile.mix(&kek).unwrap();
+        assert_ne!(mixed.as_bytes(), kek.as_bytes());
+        assert_eq!(mixed.as_bytes(), loaded.mix(&kek).unwrap().as_bytes());
+        assert_ne!(mixed.as_bytes(), Keyfile::generate().mix(&kek).unwrap().as_bytes());
+    }
+}
@@ -7,7 +7,15 @@ use serde::{Deserialize, Serialize};
 
 use super::{CryptoError, Kek, MasterKey};
 
-const MKEK_AAD: &[u8] = b"aether-drive:mkek:v1";
+pub(crate) const MKEK_AAD: &[u8] = b"aether-drive:mkek:v1";
+/// AAD d'une MKEK scellée sous le mot de passe et un fichier clé (voir [`super::keyfile`]).
+const MKEK_KEYFILE_AAD: &[u8] = b"aether-drive:mkek-keyfile:v1";
+/// Premier octet de la charge d'une MKEK à fichier clé : le besoin du fichier se lit sans
+/// rien déchiffrer, et survit aux copies qui ne gardent que `nonce` et `payload` (Wayne,
+/// paquets de configuration). Une MKEK par mot de passe seul fait exactement
+/// [`SEALED_LEN`] octets, elle ne peut pas être confondue.
+const KEYFILE_MARKER: u8 = b'K';
+const SEALED_LEN: usize = 32 + 16;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MkekCiphertext {
@@ -19,38 +27,80 @@ impl MkekCiphertext {
     pub fn new(nonce: [u8; 24], payload: Vec<u8>) -> Self {
         Self { nonce, payload }
     }
+
+    /// La MKEK ne s'ouvre qu'avec le mot de passe et le fichier clé.
+    pub fn requires_keyfile(&self) -> bool {
+        self.payload.len() == SEALED_LEN + 1 && self.payload[0] == KEYFILE_MARKER
+    }
 }
 
 pub fn encrypt_master_key(
     kek: &Kek,
     master_key: &MasterKey,
 ) -> Result<MkekCiphertext, CryptoError> {
-    let cipher = build_cipher(kek);
     let mut nonce = [0u8; 24];
     OsRng.fill_bytes(&mut nonce);
+    seal(kek, master_key, nonce)
+}
+
+/// Comme [`encrypt_master_key`], sous une KEK où le fichier clé est déjà mélangé : la MKEK
+/// porte la marque [`MkekCiphertext::requires_keyfile`].
+pub fn encrypt_master_key_for_keyfile(
+    kek: &Kek,
+    master_key: &MasterKey,
+) -> Result<MkekCiphertext, CryptoError> {
+    let mut nonce = [0u8; 24];
+    OsRng.fill_bytes(&mut nonce);
+    let cipher = build_cipher(kek);
     let ciphertext = cipher
         .encrypt(
             XNonce::from_slice(&nonce),
             Payload {
                 msg: master_key.as_bytes(),
-                aad: MKEK_AAD,
+                aad: MKEK_KEYFILE_AAD,
             },
         )
         .map_err(CryptoError::from)?;
-    Ok(MkekCiphertext::new(nonce, ciphertext))
+    let mut payload = Vec::with_capacity(ciphertext.len() + 1);
+    payload.push(KEYFILE_MARKER);
+    payload.extend_from_slice(&ciphertext);
+    Ok(MkekCiphertext::new(nonce, payload))
 }
 
-pub fn decrypt_master_key(kek: &Kek, mkek: &MkekCiphertext) -> Result<MasterKey, CryptoError> {
+/// Scelle la Master Key sous un nonce imposé (vecteurs de test, voir [`crate::vectors`]).
+#[cfg(feature = "test-vectors")]
+pub fn encrypt_master_key_with_nonce(
+    kek: &Kek,
+    master_key: &MasterKey,
+    nonce: [u8; 24],
+) -> Result<MkekCiphertext, CryptoError> {
+    seal(kek, master_key, nonce)
+}
+
+fn seal(kek: &Kek, master_key: &MasterKey, nonce: [u8; 24]) -> Result<MkekCiphertext, CryptoError> {
     let cipher = build_cipher(kek);
-    let plaintext = cipher
-        .decrypt(
-            XNonce::from_slice(&mkek.nonce),
+    let ciphertext = cipher
+        .encrypt(
+            XNonce::from_slice(&nonce),
             Payload {
-                msg: mkek.payload.as_ref(),
+                msg: master_key.as_bytes(),
                 aad: MKEK_AAD,
             },
         )
         .map_err(CryptoError::from)?;
+    Ok(MkekCiphertext::new(nonce, ciphertext))
+}
+
+pub fn decrypt_master_key(kek: &Kek, mkek: &MkekCiphertext) -> Result<MasterKey, CryptoError> {
+    let cipher = build_cipher(kek);
+    let (msg, aad) = if mkek.requires_keyfile() {
+        (&mkek.payload[1..], MKEK_KEYFILE_AAD)
+    } else {
+        (mkek.payload.as_slice(), MKEK_AAD)
+    };
+    let plaintext = cipher
+        .decrypt(XNonce::from_slice(&mkek.nonce), Payload { msg, aad })
+        .map_err(CryptoError::from)?;
     Ok(MasterKey::from_vec(plaintext))
 }
 
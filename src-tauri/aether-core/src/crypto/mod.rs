@@ -9,13 +9,19 @@ use secrecy::{ExposeSecret, SecretString};
 use sha2::Sha256;
 use zeroize::Zeroizing;
 
+pub mod keyfile;
 pub mod mkek;
+pub mod sealed;
+pub use keyfile::Keyfile;
 pub use mkek::MkekCiphertext;
 
 const KEK_LEN: usize = 32;
+/// Paramètres Argon2id (mémoire en KiB, itérations, parallélisme), voir [`CryptoCore::new`].
+pub(crate) const ARGON2_PARAMS: (u32, u32, u32) = (64 * 1024, 3, 1);
 const MASTER_KEY_LEN: usize = 32;
 const FILE_KEY_LEN: usize = 32;
 const FILE_KEY_INFO: &[u8] = b"aether-drive:file-key";
+const VAULT_FINGERPRINT_INFO: &[u8] = b"aether-drive:vault-fingerprint";
 
 /// Erreurs génériques du module Crypto Core (Phase 1).
 #[derive(Debug)]
@@ -23,6 +29,8 @@ pub enum CryptoError {
     InvalidPassword(String),
     HkdfLength,
     Aead,
+    /// La MKEK est scellée avec un fichier clé, qui n'a pas été fourni.
+    KeyfileRequired,
 }
 
 impl fmt::Display for CryptoError {
@@ -31,6 +39,7 @@ impl fmt::Display for CryptoError {
             CryptoError::InvalidPassword(err) => write!(f, "argon2 failure: {err}"),
             CryptoError::HkdfLength => write!(f, "hkdf output length invalid"),
             CryptoError::Aead => write!(f, "aead failure (xchacha20-poly1305)"),
+            CryptoError::KeyfileRequired => write!(f, "keyfile required to open this vault"),
         }
     }
 }
@@ -72,6 +81,11 @@ impl Kek {
         Self(Zeroizing::new(buffer))
     }
 
+    /// KEK conservée par l'appareil (voir [`KeyHierarchy::from_kek`]).
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self(Zeroizing::new(bytes.to_vec()))
+    }
+
     pub fn as_bytes(&self) -> &[u8] {
         self.0.as_slice()
     }
@@ -87,13 +101,23 @@ impl fmt::Debug for Kek {
 pub struct MasterKey(Zeroizing<Vec<u8>>);
 
 impl MasterKey {
-    pub(crate) fn from_vec(buffer: Vec<u8>) -> Self {
+    pub fn from_vec(buffer: Vec<u8>) -> Self {
         Self(Zeroizing::new(buffer))
     }
 
     pub fn as_bytes(&self) -> &[u8] {
         self.0.as_slice()
     }
+
+    /// Empreinte publique du coffre (16 caractères hex), dérivée par HKDF : stable d'un
+    /// appareil à l'autre, elle identifie le coffre sans rien révéler de la clé.
+    pub fn fingerprint(&self) -> String {
+        let hkdf = Hkdf::<Sha256>::new(None, self.as_bytes());
+        let mut okm = [0u8; 8];
+        hkdf.expand(VAULT_FINGERPRINT_INFO, &mut okm)
+            .expect("8 bytes is a valid HKDF-SHA256 output length");
+        hex::encode(okm)
+    }
 }
 
 impl fmt::Debug for MasterKey {
@@ -134,8 +158,11 @@ pub struct CryptoCore {
 impl CryptoCore {
     pub fn new() -> Self {
         // Paramètres CIVIL par défaut (64 MiB, 3 itérations, parallélisme 1).
+        // Ils sont figés par les MKEK existantes, y compris sur mobile : pour y éviter une
+        // dérivation à chaque ouverture, la KEK peut être confiée au trousseau de l'appareil.
+        let (m_cost, t_cost, p_cost) = ARGON2_PARAMS;
         let params =
-            Params::new(64 * 1024, 3, 1, Some(KEK_LEN)).expect("argon2 params must be valid");
+            Params::new(m_cost, t_cost, p_cost, Some(KEK_LEN)).expect("argon2 params must be valid");
         let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
         Self { argon2 }
     }
@@ -194,6 +221,8 @@ pub struct KeyHierarchy {
     core: CryptoCore,
     kek: Kek,
     master_key: MasterKey,
+    /// La KEK mélange un fichier clé : la MKEK est scellée avec la marque correspondante.
+    keyfile: bool,
 }
 
 impl KeyHierarchy {
@@ -206,6 +235,7 @@ impl KeyHierarchy {
             core,
             kek,
             master_key,
+            keyfile: false,
         })
     }
 
@@ -215,16 +245,72 @@ impl KeyHierarchy {
         salt: [u8; 16],
         mkek_ciphertext: &MkekCiphertext,
     ) -> Result<Self, CryptoError> {
+        Self::restore_with_keyfile(password, salt, mkek_ciphertext, None)
+    }
+
+    /// Comme [`Self::restore`], avec le fichier clé d'un coffre qui l'exige
+    /// ([`CryptoError::KeyfileRequired`] sinon) ; ignoré pour un coffre à mot de passe seul.
+    pub fn restore_with_keyfile(
+        password: &PasswordSecret,
+        salt: [u8; 16],
+        mkek_ciphertext: &MkekCiphertext,
+        keyfile: Option<&Keyfile>,
+    ) -> Result<Self, CryptoError> {
+        let required = mkek_ciphertext.requires_keyfile();
+        if required && keyfile.is_none() {
+            return Err(CryptoError::KeyfileRequired);
+        }
         let core = CryptoCore::default();
-        let kek = core.derive_kek(password, &salt)?;
+        let mut kek = core.derive_kek(password, &salt)?;
+        if let (true, Some(keyfile)) = (required, keyfile) {
+            kek = keyfile.mix(&kek)?;
+        }
         let master_key = mkek::decrypt_master_key(&kek, mkek_ciphertext)?;
         Ok(Self {
             core,
             kek,
             master_key,
+            keyfile: required,
+        })
+    }
+
+    /// Reconstruction depuis une KEK déjà dérivée (conservée par l'appareil) : sans Argon2.
+    /// Pour un coffre à fichier clé, la KEK conservée inclut déjà le fichier.
+    pub fn from_kek(kek: Kek, mkek_ciphertext: &MkekCiphertext) -> Result<Self, CryptoError> {
+        let master_key = mkek::decrypt_master_key(&kek, mkek_ciphertext)?;
+        Ok(Self {
+            core: CryptoCore::default(),
+            kek,
+            master_key,
+            keyfile: mkek_ciphertext.requires_keyfile(),
         })
     }
 
+    /// Même Master Key sous une nouvelle KEK (mot de passe, sel, fichier clé ou non) : seule
+    /// la MKEK change, les fichiers chiffrés restent lisibles.
+    pub fn rekey(
+        &self,
+        password: &PasswordSecret,
+        salt: [u8; 16],
+        keyfile: Option<&Keyfile>,
+    ) -> Result<Self, CryptoError> {
+        let mut kek = self.core.derive_kek(password, &salt)?;
+        if let Some(keyfile) = keyfile {
+            kek = keyfile.mix(&kek)?;
+        }
+        Ok(Self {
+            core: self.core.clone(),
+            kek,
+            master_key: MasterKey::from_vec(self.master_key.as_bytes().to_vec()),
+            keyfile: keyfile.is_some(),
+        })
+    }
+
+    /// La KEK mélange un fichier clé.
+    pub fn uses_keyfile(&self) -> bool {
+        self.keyfile
+    }
+
     pub fn kek(&self) -> &Kek {
         &self.kek
     }
@@ -238,7 +324,11 @@ impl KeyHierarchy {
     }
 
     pub fn seal_master_key(&self) -> Result<MkekCiphertext, CryptoError> {
-        mkek::encrypt_master_key(&self.kek, &self.master_key)
+        if self.keyfile {
+            mkek::encrypt_master_key_for_keyfile(&self.kek, &self.master_key)
+        } else {
+            mkek::encrypt_master_key(&self.kek, &self.master_key)
+        }
     }
 }
 
@@ -252,6 +342,11 @@ impl fmt::Debug for KeyHierarchy {
     }
 }
 
+/// Compare deux secrets en temps constant (pour une même longueur).
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -293,6 +388,17 @@ mod tests {
         assert_eq!(fk1.as_bytes(), fk2.as_bytes());
     }
 
+    #[test]
+    fn vault_fingerprint_is_stable_and_distinct_per_master_key() {
+        let core = CryptoCore::default();
+        let mk = core.generate_master_key();
+        let copy = MasterKey::from_vec(mk.as_bytes().to_vec());
+
+        assert_eq!(mk.fingerprint().len(), 16);
+        assert_eq!(mk.fingerprint(), copy.fingerprint());
+        assert_ne!(mk.fingerprint(), core.generate_master_key().fingerprint());
+    }
+
     #[test]
     fn key_hierarchy_bootstrap_and_seal_restore_roundtrip() {
         let password = PasswordSecret::new("strong-passphrase");
@@ -307,5 +413,40 @@ mod tests {
         let mk_after = restored.master_key().as_bytes().to_vec();
 
         assert_eq!(mk_before, mk_after);
+
+        let from_kek = KeyHierarchy::from_kek(Kek::from_bytes(restored.kek().as_bytes()), &mkek).unwrap();
+        assert_eq!(from_kek.master_key().as_bytes(), mk_before.as_slice());
+    }
+
+    #[test]
+    fn keyfile_rekey_requires_both_factors_and_keeps_the_master_key() {
+        let password = PasswordSecret::new("strong-passphrase");
+        let salt = [4u8; 16];
+        let hierarchy = KeyHierarchy::bootstrap(&password, salt).unwrap();
+        let plain_mkek = hierarchy.seal_master_key().unwrap();
+        assert!(!plain_mkek.requires_keyfile());
+
+        let keyfile = Keyfile::generate();
+        let enrolled = hierarchy.rekey(&password, salt, Some(&keyfile)).unwrap();
+        let mkek = enrolled.seal_master_key().unwrap();
+        assert!(mkek.requires_keyfile());
+
+        assert!(matches!(KeyHierarchy::restore(&password, salt, &mkek), Err(CryptoError::KeyfileRequired)));
+        let wrong = Keyfile::generate();
+        assert!(matches!(
+            KeyHierarchy::restore_with_keyfile(&password, salt, &mkek, Some(&wrong)),
+            Err(CryptoError::Aead)
+        ));
+        let restored = KeyHierarchy::restore_with_keyfile(&password, salt, &mkek, Some(&keyfile)).unwrap();
+        assert_eq!(restored.master_key().as_bytes(), hierarchy.master_key().as_bytes());
+        assert!(restored.uses_keyfile());
+        let from_kek = KeyHierarchy::from_kek(Kek::from_bytes(restored.kek().as_bytes()), &mkek).unwrap();
+        assert!(from_kek.uses_keyfile());
+
+        // Retrait du fichier : MKEK ordinaire, le fichier fourni en trop est ignoré
+        let removed = restored.rekey(&password, salt, None).unwrap().seal_master_key().unwrap();
+        assert!(!removed.requires_keyfile());
+        let reopened = KeyHierarchy::restore_with_keyfile(&password, salt, &removed, Some(&keyfile)).unwrap();
+        assert_eq!(reopened.master_key().as_bytes(), hierarchy.master_key().as_bytes());
     }
 }
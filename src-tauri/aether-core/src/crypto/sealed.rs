@@ -0,0 +1,113 @@
+//! Petits objets JSON scellés sous une sous-clé de la MasterKey, déposés dans le bucket à côté
+//! des fichiers (segments du journal, battements de session, verrous).
+//!
+//! Chaque famille d'objets a sa sous-clé HKDF et son préfixe d'AAD. L'objet scellé est
+//! `nonce (24 octets) || XChaCha20-Poly1305(json)`, lié à sa clé d'objet par l'AAD : il ne
+//! peut pas être présenté sous une autre.
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use hkdf::Hkdf;
+use rand::{rngs::OsRng, RngCore};
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use super::{CryptoError, MasterKey};
+
+const NONCE_LEN: usize = 24;
+
+/// Échec de scellement ou d'ouverture d'un objet.
+#[derive(Debug)]
+pub enum SealError {
+    /// Objet illisible (`reason` : code stable — `encode`, `truncated`, `decrypt`, `malformed`).
+    Invalid(&'static str),
+    Crypto(CryptoError),
+}
+
+impl From<CryptoError> for SealError {
+    fn from(e: CryptoError) -> Self {
+        SealError::Crypto(e)
+    }
+}
+
+/// Sous-clé de 32 octets dérivée de la MasterKey pour l'usage `info`.
+pub fn subkey(master_key: &MasterKey, info: &[u8]) -> Result<Zeroizing<[u8; 32]>, CryptoError> {
+    let hkdf = Hkdf::<Sha256>::new(None, master_key.as_bytes());
+    let mut key = Zeroizing::new([0u8; 32]);
+    hkdf.expand(info, key.as_mut()).map_err(|_| CryptoError::HkdfLength)?;
+    Ok(key)
+}
+
+/// Famille d'objets scellés : sous-clé (`key_info`) et préfixe d'AAD qui lui sont propres.
+#[derive(Debug, Clone, Copy)]
+pub struct SealedObjects {
+    key_info: &'static [u8],
+    aad_prefix: &'static [u8],
+}
+
+impl SealedObjects {
+    pub const fn new(key_info: &'static [u8], aad_prefix: &'static [u8]) -> Self {
+        Self { key_info, aad_prefix }
+    }
+
+    fn cipher(&self, master_key: &MasterKey) -> Result<XChaCha20Poly1305, CryptoError> {
+        let key = subkey(master_key, self.key_info)?;
+        Ok(XChaCha20Poly1305::new(Key::from_slice(key.as_ref())))
+    }
+
+    fn aad(&self, key: &str) -> Vec<u8> {
+        [self.aad_prefix, key.as_bytes()].concat()
+    }
+
+    /// Scelle `value` pour l'objet `key`.
+    pub fn seal<T: Serialize>(&self, master_key: &MasterKey, key: &str, value: &T) -> Result<Vec<u8>, SealError> {
+        let json = serde_json::to_vec(value).map_err(|_| SealError::Invalid("encode"))?;
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher(master_key)?
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: &json, aad: &self.aad(key) })
+            .map_err(CryptoError::from)?;
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    /// Ouvre l'objet `key` ; refuse un objet scellé pour une autre clé ou sous une autre MasterKey.
+    pub fn open<T: DeserializeOwned>(&self, master_key: &MasterKey, key: &str, sealed: &[u8]) -> Result<T, SealError> {
+        if sealed.len() <= NONCE_LEN {
+            return Err(SealError::Invalid("truncated"));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let json = self
+            .cipher(master_key)?
+            .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: &self.aad(key) })
+            .map_err(|_| SealError::Invalid("decrypt"))?;
+        serde_json::from_slice(&json).map_err(|_| SealError::Invalid("malformed"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OBJECTS: SealedObjects = SealedObjects::new(b"aether-drive:test-key:v1", b"aether-drive:test:v1:");
+
+    #[test]
+    fn sealed_objects_open_only_under_their_key() {
+        let master_key = MasterKey::from_vec(vec![9u8; 32]);
+        let sealed = OBJECTS.seal(&master_key, "a", &("value", 7)).unwrap();
+        assert_eq!(OBJECTS.open::<(String, i64)>(&master_key, "a", &sealed).unwrap(), ("value".to_string(), 7));
+
+        let code = |result: Result<(String, i64), SealError>| match result {
+            Err(SealError::Invalid(reason)) => reason,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(code(OBJECTS.open(&master_key, "b", &sealed)), "decrypt");
+        assert_eq!(code(OBJECTS.open(&MasterKey::from_vec(vec![1u8; 32]), "a", &sealed)), "decrypt");
+        assert_eq!(code(OBJECTS.open(&master_key, "a", &sealed[..NONCE_LEN])), "truncated");
+        let other = OBJECTS.seal(&master_key, "a", &[1u8, 2]).unwrap();
+        assert_eq!(code(OBJECTS.open(&master_key, "a", &other)), "malformed");
+    }
+}
@@ -0,0 +1,451 @@
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{Mutex, Notify};
+
+use crate::cache::BlobCache;
+use crate::clock::unix_now;
+use crate::crypto::{constant_time_eq, KeyHierarchy, Keyfile, MasterKey, MkekCiphertext, PasswordSecret};
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::keychain::KeychainStore;
+use crate::recovery;
+use crate::staging::Staging;
+use crate::storj::{self, StorjClient};
+use crate::vault::Vault;
+use crate::workspace::WorkspaceRegistry;
+
+/// Fichier (dans le dossier de données) où le daemon publie son port et son jeton.
+pub const DAEMON_ENDPOINT_FILE: &str = "daemon.json";
+const CACHE_DIR: &str = "cache";
+/// Taille maximale d'une requête de contrôle : au-delà, la connexion est fermée sans lire
+/// la suite (un `unlock`, la plus longue, tient en quelques centaines d'octets).
+const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+/// Erreurs du module Daemon.
+#[derive(Debug)]
+pub enum DaemonError {
+    Io(std::io::Error),
+    Protocol(String),
+}
+
+impl fmt::Display for DaemonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DaemonError::Io(e) => write!(f, "IO error: {}", e),
+            DaemonError::Protocol(msg) => write!(f, "Control protocol error: {}", msg),
+        }
+    }
+}
+
+impl From<std::io::Error> for DaemonError {
+    fn from(e: std::io::Error) -> Self {
+        DaemonError::Io(e)
+    }
+}
+
+impl std::error::Error for DaemonError {}
+
+/// Commandes acceptées sur le socket de contrôle (une requête JSON par ligne).
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlRequest {
+    Status,
+    Unlock {
+        password: String,
+        password_salt: [u8; 16],
+        mkek: MkekCiphertext,
+        /// Fichier clé, pour un coffre qui en exige un.
+        #[serde(default)]
+        keyfile_path: Option<PathBuf>,
+    },
+    Lock,
+    SyncNow,
+    Shutdown,
+}
+
+#[derive(Debug, Deserialize)]
+struct ControlMessage {
+    token: String,
+    #[serde(flatten)]
+    request: ControlRequest,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub unlocked: bool,
+    /// Timestamp Unix (secondes) de la dernière synchronisation réussie.
+    pub last_sync: Option<i64>,
+    pub last_sync_error: Option<String>,
+    pub remote_count: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ControlResponse {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<DaemonStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ControlResponse {
+    fn status(status: DaemonStatus) -> Self {
+        Self { ok: true, status: Some(status), error: None }
+    }
+
+    fn error(msg: impl Into<String>) -> Self {
+        Self { ok: false, status: None, error: Some(msg.into()) }
+    }
+}
+
+/// Point de contact publié pour que la GUI (ou un script) puisse s'attacher.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DaemonEndpoint {
+    pub port: u16,
+    pub token: String,
+}
+
+impl DaemonEndpoint {
+    pub fn read(data_dir: &Path) -> Result<Self, DaemonError> {
+        let raw = fs::read(data_dir.join(DAEMON_ENDPOINT_FILE))?;
+        serde_json::from_slice(&raw).map_err(|e| DaemonError::Protocol(e.to_string()))
+    }
+
+    fn write(&self, data_dir: &Path) -> Result<(), DaemonError> {
+        use std::io::Write;
+        let path = data_dir.join(DAEMON_ENDPOINT_FILE);
+        let raw = serde_json::to_vec(self).map_err(|e| DaemonError::Protocol(e.to_string()))?;
+        // Créé d'emblée en 0600 : le jeton n'est lisible par personne d'autre, même un
+        // instant. Un fichier laissé par un daemon précédent est retiré, il garderait ses droits.
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(&path)?.write_all(&raw)?;
+        Ok(())
+    }
+}
+
+/// Moteur de synchronisation sans interface.
+///
+/// Le daemon démarre verrouillé : la MasterKey n'est jamais écrite sur disque,
+/// un client doit envoyer `unlock` (mot de passe + MKEK) via le socket de contrôle.
+/// Il ouvre alors l'espace de travail actif du registre, et refuse une clé d'un autre coffre.
+/// Le socket n'écoute que sur 127.0.0.1 et chaque requête doit porter le jeton
+/// publié dans `daemon.json` (lisible uniquement par l'utilisateur).
+pub struct Daemon {
+    data_dir: PathBuf,
+    sync_interval: Duration,
+    token: String,
+    vault: Mutex<Option<Arc<Vault>>>,
+    status: Mutex<DaemonStatus>,
+    shutdown: Notify,
+}
+
+impl Daemon {
+    pub fn new(data_dir: impl AsRef<Path>, sync_interval: Duration) -> Self {
+        let mut token = [0u8; 32];
+        OsRng.fill_bytes(&mut token);
+        Self {
+            data_dir: data_dir.as_ref().to_path_buf(),
+            sync_interval,
+            token: hex::encode(token),
+            vault: Mutex::new(None),
+            status: Mutex::new(DaemonStatus::default()),
+            shutdown: Notify::new(),
+        }
+    }
+
+    /// Écoute sur `listener` jusqu'à réception de `shutdown`.
+    pub async fn run(self: Arc<Self>, listener: TcpListener) -> Result<(), DaemonError> {
+        let port = listener.local_addr()?.port();
+        fs::create_dir_all(&self.data_dir)?;
+        DaemonEndpoint { port, token: self.token.clone() }.write(&self.data_dir)?;
+        log::info!("Daemon listening on 127.0.0.1:{}", port);
+
+        let mut ticker = tokio::time::interval(self.sync_interval);
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => {
+                    let (stream, _) = accepted?;
+                    let daemon = Arc::clone(&self);
+                    tokio::spawn(async move {
+                        if let Err(e) = daemon.handle_connection(stream).await {
+                            log::warn!("Daemon control connection failed: {}", e);
+                        }
+                    });
+                }
+                _ = ticker.tick() => {
+                    self.sync().await;
+                }
+                _ = self.shutdown.notified() => break,
+            }
+        }
+
+        let _ = fs::remove_file(self.data_dir.join(DAEMON_ENDPOINT_FILE));
+        log::info!("Daemon stopped");
+        Ok(())
+    }
+
+    async fn handle_connection(&self, stream: TcpStream) -> Result<(), DaemonError> {
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = (&mut reader).take(MAX_REQUEST_BYTES as u64 + 1).read_until(b'\n', &mut line).await?;
+            if read == 0 {
+                break;
+            }
+            if line.len() > MAX_REQUEST_BYTES {
+                return Err(DaemonError::Protocol(format!("request longer than {} bytes", MAX_REQUEST_BYTES)));
+            }
+            let response = match serde_json::from_slice::<ControlMessage>(&line) {
+                Ok(msg) if constant_time_eq(msg.token.as_bytes(), self.token.as_bytes()) => self.handle(msg.request).await,
+                Ok(_) => ControlResponse::error("invalid token"),
+                Err(e) => ControlResponse::error(format!("invalid request: {}", e)),
+            };
+            let mut raw =
+                serde_json::to_vec(&response).map_err(|e| DaemonError::Protocol(e.to_string()))?;
+            raw.push(b'\n');
+            writer.write_all(&raw).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle(&self, request: ControlRequest) -> ControlResponse {
+        match request {
+            ControlRequest::Status => {}
+            ControlRequest::Unlock { password, password_salt, mkek, keyfile_path } => {
+                if let Err(msg) = self.unlock(password, password_salt, mkek, keyfile_path).await {
+                    return ControlResponse::error(msg);
+                }
+                self.recover().await;
+                self.sync().await;
+            }
+            ControlRequest::Lock => {
+                *self.vault.lock().await = None;
+                self.status.lock().await.unlocked = false;
+                log::info!("Daemon vault locked");
+            }
+            ControlRequest::SyncNow => self.sync().await,
+            ControlRequest::Shutdown => self.shutdown.notify_one(),
+        }
+        ControlResponse::status(self.status.lock().await.clone())
+    }
+
+    async fn unlock(
+        &self,
+        password: String,
+        password_salt: [u8; 16],
+        mkek: MkekCiphertext,
+        keyfile_path: Option<PathBuf>,
+    ) -> Result<(), String> {
+        let keyfile = keyfile_path
+            .map(|path| Keyfile::load(&path).map_err(|e| format!("cannot read keyfile {}: {}", path.display(), e)))
+            .transpose()?;
+        let hierarchy =
+            KeyHierarchy::restore_with_keyfile(&PasswordSecret::new(password), password_salt, &mkek, keyfile.as_ref())
+                .map_err(|e| format!("unlock failed: {}", e))?;
+        let master_key = MasterKey::from_vec(hierarchy.master_key().as_bytes().to_vec());
+        let fingerprint = master_key.fingerprint();
+
+        // Espace de travail actif et empreinte du coffre, comme au déverrouillage de l'application
+        let registry =
+            WorkspaceRegistry::load(&self.data_dir).map_err(|e| format!("cannot read workspace registry: {}", e))?;
+        let workspace = registry.active().id.clone();
+        let index_path = registry.index_path(&workspace);
+        if index_path.exists() {
+            if let Some(expected) = registry.vault_fingerprint().filter(|expected| *expected != fingerprint) {
+                return Err(format!("vault mismatch: this device holds vault {}, not {}", expected, fingerprint));
+            }
+        }
+        if let Some(parent) = index_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("cannot create workspace directory: {}", e))?;
+        }
+        let mut index = SqlCipherIndex::open(&index_path, master_key.as_bytes())
+            .map_err(|e| format!("cannot open local index: {}", e))?;
+        match index.vault_fingerprint().map_err(|e| format!("cannot read local index: {}", e))? {
+            Some(expected) if expected != fingerprint => {
+                return Err(format!("vault mismatch: local index belongs to vault {}, not {}", expected, fingerprint));
+            }
+            Some(_) => {}
+            None => index
+                .set_vault_fingerprint(&fingerprint)
+                .map_err(|e| format!("cannot write local index: {}", e))?,
+        }
+        drop(index);
+
+        let credentials = KeychainStore::storj_for_workspace(&workspace)
+            .and_then(|store| store.load(&master_key))
+            .map_err(|e| format!("cannot read OS keychain: {}", e))?
+            .ok_or("no Storj credentials in the OS keychain")?;
+        let client = StorjClient::new(credentials.into_config())
+            .await
+            .map_err(|e| format!("cannot configure Storj client: {}", e))?
+            .with_prefix(storj::vault_prefix(&fingerprint));
+
+        // Cache et zone de transit suivent l'espace actif, à côté de son index
+        let cache = BlobCache::new(index_path.with_file_name(CACHE_DIR))
+            .map_err(|e| format!("cannot open local cache: {}", e))?;
+        let staging = Staging::new(index_path.with_file_name(recovery::SCRATCH_DIR))
+            .map_err(|e| format!("cannot open staging area: {}", e))?;
+        let vault = Vault::new(master_key, index_path, Arc::new(client))
+            .with_cache(cache)
+            .with_staging(staging);
+        *self.vault.lock().await = Some(Arc::new(vault));
+        self.status.lock().await.unlocked = true;
+        log::info!("Daemon vault unlocked: workspace={}", workspace);
+        Ok(())
+    }
+
+    async fn recover(&self) {
+        let Some(vault) = self.vault.lock().await.clone() else {
+            return;
+        };
+        if let Err(e) = recovery::run(&vault, &vault.index_path().with_file_name(recovery::SCRATCH_DIR)).await {
+            log::warn!("Daemon recovery pass failed: {}", e);
+        }
+    }
+
+    async fn sync(&self) {
+        let Some(vault) = self.vault.lock().await.clone() else {
+            return;
+        };
+        if let Err(e) = vault.flush_pending().await {
+            log::warn!("Daemon failed to flush offline queue: {}", e);
+        }
+        let result = vault.reconcile().await;
+        let mut status = self.status.lock().await;
+        match result {
+            Ok(report) => {
+                status.last_sync = Some(unix_now());
+                status.last_sync_error = None;
+                status.remote_count = Some(report.remote_count);
+            }
+            Err(e) => {
+                log::warn!("Daemon sync failed: {}", e);
+                status.last_sync_error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    async fn send(port: u16, line: String) -> ControlResponse {
+        let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        writer.write_all(format!("{}\n", line).as_bytes()).await.unwrap();
+        let mut lines = BufReader::new(reader).lines();
+        serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn control_socket_requires_token() {
+        let dir = tempdir().unwrap();
+        let daemon = Arc::new(Daemon::new(dir.path(), Duration::from_secs(3600)));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let handle = tokio::spawn(Arc::clone(&daemon).run(listener));
+
+        // Laisse le daemon publier son endpoint
+        let endpoint = loop {
+            if let Ok(endpoint) = DaemonEndpoint::read(dir.path()) {
+                break endpoint;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+
+        let rejected = send(endpoint.port, r#"{"token":"nope","command":"status"}"#.to_string()).await;
+        assert!(!rejected.ok);
+
+        let status = send(
+            endpoint.port,
+            format!(r#"{{"token":"{}","command":"status"}}"#, endpoint.token),
+        )
+        .await;
+        assert!(status.ok);
+        assert!(!status.status.unwrap().unlocked);
+
+        send(
+            endpoint.port,
+            format!(r#"{{"token":"{}","command":"shutdown"}}"#, endpoint.token),
+        )
+        .await;
+        handle.await.unwrap().unwrap();
+        assert!(!dir.path().join(DAEMON_ENDPOINT_FILE).exists());
+    }
+
+    #[tokio::test]
+    async fn oversized_requests_close_the_connection() {
+        let dir = tempdir().unwrap();
+        let daemon = Arc::new(Daemon::new(dir.path(), Duration::from_secs(3600)));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = tokio::spawn(Arc::clone(&daemon).run(listener));
+
+        let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let (mut reader, mut writer) = stream.into_split();
+        // Une seule ligne sans fin : le daemon coupe sans attendre le retour à la ligne
+        let _ = writer.write_all(&vec![b'a'; MAX_REQUEST_BYTES + 1024]).await;
+        let mut buf = [0u8; 64];
+        assert!(matches!(reader.read(&mut buf).await, Ok(0) | Err(_)));
+
+        daemon.shutdown.notify_one();
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn unlock_rejects_another_vault() {
+        let dir = tempdir().unwrap();
+        let password = PasswordSecret::new("correct horse");
+        let salt = [5u8; 16];
+        let hierarchy = KeyHierarchy::bootstrap(&password, salt).unwrap();
+        let mkek = hierarchy.seal_master_key().unwrap();
+        let daemon = Daemon::new(dir.path(), Duration::from_secs(3600));
+        let unlock = || daemon.unlock("correct horse".to_string(), salt, mkek.clone(), None);
+
+        // Index de l'espace actif enregistré pour un autre coffre
+        let mut registry = WorkspaceRegistry::load(dir.path()).unwrap();
+        let index_path = registry.index_path(&registry.active().id);
+        let mut index = SqlCipherIndex::open(&index_path, hierarchy.master_key().as_bytes()).unwrap();
+        index.set_vault_fingerprint("0000000000000000").unwrap();
+        drop(index);
+        assert!(unlock().await.unwrap_err().contains("vault mismatch"));
+
+        // Empreinte du registre, vérifiée avant même d'ouvrir l'index
+        registry.set_vault_fingerprint("1111111111111111").unwrap();
+        assert!(unlock().await.unwrap_err().contains("vault 1111111111111111"));
+        assert!(!daemon.status.lock().await.unlocked);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn endpoint_file_is_private_to_the_user() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(DAEMON_ENDPOINT_FILE);
+        // Un fichier laissé lisible par un daemon précédent ne garde pas ses droits
+        fs::write(&path, b"stale").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        DaemonEndpoint { port: 4242, token: "secret".to_string() }.write(dir.path()).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(DaemonEndpoint::read(dir.path()).unwrap().port, 4242);
+    }
+}
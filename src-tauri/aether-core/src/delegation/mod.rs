@@ -0,0 +1,273 @@
+//! Identifiants de stockage délégués : limités au préfixe du coffre et à durée limitée.
+//!
+//! Plutôt que des clés S3 racines, l'appareil conserve un jeton de renouvellement qui lui
+//! est propre (révocable côté émetteur). Avec ce jeton, il obtient de l'émetteur (le service
+//! qui fournit déjà la configuration Storj) des identifiants restreints au préfixe du coffre
+//! et qui expirent. Ils sont renouvelés avant leur expiration ([`renewal_due`]). Une rotation
+//! en demande de nouveaux, puis révoque les précédents.
+//!
+//! Protocole (JSON) :
+//! - `POST <émetteur>/api/v1/storj-config/delegate`, `Authorization: Bearer <jeton>`, corps
+//!   `{ device_id, prefix, ttl_secs }`. Réponse : `{ access_key_id, secret_access_key,
+//!   endpoint, bucket_name, expires_at, renewal_token? }`. Un `renewal_token` renvoyé
+//!   remplace le précédent.
+//! - `POST <émetteur>/api/v1/storj-config/delegate/revoke`, même en-tête, corps
+//!   `{ access_key_id }`.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+use crate::clock::unix_now;
+use crate::keychain::StorjCredentials;
+
+/// Durée demandée par défaut pour des identifiants délégués.
+pub const DEFAULT_TTL_SECS: i64 = 12 * 60 * 60;
+pub const MIN_TTL_SECS: i64 = 15 * 60;
+pub const MAX_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+/// Les identifiants sont renouvelés au plus tard à cette marge de leur expiration.
+pub const RENEW_MARGIN_SECS: i64 = 10 * 60;
+
+const DELEGATE_PATH: &str = "/api/v1/storj-config/delegate";
+const REVOKE_PATH: &str = "/api/v1/storj-config/delegate/revoke";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum DelegationError {
+    /// Demande refusée localement (`reason` : code stable).
+    Invalid(&'static str),
+    /// Émetteur injoignable : les identifiants en cours restent utilisables jusqu'à expiration.
+    Unreachable(String),
+    /// L'émetteur a refusé la demande (jeton révoqué, appareil retiré…).
+    Rejected(u16),
+    /// Réponse de l'émetteur inexploitable.
+    Issuer(String),
+}
+
+impl fmt::Display for DelegationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DelegationError::Invalid(reason) => write!(f, "Invalid delegation: {}", reason),
+            DelegationError::Unreachable(msg) => write!(f, "Credential issuer unreachable: {}", msg),
+            DelegationError::Rejected(status) => write!(f, "Credential issuer refused the request: HTTP {}", status),
+            DelegationError::Issuer(msg) => write!(f, "Unusable credential issuer response: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for DelegationError {}
+
+/// Ce qu'il faut à l'appareil pour obtenir de nouveaux identifiants.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Delegation {
+    pub issuer_url: String,
+    /// Identifiant de l'appareil auprès de l'émetteur (celui du journal de synchronisation).
+    pub device_id: String,
+    pub renewal_token: String,
+    /// Préfixe du bucket auquel les identifiants sont restreints.
+    pub prefix: String,
+    pub ttl_secs: i64,
+}
+
+impl fmt::Debug for Delegation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Delegation")
+            .field("issuer_url", &self.issuer_url)
+            .field("device_id", &self.device_id)
+            .field("renewal_token", &"<redacted>")
+            .field("prefix", &self.prefix)
+            .field("ttl_secs", &self.ttl_secs)
+            .finish()
+    }
+}
+
+impl Delegation {
+    /// Vérifie l'émetteur (HTTPS, ou HTTP en boucle locale), le jeton et la durée.
+    pub fn new(
+        issuer_url: &str,
+        device_id: String,
+        renewal_token: &str,
+        prefix: String,
+        ttl_secs: Option<i64>,
+    ) -> Result<Self, DelegationError> {
+        let issuer_url = issuer_url.trim().trim_end_matches('/').to_string();
+        let parsed = reqwest::Url::parse(&issuer_url).map_err(|_| DelegationError::Invalid("invalid_url"))?;
+        let loopback = matches!(parsed.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+        if parsed.host_str().is_none() || !(parsed.scheme() == "https" || (parsed.scheme() == "http" && loopback)) {
+            return Err(DelegationError::Invalid("insecure_url"));
+        }
+        let renewal_token = renewal_token.trim();
+        if renewal_token.is_empty() || renewal_token.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err(DelegationError::Invalid("token"));
+        }
+        let ttl_secs = ttl_secs.unwrap_or(DEFAULT_TTL_SECS);
+        if !(MIN_TTL_SECS..=MAX_TTL_SECS).contains(&ttl_secs) {
+            return Err(DelegationError::Invalid("duration"));
+        }
+        Ok(Self {
+            issuer_url,
+            device_id,
+            renewal_token: renewal_token.to_string(),
+            prefix,
+            ttl_secs,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct DelegateRequest<'a> {
+    device_id: &'a str,
+    prefix: &'a str,
+    ttl_secs: i64,
+}
+
+#[derive(Deserialize)]
+struct DelegateResponse {
+    access_key_id: String,
+    secret_access_key: String,
+    endpoint: String,
+    bucket_name: String,
+    expires_at: i64,
+    #[serde(default)]
+    renewal_token: Option<String>,
+}
+
+async fn post(delegation: &Delegation, path: &str, body: Vec<u8>) -> Result<Vec<u8>, DelegationError> {
+    let response = reqwest::Client::new()
+        .post(format!("{}{}", delegation.issuer_url, path))
+        .timeout(REQUEST_TIMEOUT)
+        .bearer_auth(&delegation.renewal_token)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_connect() || e.is_timeout() {
+                DelegationError::Unreachable(e.to_string())
+            } else {
+                DelegationError::Issuer(e.to_string())
+            }
+        })?;
+    if !response.status().is_success() {
+        return Err(DelegationError::Rejected(response.status().as_u16()));
+    }
+    let body = response.bytes().await.map_err(|e| DelegationError::Issuer(e.to_string()))?;
+    Ok(body.to_vec())
+}
+
+/// Demande de nouveaux identifiants à l'émetteur.
+pub async fn issue(delegation: &Delegation) -> Result<StorjCredentials, DelegationError> {
+    let request = DelegateRequest {
+        device_id: &delegation.device_id,
+        prefix: &delegation.prefix,
+        ttl_secs: delegation.ttl_secs,
+    };
+    let body = serde_json::to_vec(&request).map_err(|e| DelegationError::Issuer(e.to_string()))?;
+    let raw = post(delegation, DELEGATE_PATH, body).await?;
+    let response: DelegateResponse =
+        serde_json::from_slice(&raw).map_err(|e| DelegationError::Issuer(e.to_string()))?;
+    if response.access_key_id.is_empty() || response.secret_access_key.is_empty() {
+        return Err(DelegationError::Issuer("empty credentials".to_string()));
+    }
+    if response.expires_at <= unix_now() {
+        return Err(DelegationError::Issuer("credentials already expired".to_string()));
+    }
+    let mut delegation = delegation.clone();
+    if let Some(token) = response.renewal_token.filter(|token| !token.trim().is_empty()) {
+        delegation.renewal_token = token;
+    }
+    log::info!("Delegated storage credentials issued, expiring at {}", response.expires_at);
+    Ok(StorjCredentials {
+        access_key_id: response.access_key_id,
+        secret_access_key: response.secret_access_key,
+        endpoint: response.endpoint,
+        bucket_name: response.bucket_name,
+        expires_at: Some(response.expires_at),
+        delegation: Some(delegation),
+    })
+}
+
+/// Révoque des identifiants délégués avant leur expiration (après une rotation).
+pub async fn revoke(delegation: &Delegation, access_key_id: &str) -> Result<(), DelegationError> {
+    let body = serde_json::to_vec(&serde_json::json!({ "access_key_id": access_key_id }))
+        .map_err(|e| DelegationError::Issuer(e.to_string()))?;
+    post(delegation, REVOKE_PATH, body).await?;
+    log::info!("Delegated storage credentials revoked");
+    Ok(())
+}
+
+/// Des identifiants délégués sont à renouveler : il leur reste moins d'un quart de leur
+/// durée, ou moins de [`RENEW_MARGIN_SECS`]. Des clés racines ne le sont jamais.
+pub fn renewal_due(credentials: &StorjCredentials, now: i64) -> bool {
+    match (credentials.expires_at, &credentials.delegation) {
+        (Some(expires_at), Some(delegation)) => expires_at - now <= RENEW_MARGIN_SECS.max(delegation.ttl_secs / 4),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn delegation(issuer_url: &str) -> Delegation {
+        Delegation::new(issuer_url, "device-1".to_string(), "renew-1", "vaults/abc/".to_string(), None).unwrap()
+    }
+
+    #[test]
+    fn issuers_must_be_https_or_local() {
+        assert!(Delegation::new("https://wayne.example/", "d".to_string(), "t", String::new(), None).is_ok());
+        assert!(Delegation::new("http://127.0.0.1:8080", "d".to_string(), "t", String::new(), None).is_ok());
+        let insecure = Delegation::new("http://wayne.example", "d".to_string(), "t", String::new(), None);
+        assert!(matches!(insecure, Err(DelegationError::Invalid("insecure_url"))));
+        let short = Delegation::new("https://wayne.example", "d".to_string(), "t", String::new(), Some(60));
+        assert!(matches!(short, Err(DelegationError::Invalid("duration"))));
+    }
+
+    #[test]
+    fn only_delegated_credentials_close_to_expiry_are_renewed() {
+        let mut credentials = StorjCredentials {
+            access_key_id: "ak".to_string(),
+            secret_access_key: "sk".to_string(),
+            endpoint: "https://gateway.storjshare.io".to_string(),
+            bucket_name: "vault".to_string(),
+            expires_at: None,
+            delegation: None,
+        };
+        assert!(!renewal_due(&credentials, i64::MAX));
+
+        credentials.delegation = Some(delegation("https://wayne.example"));
+        credentials.expires_at = Some(100_000);
+        // Un quart de 12 h reste la marge retenue
+        assert!(!renewal_due(&credentials, 100_000 - DEFAULT_TTL_SECS / 4 - 1));
+        assert!(renewal_due(&credentials, 100_000 - DEFAULT_TTL_SECS / 4));
+    }
+
+    #[tokio::test]
+    async fn issued_credentials_carry_the_rotated_renewal_token() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer_url = format!("http://{}", listener.local_addr().unwrap());
+        let expires_at = unix_now() + 3600;
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0u8; 4096];
+            let len = socket.read(&mut request).await.unwrap();
+            let body = format!(
+                r#"{{"access_key_id":"ak","secret_access_key":"sk","endpoint":"https://gateway.storjshare.io","bucket_name":"vault","expires_at":{},"renewal_token":"renew-2"}}"#,
+                expires_at
+            );
+            let response = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", body.len(), body);
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request[..len]).into_owned()
+        });
+
+        let credentials = issue(&delegation(&issuer_url)).await.unwrap();
+        let request = server.await.unwrap();
+        assert!(request.starts_with(&format!("POST {} ", DELEGATE_PATH)));
+        assert!(request.to_ascii_lowercase().contains("authorization: bearer renew-1"));
+        assert!(request.contains(r#""prefix":"vaults/abc/""#));
+        assert_eq!(credentials.expires_at, Some(expires_at));
+        assert_eq!(credentials.delegation.unwrap().renewal_token, "renew-2");
+    }
+}
@@ -0,0 +1,258 @@
+//! Auto-diagnostic : vérifie un à un les éléments dont dépend le coffre (trousseau, index,
+//! stockage distant, horloge, disque, cache) pour un écran « Dépannage ».
+//!
+//! Aucune vérification ne modifie quoi que ce soit : l'index est d'abord ouvert en lecture
+//! seule, [`SqlCipherIndex::open`] pouvant migrer son schéma.
+
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::cache::{policy, BlobCache};
+use crate::clock::unix_now;
+use crate::crypto::MasterKey;
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::keychain::{KeychainError, KeychainStore};
+use crate::storj::StorjClient;
+
+/// Délai maximal accordé à chaque requête vers le stockage distant.
+pub const REMOTE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Décalage d'horloge signalé (secondes).
+pub const CLOCK_SKEW_WARNING_SECS: i64 = 60;
+/// Décalage au-delà duquel les signatures S3 sont refusées (15 minutes).
+pub const CLOCK_SKEW_FAILURE_SECS: i64 = 15 * 60;
+/// Espace libre en dessous duquel les envois et le cache sont à l'étroit.
+pub const LOW_DISK_BYTES: u64 = 1024 * 1024 * 1024;
+/// Espace libre en dessous duquel l'index lui-même risque de ne plus pouvoir être écrit.
+pub const CRITICAL_DISK_BYTES: u64 = 100 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Failed,
+    /// Vérification impossible (coffre verrouillé, stockage non configuré…).
+    Skipped,
+}
+
+/// Résultat d'une vérification ; `name` est un code stable traduit par l'interface.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    /// Détail technique (anglais, comme les journaux).
+    pub detail: String,
+}
+
+impl DiagnosticCheck {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsReport {
+    /// Timestamp Unix (secondes) du diagnostic.
+    pub checked_at: i64,
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+impl DiagnosticsReport {
+    /// Vrai si aucune vérification n'a échoué.
+    pub fn is_healthy(&self) -> bool {
+        self.checks.iter().all(|check| check.status != CheckStatus::Failed)
+    }
+
+    pub fn status(&self, name: &str) -> Option<CheckStatus> {
+        self.checks.iter().find(|check| check.name == name).map(|check| check.status)
+    }
+}
+
+/// Ce qui est à diagnostiquer ; chaque élément peut manquer (coffre verrouillé, pas de stockage).
+pub struct DiagnosticsTarget<'a> {
+    pub master_key: Option<&'a MasterKey>,
+    pub index_path: &'a Path,
+    pub keychain: Result<KeychainStore, KeychainError>,
+    pub remote: Option<&'a StorjClient>,
+    pub cache: Option<&'a BlobCache>,
+}
+
+/// Exécute toutes les vérifications, dans l'ordre de dépendance.
+pub async fn run(target: &DiagnosticsTarget<'_>) -> DiagnosticsReport {
+    let mut checks = vec![check_keychain(target)];
+
+    let (database, index) = check_database(target);
+    checks.push(database);
+    checks.push(check_merkle(index.as_ref()));
+
+    let remote = check_remote(target.remote).await;
+    let reachable = remote.status == CheckStatus::Ok;
+    checks.push(remote);
+    checks.push(check_clock(target.remote, reachable).await);
+
+    checks.push(check_disk(target.index_path));
+    checks.push(check_cache(target.cache, index.as_ref()));
+
+    DiagnosticsReport {
+        checked_at: unix_now(),
+        checks,
+    }
+}
+
+fn check_keychain(target: &DiagnosticsTarget<'_>) -> DiagnosticCheck {
+    const NAME: &str = "keychain";
+    let store = match &target.keychain {
+        Ok(store) => store,
+        Err(e) => return DiagnosticCheck::new(NAME, CheckStatus::Failed, e.to_string()),
+    };
+    let Some(master_key) = target.master_key else {
+        return DiagnosticCheck::new(NAME, CheckStatus::Skipped, "vault locked");
+    };
+    match store.load(master_key) {
+        Ok(Some(_)) => DiagnosticCheck::new(NAME, CheckStatus::Ok, "storage credentials readable"),
+        Ok(None) => DiagnosticCheck::new(NAME, CheckStatus::Warning, "no storage credentials stored"),
+        Err(e) => DiagnosticCheck::new(NAME, CheckStatus::Failed, e.to_string()),
+    }
+}
+
+/// Ouvre l'index en lecture seule, puis normalement si la clé est la bonne.
+fn check_database(target: &DiagnosticsTarget<'_>) -> (DiagnosticCheck, Option<SqlCipherIndex>) {
+    const NAME: &str = "database";
+    let Some(master_key) = target.master_key else {
+        return (DiagnosticCheck::new(NAME, CheckStatus::Skipped, "vault locked"), None);
+    };
+    if !target.index_path.exists() {
+        return (DiagnosticCheck::new(NAME, CheckStatus::Warning, "index not created yet"), None);
+    }
+    let files = match SqlCipherIndex::inspect_snapshot(target.index_path, master_key.as_bytes()) {
+        Ok(files) => files,
+        Err(e) => return (DiagnosticCheck::new(NAME, CheckStatus::Failed, format!("index unreadable: {}", e)), None),
+    };
+    match SqlCipherIndex::open(target.index_path, master_key.as_bytes()) {
+        Ok(index) => (
+            DiagnosticCheck::new(NAME, CheckStatus::Ok, format!("{} entries indexed", files)),
+            Some(index),
+        ),
+        Err(e) => (DiagnosticCheck::new(NAME, CheckStatus::Failed, format!("index unavailable: {}", e)), None),
+    }
+}
+
+fn check_merkle(index: Option<&SqlCipherIndex>) -> DiagnosticCheck {
+    const NAME: &str = "merkle";
+    let Some(index) = index else {
+        return DiagnosticCheck::new(NAME, CheckStatus::Skipped, "index unavailable");
+    };
+    let tampered = match index.tampered_rows() {
+        Ok(rows) => rows,
+        Err(e) => return DiagnosticCheck::new(NAME, CheckStatus::Failed, e.to_string()),
+    };
+    if !tampered.is_empty() {
+        return DiagnosticCheck::new(NAME, CheckStatus::Failed, format!("{} tampered rows", tampered.len()));
+    }
+    match index.verify_integrity() {
+        Ok(true) => DiagnosticCheck::new(NAME, CheckStatus::Ok, "merkle root matches"),
+        Ok(false) => DiagnosticCheck::new(NAME, CheckStatus::Failed, "merkle root mismatch"),
+        Err(e) => DiagnosticCheck::new(NAME, CheckStatus::Failed, e.to_string()),
+    }
+}
+
+async fn check_remote(remote: Option<&StorjClient>) -> DiagnosticCheck {
+    const NAME: &str = "remote";
+    let Some(remote) = remote else {
+        return DiagnosticCheck::new(NAME, CheckStatus::Skipped, "storage not configured");
+    };
+    let started = Instant::now();
+    match tokio::time::timeout(REMOTE_TIMEOUT, remote.ping()).await {
+        Ok(Ok(())) => DiagnosticCheck::new(
+            NAME,
+            CheckStatus::Ok,
+            format!("bucket reachable in {} ms", started.elapsed().as_millis()),
+        ),
+        Ok(Err(e)) => DiagnosticCheck::new(NAME, CheckStatus::Failed, e.to_string()),
+        Err(_) => DiagnosticCheck::new(NAME, CheckStatus::Failed, "timed out"),
+    }
+}
+
+async fn check_clock(remote: Option<&StorjClient>, reachable: bool) -> DiagnosticCheck {
+    const NAME: &str = "clock";
+    let Some(remote) = remote.filter(|_| reachable) else {
+        return DiagnosticCheck::new(NAME, CheckStatus::Skipped, "storage unreachable");
+    };
+    match tokio::time::timeout(REMOTE_TIMEOUT, remote.server_time()).await {
+        Ok(Ok(server_time)) => {
+            let skew = unix_now() - server_time;
+            DiagnosticCheck::new(NAME, clock_status(skew), format!("local clock offset {} s", skew))
+        }
+        Ok(Err(e)) => DiagnosticCheck::new(NAME, CheckStatus::Skipped, e.to_string()),
+        Err(_) => DiagnosticCheck::new(NAME, CheckStatus::Skipped, "timed out"),
+    }
+}
+
+fn clock_status(skew_secs: i64) -> CheckStatus {
+    match skew_secs.abs() {
+        skew if skew >= CLOCK_SKEW_FAILURE_SECS => CheckStatus::Failed,
+        skew if skew >= CLOCK_SKEW_WARNING_SECS => CheckStatus::Warning,
+        _ => CheckStatus::Ok,
+    }
+}
+
+fn check_disk(index_path: &Path) -> DiagnosticCheck {
+    const NAME: &str = "disk";
+    let dir = index_path.parent().unwrap_or(index_path);
+    match fs2::available_space(dir) {
+        Ok(free) => DiagnosticCheck::new(NAME, disk_status(free), format!("{} bytes free", free)),
+        Err(e) => DiagnosticCheck::new(NAME, CheckStatus::Failed, e.to_string()),
+    }
+}
+
+fn disk_status(free_bytes: u64) -> CheckStatus {
+    if free_bytes < CRITICAL_DISK_BYTES {
+        CheckStatus::Failed
+    } else if free_bytes < LOW_DISK_BYTES {
+        CheckStatus::Warning
+    } else {
+        CheckStatus::Ok
+    }
+}
+
+/// Le cache doit être lisible et tenir dans la taille fixée par sa politique.
+fn check_cache(cache: Option<&BlobCache>, index: Option<&SqlCipherIndex>) -> DiagnosticCheck {
+    const NAME: &str = "cache";
+    let Some(cache) = cache else {
+        return DiagnosticCheck::new(NAME, CheckStatus::Skipped, "cache unavailable");
+    };
+    let entries = match cache.entries() {
+        Ok(entries) => entries,
+        Err(e) => return DiagnosticCheck::new(NAME, CheckStatus::Failed, e.to_string()),
+    };
+    let cached_bytes: u64 = entries.iter().map(|(_, size)| size).sum();
+    let detail = format!("{} blobs, {} bytes", entries.len(), cached_bytes);
+    let max_bytes = index.and_then(|index| policy::load(index).ok()).map(|policy| policy.max_bytes);
+    match max_bytes {
+        // Dépassement normal entre deux passes de maintenance, mais à signaler s'il persiste
+        Some(max_bytes) if cached_bytes > max_bytes => {
+            DiagnosticCheck::new(NAME, CheckStatus::Warning, format!("{} (limit {} bytes)", detail, max_bytes))
+        }
+        _ => DiagnosticCheck::new(NAME, CheckStatus::Ok, detail),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thresholds_grade_clock_skew_and_free_space() {
+        assert_eq!(clock_status(3), CheckStatus::Ok);
+        assert_eq!(clock_status(-90), CheckStatus::Warning);
+        assert_eq!(clock_status(CLOCK_SKEW_FAILURE_SECS), CheckStatus::Failed);
+        assert_eq!(disk_status(LOW_DISK_BYTES), CheckStatus::Ok);
+        assert_eq!(disk_status(LOW_DISK_BYTES - 1), CheckStatus::Warning);
+        assert_eq!(disk_status(0), CheckStatus::Failed);
+    }
+}
@@ -0,0 +1,349 @@
+//! Téléchargements reprenables par morceaux.
+//!
+//! Un objet plus gros qu'un morceau est lu par plages successives ([`CHUNK_SIZE`]) écrites
+//! dans un fichier partiel, chiffré comme l'objet lui-même. Après chaque morceau, la
+//! progression (taille et version de l'objet, empreinte SHA-256 de chaque morceau reçu) est
+//! enregistrée à côté : une connexion coupée, ou un redémarrage, reprend au premier morceau
+//! manquant au lieu de tout recommencer. À la reprise, les morceaux déjà sur disque sont
+//! revérifiés ; si l'objet distant a changé entre-temps, le téléchargement repart de zéro.
+//!
+//! Le fichier partiel et sa progression ne sont retirés qu'une fois l'objet reçu (ou
+//! disparu) : un téléchargement abandonné en cours de route, par une erreur, une annulation
+//! ou l'arrêt de l'application, reste à reprendre.
+
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::storj::{StorjClient, StorjError};
+
+/// Taille d'un morceau ; un objet qui tient dans un seul morceau est lu d'une traite.
+pub const CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+
+const PARTIAL_EXTENSION: &str = "download";
+const PROGRESS_EXTENSION: &str = "progress";
+
+/// Progression enregistrée d'un téléchargement.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Progress {
+    total: u64,
+    etag: Option<String>,
+    chunk_size: u64,
+    /// SHA-256 (hex) de chaque morceau reçu, dans l'ordre.
+    chunks: Vec<String>,
+}
+
+impl Progress {
+    fn done(&self) -> u64 {
+        (self.chunks.len() as u64 * self.chunk_size).min(self.total)
+    }
+}
+
+/// Dossier des téléchargements partiels.
+#[derive(Debug, Clone)]
+pub struct ResumableDownloads {
+    dir: PathBuf,
+    chunk_size: u64,
+}
+
+impl ResumableDownloads {
+    pub fn new(dir: impl AsRef<Path>) -> io::Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+            chunk_size: CHUNK_SIZE,
+        })
+    }
+
+    /// Taille des morceaux (au moins un octet).
+    pub fn with_chunk_size(mut self, bytes: u64) -> Self {
+        self.chunk_size = bytes.max(1);
+        self
+    }
+
+    fn paths(&self, object_key: &str) -> Option<(PathBuf, PathBuf)> {
+        // Les clés d'objet sont des UUID hex : tout autre caractère est refusé.
+        if object_key.is_empty() || !object_key.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some((
+            self.dir.join(format!("{}.{}", object_key, PARTIAL_EXTENSION)),
+            self.dir.join(format!("{}.{}", object_key, PROGRESS_EXTENSION)),
+        ))
+    }
+
+    /// Octets déjà reçus et taille totale d'un téléchargement interrompu.
+    pub fn progress(&self, object_key: &str) -> Option<(u64, u64)> {
+        let (_, progress_path) = self.paths(object_key)?;
+        let progress = load_progress(&progress_path)?;
+        Some((progress.done(), progress.total))
+    }
+
+    /// Télécharge un objet, en reprenant un téléchargement interrompu s'il y en a un.
+    ///
+    /// Un même objet déjà en cours de téléchargement ailleurs est lu d'une traite, sans
+    /// toucher au fichier partiel.
+    pub async fn fetch(&self, remote: &StorjClient, object_key: &str) -> Result<Vec<u8>, StorjError> {
+        self.fetch_with_progress(remote, object_key, |_, _| {}).await
+    }
+
+    /// Comme [`ResumableDownloads::fetch`] ; `progress` reçoit après chaque morceau les octets
+    /// reçus (reprise comprise) et la taille de l'objet.
+    pub async fn fetch_with_progress(
+        &self,
+        remote: &StorjClient,
+        object_key: &str,
+        progress: impl FnMut(u64, u64),
+    ) -> Result<Vec<u8>, StorjError> {
+        let mut downloaded = self.fetch_to_file(remote, object_key, progress).await?;
+        let mut blob = Vec::with_capacity(downloaded.len() as usize);
+        downloaded.read_to_end(&mut blob).map_err(io_error)?;
+        Ok(blob)
+    }
+
+    /// Comme [`ResumableDownloads::fetch_with_progress`], sans charger l'objet en mémoire : il
+    /// est lu depuis le fichier partiel, retiré une fois le [`Downloaded`] abandonné.
+    pub async fn fetch_to_file(
+        &self,
+        remote: &StorjClient,
+        object_key: &str,
+        mut progress: impl FnMut(u64, u64),
+    ) -> Result<Downloaded, StorjError> {
+        let Some((partial_path, progress_path)) = self.paths(object_key) else {
+            return remote.download_file(object_key).await.map(Downloaded::in_memory);
+        };
+        let partial = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&partial_path)
+            .map_err(io_error)?;
+        if partial.try_lock_exclusive().is_err() {
+            return remote.download_file(object_key).await.map(Downloaded::in_memory);
+        }
+
+        let result = self
+            .fetch_locked(remote, object_key, &partial, &partial_path, &progress_path, &mut progress)
+            .await;
+        let paths = (partial_path, progress_path);
+        match result {
+            Ok(Some(data)) => {
+                remove_partial(&paths);
+                Ok(Downloaded::in_memory(data))
+            }
+            Ok(None) => {
+                let len = partial.metadata().map_err(io_error)?.len();
+                let mut partial = partial;
+                partial.rewind().map_err(io_error)?;
+                Ok(Downloaded { source: Source::Partial { file: partial, paths }, len })
+            }
+            // Objet disparu : plus rien à reprendre
+            Err(StorjError::NotFound) => {
+                remove_partial(&paths);
+                Err(StorjError::NotFound)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Reçoit l'objet dans `partial` ; le retourne directement s'il tient dans un morceau.
+    async fn fetch_locked(
+        &self,
+        remote: &StorjClient,
+        object_key: &str,
+        mut partial: &fs::File,
+        partial_path: &Path,
+        progress_path: &Path,
+        on_progress: &mut impl FnMut(u64, u64),
+    ) -> Result<Option<Vec<u8>>, StorjError> {
+        let mut progress = match load_progress(progress_path) {
+            Some(progress) if progress.chunk_size == self.chunk_size => verified(progress, partial_path).map_err(io_error)?,
+            _ => None,
+        };
+        if let Some(progress) = &progress {
+            log::info!("Resuming download of {} at {}/{} bytes", object_key, progress.done(), progress.total);
+        }
+
+        loop {
+            let start = progress.as_ref().map_or(0, Progress::done);
+            let read = remote.download_range(object_key, start, self.chunk_size).await?;
+            if progress.as_ref().is_some_and(|current| current.total != read.total || current.etag != read.etag) {
+                log::warn!("Remote object {} changed during download, restarting", object_key);
+                progress = None;
+                continue;
+            }
+            if progress.is_none() {
+                // Tient dans un seul morceau : rien à reprendre
+                if read.data.len() as u64 >= read.total {
+                    on_progress(read.total, read.total);
+                    return Ok(Some(read.data));
+                }
+                progress = Some(Progress {
+                    total: read.total,
+                    etag: read.etag.clone(),
+                    chunk_size: self.chunk_size,
+                    chunks: Vec::new(),
+                });
+            }
+            let Some(current) = progress.as_mut() else { continue };
+
+            if read.data.len() as u64 != (current.total - start).min(self.chunk_size) {
+                return Err(StorjError::S3(format!("Short read while downloading {}", object_key)));
+            }
+            // Écrit à la suite des morceaux vérifiés (un reste d'écriture interrompue est écrasé)
+            partial.set_len(start).map_err(io_error)?;
+            partial.seek(SeekFrom::Start(start)).map_err(io_error)?;
+            partial.write_all(&read.data).map_err(io_error)?;
+            partial.sync_data().map_err(io_error)?;
+            current.chunks.push(hex::encode(Sha256::digest(&read.data)));
+            save_progress(progress_path, current).map_err(io_error)?;
+            on_progress(current.done(), current.total);
+
+            if current.done() >= current.total {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Supprime les téléchargements partiels abandonnés depuis plus de `max_age`.
+    /// Retourne le nombre de téléchargements retirés.
+    pub fn sweep_stale(&self, max_age: Duration) -> io::Result<usize> {
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if !path.extension().is_some_and(|ext| ext == PARTIAL_EXTENSION) {
+                continue;
+            }
+            let age = entry
+                .metadata()?
+                .modified()
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .unwrap_or_default();
+            if age >= max_age {
+                fs::remove_file(&path)?;
+                let _ = fs::remove_file(path.with_extension(PROGRESS_EXTENSION));
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Objet reçu en entier, lu depuis son fichier partiel (ou depuis la mémoire quand il tenait
+/// dans un seul morceau). Le fichier partiel et sa progression sont retirés à l'abandon.
+pub struct Downloaded {
+    source: Source,
+    len: u64,
+}
+
+enum Source {
+    Memory(io::Cursor<Vec<u8>>),
+    /// Fichier partiel complet, toujours verrouillé ; chemins du fichier et de sa progression.
+    Partial { file: fs::File, paths: (PathBuf, PathBuf) },
+}
+
+impl Downloaded {
+    fn in_memory(data: Vec<u8>) -> Self {
+        let len = data.len() as u64;
+        Self { source: Source::Memory(io::Cursor::new(data)), len }
+    }
+
+    /// Taille de l'objet.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Read for Downloaded {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.source {
+            Source::Memory(cursor) => cursor.read(buf),
+            Source::Partial { file, .. } => file.read(buf),
+        }
+    }
+}
+
+impl Drop for Downloaded {
+    fn drop(&mut self) {
+        if let Source::Partial { paths, .. } = &self.source {
+            remove_partial(paths);
+        }
+    }
+}
+
+fn remove_partial((partial_path, progress_path): &(PathBuf, PathBuf)) {
+    let _ = fs::remove_file(progress_path);
+    let _ = fs::remove_file(partial_path);
+}
+
+fn io_error(e: io::Error) -> StorjError {
+    StorjError::Io(format!("Partial download: {}", e))
+}
+
+fn load_progress(path: &Path) -> Option<Progress> {
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+fn save_progress(path: &Path, progress: &Progress) -> io::Result<()> {
+    let partial = path.with_extension("tmp");
+    fs::write(&partial, serde_json::to_vec(progress).unwrap_or_default())?;
+    fs::rename(partial, path)
+}
+
+/// Garde les morceaux dont le contenu sur disque correspond encore à l'empreinte
+/// enregistrée, jusqu'au premier qui diffère.
+fn verified(mut progress: Progress, partial_path: &Path) -> io::Result<Option<Progress>> {
+    let mut file = fs::File::open(partial_path)?;
+    let mut buffer = vec![0u8; progress.chunk_size as usize];
+    let mut kept = 0;
+    for (i, expected) in progress.chunks.iter().enumerate() {
+        let len = (progress.total - i as u64 * progress.chunk_size).min(progress.chunk_size) as usize;
+        if file.read_exact(&mut buffer[..len]).is_err() || hex::encode(Sha256::digest(&buffer[..len])) != *expected {
+            break;
+        }
+        kept += 1;
+    }
+    if kept < progress.chunks.len() {
+        log::warn!("Partial download {} damaged after chunk {}, resuming from there", partial_path.display(), kept);
+    }
+    progress.chunks.truncate(kept);
+    Ok((kept > 0).then_some(progress))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn damaged_chunks_on_disk_are_fetched_again() {
+        let dir = tempdir().unwrap();
+        let partial_path = dir.path().join("abcd.download");
+        let chunks = [b"0123".as_slice(), b"4567", b"89"];
+        fs::write(&partial_path, chunks.concat()).unwrap();
+        let progress = Progress {
+            total: 10,
+            etag: Some("v1".to_string()),
+            chunk_size: 4,
+            chunks: chunks.iter().map(|chunk| hex::encode(Sha256::digest(chunk))).collect(),
+        };
+        assert_eq!(verified(progress.clone(), &partial_path).unwrap().unwrap().done(), 10);
+
+        // Le second morceau a été altéré sur disque : la reprise repart de lui
+        fs::write(&partial_path, b"0123xxxx89").unwrap();
+        assert_eq!(verified(progress.clone(), &partial_path).unwrap().unwrap().done(), 4);
+        fs::write(&partial_path, b"01").unwrap();
+        assert!(verified(progress, &partial_path).unwrap().is_none());
+    }
+}
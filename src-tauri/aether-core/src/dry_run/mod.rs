@@ -0,0 +1,166 @@
+//! Essais à blanc des opérations destructives ou groupées.
+//!
+//! Chaque fonction décrit ce que l'opération correspondante toucherait, sans rien modifier :
+//! de quoi remplir une boîte de confirmation avant de demander le mot de passe.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use rusqlite::Result as SqliteResult;
+use serde::Serialize;
+
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::index::FileId;
+use crate::{guest, search};
+
+/// Ce qu'une opération toucherait.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DryRunReport {
+    /// Entrées de l'index concernées (fichiers locaux pour [`reset_local`]).
+    pub entries: usize,
+    /// Leurs chemins logiques, triés (chemins des fichiers locaux pour [`reset_local`]).
+    pub paths: Vec<String>,
+    /// Objets distants qui seraient supprimés.
+    pub deleted_objects: usize,
+    /// Octets chiffrés concernés : libérés à distance par une suppression, déplacés par une
+    /// mise à la corbeille.
+    pub bytes: u64,
+}
+
+impl DryRunReport {
+    fn with_paths(mut paths: Vec<String>) -> Self {
+        paths.sort();
+        Self {
+            entries: paths.len(),
+            paths,
+            ..Default::default()
+        }
+    }
+}
+
+/// Vidage de la corbeille (`Vault::empty_trash`) : un objet n'est supprimé que si rien hors
+/// de la corbeille ne le lit (déduplication, versions, instantanés).
+pub fn empty_trash(index: &SqlCipherIndex) -> SqliteResult<DryRunReport> {
+    let trash = index.list_trash()?;
+    let kept = index.objects_outside_trash()?;
+    let links = index.blob_links()?;
+    let mut released = HashSet::new();
+    let mut bytes = 0;
+    for (file_id, meta, _) in &trash {
+        let object_key = links.get(file_id).unwrap_or(file_id);
+        if meta.logical_path.ends_with('/') || kept.contains(object_key) {
+            continue;
+        }
+        if released.insert(object_key.clone()) {
+            bytes += meta.encrypted_size;
+        }
+    }
+    Ok(DryRunReport {
+        deleted_objects: released.len(),
+        bytes,
+        ..DryRunReport::with_paths(trash.into_iter().map(|(_, meta, _)| meta.logical_path).collect())
+    })
+}
+
+/// Suppression définitive d'un fichier de la corbeille (`Vault::purge`) : son objet reste à
+/// distance s'il est épinglé par un instantané ou lu par une autre entrée. Rapport vide si
+/// le fichier n'est pas dans la corbeille.
+pub fn purge(index: &SqlCipherIndex, file_id: &FileId) -> SqliteResult<DryRunReport> {
+    let Some(meta) = index.get_trashed(file_id)? else {
+        return Ok(DryRunReport::default());
+    };
+    let deleted = !index.is_pinned(file_id)? && !index.object_shared(file_id)?;
+    Ok(DryRunReport {
+        deleted_objects: usize::from(deleted),
+        bytes: if deleted { meta.encrypted_size } else { 0 },
+        ..DryRunReport::with_paths(vec![meta.logical_path])
+    })
+}
+
+/// Suppression de la base locale : l'index, le texte indexé pour la recherche et les clés
+/// invité. Rien n'est supprimé à distance. Les fichiers sont décrits sans ouvrir l'index,
+/// qui peut appartenir à un autre coffre.
+pub fn reset_local(index_path: &Path) -> DryRunReport {
+    let files: Vec<(String, u64)> = [
+        index_path.to_path_buf(),
+        search::content_db_path(index_path),
+        guest::sealed_keys_path(index_path),
+    ]
+    .into_iter()
+    .filter_map(|path| Some((path.to_string_lossy().into_owned(), std::fs::metadata(&path).ok()?.len())))
+    .collect();
+    DryRunReport {
+        bytes: files.iter().map(|(_, len)| len).sum(),
+        ..DryRunReport::with_paths(files.into_iter().map(|(path, _)| path).collect())
+    }
+}
+
+/// Mise à la corbeille d'un dossier et de sa sous-arborescence : rien n'est supprimé à distance.
+pub fn trash_folder(index: &SqlCipherIndex, folder_path: &str) -> SqliteResult<DryRunReport> {
+    let prefix = format!("{}/", folder_path.trim_end_matches('/'));
+    let moved: Vec<_> = index
+        .list_all()?
+        .into_iter()
+        .filter(|(_, meta)| meta.logical_path.starts_with(&prefix))
+        .collect();
+    Ok(DryRunReport {
+        bytes: moved.iter().map(|(_, meta)| meta.encrypted_size).sum(),
+        ..DryRunReport::with_paths(moved.into_iter().map(|(_, meta)| meta.logical_path).collect())
+    })
+}
+
+/// Suppression d'un instantané (voir `crate::snapshot::plan_delete`) : les objets qu'il
+/// était seul à retenir sont supprimés.
+pub fn delete_snapshot(index: &SqlCipherIndex, snapshot_id: i64) -> SqliteResult<DryRunReport> {
+    let released = index.snapshot_released(snapshot_id)?;
+    Ok(DryRunReport {
+        deleted_objects: released.len(),
+        bytes: released.iter().map(|(_, meta)| meta.encrypted_size).sum(),
+        ..DryRunReport::with_paths(released.into_iter().map(|(_, meta)| meta.logical_path).collect())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::FileMetadata;
+    use tempfile::TempDir;
+
+    fn file(path: &str, size: u64) -> FileMetadata {
+        FileMetadata { logical_path: path.to_string(), encrypted_size: size }
+    }
+
+    #[test]
+    fn reports_match_what_the_operations_then_do() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = SqlCipherIndex::open(temp_dir.path().join("index.db"), &[6u8; 32]).unwrap();
+        index.upsert("b".to_string(), file("/docs/sub/b.txt", 60)).unwrap();
+        index.upsert("c".to_string(), file("/other.txt", 10)).unwrap();
+        let snapshot = index.create_snapshot("avant").unwrap();
+        index.upsert("a".to_string(), file("/docs/a.txt", 40)).unwrap();
+
+        let report = trash_folder(&index, "/docs").unwrap();
+        assert_eq!(report.paths, vec!["/docs/a.txt", "/docs/sub/b.txt"]);
+        assert_eq!((report.entries, report.deleted_objects, report.bytes), (2, 0, 100));
+        assert_eq!(index.list_all().unwrap().len(), 3);
+        assert_eq!(index.move_folder_to_trash("/docs").unwrap(), report.entries);
+
+        // `b` reste retenu par l'instantané : seul l'objet de `a` serait supprimé
+        let report = empty_trash(&index).unwrap();
+        assert_eq!((report.entries, report.deleted_objects, report.bytes), (2, 1, 40));
+        assert_eq!(purge(&index, &"a".to_string()).unwrap().bytes, 40);
+        assert_eq!(purge(&index, &"b".to_string()).unwrap().deleted_objects, 0);
+        assert_eq!(purge(&index, &"c".to_string()).unwrap(), DryRunReport::default());
+
+        index.remove(&"c".to_string()).unwrap();
+        let report = delete_snapshot(&index, snapshot.id).unwrap();
+        assert_eq!(report.paths, vec!["/other.txt"]);
+        assert_eq!((report.deleted_objects, report.bytes), (1, 10));
+
+        // Seuls les fichiers présents sont décrits
+        let index_path = temp_dir.path().join("index.db");
+        let report = reset_local(&index_path);
+        assert_eq!(report.paths, vec![index_path.to_string_lossy().into_owned()]);
+        assert_eq!(report.bytes, std::fs::metadata(&index_path).unwrap().len());
+    }
+}
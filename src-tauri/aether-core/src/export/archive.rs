@@ -0,0 +1,591 @@
+//! Archive chiffrée portable du coffre, pour l'archivage à froid (disque USB…).
+//!
+//! Contrairement à l'export en clair, rien n'est déchiffré sur le disque de destination :
+//! l'archive contient les blobs Aether tels qu'ils sont stockés, et le chiffrement de bout
+//! en bout est conservé. Elle se lit sans l'application, avec le binaire `aether-archive`
+//! et le mot de passe du coffre.
+//!
+//! Disposition (entiers en little-endian) :
+//!
+//! ```text
+//! ARCHIVE_MAGIC | longueur u32 | en-tête JSON (clair)
+//! blob Aether | blob Aether | …
+//! manifeste scellé (nonce 24 octets + ciphertext)
+//! position u64 du manifeste | longueur u64 | FOOTER_MAGIC
+//! ```
+//!
+//! L'en-tête en clair porte l'enveloppe de la MasterKey (sel du mot de passe et MKEK) :
+//! l'archive se suffit à elle-même. Le manifeste (chemins, blobs, chemins liés à l'AAD) est
+//! chiffré sous une clé dérivée de la MasterKey et lié à l'en-tête par son AAD. Un contenu
+//! dédupliqué n'est écrit qu'une fois.
+//!
+//! Le même format sert aux lots de fichiers choisis ([`write_selection`]) : leurs contenus
+//! sont alors rechiffrés sous une clé propre au lot, que scelle un mot de passe choisi.
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+use crate::clock::unix_now;
+use crate::crypto::{sealed, CryptoCore, CryptoError, KeyHierarchy, MasterKey, MkekCiphertext, PasswordSecret};
+use crate::index::{FileId, FileMetadata};
+use crate::storage::{self, AetherFile};
+use crate::vault::{Vault, VaultError};
+
+use super::ExportError;
+
+/// Extension conseillée pour les archives.
+pub const ARCHIVE_EXTENSION: &str = "aether-archive";
+pub const ARCHIVE_FORMAT: u32 = 1;
+const ARCHIVE_MAGIC: &[u8; 8] = b"AETHARC1";
+const FOOTER_MAGIC: &[u8; 8] = b"AETHEND1";
+const FOOTER_LEN: u64 = 8 + 8 + 8;
+/// Garde-fou contre un en-tête corrompu annonçant une taille démesurée.
+const MAX_HEADER_LEN: u32 = 64 * 1024;
+const MANIFEST_KEY_INFO: &[u8] = b"aether-drive:archive-manifest-key:v1";
+const MANIFEST_AAD: &[u8] = b"aether-drive:archive-manifest:v1:";
+const NONCE_LEN: usize = 24;
+const PARTIAL_SUFFIX: &str = ".aether-part";
+/// Longueur minimale du mot de passe d'un lot remis à un tiers.
+const MIN_SELECTION_PASSWORD_CHARS: usize = 8;
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    /// Archive ou demande refusée (`reason` : code stable).
+    Invalid(&'static str),
+    Io(io::Error),
+    Crypto(CryptoError),
+    Export(ExportError),
+    Vault(VaultError),
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::Invalid(reason) => write!(f, "Invalid archive: {}", reason),
+            ArchiveError::Io(e) => write!(f, "IO error: {}", e),
+            ArchiveError::Crypto(e) => write!(f, "{}", e),
+            ArchiveError::Export(e) => write!(f, "{}", e),
+            ArchiveError::Vault(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<io::Error> for ArchiveError {
+    fn from(e: io::Error) -> Self {
+        ArchiveError::Io(e)
+    }
+}
+
+impl From<CryptoError> for ArchiveError {
+    fn from(e: CryptoError) -> Self {
+        ArchiveError::Crypto(e)
+    }
+}
+
+impl From<ExportError> for ArchiveError {
+    fn from(e: ExportError) -> Self {
+        ArchiveError::Export(e)
+    }
+}
+
+impl From<VaultError> for ArchiveError {
+    fn from(e: VaultError) -> Self {
+        ArchiveError::Vault(e)
+    }
+}
+
+impl From<rusqlite::Error> for ArchiveError {
+    fn from(e: rusqlite::Error) -> Self {
+        ArchiveError::Vault(e.into())
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+/// Enveloppe de la MasterKey, telle que conservée par le service de clés.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveKey {
+    pub password_salt: [u8; 16],
+    pub mkek: MkekCiphertext,
+}
+
+/// En-tête en clair de l'archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveHeader {
+    pub format: u32,
+    /// Empreinte publique du coffre (voir [`MasterKey::fingerprint`]).
+    pub vault_fingerprint: String,
+    pub created_at: i64,
+    pub key: ArchiveKey,
+}
+
+/// Blob Aether rangé dans l'archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveObject {
+    pub offset: u64,
+    pub len: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub logical_path: String,
+    /// Position du blob dans [`ArchiveManifest::objects`].
+    pub object: usize,
+    /// Chemin lié à l'AAD du blob (chemin d'origine d'un contenu dédupliqué ou renommé).
+    pub aad_path: String,
+}
+
+/// Contenu de l'archive, chiffré dans celle-ci.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub objects: Vec<ArchiveObject>,
+    pub entries: Vec<ArchiveEntry>,
+    pub folders: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ArchiveSummary {
+    pub path: PathBuf,
+    pub files: usize,
+    pub folders: usize,
+    /// Blobs écrits (un contenu dédupliqué ne compte qu'une fois).
+    pub objects: usize,
+    pub bytes: u64,
+    /// Fichiers absents de l'archive : blob illisible ou qui ne se déchiffre pas.
+    pub failed: Vec<String>,
+}
+
+fn cipher(master_key: &MasterKey) -> Result<XChaCha20Poly1305, CryptoError> {
+    let key = sealed::subkey(master_key, MANIFEST_KEY_INFO)?;
+    Ok(XChaCha20Poly1305::new(Key::from_slice(key.as_ref())))
+}
+
+/// Le manifeste est lié à l'en-tête : il ne peut pas être présenté avec une autre enveloppe.
+fn aad(header: &[u8]) -> Vec<u8> {
+    [MANIFEST_AAD, header].concat()
+}
+
+/// Écrit l'archive du coffre dans `dest`, qui ne doit pas exister.
+///
+/// `key` doit être l'enveloppe de la MasterKey de ce coffre : l'archive ne s'ouvrira
+/// qu'avec le mot de passe correspondant (l'appelant le vérifie, voir [`open_key`]).
+/// Chaque blob est déchiffré en mémoire avant d'être archivé : un blob corrompu est écarté
+/// et listé dans [`ArchiveSummary::failed`], il ne finit pas dans une archive à froid.
+/// `progress` reçoit (fichiers traités, total, chemin logique).
+pub async fn write(
+    vault: &Vault,
+    dest: &Path,
+    key: ArchiveKey,
+    progress: impl FnMut(usize, usize, &str),
+) -> Result<ArchiveSummary, ArchiveError> {
+    let entries = vault.list()?;
+    write_entries(vault, dest, key, entries, Packing::Stored, progress).await
+}
+
+/// Clé d'un lot de fichiers remis à un tiers (voir [`write_selection`]).
+pub struct SelectionKey {
+    envelope: ArchiveKey,
+    master_key: MasterKey,
+}
+
+impl SelectionKey {
+    /// Nouvelle clé, scellée par `password` avec son propre sel Argon2id : rien ne la relie
+    /// au mot de passe ni à la clé du coffre. Coûteux (Argon2id) : hors du runtime async.
+    pub fn new(password: &PasswordSecret) -> Result<Self, ArchiveError> {
+        if password.expose().chars().count() < MIN_SELECTION_PASSWORD_CHARS {
+            return Err(ArchiveError::Invalid("password_too_short"));
+        }
+        let password_salt = CryptoCore::default().random_password_salt();
+        let hierarchy = KeyHierarchy::bootstrap(password, password_salt)?;
+        Ok(Self {
+            envelope: ArchiveKey { password_salt, mkek: hierarchy.seal_master_key()? },
+            master_key: MasterKey::from_vec(hierarchy.master_key().as_bytes().to_vec()),
+        })
+    }
+}
+
+/// Écrit dans `dest` un lot autonome des fichiers `file_ids`, à remettre à un tiers sans
+/// partager le coffre.
+///
+/// Les contenus sont rechiffrés sous la clé du lot : le destinataire l'ouvre avec
+/// `aether-archive` et le mot de passe choisi, sans jamais disposer de la clé du coffre.
+pub async fn write_selection(
+    vault: &Vault,
+    file_ids: &[FileId],
+    dest: &Path,
+    key: SelectionKey,
+    progress: impl FnMut(usize, usize, &str),
+) -> Result<ArchiveSummary, ArchiveError> {
+    if file_ids.is_empty() {
+        return Err(ArchiveError::Invalid("empty_selection"));
+    }
+    let mut listed: HashMap<FileId, FileMetadata> = vault.list()?.into_iter().collect();
+    let mut entries = Vec::with_capacity(file_ids.len());
+    for file_id in file_ids {
+        match listed.remove_entry(file_id) {
+            Some(entry) => entries.push(entry),
+            None => return Err(VaultError::NotFound(file_id.clone()).into()),
+        }
+    }
+    write_entries(vault, dest, key.envelope, entries, Packing::Resealed(key.master_key), progress).await
+}
+
+/// Forme des blobs rangés dans l'archive.
+enum Packing {
+    /// Blobs du coffre tels qu'ils sont stockés, sous sa MasterKey.
+    Stored,
+    /// Contenus rechiffrés sous la clé d'un lot (voir [`write_selection`]).
+    Resealed(MasterKey),
+}
+
+impl Packing {
+    fn master_key<'a>(&'a self, vault: &'a Vault) -> &'a MasterKey {
+        match self {
+            Packing::Stored => vault.master_key(),
+            Packing::Resealed(master_key) => master_key,
+        }
+    }
+}
+
+/// Blob d'une entrée : à écrire, ou déjà écrit pour une entrée dédupliquée.
+enum Blob {
+    New(Vec<u8>),
+    Packed(usize),
+}
+
+async fn write_entries(
+    vault: &Vault,
+    dest: &Path,
+    key: ArchiveKey,
+    entries: Vec<(FileId, FileMetadata)>,
+    packing: Packing,
+    mut progress: impl FnMut(usize, usize, &str),
+) -> Result<ArchiveSummary, ArchiveError> {
+    if dest.exists() {
+        return Err(ArchiveError::Invalid("destination_exists"));
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let header = serde_json::to_vec(&ArchiveHeader {
+        format: ARCHIVE_FORMAT,
+        vault_fingerprint: packing.master_key(vault).fingerprint(),
+        created_at: unix_now(),
+        key,
+    })
+    .map_err(|_| ArchiveError::Invalid("encode"))?;
+
+    let mut partial = dest.as_os_str().to_owned();
+    partial.push(PARTIAL_SUFFIX);
+    let partial = PathBuf::from(partial);
+    let result = write_to(vault, &partial, &header, entries, &packing, &mut progress).await;
+    let mut summary = match result {
+        Ok(summary) => summary,
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+    };
+    fs::rename(&partial, dest)?;
+    summary.path = dest.to_path_buf();
+    log::info!(
+        "Vault archived: files={}, objects={}, bytes={}, failed={}",
+        summary.files,
+        summary.objects,
+        summary.bytes,
+        summary.failed.len()
+    );
+    Ok(summary)
+}
+
+async fn write_to(
+    vault: &Vault,
+    path: &Path,
+    header: &[u8],
+    mut entries: Vec<(FileId, FileMetadata)>,
+    packing: &Packing,
+    progress: &mut impl FnMut(usize, usize, &str),
+) -> Result<ArchiveSummary, ArchiveError> {
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    out.write_all(ARCHIVE_MAGIC)?;
+    out.write_all(&(header.len() as u32).to_le_bytes())?;
+    out.write_all(header)?;
+    let mut offset = (ARCHIVE_MAGIC.len() + 4 + header.len()) as u64;
+
+    entries.sort_by(|a, b| a.1.logical_path.cmp(&b.1.logical_path));
+    let total = entries.len();
+    let mut manifest = ArchiveManifest::default();
+    let mut summary = ArchiveSummary::default();
+    // Clé d'objet -> position dans le manifeste
+    let mut packed: HashMap<FileId, usize> = HashMap::new();
+
+    for (done, (file_id, meta)) in entries.into_iter().enumerate() {
+        // Même règle que l'export en clair : une entrée sans contenu est un dossier
+        if meta.logical_path.ends_with('/') || meta.encrypted_size == 0 {
+            manifest.folders.push(meta.logical_path.clone());
+            summary.folders += 1;
+        } else {
+            let object_key = vault.open_index()?.object_key(&file_id)?;
+            let blob = match (packing, packed.get(&object_key)) {
+                (Packing::Stored, Some(&object)) => {
+                    checked_aad_path(vault, &file_id, &meta.logical_path, None).map(|aad_path| (Blob::Packed(object), aad_path))
+                }
+                (Packing::Stored, None) => match vault.fetch_blob(&file_id).await {
+                    Ok(blob) => checked_aad_path(vault, &file_id, &meta.logical_path, Some(&blob))
+                        .map(|aad_path| (Blob::New(blob), aad_path)),
+                    Err(e) => Err(e.into()),
+                },
+                // Rechiffré sous son chemin actuel : le lot ne garde rien des chemins liés du coffre
+                (Packing::Resealed(master_key), _) => match vault.get(&file_id).await {
+                    Ok(plaintext) => storage::encrypt_file(master_key, &Zeroizing::new(plaintext), &meta.logical_path)
+                        .map(|aether_file| (Blob::New(aether_file.to_bytes()), meta.logical_path.clone()))
+                        .map_err(|_| ArchiveError::Invalid("blob")),
+                    Err(e) => Err(e.into()),
+                },
+            };
+            let archived = match blob {
+                Ok((Blob::New(blob), aad_path)) => {
+                    out.write_all(&blob)?;
+                    manifest.objects.push(ArchiveObject { offset, len: blob.len() as u64 });
+                    offset += blob.len() as u64;
+                    summary.bytes += blob.len() as u64;
+                    if matches!(packing, Packing::Stored) {
+                        packed.insert(object_key, manifest.objects.len() - 1);
+                    }
+                    Ok((manifest.objects.len() - 1, aad_path))
+                }
+                Ok((Blob::Packed(object), aad_path)) => Ok((object, aad_path)),
+                Err(e) => Err(e),
+            };
+            match archived {
+                Ok((object, aad_path)) => {
+                    manifest.entries.push(ArchiveEntry { logical_path: meta.logical_path.clone(), object, aad_path });
+                    summary.files += 1;
+                }
+                Err(e) => {
+                    log::warn!("Leaving {} ({}) out of the archive: {}", file_id, meta.logical_path, e);
+                    summary.failed.push(meta.logical_path.clone());
+                }
+            }
+        }
+        progress(done + 1, total, &meta.logical_path);
+    }
+    summary.objects = manifest.objects.len();
+
+    let json = Zeroizing::new(serde_json::to_vec(&manifest).map_err(|_| ArchiveError::Invalid("encode"))?);
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let sealed = cipher(packing.master_key(vault))?
+        .encrypt(XNonce::from_slice(&nonce), Payload { msg: &json, aad: &aad(header) })
+        .map_err(CryptoError::from)?;
+    out.write_all(&nonce)?;
+    out.write_all(&sealed)?;
+    out.write_all(&offset.to_le_bytes())?;
+    out.write_all(&((NONCE_LEN + sealed.len()) as u64).to_le_bytes())?;
+    out.write_all(FOOTER_MAGIC)?;
+    let file = out.into_inner().map_err(|e| e.into_error())?;
+    file.sync_all()?;
+    Ok(summary)
+}
+
+/// Chemin sous lequel le blob de `file_id` se déchiffre (lien de déduplication, chemin lié,
+/// puis chemin actuel). `blob` : contenu à vérifier, `None` s'il l'a déjà été.
+fn checked_aad_path(
+    vault: &Vault,
+    file_id: &FileId,
+    logical_path: &str,
+    blob: Option<&[u8]>,
+) -> Result<String, ArchiveError> {
+    let index = vault.open_index()?;
+    let mut candidates = Vec::new();
+    if let Some((_, aad_path)) = index.blob_link(file_id)? {
+        candidates.push(aad_path);
+    }
+    if let Some(bound_path) = index.bound_path(file_id)? {
+        candidates.push(bound_path);
+    }
+    candidates.push(logical_path.to_string());
+    let Some(blob) = blob else {
+        return Ok(candidates.swap_remove(0));
+    };
+    let aether_file = AetherFile::from_bytes(blob).map_err(|_| ArchiveError::Invalid("blob"))?;
+    candidates
+        .into_iter()
+        .find(|path| storage::decrypt_file(vault.master_key(), &aether_file, path).map(Zeroizing::new).is_ok())
+        .ok_or(ArchiveError::Invalid("blob"))
+}
+
+/// Déchiffre l'enveloppe d'une archive (ou d'un coffre) avec le mot de passe et vérifie
+/// qu'elle appartient au coffre `vault_fingerprint`.
+pub fn open_key(key: &ArchiveKey, password: &PasswordSecret, vault_fingerprint: &str) -> Result<MasterKey, ArchiveError> {
+    let hierarchy = KeyHierarchy::restore(password, key.password_salt, &key.mkek)
+        .map_err(|_| ArchiveError::Invalid("wrong_password"))?;
+    let master_key = MasterKey::from_vec(hierarchy.master_key().as_bytes().to_vec());
+    if master_key.fingerprint() != vault_fingerprint {
+        return Err(ArchiveError::Invalid("key_mismatch"));
+    }
+    Ok(master_key)
+}
+
+/// Lecture d'une archive.
+pub struct ArchiveReader {
+    file: fs::File,
+    header: ArchiveHeader,
+    header_raw: Vec<u8>,
+    manifest_offset: u64,
+    manifest_len: u64,
+    /// Fin de la zone des blobs.
+    objects_end: u64,
+}
+
+impl ArchiveReader {
+    /// Ouvre une archive et lit son en-tête (aucun secret nécessaire).
+    pub fn open(path: &Path) -> Result<Self, ArchiveError> {
+        let mut file = fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        let mut magic = [0u8; 8];
+        file.read_exact(&mut magic).map_err(|_| ArchiveError::Invalid("not_an_archive"))?;
+        if &magic != ARCHIVE_MAGIC {
+            return Err(ArchiveError::Invalid("not_an_archive"));
+        }
+        let mut raw_len = [0u8; 4];
+        file.read_exact(&mut raw_len).map_err(|_| ArchiveError::Invalid("truncated"))?;
+        let header_len = u32::from_le_bytes(raw_len);
+        if header_len > MAX_HEADER_LEN {
+            return Err(ArchiveError::Invalid("header"));
+        }
+        let mut header_raw = vec![0u8; header_len as usize];
+        file.read_exact(&mut header_raw).map_err(|_| ArchiveError::Invalid("truncated"))?;
+        let header: ArchiveHeader = serde_json::from_slice(&header_raw).map_err(|_| ArchiveError::Invalid("header"))?;
+        if header.format != ARCHIVE_FORMAT {
+            return Err(ArchiveError::Invalid("unsupported_format"));
+        }
+
+        let objects_start = 8 + 4 + header_len as u64;
+        if len < objects_start + FOOTER_LEN {
+            return Err(ArchiveError::Invalid("truncated"));
+        }
+        let mut footer = [0u8; FOOTER_LEN as usize];
+        file.seek(SeekFrom::Start(len - FOOTER_LEN))?;
+        file.read_exact(&mut footer)?;
+        if &footer[16..] != FOOTER_MAGIC {
+            return Err(ArchiveError::Invalid("truncated"));
+        }
+        let manifest_offset = u64::from_le_bytes(footer[..8].try_into().unwrap_or_default());
+        let manifest_len = u64::from_le_bytes(footer[8..16].try_into().unwrap_or_default());
+        if manifest_offset < objects_start || manifest_offset.checked_add(manifest_len) != Some(len - FOOTER_LEN) {
+            return Err(ArchiveError::Invalid("truncated"));
+        }
+        Ok(Self { file, header, header_raw, manifest_offset, manifest_len, objects_end: manifest_offset })
+    }
+
+    pub fn header(&self) -> &ArchiveHeader {
+        &self.header
+    }
+
+    /// MasterKey de l'archive à partir du mot de passe du coffre.
+    pub fn unlock(&self, password: &PasswordSecret) -> Result<MasterKey, ArchiveError> {
+        open_key(&self.header.key, password, &self.header.vault_fingerprint)
+    }
+
+    /// Déchiffre le manifeste.
+    pub fn manifest(&mut self, master_key: &MasterKey) -> Result<ArchiveManifest, ArchiveError> {
+        let sealed = self.read_at(self.manifest_offset, self.manifest_len)?;
+        if sealed.len() <= NONCE_LEN {
+            return Err(ArchiveError::Invalid("manifest"));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let json = Zeroizing::new(
+            cipher(master_key)?
+                .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: &aad(&self.header_raw) })
+                .map_err(|_| ArchiveError::Invalid("manifest"))?,
+        );
+        serde_json::from_slice(&json).map_err(|_| ArchiveError::Invalid("manifest"))
+    }
+
+    /// Déchiffre le contenu d'une entrée du manifeste.
+    pub fn open_entry(
+        &mut self,
+        master_key: &MasterKey,
+        manifest: &ArchiveManifest,
+        entry: &ArchiveEntry,
+    ) -> Result<Vec<u8>, ArchiveError> {
+        let object = manifest.objects.get(entry.object).ok_or(ArchiveError::Invalid("manifest"))?;
+        if object.offset.checked_add(object.len).map_or(true, |end| end > self.objects_end) {
+            return Err(ArchiveError::Invalid("manifest"));
+        }
+        let blob = self.read_at(object.offset, object.len)?;
+        let aether_file = AetherFile::from_bytes(&blob).map_err(|_| ArchiveError::Invalid("blob"))?;
+        storage::decrypt_file(master_key, &aether_file, &entry.aad_path).map_err(|_| ArchiveError::Invalid("blob"))
+    }
+
+    /// Comme [`Self::open_entry`], en déchiffrant le contenu directement dans `dest` (voir
+    /// [`storage::decrypt_file_to_path`]) ; retourne sa taille.
+    pub fn extract_entry_to(
+        &mut self,
+        master_key: &MasterKey,
+        manifest: &ArchiveManifest,
+        entry: &ArchiveEntry,
+        dest: &Path,
+    ) -> Result<u64, ArchiveError> {
+        let object = manifest.objects.get(entry.object).ok_or(ArchiveError::Invalid("manifest"))?;
+        if object.offset.checked_add(object.len).map_or(true, |end| end > self.objects_end) {
+            return Err(ArchiveError::Invalid("manifest"));
+        }
+        self.file.seek(SeekFrom::Start(object.offset))?;
+        storage::decrypt_file_to_path(master_key, (&mut self.file).take(object.len), &entry.aad_path, dest).map_err(|e| match e {
+            storage::StorageError::Io(e) => ArchiveError::Io(io::Error::other(e)),
+            _ => ArchiveError::Invalid("blob"),
+        })
+    }
+
+    fn read_at(&mut self, offset: u64, len: u64) -> Result<Vec<u8>, ArchiveError> {
+        self.file.seek(SeekFrom::Start(offset))?;
+        let mut buffer = vec![0u8; len as usize];
+        self.file.read_exact(&mut buffer).map_err(|_| ArchiveError::Invalid("truncated"))?;
+        Ok(buffer)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ExtractReport {
+    pub files: usize,
+    pub folders: usize,
+    pub failed: Vec<String>,
+}
+
+/// Déchiffre toute l'archive sous `out_dir` (arborescence des chemins logiques). Une entrée
+/// qui ne se déchiffre pas est listée dans `failed` sans interrompre l'extraction.
+pub fn extract(reader: &mut ArchiveReader, master_key: &MasterKey, out_dir: &Path) -> Result<ExtractReport, ArchiveError> {
+    let manifest = reader.manifest(master_key)?;
+    let mut report = ExtractReport::default();
+    for folder in &manifest.folders {
+        fs::create_dir_all(super::destination_for(out_dir, folder)?)?;
+        report.folders += 1;
+    }
+    for entry in &manifest.entries {
+        let dest = super::destination_for(out_dir, &entry.logical_path)?;
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match reader.extract_entry_to(master_key, &manifest, entry, &dest) {
+            Ok(_) => report.files += 1,
+            Err(ArchiveError::Io(e)) => return Err(ArchiveError::Io(e)),
+            Err(e) => {
+                log::warn!("Failed to extract {}: {}", entry.logical_path, e);
+                report.failed.push(entry.logical_path.clone());
+            }
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+pub mod archive;
+pub mod report;
+
+/// Fichier de reprise écrit à la racine du dossier d'export.
+pub const EXPORT_MANIFEST_NAME: &str = ".aether-export.json";
+const PARTIAL_SUFFIX: &str = ".aether-part";
+
+/// Erreurs du module Export.
+#[derive(Debug)]
+pub enum ExportError {
+    Io(std::io::Error),
+    Manifest(String),
+    /// Le chemin logique sortirait du dossier de destination (`..`, chemin absolu Windows...).
+    UnsafePath(String),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Io(e) => write!(f, "IO error: {}", e),
+            ExportError::Manifest(msg) => write!(f, "Export manifest error: {}", msg),
+            ExportError::UnsafePath(path) => write!(f, "Unsafe logical path: {}", path),
+        }
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(e: std::io::Error) -> Self {
+        ExportError::Io(e)
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// Journal des fichiers déjà exportés, pour reprendre un export interrompu.
+///
+/// Réécrit après chaque fichier : un crash ne fait perdre que le fichier en cours.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ExportManifest {
+    completed: BTreeSet<String>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl ExportManifest {
+    /// Charge le journal existant de `root`, ou en démarre un vide.
+    pub fn load(root: &Path) -> Result<Self, ExportError> {
+        let path = root.join(EXPORT_MANIFEST_NAME);
+        let mut manifest = if path.exists() {
+            let raw = fs::read(&path)?;
+            serde_json::from_slice::<ExportManifest>(&raw)
+                .map_err(|e| ExportError::Manifest(e.to_string()))?
+        } else {
+            ExportManifest::default()
+        };
+        manifest.path = path;
+        Ok(manifest)
+    }
+
+    pub fn is_done(&self, file_id: &str) -> bool {
+        self.completed.contains(file_id)
+    }
+
+    pub fn mark_done(&mut self, file_id: &str) -> Result<(), ExportError> {
+        self.completed.insert(file_id.to_string());
+        let raw =
+            serde_json::to_vec(self).map_err(|e| ExportError::Manifest(e.to_string()))?;
+        write_atomic(&self.path, &raw)
+    }
+}
+
+/// Résout le chemin de destination d'un chemin logique sous `root`.
+///
+/// Les chemins logiques viennent de l'index (donc du coffre) : on refuse tout
+/// composant qui permettrait d'écrire en dehors du dossier choisi.
+pub fn destination_for(root: &Path, logical_path: &str) -> Result<PathBuf, ExportError> {
+    let relative = Path::new(logical_path.trim_start_matches('/'));
+    let mut dest = root.to_path_buf();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => dest.push(part),
+            Component::CurDir => {}
+            _ => return Err(ExportError::UnsafePath(logical_path.to_string())),
+        }
+    }
+    if dest == root {
+        return Err(ExportError::UnsafePath(logical_path.to_string()));
+    }
+    Ok(dest)
+}
+
+/// Écrit `data` dans un fichier temporaire voisin puis le renomme sur `dest`.
+pub fn write_atomic(dest: &Path, data: &[u8]) -> Result<(), ExportError> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut partial = dest.as_os_str().to_owned();
+    partial.push(PARTIAL_SUFFIX);
+    let partial = PathBuf::from(partial);
+
+    let mut file = fs::File::create(&partial)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&partial, dest)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn destination_rejects_traversal() {
+        let root = Path::new("/export");
+        assert_eq!(
+            destination_for(root, "/docs/a.txt").unwrap(),
+            PathBuf::from("/export/docs/a.txt")
+        );
+        assert!(destination_for(root, "/../etc/passwd").is_err());
+        assert!(destination_for(root, "/").is_err());
+    }
+
+    #[test]
+    fn manifest_survives_reload() {
+        let dir = tempdir().unwrap();
+        let mut manifest = ExportManifest::load(dir.path()).unwrap();
+        manifest.mark_done("abc").unwrap();
+
+        let reloaded = ExportManifest::load(dir.path()).unwrap();
+        assert!(reloaded.is_done("abc"));
+        assert!(!reloaded.is_done("def"));
+    }
+}
@@ -0,0 +1,239 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::index::FileId;
+use crate::remote_keys;
+use crate::storage::AetherFile;
+use crate::storj::StorjError;
+use crate::vault::{Vault, VaultError};
+
+use super::ExportError;
+
+/// Format du rapport d'inventaire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+impl ReportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "csv" => Some(ReportFormat::Csv),
+            "json" => Some(ReportFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// État d'une entrée vis-à-vis du bucket au moment du rapport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    Synced,
+    /// Envoi créé hors ligne, pas encore rejoué.
+    PendingUpload,
+    MissingRemote,
+    /// L'objet distant n'a pas la taille indexée (troncature, écrasement).
+    SizeMismatch,
+    /// Stockage injoignable pendant le rapport.
+    Unknown,
+    /// Dossier : aucun objet distant.
+    Folder,
+}
+
+impl SyncState {
+    fn as_str(self) -> &'static str {
+        match self {
+            SyncState::Synced => "synced",
+            SyncState::PendingUpload => "pending_upload",
+            SyncState::MissingRemote => "missing_remote",
+            SyncState::SizeMismatch => "size_mismatch",
+            SyncState::Unknown => "unknown",
+            SyncState::Folder => "folder",
+        }
+    }
+}
+
+/// Une ligne de l'inventaire : fichier, dossier ou élément de la corbeille.
+///
+/// Les dates sont des timestamps Unix (secondes).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InventoryRow {
+    pub file_id: FileId,
+    pub logical_path: String,
+    pub is_folder: bool,
+    pub encrypted_size: u64,
+    pub plaintext_size: u64,
+    /// Date de dernière écriture de l'objet distant, si connue.
+    pub uploaded_at: Option<i64>,
+    /// Renseigné pour les éléments de la corbeille.
+    pub deleted_at: Option<i64>,
+    /// Nombre d'entrées (index, corbeille et anciennes versions) partageant ce chemin logique.
+    pub versions: usize,
+    pub sync_state: SyncState,
+}
+
+const CSV_HEADER: [&str; 9] = [
+    "file_id",
+    "logical_path",
+    "is_folder",
+    "encrypted_size",
+    "plaintext_size",
+    "uploaded_at",
+    "deleted_at",
+    "versions",
+    "sync_state",
+];
+
+/// Construit l'inventaire du coffre, corbeille comprise, trié par chemin logique.
+///
+/// Un stockage injoignable ne fait pas échouer le rapport : l'état des fichiers est `unknown`.
+pub async fn build(vault: &Vault) -> Result<Vec<InventoryRow>, VaultError> {
+    let remote: Option<HashMap<String, (u64, Option<i64>)>> = match vault.remote().list_objects().await {
+        Ok(objects) => Some(
+            objects
+                .into_iter()
+                .map(|obj| (remote_keys::normalize(&obj.key), (obj.size, obj.last_modified)))
+                .collect(),
+        ),
+        Err(StorjError::Unreachable(e)) => {
+            log::warn!("Remote storage unreachable, inventory sync state unknown: {}", e);
+            None
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let index = vault.open_index()?;
+    let pending = index.pending_upload_ids()?;
+    let links = index.blob_links()?;
+    let sizes = index.plaintext_sizes()?;
+    let entries: Vec<_> = index
+        .list_all()?
+        .into_iter()
+        .map(|(file_id, meta)| (file_id, meta, None))
+        .chain(
+            index
+                .list_trash()?
+                .into_iter()
+                .map(|(file_id, meta, deleted_at)| (file_id, meta, Some(deleted_at))),
+        )
+        .collect();
+
+    let retired = index.list_all_versions()?;
+    let mut versions: HashMap<&str, usize> = HashMap::new();
+    for (_, meta, _) in &entries {
+        *versions.entry(meta.logical_path.as_str()).or_default() += 1;
+    }
+    for version in &retired {
+        *versions.entry(version.logical_path.as_str()).or_default() += 1;
+    }
+
+    let mut rows: Vec<InventoryRow> = entries
+        .iter()
+        .map(|(file_id, meta, deleted_at)| {
+            let is_folder = meta.logical_path.ends_with('/') || meta.encrypted_size == 0;
+            let object_key = links.get(file_id).unwrap_or(file_id);
+            let object = remote.as_ref().and_then(|objects| objects.get(object_key.as_str()));
+            let sync_state = if is_folder {
+                SyncState::Folder
+            } else if pending.contains(file_id) {
+                SyncState::PendingUpload
+            } else {
+                match (&remote, object) {
+                    (None, _) => SyncState::Unknown,
+                    (Some(_), None) => SyncState::MissingRemote,
+                    (Some(_), Some((size, _))) if *size != meta.encrypted_size => SyncState::SizeMismatch,
+                    (Some(_), Some(_)) => SyncState::Synced,
+                }
+            };
+            InventoryRow {
+                file_id: file_id.clone(),
+                logical_path: meta.logical_path.clone(),
+                is_folder,
+                encrypted_size: meta.encrypted_size,
+                plaintext_size: match sizes.get(file_id) {
+                    _ if is_folder => 0,
+                    Some(size) => *size,
+                    None => AetherFile::v1_plaintext_len(meta.encrypted_size),
+                },
+                uploaded_at: object.and_then(|(_, modified)| *modified),
+                deleted_at: *deleted_at,
+                versions: versions[meta.logical_path.as_str()],
+                sync_state,
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| a.logical_path.cmp(&b.logical_path).then(a.deleted_at.cmp(&b.deleted_at)));
+    Ok(rows)
+}
+
+/// Sérialise l'inventaire dans le format demandé.
+pub fn render(rows: &[InventoryRow], format: ReportFormat) -> Result<Vec<u8>, ExportError> {
+    match format {
+        ReportFormat::Csv => Ok(to_csv(rows).into_bytes()),
+        ReportFormat::Json => Ok(serde_json::to_vec_pretty(rows).map_err(std::io::Error::from)?),
+    }
+}
+
+/// CSV (RFC 4180) : en-tête puis une ligne par entrée, séparateur `,`, fins de ligne CRLF.
+pub fn to_csv(rows: &[InventoryRow]) -> String {
+    let mut out = CSV_HEADER.join(",");
+    out.push_str("\r\n");
+    for row in rows {
+        let optional = |value: Option<i64>| value.map(|v| v.to_string()).unwrap_or_default();
+        let fields = [
+            row.file_id.clone(),
+            csv_field(&row.logical_path),
+            row.is_folder.to_string(),
+            row.encrypted_size.to_string(),
+            row.plaintext_size.to_string(),
+            optional(row.uploaded_at),
+            optional(row.deleted_at),
+            row.versions.to_string(),
+            row.sync_state.as_str().to_string(),
+        ];
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_quotes_paths_and_leaves_missing_dates_empty() {
+        let row = InventoryRow {
+            file_id: "ab12".to_string(),
+            logical_path: "/notes, \"brouillon\".txt".to_string(),
+            is_folder: false,
+            encrypted_size: 150,
+            plaintext_size: 16,
+            uploaded_at: Some(1_700_000_000),
+            deleted_at: None,
+            versions: 2,
+            sync_state: SyncState::PendingUpload,
+        };
+        let csv = to_csv(std::slice::from_ref(&row));
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(lines[0], CSV_HEADER.join(","));
+        assert_eq!(lines[1], "ab12,\"/notes, \"\"brouillon\"\".txt\",false,150,16,1700000000,,2,pending_upload");
+        assert_eq!(lines[2], "");
+
+        let json: serde_json::Value = serde_json::from_slice(&render(&[row], ReportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json[0]["sync_state"], "pending_upload");
+        assert_eq!(json[0]["deleted_at"], serde_json::Value::Null);
+        assert_eq!(ReportFormat::parse("CSV"), Some(ReportFormat::Csv));
+        assert_eq!(ReportFormat::parse("xlsx"), None);
+    }
+}
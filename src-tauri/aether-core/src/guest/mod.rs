@@ -0,0 +1,222 @@
+//! Mode invité : consulter les métadonnées de l'index (chemins, tailles) sans la MasterKey,
+//! par exemple sur une machine à laquelle on ne fait pas confiance.
+//!
+//! Les clés de l'index ([`IndexKeys`]) sont scellées sous une phrase d'accès distincte du
+//! mot de passe maître (Argon2id puis XChaCha20-Poly1305) dans un fichier posé à côté de
+//! l'index. Elles ne déchiffrent aucun fichier : le contenu reste verrouillé jusqu'au
+//! déverrouillage complet, et une phrase d'accès interceptée ne livre pas la MasterKey.
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+use crate::crypto::{CryptoCore, CryptoError, MasterKey, PasswordSecret};
+use crate::export::write_atomic;
+use crate::index::sqlcipher::{IndexKeys, SqlCipherIndex};
+
+/// Extension du fichier des clés scellées (`index.guest` à côté de `index.db`).
+const SEALED_KEYS_EXTENSION: &str = "guest";
+const GUEST_KEYS_AAD: &[u8] = b"aether-drive:guest-index-keys:v1";
+const MIN_PASSPHRASE_CHARS: usize = 8;
+/// Résultats renvoyés au plus par une recherche.
+pub const MAX_RESULTS: usize = 200;
+
+#[derive(Debug)]
+pub enum GuestError {
+    /// Opération refusée (`reason` : code stable).
+    Invalid(&'static str),
+    Crypto(CryptoError),
+    Index(rusqlite::Error),
+    Io(String),
+}
+
+impl fmt::Display for GuestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuestError::Invalid(reason) => write!(f, "Invalid guest mode operation: {}", reason),
+            GuestError::Crypto(e) => write!(f, "Crypto error: {}", e),
+            GuestError::Index(e) => write!(f, "Index error: {}", e),
+            GuestError::Io(msg) => write!(f, "IO error: {}", msg),
+        }
+    }
+}
+
+impl From<CryptoError> for GuestError {
+    fn from(e: CryptoError) -> Self {
+        GuestError::Crypto(e)
+    }
+}
+
+impl From<rusqlite::Error> for GuestError {
+    fn from(e: rusqlite::Error) -> Self {
+        GuestError::Index(e)
+    }
+}
+
+impl std::error::Error for GuestError {}
+
+/// Clés de l'index scellées sous la phrase d'accès invité.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SealedIndexKeys {
+    salt: [u8; 16],
+    nonce: [u8; 24],
+    payload: Vec<u8>,
+}
+
+/// Entrée de l'index telle que le mode invité la montre.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GuestEntry {
+    pub logical_path: String,
+    pub encrypted_size: u64,
+    pub is_folder: bool,
+    pub in_trash: bool,
+}
+
+/// Fichier des clés scellées de l'index `index_path`.
+pub fn sealed_keys_path(index_path: &Path) -> PathBuf {
+    index_path.with_extension(SEALED_KEYS_EXTENSION)
+}
+
+pub fn is_enabled(index_path: &Path) -> bool {
+    sealed_keys_path(index_path).exists()
+}
+
+/// Scelle les clés de l'index sous `passphrase` (remplace une phrase précédente).
+pub fn enable(index_path: &Path, master_key: &MasterKey, passphrase: &PasswordSecret) -> Result<(), GuestError> {
+    if passphrase.expose().chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(GuestError::Invalid("passphrase_too_short"));
+    }
+    let keys = IndexKeys::derive(master_key.as_bytes())?;
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; 24];
+    OsRng.fill_bytes(&mut nonce);
+    let payload = build_cipher(passphrase, &salt)?
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: keys.to_bytes().as_slice(),
+                aad: GUEST_KEYS_AAD,
+            },
+        )
+        .map_err(CryptoError::from)?;
+
+    // La sérialisation d'octets ne peut pas échouer
+    let raw = serde_json::to_vec(&SealedIndexKeys { salt, nonce, payload }).unwrap_or_default();
+    write_atomic(&sealed_keys_path(index_path), &raw).map_err(|e| GuestError::Io(e.to_string()))?;
+    log::info!("Guest mode enabled for {}", index_path.display());
+    Ok(())
+}
+
+pub fn disable(index_path: &Path) -> Result<(), GuestError> {
+    match fs::remove_file(sealed_keys_path(index_path)) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(GuestError::Io(e.to_string())),
+    }
+}
+
+/// Descelle les clés de l'index ; `Crypto(Aead)` si la phrase d'accès est fausse.
+pub fn unseal(index_path: &Path, passphrase: &PasswordSecret) -> Result<IndexKeys, GuestError> {
+    let raw = match fs::read(sealed_keys_path(index_path)) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(GuestError::Invalid("not_enabled")),
+        Err(e) => return Err(GuestError::Io(e.to_string())),
+    };
+    let sealed: SealedIndexKeys = serde_json::from_slice(&raw).map_err(|_| GuestError::Invalid("corrupted_keys"))?;
+    let plaintext = Zeroizing::new(
+        build_cipher(passphrase, &sealed.salt)?
+            .decrypt(
+                XNonce::from_slice(&sealed.nonce),
+                Payload {
+                    msg: sealed.payload.as_ref(),
+                    aad: GUEST_KEYS_AAD,
+                },
+            )
+            .map_err(CryptoError::from)?,
+    );
+    IndexKeys::from_bytes(&plaintext).ok_or(GuestError::Invalid("corrupted_keys"))
+}
+
+/// Ouvre l'index en lecture seule avec des clés descellées.
+pub fn open_index(index_path: &Path, keys: &IndexKeys) -> Result<SqlCipherIndex, GuestError> {
+    Ok(SqlCipherIndex::open_read_only(index_path, keys)?)
+}
+
+/// Entrées (corbeille comprise) dont le chemin contient `query`, sans tenir compte de la
+/// casse ; au plus [`MAX_RESULTS`], par chemin.
+pub fn search(index: &SqlCipherIndex, query: &str) -> Result<Vec<GuestEntry>, GuestError> {
+    let needle = query.trim().to_lowercase();
+    let live = index.list_all()?.into_iter().map(|(_, meta)| (meta, false));
+    let trashed = index.list_trash()?.into_iter().map(|(_, meta, _)| (meta, true));
+    let mut entries: Vec<GuestEntry> = live
+        .chain(trashed)
+        .filter(|(meta, _)| meta.logical_path.to_lowercase().contains(&needle))
+        .map(|(meta, in_trash)| GuestEntry {
+            is_folder: meta.logical_path.ends_with('/') || meta.encrypted_size == 0,
+            logical_path: meta.logical_path,
+            encrypted_size: meta.encrypted_size,
+            in_trash,
+        })
+        .collect();
+    entries.sort_by(|a, b| a.logical_path.cmp(&b.logical_path));
+    entries.truncate(MAX_RESULTS);
+    Ok(entries)
+}
+
+fn build_cipher(passphrase: &PasswordSecret, salt: &[u8; 16]) -> Result<XChaCha20Poly1305, GuestError> {
+    let kek = CryptoCore::new().derive_kek(passphrase, salt)?;
+    Ok(XChaCha20Poly1305::new(Key::from_slice(kek.as_bytes())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::FileMetadata;
+    use tempfile::TempDir;
+
+    #[test]
+    fn guest_passphrase_reveals_paths_but_not_the_master_key() {
+        let dir = TempDir::new().unwrap();
+        let index_path = dir.path().join("index.db");
+        let master_key = MasterKey::from_vec(vec![7u8; 32]);
+        let mut index = SqlCipherIndex::open(&index_path, master_key.as_bytes()).unwrap();
+        let meta = FileMetadata {
+            logical_path: "/Impôts/2025.pdf".to_string(),
+            encrypted_size: 120,
+        };
+        index.upsert("file-1".to_string(), meta).unwrap();
+        drop(index);
+
+        assert!(matches!(
+            enable(&index_path, &master_key, &PasswordSecret::new("court")),
+            Err(GuestError::Invalid("passphrase_too_short"))
+        ));
+        assert!(matches!(
+            unseal(&index_path, &PasswordSecret::new("phrase d'accès")),
+            Err(GuestError::Invalid("not_enabled"))
+        ));
+        enable(&index_path, &master_key, &PasswordSecret::new("phrase d'accès")).unwrap();
+        assert!(matches!(
+            unseal(&index_path, &PasswordSecret::new("mauvaise phrase")),
+            Err(GuestError::Crypto(CryptoError::Aead))
+        ));
+
+        let keys = unseal(&index_path, &PasswordSecret::new("phrase d'accès")).unwrap();
+        let guest = open_index(&index_path, &keys).unwrap();
+        let found = search(&guest, "impôts").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].encrypted_size, 120);
+        assert!(search(&guest, "photos").unwrap().is_empty());
+
+        disable(&index_path).unwrap();
+        assert!(!is_enabled(&index_path));
+    }
+}
@@ -0,0 +1,328 @@
+//! Score de santé du coffre : une note sur 100 qui résume si les données chiffrées sont
+//! réellement récupérables (vérification d'intégrité, lignes altérées, entrées orphelines,
+//! fraîcheur des sauvegardes de l'index, taille de la corbeille).
+//!
+//! Le calcul est purement local : il relit l'index et le dernier rapport d'intégrité, sans
+//! interroger le stockage distant.
+
+use serde::Serialize;
+
+use crate::backup::{self, BackupPolicy, BackupState};
+use crate::clock::unix_now;
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::index::PendingKind;
+use crate::integrity::{self, IntegrityReport, MISSING_REMOTE_REASON};
+use crate::vault::VaultError;
+
+/// Ancienneté au-delà de laquelle la dernière sauvegarde de l'index est jugée périmée.
+pub const BACKUP_STALE_SECS: i64 = 7 * 24 * 60 * 60;
+/// Délai après lequel des modifications non sauvegardées deviennent préoccupantes.
+pub const BACKUP_LAG_SECS: i64 = 24 * 60 * 60;
+
+/// Poids de chaque catégorie dans la note globale (total : 100).
+const WEIGHTS: [(&str, u32); 5] = [
+    ("integrity", 30),
+    ("tampered_rows", 25),
+    ("orphans", 20),
+    ("backup", 20),
+    ("trash", 5),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Good,
+    Warning,
+    Critical,
+}
+
+/// Note d'une catégorie ; `name` est un code stable traduit par l'interface.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthCategory {
+    pub name: &'static str,
+    pub status: HealthStatus,
+    /// Note sur 100.
+    pub score: u8,
+    /// Détail technique (anglais, comme les journaux).
+    pub detail: String,
+}
+
+impl HealthCategory {
+    fn new(name: &'static str, status: HealthStatus, score: u8, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            score,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    /// Timestamp Unix (secondes) du calcul.
+    pub checked_at: i64,
+    /// Note globale sur 100, moyenne pondérée des catégories.
+    pub score: u8,
+    /// Pire état parmi les catégories.
+    pub status: HealthStatus,
+    pub categories: Vec<HealthCategory>,
+}
+
+/// Éléments relus dans l'index, séparés du calcul pour pouvoir le tester isolément.
+#[derive(Debug, Clone, Default)]
+struct HealthInputs {
+    integrity: Option<IntegrityReport>,
+    /// La chronologie Merkle a été réécrite ou l'index modifié hors de l'application.
+    timeline_anomaly: bool,
+    tampered_rows: usize,
+    live_files: usize,
+    live_bytes: u64,
+    pending_uploads: usize,
+    trash_files: usize,
+    trash_bytes: u64,
+    backup_policy: BackupPolicy,
+    backup_state: BackupState,
+    unsaved_changes: i64,
+}
+
+/// Calcule le score de santé depuis l'index (coffre déverrouillé).
+pub fn evaluate(index: &SqlCipherIndex) -> Result<HealthReport, VaultError> {
+    let tampered_rows = index.tampered_rows()?.len();
+    let timeline = integrity::timeline(index, 1)?;
+    // `list_all` échoue sur une ligne altérée : les volumes sont alors inconnus
+    let live = if tampered_rows == 0 { index.list_all()? } else { Vec::new() };
+    let trash = if tampered_rows == 0 { index.list_trash()? } else { Vec::new() };
+    let backup_state = backup::load_state(index)?;
+    let inputs = HealthInputs {
+        integrity: integrity::last_report(index)?,
+        timeline_anomaly: timeline.unexpected_change || timeline.broken_chain,
+        tampered_rows,
+        live_files: live.iter().filter(|(_, meta)| !meta.logical_path.ends_with('/')).count(),
+        live_bytes: live.iter().map(|(_, meta)| meta.encrypted_size).sum(),
+        pending_uploads: index.list_pending()?.iter().filter(|op| op.kind == PendingKind::Upload).count(),
+        trash_files: trash.len(),
+        trash_bytes: trash.iter().map(|(_, meta, _)| meta.encrypted_size).sum(),
+        backup_policy: backup::load_policy(index)?,
+        unsaved_changes: index.change_seq()? - backup_state.backed_up_seq,
+        backup_state,
+    };
+    let report = score(&inputs, unix_now());
+    if report.status != HealthStatus::Good {
+        log::info!("Vault health {}/100 ({:?})", report.score, report.status);
+    }
+    Ok(report)
+}
+
+fn score(inputs: &HealthInputs, now: i64) -> HealthReport {
+    let categories = vec![
+        integrity_category(inputs),
+        tampered_category(inputs),
+        orphans_category(inputs),
+        backup_category(inputs, now),
+        trash_category(inputs),
+    ];
+    let weighted: u32 = categories
+        .iter()
+        .map(|category| {
+            let weight = WEIGHTS.iter().find(|(name, _)| *name == category.name).map_or(0, |(_, w)| *w);
+            u32::from(category.score) * weight
+        })
+        .sum();
+    let total_weight: u32 = WEIGHTS.iter().map(|(_, weight)| weight).sum();
+    let status = categories
+        .iter()
+        .map(|category| category.status)
+        .max()
+        .unwrap_or(HealthStatus::Good);
+    HealthReport {
+        checked_at: now,
+        score: (weighted / total_weight) as u8,
+        status,
+        categories,
+    }
+}
+
+fn integrity_category(inputs: &HealthInputs) -> HealthCategory {
+    let Some(report) = &inputs.integrity else {
+        return HealthCategory::new("integrity", HealthStatus::Warning, 50, "no integrity check yet");
+    };
+    let drift = report
+        .remote_drift
+        .iter()
+        .filter(|drift| drift.reason != MISSING_REMOTE_REASON)
+        .count();
+    if !report.merkle_ok || drift > 0 {
+        return HealthCategory::new(
+            "integrity",
+            HealthStatus::Critical,
+            0,
+            format!("merkle_ok={}, inconsistent remote objects={}", report.merkle_ok, drift),
+        );
+    }
+    if inputs.timeline_anomaly {
+        return HealthCategory::new("integrity", HealthStatus::Warning, 40, "Merkle timeline anomaly");
+    }
+    if report.is_stale() {
+        return HealthCategory::new(
+            "integrity",
+            HealthStatus::Warning,
+            70,
+            format!("last check at {} is stale", report.checked_at),
+        );
+    }
+    HealthCategory::new(
+        "integrity",
+        HealthStatus::Good,
+        100,
+        format!("{} remote objects sampled", report.remote_sampled),
+    )
+}
+
+fn tampered_category(inputs: &HealthInputs) -> HealthCategory {
+    if inputs.tampered_rows > 0 {
+        HealthCategory::new(
+            "tampered_rows",
+            HealthStatus::Critical,
+            0,
+            format!("{} rows fail HMAC verification", inputs.tampered_rows),
+        )
+    } else {
+        HealthCategory::new("tampered_rows", HealthStatus::Good, 100, "all rows verified")
+    }
+}
+
+/// Entrées sans objet distant (constatées par la dernière vérification) et fichiers encore
+/// en attente d'envoi : dans les deux cas, seule la copie locale existe.
+fn orphans_category(inputs: &HealthInputs) -> HealthCategory {
+    let missing = inputs.integrity.as_ref().map_or(0, |report| {
+        report
+            .remote_drift
+            .iter()
+            .filter(|drift| drift.reason == MISSING_REMOTE_REASON)
+            .count()
+    });
+    let detail = format!("missing remote objects={}, pending uploads={}", missing, inputs.pending_uploads);
+    if missing > 0 {
+        HealthCategory::new("orphans", HealthStatus::Critical, 0, detail)
+    } else if inputs.pending_uploads > 0 {
+        HealthCategory::new("orphans", HealthStatus::Warning, 70, detail)
+    } else {
+        HealthCategory::new("orphans", HealthStatus::Good, 100, detail)
+    }
+}
+
+fn backup_category(inputs: &HealthInputs, now: i64) -> HealthCategory {
+    let state = &inputs.backup_state;
+    if inputs.live_files == 0 && state.last_backup_at.is_none() {
+        return HealthCategory::new("backup", HealthStatus::Good, 100, "empty vault");
+    }
+    if !inputs.backup_policy.enabled {
+        return HealthCategory::new("backup", HealthStatus::Warning, 30, "automatic backups disabled");
+    }
+    let Some(last_backup_at) = state.last_backup_at else {
+        return HealthCategory::new("backup", HealthStatus::Critical, 0, "index never backed up");
+    };
+    let age = now - last_backup_at;
+    let lag = state.pending_since.map_or(0, |since| now - since);
+    let detail = format!("last backup {}s ago, {} unsaved changes", age, inputs.unsaved_changes.max(0));
+    if inputs.unsaved_changes > 0 && (age >= BACKUP_STALE_SECS || lag >= BACKUP_LAG_SECS) {
+        HealthCategory::new("backup", HealthStatus::Warning, 50, detail)
+    } else {
+        HealthCategory::new("backup", HealthStatus::Good, 100, detail)
+    }
+}
+
+/// La corbeille ne menace pas les données, mais une corbeille plus lourde que le coffre
+/// lui-même coûte du stockage pour rien.
+fn trash_category(inputs: &HealthInputs) -> HealthCategory {
+    let detail = format!("{} files, {} bytes in trash", inputs.trash_files, inputs.trash_bytes);
+    if inputs.trash_bytes > 0 && inputs.trash_bytes > inputs.live_bytes {
+        HealthCategory::new("trash", HealthStatus::Warning, 60, detail)
+    } else {
+        HealthCategory::new("trash", HealthStatus::Good, 100, detail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::FileMetadata;
+    use crate::integrity::RemoteDrift;
+    use tempfile::TempDir;
+
+    const NOW: i64 = 1_800_000_000;
+
+    fn healthy() -> HealthInputs {
+        HealthInputs {
+            integrity: Some(IntegrityReport {
+                checked_at: unix_now(),
+                merkle_ok: true,
+                remote_sampled: 8,
+                ..Default::default()
+            }),
+            live_files: 10,
+            live_bytes: 1000,
+            backup_state: BackupState {
+                backed_up_seq: 5,
+                last_backup_at: Some(NOW - 60),
+                pending_since: None,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn healthy_vault_scores_full_marks() {
+        let report = score(&healthy(), NOW);
+        assert_eq!(report.score, 100);
+        assert_eq!(report.status, HealthStatus::Good);
+        assert_eq!(report.categories.len(), WEIGHTS.len());
+    }
+
+    #[test]
+    fn missing_objects_count_as_orphans_not_drift() {
+        let mut inputs = healthy();
+        if let Some(report) = inputs.integrity.as_mut() {
+            report.remote_drift.push(RemoteDrift {
+                file_id: "abc".to_string(),
+                reason: MISSING_REMOTE_REASON.to_string(),
+            });
+        }
+        let report = score(&inputs, NOW);
+        let status_of = |name| report.categories.iter().find(|c| c.name == name).unwrap().status;
+        assert_eq!(status_of("integrity"), HealthStatus::Good);
+        assert_eq!(status_of("orphans"), HealthStatus::Critical);
+        assert_eq!(report.status, HealthStatus::Critical);
+        assert_eq!(report.score, 80);
+    }
+
+    #[test]
+    fn stale_backup_with_unsaved_changes_is_a_warning() {
+        let mut inputs = healthy();
+        inputs.unsaved_changes = 3;
+        inputs.backup_state.last_backup_at = Some(NOW - BACKUP_STALE_SECS);
+        let report = score(&inputs, NOW);
+        assert_eq!(report.status, HealthStatus::Warning);
+        assert_eq!(report.score, 90);
+
+        inputs.backup_policy.enabled = false;
+        assert_eq!(score(&inputs, NOW).categories[3].score, 30);
+    }
+
+    #[test]
+    fn evaluate_reads_the_index() {
+        let dir = TempDir::new().unwrap();
+        let mut index = SqlCipherIndex::open(dir.path().join("health.db"), &[31u8; 32]).unwrap();
+        // Coffre vide : seule l'absence de vérification d'intégrité est signalée
+        let report = evaluate(&index).unwrap();
+        assert_eq!(report.status, HealthStatus::Warning);
+        assert_eq!(report.categories[3].status, HealthStatus::Good);
+
+        index
+            .upsert("file-1".to_string(), FileMetadata { logical_path: "/a".to_string(), encrypted_size: 10 })
+            .unwrap();
+        let report = evaluate(&index).unwrap();
+        assert_eq!(report.categories[3].status, HealthStatus::Critical);
+    }
+}
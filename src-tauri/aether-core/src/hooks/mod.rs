@@ -0,0 +1,379 @@
+//! Hooks d'événements : un script local ou un webhook HTTPS prévenu quand un envoi se
+//! termine, qu'un conflit apparaît ou que l'intégrité de l'index est compromise.
+//!
+//! Les définitions sont stockées dans l'index (chiffré), comme les règles : les hooks ne se
+//! déclenchent donc que coffre déverrouillé. La charge utile contient des chemins logiques,
+//! c'est-à-dire des métadonnées du coffre : elle ne part que vers les cibles configurées.
+
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fmt;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+use crate::clock::unix_now;
+use crate::index::sqlcipher::SqlCipherIndex;
+
+/// Durée maximale d'une livraison (script ou requête HTTPS).
+pub const DELIVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// En-têtes des webhooks. La signature couvre `"{timestamp}.{corps}"`.
+pub const SIGNATURE_HEADER: &str = "X-Aether-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Aether-Timestamp";
+pub const EVENT_HEADER: &str = "X-Aether-Event";
+
+/// Variable d'environnement portant le nom de l'événement pour un script.
+pub const EVENT_ENV: &str = "AETHER_EVENT";
+
+const MAX_NAME_CHARS: usize = 80;
+const SECRET_LEN: usize = 32;
+
+#[derive(Debug)]
+pub enum HookError {
+    /// Définition refusée (`reason` : code stable).
+    Invalid(&'static str),
+    NotFound(i64),
+    Index(rusqlite::Error),
+}
+
+impl fmt::Display for HookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HookError::Invalid(reason) => write!(f, "Invalid hook: {}", reason),
+            HookError::NotFound(id) => write!(f, "Hook not found: {}", id),
+            HookError::Index(e) => write!(f, "Index error: {}", e),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for HookError {
+    fn from(e: rusqlite::Error) -> Self {
+        HookError::Index(e)
+    }
+}
+
+impl std::error::Error for HookError {}
+
+/// Événement du coffre auquel un hook peut s'abonner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    UploadComplete,
+    DownloadComplete,
+    Conflict,
+    IntegrityFailure,
+    TrashPurged,
+}
+
+impl HookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HookEvent::UploadComplete => "upload_complete",
+            HookEvent::DownloadComplete => "download_complete",
+            HookEvent::Conflict => "conflict",
+            HookEvent::IntegrityFailure => "integrity_failure",
+            HookEvent::TrashPurged => "trash_purged",
+        }
+    }
+}
+
+/// Destination d'un hook.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HookTarget {
+    /// Programme local (chemin absolu, sans arguments) : la charge utile JSON arrive sur
+    /// l'entrée standard et le nom de l'événement dans [`EVENT_ENV`].
+    Script { program: String },
+    /// Requête `POST` JSON signée en HMAC-SHA256 avec `secret` (généré s'il est vide).
+    Webhook { url: String, secret: String },
+}
+
+impl HookTarget {
+    /// Refuse les cibles non absolues ou non HTTPS ; génère le secret manquant.
+    pub fn validate(self) -> Result<Self, &'static str> {
+        match self {
+            HookTarget::Script { program } => {
+                let program = program.trim().to_string();
+                if !Path::new(&program).is_absolute() {
+                    return Err("relative_program");
+                }
+                Ok(HookTarget::Script { program })
+            }
+            HookTarget::Webhook { url, secret } => {
+                let url = url.trim().to_string();
+                let parsed = reqwest::Url::parse(&url).map_err(|_| "invalid_url")?;
+                if parsed.scheme() != "https" || parsed.host_str().is_none() {
+                    return Err("insecure_url");
+                }
+                let secret = if secret.trim().is_empty() {
+                    let mut bytes = [0u8; SECRET_LEN];
+                    OsRng.fill_bytes(&mut bytes);
+                    hex::encode(bytes)
+                } else {
+                    secret.trim().to_string()
+                };
+                Ok(HookTarget::Webhook { url, secret })
+            }
+        }
+    }
+}
+
+/// Définition sérialisée dans la colonne `spec`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct HookSpec {
+    events: Vec<HookEvent>,
+    target: HookTarget,
+}
+
+/// Hook tel que présenté au frontend.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Hook {
+    pub id: i64,
+    pub name: String,
+    pub enabled: bool,
+    pub events: Vec<HookEvent>,
+    pub target: HookTarget,
+    /// Timestamp Unix du dernier déclenchement.
+    pub last_fired: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// Corps envoyé à chaque cible.
+#[derive(Debug, Clone, Serialize)]
+pub struct HookPayload {
+    pub event: HookEvent,
+    pub occurred_at: i64,
+    /// Détail de l'événement (identifiant, chemin logique…).
+    pub data: serde_json::Value,
+}
+
+impl HookPayload {
+    pub fn new(event: HookEvent, data: serde_json::Value) -> Self {
+        Self {
+            event,
+            occurred_at: unix_now(),
+            data,
+        }
+    }
+}
+
+/// Hooks enregistrés ; une définition illisible (version future) est ignorée.
+pub fn list(index: &SqlCipherIndex) -> rusqlite::Result<Vec<Hook>> {
+    Ok(index
+        .list_hooks()?
+        .into_iter()
+        .filter_map(|stored| match serde_json::from_str::<HookSpec>(&stored.spec) {
+            Ok(spec) => Some(Hook {
+                id: stored.id,
+                name: stored.name,
+                enabled: stored.enabled,
+                events: spec.events,
+                target: spec.target,
+                last_fired: stored.last_fired,
+                last_error: stored.last_error,
+            }),
+            Err(e) => {
+                log::warn!("Skipping unreadable hook {}: {}", stored.id, e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Hooks actifs abonnés à `event`.
+pub fn subscribers(index: &SqlCipherIndex, event: HookEvent) -> rusqlite::Result<Vec<Hook>> {
+    Ok(list(index)?
+        .into_iter()
+        .filter(|hook| hook.enabled && hook.events.contains(&event))
+        .collect())
+}
+
+/// Crée (`id` absent) ou met à jour un hook.
+pub fn save(
+    index: &mut SqlCipherIndex,
+    id: Option<i64>,
+    name: &str,
+    enabled: bool,
+    events: Vec<HookEvent>,
+    target: HookTarget,
+) -> Result<Hook, HookError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(HookError::Invalid("empty_name"));
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(HookError::Invalid("name_too_long"));
+    }
+    let mut events = events;
+    events.sort_by_key(|event| event.as_str());
+    events.dedup();
+    if events.is_empty() {
+        return Err(HookError::Invalid("no_events"));
+    }
+    let target = target.validate().map_err(HookError::Invalid)?;
+    // La sérialisation d'énumérations et de chaînes ne peut pas échouer
+    let spec = serde_json::to_string(&HookSpec { events, target }).unwrap_or_default();
+
+    let id = match id {
+        Some(id) => match index.update_hook(id, name, enabled, &spec) {
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Err(HookError::NotFound(id)),
+            other => other.map(|()| id)?,
+        },
+        None => index.insert_hook(name, enabled, &spec)?,
+    };
+    list(index)?
+        .into_iter()
+        .find(|hook| hook.id == id)
+        .ok_or(HookError::NotFound(id))
+}
+
+pub fn delete(index: &mut SqlCipherIndex, id: i64) -> Result<(), HookError> {
+    match index.delete_hook(id) {
+        Err(rusqlite::Error::QueryReturnedNoRows) => Err(HookError::NotFound(id)),
+        other => Ok(other?),
+    }
+}
+
+/// Livre `payload` à chaque hook ; renvoie, dans l'ordre, le résultat de chacun.
+///
+/// Les livraisons sont indépendantes : l'échec de l'une n'empêche pas les suivantes.
+pub async fn deliver_all(hooks: &[Hook], payload: &HookPayload) -> Vec<(i64, Result<(), String>)> {
+    // La sérialisation d'une valeur JSON ne peut pas échouer
+    let body = serde_json::to_vec(payload).unwrap_or_default();
+    let mut results = Vec::with_capacity(hooks.len());
+    for hook in hooks {
+        let result = match tokio::time::timeout(DELIVERY_TIMEOUT, deliver(&hook.target, payload.event, &body)).await {
+            Ok(result) => result,
+            Err(_) => Err("timed out".to_string()),
+        };
+        match &result {
+            Ok(()) => log::info!("Hook {} fired for {}", hook.id, payload.event.as_str()),
+            Err(e) => log::warn!("Hook {} failed for {}: {}", hook.id, payload.event.as_str(), e),
+        }
+        results.push((hook.id, result));
+    }
+    results
+}
+
+/// Consigne le résultat des livraisons sur chaque hook (`last_fired`, `last_error`).
+pub fn record_results(index: &mut SqlCipherIndex, results: &[(i64, Result<(), String>)]) -> rusqlite::Result<()> {
+    let now = unix_now();
+    for (id, result) in results {
+        index.set_hook_result(*id, now, result.as_ref().err().map(String::as_str))?;
+    }
+    Ok(())
+}
+
+/// Signature d'un webhook : HMAC-SHA256 de `"{timestamp}.{corps}"`, en hexadécimal préfixé
+/// par `sha256=`. Le destinataire la recalcule et refuse un horodatage trop ancien.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    // HMAC accepte une clé de toute longueur
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+async fn deliver(target: &HookTarget, event: HookEvent, body: &[u8]) -> Result<(), String> {
+    match target {
+        HookTarget::Script { program } => {
+            let mut child = tokio::process::Command::new(program)
+                .env(EVENT_ENV, event.as_str())
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .kill_on_drop(true)
+                .spawn()
+                .map_err(|e| format!("failed to start {}: {}", program, e))?;
+            if let Some(mut stdin) = child.stdin.take() {
+                // Un script qui ignore son entrée la ferme : ce n'est pas une erreur
+                let _ = stdin.write_all(body).await;
+            }
+            let status = child.wait().await.map_err(|e| e.to_string())?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(format!("exited with {}", status))
+            }
+        }
+        HookTarget::Webhook { url, secret } => {
+            let timestamp = unix_now();
+            let response = reqwest::Client::new()
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(EVENT_HEADER, event.as_str())
+                .header(TIMESTAMP_HEADER, timestamp.to_string())
+                .header(SIGNATURE_HEADER, sign(secret, timestamp, body))
+                .body(body.to_vec())
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(format!("HTTP {}", response.status()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn validation_requires_https_and_generates_a_secret() {
+        let plain = HookTarget::Webhook { url: "http://example.com/hook".to_string(), secret: String::new() };
+        assert_eq!(plain.validate(), Err("insecure_url"));
+        let relative = HookTarget::Script { program: "notify.sh".to_string() };
+        assert_eq!(relative.validate(), Err("relative_program"));
+
+        let webhook = HookTarget::Webhook { url: "https://example.com/hook".to_string(), secret: " ".to_string() };
+        match webhook.validate().unwrap() {
+            HookTarget::Webhook { secret, .. } => assert_eq!(secret.len(), SECRET_LEN * 2),
+            other => panic!("unexpected target {:?}", other),
+        }
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let signature = sign("secret", 1_700_000_000, b"{}");
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature, sign("secret", 1_700_000_000, b"{}"));
+        assert_ne!(signature, sign("secret", 1_700_000_001, b"{}"));
+        assert_ne!(signature, sign("other", 1_700_000_000, b"{}"));
+    }
+
+    #[test]
+    fn hooks_round_trip_through_the_index() {
+        let dir = TempDir::new().unwrap();
+        let mut index = SqlCipherIndex::open(dir.path().join("hooks.db"), &[29u8; 32]).unwrap();
+        let target = HookTarget::Script { program: "/usr/local/bin/notify".to_string() };
+        assert!(matches!(
+            save(&mut index, None, "Notifier", true, Vec::new(), target.clone()),
+            Err(HookError::Invalid("no_events"))
+        ));
+
+        let events = vec![HookEvent::Conflict, HookEvent::UploadComplete, HookEvent::Conflict];
+        let hook = save(&mut index, None, " Notifier ", true, events, target.clone()).unwrap();
+        assert_eq!(hook.name, "Notifier");
+        assert_eq!(hook.events, vec![HookEvent::Conflict, HookEvent::UploadComplete]);
+        assert_eq!(subscribers(&index, HookEvent::Conflict).unwrap(), vec![hook.clone()]);
+        assert!(subscribers(&index, HookEvent::TrashPurged).unwrap().is_empty());
+
+        record_results(&mut index, &[(hook.id, Err("exited with 1".to_string()))]).unwrap();
+        assert_eq!(list(&index).unwrap()[0].last_error.as_deref(), Some("exited with 1"));
+
+        save(&mut index, Some(hook.id), "Notifier", false, vec![HookEvent::Conflict], target).unwrap();
+        assert!(subscribers(&index, HookEvent::Conflict).unwrap().is_empty());
+
+        delete(&mut index, hook.id).unwrap();
+        assert!(matches!(delete(&mut index, hook.id), Err(HookError::NotFound(_))));
+    }
+}
@@ -0,0 +1,197 @@
+//! Cache en mémoire des lectures fréquentes de l'index.
+//!
+//! L'interface relit l'index après chaque opération : entrée par identifiant, contenu d'un
+//! dossier, liste complète. Chaque lecture repassait par SQLCipher et revérifiait le HMAC de
+//! toutes les lignes. Les résultats sont gardés tant que le compteur de modifications de
+//! l'index ([`SqlCipherIndex::change_seq`]) n'a pas bougé : toute écriture d'une entrée, quelle
+//! que soit la connexion qui l'a faite, invalide le cache à la lecture suivante.
+//!
+//! Le compteur d'une base recréée au même chemin repart de zéro : quiconque supprime ou
+//! remplace le fichier d'index doit aussi oublier le cache ([`IndexCache::invalidate`]).
+
+use rusqlite::Result as SqliteResult;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::sqlcipher::SqlCipherIndex;
+use super::{FileId, FileMetadata};
+
+/// Entrées gardées par identifiant ; au-delà, le cache des entrées repart de zéro.
+const MAX_ENTRIES: usize = 10_000;
+
+/// Dossiers gardés ; au-delà, le cache des dossiers repart de zéro.
+const MAX_FOLDERS: usize = 64;
+
+/// Un dossier plus gros n'est pas gardé (il reste lu au fil du curseur).
+const MAX_FOLDER_ENTRIES: usize = 50_000;
+
+type Listing = Arc<Vec<(FileId, FileMetadata)>>;
+
+#[derive(Default)]
+struct Cached {
+    /// Compteur de modifications auquel les résultats ont été lus.
+    seq: Option<i64>,
+    entries: HashMap<FileId, Option<FileMetadata>>,
+    folders: HashMap<String, Listing>,
+    all: Option<Listing>,
+}
+
+impl Cached {
+    /// Oublie tout si l'index a été modifié depuis les lectures gardées.
+    fn sync(&mut self, seq: i64) {
+        if self.seq != Some(seq) {
+            *self = Cached { seq: Some(seq), ..Cached::default() };
+        }
+    }
+}
+
+/// Cache partagé des lectures d'un index ; un cache par fichier d'index.
+#[derive(Default)]
+pub struct IndexCache {
+    cached: Mutex<Cached>,
+}
+
+impl IndexCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Comme [`SqlCipherIndex::get`].
+    pub fn get(&self, index: &SqlCipherIndex, id: &FileId) -> SqliteResult<Option<FileMetadata>> {
+        let seq = index.change_seq()?;
+        if let Some(meta) = self.with(seq, |cached| cached.entries.get(id).cloned()).flatten() {
+            return Ok(meta);
+        }
+        let meta = index.get(id)?;
+        self.with(seq, |cached| {
+            if cached.entries.len() >= MAX_ENTRIES {
+                cached.entries.clear();
+            }
+            cached.entries.insert(id.clone(), meta.clone());
+        });
+        Ok(meta)
+    }
+
+    /// Comme [`SqlCipherIndex::list_all`].
+    pub fn list_all(&self, index: &SqlCipherIndex) -> SqliteResult<Vec<(FileId, FileMetadata)>> {
+        let seq = index.change_seq()?;
+        if let Some(all) = self.with(seq, |cached| cached.all.clone()).flatten() {
+            return Ok(all.as_ref().clone());
+        }
+        let all = index.list_all()?;
+        let listing = Arc::new(all.clone());
+        self.with(seq, |cached| cached.all = Some(listing));
+        Ok(all)
+    }
+
+    /// Comme [`SqlCipherIndex::scan_prefix`] : un dossier déjà lu est parcouru depuis la
+    /// mémoire ; sinon il est lu au fil du curseur et gardé si le parcours va jusqu'au bout.
+    pub fn scan_prefix(
+        &self,
+        index: &SqlCipherIndex,
+        prefix: &str,
+        mut visit: impl FnMut(FileId, FileMetadata) -> bool,
+    ) -> SqliteResult<usize> {
+        // Lu avant le parcours : une écriture pendant celui-ci invalide ce qui sera gardé
+        let seq = index.change_seq()?;
+        if let Some(listing) = self.with(seq, |cached| cached.folders.get(prefix).cloned()).flatten() {
+            let mut visited = 0;
+            for (id, meta) in listing.iter() {
+                visited += 1;
+                if !visit(id.clone(), meta.clone()) {
+                    break;
+                }
+            }
+            return Ok(visited);
+        }
+
+        let mut listing = Vec::new();
+        let (mut kept, mut interrupted) = (true, false);
+        let visited = index.scan_prefix(prefix, |id, meta| {
+            kept &= listing.len() < MAX_FOLDER_ENTRIES;
+            if kept {
+                listing.push((id.clone(), meta.clone()));
+            }
+            interrupted = !visit(id, meta);
+            !interrupted
+        })?;
+        if kept && !interrupted {
+            self.with(seq, |cached| {
+                if cached.folders.len() >= MAX_FOLDERS {
+                    cached.folders.clear();
+                }
+                cached.folders.insert(prefix.to_string(), Arc::new(listing));
+            });
+        }
+        Ok(visited)
+    }
+
+    /// Oublie toutes les lectures gardées (verrouillage du coffre, index remplacé).
+    pub fn invalidate(&self) {
+        if let Ok(mut cached) = self.cached.lock() {
+            *cached = Cached::default();
+        }
+    }
+
+    /// Applique `f` aux lectures gardées, remises à zéro si elles datent d'avant `seq`.
+    fn with<R>(&self, seq: i64, f: impl FnOnce(&mut Cached) -> R) -> Option<R> {
+        let mut cached = self.cached.lock().ok()?;
+        cached.sync(seq);
+        Some(f(&mut cached))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn meta(path: &str) -> FileMetadata {
+        FileMetadata { logical_path: path.to_string(), encrypted_size: 1 }
+    }
+
+    #[test]
+    fn cached_reads_follow_writes_from_any_connection() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("index.db");
+        let reader = SqlCipherIndex::open(&db_path, &[7u8; 32]).unwrap();
+        let mut writer = SqlCipherIndex::open(&db_path, &[7u8; 32]).unwrap();
+        writer.upsert("a".to_string(), meta("/docs/a.txt")).unwrap();
+        writer.upsert("b".to_string(), meta("/docs/b.txt")).unwrap();
+        let cache = IndexCache::new();
+
+        let docs = |cache: &IndexCache| {
+            let mut paths = Vec::new();
+            cache.scan_prefix(&reader, "/docs/", |_, meta| {
+                paths.push(meta.logical_path);
+                true
+            })
+            .unwrap();
+            paths
+        };
+        assert_eq!(docs(&cache), vec!["/docs/a.txt", "/docs/b.txt"]);
+        assert_eq!(docs(&cache), vec!["/docs/a.txt", "/docs/b.txt"]);
+        assert_eq!(cache.get(&reader, &"a".to_string()).unwrap().unwrap().logical_path, "/docs/a.txt");
+        assert!(cache.get(&reader, &"c".to_string()).unwrap().is_none());
+        assert_eq!(cache.list_all(&reader).unwrap().len(), 2);
+
+        // Une écriture par une autre connexion est vue à la lecture suivante
+        writer.upsert("a".to_string(), meta("/docs/z.txt")).unwrap();
+        writer.upsert("c".to_string(), meta("/docs/c.txt")).unwrap();
+        assert_eq!(docs(&cache), vec!["/docs/b.txt", "/docs/c.txt", "/docs/z.txt"]);
+        assert_eq!(cache.get(&reader, &"a".to_string()).unwrap().unwrap().logical_path, "/docs/z.txt");
+        assert!(cache.get(&reader, &"c".to_string()).unwrap().is_some());
+        writer.remove(&"b".to_string()).unwrap();
+        assert_eq!(cache.list_all(&reader).unwrap().len(), 2);
+
+        // Un parcours interrompu n'est pas gardé
+        let mut first = Vec::new();
+        cache.scan_prefix(&reader, "/", |id, _| {
+            first.push(id);
+            false
+        })
+        .unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(cache.scan_prefix(&reader, "/", |_, _| true).unwrap(), 2);
+    }
+}
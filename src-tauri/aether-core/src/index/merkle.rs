@@ -66,7 +66,7 @@ impl MerkleTree {
         hasher.update(b":");
         hasher.update(meta.logical_path.as_bytes());
         hasher.update(b":");
-        hasher.update(&meta.encrypted_size.to_le_bytes());
+        hasher.update(meta.encrypted_size.to_le_bytes());
         hasher.finalize().into()
     }
 
@@ -0,0 +1,311 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub mod cache;
+pub mod sqlcipher;
+pub mod merkle;
+pub mod path;
+pub mod writer;
+
+/// Identifiant logique d'un fichier dans l'index local.
+pub type FileId = String;
+
+/// Métadonnées minimales d'un fichier chiffré.
+#[derive(Debug, Clone)]
+pub struct FileMetadata {
+    /// Chemin logique présenté à l'utilisateur (inclus dans l'AAD côté crypto).
+    pub logical_path: String,
+    /// Taille du contenu chiffré, en octets.
+    pub encrypted_size: u64,
+}
+
+/// Apparence personnalisée d'un dossier (purement visuelle, sans effet sur les fichiers).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderAppearance {
+    /// Couleur `#rrggbb`.
+    pub color: Option<String>,
+    /// Emoji ou court pictogramme affiché à la place de l'icône par défaut.
+    pub icon: Option<String>,
+}
+
+impl FolderAppearance {
+    /// Longueur maximale d'une icône, en caractères (un emoji composé en compte plusieurs).
+    pub const MAX_ICON_CHARS: usize = 8;
+
+    /// Nettoie et valide l'apparence ; les champs vides sont ramenés à `None`.
+    pub fn validate(self) -> Result<Self, &'static str> {
+        let color = self.color.map(|c| c.trim().to_ascii_lowercase()).filter(|c| !c.is_empty());
+        let icon = self.icon.map(|i| i.trim().to_string()).filter(|i| !i.is_empty());
+        if let Some(color) = &color {
+            let hex = color.strip_prefix('#').unwrap_or("");
+            if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err("invalid_color");
+            }
+        }
+        if let Some(icon) = &icon {
+            if icon.chars().count() > Self::MAX_ICON_CHARS || icon.chars().any(|c| c.is_control() || c.is_whitespace()) {
+                return Err("invalid_icon");
+            }
+        }
+        Ok(Self { color, icon })
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.color.is_none() && self.icon.is_none()
+    }
+
+    /// Clé d'un dossier : chemin absolu, sans `/` final (`/a/b/` et `a/b` désignent le même dossier).
+    pub fn folder_key(path: &str) -> String {
+        format!("/{}", path.trim_matches('/'))
+    }
+}
+
+/// Type d'opération distante mise en attente (mode hors ligne).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingKind {
+    /// Le blob chiffré est dans le cache local, il reste à l'envoyer.
+    Upload,
+    /// L'objet distant doit être supprimé définitivement.
+    Delete,
+    /// L'objet distant doit être re-chiffré sous son nouveau chemin logique (dossier renommé).
+    Rebind,
+}
+
+impl PendingKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PendingKind::Upload => "upload",
+            PendingKind::Delete => "delete",
+            PendingKind::Rebind => "rebind",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "upload" => Some(PendingKind::Upload),
+            "delete" => Some(PendingKind::Delete),
+            "rebind" => Some(PendingKind::Rebind),
+            _ => None,
+        }
+    }
+}
+
+/// Opération distante différée, rejouée dans l'ordre d'insertion.
+#[derive(Debug, Clone)]
+pub struct PendingOp {
+    pub seq: i64,
+    pub kind: PendingKind,
+    pub file_id: FileId,
+    pub created_at: i64,
+}
+
+/// Règle d'automatisation telle que stockée ; `spec` est interprétée par le module `rules`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredRule {
+    pub id: i64,
+    pub name: String,
+    pub enabled: bool,
+    /// Définition JSON de la règle.
+    pub spec: String,
+    pub last_run: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// Hook d'événement tel que stocké ; `spec` est interprétée par le module `hooks`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredHook {
+    pub id: i64,
+    pub name: String,
+    pub enabled: bool,
+    /// Définition JSON du hook (événements et cible).
+    pub spec: String,
+    pub last_fired: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// Ancienne version d'un fichier, remplacée par un nouvel envoi au même chemin logique.
+///
+/// Son objet distant est conservé tant que la version n'est pas retirée.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileVersion {
+    pub id: FileId,
+    pub logical_path: String,
+    pub encrypted_size: u64,
+    /// Fichier qui l'a remplacée.
+    pub replaced_by: FileId,
+    /// Timestamp Unix du remplacement.
+    pub replaced_at: i64,
+}
+
+/// Fichier déjà présent dont le contenu est identique à un nouvel envoi (déduplication).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExistingBlob {
+    pub file_id: FileId,
+    pub logical_path: String,
+    pub encrypted_size: u64,
+    /// Objet distant qui porte le contenu.
+    pub object_key: FileId,
+    /// Chemin lié à l'AAD du contenu chiffré.
+    pub aad_path: String,
+}
+
+/// Alias : entrée qui fait apparaître un fichier à un autre emplacement du coffre, sans
+/// dupliquer son contenu chiffré.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AliasEntry {
+    pub id: FileId,
+    pub logical_path: String,
+    /// Fichier désigné (clé de `file_index`).
+    pub target_id: FileId,
+    pub created_at: i64,
+}
+
+/// Instantané de l'index : état complet du coffre à un instant donné.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SnapshotInfo {
+    pub id: i64,
+    pub name: String,
+    pub created_at: i64,
+    /// Entrées (fichiers et dossiers) capturées.
+    pub file_count: usize,
+}
+
+/// Bilan d'une restauration d'instantané.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SnapshotRestore {
+    /// Entrées remises à leur état de l'instantané.
+    pub restored: usize,
+    /// Entrées postérieures à l'instantané, envoyées à la corbeille.
+    pub trashed: usize,
+}
+
+/// Dossier envoyé à la corbeille d'un bloc, avec toute sa sous-arborescence.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrashedFolder {
+    /// Chemin d'origine du dossier (se termine par `/`), où il sera restauré.
+    pub path: String,
+    pub deleted_at: i64,
+    /// Fichiers contenus (les dossiers vides ne comptent pas).
+    pub file_count: usize,
+    pub encrypted_size: u64,
+}
+
+/// Métadonnées d'une photo lues avant le chiffrement (EXIF ou en-tête de l'image).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PhotoInfo {
+    /// Date de prise de vue, heure locale de l'appareil (`AAAA-MM-JJTHH:MM:SS`).
+    pub taken_at: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// Entrée dont l'objet distant a échoué à la vérification (voir `crate::quarantine`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QuarantineEntry {
+    pub file_id: FileId,
+    pub reason: String,
+    /// Où l'échec a été constaté : `scrub` (vérification d'intégrité) ou `download`.
+    pub source: String,
+    pub detected_at: i64,
+}
+
+/// Modification de l'index inscrite au journal de synchronisation (voir `crate::journal`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalOp {
+    /// Position dans le journal de l'appareil auteur.
+    pub seq: i64,
+    pub file_id: FileId,
+    /// État de l'entrée après l'opération ; `None` si elle a quitté l'index.
+    pub entry: Option<JournalEntry>,
+    /// Racine Merkle de l'index de l'auteur après l'opération (hex).
+    pub root: Option<String>,
+    pub recorded_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub logical_path: String,
+    pub encrypted_size: u64,
+    /// Chemin lié à l'AAD, s'il diffère du chemin logique.
+    pub bound_path: Option<String>,
+    /// Objet distant partagé (entrée dédupliquée) et chemin AAD de son contenu.
+    pub blob_link: Option<(FileId, String)>,
+    /// Taille du contenu en clair, si l'appareil d'origine l'a relevée.
+    pub plaintext_size: Option<u64>,
+}
+
+/// Bilan du rejeu d'opérations distantes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JournalApply {
+    pub applied: usize,
+    /// Opérations écartées : l'entrée a été modifiée ici sans que l'auteur l'ait vu, ou son
+    /// chemin est pris par une autre entrée. L'état local est conservé.
+    pub conflicts: Vec<JournalOp>,
+    /// Fichiers en conflit conservés quand même (mode conservateur), avec le chemin retenu.
+    pub kept: Vec<(FileId, String)>,
+}
+
+/// Résolution des conflits au rejeu du journal de synchronisation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictMode {
+    /// L'entrée locale l'emporte, l'opération distante est écartée.
+    #[default]
+    Normal,
+    /// Un autre appareil modifie le coffre en même temps (voir `crate::sessions`) : un
+    /// fichier distant en conflit est en plus gardé sous un autre nom, rien n'est écarté
+    /// sans copie.
+    Conservative,
+}
+
+/// Racine Merkle enregistrée après une modification de l'index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MerkleRootRecord {
+    pub seq: i64,
+    /// Racine (hex).
+    pub root: String,
+    /// Opération qui l'a produite (`upsert`, `remove`, `trash`, `restore`, `rename_folder`,
+    /// `snapshot_restore`, `journal`).
+    pub operation: String,
+    pub recorded_at: i64,
+    /// Le maillon est chaîné (HMAC) au précédent : l'historique n'a pas été réécrit.
+    pub chain_valid: bool,
+}
+
+/// API de base pour l'index local.
+///
+/// NOTE : cette première version est purement en mémoire.
+/// La persistance réelle se fera via SQLite + SQLCipher,
+/// conformément à la blueprint, dans une micro-étape suivante.
+#[derive(Default)]
+pub struct InMemoryIndex {
+    entries: HashMap<FileId, FileMetadata>,
+}
+
+impl InMemoryIndex {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn upsert(&mut self, id: FileId, meta: FileMetadata) {
+        self.entries.insert(id, meta);
+    }
+
+    pub fn get(&self, id: &FileId) -> Option<&FileMetadata> {
+        self.entries.get(id)
+    }
+
+    pub fn remove(&mut self, id: &FileId) {
+        self.entries.remove(id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
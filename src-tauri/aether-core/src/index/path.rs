@@ -0,0 +1,108 @@
+//! Forme canonique et règles des chemins logiques.
+//!
+//! Un même nom peut s'écrire en Unicode sous forme composée (NFC, « é ») ou décomposée
+//! (NFD, « e » + accent, celle de macOS). Sans normalisation, deux fichiers d'apparence
+//! identique cohabitent et une recherche par chemin échoue selon l'appareil d'origine.
+//! Index, AAD et interface utilisent donc tous la forme produite par [`normalize`].
+
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+/// Longueur maximale d'un chemin logique, en octets.
+pub const MAX_PATH_BYTES: usize = 1024;
+/// Longueur maximale d'un nom (la limite de la plupart des systèmes de fichiers) : au-delà,
+/// le fichier ne pourrait pas être restauré ou exporté sur disque.
+pub const MAX_NAME_BYTES: usize = 255;
+/// Caractères refusés par Windows : un tel nom ne se restaurerait pas sur tous les appareils.
+const RESERVED_CHARS: &[char] = &['\\', ':', '*', '?', '"', '<', '>', '|'];
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Forme canonique : NFC, `/` initial, sans `/` répétés. Un `/` final (dossier) est conservé.
+pub fn normalize(path: &str) -> String {
+    let mut normalized = String::with_capacity(path.len() + 1);
+    if !path.starts_with('/') {
+        normalized.push('/');
+    }
+    for c in path.nfc() {
+        if c == '/' && normalized.ends_with('/') {
+            continue;
+        }
+        normalized.push(c);
+    }
+    normalized
+}
+
+/// Le chemin est déjà sous sa forme canonique.
+pub fn is_normalized(path: &str) -> bool {
+    path.starts_with('/') && !path.contains("//") && is_nfc(path)
+}
+
+/// Vérifie un nom (fichier ou dossier) : il pourra être stocké puis restauré partout.
+pub fn validate_name(name: &str) -> Result<(), &'static str> {
+    if name.is_empty() {
+        return Err("empty_name");
+    }
+    if name == "." || name == ".." {
+        return Err("relative_name");
+    }
+    if name.len() > MAX_NAME_BYTES {
+        return Err("name_too_long");
+    }
+    if name.chars().any(|c| c.is_control()) {
+        return Err("control_character");
+    }
+    if name.contains(RESERVED_CHARS) {
+        return Err("reserved_character");
+    }
+    if name.ends_with(['.', ' ']) {
+        return Err("trailing_dot_or_space");
+    }
+    let stem = name.split('.').next().unwrap_or(name);
+    if RESERVED_NAMES.iter().any(|reserved| stem.eq_ignore_ascii_case(reserved)) {
+        return Err("reserved_name");
+    }
+    Ok(())
+}
+
+/// Vérifie le chemin (déjà normalisé) d'un fichier : longueur totale et chaque nom.
+pub fn validate_file_path(path: &str) -> Result<(), &'static str> {
+    if !path.starts_with('/') || path.ends_with('/') {
+        return Err("not_a_file_path");
+    }
+    if path.len() > MAX_PATH_BYTES {
+        return Err("path_too_long");
+    }
+    path[1..].split('/').try_for_each(validate_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decomposed_and_composed_forms_share_one_path() {
+        let composed = "/Été/café.txt";
+        let decomposed = "/E\u{301}te\u{301}/cafe\u{301}.txt";
+        assert_ne!(composed, decomposed);
+        assert_eq!(normalize(decomposed), composed);
+        assert!(is_normalized(composed));
+        assert!(!is_normalized(decomposed));
+        assert_eq!(normalize("docs///a.txt"), "/docs/a.txt");
+        assert_eq!(normalize("/docs//"), "/docs/");
+    }
+
+    #[test]
+    fn paths_that_would_not_restore_everywhere_are_refused() {
+        assert_eq!(validate_file_path("/Documents/rapport final.pdf"), Ok(()));
+        assert_eq!(validate_file_path("/Documents/"), Err("not_a_file_path"));
+        assert_eq!(validate_file_path("/a//b.txt"), Err("empty_name"));
+        assert_eq!(validate_file_path("/a/../b.txt"), Err("relative_name"));
+        assert_eq!(validate_file_path("/notes: brouillon.txt"), Err("reserved_character"));
+        assert_eq!(validate_file_path("/fin."), Err("trailing_dot_or_space"));
+        assert_eq!(validate_file_path("/aux.txt"), Err("reserved_name"));
+        assert_eq!(validate_file_path(&format!("/{}", "é".repeat(128))), Err("name_too_long"));
+        assert_eq!(validate_file_path(&format!("/{}", ["dossier"; 150].join("/"))), Err("path_too_long"));
+    }
+}
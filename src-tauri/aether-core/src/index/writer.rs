@@ -0,0 +1,122 @@
+//! File d'écriture unique de l'index.
+//!
+//! Chaque commande ouvrait sa propre connexion : deux écritures concurrentes (un envoi qui
+//! indexe son fichier pendant le nettoyage des orphelins, par exemple) pouvaient s'entrelacer
+//! et perdre une mise à jour. Les écritures passent désormais par une seule connexion, tenue
+//! par un thread dédié qui les exécute une à une dans l'ordre d'arrivée.
+
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use tokio::sync::{mpsc, oneshot};
+
+use super::sqlcipher::SqlCipherIndex;
+
+type WriteJob = Box<dyn FnOnce(&mut SqlCipherIndex) + Send>;
+
+#[derive(Debug)]
+pub enum WriteError {
+    Index(rusqlite::Error),
+    /// Le thread d'écriture est arrêté (coffre verrouillé, ou écriture ayant paniqué).
+    Stopped,
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteError::Index(e) => write!(f, "Index error: {}", e),
+            WriteError::Stopped => write!(f, "Index writer stopped"),
+        }
+    }
+}
+
+impl std::error::Error for WriteError {}
+
+impl From<rusqlite::Error> for WriteError {
+    fn from(e: rusqlite::Error) -> Self {
+        WriteError::Index(e)
+    }
+}
+
+/// Accès en écriture à l'index, clonable ; le thread s'arrête avec le dernier clone.
+#[derive(Clone)]
+pub struct IndexWriter {
+    jobs: mpsc::UnboundedSender<WriteJob>,
+}
+
+impl IndexWriter {
+    /// Ouvre la connexion d'écriture et démarre le thread qui la détient.
+    pub fn spawn(db_path: &Path, master_key: &[u8]) -> Result<Self, WriteError> {
+        let mut index = SqlCipherIndex::open(db_path, master_key)?;
+        let (jobs, mut queue) = mpsc::unbounded_channel::<WriteJob>();
+        std::thread::Builder::new()
+            .name("index-writer".to_string())
+            .spawn(move || {
+                while let Some(job) = queue.blocking_recv() {
+                    // Une écriture qui panique ne doit pas arrêter les suivantes
+                    if panic::catch_unwind(AssertUnwindSafe(|| job(&mut index))).is_err() {
+                        log::error!("Index write panicked, continuing with the next one");
+                    }
+                }
+                log::info!("Index writer stopped");
+            })
+            .map_err(|e| {
+                log::error!("Failed to start the index writer: {}", e);
+                WriteError::Stopped
+            })?;
+        Ok(Self { jobs })
+    }
+
+    /// Exécute `f` sur la connexion d'écriture, après les écritures déjà en file.
+    ///
+    /// Le résultat de `f` (souvent lui-même un `Result`) est rendu tel quel.
+    pub async fn write<R, F>(&self, f: F) -> Result<R, WriteError>
+    where
+        F: FnOnce(&mut SqlCipherIndex) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (done, result) = oneshot::channel();
+        self.jobs
+            .send(Box::new(move |index| {
+                let _ = done.send(f(index));
+            }))
+            .map_err(|_| WriteError::Stopped)?;
+        result.await.map_err(|_| WriteError::Stopped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::FileMetadata;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn concurrent_writes_are_all_applied() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("index.db");
+        let writer = IndexWriter::spawn(&db_path, &[31u8; 32]).unwrap();
+
+        let writes = (0..20).map(|i| {
+            let writer = writer.clone();
+            tokio::spawn(async move {
+                writer
+                    .write(move |index| {
+                        let meta = FileMetadata { logical_path: format!("/{}.txt", i), encrypted_size: 1 };
+                        index.upsert(format!("file-{}", i), meta)
+                    })
+                    .await
+            })
+        });
+        for write in writes.collect::<Vec<_>>() {
+            write.await.unwrap().unwrap().unwrap();
+        }
+
+        let count = writer.write(|index| index.len()).await.unwrap().unwrap();
+        assert_eq!(count, 20);
+        // Une écriture qui panique n'arrête pas le thread
+        let panicked: Result<(), _> = writer.write(|_| panic!("boom")).await;
+        assert!(matches!(panicked, Err(WriteError::Stopped)));
+        assert_eq!(writer.write(|index| index.len()).await.unwrap().unwrap(), 20);
+    }
+}
@@ -0,0 +1,283 @@
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::clock::unix_now;
+use crate::crypto::MasterKey;
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::index::MerkleRootRecord;
+use crate::storage::{self, AetherFile, StorageError};
+use crate::storj::StorjError;
+use crate::vault::{Vault, VaultError};
+
+/// Intervalle entre deux vérifications automatiques.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Nombre d'objets distants dont l'en-tête est relu à chaque vérification.
+pub const REMOTE_SAMPLE_SIZE: usize = 8;
+/// Durée pendant laquelle un objet vérifié (ou envoyé) d'ETag inchangé n'est pas relu :
+/// au-delà, son en-tête est relu quel que soit l'ETag annoncé par le service.
+pub const ETAG_TRUST: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Raison d'une dérive : l'objet distant a disparu (l'entrée d'index est orpheline).
+pub const MISSING_REMOTE_REASON: &str = "missing remote object";
+
+/// Origine d'une mise en quarantaine par la vérification périodique.
+pub const QUARANTINE_SOURCE: &str = "scrub";
+
+/// Clé `index_metadata` sous laquelle le dernier rapport est conservé.
+const REPORT_META_KEY: &str = "integrity_report";
+
+/// Écart constaté entre un objet distant et son entrée d'index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteDrift {
+    pub file_id: String,
+    pub reason: String,
+}
+
+/// Résultat d'une vérification d'intégrité (conservé dans l'index jusqu'à la suivante).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// Timestamp Unix (secondes) de la vérification.
+    pub checked_at: i64,
+    pub merkle_ok: bool,
+    /// Lignes dont le HMAC ne correspond plus (index ou corbeille).
+    pub tampered_rows: Vec<String>,
+    /// Objets distants effectivement relus (0 si le stockage était injoignable).
+    pub remote_sampled: usize,
+    /// Objets distants de l'échantillon non relus : ETag inchangé depuis leur dernière
+    /// vérification (requête conditionnelle).
+    #[serde(default)]
+    pub remote_unchanged: usize,
+    pub remote_drift: Vec<RemoteDrift>,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.merkle_ok && self.tampered_rows.is_empty() && self.remote_drift.is_empty()
+    }
+
+    /// Vrai si la dernière vérification date de plus de [`CHECK_INTERVAL`].
+    pub fn is_stale(&self) -> bool {
+        unix_now() - self.checked_at >= CHECK_INTERVAL.as_secs() as i64
+    }
+
+    /// Résumé technique (anglais, pour les journaux et l'événement d'alerte).
+    pub fn summary(&self) -> String {
+        format!(
+            "merkle_ok={}, tampered_rows={}, remote_drift={}",
+            self.merkle_ok,
+            self.tampered_rows.len(),
+            self.remote_drift.len()
+        )
+    }
+}
+
+/// Historique des racines Merkle, pour repérer un changement survenu hors de l'application.
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityTimeline {
+    /// De la plus récente à la plus ancienne.
+    pub records: Vec<MerkleRootRecord>,
+    /// Racine recalculée depuis l'index actuel (hex).
+    pub current_root: String,
+    /// L'index ne correspond plus à la dernière racine enregistrée (modifié app fermée).
+    pub unexpected_change: bool,
+    /// Au moins un maillon de l'historique a été réécrit.
+    pub broken_chain: bool,
+}
+
+/// Chronologie des racines Merkle (au plus `limit`) comparée à l'état actuel de l'index.
+pub fn timeline(index: &SqlCipherIndex, limit: usize) -> Result<IntegrityTimeline, VaultError> {
+    let records = index.merkle_history(limit)?;
+    let current_root = hex::encode(index.computed_merkle_root()?);
+    let unexpected_change = records.first().is_some_and(|latest| latest.root != current_root);
+    let broken_chain = records.iter().any(|record| !record.chain_valid);
+    if unexpected_change || broken_chain {
+        log::warn!(
+            "Merkle timeline anomaly: unexpected_change={}, broken_chain={}",
+            unexpected_change, broken_chain
+        );
+    }
+    Ok(IntegrityTimeline {
+        records,
+        current_root,
+        unexpected_change,
+        broken_chain,
+    })
+}
+
+/// Dernier rapport enregistré, s'il existe.
+pub fn last_report(index: &SqlCipherIndex) -> Result<Option<IntegrityReport>, VaultError> {
+    Ok(index.get_meta_json(REPORT_META_KEY)?)
+}
+
+/// Vérifie l'index local (Merkle + HMAC ligne par ligne) puis un échantillon d'objets distants.
+///
+/// Le rapport est enregistré dans l'index : une dérive reste signalée tant qu'une
+/// vérification ultérieure ne l'a pas levée. Un objet altéré met son entrée en quarantaine
+/// (voir `crate::quarantine`) ; un objet manquant relève de la réconciliation.
+pub async fn check(vault: &Vault, sample_size: usize) -> Result<IntegrityReport, VaultError> {
+    let (tampered_rows, merkle_ok, mut sample) = {
+        let index = vault.open_index()?;
+        let tampered_rows = index.tampered_rows()?;
+        // `verify_integrity` relit l'index via `list_all`, qui échoue sur une ligne altérée
+        let merkle_ok = tampered_rows.is_empty() && index.verify_integrity()?;
+        let files = if tampered_rows.is_empty() {
+            index
+                .list_all()?
+                .into_iter()
+                .filter(|(_, meta)| !meta.logical_path.ends_with('/') && meta.encrypted_size > 0)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        (tampered_rows, merkle_ok, files)
+    };
+    let (pending, links) = {
+        let index = vault.open_index()?;
+        (index.pending_upload_ids()?, index.blob_links()?)
+    };
+    sample.retain(|(file_id, _)| !pending.contains(file_id));
+    sample.shuffle(&mut rand::thread_rng());
+    sample.truncate(sample_size);
+
+    let mut report = IntegrityReport {
+        checked_at: unix_now(),
+        merkle_ok,
+        tampered_rows,
+        ..Default::default()
+    };
+
+    for (file_id, meta) in sample {
+        // Une entrée dédupliquée est vérifiée sur l'objet qu'elle partage
+        let object_key = links.get(&file_id).unwrap_or(&file_id).clone();
+        let known = vault
+            .open_index()?
+            .known_etag(&object_key)?
+            .filter(|(_, verified_at)| report.checked_at - verified_at < ETAG_TRUST.as_secs() as i64);
+        let header_len = AetherFile::HEADER_LEN as u64;
+        let read = match &known {
+            Some((etag, _)) => vault.remote().download_range_if_changed(&object_key, 0, header_len, etag).await,
+            None => vault.remote().download_range(&object_key, 0, header_len).await.map(Some),
+        };
+        let read = match read {
+            Ok(Some(read)) => read,
+            Ok(None) => {
+                report.remote_unchanged += 1;
+                continue;
+            }
+            Err(StorjError::Unreachable(msg)) => {
+                log::info!("Integrity check skipped remote sampling: {}", msg);
+                break;
+            }
+            Err(StorjError::NotFound) => {
+                report.remote_drift.push(RemoteDrift {
+                    file_id,
+                    reason: MISSING_REMOTE_REASON.to_string(),
+                });
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        report.remote_sampled += 1;
+
+        let total = read.total;
+        let reason = object_drift(vault.master_key(), &object_key, &read.data, total).or_else(|| {
+            (total != meta.encrypted_size)
+                .then(|| format!("object size {} differs from indexed size {}", total, meta.encrypted_size))
+        });
+        let mut index = vault.open_index()?;
+        match (reason, read.etag) {
+            (Some(reason), _) => {
+                // Le contenu n'est plus servi tant que l'entrée n'a pas été réparée
+                index.forget_etag(&object_key)?;
+                index.quarantine(&file_id, &reason, QUARANTINE_SOURCE)?;
+                log::warn!("Quarantined {} after integrity check: {}", file_id, reason);
+                report.remote_drift.push(RemoteDrift { file_id, reason });
+            }
+            (None, Some(etag)) => index.record_etag(&object_key, &etag)?,
+            (None, None) => {}
+        }
+    }
+
+    let raw = serde_json::to_vec(&report).map_err(|e| VaultError::Storage(StorageError::Io(e.to_string())))?;
+    vault.open_index()?.put_meta(REPORT_META_KEY, &raw)?;
+
+    if report.is_clean() {
+        log::info!("Integrity check passed: sampled={}, unchanged={}", report.remote_sampled, report.remote_unchanged);
+    } else {
+        log::warn!("Integrity check found drift: {}", report.summary());
+    }
+    Ok(report)
+}
+
+/// Vérifie l'en-tête d'un objet distant (début `prefix` d'un objet de `total` octets) :
+/// format, UUID égal à la clé d'objet, longueur annoncée et engagement sous la FileKey.
+///
+/// Retourne la raison de l'échec, `None` si l'en-tête est sain.
+pub fn object_drift(master_key: &MasterKey, object_key: &str, prefix: &[u8], total: u64) -> Option<String> {
+    match AetherFile::parse_header(prefix) {
+        Err(e) => Some(format!("invalid header: {}", e)),
+        Ok((header, _)) if hex::encode(header.uuid) != object_key => {
+            Some("header UUID does not match object key".to_string())
+        }
+        Ok((_, ciphertext_len)) if ciphertext_len + AetherFile::HEADER_LEN as u64 != total => {
+            Some("header length does not match object size".to_string())
+        }
+        Ok((header, _)) if !storage::encryption_details(master_key, &header).is_ok_and(|d| d.commitment_valid) => {
+            Some("header commitment does not verify".to_string())
+        }
+        Ok(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::FileMetadata;
+    use tempfile::TempDir;
+
+    #[test]
+    fn report_roundtrips_through_index_metadata() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = SqlCipherIndex::open(temp_dir.path().join("index.db"), &[3u8; 32]).unwrap();
+        assert!(last_report(&index).unwrap().is_none());
+
+        index
+            .upsert("file-1".to_string(), FileMetadata { logical_path: "/a".to_string(), encrypted_size: 1 })
+            .unwrap();
+        let report = IntegrityReport {
+            checked_at: unix_now(),
+            merkle_ok: true,
+            tampered_rows: Vec::new(),
+            remote_sampled: 1,
+            remote_unchanged: 0,
+            remote_drift: vec![RemoteDrift { file_id: "file-1".to_string(), reason: "missing".to_string() }],
+        };
+        index.put_meta(REPORT_META_KEY, &serde_json::to_vec(&report).unwrap()).unwrap();
+
+        let stored = last_report(&index).unwrap().unwrap();
+        assert!(!stored.is_clean());
+        assert!(!stored.is_stale());
+        assert_eq!(stored.remote_drift, report.remote_drift);
+    }
+
+    #[test]
+    fn timeline_matches_the_latest_recorded_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = SqlCipherIndex::open(temp_dir.path().join("index.db"), &[4u8; 32]).unwrap();
+        // Historique vide : rien à comparer
+        assert!(!timeline(&index, 10).unwrap().unexpected_change);
+
+        for id in ["file-1", "file-2"] {
+            index
+                .upsert(id.to_string(), FileMetadata { logical_path: format!("/{}", id), encrypted_size: 1 })
+                .unwrap();
+        }
+        index.remove(&"file-1".to_string()).unwrap();
+        let history = timeline(&index, 2).unwrap();
+        assert_eq!(history.records.len(), 2);
+        assert_eq!(history.records[0].operation, "remove");
+        assert_eq!(history.records[0].root, history.current_root);
+        assert!(!history.unexpected_change && !history.broken_chain);
+    }
+}
@@ -0,0 +1,208 @@
+//! Opérations composées (renommage, et plus tard re-chiffrement ou migration) découpées
+//! en étapes : chaque étape est annoncée à l'appelant et l'utilisateur peut annuler
+//! entre deux étapes. L'opération défait elle-même ce qui peut l'être sans risque.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Identifiant d'un job, unique pour la durée de la session.
+pub type JobId = u64;
+
+/// Drapeau d'annulation partagé entre le job et celui qui peut l'annuler.
+///
+/// L'annulation est coopérative : elle n'est constatée qu'au début de l'étape suivante.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Étape d'un job au moment où elle est annoncée.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobState {
+    Running,
+    /// Annulé : ce qui avait été fait a été défait.
+    Cancelled,
+    Completed,
+    Failed,
+}
+
+/// Avancement d'un job, transmis à chaque changement d'étape.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobProgress {
+    pub job_id: JobId,
+    pub step: &'static str,
+    /// Rang de l'étape (à partir de 0) ; vaut `step_count` une fois le job terminé.
+    pub step_index: usize,
+    pub step_count: usize,
+    pub state: JobState,
+}
+
+type ProgressFn<'a> = Box<dyn Fn(JobProgress) + Send + Sync + 'a>;
+
+/// Job en cours : ses étapes, son jeton d'annulation et le destinataire de l'avancement.
+pub struct Job<'a> {
+    id: JobId,
+    steps: &'static [&'static str],
+    token: CancelToken,
+    current: AtomicUsize,
+    on_progress: ProgressFn<'a>,
+}
+
+impl<'a> Job<'a> {
+    pub fn new(
+        id: JobId,
+        steps: &'static [&'static str],
+        token: CancelToken,
+        on_progress: impl Fn(JobProgress) + Send + Sync + 'a,
+    ) -> Self {
+        Self {
+            id,
+            steps,
+            token,
+            current: AtomicUsize::new(0),
+            on_progress: Box::new(on_progress),
+        }
+    }
+
+    /// Job sans suivi ni annulation possible (appels directs de l'API du coffre).
+    pub fn detached(steps: &'static [&'static str]) -> Self {
+        Self::new(0, steps, CancelToken::default(), |_| {})
+    }
+
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Annonce l'étape `index`. Renvoie `false` si le job a été annulé entre-temps :
+    /// l'appelant défait alors ce qui doit l'être puis appelle [`Job::cancelled`].
+    pub fn step(&self, index: usize) -> bool {
+        self.current.store(index, Ordering::SeqCst);
+        if self.is_cancelled() {
+            return false;
+        }
+        self.report(index, JobState::Running);
+        true
+    }
+
+    pub fn completed(&self) {
+        self.report(self.steps.len(), JobState::Completed);
+    }
+
+    /// L'étape en cours n'a pas été exécutée (ni celles qui suivent).
+    pub fn cancelled(&self) {
+        self.report(self.current.load(Ordering::SeqCst), JobState::Cancelled);
+    }
+
+    /// L'étape en cours a échoué.
+    pub fn failed(&self) {
+        self.report(self.current.load(Ordering::SeqCst), JobState::Failed);
+    }
+
+    fn report(&self, index: usize, state: JobState) {
+        let step = self.steps.get(index).copied().unwrap_or("done");
+        (self.on_progress)(JobProgress {
+            job_id: self.id,
+            step,
+            step_index: index,
+            step_count: self.steps.len(),
+            state,
+        });
+    }
+}
+
+/// Jobs en cours, pour que l'interface puisse les annuler par leur identifiant.
+#[derive(Debug)]
+pub struct JobRegistry {
+    next_id: AtomicU64,
+    running: Mutex<HashMap<JobId, CancelToken>>,
+}
+
+impl Default for JobRegistry {
+    fn default() -> Self {
+        Self {
+            next_id: AtomicU64::new(1),
+            running: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl JobRegistry {
+    /// Réserve un identifiant et le jeton d'annulation associé.
+    pub fn start(&self) -> (JobId, CancelToken) {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let token = CancelToken::default();
+        self.running
+            .lock()
+            .expect("job registry poisoned")
+            .insert(id, token.clone());
+        (id, token)
+    }
+
+    /// Demande l'annulation d'un job. Renvoie `false` s'il est inconnu ou déjà terminé.
+    pub fn cancel(&self, id: JobId) -> bool {
+        match self.running.lock().expect("job registry poisoned").get(&id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn finish(&self, id: JobId) {
+        self.running.lock().expect("job registry poisoned").remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEPS: &[&str] = &["read", "write"];
+
+    #[test]
+    fn steps_are_reported_until_the_job_is_cancelled() {
+        let registry = JobRegistry::default();
+        let (id, token) = registry.start();
+        let seen = Mutex::new(Vec::new());
+        let job = Job::new(id, STEPS, token, |progress| seen.lock().unwrap().push(progress));
+
+        assert!(job.step(0));
+        assert!(registry.cancel(id));
+        assert!(!job.step(1));
+        job.cancelled();
+        registry.finish(id);
+        assert!(!registry.cancel(id));
+        drop(job);
+
+        let seen = seen.into_inner().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!((seen[0].step, seen[0].state), ("read", JobState::Running));
+        assert_eq!((seen[1].step, seen[1].state), ("write", JobState::Cancelled));
+        assert_eq!(seen[1].step_count, 2);
+    }
+
+    #[test]
+    fn registry_hands_out_distinct_ids() {
+        let registry = JobRegistry::default();
+        let (first, _) = registry.start();
+        let (second, _) = registry.start();
+        assert_ne!(first, second);
+    }
+}
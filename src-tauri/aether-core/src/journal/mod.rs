@@ -0,0 +1,414 @@
+//! Journal de synchronisation de l'index entre appareils.
+//!
+//! Chaque écriture de l'index (entrée ajoutée, modifiée ou retirée) est inscrite par des
+//! déclencheurs SQLite dans un journal en ajout seul, avec la racine Merkle qui en résulte.
+//! Les opérations locales sont envoyées par segments chiffrés sous
+//! `JOURNAL_PREFIX<appareil>/`, et celles des autres appareils sont rejouées ici : seuls les
+//! segments nouveaux sont téléchargés, au lieu de comparer la liste complète du bucket.
+//!
+//! Un segment indique jusqu'où son auteur avait rejoué les journaux des autres appareils.
+//! Une opération distante sur une entrée modifiée ici depuis, sans que l'auteur l'ait vu,
+//! est un conflit : elle n'est pas appliquée et l'entrée locale est conservée.
+//! Quand un autre appareil modifie le coffre en même temps ([`sessions`]), un fichier distant
+//! écarté est en plus gardé sous un chemin libre, plutôt que perdu de vue.
+//!
+//! Les entrées des dossiers exclus de la synchronisation ([`policies`]) restent sur cet appareil.
+//!
+//! Après chaque synchronisation, l'appareil publie sa racine Merkle signée ([`roots`]).
+
+pub mod roots;
+
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::activity;
+use crate::clock::unix_now;
+use crate::crypto::sealed::{SealError, SealedObjects};
+use crate::crypto::CryptoError;
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::index::merkle::MerkleTree;
+use crate::index::{FileId, FileMetadata, JournalOp};
+use crate::policies::{self, FolderPolicies};
+use crate::sessions;
+use crate::storj::StorjError;
+use crate::vault::{Vault, VaultError};
+
+/// Préfixe des segments de journal dans le bucket (sous le préfixe réservé).
+pub const JOURNAL_PREFIX: &str = ".aether/journal/";
+
+/// Opérations par segment envoyé.
+const SEGMENT_OPS: usize = 1_000;
+/// Segments conservés dans le bucket par appareil ; un appareil resté hors ligne plus
+/// longtemps retombe sur une réconciliation complète.
+const SEGMENT_RETENTION: usize = 200;
+
+const DEVICE_META_KEY: &str = "journal_device_id";
+const STATE_META_KEY: &str = "journal_sync_state";
+const JOURNAL_KEY_INFO: &[u8] = b"aether-drive:journal-key:v1";
+const JOURNAL_AAD: &[u8] = b"aether-drive:journal:v1:";
+/// Segments liés à leur clé : ils ne peuvent pas être présentés sous une autre.
+const SEGMENTS: SealedObjects = SealedObjects::new(JOURNAL_KEY_INFO, JOURNAL_AAD);
+
+#[derive(Debug)]
+pub enum JournalError {
+    /// Segment illisible (`reason` : code stable).
+    Invalid(&'static str),
+    Crypto(CryptoError),
+    Vault(VaultError),
+}
+
+impl fmt::Display for JournalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JournalError::Invalid(reason) => write!(f, "Invalid sync journal segment: {}", reason),
+            JournalError::Crypto(e) => write!(f, "{}", e),
+            JournalError::Vault(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<CryptoError> for JournalError {
+    fn from(e: CryptoError) -> Self {
+        JournalError::Crypto(e)
+    }
+}
+
+impl From<SealError> for JournalError {
+    fn from(e: SealError) -> Self {
+        match e {
+            SealError::Invalid(reason) => JournalError::Invalid(reason),
+            SealError::Crypto(e) => JournalError::Crypto(e),
+        }
+    }
+}
+
+impl From<VaultError> for JournalError {
+    fn from(e: VaultError) -> Self {
+        JournalError::Vault(e)
+    }
+}
+
+impl From<StorjError> for JournalError {
+    fn from(e: StorjError) -> Self {
+        JournalError::Vault(e.into())
+    }
+}
+
+impl From<rusqlite::Error> for JournalError {
+    fn from(e: rusqlite::Error) -> Self {
+        JournalError::Vault(e.into())
+    }
+}
+
+impl std::error::Error for JournalError {}
+
+/// Progression de la synchronisation, conservée dans l'index.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncState {
+    /// Dernière opération locale envoyée.
+    pub pushed_seq: i64,
+    /// Dernière opération rejouée de chaque autre appareil (numérotation de l'appareil).
+    pub applied: BTreeMap<String, i64>,
+    pub last_sync_at: Option<i64>,
+}
+
+/// Contenu chiffré d'un segment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Segment {
+    /// Valeur de [`SyncState::applied`] de l'auteur à l'envoi.
+    seen: BTreeMap<String, i64>,
+    /// Racine Merkle de l'index de l'auteur à l'envoi (hex), rejeux compris.
+    root: String,
+    ops: Vec<JournalOp>,
+}
+
+/// Segment présent dans le bucket, d'après sa clé `<appareil>/<après>-<dernier>.seg`.
+///
+/// Le journal d'un appareil numérote aussi les opérations qu'il a rejouées : un segment
+/// indique donc la dernière opération envoyée avant lui (`after`), pour repérer un trou.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SegmentKey {
+    key: String,
+    device: String,
+    after: i64,
+    last: i64,
+}
+
+impl SegmentKey {
+    fn new(device: &str, after: i64, last: i64) -> Self {
+        Self {
+            key: format!("{}{}/{:020}-{:020}.seg", JOURNAL_PREFIX, device, after, last),
+            device: device.to_string(),
+            after,
+            last,
+        }
+    }
+
+    fn parse(key: &str) -> Option<Self> {
+        let (device, name) = key.strip_prefix(JOURNAL_PREFIX)?.split_once('/')?;
+        let (after, last) = name.strip_suffix(".seg")?.split_once('-')?;
+        Some(Self {
+            key: key.to_string(),
+            device: device.to_string(),
+            after: after.parse().ok()?,
+            last: last.parse().ok()?,
+        })
+    }
+}
+
+/// Opération distante écartée.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JournalConflict {
+    pub device: String,
+    pub file_id: FileId,
+    /// Chemin proposé par l'autre appareil, ou `None` pour un retrait.
+    pub logical_path: Option<String>,
+    /// Chemin sous lequel le fichier de l'autre appareil a tout de même été gardé (mode
+    /// conservateur, voir [`sessions`](crate::sessions)).
+    pub kept_as: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct JournalSyncReport {
+    pub pushed: usize,
+    pub applied: usize,
+    pub conflicts: Vec<JournalConflict>,
+    /// Le journal ne suffit pas à garantir l'accord avec le bucket : premier passage,
+    /// segments manquants ou racine Merkle différente de celle de l'auteur.
+    pub full_reconcile_needed: bool,
+}
+
+/// Identifiant de cet appareil dans les journaux, créé au premier besoin.
+pub fn device_id(index: &mut SqlCipherIndex) -> rusqlite::Result<String> {
+    if let Some(id) = index.get_meta(DEVICE_META_KEY)?.and_then(|raw| String::from_utf8(raw).ok()) {
+        return Ok(id);
+    }
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    let id = hex::encode(bytes);
+    index.put_meta(DEVICE_META_KEY, id.as_bytes())?;
+    Ok(id)
+}
+
+pub fn load_state(index: &SqlCipherIndex) -> rusqlite::Result<SyncState> {
+    Ok(index.get_meta_json(STATE_META_KEY)?.unwrap_or_default())
+}
+
+fn save_state(index: &mut SqlCipherIndex, state: &SyncState) -> rusqlite::Result<()> {
+    index.put_meta_json(STATE_META_KEY, state)
+}
+
+/// Racine Merkle de la part de l'index partagée avec les autres appareils : les entrées des
+/// dossiers exclus de la synchronisation ([`policies`]) n'y figurent pas.
+pub(crate) fn shared_root(index: &SqlCipherIndex) -> rusqlite::Result<String> {
+    let policies = policies::load(index)?;
+    if policies.is_empty() {
+        return Ok(hex::encode(index.computed_merkle_root()?));
+    }
+    let shared: HashMap<FileId, FileMetadata> = index
+        .list_all()?
+        .into_iter()
+        .filter(|(_, meta)| !policies.resolve(&meta.logical_path).exclude_from_sync)
+        .collect();
+    Ok(hex::encode(MerkleTree::build(&shared).root_hash()))
+}
+
+/// Une opération sur une entrée exclue de la synchronisation part comme un retrait : les
+/// autres appareils oublient l'entrée (ou ne la voient jamais) sans en connaître le contenu.
+fn shareable(policies: &FolderPolicies, mut op: JournalOp) -> JournalOp {
+    if op.entry.as_ref().is_some_and(|entry| policies.resolve(&entry.logical_path).exclude_from_sync) {
+        op.entry = None;
+        op.root = None;
+    }
+    op
+}
+
+/// Rejoue les segments nouveaux des autres appareils, puis envoie les opérations locales.
+///
+/// Les conflits sont résolus selon [`sessions::conflict_mode`] : un autre appareil qui
+/// modifie le coffre en même temps rend la résolution conservatrice.
+pub async fn sync(vault: &Vault) -> Result<JournalSyncReport, JournalError> {
+    let (device, mut state, mode) = {
+        let mut index = vault.open_index()?;
+        (device_id(&mut index)?, load_state(&index)?, sessions::conflict_mode(&index)?)
+    };
+    let mut report = JournalSyncReport {
+        full_reconcile_needed: state.last_sync_at.is_none(),
+        ..JournalSyncReport::default()
+    };
+
+    let mut segments: Vec<SegmentKey> = vault
+        .remote()
+        .list_prefix(JOURNAL_PREFIX)
+        .await?
+        .into_iter()
+        .filter_map(|object| SegmentKey::parse(&object.key))
+        .collect();
+    segments.sort_by(|a, b| a.device.cmp(&b.device).then(a.after.cmp(&b.after)));
+
+    for segment in segments.iter().filter(|segment| segment.device != device) {
+        let applied = state.applied.get(&segment.device).copied().unwrap_or(0);
+        if segment.last <= applied {
+            continue;
+        }
+        if segment.after > applied {
+            log::warn!(
+                "Sync journal of device {} has a gap: applied={}, next segment follows {}",
+                segment.device, applied, segment.after
+            );
+            report.full_reconcile_needed = true;
+        }
+        let sealed = vault.remote().download_file(&segment.key).await?;
+        let contents: Segment = SEGMENTS.open(vault.master_key(), &segment.key, &sealed)?;
+        let ops: Vec<JournalOp> = contents.ops.into_iter().filter(|op| op.seq > applied).collect();
+        let seen = contents.seen.get(&device).copied().unwrap_or(0);
+
+        let mut index = vault.open_index()?;
+        let outcome = index.apply_journal_ops(&segment.device, &ops, seen, mode)?;
+        activity::record_conflicts(&mut index, outcome.conflicts.len())?;
+        report.applied += outcome.applied;
+        report.conflicts.extend(outcome.conflicts.iter().map(|op| JournalConflict {
+            device: segment.device.clone(),
+            file_id: op.file_id.clone(),
+            logical_path: op.entry.as_ref().map(|entry| entry.logical_path.clone()),
+            kept_as: outcome.kept.iter().find(|(file_id, _)| *file_id == op.file_id).map(|(_, path)| path.clone()),
+        }));
+
+        // L'auteur avait vu tout ce qui s'est fait ici : les deux index doivent concorder
+        // (au pire, avec un troisième appareil, une réconciliation complète de trop)
+        if outcome.conflicts.is_empty()
+            && index.journal_last_seq()? <= seen
+            && shared_root(&index)? != contents.root
+        {
+            log::warn!("Index diverged from device {} after replaying its journal", segment.device);
+            report.full_reconcile_needed = true;
+        }
+        state.applied.insert(segment.device.clone(), segment.last);
+        save_state(&mut index, &state)?;
+    }
+
+    loop {
+        let (ops, root, policies) = {
+            let index = vault.open_index()?;
+            (index.journal_ops_after(state.pushed_seq, SEGMENT_OPS)?, shared_root(&index)?, policies::load(&index)?)
+        };
+        let Some(last) = ops.last().map(|op| op.seq) else {
+            break;
+        };
+        let ops: Vec<JournalOp> = ops.into_iter().map(|op| shareable(&policies, op)).collect();
+        let segment_key = SegmentKey::new(&device, state.pushed_seq, last);
+        let count = ops.len();
+        let sealed = SEGMENTS.seal(vault.master_key(), &segment_key.key, &Segment { seen: state.applied.clone(), root, ops })?;
+        vault.remote().upload_file(&segment_key.key, &sealed).await?;
+        log::info!("Sync journal segment uploaded: key={}, ops={}", segment_key.key, count);
+        report.pushed += count;
+        state.pushed_seq = last;
+        save_state(&mut vault.open_index()?, &state)?;
+    }
+
+    let own: Vec<&SegmentKey> = segments.iter().filter(|segment| segment.device == device).collect();
+    for segment in own.iter().rev().skip(SEGMENT_RETENTION) {
+        if let Err(e) = vault.remote().delete_file(&segment.key).await {
+            log::warn!("Failed to prune sync journal segment {}: {}", segment.key, e);
+        }
+    }
+
+    {
+        let mut index = vault.open_index()?;
+        index.prune_journal(state.pushed_seq)?;
+        state.last_sync_at = Some(unix_now());
+        save_state(&mut index, &state)?;
+    }
+    if let Err(e) = roots::publish(vault).await {
+        log::warn!("Failed to publish Merkle root: {}", e);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::MasterKey;
+    use crate::index::{ConflictMode, FileMetadata};
+    use tempfile::TempDir;
+
+    fn meta(path: &str) -> FileMetadata {
+        FileMetadata { logical_path: path.to_string(), encrypted_size: 10 }
+    }
+
+    #[test]
+    fn journal_replays_remote_ops_and_flags_concurrent_edits() {
+        let dir = TempDir::new().unwrap();
+        let mut a = SqlCipherIndex::open(dir.path().join("a.db"), &[4u8; 32]).unwrap();
+        let mut b = SqlCipherIndex::open(dir.path().join("b.db"), &[4u8; 32]).unwrap();
+
+        a.upsert("f1".to_string(), meta("/notes.txt")).unwrap();
+        a.upsert("f2".to_string(), meta("/photo.jpg")).unwrap();
+        a.link_blob(&"f2".to_string(), &"f0".to_string(), "/ancienne.jpg").unwrap();
+        a.move_to_trash(&"f1".to_string(), &meta("/notes.txt")).unwrap();
+        let ops = a.journal_ops_after(0, SEGMENT_OPS).unwrap();
+        assert_eq!(ops.len(), 3);
+        assert_eq!(ops[1].entry.as_ref().unwrap().blob_link, Some(("f0".to_string(), "/ancienne.jpg".to_string())));
+        assert_eq!(ops[2].entry, None);
+        assert!(ops.iter().all(|op| op.root.is_some()));
+
+        let applied = b.apply_journal_ops("a", &ops, 0, ConflictMode::Normal).unwrap();
+        assert_eq!((applied.applied, applied.conflicts.len()), (3, 0));
+        assert_eq!(b.computed_merkle_root().unwrap(), a.computed_merkle_root().unwrap());
+        assert_eq!(b.blob_link(&"f2".to_string()).unwrap().unwrap().0, "f0");
+        // Ce qui a été rejoué n'est pas renvoyé
+        assert!(b.journal_ops_after(0, SEGMENT_OPS).unwrap().is_empty());
+
+        // Renommé des deux côtés sans que l'un voie l'autre : la version locale reste
+        b.upsert("f2".to_string(), meta("/Vacances/photo.jpg")).unwrap();
+        a.upsert("f2".to_string(), meta("/Photos/photo.jpg")).unwrap();
+        let remote = a.journal_ops_after(3, SEGMENT_OPS).unwrap();
+        let applied = b.apply_journal_ops("a", &remote, 0, ConflictMode::Normal).unwrap();
+        assert_eq!(applied.applied, 0);
+        assert_eq!(applied.conflicts[0].file_id, "f2");
+        assert_eq!(b.get(&"f2".to_string()).unwrap().unwrap().logical_path, "/Vacances/photo.jpg");
+
+        // Une fois la modification locale vue par l'auteur, la sienne s'applique
+        let seen = b.journal_last_seq().unwrap();
+        assert_eq!(b.apply_journal_ops("a", &remote, seen, ConflictMode::Normal).unwrap().applied, 1);
+        assert_eq!(b.get(&"f2".to_string()).unwrap().unwrap().logical_path, "/Photos/photo.jpg");
+    }
+
+    #[test]
+    fn conservative_mode_keeps_both_sides_of_a_conflict() {
+        let dir = TempDir::new().unwrap();
+        let mut a = SqlCipherIndex::open(dir.path().join("a.db"), &[4u8; 32]).unwrap();
+        let mut b = SqlCipherIndex::open(dir.path().join("b.db"), &[4u8; 32]).unwrap();
+
+        // Même chemin créé des deux côtés pendant que l'autre appareil travaille
+        b.upsert("local".to_string(), meta("/notes.txt")).unwrap();
+        a.upsert("remote".to_string(), meta("/notes.txt")).unwrap();
+        let ops = a.journal_ops_after(0, SEGMENT_OPS).unwrap();
+
+        let normal = b.apply_journal_ops("a", &ops, 0, ConflictMode::Normal).unwrap();
+        assert_eq!((normal.conflicts.len(), normal.kept.len()), (1, 0));
+        assert!(b.get(&"remote".to_string()).unwrap().is_none());
+
+        let conservative = b.apply_journal_ops("a", &ops, 0, ConflictMode::Conservative).unwrap();
+        assert_eq!(conservative.kept, vec![("remote".to_string(), "/notes (2).txt".to_string())]);
+        assert_eq!(b.get(&"local".to_string()).unwrap().unwrap().logical_path, "/notes.txt");
+        // Le contenu de l'autre appareil reste lié à son chemin d'origine (AAD)
+        assert_eq!(b.bound_path(&"remote".to_string()).unwrap().as_deref(), Some("/notes.txt"));
+        assert!(b.verify_integrity().unwrap());
+    }
+
+    #[test]
+    fn segments_are_bound_to_their_key() {
+        let master_key = MasterKey::from_vec(vec![9u8; 32]);
+        let key = SegmentKey::new("abcd", 0, 2);
+        assert_eq!(SegmentKey::parse(&key.key), Some(key.clone()));
+        let segment = Segment { seen: BTreeMap::from([("ef01".to_string(), 7)]), root: String::new(), ops: Vec::new() };
+
+        let sealed = SEGMENTS.seal(&master_key, &key.key, &segment).unwrap();
+        assert_eq!(SEGMENTS.open::<Segment>(&master_key, &key.key, &sealed).unwrap().seen["ef01"], 7);
+        let other = SegmentKey::new("abcd", 2, 4);
+        assert!(matches!(SEGMENTS.open::<Segment>(&master_key, &other.key, &sealed), Err(SealError::Invalid("decrypt"))));
+    }
+}
@@ -0,0 +1,173 @@
+//! Racines Merkle publiées par chaque appareil, pour repérer tôt deux index qui ont divergé.
+//!
+//! Chaque appareil publie sous [`ROOTS_PREFIX`] sa dernière racine, signée (HMAC sous une
+//! clé dérivée de la MasterKey), avec sa frontière : jusqu'où il a incorporé le journal de
+//! chaque appareil, le sien compris. Deux appareils à la même frontière doivent avoir la même
+//! racine ; sinon leurs index ont divergé sans que le journal l'explique.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+
+use super::{device_id, load_state, shared_root, unix_now, JournalError};
+use crate::crypto::sealed;
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::vault::Vault;
+
+/// Préfixe des racines publiées dans le bucket (sous le préfixe réservé).
+pub const ROOTS_PREFIX: &str = ".aether/roots/";
+
+const ROOT_KEY_INFO: &[u8] = b"aether-drive:root-signing-key:v1";
+
+/// Racine publiée par un appareil.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublishedRoot {
+    pub device: String,
+    /// Racine Merkle de son index (hex).
+    pub root: String,
+    /// Dernière opération incorporée du journal de chaque appareil.
+    pub frontier: BTreeMap<String, i64>,
+    pub published_at: i64,
+    /// HMAC-SHA256 (hex) des champs précédents.
+    pub mac: String,
+}
+
+/// Appareil dont l'index diffère du nôtre à frontière égale.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PeerDivergence {
+    pub device: String,
+    pub local_root: String,
+    pub peer_root: String,
+    pub published_at: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RootCheck {
+    /// Autres appareils dont la racine a été lue.
+    pub peers: usize,
+    pub diverged: Vec<PeerDivergence>,
+    /// Racines dont la signature ne vérifie pas (modifiées, ou d'un autre coffre).
+    pub untrusted: Vec<String>,
+}
+
+fn mac(key: &[u8; 32], root: &PublishedRoot) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    // Champs signés, sérialisés dans un ordre fixe (la frontière est triée)
+    let signed = serde_json::to_vec(&(&root.device, &root.root, &root.frontier, root.published_at)).unwrap_or_default();
+    mac.update(&signed);
+    mac
+}
+
+/// Racine et frontière actuelles de l'index local, non signées.
+fn current(index: &mut SqlCipherIndex) -> Result<PublishedRoot, JournalError> {
+    let device = device_id(index)?;
+    let mut frontier = load_state(index)?.applied;
+    frontier.insert(device.clone(), index.journal_last_seq()?);
+    Ok(PublishedRoot {
+        device,
+        root: shared_root(index)?,
+        frontier,
+        published_at: unix_now(),
+        mac: String::new(),
+    })
+}
+
+fn verify(key: &[u8; 32], root: &PublishedRoot) -> bool {
+    hex::decode(&root.mac).is_ok_and(|tag| mac(key, root).verify_slice(&tag).is_ok())
+}
+
+/// Publie la racine locale signée, en remplaçant la précédente de cet appareil.
+pub async fn publish(vault: &Vault) -> Result<PublishedRoot, JournalError> {
+    let key = sealed::subkey(vault.master_key(), ROOT_KEY_INFO)?;
+    let mut root = current(&mut vault.open_index()?)?;
+    root.mac = hex::encode(mac(&key, &root).finalize().into_bytes());
+    let object_key = format!("{}{}.json", ROOTS_PREFIX, root.device);
+    let body = serde_json::to_vec(&root).map_err(|_| JournalError::Invalid("encode"))?;
+    vault.remote().upload_file(&object_key, &body).await?;
+    log::info!("Merkle root published: device={}, root={}", root.device, root.root);
+    Ok(root)
+}
+
+/// Compare la racine locale à celles publiées par les autres appareils.
+pub async fn check_peers(vault: &Vault) -> Result<RootCheck, JournalError> {
+    let key = sealed::subkey(vault.master_key(), ROOT_KEY_INFO)?;
+    let local = current(&mut vault.open_index()?)?;
+    let mut check = RootCheck::default();
+    for object in vault.remote().list_prefix(ROOTS_PREFIX).await? {
+        let data = vault.remote().download_file(&object.key).await?;
+        let peer = match serde_json::from_slice::<PublishedRoot>(&data) {
+            Ok(peer) if verify(&key, &peer) => peer,
+            _ => {
+                log::warn!("Ignoring untrusted Merkle root {}", object.key);
+                check.untrusted.push(object.key);
+                continue;
+            }
+        };
+        if peer.device == local.device {
+            continue;
+        }
+        check.peers += 1;
+        if let Some(divergence) = compare(&local, &peer) {
+            log::warn!("Index diverged from device {}: local={}, peer={}", peer.device, local.root, peer.root);
+            check.diverged.push(divergence);
+        }
+    }
+    Ok(check)
+}
+
+/// Divergence que le journal n'explique pas : même frontière, racines différentes.
+fn compare(local: &PublishedRoot, peer: &PublishedRoot) -> Option<PeerDivergence> {
+    // Un appareil absent d'une frontière n'y a rien apporté
+    let known = |frontier: &BTreeMap<String, i64>| -> BTreeMap<String, i64> {
+        frontier.iter().filter(|(_, seq)| **seq > 0).map(|(device, seq)| (device.clone(), *seq)).collect()
+    };
+    (known(&local.frontier) == known(&peer.frontier) && local.root != peer.root).then(|| PeerDivergence {
+        device: peer.device.clone(),
+        local_root: local.root.clone(),
+        peer_root: peer.root.clone(),
+        published_at: peer.published_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::MasterKey;
+
+    fn root(device: &str, root: &str, frontier: &[(&str, i64)]) -> PublishedRoot {
+        PublishedRoot {
+            device: device.to_string(),
+            root: root.to_string(),
+            frontier: frontier.iter().map(|(device, seq)| (device.to_string(), *seq)).collect(),
+            published_at: 1_700_000_000,
+            mac: String::new(),
+        }
+    }
+
+    #[test]
+    fn only_unexplained_divergence_is_reported() {
+        let local = root("a", "11", &[("a", 4), ("b", 7), ("c", 0)]);
+        // Le pair n'a pas encore rejoué nos dernières opérations : écart attendu
+        assert_eq!(compare(&local, &root("b", "22", &[("a", 3), ("b", 7)])), None);
+        // Même frontière, même racine
+        assert_eq!(compare(&local, &root("b", "11", &[("a", 4), ("b", 7)])), None);
+        // Même frontière, racines différentes
+        let diverged = compare(&local, &root("b", "22", &[("a", 4), ("b", 7)])).unwrap();
+        assert_eq!((diverged.device.as_str(), diverged.peer_root.as_str()), ("b", "22"));
+    }
+
+    #[test]
+    fn signatures_cover_every_field() {
+        let key = sealed::subkey(&MasterKey::from_vec(vec![3u8; 32]), ROOT_KEY_INFO).unwrap();
+        let mut signed = root("a", "11", &[("a", 4)]);
+        signed.mac = hex::encode(mac(&key, &signed).finalize().into_bytes());
+        assert!(verify(&key, &signed));
+
+        let mut forged = signed.clone();
+        forged.frontier.insert("a".to_string(), 5);
+        assert!(!verify(&key, &forged));
+        let other = sealed::subkey(&MasterKey::from_vec(vec![4u8; 32]), ROOT_KEY_INFO).unwrap();
+        assert!(!verify(&other, &signed));
+    }
+}
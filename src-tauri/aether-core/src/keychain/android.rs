@@ -0,0 +1,260 @@
+//! Magasin de secrets Android pour `keyring` : chaque secret est chiffré en AES-GCM par une
+//! clé non exportable de l'Android Keystore, puis rangé dans un fichier de l'application.
+//!
+//! Le fichier seul ne livre rien : la clé ne quitte jamais le Keystore (matériel sécurisé
+//! quand l'appareil en dispose) et disparaît avec la désinstallation.
+
+use std::any::Any;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use jni::objects::{JByteArray, JObject, JValue};
+use jni::{JNIEnv, JavaVM};
+use keyring::credential::{Credential, CredentialApi, CredentialBuilder, CredentialBuilderApi};
+use sha2::{Digest, Sha256};
+
+use super::KeychainError;
+use crate::export::write_atomic;
+
+/// Alias de la clé AES du Keystore qui chiffre tous les secrets de l'application.
+const KEY_ALIAS: &str = "aether-drive-keychain";
+const KEYSTORE_PROVIDER: &str = "AndroidKeyStore";
+const TRANSFORMATION: &str = "AES/GCM/NoPadding";
+const SECRETS_DIR: &str = "keystore";
+const TAG_BITS: i32 = 128;
+const ENCRYPT_MODE: i32 = 1;
+const DECRYPT_MODE: i32 = 2;
+/// `KeyProperties.PURPOSE_ENCRYPT | KeyProperties.PURPOSE_DECRYPT`.
+const PURPOSE_ENCRYPT_DECRYPT: i32 = 3;
+const SPEC_BUILDER: &str = "android/security/keystore/KeyGenParameterSpec$Builder";
+
+/// Remplace le magasin par défaut de `keyring` (factice et volatil sur Android).
+pub(super) fn install(data_dir: &Path) -> Result<(), KeychainError> {
+    let dir = data_dir.join(SECRETS_DIR);
+    fs::create_dir_all(&dir).map_err(|e| KeychainError::Backend(e.to_string()))?;
+    keyring::set_default_credential_builder(Box::new(KeystoreCredentialBuilder { dir }));
+    log::info!("Android Keystore credential store installed");
+    Ok(())
+}
+
+struct KeystoreCredentialBuilder {
+    dir: PathBuf,
+}
+
+impl CredentialBuilderApi for KeystoreCredentialBuilder {
+    fn build(&self, _target: Option<&str>, service: &str, user: &str) -> keyring::Result<Box<Credential>> {
+        let label = format!("{}:{}", service, user);
+        let file_name = format!("{}.bin", hex::encode(Sha256::digest(label.as_bytes())));
+        Ok(Box::new(KeystoreCredential {
+            path: self.dir.join(file_name),
+            aad: label.into_bytes(),
+        }))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Secret rangé dans `path` ; `aad` (service et compte) lie le chiffré à son entrée.
+///
+/// Format : [Longueur IV(1)][IV][Ciphertext + tag]
+#[derive(Debug)]
+struct KeystoreCredential {
+    path: PathBuf,
+    aad: Vec<u8>,
+}
+
+impl CredentialApi for KeystoreCredential {
+    fn set_password(&self, password: &str) -> keyring::Result<()> {
+        self.set_secret(password.as_bytes())
+    }
+
+    fn set_secret(&self, secret: &[u8]) -> keyring::Result<()> {
+        let (iv, ciphertext) = with_env(|env| encrypt(env, secret, &self.aad))?;
+        let iv_len = u8::try_from(iv.len()).map_err(|_| platform_failure("unexpected IV length"))?;
+        let mut sealed = Vec::with_capacity(1 + iv.len() + ciphertext.len());
+        sealed.push(iv_len);
+        sealed.extend_from_slice(&iv);
+        sealed.extend_from_slice(&ciphertext);
+        write_atomic(&self.path, &sealed).map_err(platform_failure)
+    }
+
+    fn get_password(&self) -> keyring::Result<String> {
+        let secret = self.get_secret()?;
+        String::from_utf8(secret).map_err(|e| keyring::Error::BadEncoding(e.into_bytes()))
+    }
+
+    fn get_secret(&self) -> keyring::Result<Vec<u8>> {
+        let sealed = match fs::read(&self.path) {
+            Ok(sealed) => sealed,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(keyring::Error::NoEntry),
+            Err(e) => return Err(platform_failure(e)),
+        };
+        let iv_len = usize::from(*sealed.first().ok_or_else(|| platform_failure("empty secret file"))?);
+        if sealed.len() <= 1 + iv_len {
+            return Err(platform_failure("truncated secret file"));
+        }
+        let (iv, ciphertext) = sealed[1..].split_at(iv_len);
+        with_env(|env| decrypt(env, iv, ciphertext, &self.aad))
+    }
+
+    fn delete_credential(&self) -> keyring::Result<()> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(keyring::Error::NoEntry),
+            Err(e) => Err(platform_failure(e)),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn platform_failure(e: impl ToString) -> keyring::Error {
+    keyring::Error::PlatformFailure(e.to_string().into())
+}
+
+/// Exécute `f` dans la JVM de l'application, dans un cadre de références locales dédié ;
+/// une exception Java est effacée et remontée comme erreur.
+fn with_env<T>(f: impl FnOnce(&mut JNIEnv) -> jni::errors::Result<T>) -> keyring::Result<T> {
+    let context = ndk_context::android_context();
+    // SAFETY : le pointeur de JavaVM fourni par ndk-context reste valide toute la vie du processus
+    let vm = unsafe { JavaVM::from_raw(context.vm().cast()) }.map_err(platform_failure)?;
+    let mut env = vm.attach_current_thread().map_err(platform_failure)?;
+    let result = env.with_local_frame(16, |env| f(env));
+    if env.exception_check().unwrap_or(false) {
+        let _ = env.exception_describe();
+        let _ = env.exception_clear();
+    }
+    result.map_err(platform_failure)
+}
+
+/// Clé AES-256 de l'application dans l'Android Keystore, créée au premier usage.
+fn wrapping_key<'local>(env: &mut JNIEnv<'local>) -> jni::errors::Result<JObject<'local>> {
+    let provider = env.new_string(KEYSTORE_PROVIDER)?;
+    let alias = env.new_string(KEY_ALIAS)?;
+    let store = env
+        .call_static_method(
+            "java/security/KeyStore",
+            "getInstance",
+            "(Ljava/lang/String;)Ljava/security/KeyStore;",
+            &[JValue::from(&provider)],
+        )?
+        .l()?;
+    env.call_method(
+        &store,
+        "load",
+        "(Ljava/security/KeyStore$LoadStoreParameter;)V",
+        &[JValue::Object(&JObject::null())],
+    )?;
+    let key = env
+        .call_method(
+            &store,
+            "getKey",
+            "(Ljava/lang/String;[C)Ljava/security/Key;",
+            &[JValue::from(&alias), JValue::Object(&JObject::null())],
+        )?
+        .l()?;
+    if !key.is_null() {
+        return Ok(key);
+    }
+
+    let builder = env.new_object(
+        SPEC_BUILDER,
+        "(Ljava/lang/String;I)V",
+        &[JValue::from(&alias), JValue::Int(PURPOSE_ENCRYPT_DECRYPT)],
+    )?;
+    let builder_sig = format!("([Ljava/lang/String;)L{};", SPEC_BUILDER);
+    let block_modes = string_array(env, "GCM")?;
+    env.call_method(&builder, "setBlockModes", &builder_sig, &[JValue::from(&block_modes)])?;
+    let paddings = string_array(env, "NoPadding")?;
+    env.call_method(&builder, "setEncryptionPaddings", &builder_sig, &[JValue::from(&paddings)])?;
+    env.call_method(
+        &builder,
+        "setKeySize",
+        &format!("(I)L{};", SPEC_BUILDER),
+        &[JValue::Int(256)],
+    )?;
+    let spec = env
+        .call_method(&builder, "build", "()Landroid/security/keystore/KeyGenParameterSpec;", &[])?
+        .l()?;
+
+    let algorithm = env.new_string("AES")?;
+    let generator = env
+        .call_static_method(
+            "javax/crypto/KeyGenerator",
+            "getInstance",
+            "(Ljava/lang/String;Ljava/lang/String;)Ljavax/crypto/KeyGenerator;",
+            &[JValue::from(&algorithm), JValue::from(&provider)],
+        )?
+        .l()?;
+    env.call_method(
+        &generator,
+        "init",
+        "(Ljava/security/spec/AlgorithmParameterSpec;)V",
+        &[JValue::from(&spec)],
+    )?;
+    log::info!("Android Keystore key created");
+    env.call_method(&generator, "generateKey", "()Ljavax/crypto/SecretKey;", &[])?
+        .l()
+}
+
+fn string_array<'local>(env: &mut JNIEnv<'local>, value: &str) -> jni::errors::Result<JObject<'local>> {
+    let value = env.new_string(value)?;
+    let array = env.new_object_array(1, "java/lang/String", &value)?;
+    Ok(JObject::from(array))
+}
+
+fn cipher<'local>(env: &mut JNIEnv<'local>) -> jni::errors::Result<JObject<'local>> {
+    let transformation = env.new_string(TRANSFORMATION)?;
+    env.call_static_method(
+        "javax/crypto/Cipher",
+        "getInstance",
+        "(Ljava/lang/String;)Ljavax/crypto/Cipher;",
+        &[JValue::from(&transformation)],
+    )?
+    .l()
+}
+
+/// Chiffre `plaintext` ; le Keystore impose un IV aléatoire, renvoyé avec le chiffré.
+fn encrypt(env: &mut JNIEnv, plaintext: &[u8], aad: &[u8]) -> jni::errors::Result<(Vec<u8>, Vec<u8>)> {
+    let key = wrapping_key(env)?;
+    let cipher = cipher(env)?;
+    env.call_method(
+        &cipher,
+        "init",
+        "(ILjava/security/Key;)V",
+        &[JValue::Int(ENCRYPT_MODE), JValue::from(&key)],
+    )?;
+    let iv = JByteArray::from(env.call_method(&cipher, "getIV", "()[B", &[])?.l()?);
+    let ciphertext = finish(env, &cipher, plaintext, aad)?;
+    Ok((env.convert_byte_array(&iv)?, ciphertext))
+}
+
+fn decrypt(env: &mut JNIEnv, iv: &[u8], ciphertext: &[u8], aad: &[u8]) -> jni::errors::Result<Vec<u8>> {
+    let key = wrapping_key(env)?;
+    let cipher = cipher(env)?;
+    let iv = env.byte_array_from_slice(iv)?;
+    let spec = env.new_object(
+        "javax/crypto/spec/GCMParameterSpec",
+        "(I[B)V",
+        &[JValue::Int(TAG_BITS), JValue::from(&iv)],
+    )?;
+    env.call_method(
+        &cipher,
+        "init",
+        "(ILjava/security/Key;Ljava/security/spec/AlgorithmParameterSpec;)V",
+        &[JValue::Int(DECRYPT_MODE), JValue::from(&key), JValue::from(&spec)],
+    )?;
+    finish(env, &cipher, ciphertext, aad)
+}
+
+fn finish(env: &mut JNIEnv, cipher: &JObject, input: &[u8], aad: &[u8]) -> jni::errors::Result<Vec<u8>> {
+    let aad = env.byte_array_from_slice(aad)?;
+    env.call_method(cipher, "updateAAD", "([B)V", &[JValue::from(&aad)])?;
+    let input = env.byte_array_from_slice(input)?;
+    let output = JByteArray::from(env.call_method(cipher, "doFinal", "([B)[B", &[JValue::from(&input)])?.l()?);
+    env.convert_byte_array(&output)
+}
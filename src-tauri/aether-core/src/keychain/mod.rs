@@ -0,0 +1,324 @@
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+use crate::crypto::{sealed, CryptoError, Kek, MasterKey};
+use crate::delegation::Delegation;
+use crate::storj::StorjConfig;
+
+#[cfg(target_os = "android")]
+mod android;
+
+/// Nom du service sous lequel les secrets sont rangés dans le trousseau de l'OS.
+const KEYCHAIN_SERVICE: &str = "aether-drive";
+const STORJ_CREDENTIALS_ACCOUNT: &str = "storj-credentials";
+const CRASH_LOG_KEY_ACCOUNT: &str = "crash-log-key";
+const LOG_STORE_KEY_ACCOUNT: &str = "log-store-key";
+const DEVICE_KEK_ACCOUNT: &str = "device-kek";
+const KEYCHAIN_KEY_INFO: &[u8] = b"aether-drive:keychain-key:v1";
+const STORJ_CREDENTIALS_AAD: &[u8] = b"aether-drive:keychain:storj:v1";
+const NONCE_LEN: usize = 24;
+
+/// Erreurs du module Keychain.
+#[derive(Debug)]
+pub enum KeychainError {
+    Backend(String),
+    Serialization(String),
+    InvalidFormat,
+    Crypto(CryptoError),
+}
+
+impl fmt::Display for KeychainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeychainError::Backend(msg) => write!(f, "OS keychain error: {}", msg),
+            KeychainError::Serialization(msg) => write!(f, "Serialization error: {}", msg),
+            KeychainError::InvalidFormat => write!(f, "Invalid keychain payload"),
+            KeychainError::Crypto(e) => write!(f, "Crypto error: {}", e),
+        }
+    }
+}
+
+impl From<CryptoError> for KeychainError {
+    fn from(e: CryptoError) -> Self {
+        KeychainError::Crypto(e)
+    }
+}
+
+impl From<keyring::Error> for KeychainError {
+    fn from(e: keyring::Error) -> Self {
+        KeychainError::Backend(e.to_string())
+    }
+}
+
+impl std::error::Error for KeychainError {}
+
+/// Identifiants Storj (S3) conservés dans le trousseau de l'OS.
+///
+/// Des identifiants délégués ([`crate::delegation`]) expirent et portent de quoi les
+/// renouveler ; des clés racines n'ont ni l'un ni l'autre.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StorjCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub endpoint: String,
+    pub bucket_name: String,
+    /// Expiration (timestamp Unix, secondes) des identifiants délégués.
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+    #[serde(default)]
+    pub delegation: Option<Delegation>,
+}
+
+impl StorjCredentials {
+    pub fn into_config(self) -> StorjConfig {
+        StorjConfig::new(
+            self.access_key_id,
+            self.secret_access_key,
+            self.endpoint,
+            self.bucket_name,
+        )
+    }
+}
+
+impl fmt::Debug for StorjCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StorjCredentials")
+            .field("access_key_id", &"<redacted>")
+            .field("secret_access_key", &"<redacted>")
+            .field("endpoint", &self.endpoint)
+            .field("bucket_name", &self.bucket_name)
+            .field("expires_at", &self.expires_at)
+            .field("delegated", &self.delegation.is_some())
+            .finish()
+    }
+}
+
+/// Scelle les identifiants avec une clé dérivée de la MasterKey.
+///
+/// Le trousseau de l'OS ne contient donc qu'un blob opaque : sans coffre
+/// déverrouillé, les identifiants restent inexploitables.
+///
+/// Format : [Nonce(24)][Ciphertext(N)]
+pub fn seal_credentials(
+    master_key: &MasterKey,
+    credentials: &StorjCredentials,
+) -> Result<Vec<u8>, KeychainError> {
+    let plaintext = Zeroizing::new(
+        serde_json::to_vec(credentials)
+            .map_err(|e| KeychainError::Serialization(e.to_string()))?,
+    );
+    let cipher = build_cipher(master_key)?;
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = cipher
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext.as_slice(),
+                aad: STORJ_CREDENTIALS_AAD,
+            },
+        )
+        .map_err(CryptoError::from)?;
+
+    let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Ouvre un blob produit par `seal_credentials`.
+pub fn open_credentials(
+    master_key: &MasterKey,
+    sealed: &[u8],
+) -> Result<StorjCredentials, KeychainError> {
+    if sealed.len() <= NONCE_LEN {
+        return Err(KeychainError::InvalidFormat);
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let cipher = build_cipher(master_key)?;
+    let plaintext = Zeroizing::new(
+        cipher
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: STORJ_CREDENTIALS_AAD,
+                },
+            )
+            .map_err(CryptoError::from)?,
+    );
+    serde_json::from_slice(&plaintext).map_err(|e| KeychainError::Serialization(e.to_string()))
+}
+
+fn build_cipher(master_key: &MasterKey) -> Result<XChaCha20Poly1305, KeychainError> {
+    let key = sealed::subkey(master_key, KEYCHAIN_KEY_INFO)?;
+    Ok(XChaCha20Poly1305::new(Key::from_slice(key.as_ref())))
+}
+
+/// Branche le magasin de secrets propre à la plateforme, avant tout accès au trousseau.
+///
+/// Android n'a pas de trousseau accessible depuis Rust : les secrets y sont chiffrés par
+/// une clé de l'Android Keystore et rangés sous `data_dir`. Ailleurs, le trousseau natif
+/// est déjà utilisé et rien n'est à faire.
+pub fn install_platform_store(data_dir: &Path) -> Result<(), KeychainError> {
+    #[cfg(target_os = "android")]
+    {
+        android::install(data_dir)
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        let _ = data_dir;
+        Ok(())
+    }
+}
+
+/// Accès au trousseau de l'OS (Keychain macOS et iOS, Credential Manager Windows, keyutils
+/// Linux, Keystore Android).
+pub struct KeychainStore {
+    entry: keyring::Entry,
+}
+
+impl KeychainStore {
+    /// Entrée du trousseau dédiée aux identifiants Storj.
+    pub fn storj() -> Result<Self, KeychainError> {
+        let entry = keyring::Entry::new(KEYCHAIN_SERVICE, STORJ_CREDENTIALS_ACCOUNT)?;
+        Ok(Self { entry })
+    }
+
+    /// Entrée des identifiants du stockage d'un espace de travail ; l'espace par défaut
+    /// garde l'entrée historique de [`KeychainStore::storj`].
+    pub fn storj_for_workspace(workspace_id: &str) -> Result<Self, KeychainError> {
+        if workspace_id == crate::workspace::DEFAULT_WORKSPACE {
+            return Self::storj();
+        }
+        let account = format!("{}:{}", STORJ_CREDENTIALS_ACCOUNT, workspace_id);
+        let entry = keyring::Entry::new(KEYCHAIN_SERVICE, &account)?;
+        Ok(Self { entry })
+    }
+
+    /// Entrée du trousseau contenant la clé du journal de plantages (propre à l'appareil).
+    pub fn crash_log() -> Result<Self, KeychainError> {
+        let entry = keyring::Entry::new(KEYCHAIN_SERVICE, CRASH_LOG_KEY_ACCOUNT)?;
+        Ok(Self { entry })
+    }
+
+    /// Entrée du trousseau contenant la clé du journal applicatif (propre à l'appareil).
+    pub fn log_store() -> Result<Self, KeychainError> {
+        let entry = keyring::Entry::new(KEYCHAIN_SERVICE, LOG_STORE_KEY_ACCOUNT)?;
+        Ok(Self { entry })
+    }
+
+    /// Entrée de la KEK confiée à l'appareil : le déverrouillage se passe alors d'Argon2.
+    pub fn device_kek() -> Result<Self, KeychainError> {
+        let entry = keyring::Entry::new(KEYCHAIN_SERVICE, DEVICE_KEK_ACCOUNT)?;
+        Ok(Self { entry })
+    }
+
+    /// Lit la clé brute de 32 octets rangée dans l'entrée, ou en crée une.
+    pub fn load_or_create_key(&self) -> Result<[u8; 32], KeychainError> {
+        match self.entry.get_secret() {
+            Ok(secret) => secret.try_into().map_err(|_| KeychainError::InvalidFormat),
+            Err(keyring::Error::NoEntry) => {
+                let mut key = [0u8; 32];
+                OsRng.fill_bytes(&mut key);
+                self.entry.set_secret(&key)?;
+                Ok(key)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save_kek(&self, kek: &Kek) -> Result<(), KeychainError> {
+        self.entry.set_secret(kek.as_bytes())?;
+        Ok(())
+    }
+
+    /// Retourne `None` si aucune KEK n'a été confiée à l'appareil.
+    pub fn load_kek(&self) -> Result<Option<Kek>, KeychainError> {
+        match self.entry.get_secret() {
+            Ok(secret) => Ok(Some(Kek::from_bytes(&Zeroizing::new(secret)))),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(
+        &self,
+        master_key: &MasterKey,
+        credentials: &StorjCredentials,
+    ) -> Result<(), KeychainError> {
+        let sealed = seal_credentials(master_key, credentials)?;
+        self.entry.set_secret(&sealed)?;
+        Ok(())
+    }
+
+    /// Retourne `None` si aucun identifiant n'a encore été enregistré.
+    pub fn load(&self, master_key: &MasterKey) -> Result<Option<StorjCredentials>, KeychainError> {
+        match self.entry.get_secret() {
+            Ok(sealed) => open_credentials(master_key, &sealed).map(Some),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Supprime l'entrée du trousseau (idempotent).
+    pub fn delete(&self) -> Result<(), KeychainError> {
+        match self.entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::CryptoCore;
+
+    fn sample_credentials() -> StorjCredentials {
+        StorjCredentials {
+            access_key_id: "test-access-key".to_string(),
+            secret_access_key: "test-secret-key".to_string(),
+            endpoint: "https://gateway.storjshare.io".to_string(),
+            bucket_name: "test-bucket".to_string(),
+            expires_at: None,
+            delegation: None,
+        }
+    }
+
+    #[test]
+    fn seal_open_roundtrip() {
+        let master_key = CryptoCore::default().generate_master_key();
+        let sealed = seal_credentials(&master_key, &sample_credentials()).unwrap();
+
+        let opened = open_credentials(&master_key, &sealed).unwrap();
+        assert_eq!(opened.access_key_id, "test-access-key");
+        assert_eq!(opened.secret_access_key, "test-secret-key");
+        assert_eq!(opened.bucket_name, "test-bucket");
+    }
+
+    #[test]
+    fn open_with_wrong_master_key_fails() {
+        let core = CryptoCore::default();
+        let sealed = seal_credentials(&core.generate_master_key(), &sample_credentials()).unwrap();
+
+        let result = open_credentials(&core.generate_master_key(), &sealed);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn sealed_payload_does_not_leak_secret() {
+        let master_key = CryptoCore::default().generate_master_key();
+        let sealed = seal_credentials(&master_key, &sample_credentials()).unwrap();
+
+        let needle = b"test-secret-key";
+        assert!(!sealed.windows(needle.len()).any(|w| w == needle));
+    }
+}
@@ -0,0 +1,57 @@
+//! Cœur d'Aether Drive, indépendant de Tauri.
+//!
+//! Regroupe la hiérarchie de clés, l'index local chiffré (SQLCipher), le format de
+//! fichier Aether et le client Storj. L'application desktop, une future CLI ou des
+//! bindings mobiles pilotent tous exactement le même code via [`vault::Vault`].
+
+pub mod activity;
+pub mod backup;
+pub mod bundle;
+pub mod cache;
+pub mod clock;
+pub mod clone;
+pub mod crash;
+pub mod crypto;
+pub mod diagnostics;
+pub mod downloads;
+pub mod dry_run;
+pub mod daemon;
+pub mod delegation;
+pub mod export;
+pub mod guest;
+pub mod health;
+pub mod hooks;
+pub mod index;
+pub mod integrity;
+pub mod journal;
+pub mod jobs;
+pub mod keychain;
+pub mod locks;
+pub mod logstore;
+pub mod mime;
+pub mod paging;
+pub mod photos;
+pub mod policies;
+pub mod preflight;
+pub mod quarantine;
+pub mod quota;
+pub mod reauth;
+pub mod recovery;
+pub mod remote_keys;
+pub mod rules;
+pub mod search;
+pub mod sessions;
+pub mod setup;
+pub mod share;
+pub mod snapshot;
+pub mod staging;
+pub mod storage;
+pub mod storj;
+pub mod summaries;
+pub mod tombstones;
+pub mod transfers;
+pub mod vault;
+#[cfg(feature = "test-vectors")]
+pub mod vectors;
+pub mod webdav;
+pub mod workspace;
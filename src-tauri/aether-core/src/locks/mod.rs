@@ -0,0 +1,277 @@
+//! Verrous consultatifs sur les fichiers partagés entre les appareils d'un coffre.
+//!
+//! Un verrou indique qui modifie un document (appareil, nom affiché) et jusqu'à quand. Il
+//! est publié sous [`LOCKS_PREFIX`] dans un petit objet chiffré sous une clé dérivée de la
+//! MasterKey ; la clé de l'objet est un HMAC du chemin logique, qui n'apparaît donc pas en
+//! clair dans le bucket. Rien n'est bloqué : un envoi sur un document verrouillé par un autre
+//! appareil est seulement signalé ([`conflicting`]). Le bucket n'offrant pas d'écriture
+//! conditionnelle, deux prises simultanées se départagent à la relecture : le dernier écrit
+//! l'emporte et l'autre appareil reçoit [`LockError::Held`].
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fmt;
+
+use crate::clock::unix_now;
+use crate::crypto::sealed::{self, SealError, SealedObjects};
+use crate::crypto::{CryptoError, MasterKey};
+use crate::journal;
+use crate::storj::StorjError;
+use crate::vault::{Vault, VaultError};
+
+/// Préfixe des verrous dans le bucket (sous le préfixe réservé).
+pub const LOCKS_PREFIX: &str = ".aether/locks/";
+/// Durée d'un verrou pris sans durée explicite.
+pub const DEFAULT_TTL_SECS: i64 = 8 * 60 * 60;
+pub const MIN_TTL_SECS: i64 = 60;
+pub const MAX_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+
+const MAX_HOLDER_CHARS: usize = 64;
+const LOCK_KEY_INFO: &[u8] = b"aether-drive:lock-key:v1";
+const LOCK_NAME_INFO: &[u8] = b"aether-drive:lock-name-key:v1";
+const LOCK_AAD: &[u8] = b"aether-drive:lock:v1:";
+/// Verrous liés à leur clé : ils ne peuvent pas être présentés pour un autre document.
+const LOCKS: SealedObjects = SealedObjects::new(LOCK_KEY_INFO, LOCK_AAD);
+
+#[derive(Debug)]
+pub enum LockError {
+    /// Demande refusée (`reason` : code stable).
+    Invalid(&'static str),
+    /// Le document est verrouillé par un autre appareil.
+    Held(FileLock),
+    Crypto(CryptoError),
+    Vault(VaultError),
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockError::Invalid(reason) => write!(f, "Invalid lock request: {}", reason),
+            LockError::Held(lock) => write!(f, "File locked by device {} until {}", lock.device, lock.expires_at),
+            LockError::Crypto(e) => write!(f, "{}", e),
+            LockError::Vault(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<CryptoError> for LockError {
+    fn from(e: CryptoError) -> Self {
+        LockError::Crypto(e)
+    }
+}
+
+impl From<SealError> for LockError {
+    fn from(e: SealError) -> Self {
+        match e {
+            SealError::Invalid(reason) => LockError::Invalid(reason),
+            SealError::Crypto(e) => LockError::Crypto(e),
+        }
+    }
+}
+
+impl From<VaultError> for LockError {
+    fn from(e: VaultError) -> Self {
+        LockError::Vault(e)
+    }
+}
+
+impl From<StorjError> for LockError {
+    fn from(e: StorjError) -> Self {
+        LockError::Vault(e.into())
+    }
+}
+
+impl From<rusqlite::Error> for LockError {
+    fn from(e: rusqlite::Error) -> Self {
+        LockError::Vault(e.into())
+    }
+}
+
+impl std::error::Error for LockError {}
+
+/// Contenu chiffré d'un verrou.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LockRecord {
+    logical_path: String,
+    /// Identifiant de l'appareil dans le journal de synchronisation.
+    device: String,
+    holder: String,
+    acquired_at: i64,
+    expires_at: i64,
+}
+
+/// Verrou actif sur un document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileLock {
+    pub logical_path: String,
+    pub device: String,
+    /// Nom affiché de qui a pris le verrou.
+    pub holder: String,
+    pub acquired_at: i64,
+    pub expires_at: i64,
+    /// Pris par cet appareil.
+    pub mine: bool,
+}
+
+impl FileLock {
+    fn from_record(record: LockRecord, device: &str) -> Self {
+        Self {
+            mine: record.device == device,
+            logical_path: record.logical_path,
+            device: record.device,
+            holder: record.holder,
+            acquired_at: record.acquired_at,
+            expires_at: record.expires_at,
+        }
+    }
+}
+
+/// Clé de l'objet du verrou de `logical_path`.
+fn object_key(master_key: &MasterKey, logical_path: &str) -> Result<String, CryptoError> {
+    let key = sealed::subkey(master_key, LOCK_NAME_INFO)?;
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key.as_ref()).expect("HMAC accepts any key length");
+    mac.update(logical_path.as_bytes());
+    Ok(format!("{}{}.lock", LOCKS_PREFIX, hex::encode(mac.finalize().into_bytes())))
+}
+
+/// Verrou publié sous `key`, s'il existe, est lisible et n'a pas expiré.
+async fn read(vault: &Vault, key: &str, now: i64) -> Result<Option<LockRecord>, LockError> {
+    let sealed = match vault.remote().download_file(key).await {
+        Ok(sealed) => sealed,
+        Err(StorjError::NotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    match LOCKS.open::<LockRecord>(vault.master_key(), key, &sealed) {
+        Ok(record) if record.expires_at > now => Ok(Some(record)),
+        Ok(_) => Ok(None),
+        Err(_) => {
+            log::warn!("Ignoring unreadable lock {}", key);
+            Ok(None)
+        }
+    }
+}
+
+fn device(vault: &Vault) -> Result<String, LockError> {
+    Ok(journal::device_id(&mut vault.open_index()?)?)
+}
+
+/// Prend (ou prolonge) le verrou de `logical_path` pour `ttl_secs` secondes
+/// ([`DEFAULT_TTL_SECS`] par défaut). [`LockError::Held`] si un autre appareil le détient.
+pub async fn acquire(vault: &Vault, logical_path: &str, holder: &str, ttl_secs: Option<i64>) -> Result<FileLock, LockError> {
+    let holder = holder.trim();
+    if !logical_path.starts_with('/') || logical_path.ends_with('/') {
+        return Err(LockError::Invalid("path"));
+    }
+    if holder.is_empty() || holder.chars().count() > MAX_HOLDER_CHARS || holder.chars().any(char::is_control) {
+        return Err(LockError::Invalid("holder"));
+    }
+    let ttl = ttl_secs.unwrap_or(DEFAULT_TTL_SECS);
+    if !(MIN_TTL_SECS..=MAX_TTL_SECS).contains(&ttl) {
+        return Err(LockError::Invalid("duration"));
+    }
+    let device = device(vault)?;
+    let key = object_key(vault.master_key(), logical_path)?;
+    let now = unix_now();
+    let existing = read(vault, &key, now).await?;
+    if let Some(record) = existing.as_ref().filter(|record| record.device != device) {
+        return Err(LockError::Held(FileLock::from_record(record.clone(), &device)));
+    }
+
+    let record = LockRecord {
+        logical_path: logical_path.to_string(),
+        device: device.clone(),
+        holder: holder.to_string(),
+        // Une prolongation garde l'heure de la prise initiale
+        acquired_at: existing.map_or(now, |record| record.acquired_at),
+        expires_at: now + ttl,
+    };
+    vault.remote().upload_file(&key, &LOCKS.seal(vault.master_key(), &key, &record)?).await?;
+    // Un autre appareil a pu écrire entre la lecture et l'envoi : la relecture tranche
+    match read(vault, &key, now).await? {
+        Some(winner) if winner.device != device => Err(LockError::Held(FileLock::from_record(winner, &device))),
+        _ => {
+            log::info!("Lock acquired: {} until {}", logical_path, record.expires_at);
+            Ok(FileLock::from_record(record, &device))
+        }
+    }
+}
+
+/// Libère le verrou de `logical_path`. Celui d'un autre appareil n'est libéré qu'avec
+/// `force` (appareil perdu, oubli). Retourne `false` s'il n'y avait pas de verrou actif.
+pub async fn release(vault: &Vault, logical_path: &str, force: bool) -> Result<bool, LockError> {
+    let device = device(vault)?;
+    let key = object_key(vault.master_key(), logical_path)?;
+    let Some(record) = read(vault, &key, unix_now()).await? else {
+        return Ok(false);
+    };
+    if record.device != device && !force {
+        return Err(LockError::Held(FileLock::from_record(record, &device)));
+    }
+    vault.remote().delete_file(&key).await?;
+    log::info!("Lock released: {} (forced={})", logical_path, record.device != device);
+    Ok(true)
+}
+
+/// Verrous actifs, par chemin logique. Les verrous expirés sont retirés du bucket au passage.
+pub async fn list(vault: &Vault) -> Result<Vec<FileLock>, LockError> {
+    let device = device(vault)?;
+    let now = unix_now();
+    let mut locks = Vec::new();
+    for object in vault.remote().list_prefix(LOCKS_PREFIX).await? {
+        let sealed = match vault.remote().download_file(&object.key).await {
+            Ok(sealed) => sealed,
+            // Libéré entre la liste et la lecture
+            Err(StorjError::NotFound) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        match LOCKS.open::<LockRecord>(vault.master_key(), &object.key, &sealed) {
+            Ok(record) if record.expires_at > now => locks.push(FileLock::from_record(record, &device)),
+            Ok(_) => {
+                if let Err(e) = vault.remote().delete_file(&object.key).await {
+                    log::warn!("Failed to remove expired lock {}: {}", object.key, e);
+                }
+            }
+            Err(_) => log::warn!("Ignoring unreadable lock {}", object.key),
+        }
+    }
+    locks.sort_by(|a, b| a.logical_path.cmp(&b.logical_path));
+    Ok(locks)
+}
+
+/// Verrou actif d'un autre appareil sur `logical_path` : à signaler avant d'écraser le document.
+pub async fn conflicting(vault: &Vault, logical_path: &str) -> Result<Option<FileLock>, LockError> {
+    let device = device(vault)?;
+    let key = object_key(vault.master_key(), logical_path)?;
+    Ok(read(vault, &key, unix_now())
+        .await?
+        .filter(|record| record.device != device)
+        .map(|record| FileLock::from_record(record, &device)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locks_are_bound_to_their_path_and_vault() {
+        let master_key = MasterKey::from_vec(vec![3u8; 32]);
+        let key = object_key(&master_key, "/docs/plan.odt").unwrap();
+        assert!(key.starts_with(LOCKS_PREFIX) && !key.contains("plan"));
+        assert_ne!(key, object_key(&master_key, "/docs/plan2.odt").unwrap());
+
+        let record = LockRecord {
+            logical_path: "/docs/plan.odt".to_string(),
+            device: "a".to_string(),
+            holder: "Alice".to_string(),
+            acquired_at: 1,
+            expires_at: 2,
+        };
+        let sealed = LOCKS.seal(&master_key, &key, &record).unwrap();
+        assert_eq!(LOCKS.open::<LockRecord>(&master_key, &key, &sealed).unwrap().holder, "Alice");
+        // Présenté sous la clé d'un autre document, ou lu avec une autre MasterKey
+        let other_key = object_key(&master_key, "/docs/other.odt").unwrap();
+        assert!(LOCKS.open::<LockRecord>(&master_key, &other_key, &sealed).is_err());
+        assert!(LOCKS.open::<LockRecord>(&MasterKey::from_vec(vec![4u8; 32]), &key, &sealed).is_err());
+    }
+}
@@ -0,0 +1,350 @@
+//! Journal applicatif chiffré, consultable dans l'application et exportable.
+//!
+//! Chaque message admis par le logger y est ajouté, chiffré avec une clé propre à l'appareil
+//! rangée dans le trousseau : comme le journal de plantages, il reste lisible coffre
+//! verrouillé. Au-delà de [`SEGMENT_BYTES`], un nouveau segment est ouvert et les plus
+//! anciens au-delà de [`KEPT_SEGMENTS`] sont supprimés.
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::Zeroizing;
+
+use crate::crash;
+use crate::keychain::{KeychainError, KeychainStore};
+
+/// Dossier (dans le dossier de données) contenant les segments chiffrés.
+pub const LOG_DIR: &str = "logs";
+/// Taille à partir de laquelle un nouveau segment est ouvert.
+pub const SEGMENT_BYTES: u64 = 1024 * 1024;
+/// Segments conservés, le segment courant compris.
+pub const KEPT_SEGMENTS: usize = 8;
+/// Nombre de messages renvoyés par défaut par [`LogStore::query`].
+pub const QUERY_LIMIT: usize = 500;
+
+const SEGMENT_EXTENSION: &str = "seg";
+const LOG_AAD: &[u8] = b"aether-drive:log-store:v1";
+const NONCE_LEN: usize = 24;
+/// Un message plus long est tronqué avant chiffrement.
+const MAX_MESSAGE_LEN: usize = 8 * 1024;
+
+/// Gravité d'un message, de la plus grave à la plus détaillée.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogRecord {
+    /// Timestamp Unix en millisecondes.
+    pub timestamp_ms: i64,
+    pub level: LogLevel,
+    /// Module émetteur (`aether_core::vault`).
+    pub module: String,
+    pub message: String,
+}
+
+/// Filtre d'une consultation ; un champ absent ne filtre rien.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogQuery {
+    /// Préfixe de module (`aether_core` retient aussi `aether_core::vault`).
+    pub module: Option<String>,
+    /// Gravité la moins grave retenue (`warn` : erreurs et avertissements).
+    pub level: Option<LogLevel>,
+    pub since_ms: Option<i64>,
+    pub until_ms: Option<i64>,
+    /// Au plus, les plus récents ; [`QUERY_LIMIT`] par défaut.
+    pub limit: Option<usize>,
+}
+
+impl LogQuery {
+    fn matches(&self, record: &LogRecord) -> bool {
+        let module_matches = self.module.as_deref().map_or(true, |module| {
+            record.module == module
+                || record.module.strip_prefix(module).is_some_and(|rest| rest.starts_with("::"))
+        });
+        module_matches
+            && self.level.map_or(true, |level| record.level <= level)
+            && self.since_ms.map_or(true, |since| record.timestamp_ms >= since)
+            && self.until_ms.map_or(true, |until| record.timestamp_ms <= until)
+    }
+}
+
+/// Paquet de diagnostic à joindre à un rapport de bug.
+#[derive(Debug, Serialize)]
+pub struct SupportBundle {
+    pub app_version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub exported_at_ms: i64,
+    /// Messages expurgés à nouveau, quel que soit le mode de journalisation à l'écriture.
+    pub records: Vec<LogRecord>,
+    /// Rapports de plantage (déjà expurgés).
+    pub crash_reports: Vec<String>,
+}
+
+/// Segment en cours d'écriture.
+struct Current {
+    file: File,
+    len: u64,
+}
+
+/// Journal chiffré en segments d'enregistrements `[longueur u32][nonce][chiffré]`.
+pub struct LogStore {
+    dir: PathBuf,
+    key: Zeroizing<[u8; 32]>,
+    current: Mutex<Option<Current>>,
+}
+
+impl LogStore {
+    pub fn new(dir: impl AsRef<Path>, key: [u8; 32]) -> io::Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+            key: Zeroizing::new(key),
+            current: Mutex::new(None),
+        })
+    }
+
+    /// Ouvre le journal avec la clé de l'appareil (créée au premier usage).
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, KeychainError> {
+        let key = KeychainStore::log_store()?.load_or_create_key()?;
+        Self::new(dir, key).map_err(|e| KeychainError::Backend(e.to_string()))
+    }
+
+    /// Chiffre et ajoute un message.
+    ///
+    /// Appelé depuis le logger : ne journalise jamais lui-même, l'appelant traite l'erreur.
+    pub fn append(&self, record: &LogRecord) -> io::Result<()> {
+        let mut record = record.clone();
+        if record.message.len() > MAX_MESSAGE_LEN {
+            let mut end = MAX_MESSAGE_LEN;
+            while !record.message.is_char_boundary(end) {
+                end -= 1;
+            }
+            record.message.truncate(end);
+        }
+        let plaintext = serde_json::to_vec(&record).map_err(io::Error::other)?;
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher()
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: &plaintext, aad: LOG_AAD })
+            .map_err(|_| io::Error::other("log record encryption failed"))?;
+        let mut frame = ((NONCE_LEN + ciphertext.len()) as u32).to_le_bytes().to_vec();
+        frame.extend_from_slice(&nonce);
+        frame.extend_from_slice(&ciphertext);
+
+        let mut current = self.current.lock().map_err(|_| io::Error::other("log store lock poisoned"))?;
+        if current.as_ref().map_or(true, |segment| segment.len >= SEGMENT_BYTES) {
+            let resuming = current.is_none();
+            *current = Some(self.next_segment(resuming)?);
+        }
+        let segment = current.as_mut().expect("segment opened above");
+        segment.file.write_all(&frame)?;
+        segment.len += frame.len() as u64;
+        Ok(())
+    }
+
+    /// Au démarrage (`resuming`), reprend le dernier segment s'il a de la place ; sinon en
+    /// ouvre un nouveau et supprime les plus anciens.
+    fn next_segment(&self, resuming: bool) -> io::Result<Current> {
+        let mut segments = self.segments()?;
+        let reusable = resuming && segments.last().is_some_and(|last| fs::metadata(last).is_ok_and(|meta| meta.len() < SEGMENT_BYTES));
+        let path = match segments.last() {
+            Some(last) if reusable => last.clone(),
+            _ => {
+                // Nom horodaté, toujours après le dernier segment pour que le tri reste
+                // chronologique
+                let last = segments
+                    .last()
+                    .and_then(|path| path.file_stem()?.to_str()?.parse::<i64>().ok())
+                    .unwrap_or(0);
+                let path = self.segment_path(unix_millis().max(last + 1));
+                segments.push(path.clone());
+                path
+            }
+        };
+        for expired in segments.iter().take(segments.len().saturating_sub(KEPT_SEGMENTS)) {
+            let _ = fs::remove_file(expired);
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let len = file.metadata()?.len();
+        Ok(Current { file, len })
+    }
+
+    fn segment_path(&self, millis: i64) -> PathBuf {
+        self.dir.join(format!("{:020}.{}", millis, SEGMENT_EXTENSION))
+    }
+
+    /// Segments du plus ancien au plus récent.
+    fn segments(&self) -> io::Result<Vec<PathBuf>> {
+        let mut segments: Vec<PathBuf> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == SEGMENT_EXTENSION))
+            .collect();
+        segments.sort();
+        Ok(segments)
+    }
+
+    /// Messages d'un segment, dans l'ordre d'écriture. Une fin tronquée (arrêt pendant une
+    /// écriture) et les enregistrements illisibles sont ignorés.
+    fn read_segment(&self, path: &Path) -> io::Result<Vec<LogRecord>> {
+        let data = fs::read(path)?;
+        let mut records = Vec::new();
+        let mut rest = data.as_slice();
+        while rest.len() >= 4 {
+            let len = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+            let Some(frame) = rest.get(4..4 + len) else {
+                break;
+            };
+            rest = &rest[4 + len..];
+            if frame.len() <= NONCE_LEN {
+                continue;
+            }
+            let (nonce, ciphertext) = frame.split_at(NONCE_LEN);
+            let record = self
+                .cipher()
+                .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: LOG_AAD })
+                .ok()
+                .and_then(|plaintext| serde_json::from_slice::<LogRecord>(&plaintext).ok());
+            if let Some(record) = record {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// Messages retenus par `query`, du plus récent au plus ancien.
+    pub fn query(&self, query: &LogQuery) -> io::Result<Vec<LogRecord>> {
+        let limit = query.limit.unwrap_or(QUERY_LIMIT);
+        let mut found = Vec::new();
+        for segment in self.segments()?.iter().rev() {
+            for record in self.read_segment(segment)?.into_iter().rev() {
+                if found.len() >= limit {
+                    return Ok(found);
+                }
+                if query.matches(&record) {
+                    found.push(record);
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    /// Écrit un paquet de diagnostic JSON : messages retenus par `query`, expurgés à
+    /// nouveau, et rapports de plantage. Retourne le nombre de messages exportés.
+    pub fn export_bundle(&self, destination: &Path, query: &LogQuery, crash_reports: Vec<String>) -> io::Result<usize> {
+        let records: Vec<LogRecord> = self
+            .query(query)?
+            .into_iter()
+            .map(|record| LogRecord {
+                message: crash::redact(&record.message),
+                ..record
+            })
+            .collect();
+        let count = records.len();
+        let bundle = SupportBundle {
+            app_version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            exported_at_ms: unix_millis(),
+            records,
+            crash_reports,
+        };
+        fs::write(destination, serde_json::to_vec_pretty(&bundle).map_err(io::Error::other)?)?;
+        Ok(count)
+    }
+
+    /// Supprime tous les segments.
+    pub fn clear(&self) -> io::Result<()> {
+        let mut current = self.current.lock().map_err(|_| io::Error::other("log store lock poisoned"))?;
+        *current = None;
+        for path in self.segments()? {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(Key::from_slice(self.key.as_ref()))
+    }
+}
+
+pub fn unix_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn record(timestamp_ms: i64, level: LogLevel, module: &str, message: &str) -> LogRecord {
+        LogRecord {
+            timestamp_ms,
+            level,
+            module: module.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn records_are_encrypted_and_filtered() {
+        let dir = tempdir().unwrap();
+        let store = LogStore::new(dir.path(), [9u8; 32]).unwrap();
+        store.append(&record(1_000, LogLevel::Info, "app_lib", "Vault unlocked")).unwrap();
+        store.append(&record(2_000, LogLevel::Warn, "aether_core::vault", "Failed to cache blob")).unwrap();
+        store.append(&record(3_000, LogLevel::Debug, "aether_core_extra", "noise")).unwrap();
+
+        let raw: Vec<u8> = store.segments().unwrap().iter().flat_map(|path| fs::read(path).unwrap()).collect();
+        assert!(!String::from_utf8_lossy(&raw).contains("Vault unlocked"));
+
+        let all = store.query(&LogQuery::default()).unwrap();
+        assert_eq!(all.iter().map(|r| r.timestamp_ms).collect::<Vec<_>>(), vec![3_000, 2_000, 1_000]);
+        let query = LogQuery { module: Some("aether_core".to_string()), ..Default::default() };
+        assert_eq!(store.query(&query).unwrap().len(), 1);
+        let query = LogQuery { level: Some(LogLevel::Info), since_ms: Some(1_500), ..Default::default() };
+        assert_eq!(store.query(&query).unwrap()[0].message, "Failed to cache blob");
+
+        // Une autre clé ne relit rien
+        let other = LogStore::new(dir.path(), [8u8; 32]).unwrap();
+        assert!(other.query(&LogQuery::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn segments_rotate_and_exports_are_redacted() {
+        let dir = tempdir().unwrap();
+        let store = LogStore::new(dir.path(), [9u8; 32]).unwrap();
+        let filler = "x".repeat(MAX_MESSAGE_LEN);
+        let per_segment = (SEGMENT_BYTES as usize / MAX_MESSAGE_LEN) + 1;
+        for i in 0..per_segment * (KEPT_SEGMENTS + 2) {
+            store.append(&record(i as i64, LogLevel::Info, "app_lib", &filler)).unwrap();
+        }
+        assert_eq!(store.segments().unwrap().len(), KEPT_SEGMENTS);
+
+        store.append(&record(i64::MAX, LogLevel::Error, "app_lib", "Failed to open /home/alice/taxes.pdf")).unwrap();
+        let destination = dir.path().join("bundle.json");
+        let query = LogQuery { level: Some(LogLevel::Error), ..Default::default() };
+        assert_eq!(store.export_bundle(&destination, &query, vec!["boom".to_string()]).unwrap(), 1);
+        let exported = fs::read_to_string(destination).unwrap();
+        assert!(!exported.contains("alice") && exported.contains("<path>") && exported.contains("boom"));
+    }
+}
@@ -0,0 +1,300 @@
+//! Type MIME des fichiers, détecté au chiffrement d'après les premiers octets du contenu.
+//!
+//! Les signatures priment ; l'extension ne sert qu'à distinguer les formats bâtis sur un même
+//! conteneur (documents Office dans un ZIP, WebM et Matroska…) et, pour les fichiers indexés
+//! avant la détection, à deviner un type.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::index::{sqlcipher::SqlCipherIndex, FileId, FileMetadata};
+
+/// Octets de début de contenu examinés.
+pub const SNIFF_LEN: usize = 512;
+
+const OCTET_STREAM: &str = "application/octet-stream";
+
+/// Catégorie d'une vue « intelligente » (Photos, Documents…).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Images,
+    Documents,
+    Video,
+    Audio,
+    Other,
+}
+
+impl Category {
+    pub fn of(mime_type: &str) -> Self {
+        let (kind, subtype) = mime_type.split_once('/').unwrap_or((mime_type, ""));
+        match kind {
+            "image" => Category::Images,
+            "video" => Category::Video,
+            "audio" => Category::Audio,
+            "text" => Category::Documents,
+            "application"
+                if subtype == "pdf"
+                    || subtype == "rtf"
+                    || subtype == "msword"
+                    || subtype == "epub+zip"
+                    || subtype.starts_with("vnd.openxmlformats-officedocument.")
+                    || subtype.starts_with("vnd.oasis.opendocument.")
+                    || subtype.starts_with("vnd.ms-") =>
+            {
+                Category::Documents
+            }
+            _ => Category::Other,
+        }
+    }
+}
+
+/// Type MIME d'un contenu dont `head` sont les premiers octets (au moins [`SNIFF_LEN`] si
+/// le fichier est assez long).
+pub fn detect(head: &[u8], logical_path: &str) -> &'static str {
+    let extension = extension(logical_path);
+    let ext = extension.as_deref().unwrap_or("");
+    let at = |offset: usize, signature: &[u8]| head.get(offset..offset + signature.len()) == Some(signature);
+
+    if at(0, b"\xFF\xD8\xFF") {
+        return "image/jpeg";
+    }
+    if at(0, b"\x89PNG\r\n\x1A\n") {
+        return "image/png";
+    }
+    if at(0, b"GIF87a") || at(0, b"GIF89a") {
+        return "image/gif";
+    }
+    if at(0, b"RIFF") {
+        if at(8, b"WEBP") {
+            return "image/webp";
+        }
+        if at(8, b"WAVE") {
+            return "audio/wav";
+        }
+        if at(8, b"AVI ") {
+            return "video/x-msvideo";
+        }
+    }
+    if at(0, b"II*\0") || at(0, b"MM\0*") {
+        return "image/tiff";
+    }
+    if at(0, b"\0\0\x01\0") && ext == "ico" {
+        return "image/x-icon";
+    }
+    if at(0, b"BM") && ext == "bmp" {
+        return "image/bmp";
+    }
+    if at(4, b"ftyp") {
+        let brand = head.get(8..12).unwrap_or(b"");
+        return match brand {
+            b"heic" | b"heix" | b"mif1" | b"msf1" => "image/heic",
+            b"avif" => "image/avif",
+            b"M4A " | b"M4B " => "audio/mp4",
+            b"qt  " => "video/quicktime",
+            _ => "video/mp4",
+        };
+    }
+    if at(0, b"\x1A\x45\xDF\xA3") {
+        return if ext == "webm" { "video/webm" } else { "video/x-matroska" };
+    }
+    if at(0, b"ID3") || (head.len() > 1 && head[0] == 0xFF && head[1] & 0xE0 == 0xE0 && head[1] & 0x06 != 0) {
+        return "audio/mpeg";
+    }
+    if at(0, b"fLaC") {
+        return "audio/flac";
+    }
+    if at(0, b"OggS") {
+        return if ext == "ogv" { "video/ogg" } else { "audio/ogg" };
+    }
+    if at(0, b"%PDF-") {
+        return "application/pdf";
+    }
+    if at(0, b"{\\rtf") {
+        return "application/rtf";
+    }
+    if at(0, b"PK\x03\x04") {
+        return match ext {
+            "docx" | "xlsx" | "pptx" | "odt" | "ods" | "odp" | "epub" => from_extension(ext).unwrap_or(OCTET_STREAM),
+            _ => "application/zip",
+        };
+    }
+    // Conteneur OLE des anciens formats Office
+    if at(0, b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1") {
+        return match ext {
+            "doc" | "xls" | "ppt" => from_extension(ext).unwrap_or(OCTET_STREAM),
+            _ => OCTET_STREAM,
+        };
+    }
+    if at(0, b"7z\xBC\xAF\x27\x1C") {
+        return "application/x-7z-compressed";
+    }
+    if at(0, b"Rar!\x1A\x07") {
+        return "application/vnd.rar";
+    }
+    if at(0, b"\x1F\x8B") {
+        return "application/gzip";
+    }
+
+    // Sans signature : du texte (éventuellement précisé par l'extension) ou du binaire
+    if head.contains(&0) || std::str::from_utf8(utf8_prefix(head)).is_err() {
+        return OCTET_STREAM;
+    }
+    match from_extension(ext) {
+        Some(mime_type) if mime_type.starts_with("text/") || mime_type == "image/svg+xml" || mime_type == "application/json" => {
+            mime_type
+        }
+        _ => "text/plain",
+    }
+}
+
+/// Intérêt d'une compression DEFLATE pour un type : `Some(false)` pour un format déjà
+/// compressé (JPEG, vidéo, archives, documents Office en conteneur ZIP…), `Some(true)` pour
+/// le texte et les formats bruts ou peu denses, `None` quand le type ne dit rien (binaire
+/// inconnu).
+pub fn compressible(mime_type: &str) -> Option<bool> {
+    let (kind, subtype) = mime_type.split_once('/').unwrap_or((mime_type, ""));
+    match (kind, subtype) {
+        ("text", _) => Some(true),
+        ("image", "svg+xml" | "bmp" | "tiff" | "x-icon") | ("audio", "wav") => Some(true),
+        ("application", "json" | "rtf" | "msword" | "vnd.ms-excel" | "vnd.ms-powerpoint") => Some(true),
+        ("image" | "video" | "audio", _) => Some(false),
+        ("application", "zip" | "gzip" | "x-7z-compressed" | "vnd.rar" | "pdf" | "epub+zip") => Some(false),
+        ("application", _)
+            if subtype.starts_with("vnd.openxmlformats-officedocument.")
+                || subtype.starts_with("vnd.oasis.opendocument.") =>
+        {
+            Some(false)
+        }
+        _ => None,
+    }
+}
+
+/// Type deviné d'après la seule extension (entrées indexées avant la détection).
+pub fn guess_from_path(logical_path: &str) -> &'static str {
+    extension(logical_path).as_deref().and_then(from_extension).unwrap_or(OCTET_STREAM)
+}
+
+fn extension(logical_path: &str) -> Option<String> {
+    let name = logical_path.rsplit('/').next().unwrap_or(logical_path);
+    name.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase())
+}
+
+fn from_extension(ext: &str) -> Option<&'static str> {
+    Some(match ext {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "heic" | "heif" => "image/heic",
+        "avif" => "image/avif",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        "ico" => "image/x-icon",
+        "svg" => "image/svg+xml",
+        "mp4" | "m4v" => "video/mp4",
+        "mov" => "video/quicktime",
+        "webm" => "video/webm",
+        "mkv" => "video/x-matroska",
+        "avi" => "video/x-msvideo",
+        "ogv" => "video/ogg",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "ogg" | "oga" | "opus" => "audio/ogg",
+        "pdf" => "application/pdf",
+        "rtf" => "application/rtf",
+        "doc" => "application/msword",
+        "xls" => "application/vnd.ms-excel",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "odt" => "application/vnd.oasis.opendocument.text",
+        "ods" => "application/vnd.oasis.opendocument.spreadsheet",
+        "odp" => "application/vnd.oasis.opendocument.presentation",
+        "epub" => "application/epub+zip",
+        "txt" | "log" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        "7z" => "application/x-7z-compressed",
+        "rar" => "application/vnd.rar",
+        "gz" => "application/gzip",
+        _ => return None,
+    })
+}
+
+/// Préfixe sans le caractère UTF-8 éventuellement coupé par la fin de l'échantillon.
+fn utf8_prefix(head: &[u8]) -> &[u8] {
+    match std::str::from_utf8(head) {
+        Err(e) if e.error_len().is_none() => &head[..e.valid_up_to()],
+        _ => head,
+    }
+}
+
+/// Fichiers (hors dossiers) d'une catégorie, dans l'ordre des chemins, avec leur type.
+pub fn list_by_category(
+    index: &SqlCipherIndex,
+    category: Category,
+) -> rusqlite::Result<Vec<(FileId, FileMetadata, String)>> {
+    let mut types: HashMap<FileId, String> = index.mime_types()?;
+    Ok(index
+        .list_all()?
+        .into_iter()
+        .filter(|(_, meta)| !meta.logical_path.ends_with('/') && meta.encrypted_size > 0)
+        .filter_map(|(id, meta)| {
+            let mime_type = types.remove(&id).unwrap_or_else(|| guess_from_path(&meta.logical_path).to_string());
+            (Category::of(&mime_type) == category).then_some((id, meta, mime_type))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn content_wins_over_a_misleading_extension() {
+        assert_eq!(detect(b"\xFF\xD8\xFF\xE0\0\x10JFIF", "/photo.txt"), "image/jpeg");
+        assert_eq!(detect(b"\0\0\0\x18ftypheic", "/IMG_0001.HEIC"), "image/heic");
+        assert_eq!(detect(b"\0\0\0\x20ftypisom", "/clip"), "video/mp4");
+        assert_eq!(detect(b"PK\x03\x04\x14\0", "/Rapport.docx"), guess_from_path("/a.docx"));
+        assert_eq!(detect(b"PK\x03\x04\x14\0", "/photos.docx.zip"), "application/zip");
+        assert_eq!(detect("Compte rendu, réunion".as_bytes(), "/notes.md"), "text/markdown");
+        assert_eq!(detect(b"\x00\x01\x02binary", "/notes.md"), OCTET_STREAM);
+
+        assert_eq!(Category::of("image/heic"), Category::Images);
+        assert_eq!(Category::of(guess_from_path("/a.xlsx")), Category::Documents);
+        assert_eq!(Category::of("application/zip"), Category::Other);
+
+        assert_eq!(compressible(detect(b"\xFF\xD8\xFF\xE0\0\x10JFIF", "/photo.jpg")), Some(false));
+        assert_eq!(compressible(detect(b"PK\x03\x04\x14\0", "/Rapport.docx")), Some(false));
+        assert_eq!(compressible(guess_from_path("/ancien.doc")), Some(true));
+        assert_eq!(compressible(detect(b"id;nom\n1;Ada", "/export.csv")), Some(true));
+        assert_eq!(compressible(OCTET_STREAM), None);
+    }
+
+    #[test]
+    fn categories_use_stored_types_and_fall_back_to_extensions() {
+        let dir = TempDir::new().unwrap();
+        let mut index = SqlCipherIndex::open(dir.path().join("index.db"), &[4u8; 32]).unwrap();
+        for (id, path) in [("a", "/scan.bin"), ("b", "/Vacances/plage.jpg"), ("c", "/lettre.pdf"), ("d", "/Vacances/")] {
+            let size = if path.ends_with('/') { 0 } else { 10 };
+            index.upsert(id.to_string(), FileMetadata { logical_path: path.to_string(), encrypted_size: size }).unwrap();
+        }
+        // Le contenu de « scan.bin » est une image, ce que l'extension ne dit pas
+        index.set_mime_type(&"a".to_string(), "image/png").unwrap();
+
+        let images: Vec<FileId> = list_by_category(&index, Category::Images).unwrap().into_iter().map(|(id, _, _)| id).collect();
+        assert_eq!(images, ["b", "a"]);
+        let documents = list_by_category(&index, Category::Documents).unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].2, "application/pdf");
+        assert!(list_by_category(&index, Category::Other).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+//! Pagination des réponses IPC potentiellement volumineuses (listes, recherche, journal,
+//! corbeille).
+//!
+//! Une commande renvoie une [`Page`] bornée à la fois en nombre d'éléments et en taille JSON,
+//! avec un jeton de continuation opaque : la webview ne reçoit jamais une réponse de plusieurs
+//! centaines de mégaoctets, et redemande la suite tant que `next_cursor` est présent.
+//!
+//! Le jeton désigne une position dans la liste : un élément ajouté ou retiré entre deux pages
+//! peut décaler la suite, `total` permet de le détecter.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Éléments par page quand la requête n'en précise pas.
+pub const DEFAULT_PAGE_ITEMS: usize = 500;
+/// Éléments au plus par page, quelle que soit la requête.
+pub const MAX_PAGE_ITEMS: usize = 5_000;
+/// Taille JSON au plus d'une page (au moins un élément est toujours renvoyé).
+pub const MAX_PAGE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Préfixe des jetons : un jeton d'un autre format est refusé plutôt que mal interprété.
+const CURSOR_PREFIX: &str = "p1.";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PageError {
+    /// Jeton de continuation illisible ou au-delà de la fin de la liste.
+    InvalidCursor,
+}
+
+impl fmt::Display for PageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PageError::InvalidCursor => write!(f, "Invalid continuation cursor"),
+        }
+    }
+}
+
+impl std::error::Error for PageError {}
+
+/// Page demandée ; sans jeton, la première.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PageRequest {
+    /// `next_cursor` de la page précédente.
+    pub cursor: Option<String>,
+    /// Éléments souhaités, ramenés à [`MAX_PAGE_ITEMS`].
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Jeton de la page suivante, absent sur la dernière.
+    pub next_cursor: Option<String>,
+    /// Éléments de la liste complète.
+    pub total: usize,
+}
+
+/// Découpe dans `items` la page demandée par `request`.
+pub fn paginate<T: Serialize>(items: Vec<T>, request: &PageRequest) -> Result<Page<T>, PageError> {
+    let total = items.len();
+    let start = match &request.cursor {
+        Some(cursor) => decode_cursor(cursor).filter(|start| *start <= total).ok_or(PageError::InvalidCursor)?,
+        None => 0,
+    };
+    let limit = request.limit.unwrap_or(DEFAULT_PAGE_ITEMS).clamp(1, MAX_PAGE_ITEMS);
+
+    let mut page = Vec::new();
+    let mut bytes = 2;
+    for item in items.into_iter().skip(start).take(limit) {
+        // Sérialisation d'une structure de données ordinaire : ne peut pas échouer
+        let len = serde_json::to_vec(&item).map_or(0, |raw| raw.len()) + 1;
+        if !page.is_empty() && bytes + len > MAX_PAGE_BYTES {
+            break;
+        }
+        bytes += len;
+        page.push(item);
+    }
+    let end = start + page.len();
+    Ok(Page { next_cursor: (end < total).then(|| encode_cursor(end)), items: page, total })
+}
+
+fn encode_cursor(offset: usize) -> String {
+    format!("{}{}", CURSOR_PREFIX, offset)
+}
+
+fn decode_cursor(cursor: &str) -> Option<usize> {
+    cursor.strip_prefix(CURSOR_PREFIX)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_follow_the_cursor_and_stay_under_the_size_limit() {
+        let request = |cursor: Option<String>, limit| PageRequest { cursor, limit };
+        let first = paginate((0..12).collect(), &request(None, Some(5))).unwrap();
+        assert_eq!((first.items.clone(), first.total), ((0..5).collect(), 12));
+        let second = paginate((0..12).collect(), &request(first.next_cursor, Some(5))).unwrap();
+        let last = paginate((0..12).collect(), &request(second.next_cursor, Some(5))).unwrap();
+        assert_eq!(last.items, [10, 11]);
+        assert_eq!(last.next_cursor, None);
+
+        // Limite ramenée au maximum ; liste vide : une seule page, vide
+        assert_eq!(paginate((0..6_000).collect::<Vec<_>>(), &request(None, Some(100_000))).unwrap().items.len(), MAX_PAGE_ITEMS);
+        let empty = paginate(Vec::<u8>::new(), &PageRequest::default()).unwrap();
+        assert!(empty.items.is_empty() && empty.next_cursor.is_none());
+
+        // Gros éléments : la page s'arrête avant la taille maximale, sans sauter d'élément
+        let big = vec!["x".repeat(MAX_PAGE_BYTES / 3); 4];
+        let page = paginate(big.clone(), &PageRequest::default()).unwrap();
+        assert_eq!(page.items.len(), 2);
+        let rest = paginate(big, &request(page.next_cursor, None)).unwrap();
+        assert_eq!(rest.items.len(), 2);
+
+        for cursor in ["13", "p1.x", "p1.13"] {
+            assert_eq!(paginate((0..12).collect::<Vec<_>>(), &request(Some(cursor.to_string()), None)).unwrap_err(), PageError::InvalidCursor);
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Lecture de la date de prise de vue et des dimensions d'une image, sur le contenu en clair.
+//!
+//! Seuls les en-têtes sont lus : segments JPEG jusqu'au début des données compressées,
+//! chunks PNG jusqu'à `IDAT`, répertoires TIFF. Les formats HEIC/AVIF (boîtes ISO BMFF) ne
+//! sont pas décodés ; leurs photos apparaissent sans date.
+
+use crate::index::PhotoInfo;
+
+/// Octets de début de fichier lus : l'EXIF d'un JPEG tient dans son segment APP1 (64 Kio au
+/// plus), placé avant l'image.
+pub const HEAD_LEN: usize = 256 * 1024;
+
+const TAG_WIDTH: u16 = 0x0100;
+const TAG_HEIGHT: u16 = 0x0101;
+const TAG_DATE_TIME: u16 = 0x0132;
+const TAG_EXIF_IFD: u16 = 0x8769;
+const TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+const TAG_PIXEL_X: u16 = 0xA002;
+const TAG_PIXEL_Y: u16 = 0xA003;
+
+/// Métadonnées d'une image dont `head` sont les premiers octets ; `None` si le format n'est
+/// pas reconnu.
+pub fn read(head: &[u8]) -> Option<PhotoInfo> {
+    if head.starts_with(b"\xFF\xD8") {
+        return Some(jpeg(head));
+    }
+    if head.starts_with(b"\x89PNG\r\n\x1A\n") {
+        return Some(png(head));
+    }
+    if head.starts_with(b"II*\0") || head.starts_with(b"MM\0*") {
+        return Some(tiff(head));
+    }
+    None
+}
+
+fn jpeg(head: &[u8]) -> PhotoInfo {
+    let mut info = PhotoInfo::default();
+    let mut i = 2;
+    while i + 4 <= head.len() && head[i] == 0xFF {
+        let marker = head[i + 1];
+        // Remplissage entre segments
+        if marker == 0xFF {
+            i += 1;
+            continue;
+        }
+        let len = u16::from_be_bytes([head[i + 2], head[i + 3]]) as usize;
+        let Some(segment) = head.get(i + 4..i + 2 + len) else {
+            break;
+        };
+        match marker {
+            0xE1 if segment.starts_with(b"Exif\0\0") => {
+                let exif = tiff(&segment[6..]);
+                info.taken_at = info.taken_at.or(exif.taken_at);
+                info.width = info.width.or(exif.width);
+                info.height = info.height.or(exif.height);
+            }
+            // SOFn (hors DHT, JPG et DAC, qui partagent la plage) : dimensions réelles de l'image
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) && segment.len() >= 5 => {
+                info.height = Some(u16::from_be_bytes([segment[1], segment[2]]) as u32);
+                info.width = Some(u16::from_be_bytes([segment[3], segment[4]]) as u32);
+            }
+            // Début des données compressées
+            0xDA => break,
+            _ => {}
+        }
+        i += 2 + len;
+    }
+    info
+}
+
+fn png(head: &[u8]) -> PhotoInfo {
+    let mut info = PhotoInfo::default();
+    let mut i = 8;
+    while let Some(header) = head.get(i..i + 8) {
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let Some(data) = head.get(i + 8..(i + 8).saturating_add(len)) else {
+            break;
+        };
+        match &header[4..8] {
+            b"IHDR" if data.len() >= 8 => {
+                info.width = Some(u32::from_be_bytes([data[0], data[1], data[2], data[3]]));
+                info.height = Some(u32::from_be_bytes([data[4], data[5], data[6], data[7]]));
+            }
+            b"eXIf" => info.taken_at = tiff(data).taken_at,
+            b"IDAT" | b"IEND" => break,
+            _ => {}
+        }
+        // Données puis CRC
+        i += 12 + len;
+    }
+    info
+}
+
+/// Structure TIFF (fichier TIFF ou bloc EXIF) : répertoire principal puis sous-répertoire EXIF.
+fn tiff(data: &[u8]) -> PhotoInfo {
+    let mut info = PhotoInfo::default();
+    let Some(reader) = Tiff::new(data) else {
+        return info;
+    };
+    let Some(ifd0) = reader.u32_at(4) else {
+        return info;
+    };
+    let mut date_time = None;
+    let mut exif_ifd = None;
+    for (tag, entry) in reader.entries(ifd0 as usize) {
+        match tag {
+            TAG_WIDTH => info.width = reader.integer(entry),
+            TAG_HEIGHT => info.height = reader.integer(entry),
+            TAG_DATE_TIME => date_time = reader.ascii(entry),
+            TAG_EXIF_IFD => exif_ifd = reader.integer(entry),
+            _ => {}
+        }
+    }
+    if let Some(offset) = exif_ifd {
+        for (tag, entry) in reader.entries(offset as usize) {
+            match tag {
+                TAG_DATE_TIME_ORIGINAL => info.taken_at = reader.ascii(entry).as_deref().and_then(normalize_date),
+                TAG_PIXEL_X => info.width = reader.integer(entry).or(info.width),
+                TAG_PIXEL_Y => info.height = reader.integer(entry).or(info.height),
+                _ => {}
+            }
+        }
+    }
+    // À défaut de date de prise de vue, celle de la dernière écriture par l'appareil
+    info.taken_at = info.taken_at.or_else(|| date_time.as_deref().and_then(normalize_date));
+    info
+}
+
+/// `AAAA:MM:JJ HH:MM:SS` (EXIF) → `AAAA-MM-JJTHH:MM:SS` ; les dates nulles sont ignorées.
+fn normalize_date(raw: &str) -> Option<String> {
+    let raw = raw.trim_end_matches('\0').trim();
+    let bytes = raw.as_bytes();
+    if bytes.len() < 19 {
+        return None;
+    }
+    let digits = [0..4, 5..7, 8..10, 11..13, 14..16, 17..19];
+    if digits.iter().any(|range| !bytes[range.clone()].iter().all(u8::is_ascii_digit)) {
+        return None;
+    }
+    let month: u32 = raw[5..7].parse().ok()?;
+    let day: u32 = raw[8..10].parse().ok()?;
+    if &raw[0..4] == "0000" || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(format!("{}-{}-{}T{}:{}:{}", &raw[0..4], &raw[5..7], &raw[8..10], &raw[11..13], &raw[14..16], &raw[17..19]))
+}
+
+struct Tiff<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+/// Entrée de répertoire : type, nombre de valeurs et valeur (ou position de la valeur).
+#[derive(Clone, Copy)]
+struct Entry {
+    kind: u16,
+    count: u32,
+    value_at: usize,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        let little_endian = match data.get(0..2)? {
+            b"II" => true,
+            b"MM" => false,
+            _ => return None,
+        };
+        Some(Self { data, little_endian })
+    }
+
+    fn u16_at(&self, at: usize) -> Option<u16> {
+        let bytes = [*self.data.get(at)?, *self.data.get(at + 1)?];
+        Some(if self.little_endian { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+    }
+
+    fn u32_at(&self, at: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(at..at + 4)?.try_into().ok()?;
+        Some(if self.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+
+    fn entries(&self, ifd: usize) -> Vec<(u16, Entry)> {
+        let Some(count) = self.u16_at(ifd) else {
+            return Vec::new();
+        };
+        (0..count as usize)
+            .map_while(|n| {
+                let at = ifd + 2 + n * 12;
+                let entry = Entry { kind: self.u16_at(at + 2)?, count: self.u32_at(at + 4)?, value_at: at + 8 };
+                Some((self.u16_at(at)?, entry))
+            })
+            .collect()
+    }
+
+    /// Valeur SHORT ou LONG unique.
+    fn integer(&self, entry: Entry) -> Option<u32> {
+        match entry.kind {
+            3 => self.u16_at(entry.value_at).map(u32::from),
+            4 => self.u32_at(entry.value_at),
+            _ => None,
+        }
+    }
+
+    fn ascii(&self, entry: Entry) -> Option<String> {
+        if entry.kind != 2 {
+            return None;
+        }
+        let len = entry.count as usize;
+        // Jusqu'à 4 octets, la valeur est dans l'entrée ; au-delà, l'entrée donne sa position
+        let at = if len <= 4 { entry.value_at } else { self.u32_at(entry.value_at)? as usize };
+        let bytes = self.data.get(at..at.checked_add(len)?)?;
+        Some(String::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bloc EXIF (TIFF petit-boutiste) : IFD0 → sous-répertoire EXIF avec date et dimensions.
+    fn exif_block(date: &str) -> Vec<u8> {
+        let mut tiff = b"II*\0\x08\0\0\0".to_vec();
+        // IFD0 : une entrée (pointeur EXIF vers l'offset 26)
+        tiff.extend_from_slice(&1u16.to_le_bytes());
+        tiff.extend_from_slice(&TAG_EXIF_IFD.to_le_bytes());
+        tiff.extend_from_slice(&4u16.to_le_bytes());
+        tiff.extend_from_slice(&1u32.to_le_bytes());
+        tiff.extend_from_slice(&26u32.to_le_bytes());
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        // IFD EXIF : date (hors entrée, à l'offset 68) puis largeur et hauteur
+        tiff.extend_from_slice(&3u16.to_le_bytes());
+        for (tag, kind, count, value) in
+            [(TAG_DATE_TIME_ORIGINAL, 2u16, 20u32, 68u32), (TAG_PIXEL_X, 4, 1, 4032), (TAG_PIXEL_Y, 3, 1, 3024)]
+        {
+            tiff.extend_from_slice(&tag.to_le_bytes());
+            tiff.extend_from_slice(&kind.to_le_bytes());
+            tiff.extend_from_slice(&count.to_le_bytes());
+            tiff.extend_from_slice(&value.to_le_bytes());
+        }
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        assert_eq!(tiff.len(), 68);
+        tiff.extend_from_slice(date.as_bytes());
+        tiff.push(0);
+        tiff
+    }
+
+    #[test]
+    fn reads_capture_date_and_dimensions_from_jpeg_and_png_headers() {
+        let exif = exif_block("2023:07:14 18:02:11");
+        let mut jpeg = b"\xFF\xD8\xFF\xE1".to_vec();
+        jpeg.extend_from_slice(&((exif.len() + 8) as u16).to_be_bytes());
+        jpeg.extend_from_slice(b"Exif\0\0");
+        jpeg.extend_from_slice(&exif);
+        // SOF0 : 8 bits, 600 × 800
+        jpeg.extend_from_slice(b"\xFF\xC0\0\x0B\x08\x02\x58\x03\x20\x01\x01\x11\0");
+        jpeg.extend_from_slice(b"\xFF\xDA\0\x02");
+        let info = read(&jpeg).unwrap();
+        assert_eq!(info.taken_at.as_deref(), Some("2023-07-14T18:02:11"));
+        assert_eq!((info.width, info.height), (Some(800), Some(600)));
+
+        // Date nulle laissée par certains appareils
+        let info = read(&exif_block("0000:00:00 00:00:00")).unwrap();
+        assert_eq!(info.taken_at, None);
+        assert_eq!((info.width, info.height), (Some(4032), Some(3024)));
+
+        let mut png = b"\x89PNG\r\n\x1A\n\0\0\0\x0DIHDR".to_vec();
+        png.extend_from_slice(&1920u32.to_be_bytes());
+        png.extend_from_slice(&1080u32.to_be_bytes());
+        png.extend_from_slice(&[8, 6, 0, 0, 0, 0, 0, 0, 0]);
+        let info = read(&png).unwrap();
+        assert_eq!(info, PhotoInfo { taken_at: None, width: Some(1920), height: Some(1080) });
+
+        assert_eq!(read(b"GIF89a"), None);
+        // Un en-tête tronqué ne fait rien échouer
+        assert_eq!(read(&jpeg[..30]).unwrap().width, None);
+    }
+}
@@ -0,0 +1,113 @@
+//! Chronologie des photos : date de prise de vue et dimensions, lues sur le clair avant le
+//! chiffrement ([`exif`]) et conservées dans l'index (chiffré comme le reste de l'index).
+//!
+//! Les photos sont regroupées par mois de prise de vue (`AAAA-MM`) ; celles dont la date est
+//! inconnue forment le groupe [`UNDATED`].
+
+pub mod exif;
+
+use serde::Serialize;
+
+use crate::index::{sqlcipher::SqlCipherIndex, FileId, PhotoInfo};
+use crate::mime::{self, Category};
+
+/// Groupe des photos sans date de prise de vue.
+pub const UNDATED: &str = "undated";
+
+/// Photo d'un groupe de la chronologie.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TimelinePhoto {
+    pub file_id: FileId,
+    pub logical_path: String,
+    pub encrypted_size: u64,
+    #[serde(flatten)]
+    pub info: PhotoInfo,
+}
+
+/// Mois de la chronologie et nombre de photos prises ce mois-là.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MonthBucket {
+    pub month: String,
+    pub count: usize,
+}
+
+fn bucket_of(info: &PhotoInfo) -> &str {
+    info.taken_at.as_deref().and_then(|taken_at| taken_at.get(..7)).unwrap_or(UNDATED)
+}
+
+/// Photos du coffre (catégorie Images) avec leurs métadonnées, éventuellement vides si
+/// elles ont été envoyées avant la lecture des métadonnées.
+fn photos(index: &SqlCipherIndex) -> rusqlite::Result<Vec<TimelinePhoto>> {
+    let mut infos = index.photo_infos()?;
+    Ok(mime::list_by_category(index, Category::Images)?
+        .into_iter()
+        .map(|(file_id, meta, _)| TimelinePhoto {
+            info: infos.remove(&file_id).unwrap_or_default(),
+            file_id,
+            logical_path: meta.logical_path,
+            encrypted_size: meta.encrypted_size,
+        })
+        .collect())
+}
+
+/// Mois contenant au moins une photo, du plus récent au plus ancien, [`UNDATED`] en dernier.
+pub fn months(index: &SqlCipherIndex) -> rusqlite::Result<Vec<MonthBucket>> {
+    let mut counts = std::collections::BTreeMap::<String, usize>::new();
+    for photo in photos(index)? {
+        *counts.entry(bucket_of(&photo.info).to_string()).or_default() += 1;
+    }
+    let undated = counts.remove(UNDATED);
+    let mut buckets: Vec<MonthBucket> =
+        counts.into_iter().rev().map(|(month, count)| MonthBucket { month, count }).collect();
+    buckets.extend(undated.map(|count| MonthBucket { month: UNDATED.to_string(), count }));
+    Ok(buckets)
+}
+
+/// Photos d'un mois (`AAAA-MM` ou [`UNDATED`]), de la plus récente à la plus ancienne.
+pub fn timeline(index: &SqlCipherIndex, month_bucket: &str) -> rusqlite::Result<Vec<TimelinePhoto>> {
+    let mut photos: Vec<TimelinePhoto> =
+        photos(index)?.into_iter().filter(|photo| bucket_of(&photo.info) == month_bucket).collect();
+    photos.sort_by(|a, b| b.info.taken_at.cmp(&a.info.taken_at).then_with(|| a.logical_path.cmp(&b.logical_path)));
+    Ok(photos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::FileMetadata;
+    use tempfile::TempDir;
+
+    #[test]
+    fn photos_are_grouped_by_capture_month() {
+        let dir = TempDir::new().unwrap();
+        let mut index = SqlCipherIndex::open(dir.path().join("index.db"), &[6u8; 32]).unwrap();
+        let photos = [
+            ("a", "/Vacances/plage.jpg", Some("2023-07-14T18:02:11")),
+            ("b", "/Vacances/port.jpg", Some("2023-07-02T09:30:00")),
+            ("c", "/Noël.png", Some("2022-12-24T20:00:00")),
+            ("d", "/capture.png", None),
+        ];
+        for (id, path, taken_at) in photos {
+            index.upsert(id.to_string(), FileMetadata { logical_path: path.to_string(), encrypted_size: 10 }).unwrap();
+            let info = PhotoInfo { taken_at: taken_at.map(str::to_string), width: Some(800), height: Some(600) };
+            index.set_photo_info(&id.to_string(), &info).unwrap();
+        }
+        // Envoyée avant la lecture des métadonnées, et un document qui n'a rien à faire ici
+        index.upsert("e".to_string(), FileMetadata { logical_path: "/ancienne.jpg".to_string(), encrypted_size: 10 }).unwrap();
+        index.upsert("f".to_string(), FileMetadata { logical_path: "/lettre.pdf".to_string(), encrypted_size: 10 }).unwrap();
+
+        let buckets: Vec<(String, usize)> = months(&index).unwrap().into_iter().map(|b| (b.month, b.count)).collect();
+        assert_eq!(buckets, [("2023-07".to_string(), 2), ("2022-12".to_string(), 1), (UNDATED.to_string(), 2)]);
+
+        let july: Vec<FileId> = timeline(&index, "2023-07").unwrap().into_iter().map(|p| p.file_id).collect();
+        assert_eq!(july, ["a", "b"]);
+        assert_eq!(timeline(&index, UNDATED).unwrap().len(), 2);
+
+        // Une photo purgée quitte la chronologie avec ses métadonnées
+        let meta = FileMetadata { logical_path: "/Vacances/plage.jpg".to_string(), encrypted_size: 10 };
+        index.move_to_trash(&"a".to_string(), &meta).unwrap();
+        index.remove_from_trash(&"a".to_string()).unwrap();
+        assert!(!index.photo_infos().unwrap().contains_key("a"));
+        assert_eq!(timeline(&index, "2023-07").unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,173 @@
+//! Politiques par dossier, stockées dans l'index.
+//!
+//! Une politique s'applique au dossier et à tout ce qu'il contient ; un sous-dossier qui a
+//! sa propre politique la remplace entièrement (la plus proche l'emporte, sans fusion).
+//!
+//! - `compress` et `padding` : transformations du contenu avant chiffrement, appliquées à
+//!   l'envoi (voir [`crate::storage::ContentEncoding`]). La compression se décide d'abord
+//!   sur le type détecté du contenu ([`crate::mime::compressible`]) : le texte et les anciens
+//!   formats Office sont compressés d'office au-delà de quelques Kio, les photos, vidéos et
+//!   archives jamais ; `compress` tranche pour le reste ;
+//! - `pin_offline` : les fichiers restent dans le cache local, téléchargés d'avance et jamais
+//!   évincés (voir [`crate::vault::Vault::maintain_cache`]) ;
+//! - `exclude_from_sync` : les entrées ne passent pas par le journal de synchronisation ;
+//!   les autres appareils ne les voient pas (leurs objets restent chiffrés dans le bucket).
+//!
+//! Les politiques sont propres à l'appareil : elles ne voyagent pas avec le journal.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::index::FolderAppearance;
+use crate::mime;
+use crate::storage::{ContentEncoding, Padding};
+
+/// Taille à partir de laquelle un contenu compressible l'est sans politique : en deçà, le
+/// gain ne paie pas la fin du corps encadré.
+pub const AUTO_COMPRESS_MIN_LEN: u64 = 4096;
+
+/// Politique d'un dossier ; les champs absents d'une définition enregistrée valent `false`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FolderPolicy {
+    /// Compresse aussi les petits contenus compressibles et ceux dont le type ne dit pas
+    /// s'ils gagneraient à l'être.
+    pub compress: bool,
+    pub padding: Padding,
+    pub pin_offline: bool,
+    pub exclude_from_sync: bool,
+}
+
+impl FolderPolicy {
+    pub fn is_default(&self) -> bool {
+        *self == FolderPolicy::default()
+    }
+
+    /// Transformations à appliquer à l'envoi d'un contenu de type `mime_type` ; la décision
+    /// de compression est enregistrée dans la fin du corps encadré.
+    pub fn encoding(&self, mime_type: &str, content_len: u64) -> ContentEncoding {
+        let compress = match mime::compressible(mime_type) {
+            Some(true) => self.compress || content_len >= AUTO_COMPRESS_MIN_LEN,
+            Some(false) => false,
+            None => self.compress,
+        };
+        ContentEncoding {
+            compress,
+            padding: self.padding,
+        }
+    }
+}
+
+/// Politiques enregistrées, par clé de dossier ([`FolderAppearance::folder_key`]).
+#[derive(Debug, Clone, Default)]
+pub struct FolderPolicies {
+    by_folder: HashMap<String, FolderPolicy>,
+}
+
+impl FolderPolicies {
+    /// Politique applicable à un chemin logique : celle du dossier parent le plus proche qui
+    /// en a une. Un dossier (chemin terminé par `/`) relève de sa propre politique.
+    pub fn resolve(&self, logical_path: &str) -> FolderPolicy {
+        if self.by_folder.is_empty() {
+            return FolderPolicy::default();
+        }
+        let mut folder = if logical_path.ends_with('/') {
+            FolderAppearance::folder_key(logical_path)
+        } else {
+            FolderAppearance::folder_key(logical_path.rsplit_once('/').map_or("", |(parent, _)| parent))
+        };
+        loop {
+            if let Some(policy) = self.by_folder.get(&folder) {
+                return *policy;
+            }
+            match folder.rsplit_once('/') {
+                Some((parent, _)) if folder != "/" => folder = FolderAppearance::folder_key(parent),
+                _ => return FolderPolicy::default(),
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_folder.is_empty()
+    }
+}
+
+/// Toutes les politiques enregistrées ; une définition illisible est ignorée.
+pub fn load(index: &SqlCipherIndex) -> rusqlite::Result<FolderPolicies> {
+    let by_folder = index
+        .folder_policies()?
+        .into_iter()
+        .filter_map(|(folder, spec)| match serde_json::from_str(&spec) {
+            Ok(policy) => Some((folder, policy)),
+            Err(e) => {
+                log::warn!("Ignoring unreadable policy of folder {}: {}", folder, e);
+                None
+            }
+        })
+        .collect();
+    Ok(FolderPolicies { by_folder })
+}
+
+/// Politique applicable à un chemin logique (voir [`FolderPolicies::resolve`]).
+pub fn effective(index: &SqlCipherIndex, logical_path: &str) -> rusqlite::Result<FolderPolicy> {
+    Ok(load(index)?.resolve(logical_path))
+}
+
+/// Politique enregistrée sur le dossier lui-même (par défaut si aucune).
+pub fn get(index: &SqlCipherIndex, folder_path: &str) -> rusqlite::Result<FolderPolicy> {
+    Ok(load(index)?
+        .by_folder
+        .get(&FolderAppearance::folder_key(folder_path))
+        .copied()
+        .unwrap_or_default())
+}
+
+/// Enregistre la politique d'un dossier ; une politique par défaut efface l'entrée.
+pub fn set(index: &mut SqlCipherIndex, folder_path: &str, policy: &FolderPolicy) -> rusqlite::Result<()> {
+    if policy.is_default() {
+        return index.set_folder_policy(folder_path, None);
+    }
+    // La sérialisation d'une structure de booléens ne peut pas échouer
+    let spec = serde_json::to_string(policy).unwrap_or_default();
+    index.set_folder_policy(folder_path, Some(&spec))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn the_closest_folder_policy_applies() {
+        let dir = tempdir().unwrap();
+        let mut index = SqlCipherIndex::open(dir.path().join("index.db"), &[5u8; 32]).unwrap();
+        let archive = FolderPolicy { compress: true, padding: Padding::High, ..FolderPolicy::default() };
+        let offline = FolderPolicy { pin_offline: true, ..FolderPolicy::default() };
+        set(&mut index, "/archive/", &archive).unwrap();
+        set(&mut index, "/archive/travel", &offline).unwrap();
+
+        let policies = load(&index).unwrap();
+        assert_eq!(policies.resolve("/archive/2019.tar"), archive);
+        assert_eq!(policies.resolve("/archive/old/notes.txt"), archive);
+        assert_eq!(policies.resolve("/archive/travel/map.pdf"), offline);
+        assert_eq!(policies.resolve("/archive/travel/"), offline);
+        assert_eq!(policies.resolve("/archived.txt"), FolderPolicy::default());
+        assert_eq!(get(&index, "/archive").unwrap(), archive);
+
+        // La compression suit le type du contenu ; la politique ne tranche que l'inconnu
+        let plain = FolderPolicy::default();
+        assert!(archive.encoding("text/csv", 10).compress);
+        assert!(!archive.encoding("video/mp4", 1 << 20).compress);
+        assert!(archive.encoding("application/octet-stream", 10).compress);
+        assert!(plain.encoding("text/plain", AUTO_COMPRESS_MIN_LEN).compress);
+        assert!(!plain.encoding("text/plain", 10).compress);
+        assert!(!plain.encoding("application/octet-stream", 1 << 20).compress);
+
+        // La politique suit le dossier renommé ; une politique par défaut l'efface
+        index.rename_folder("/archive/", "/cold/").unwrap();
+        assert_eq!(effective(&index, "/cold/travel/map.pdf").unwrap(), offline);
+        set(&mut index, "/cold/travel/", &FolderPolicy::default()).unwrap();
+        assert_eq!(effective(&index, "/cold/travel/map.pdf").unwrap(), archive);
+    }
+}
@@ -0,0 +1,164 @@
+//! Vérifications préalables d'un lot d'envois.
+//!
+//! Tous les problèmes sont relevés d'un coup, avant le premier octet chiffré : chemin
+//! logique refusé (voir [`path::validate_file_path`]), fichier local illisible, budget de
+//! stockage dépassé ou zone de transit trop petite. Sans cela, un lot échoue au milieu,
+//! fichier après fichier.
+
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::index::{path, sqlcipher::SqlCipherIndex};
+use crate::quota::{self, QuotaCheck};
+use crate::staging::Staging;
+use crate::storage::AetherFile;
+
+/// Fichier à envoyer sous `logical_path`, déjà sondé : sa taille, ou pourquoi il est illisible.
+#[derive(Debug, Clone)]
+pub struct UploadCandidate {
+    pub logical_path: String,
+    /// Chemin local ou URI de la plateforme, pour le rapport.
+    pub source: String,
+    pub size: Result<u64, String>,
+}
+
+impl UploadCandidate {
+    /// Sonde un fichier local.
+    pub fn local(logical_path: String, path: &Path) -> Self {
+        Self::opened(logical_path, path.to_string_lossy().into_owned(), fs::File::open(path))
+    }
+
+    /// Sonde un document ouvert autrement (URI `content://` sur Android, par exemple).
+    pub fn opened(logical_path: String, source: String, file: io::Result<fs::File>) -> Self {
+        let size = file.and_then(|file| file.metadata()).map_err(|e| e.to_string()).and_then(|metadata| {
+            if metadata.is_file() {
+                Ok(metadata.len())
+            } else {
+                Err("not a regular file".to_string())
+            }
+        });
+        Self { logical_path, source, size }
+    }
+}
+
+/// Problème qui ferait échouer l'envoi.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PreflightProblem {
+    /// Chemin logique refusé (`reason` : code stable).
+    InvalidPath { logical_path: String, reason: &'static str },
+    /// Fichier local absent, illisible ou qui n'est pas un fichier.
+    Unreadable { source: String, detail: String },
+    QuotaExceeded { requested_bytes: u64, used_bytes: u64, limit_bytes: u64 },
+    /// Pas assez de place pour chiffrer le plus gros fichier dans la zone de transit.
+    InsufficientDiskSpace { required_bytes: u64, available_bytes: u64 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightReport {
+    pub files: usize,
+    /// Taille en clair des fichiers lisibles.
+    pub total_bytes: u64,
+    /// Taille une fois chiffrés (ce que le budget de stockage comptera).
+    pub encrypted_bytes: u64,
+    pub problems: Vec<PreflightProblem>,
+}
+
+impl PreflightReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Vérifie un lot d'envois ; seules les erreurs de l'index interrompent la vérification.
+///
+/// Les fichiers sont chiffrés un par un dans la zone de transit : il lui faut la place du
+/// plus gros, pas celle du lot entier.
+pub fn check(
+    index: &SqlCipherIndex,
+    staging: Option<&Staging>,
+    candidates: &[UploadCandidate],
+) -> rusqlite::Result<PreflightReport> {
+    let mut problems = Vec::new();
+    let (mut total_bytes, mut encrypted_bytes, mut largest_blob) = (0u64, 0u64, 0u64);
+    for candidate in candidates {
+        if let Err(reason) = path::validate_file_path(&candidate.logical_path) {
+            problems.push(PreflightProblem::InvalidPath {
+                logical_path: candidate.logical_path.clone(),
+                reason,
+            });
+        }
+        match candidate.size {
+            Ok(len) => {
+                let blob_len = len.saturating_add(AetherFile::OVERHEAD);
+                total_bytes = total_bytes.saturating_add(len);
+                encrypted_bytes = encrypted_bytes.saturating_add(blob_len);
+                largest_blob = largest_blob.max(blob_len);
+            }
+            Err(ref detail) => problems.push(PreflightProblem::Unreadable {
+                source: candidate.source.clone(),
+                detail: detail.clone(),
+            }),
+        }
+    }
+
+    if let QuotaCheck::Exceeded { used_bytes, limit_bytes } = quota::check(index, encrypted_bytes)? {
+        problems.push(PreflightProblem::QuotaExceeded {
+            requested_bytes: encrypted_bytes,
+            used_bytes,
+            limit_bytes,
+        });
+    }
+    if let Some(staging) = staging.filter(|_| largest_blob > 0) {
+        match staging.available_space() {
+            Ok(available_bytes) if available_bytes < largest_blob => {
+                problems.push(PreflightProblem::InsufficientDiskSpace {
+                    required_bytes: largest_blob,
+                    available_bytes,
+                });
+            }
+            Ok(_) => {}
+            // L'envoi dira lui-même ce qui ne va pas avec la zone de transit
+            Err(e) => log::warn!("Failed to read free space of the staging area: {}", e),
+        }
+    }
+
+    Ok(PreflightReport {
+        files: candidates.len(),
+        total_bytes,
+        encrypted_bytes,
+        problems,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn every_problem_of_a_batch_is_reported_at_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = SqlCipherIndex::open(temp_dir.path().join("index.db"), &[3u8; 32]).unwrap();
+        quota::set_limit(&mut index, Some(1_000)).unwrap();
+        let staging = Staging::new(temp_dir.path().join("staging")).unwrap();
+        let big = temp_dir.path().join("big.bin");
+        fs::write(&big, vec![0u8; 2_000]).unwrap();
+
+        let candidates = [
+            UploadCandidate::local("/big.bin".to_string(), &big),
+            UploadCandidate::local("/a|b.txt".to_string(), &temp_dir.path().join("missing.txt")),
+        ];
+        let report = check(&index, Some(&staging), &candidates).unwrap();
+
+        assert_eq!(report.files, 2);
+        assert_eq!(report.total_bytes, 2_000);
+        assert_eq!(report.encrypted_bytes, 2_000 + AetherFile::OVERHEAD);
+        assert!(matches!(report.problems[0], PreflightProblem::InvalidPath { reason: "reserved_character", .. }));
+        assert!(matches!(report.problems[1], PreflightProblem::Unreadable { .. }));
+        assert!(matches!(report.problems[2], PreflightProblem::QuotaExceeded { limit_bytes: 1_000, .. }));
+        assert_eq!(report.problems.len(), 3);
+    }
+}
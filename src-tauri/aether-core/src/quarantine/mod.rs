@@ -0,0 +1,251 @@
+//! Quarantaine des entrées dont l'objet distant a échoué à la vérification.
+//!
+//! La vérification périodique (`crate::integrity::check`) et le téléchargement
+//! ([`Vault::fetch_blob`], [`Vault::get_with_path`]), comme la restauration depuis la
+//! corbeille ([`Vault::verify_trashed`]), y placent une entrée dont l'objet manque ou dont
+//! l'en-tête, l'engagement ou le déchiffrement authentifié ne vérifie pas. Son contenu n'est alors plus
+//! servi, jusqu'à l'une des réparations de ce module : remettre une version antérieure,
+//! renvoyer la copie chiffrée intacte conservée dans le cache local, ou abandonner le fichier.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::index::{FileId, FileMetadata, FileVersion};
+use crate::integrity;
+use crate::storage::AetherFile;
+use crate::vault::{is_verification_failure, Vault, VaultError};
+
+/// Entrée en quarantaine et réparations possibles.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuarantinedFile {
+    pub file_id: FileId,
+    /// Chemin logique (`None` si l'entrée n'est plus ni indexée, ni à la corbeille, ni une version).
+    pub logical_path: Option<String>,
+    /// L'entrée est à la corbeille.
+    pub trashed: bool,
+    /// L'entrée est une ancienne version.
+    pub is_version: bool,
+    pub reason: String,
+    /// `scrub` (vérification d'intégrité), `download` ou `restore` (sortie de la corbeille).
+    pub source: String,
+    pub detected_at: i64,
+    /// Versions antérieures hors quarantaine, de la plus récente à la plus ancienne.
+    pub versions: Vec<FileVersion>,
+    /// Une copie chiffrée à l'en-tête valide est présente dans le cache local.
+    pub local_copy: bool,
+}
+
+/// Entrées en quarantaine, de la plus récente à la plus ancienne.
+pub fn list(vault: &Vault) -> Result<Vec<QuarantinedFile>, VaultError> {
+    let index = vault.open_index()?;
+    let entries = index.quarantined()?;
+    if entries.is_empty() {
+        return Ok(Vec::new());
+    }
+    let all_versions = index.list_all_versions()?;
+    let mut files = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let (logical_path, trashed, is_version) = match index.get(&entry.file_id)? {
+            Some(meta) => (Some(meta.logical_path), false, false),
+            None => match index.get_trashed(&entry.file_id)? {
+                Some(meta) => (Some(meta.logical_path), true, false),
+                None => match all_versions.iter().find(|version| version.id == entry.file_id) {
+                    Some(version) => (Some(version.logical_path.clone()), false, true),
+                    None => (None, false, false),
+                },
+            },
+        };
+        let versions = match (&logical_path, trashed || is_version) {
+            (Some(path), false) => {
+                let mut candidates = Vec::new();
+                for version in index.list_versions(path)? {
+                    if index.quarantine_entry(&version.id)?.is_none() {
+                        candidates.push(version);
+                    }
+                }
+                candidates
+            }
+            _ => Vec::new(),
+        };
+        let object_key = index.object_key(&entry.file_id)?;
+        let local_copy = vault.cache().and_then(|cache| cache.get(&object_key)).is_some_and(|blob| {
+            integrity::object_drift(vault.master_key(), &object_key, &blob, blob.len() as u64).is_none()
+        });
+        files.push(QuarantinedFile {
+            file_id: entry.file_id,
+            logical_path,
+            trashed,
+            is_version,
+            reason: entry.reason,
+            source: entry.source,
+            detected_at: entry.detected_at,
+            versions,
+            local_copy,
+        });
+    }
+    Ok(files)
+}
+
+/// Métadonnées d'une entrée en quarantaine, active ou à la corbeille ; `NotFound` si elle
+/// n'y est pas.
+fn quarantined_entry(vault: &Vault, file_id: &FileId) -> Result<FileMetadata, VaultError> {
+    let index = vault.open_index()?;
+    if index.quarantine_entry(file_id)?.is_none() {
+        return Err(VaultError::NotFound(file_id.clone()));
+    }
+    match index.get(file_id)? {
+        Some(meta) => Ok(meta),
+        None => index.get_trashed(file_id)?.ok_or_else(|| VaultError::NotFound(file_id.clone())),
+    }
+}
+
+/// Vérifie un blob complet : en-tête, taille indexée, puis déchiffrement authentifié.
+fn verify_blob(vault: &Vault, file_id: &FileId, meta: &FileMetadata, blob: &[u8]) -> Result<(), VaultError> {
+    let object_key = vault.open_index()?.object_key(file_id)?;
+    if integrity::object_drift(vault.master_key(), &object_key, blob, blob.len() as u64).is_some()
+        || blob.len() as u64 != meta.encrypted_size
+    {
+        return Err(VaultError::Quarantined(file_id.clone()));
+    }
+    let aether_file = AetherFile::from_bytes(blob)?;
+    let plaintext = vault.decrypt_bound(file_id, &aether_file, &meta.logical_path)?;
+    // Une empreinte enregistrée à l'envoi doit aussi correspondre
+    let recorded = vault.open_index()?.content_hash(file_id)?;
+    if recorded.is_some_and(|hash| hash != <[u8; 32]>::from(Sha256::digest(&plaintext))) {
+        return Err(VaultError::Quarantined(file_id.clone()));
+    }
+    Ok(())
+}
+
+/// Relit l'objet distant en entier et lève la quarantaine s'il vérifie (échec passager, objet
+/// réparé ailleurs). Retourne `false` si l'objet reste invalide.
+pub async fn recheck(vault: &Vault, file_id: &FileId) -> Result<bool, VaultError> {
+    let meta = quarantined_entry(vault, file_id)?;
+    let object_key = vault.open_index()?.object_key(file_id)?;
+    let blob = vault.remote().download_file(&object_key).await?;
+    match verify_blob(vault, file_id, &meta, &blob) {
+        Ok(()) => {
+            vault.open_index()?.release_quarantine(file_id)?;
+            if let Some(cache) = vault.cache() {
+                if let Err(e) = cache.put(&object_key, &blob) {
+                    log::warn!("Failed to cache blob {}: {}", object_key, e);
+                }
+            }
+            log::info!("Quarantine released after recheck: {}", file_id);
+            Ok(true)
+        }
+        Err(e) if is_verification_failure(&e) || matches!(e, VaultError::Quarantined(_)) => {
+            log::warn!("Quarantined {} still fails verification: {}", file_id, e);
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Renvoie la copie chiffrée conservée dans le cache local à la place de l'objet distant
+/// altéré, puis lève la quarantaine. La copie est d'abord vérifiée en entier.
+pub async fn restore_local_copy(vault: &Vault, file_id: &FileId) -> Result<(), VaultError> {
+    let meta = quarantined_entry(vault, file_id)?;
+    let object_key = vault.open_index()?.object_key(file_id)?;
+    let blob = vault
+        .cache()
+        .and_then(|cache| cache.get(&object_key))
+        .ok_or_else(|| VaultError::CacheMiss(file_id.clone()))?;
+    if let Err(e) = verify_blob(vault, file_id, &meta, &blob) {
+        log::warn!("Local copy of {} fails verification, discarding it: {}", file_id, e);
+        if let Some(cache) = vault.cache() {
+            cache.remove(&object_key);
+        }
+        return Err(VaultError::CacheMiss(file_id.clone()));
+    }
+    vault.remote().upload_file(&object_key, &blob).await?;
+    vault.open_index()?.release_quarantine(file_id)?;
+    log::info!("Remote object of {} restored from the local copy", file_id);
+    Ok(())
+}
+
+/// Remet en place la version `version_id` ; l'entrée altérée, devenue une version à son
+/// tour, est retirée avec son objet distant (sauf s'il est retenu par un instantané).
+pub async fn restore_version(vault: &Vault, file_id: &FileId, version_id: &FileId) -> Result<FileMetadata, VaultError> {
+    let meta = quarantined_entry(vault, file_id)?;
+    {
+        let index = vault.open_index()?;
+        if !index.list_versions(&meta.logical_path)?.iter().any(|version| version.id == *version_id) {
+            return Err(VaultError::NotFound(version_id.clone()));
+        }
+        if index.quarantine_entry(version_id)?.is_some() {
+            return Err(VaultError::Quarantined(version_id.clone()));
+        }
+    }
+    let restored = vault.restore_version(version_id)?;
+    if !vault.open_index()?.is_pinned(file_id)? {
+        vault.delete_remote(file_id).await?;
+    }
+    // Retire aussi la ligne de quarantaine
+    vault.open_index()?.remove_version(file_id)?;
+    log::info!("Quarantined {} replaced by version {}", file_id, version_id);
+    Ok(restored)
+}
+
+/// Abandonne une entrée en quarantaine : elle est supprimée définitivement, objet distant
+/// compris (voir [`Vault::purge`]).
+pub async fn discard(vault: &Vault, file_id: &FileId) -> Result<(), VaultError> {
+    let index = vault.open_index()?;
+    if index.quarantine_entry(file_id)?.is_none() {
+        return Err(VaultError::NotFound(file_id.clone()));
+    }
+    let is_version = index.get(file_id)?.is_none()
+        && index.get_trashed(file_id)?.is_none()
+        && index.list_all_versions()?.iter().any(|version| version.id == *file_id);
+    drop(index);
+    if is_version {
+        if !vault.open_index()?.is_pinned(file_id)? {
+            vault.delete_remote(file_id).await?;
+        }
+        vault.open_index()?.remove_version(file_id)?;
+    } else {
+        if vault.open_index()?.get(file_id)?.is_some() {
+            vault.trash(file_id)?;
+        }
+        vault.purge(file_id).await?;
+    }
+    // Sans effet si la suppression l'a déjà retirée
+    vault.open_index()?.release_quarantine(file_id)?;
+    log::info!("Quarantined {} discarded", file_id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::sqlcipher::SqlCipherIndex;
+    use crate::index::FileMetadata;
+    use tempfile::TempDir;
+
+    #[test]
+    fn quarantine_follows_the_entry_and_blocks_deduplication() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = SqlCipherIndex::open(temp_dir.path().join("index.db"), &[5u8; 32]).unwrap();
+        let id = "aa".repeat(16);
+        index
+            .upsert(id.clone(), FileMetadata { logical_path: "/a.txt".to_string(), encrypted_size: 80 })
+            .unwrap();
+        let hash = [7u8; 32];
+        index.set_content_hash(&id, &hash).unwrap();
+        assert!(index.find_by_content_hash(&hash, "/b.txt").unwrap().is_some());
+
+        index.quarantine(&id, "invalid header", "scrub").unwrap();
+        index.quarantine(&id, "header commitment does not verify", "download").unwrap();
+        let entries = index.quarantined().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].reason.as_str(), entries[0].source.as_str()), ("header commitment does not verify", "download"));
+        // Un objet altéré n'est pas réutilisé pour un nouvel envoi au contenu identique
+        assert!(index.find_by_content_hash(&hash, "/b.txt").unwrap().is_none());
+
+        let meta = index.get(&id).unwrap().unwrap();
+        index.move_to_trash(&id, &meta).unwrap();
+        assert!(index.quarantine_entry(&id).unwrap().is_some());
+        index.remove_from_trash(&id).unwrap();
+        assert!(index.quarantined().unwrap().is_empty());
+        assert!(!index.release_quarantine(&id).unwrap());
+    }
+}
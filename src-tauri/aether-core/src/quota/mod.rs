@@ -0,0 +1,221 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::index::{sqlcipher::SqlCipherIndex, FileId};
+
+/// Seuils d'alerte (pourcentage du budget) franchis lors d'un envoi.
+pub const WARNING_THRESHOLDS: [u8; 2] = [80, 95];
+
+/// Clé `index_metadata` sous laquelle le budget (octets, big-endian) est conservé.
+pub(crate) const QUOTA_META_KEY: &str = "storage_quota";
+
+/// Part de l'occupation (en %) au-delà de laquelle vider la corbeille est suggéré.
+pub const DEFAULT_TRASH_SUGGEST_PERCENT: u8 = 20;
+
+/// Clé `index_metadata` du seuil de suggestion (un octet ; `0` désactive la suggestion).
+pub(crate) const TRASH_SUGGEST_META_KEY: &str = "trash_suggest_percent";
+
+/// Consommation actuelle comparée au budget choisi par l'utilisateur.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QuotaStatus {
+    /// `None` : aucun budget défini.
+    pub limit_bytes: Option<u64>,
+    /// Taille chiffrée de tout ce qui occupe le bucket (fichiers et corbeille).
+    pub used_bytes: u64,
+    pub remaining_bytes: Option<u64>,
+    /// Part de `used_bytes` libérée en vidant la corbeille (objets qu'aucun fichier ni
+    /// version ne partage).
+    pub trash_bytes: u64,
+    /// Seuil de suggestion, en pourcentage de `used_bytes` (`0` : jamais).
+    pub trash_suggest_percent: u8,
+}
+
+/// Verdict pour un envoi de `requested` octets supplémentaires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaCheck {
+    Within,
+    /// L'envoi fait franchir ce seuil d'alerte (sans dépasser le budget).
+    Warning { threshold_percent: u8 },
+    Exceeded { used_bytes: u64, limit_bytes: u64 },
+}
+
+pub fn limit(index: &SqlCipherIndex) -> rusqlite::Result<Option<u64>> {
+    Ok(index
+        .get_meta(QUOTA_META_KEY)?
+        .and_then(|raw| <[u8; 8]>::try_from(raw.as_slice()).ok())
+        .map(u64::from_be_bytes))
+}
+
+/// Définit (ou retire, avec `None`) le budget de stockage.
+pub fn set_limit(index: &mut SqlCipherIndex, limit_bytes: Option<u64>) -> rusqlite::Result<()> {
+    match limit_bytes {
+        Some(limit) => index.put_meta(QUOTA_META_KEY, &limit.to_be_bytes()),
+        None => index.delete_meta(QUOTA_META_KEY),
+    }
+}
+
+pub fn trash_suggest_percent(index: &SqlCipherIndex) -> rusqlite::Result<u8> {
+    Ok(index
+        .get_meta(TRASH_SUGGEST_META_KEY)?
+        .and_then(|raw| raw.first().copied())
+        .unwrap_or(DEFAULT_TRASH_SUGGEST_PERCENT))
+}
+
+/// Définit le seuil de suggestion (plafonné à 100 %) ; `None` rétablit la valeur par défaut.
+pub fn set_trash_suggest_percent(index: &mut SqlCipherIndex, percent: Option<u8>) -> rusqlite::Result<()> {
+    match percent {
+        Some(percent) => index.put_meta(TRASH_SUGGEST_META_KEY, &[percent.min(100)]),
+        None => index.delete_meta(TRASH_SUGGEST_META_KEY),
+    }
+}
+
+/// Octets occupés côté distant : la corbeille et les anciennes versions comptent, leurs
+/// objets n'étant pas encore supprimés.
+pub fn used_bytes(index: &SqlCipherIndex) -> rusqlite::Result<u64> {
+    Ok(usage(index)?.0)
+}
+
+/// Occupation totale et part qui ne tient qu'à la corbeille.
+fn usage(index: &SqlCipherIndex) -> rusqlite::Result<(u64, u64)> {
+    // Un objet partagé par des entrées dédupliquées n'est compté qu'une fois ; il ne revient à
+    // la corbeille que si aucune entrée vivante ne le référence
+    let links = index.blob_links()?;
+    let mut objects: HashMap<FileId, (u64, bool)> = HashMap::new();
+    let files = index.list_all()?.into_iter().map(|(id, meta)| (id, meta.encrypted_size, false));
+    let trash = index.list_trash()?.into_iter().map(|(id, meta, _)| (id, meta.encrypted_size, true));
+    let versions =
+        index.list_all_versions()?.into_iter().map(|version| (version.id, version.encrypted_size, false));
+    for (id, size, trashed) in files.chain(trash).chain(versions) {
+        let object_key = links.get(&id).cloned().unwrap_or(id);
+        let object = objects.entry(object_key).or_insert((size, trashed));
+        object.0 = size;
+        object.1 &= trashed;
+    }
+    let used = objects.values().map(|(size, _)| size).sum();
+    let trash = objects.values().filter(|(_, trashed)| *trashed).map(|(size, _)| size).sum();
+    Ok((used, trash))
+}
+
+pub fn status(index: &SqlCipherIndex) -> rusqlite::Result<QuotaStatus> {
+    let limit_bytes = limit(index)?;
+    let (used_bytes, trash_bytes) = usage(index)?;
+    Ok(QuotaStatus {
+        limit_bytes,
+        used_bytes,
+        remaining_bytes: limit_bytes.map(|limit| limit.saturating_sub(used_bytes)),
+        trash_bytes,
+        trash_suggest_percent: trash_suggest_percent(index)?,
+    })
+}
+
+impl QuotaStatus {
+    /// La corbeille occupe au moins la part choisie de l'espace utilisé.
+    pub fn trash_over_share(&self) -> bool {
+        self.trash_suggest_percent > 0
+            && self.trash_bytes > 0
+            && self.trash_bytes as u128 * 100 >= self.used_bytes as u128 * self.trash_suggest_percent as u128
+    }
+
+    /// Part (en %) de l'espace utilisé occupée par la corbeille.
+    pub fn trash_share_percent(&self) -> u8 {
+        if self.used_bytes == 0 {
+            return 0;
+        }
+        (self.trash_bytes as u128 * 100 / self.used_bytes as u128).min(100) as u8
+    }
+}
+
+/// La corbeille vient de dépasser son seuil entre `before` et `after` : la suggestion n'est
+/// faite qu'au franchissement, pas à chaque suppression suivante.
+pub fn trash_suggestion_crossed(before: &QuotaStatus, after: &QuotaStatus) -> bool {
+    after.trash_over_share() && !before.trash_over_share()
+}
+
+pub fn check(index: &SqlCipherIndex, requested: u64) -> rusqlite::Result<QuotaCheck> {
+    let Some(limit_bytes) = limit(index)? else {
+        return Ok(QuotaCheck::Within);
+    };
+    Ok(evaluate(used_bytes(index)?, requested, limit_bytes))
+}
+
+fn evaluate(used_bytes: u64, requested: u64, limit_bytes: u64) -> QuotaCheck {
+    let projected = used_bytes.saturating_add(requested);
+    if projected > limit_bytes {
+        return QuotaCheck::Exceeded { used_bytes, limit_bytes };
+    }
+    // u128 : pas de débordement pour des budgets proches de u64::MAX
+    let reached = |bytes: u64, percent: u8| bytes as u128 * 100 >= limit_bytes as u128 * percent as u128;
+    WARNING_THRESHOLDS
+        .iter()
+        .rev()
+        .find(|&&percent| reached(projected, percent) && !reached(used_bytes, percent))
+        .map_or(QuotaCheck::Within, |&threshold_percent| QuotaCheck::Warning { threshold_percent })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::FileMetadata;
+    use tempfile::TempDir;
+
+    #[test]
+    fn thresholds_warn_once_and_budget_is_enforced() {
+        assert_eq!(evaluate(0, 50, 100), QuotaCheck::Within);
+        assert_eq!(evaluate(70, 15, 100), QuotaCheck::Warning { threshold_percent: 80 });
+        assert_eq!(evaluate(70, 30, 100), QuotaCheck::Warning { threshold_percent: 95 });
+        // Seuil déjà franchi : pas de nouvelle alerte
+        assert_eq!(evaluate(85, 5, 100), QuotaCheck::Within);
+        assert_eq!(evaluate(90, 11, 100), QuotaCheck::Exceeded { used_bytes: 90, limit_bytes: 100 });
+    }
+
+    #[test]
+    fn trash_counts_towards_usage() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = SqlCipherIndex::open(temp_dir.path().join("index.db"), &[5u8; 32]).unwrap();
+        assert_eq!(check(&index, u64::MAX).unwrap(), QuotaCheck::Within);
+
+        let meta = FileMetadata { logical_path: "/a".to_string(), encrypted_size: 40 };
+        index.upsert("a".to_string(), meta.clone()).unwrap();
+        index.move_to_trash(&"a".to_string(), &meta).unwrap();
+        index
+            .upsert("b".to_string(), FileMetadata { logical_path: "/b".to_string(), encrypted_size: 20 })
+            .unwrap();
+
+        set_limit(&mut index, Some(100)).unwrap();
+        let status = status(&index).unwrap();
+        assert_eq!(status.used_bytes, 60);
+        assert_eq!(status.remaining_bytes, Some(40));
+
+        assert_eq!(status.trash_bytes, 40);
+
+        set_limit(&mut index, None).unwrap();
+        assert_eq!(limit(&index).unwrap(), None);
+    }
+
+    #[test]
+    fn trash_suggestion_fires_once_past_its_share() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = SqlCipherIndex::open(temp_dir.path().join("index.db"), &[6u8; 32]).unwrap();
+        for (id, size) in [("a", 30), ("b", 70)] {
+            let meta = FileMetadata { logical_path: format!("/{}", id), encrypted_size: size };
+            index.upsert(id.to_string(), meta).unwrap();
+        }
+        let before = status(&index).unwrap();
+        assert_eq!(before.trash_suggest_percent, DEFAULT_TRASH_SUGGEST_PERCENT);
+
+        let meta = FileMetadata { logical_path: "/a".to_string(), encrypted_size: 30 };
+        index.move_to_trash(&"a".to_string(), &meta).unwrap();
+        let after = status(&index).unwrap();
+        assert_eq!(after.trash_share_percent(), 30);
+        assert!(trash_suggestion_crossed(&before, &after));
+        // Déjà au-dessus du seuil : pas de nouvelle suggestion
+        assert!(!trash_suggestion_crossed(&after, &after));
+
+        set_trash_suggest_percent(&mut index, Some(50)).unwrap();
+        assert!(!status(&index).unwrap().trash_over_share());
+        set_trash_suggest_percent(&mut index, Some(0)).unwrap();
+        assert!(!status(&index).unwrap().trash_over_share());
+        set_trash_suggest_percent(&mut index, None).unwrap();
+        assert!(status(&index).unwrap().trash_over_share());
+    }
+}
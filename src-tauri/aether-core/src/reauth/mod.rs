@@ -0,0 +1,141 @@
+//! Fraîcheur de l'authentification pendant une session déverrouillée.
+//!
+//! Un coffre peut rester ouvert des heures. Les opérations qui font sortir la clé du coffre
+//! (kit de récupération, paquet de configuration) exigent en plus que le mot de passe ait
+//! été saisi depuis moins que la fenêtre choisie ; au-delà, il est redemandé. Un
+//! déverrouillage par l'appareil ouvre le coffre sans rendre la session fraîche.
+
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::index::sqlcipher::SqlCipherIndex;
+
+/// Fenêtre par défaut après la dernière saisie du mot de passe.
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(15 * 60);
+/// Bornes de la fenêtre réglable.
+pub const MIN_WINDOW: Duration = Duration::from_secs(60);
+pub const MAX_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Clé `index_metadata` de la fenêtre (secondes, big-endian).
+const WINDOW_META_KEY: &str = "reauth_window_secs";
+
+pub fn window(index: &SqlCipherIndex) -> rusqlite::Result<Duration> {
+    Ok(index
+        .get_meta(WINDOW_META_KEY)?
+        .and_then(|raw| <[u8; 8]>::try_from(raw.as_slice()).ok())
+        .map_or(DEFAULT_WINDOW, |secs| Duration::from_secs(u64::from_be_bytes(secs))))
+}
+
+/// Définit la fenêtre, ramenée entre [`MIN_WINDOW`] et [`MAX_WINDOW`] ; retourne celle retenue.
+pub fn set_window(index: &mut SqlCipherIndex, window: Duration) -> rusqlite::Result<Duration> {
+    let window = Duration::from_secs(window.clamp(MIN_WINDOW, MAX_WINDOW).as_secs());
+    index.put_meta(WINDOW_META_KEY, &window.as_secs().to_be_bytes())?;
+    Ok(window)
+}
+
+/// Horodatage de la session en cours (horloge monotone, en mémoire seulement).
+#[derive(Debug, Clone)]
+pub struct Freshness {
+    unlocked_at: Option<Instant>,
+    authenticated_at: Option<Instant>,
+    window: Duration,
+}
+
+/// État de la session présenté au frontend.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FreshnessStatus {
+    /// Secondes depuis le déverrouillage ; `None` : coffre verrouillé.
+    pub unlocked_secs: Option<u64>,
+    /// Secondes depuis la dernière saisie vérifiée du mot de passe.
+    pub authenticated_secs: Option<u64>,
+    pub window_secs: u64,
+    /// Les opérations sensibles passent sans nouvelle saisie.
+    pub fresh: bool,
+}
+
+impl Default for Freshness {
+    fn default() -> Self {
+        Self { unlocked_at: None, authenticated_at: None, window: DEFAULT_WINDOW }
+    }
+}
+
+impl Freshness {
+    /// Coffre ouvert à `now` (le premier appel de la session compte), avec le mot de passe
+    /// ou non.
+    pub fn unlocked(&mut self, now: Instant, with_password: bool) {
+        self.unlocked_at.get_or_insert(now);
+        if with_password {
+            self.authenticated_at = Some(now);
+        }
+    }
+
+    /// Mot de passe vérifié de nouveau à `now`.
+    pub fn authenticated(&mut self, now: Instant) {
+        self.authenticated_at = Some(now);
+    }
+
+    pub fn set_window(&mut self, window: Duration) {
+        self.window = window;
+    }
+
+    pub fn is_fresh(&self, now: Instant) -> bool {
+        self.authenticated_at.is_some_and(|at| now.saturating_duration_since(at) <= self.window)
+    }
+
+    pub fn status(&self, now: Instant) -> FreshnessStatus {
+        let since = |at: Option<Instant>| at.map(|at| now.saturating_duration_since(at).as_secs());
+        FreshnessStatus {
+            unlocked_secs: since(self.unlocked_at),
+            authenticated_secs: since(self.authenticated_at),
+            window_secs: self.window.as_secs(),
+            fresh: self.is_fresh(now),
+        }
+    }
+
+    /// Verrouillage : la session suivante repart de zéro, la fenêtre choisie est conservée.
+    pub fn clear(&mut self) {
+        self.unlocked_at = None;
+        self.authenticated_at = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn freshness_expires_after_the_window_and_survives_only_password_entries() {
+        let start = Instant::now();
+        let mut freshness = Freshness::default();
+        assert!(!freshness.is_fresh(start));
+
+        // Déverrouillage par l'appareil : ouvert, mais pas frais
+        freshness.unlocked(start, false);
+        assert!(!freshness.is_fresh(start));
+        freshness.authenticated(start + Duration::from_secs(30));
+        assert!(freshness.is_fresh(start + DEFAULT_WINDOW));
+        assert!(!freshness.is_fresh(start + DEFAULT_WINDOW + Duration::from_secs(31)));
+
+        // Un nouveau déverrouillage dans la même session ne remet pas l'horloge à zéro
+        let later = start + Duration::from_secs(3600);
+        freshness.unlocked(later, true);
+        let status = freshness.status(later);
+        assert_eq!((status.unlocked_secs, status.authenticated_secs, status.fresh), (Some(3600), Some(0), true));
+
+        freshness.clear();
+        assert_eq!(freshness.status(later).unlocked_secs, None);
+        assert!(!freshness.is_fresh(later));
+    }
+
+    #[test]
+    fn window_is_stored_and_clamped() {
+        let dir = tempdir().unwrap();
+        let mut index = SqlCipherIndex::open(dir.path().join("index.db"), &[4u8; 32]).unwrap();
+        assert_eq!(window(&index).unwrap(), DEFAULT_WINDOW);
+        assert_eq!(set_window(&mut index, Duration::from_secs(5)).unwrap(), MIN_WINDOW);
+        assert_eq!(set_window(&mut index, Duration::from_secs(7200)).unwrap(), Duration::from_secs(7200));
+        assert_eq!(window(&index).unwrap(), Duration::from_secs(7200));
+        assert_eq!(set_window(&mut index, Duration::from_secs(u64::MAX)).unwrap(), MAX_WINDOW);
+    }
+}
@@ -0,0 +1,162 @@
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::index::PendingKind;
+use crate::storj::StorjError;
+use crate::vault::{Vault, VaultError};
+
+/// Dossier (dans le dossier de données) réservé aux fichiers temporaires en clair.
+///
+/// Son contenu n'a aucune valeur après un redémarrage : la passe de reprise le vide.
+pub const SCRATCH_DIR: &str = "tmp";
+
+/// Un téléchargement partiel resté sans reprise plus longtemps est abandonné.
+const STALE_DOWNLOAD_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Bilan de la passe de reprise exécutée au déverrouillage.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RecoveryReport {
+    /// Fichiers temporaires supprimés (clair dans `tmp/`, blobs `.part` du cache,
+    /// téléchargements partiels abandonnés).
+    pub removed_temp_files: usize,
+    /// Envois multipart inachevés annulés côté Storj.
+    pub aborted_multipart_uploads: usize,
+    /// Envois en attente rejoués avec succès.
+    pub resumed_uploads: usize,
+    /// Envois en attente abandonnés faute de blob en cache (chemins logiques).
+    pub dropped_uploads: Vec<String>,
+    /// Opérations toujours en file (stockage injoignable).
+    pub remaining_pending: usize,
+    /// Objets d'avant les préfixes par coffre, déplacés sous le préfixe du coffre.
+    pub adopted_objects: usize,
+}
+
+impl RecoveryReport {
+    pub fn is_empty(&self) -> bool {
+        self.removed_temp_files == 0
+            && self.aborted_multipart_uploads == 0
+            && self.resumed_uploads == 0
+            && self.dropped_uploads.is_empty()
+            && self.remaining_pending == 0
+            && self.adopted_objects == 0
+    }
+}
+
+/// Vide le dossier temporaire. Retourne le nombre d'entrées supprimées.
+pub fn sweep_scratch(dir: &Path) -> io::Result<usize> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let mut removed = 0;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else {
+            fs::remove_file(&path)?;
+        }
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Reprend ou nettoie le travail laissé à moitié par un arrêt brutal.
+///
+/// Les étapes distantes sont tolérantes : un stockage injoignable laisse simplement
+/// la file hors ligne en place pour la prochaine synchronisation.
+pub async fn run(vault: &Vault, scratch_dir: &Path) -> Result<RecoveryReport, VaultError> {
+    let mut report = RecoveryReport {
+        removed_temp_files: sweep_scratch(scratch_dir)
+            .inspect_err(|e| log::warn!("Failed to sweep scratch dir: {}", e))
+            .unwrap_or(0),
+        ..Default::default()
+    };
+    if let Some(cache) = vault.cache() {
+        report.removed_temp_files += cache
+            .sweep_partials()
+            .inspect_err(|e| log::warn!("Failed to sweep partial cache blobs: {}", e))
+            .unwrap_or(0);
+    }
+    // Les téléchargements partiels récents sont gardés : ils reprendront
+    if let Some(downloads) = vault.downloads() {
+        report.removed_temp_files += downloads
+            .sweep_stale(STALE_DOWNLOAD_AGE)
+            .inspect_err(|e| log::warn!("Failed to sweep stale partial downloads: {}", e))
+            .unwrap_or(0);
+    }
+
+    // Un envoi en attente dont le blob a disparu ne peut plus aboutir : l'entrée d'index
+    // pointerait vers un objet qui n'existera jamais.
+    {
+        let mut index = vault.open_index()?;
+        for op in index.list_pending()? {
+            if op.kind != PendingKind::Upload || vault.cache().is_some_and(|c| c.contains(&op.file_id)) {
+                continue;
+            }
+            let logical_path = index
+                .get(&op.file_id)?
+                .map(|meta| meta.logical_path)
+                .unwrap_or_else(|| op.file_id.clone());
+            index.remove_pending(op.seq)?;
+            index.remove(&op.file_id)?;
+            log::warn!("Dropped pending upload without cached blob: {}", op.file_id);
+            report.dropped_uploads.push(logical_path);
+        }
+    }
+
+    // Les objets envoyés avant les préfixes par coffre sont restés à la racine du bucket
+    let known = vault.open_index()?.remote_object_ids()?;
+    report.adopted_objects = match vault.remote().adopt_unprefixed(&known).await {
+        Ok(count) => count,
+        Err(StorjError::Unreachable(_)) => 0,
+        Err(e) => {
+            log::warn!("Failed to move legacy objects under the vault prefix: {}", e);
+            0
+        }
+    };
+
+    report.aborted_multipart_uploads = match vault.remote().abort_incomplete_uploads().await {
+        Ok(count) => count,
+        Err(StorjError::Unreachable(_)) => 0,
+        Err(e) => {
+            log::warn!("Failed to abort incomplete multipart uploads: {}", e);
+            0
+        }
+    };
+
+    let flush = vault.flush_pending().await?;
+    report.resumed_uploads = flush.completed;
+    report.remaining_pending = flush.remaining;
+
+    log::info!(
+        "Recovery pass finished: temp_files={}, multipart={}, resumed={}, dropped={}, remaining={}, adopted={}",
+        report.removed_temp_files,
+        report.aborted_multipart_uploads,
+        report.resumed_uploads,
+        report.dropped_uploads.len(),
+        report.remaining_pending,
+        report.adopted_objects
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn sweep_scratch_empties_directory() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("plain.txt"), b"secret").unwrap();
+        fs::create_dir(dir.path().join("nested")).unwrap();
+        fs::write(dir.path().join("nested/part"), b"x").unwrap();
+
+        assert_eq!(sweep_scratch(dir.path()).unwrap(), 2);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+        assert_eq!(sweep_scratch(&dir.path().join("missing")).unwrap(), 0);
+    }
+}
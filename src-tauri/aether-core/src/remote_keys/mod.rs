@@ -0,0 +1,186 @@
+//! Clés distantes détachées des identifiants de l'index.
+//!
+//! Par défaut, un objet distant porte l'UUID de son entrée : le fournisseur peut suivre un
+//! fichier d'un envoi à l'autre. Une fois ce mode activé, chaque envoi reçoit une clé
+//! aléatoire (l'UUID de l'en-tête, sans lien avec l'entrée) et l'index garde la
+//! correspondance entrée → objet dans ses liens de blob, comme pour un contenu dédupliqué.
+//! Une entrée liée n'est plus re-chiffrée quand son dossier est renommé : rien ne change
+//! dans le bucket.
+//!
+//! Le mode ne vaut que pour les envois suivants ; les objets déjà envoyés gardent leur clé.
+//!
+//! Dans tous les cas, une clé d'objet est un UUID en 32 caractères hexadécimaux minuscules.
+//! D'anciennes versions envoyaient l'UUID avec tirets : [`repair`] les renomme côté serveur.
+
+use rand::{rngs::OsRng, RngCore};
+use rusqlite::Result as SqliteResult;
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::index::FileId;
+use crate::storj::RemoteObject;
+use crate::vault::{Vault, VaultError};
+
+const ENABLED_META_KEY: &str = "random_remote_keys";
+
+pub fn enabled(index: &SqlCipherIndex) -> SqliteResult<bool> {
+    Ok(index.get_meta(ENABLED_META_KEY)?.is_some_and(|raw| raw.first() == Some(&1)))
+}
+
+pub fn set_enabled(index: &mut SqlCipherIndex, enable: bool) -> SqliteResult<()> {
+    if enable {
+        index.put_meta(ENABLED_META_KEY, &[1])
+    } else {
+        index.delete_meta(ENABLED_META_KEY)
+    }
+}
+
+/// Identifiant d'entrée pour un objet envoyé sous `object_key` : un nouvel UUID en mode
+/// clés aléatoires, la clé elle-même sinon.
+pub fn entry_id(index: &SqlCipherIndex, object_key: &FileId) -> SqliteResult<FileId> {
+    if !enabled(index)? {
+        return Ok(object_key.clone());
+    }
+    let mut uuid = [0u8; 16];
+    OsRng.fill_bytes(&mut uuid);
+    Ok(hex::encode(uuid))
+}
+
+/// Clé attendue pour un objet listé sous `key` ; `None` si ce n'est pas un UUID (objet
+/// étranger au coffre).
+pub fn canonical_key(key: &str) -> Option<FileId> {
+    let hyphenated = key.len() == 36
+        && key.char_indices().all(|(i, c)| matches!(i, 8 | 13 | 18 | 23) == (c == '-'));
+    let hex: String = if hyphenated { key.replace('-', "") } else { key.to_string() };
+    (hex.len() == 32 && hex.bytes().all(|b| b.is_ascii_hexdigit())).then(|| hex.to_ascii_lowercase())
+}
+
+/// Clé d'un objet listé, telle que l'index la connaît. Un bucket réparé ([`repair`]) n'a plus
+/// que des clés attendues ; les autres restent lues sous leur forme attendue en attendant.
+pub fn normalize(key: &str) -> String {
+    canonical_key(key).unwrap_or_else(|| key.to_string())
+}
+
+/// Objet rangé sous une clé autre que celle attendue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MisnamedObject {
+    pub key: String,
+    pub expected: FileId,
+}
+
+/// Résultat d'un audit (et, le cas échéant, d'une réparation) des clés du bucket.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct KeyAudit {
+    /// Objets examinés.
+    pub checked: usize,
+    /// Objets à renommer (restants, après une réparation).
+    pub misnamed: Vec<MisnamedObject>,
+    /// Objets présents aussi sous leur clé attendue avec un autre contenu : laissés en place.
+    pub duplicates: Vec<MisnamedObject>,
+    /// Clés qui ne sont pas des UUID : ignorées.
+    pub unrecognized: Vec<String>,
+    /// Objets renommés (ou doublons identiques supprimés) par la réparation.
+    pub repaired: usize,
+}
+
+/// Classe les objets du bucket selon leur clé.
+pub fn audit(objects: &[RemoteObject]) -> KeyAudit {
+    let keys: HashSet<&str> = objects.iter().map(|obj| obj.key.as_str()).collect();
+    let mut report = KeyAudit { checked: objects.len(), ..KeyAudit::default() };
+    for obj in objects {
+        match canonical_key(&obj.key) {
+            None => report.unrecognized.push(obj.key.clone()),
+            Some(expected) if expected == obj.key => {}
+            Some(expected) if keys.contains(expected.as_str()) => {
+                report.duplicates.push(MisnamedObject { key: obj.key.clone(), expected })
+            }
+            Some(expected) => report.misnamed.push(MisnamedObject { key: obj.key.clone(), expected }),
+        }
+    }
+    report
+}
+
+/// Audite les clés du bucket et, avec `apply`, renomme les objets mal nommés : copie côté
+/// serveur sous la clé attendue, liens de l'index mis à jour, puis suppression de l'original.
+/// Un doublon au contenu identique (réparation interrompue) est supprimé ; un doublon différent
+/// est signalé sans être touché.
+pub async fn repair(vault: &Vault, apply: bool) -> Result<KeyAudit, VaultError> {
+    let remote = vault.remote();
+    let mut report = audit(&remote.list_objects().await?);
+    if !apply {
+        return Ok(report);
+    }
+
+    let mut kept = Vec::new();
+    for duplicate in std::mem::take(&mut report.duplicates) {
+        if remote.download_file(&duplicate.key).await? == remote.download_file(&duplicate.expected).await? {
+            vault.open_index()?.relink_object(&duplicate.key, &duplicate.expected)?;
+            remote.delete_file(&duplicate.key).await?;
+            log::info!("Removed duplicate remote object: {} (kept {})", duplicate.key, duplicate.expected);
+            report.repaired += 1;
+        } else {
+            log::warn!("Remote object {} differs from {}, left in place", duplicate.key, duplicate.expected);
+            kept.push(duplicate);
+        }
+    }
+    report.duplicates = kept;
+
+    for misnamed in std::mem::take(&mut report.misnamed) {
+        remote.copy_file(&misnamed.key, &misnamed.expected).await?;
+        vault.open_index()?.relink_object(&misnamed.key, &misnamed.expected)?;
+        remote.delete_file(&misnamed.key).await?;
+        log::info!("Renamed remote object {} to {}", misnamed.key, misnamed.expected);
+        report.repaired += 1;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn entries_get_their_own_id_once_enabled() {
+        let dir = tempdir().unwrap();
+        let mut index = SqlCipherIndex::open(dir.path().join("index.db"), &[9u8; 32]).unwrap();
+        let object_key = "00112233445566778899aabbccddeeff".to_string();
+        assert_eq!(entry_id(&index, &object_key).unwrap(), object_key);
+
+        set_enabled(&mut index, true).unwrap();
+        let id = entry_id(&index, &object_key).unwrap();
+        assert_ne!(id, object_key);
+        assert_eq!(id.len(), object_key.len());
+        assert_ne!(entry_id(&index, &object_key).unwrap(), id);
+
+        set_enabled(&mut index, false).unwrap();
+        assert_eq!(entry_id(&index, &object_key).unwrap(), object_key);
+    }
+
+    #[test]
+    fn audit_sorts_keys_by_format() {
+        let object = |key: &str| RemoteObject { key: key.to_string(), size: 1, last_modified: None };
+        let plain = "00112233445566778899aabbccddeeff";
+        assert_eq!(canonical_key("00112233-4455-6677-8899-AABBCCDDEEFF").as_deref(), Some(plain));
+        assert_eq!(canonical_key("0011223344-55-6677-8899-aabbccddeeff"), None);
+        assert_eq!(normalize("notes.txt"), "notes.txt");
+
+        let report = audit(&[
+            object(plain),
+            object("00112233-4455-6677-8899-aabbccddeeff"),
+            object("ffeeddcc-bbaa-9988-7766-554433221100"),
+            object("notes.txt"),
+        ]);
+        assert_eq!(report.checked, 4);
+        assert_eq!(
+            report.misnamed,
+            vec![MisnamedObject {
+                key: "ffeeddcc-bbaa-9988-7766-554433221100".to_string(),
+                expected: "ffeeddccbbaa99887766554433221100".to_string(),
+            }]
+        );
+        assert_eq!(report.duplicates.len(), 1);
+        assert_eq!(report.unrecognized, vec!["notes.txt".to_string()]);
+    }
+}
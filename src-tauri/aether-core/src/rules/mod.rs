@@ -0,0 +1,369 @@
+//! Règles d'automatisation, stockées dans l'index et exécutées par le planificateur de fond.
+//!
+//! Deux actions : importer les fichiers qui apparaissent dans un dossier local vers un
+//! dossier du coffre, et mettre à la corbeille les fichiers d'un dossier du coffre plus
+//! anciens qu'un nombre de jours (date d'envoi de l'objet distant).
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::clock::unix_now;
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::remote_keys;
+use crate::vault::{Vault, VaultError};
+
+/// Un fichier local n'est importé que s'il n'a pas changé depuis ce délai (écriture terminée).
+pub const STABLE_AFTER_SECS: i64 = 30;
+
+/// Âge maximal accepté pour une règle de mise à la corbeille (dix ans).
+pub const MAX_AGE_DAYS: u32 = 3650;
+
+const MAX_NAME_CHARS: usize = 80;
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+#[derive(Debug)]
+pub enum RuleError {
+    /// Définition refusée (`reason` : code stable).
+    Invalid(&'static str),
+    NotFound(i64),
+    Index(rusqlite::Error),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::Invalid(reason) => write!(f, "Invalid rule: {}", reason),
+            RuleError::NotFound(id) => write!(f, "Rule not found: {}", id),
+            RuleError::Index(e) => write!(f, "Index error: {}", e),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for RuleError {
+    fn from(e: rusqlite::Error) -> Self {
+        RuleError::Index(e)
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+/// Action d'une règle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Envoie dans `vault_folder` les fichiers apparus (ou modifiés) à la racine de `local_dir`.
+    ImportFolder { local_dir: String, vault_folder: String },
+    /// Met à la corbeille les fichiers de `vault_folder` (sous-dossiers compris) envoyés il y a
+    /// plus de `older_than_days` jours.
+    AutoTrash { vault_folder: String, older_than_days: u32 },
+}
+
+impl RuleAction {
+    /// Normalise les chemins du coffre (`/a/b`) et refuse les définitions dangereuses.
+    pub fn validate(self) -> Result<Self, &'static str> {
+        match self {
+            RuleAction::ImportFolder { local_dir, vault_folder } => {
+                if !Path::new(&local_dir).is_absolute() {
+                    return Err("relative_local_dir");
+                }
+                Ok(RuleAction::ImportFolder {
+                    local_dir,
+                    vault_folder: folder_key(&vault_folder),
+                })
+            }
+            RuleAction::AutoTrash { vault_folder, older_than_days } => {
+                let vault_folder = folder_key(&vault_folder);
+                // Toute la racine serait vidée au fil du temps
+                if vault_folder == "/" {
+                    return Err("root_folder");
+                }
+                if older_than_days == 0 || older_than_days > MAX_AGE_DAYS {
+                    return Err("invalid_age");
+                }
+                Ok(RuleAction::AutoTrash { vault_folder, older_than_days })
+            }
+        }
+    }
+}
+
+/// Règle telle que présentée au frontend.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Rule {
+    pub id: i64,
+    pub name: String,
+    pub enabled: bool,
+    pub action: RuleAction,
+    /// Timestamp Unix de la dernière exécution.
+    pub last_run: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// Bilan d'une passe d'exécution des règles actives.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RulesReport {
+    pub rules_run: usize,
+    pub uploaded: usize,
+    pub trashed: usize,
+    pub failures: Vec<RuleFailure>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleFailure {
+    pub rule_id: i64,
+    pub name: String,
+    pub detail: String,
+}
+
+/// Règles enregistrées ; une définition illisible (version future) est ignorée.
+pub fn list(index: &SqlCipherIndex) -> rusqlite::Result<Vec<Rule>> {
+    Ok(index
+        .list_rules()?
+        .into_iter()
+        .filter_map(|stored| match serde_json::from_str(&stored.spec) {
+            Ok(action) => Some(Rule {
+                id: stored.id,
+                name: stored.name,
+                enabled: stored.enabled,
+                action,
+                last_run: stored.last_run,
+                last_error: stored.last_error,
+            }),
+            Err(e) => {
+                log::warn!("Skipping unreadable rule {}: {}", stored.id, e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Crée (`id` absent) ou met à jour une règle.
+pub fn save(
+    index: &mut SqlCipherIndex,
+    id: Option<i64>,
+    name: &str,
+    enabled: bool,
+    action: RuleAction,
+) -> Result<Rule, RuleError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(RuleError::Invalid("empty_name"));
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(RuleError::Invalid("name_too_long"));
+    }
+    let action = action.validate().map_err(RuleError::Invalid)?;
+    // La sérialisation d'une énumération de chaînes et d'entiers ne peut pas échouer
+    let spec = serde_json::to_string(&action).unwrap_or_default();
+
+    let id = match id {
+        Some(id) => match index.update_rule(id, name, enabled, &spec) {
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Err(RuleError::NotFound(id)),
+            other => other.map(|()| id)?,
+        },
+        None => index.insert_rule(name, enabled, &spec)?,
+    };
+    list(index)?
+        .into_iter()
+        .find(|rule| rule.id == id)
+        .ok_or(RuleError::NotFound(id))
+}
+
+pub fn delete(index: &mut SqlCipherIndex, id: i64) -> Result<(), RuleError> {
+    match index.delete_rule(id) {
+        Err(rusqlite::Error::QueryReturnedNoRows) => Err(RuleError::NotFound(id)),
+        other => Ok(other?),
+    }
+}
+
+/// Exécute toutes les règles actives. L'échec d'une règle est consigné sur la règle
+/// (`last_error`) et dans le bilan, sans interrompre les suivantes.
+pub async fn run_all(vault: &Vault) -> Result<RulesReport, VaultError> {
+    run_all_at(vault, unix_now()).await
+}
+
+/// Comme [`run_all`], à une date donnée (tests).
+pub async fn run_all_at(vault: &Vault, now: i64) -> Result<RulesReport, VaultError> {
+    let rules = list(&vault.open_index()?)?;
+
+    let mut report = RulesReport::default();
+    for rule in rules.into_iter().filter(|rule| rule.enabled) {
+        let result = match &rule.action {
+            RuleAction::ImportFolder { local_dir, vault_folder } => {
+                run_import(vault, rule.id, Path::new(local_dir), vault_folder, now)
+                    .await
+                    .map(|uploaded| report.uploaded += uploaded)
+            }
+            RuleAction::AutoTrash { vault_folder, older_than_days } => {
+                run_auto_trash(vault, vault_folder, *older_than_days, now)
+                    .await
+                    .map(|trashed| report.trashed += trashed)
+            }
+        };
+        report.rules_run += 1;
+
+        let error = result.err();
+        if let Some(detail) = &error {
+            log::warn!("Rule {} ({}) failed: {}", rule.id, rule.name, detail);
+            report.failures.push(RuleFailure {
+                rule_id: rule.id,
+                name: rule.name.clone(),
+                detail: detail.clone(),
+            });
+        }
+        vault.open_index()?.set_rule_result(rule.id, now, error.as_deref())?;
+    }
+    Ok(report)
+}
+
+/// Importe les fichiers nouveaux ou modifiés ; un fichier déjà présent au même chemin du
+/// coffre est remplacé (l'ancien est conservé comme version, voir [`Vault::put_path`]).
+async fn run_import(vault: &Vault, rule_id: i64, local_dir: &Path, vault_folder: &str, now: i64) -> Result<usize, String> {
+    let imported = vault.open_index().map_err(|e| e.to_string())?.rule_imports(rule_id).map_err(|e| e.to_string())?;
+    let candidates = import_candidates(local_dir, &imported, now).map_err(|e| format!("Local folder unreadable: {}", e))?;
+
+    let mut uploaded = 0;
+    let mut first_error = None;
+    for (path, name, size, modified) in candidates {
+        let logical_path = format!("{}/{}", vault_folder.trim_end_matches('/'), name);
+        let local_path = path.to_string_lossy().into_owned();
+        let result = async {
+            vault.put_path(&logical_path, &path).await.map_err(|e| e.to_string())?;
+            vault
+                .open_index()
+                .map_err(|e| e.to_string())?
+                .mark_rule_import(rule_id, &local_path, size, modified)
+                .map_err(|e| e.to_string())
+        }
+        .await;
+        match result {
+            Ok(()) => uploaded += 1,
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(uploaded),
+    }
+}
+
+/// Fichiers à la racine de `dir` à importer : ni cachés, ni en cours d'écriture, ni déjà importés
+/// dans cet état. Triés par nom pour un ordre d'envoi stable.
+fn import_candidates(
+    dir: &Path,
+    imported: &HashMap<String, (u64, i64)>,
+    now: i64,
+) -> std::io::Result<Vec<(PathBuf, String, u64, i64)>> {
+    let mut candidates = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if !metadata.is_file() || name.starts_with('.') {
+            continue;
+        }
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs() as i64);
+        if now - modified < STABLE_AFTER_SECS {
+            continue;
+        }
+        let path = entry.path();
+        if imported.get(path.to_string_lossy().as_ref()) == Some(&(metadata.len(), modified)) {
+            continue;
+        }
+        candidates.push((path, name, metadata.len(), modified));
+    }
+    candidates.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(candidates)
+}
+
+/// Met à la corbeille les fichiers du dossier envoyés avant la date limite.
+///
+/// Un fichier sans date distante (envoi encore en attente) est toujours conservé.
+async fn run_auto_trash(vault: &Vault, vault_folder: &str, older_than_days: u32, now: i64) -> Result<usize, String> {
+    let cutoff = now - i64::from(older_than_days) * SECS_PER_DAY;
+    let uploaded_at: HashMap<String, i64> = vault
+        .remote()
+        .list_objects()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter_map(|object| Some((remote_keys::normalize(&object.key), object.last_modified?)))
+        .collect();
+
+    let prefix = format!("{}/", vault_folder.trim_end_matches('/'));
+    let mut trashed = 0;
+    for (id, meta) in vault.list().map_err(|e| e.to_string())? {
+        if meta.encrypted_size == 0 || !meta.logical_path.starts_with(&prefix) {
+            continue;
+        }
+        if uploaded_at.get(&id).is_some_and(|at| *at < cutoff) {
+            vault.trash(&id).map_err(|e| e.to_string())?;
+            trashed += 1;
+        }
+    }
+    Ok(trashed)
+}
+
+fn folder_key(path: &str) -> String {
+    format!("/{}", path.trim().trim_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn validation_normalizes_folders_and_refuses_root_trash() {
+        let action = RuleAction::AutoTrash {
+            vault_folder: "tmp-scans/".to_string(),
+            older_than_days: 7,
+        };
+        assert_eq!(
+            action.validate(),
+            Ok(RuleAction::AutoTrash {
+                vault_folder: "/tmp-scans".to_string(),
+                older_than_days: 7
+            })
+        );
+        let root = RuleAction::AutoTrash { vault_folder: "/".to_string(), older_than_days: 7 };
+        assert_eq!(root.validate(), Err("root_folder"));
+        let zero = RuleAction::AutoTrash { vault_folder: "/a".to_string(), older_than_days: 0 };
+        assert_eq!(zero.validate(), Err("invalid_age"));
+        let relative = RuleAction::ImportFolder {
+            local_dir: "scans".to_string(),
+            vault_folder: "/".to_string(),
+        };
+        assert_eq!(relative.validate(), Err("relative_local_dir"));
+    }
+
+    #[test]
+    fn rules_round_trip_through_the_index() {
+        let dir = TempDir::new().unwrap();
+        let mut index = SqlCipherIndex::open(dir.path().join("rules.db"), &[23u8; 32]).unwrap();
+        let action = RuleAction::AutoTrash { vault_folder: "/tmp".to_string(), older_than_days: 30 };
+
+        let rule = save(&mut index, None, " Nettoyage ", true, action.clone()).unwrap();
+        assert_eq!(rule.name, "Nettoyage");
+        assert_eq!(list(&index).unwrap(), vec![rule.clone()]);
+
+        let disabled = save(&mut index, Some(rule.id), "Nettoyage", false, action.clone()).unwrap();
+        assert!(!disabled.enabled);
+        assert!(matches!(save(&mut index, Some(99), "x", true, action), Err(RuleError::NotFound(99))));
+
+        delete(&mut index, rule.id).unwrap();
+        assert!(list(&index).unwrap().is_empty());
+        assert!(matches!(delete(&mut index, rule.id), Err(RuleError::NotFound(_))));
+    }
+}
@@ -0,0 +1,273 @@
+//! Extraction du texte indexable d'un fichier, au moment de l'envoi (contenu en clair).
+//!
+//! Volontairement simple : texte brut, documents Office Open XML et OpenDocument (le XML de
+//! l'archive, balises retirées) et PDF dont le texte est encodé en chaînes littérales. Un PDF
+//! numérisé ou à polices CID ne produit rien ; le fichier reste trouvable par son nom.
+
+use flate2::read::ZlibDecoder;
+use std::io::{Cursor, Read};
+
+/// Au-delà, le fichier n'est pas lu pour l'indexation.
+pub const MAX_SOURCE_BYTES: u64 = 32 * 1024 * 1024;
+/// Texte conservé au plus par fichier, en octets.
+pub const MAX_TEXT_BYTES: usize = 512 * 1024;
+
+const TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "md", "markdown", "csv", "tsv", "log", "json", "xml", "html", "htm", "yaml", "yml", "toml", "ini",
+    "tex", "srt", "rs", "py", "js", "ts", "c", "h", "cpp", "java", "go", "sh", "sql",
+];
+
+enum Kind {
+    Text,
+    Pdf,
+    /// Archive ZIP : les parties XML qui portent le texte du document.
+    Package(fn(&str) -> bool),
+}
+
+fn kind(logical_path: &str) -> Option<Kind> {
+    let name = logical_path.rsplit('/').next().unwrap_or(logical_path);
+    let (_, extension) = name.rsplit_once('.')?;
+    let extension = extension.to_ascii_lowercase();
+    match extension.as_str() {
+        "pdf" => Some(Kind::Pdf),
+        "docx" => Some(Kind::Package(|part| part == "word/document.xml")),
+        "pptx" => Some(Kind::Package(|part| part.starts_with("ppt/slides/slide") && part.ends_with(".xml"))),
+        "xlsx" => Some(Kind::Package(|part| part == "xl/sharedStrings.xml")),
+        "odt" | "ods" | "odp" => Some(Kind::Package(|part| part == "content.xml")),
+        ext if TEXT_EXTENSIONS.contains(&ext) => Some(Kind::Text),
+        _ => None,
+    }
+}
+
+/// Le type de fichier (d'après son extension) se prête à l'extraction.
+pub fn is_extractable(logical_path: &str) -> bool {
+    kind(logical_path).is_some()
+}
+
+/// Texte du fichier, espaces normalisés et tronqué à [`MAX_TEXT_BYTES`] ; `None` si le type
+/// n'est pas pris en charge ou si rien n'a pu être extrait.
+pub fn extract_text(logical_path: &str, content: &[u8]) -> Option<String> {
+    if content.len() as u64 > MAX_SOURCE_BYTES {
+        return None;
+    }
+    let raw = match kind(logical_path)? {
+        Kind::Text => {
+            // Un fichier binaire mal nommé n'a rien à faire dans l'index
+            if content.iter().take(8192).any(|&b| b == 0) {
+                return None;
+            }
+            String::from_utf8_lossy(content).into_owned()
+        }
+        Kind::Pdf => pdf_text(content),
+        Kind::Package(wanted) => package_text(content, wanted)?,
+    };
+    let text = normalize(&raw);
+    (!text.is_empty()).then_some(text)
+}
+
+fn normalize(raw: &str) -> String {
+    let mut text = String::with_capacity(raw.len().min(MAX_TEXT_BYTES));
+    for word in raw.split_whitespace() {
+        if text.len() + word.len() + 1 > MAX_TEXT_BYTES {
+            break;
+        }
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(word);
+    }
+    text
+}
+
+fn package_text(content: &[u8], wanted: fn(&str) -> bool) -> Option<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(content)).ok()?;
+    let mut parts: Vec<String> = archive.file_names().filter(|name| wanted(name)).map(str::to_string).collect();
+    // slide2 avant slide10
+    parts.sort_by_key(|name| (name.len(), name.clone()));
+    let mut text = String::new();
+    for part in parts {
+        let mut xml = String::new();
+        let entry = archive.by_name(&part).ok()?;
+        entry.take(MAX_SOURCE_BYTES).read_to_string(&mut xml).ok()?;
+        text.push_str(&xml_text(&xml));
+        text.push(' ');
+        if text.len() > MAX_TEXT_BYTES {
+            break;
+        }
+    }
+    Some(text)
+}
+
+/// Texte d'un document XML : balises retirées, une espace à chaque fin de paragraphe, de
+/// cellule ou de ligne (les fragments d'un même mot, `<w:t>` successifs, restent collés).
+fn xml_text(xml: &str) -> String {
+    let mut text = String::with_capacity(xml.len() / 2);
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&decode_entities(&rest[..start]));
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim_start_matches('/');
+        let name = tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("");
+        let local = name.rsplit(':').next().unwrap_or(name);
+        if matches!(local, "p" | "br" | "tab" | "tc" | "h" | "si" | "s" | "line-break") {
+            text.push(' ');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text
+}
+
+fn decode_entities(raw: &str) -> String {
+    if !raw.contains('&') {
+        return raw.to_string();
+    }
+    raw.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#160;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Chaînes littérales des opérateurs de texte (`Tj`, `TJ`, `'`, `"`) de chaque flux, décompressé
+/// s'il est en `FlateDecode`.
+fn pdf_text(content: &[u8]) -> String {
+    let mut text = String::new();
+    let mut rest = content;
+    while let Some(start) = find(rest, b"stream") {
+        let dictionary = &rest[..start];
+        let mut body = &rest[start + b"stream".len()..];
+        body = body.strip_prefix(b"\r").unwrap_or(body);
+        body = body.strip_prefix(b"\n").unwrap_or(body);
+        let Some(end) = find(body, b"endstream") else {
+            break;
+        };
+        let data = &body[..end];
+        // Le dictionnaire du flux précède immédiatement `stream`
+        let header = &dictionary[dictionary.len().saturating_sub(256)..];
+        if find(header, b"/FlateDecode").is_some() {
+            let mut decoded = Vec::new();
+            if ZlibDecoder::new(data).take(MAX_SOURCE_BYTES).read_to_end(&mut decoded).is_ok() {
+                content_stream_text(&decoded, &mut text);
+            }
+        } else {
+            content_stream_text(data, &mut text);
+        }
+        if text.len() > MAX_TEXT_BYTES {
+            break;
+        }
+        rest = &body[end + b"endstream".len()..];
+    }
+    text
+}
+
+/// Chaînes d'un flux de contenu, entre `BT` et `ET` ; les morceaux d'un `TJ` restent collés.
+fn content_stream_text(stream: &[u8], text: &mut String) {
+    let mut in_text = false;
+    let mut i = 0;
+    while i < stream.len() {
+        match stream[i] {
+            b'(' if in_text => {
+                let (string, next) = literal_string(stream, i + 1);
+                text.push_str(&string);
+                i = next;
+                continue;
+            }
+            b'B' if stream[i..].starts_with(b"BT") && is_operator_at(stream, i, 2) => in_text = true,
+            b'E' if stream[i..].starts_with(b"ET") && is_operator_at(stream, i, 2) => {
+                in_text = false;
+                text.push(' ');
+            }
+            b'T' if in_text && (stream[i..].starts_with(b"Tj") || stream[i..].starts_with(b"TJ")) => text.push(' '),
+            b'T' if in_text && (stream[i..].starts_with(b"T*") || stream[i..].starts_with(b"Td")) => text.push(' '),
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+fn is_operator_at(stream: &[u8], at: usize, len: usize) -> bool {
+    let before = at.checked_sub(1).map_or(true, |b| stream[b].is_ascii_whitespace());
+    let after = stream.get(at + len).map_or(true, |c| c.is_ascii_whitespace());
+    before && after
+}
+
+/// Chaîne littérale PDF commençant après `(` ; renvoie son texte et la position qui la suit.
+fn literal_string(stream: &[u8], mut i: usize) -> (String, usize) {
+    let mut bytes = Vec::new();
+    let mut depth = 0;
+    while i < stream.len() {
+        match stream[i] {
+            b'\\' => {
+                i += 1;
+                match stream.get(i) {
+                    Some(b'n') | Some(b'r') | Some(b't') => bytes.push(b' '),
+                    Some(c @ b'0'..=b'7') => {
+                        let digits = stream[i..].iter().take(3).take_while(|d| (b'0'..=b'7').contains(d)).count();
+                        let octal = std::str::from_utf8(&stream[i..i + digits]).unwrap_or("0");
+                        bytes.push(u8::from_str_radix(octal, 8).unwrap_or(*c));
+                        i += digits - 1;
+                    }
+                    Some(&c) => bytes.push(c),
+                    None => {}
+                }
+            }
+            b'(' => {
+                depth += 1;
+                bytes.push(b'(');
+            }
+            b')' if depth == 0 => return (decode_pdf_string(&bytes), i + 1),
+            b')' => {
+                depth -= 1;
+                bytes.push(b')');
+            }
+            c => bytes.push(c),
+        }
+        i += 1;
+    }
+    (decode_pdf_string(&bytes), i)
+}
+
+/// UTF-16BE avec BOM, sinon Latin-1 (proche de PDFDocEncoding pour le texte courant).
+fn decode_pdf_string(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = utf16.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+        return String::from_utf16_lossy(&units);
+    }
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    #[test]
+    fn extracts_text_from_plain_office_and_pdf_files() {
+        assert_eq!(extract_text("/notes.md", b"  Bonjour\n\nle   monde ").as_deref(), Some("Bonjour le monde"));
+        assert_eq!(extract_text("/photo.jpg", b"Bonjour"), None);
+        assert_eq!(extract_text("/faux.txt", b"\0\0binaire"), None);
+
+        let mut docx = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        docx.start_file("word/document.xml", zip::write::SimpleFileOptions::default()).unwrap();
+        docx.write_all(b"<w:document><w:p><w:r><w:t>Contrac</w:t></w:r><w:r><w:t>tuel</w:t></w:r></w:p><w:p><w:t>L&apos;annexe</w:t></w:p></w:document>")
+            .unwrap();
+        let docx = docx.finish().unwrap().into_inner();
+        assert_eq!(extract_text("/Contrat.DOCX", &docx).as_deref(), Some("Contractuel L'annexe"));
+
+        let mut stream = ZlibEncoder::new(Vec::new(), Compression::default());
+        stream.write_all(b"BT /F1 12 Tf 72 712 Td (Facture n\\260 42) Tj T* [(Mon)-20(tant)] TJ ET").unwrap();
+        let stream = stream.finish().unwrap();
+        let mut pdf = b"%PDF-1.4\n4 0 obj << /Length 60 /Filter /FlateDecode >>\nstream\n".to_vec();
+        pdf.extend_from_slice(&stream);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n%%EOF");
+        assert_eq!(extract_text("/facture.pdf", &pdf).as_deref(), Some("Facture n° 42 Montant"));
+    }
+}
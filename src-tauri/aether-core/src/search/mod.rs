@@ -0,0 +1,241 @@
+//! Recherche plein texte dans le contenu des fichiers (optionnelle).
+//!
+//! Le texte extrait à l'envoi ([`extract`]) est indexé en FTS5 dans une base SQLCipher
+//! distincte de l'index (`index.content.db`), chiffrée sous une clé dérivée de la MasterKey :
+//! les clés de l'index, confiées au mode invité, ne donnent pas accès au contenu. La base ne
+//! contient rien tant que l'utilisateur n'a pas activé la recherche ; la désactiver la
+//! supprime. Seuls les fichiers envoyés après l'activation sont indexés.
+
+pub mod extract;
+
+use hkdf::Hkdf;
+use rusqlite::{params, Connection, Result as SqliteResult};
+use serde::Serialize;
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+use crate::index::{sqlcipher::SqlCipherIndex, FileId};
+
+const CONTENT_KEY_INFO: &[u8] = b"aether-drive:content-index-key:v1";
+/// Clé `index_metadata` de l'activation (un octet).
+pub(crate) const ENABLED_META_KEY: &str = "content_search";
+/// Résultats renvoyés au plus par une recherche.
+pub const MAX_RESULTS: usize = 200;
+
+/// Résultat de recherche ; `snippet` est présent quand le contenu correspond.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchHit {
+    pub file_id: FileId,
+    pub logical_path: String,
+    /// Extrait du contenu autour des termes trouvés, délimités par `[` et `]`.
+    pub snippet: Option<String>,
+}
+
+/// Base du texte des fichiers, à côté de l'index.
+pub fn content_db_path(index_path: &Path) -> PathBuf {
+    index_path.with_extension("content.db")
+}
+
+pub fn enabled(index: &SqlCipherIndex) -> SqliteResult<bool> {
+    Ok(index.get_meta(ENABLED_META_KEY)?.is_some_and(|raw| raw.first() == Some(&1)))
+}
+
+/// Active ou désactive la recherche dans le contenu ; la désactiver supprime le texte indexé.
+pub fn set_enabled(index: &mut SqlCipherIndex, index_path: &Path, enable: bool) -> std::io::Result<()> {
+    if enable {
+        index.put_meta(ENABLED_META_KEY, &[1]).map_err(std::io::Error::other)?;
+        return Ok(());
+    }
+    index.delete_meta(ENABLED_META_KEY).map_err(std::io::Error::other)?;
+    discard(index_path)
+}
+
+/// Supprime la base du contenu d'un index (désactivation, index supprimé ou archivé).
+pub fn discard(index_path: &Path) -> std::io::Result<()> {
+    let path = content_db_path(index_path);
+    for file in [path.clone(), path.with_extension("db-wal"), path.with_extension("db-journal")] {
+        match std::fs::remove_file(&file) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Base FTS5 du texte extrait, une ligne par fichier.
+pub struct ContentIndex {
+    conn: Connection,
+}
+
+impl ContentIndex {
+    pub fn open(index_path: &Path, master_key: &[u8]) -> SqliteResult<Self> {
+        let hkdf = Hkdf::<Sha256>::new(None, master_key);
+        let mut key = Zeroizing::new([0u8; 32]);
+        hkdf.expand(CONTENT_KEY_INFO, key.as_mut()).map_err(|_| rusqlite::Error::InvalidQuery)?;
+
+        let conn = Connection::open(content_db_path(index_path))?;
+        conn.pragma_update(None, "key", format!("x'{}'", hex::encode(key.as_ref())))?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS content_fts USING fts5(
+                file_id UNINDEXED,
+                body,
+                tokenize = 'unicode61 remove_diacritics 2'
+            )",
+            [],
+        )?;
+        Ok(Self { conn })
+    }
+
+    /// Enregistre (ou remplace) le texte d'un fichier.
+    pub fn set_text(&mut self, file_id: &FileId, text: &str) -> SqliteResult<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM content_fts WHERE file_id = ?1", [file_id])?;
+        tx.execute("INSERT INTO content_fts (file_id, body) VALUES (?1, ?2)", params![file_id, text])?;
+        tx.commit()
+    }
+
+    pub fn remove(&mut self, file_id: &FileId) -> SqliteResult<()> {
+        self.conn.execute("DELETE FROM content_fts WHERE file_id = ?1", [file_id])?;
+        Ok(())
+    }
+
+    pub fn len(&self) -> SqliteResult<usize> {
+        self.conn.query_row("SELECT COUNT(*) FROM content_fts", [], |row| row.get::<_, i64>(0)).map(|n| n as usize)
+    }
+
+    pub fn is_empty(&self) -> SqliteResult<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Fichiers dont le texte contient tous les termes (préfixes compris), du plus pertinent au
+    /// moins pertinent, avec un extrait.
+    pub fn search(&self, query: &str, limit: usize) -> SqliteResult<Vec<(FileId, String)>> {
+        let Some(expression) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let mut stmt = self.conn.prepare(
+            "SELECT file_id, snippet(content_fts, 1, '[', ']', '…', 12) FROM content_fts
+             WHERE content_fts MATCH ?1 ORDER BY rank LIMIT ?2",
+        )?;
+        let hits = stmt.query_map(params![expression, limit as i64], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        hits.collect()
+    }
+}
+
+/// Expression FTS5 : chaque mot entre guillemets (la syntaxe FTS de l'utilisateur n'est pas
+/// interprétée), en préfixe, tous requis.
+fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"*", term))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Fichiers dont le nom ou (si `content` est fourni) le contenu correspond à `query`.
+///
+/// Le texte d'un fichier qui n'est plus ni dans l'index, ni dans la corbeille, ni parmi les
+/// versions est retiré au passage.
+pub fn search(
+    index: &SqlCipherIndex,
+    content: Option<&mut ContentIndex>,
+    query: &str,
+) -> SqliteResult<Vec<SearchHit>> {
+    let needle = query.trim().to_lowercase();
+    if needle.is_empty() {
+        return Ok(Vec::new());
+    }
+    let files = index.list_all()?;
+    let mut hits: Vec<SearchHit> = Vec::new();
+    if let Some(content) = content {
+        let mut stale = Vec::new();
+        for (file_id, snippet) in content.search(query, MAX_RESULTS)? {
+            match files.iter().find(|(id, _)| *id == file_id) {
+                Some((_, meta)) => hits.push(SearchHit {
+                    logical_path: meta.logical_path.clone(),
+                    file_id,
+                    snippet: Some(snippet),
+                }),
+                None => stale.push(file_id),
+            }
+        }
+        if !stale.is_empty() {
+            let retained: HashSet<FileId> = index
+                .list_trash()?
+                .into_iter()
+                .map(|(id, _, _)| id)
+                .chain(index.list_all_versions()?.into_iter().map(|version| version.id))
+                .collect();
+            for file_id in stale.iter().filter(|id| !retained.contains(*id)) {
+                content.remove(file_id)?;
+            }
+        }
+    }
+    let found: HashSet<FileId> = hits.iter().map(|hit| hit.file_id.clone()).collect();
+    let mut by_name: Vec<SearchHit> = files
+        .into_iter()
+        .filter(|(id, meta)| {
+            let name = meta.logical_path.trim_end_matches('/').rsplit('/').next().unwrap_or("");
+            !found.contains(id) && name.to_lowercase().contains(&needle)
+        })
+        .map(|(file_id, meta)| SearchHit { file_id, logical_path: meta.logical_path, snippet: None })
+        .collect();
+    by_name.sort_by(|a, b| a.logical_path.cmp(&b.logical_path));
+    hits.extend(by_name);
+    hits.truncate(MAX_RESULTS);
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::FileMetadata;
+    use tempfile::TempDir;
+
+    #[test]
+    fn content_search_matches_text_and_drops_purged_files() {
+        let dir = TempDir::new().unwrap();
+        let index_path = dir.path().join("index.db");
+        let master_key = [9u8; 32];
+        let mut index = SqlCipherIndex::open(&index_path, &master_key).unwrap();
+        assert!(!enabled(&index).unwrap());
+        set_enabled(&mut index, &index_path, true).unwrap();
+        assert!(enabled(&index).unwrap());
+
+        for (id, path) in [("a", "/Contrats/bail.pdf"), ("b", "/notes.txt"), ("c", "/Échéancier.txt")] {
+            let meta = FileMetadata { logical_path: path.to_string(), encrypted_size: 10 };
+            index.upsert(id.to_string(), meta).unwrap();
+        }
+        let mut content = ContentIndex::open(&index_path, &master_key).unwrap();
+        content.set_text(&"a".to_string(), "Le loyer mensuel est fixé à 900 euros").unwrap();
+        content.set_text(&"b".to_string(), "Penser au dépôt de garantie").unwrap();
+
+        let hits = search(&index, Some(&mut content), "loy").unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].file_id, "a");
+        assert!(hits[0].snippet.as_deref().unwrap().contains("[loyer]"));
+        // Accents ignorés, noms de fichiers compris, syntaxe FTS neutralisée
+        let hits = search(&index, Some(&mut content), "depot").unwrap();
+        assert_eq!(hits.iter().map(|hit| hit.file_id.as_str()).collect::<Vec<_>>(), ["b"]);
+        let hits = search(&index, Some(&mut content), "échéancier").unwrap();
+        assert_eq!(hits[0].snippet, None);
+        assert!(search(&index, Some(&mut content), "\"loyer OR").unwrap().is_empty());
+
+        // Un fichier purgé disparaît de la base du contenu au passage
+        index.remove(&"b".to_string()).unwrap();
+        assert!(search(&index, Some(&mut content), "garantie").unwrap().is_empty());
+        assert_eq!(content.len().unwrap(), 1);
+
+        // La clé de l'index ne suffit pas à lire le contenu
+        drop(content);
+        assert!(SqlCipherIndex::open(content_db_path(&index_path), &master_key).is_err());
+
+        set_enabled(&mut index, &index_path, false).unwrap();
+        assert!(!content_db_path(&index_path).exists());
+    }
+}
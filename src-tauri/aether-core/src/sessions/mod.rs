@@ -0,0 +1,279 @@
+//! Détection des sessions simultanées d'un même coffre sur plusieurs appareils.
+//!
+//! Tant qu'il est déverrouillé, chaque appareil publie régulièrement un battement sous
+//! [`SESSIONS_PREFIX`] : un petit objet chiffré (clé dérivée de la MasterKey) qui indique
+//! depuis combien de temps l'appareil n'a plus modifié l'index. Les battements des autres
+//! appareils disent qui est en ligne et qui modifie le coffre en ce moment ; dans ce cas,
+//! le journal de synchronisation passe en [`ConflictMode::Conservative`].
+//!
+//! Les âges sont mesurés sur l'horloge du bucket (date d'écriture des objets) quand il la
+//! fournit : une horloge d'appareil décalée ne fausse pas la détection.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+use crate::clock::unix_now;
+use crate::crypto::sealed::{SealError, SealedObjects};
+use crate::crypto::CryptoError;
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::index::ConflictMode;
+use crate::journal;
+use crate::storj::{RemoteObject, StorjError};
+use crate::vault::{Vault, VaultError};
+
+/// Préfixe des battements dans le bucket (sous le préfixe réservé).
+pub const SESSIONS_PREFIX: &str = ".aether/sessions/";
+/// Intervalle entre deux battements.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+/// Un appareil dont le dernier battement est plus ancien n'est plus en ligne.
+pub const ACTIVE_WINDOW_SECS: i64 = 3 * 60;
+/// Un appareil qui a modifié l'index depuis moins longtemps est en train de le modifier.
+pub const MUTATION_WINDOW_SECS: i64 = 10 * 60;
+/// Battement abandonné (appareil perdu, arrêt brutal) : retiré du bucket au passage.
+const STALE_SECS: i64 = 7 * 24 * 60 * 60;
+
+const MAX_LABEL_CHARS: usize = 64;
+const STATE_META_KEY: &str = "session_state";
+const SESSION_KEY_INFO: &[u8] = b"aether-drive:session-key:v1";
+const SESSION_AAD: &[u8] = b"aether-drive:session:v1:";
+/// Battements liés à leur clé : ils ne peuvent pas être présentés pour un autre appareil.
+const HEARTBEATS: SealedObjects = SealedObjects::new(SESSION_KEY_INFO, SESSION_AAD);
+
+#[derive(Debug)]
+pub enum SessionError {
+    Crypto(CryptoError),
+    Vault(VaultError),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Crypto(e) => write!(f, "{}", e),
+            SessionError::Vault(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<CryptoError> for SessionError {
+    fn from(e: CryptoError) -> Self {
+        SessionError::Crypto(e)
+    }
+}
+
+impl From<SealError> for SessionError {
+    fn from(e: SealError) -> Self {
+        match e {
+            SealError::Crypto(e) => SessionError::Crypto(e),
+            // Seul l'encodage peut échouer au scellement, et un battement s'encode toujours
+            SealError::Invalid(_) => SessionError::Crypto(CryptoError::Aead),
+        }
+    }
+}
+
+impl From<VaultError> for SessionError {
+    fn from(e: VaultError) -> Self {
+        SessionError::Vault(e)
+    }
+}
+
+impl From<StorjError> for SessionError {
+    fn from(e: StorjError) -> Self {
+        SessionError::Vault(e.into())
+    }
+}
+
+impl From<rusqlite::Error> for SessionError {
+    fn from(e: rusqlite::Error) -> Self {
+        SessionError::Vault(e.into())
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+/// Contenu chiffré d'un battement.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Heartbeat {
+    /// Identifiant de l'appareil dans le journal de synchronisation.
+    device: String,
+    label: String,
+    /// Horloge de l'appareil, en repli si le bucket ne date pas ses objets.
+    sent_at: i64,
+    /// Secondes écoulées depuis la dernière modification locale de l'index, à l'envoi.
+    mutation_age_secs: Option<i64>,
+}
+
+/// Session de cet appareil, conservée dans l'index.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct SessionState {
+    /// Dernière opération locale du journal déjà prise en compte.
+    observed_seq: Option<i64>,
+    last_mutation_at: Option<i64>,
+    mode: ConflictMode,
+    checked_at: Option<i64>,
+}
+
+/// Autre appareil en ligne sur ce coffre.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PeerSession {
+    pub device: String,
+    /// Nom affiché de l'appareil.
+    pub label: String,
+    /// Secondes depuis son dernier battement.
+    pub seen_secs_ago: i64,
+    /// Secondes depuis sa dernière modification de l'index, s'il en a fait une.
+    pub mutated_secs_ago: Option<i64>,
+    /// Il modifie le coffre en ce moment (voir [`MUTATION_WINDOW_SECS`]).
+    pub mutating: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionStatus {
+    pub device: String,
+    pub peers: Vec<PeerSession>,
+    pub mode: ConflictMode,
+}
+
+fn object_key(device: &str) -> String {
+    format!("{}{}.session", SESSIONS_PREFIX, device)
+}
+
+fn load_state(index: &SqlCipherIndex) -> rusqlite::Result<SessionState> {
+    Ok(index.get_meta_json(STATE_META_KEY)?.unwrap_or_default())
+}
+
+fn save_state(index: &mut SqlCipherIndex, state: &SessionState) -> rusqlite::Result<()> {
+    index.put_meta_json(STATE_META_KEY, state)
+}
+
+/// Mode de résolution des conflits que le journal doit appliquer : conservateur tant qu'un
+/// autre appareil a été vu en train de modifier le coffre récemment.
+pub fn conflict_mode(index: &SqlCipherIndex) -> rusqlite::Result<ConflictMode> {
+    let state = load_state(index)?;
+    let recent = state.checked_at.is_some_and(|checked_at| unix_now() - checked_at < MUTATION_WINDOW_SECS);
+    Ok(if recent { state.mode } else { ConflictMode::Normal })
+}
+
+/// Publie le battement de cet appareil (`label` : nom affiché) et relève ceux des autres.
+pub async fn heartbeat(vault: &Vault, label: &str) -> Result<SessionStatus, SessionError> {
+    let now = unix_now();
+    let (device, mut state) = {
+        let mut index = vault.open_index()?;
+        let device = journal::device_id(&mut index)?;
+        let mut state = load_state(&index)?;
+        let seq = index.journal_last_seq()?;
+        // Le premier battement ne fait que relever le compteur
+        if state.observed_seq.is_some_and(|observed| seq > observed) {
+            state.last_mutation_at = Some(now);
+        }
+        state.observed_seq = Some(seq);
+        (device, state)
+    };
+
+    let key = object_key(&device);
+    let label: String = label.chars().filter(|c| !c.is_control()).take(MAX_LABEL_CHARS).collect();
+    let beat = Heartbeat {
+        device: device.clone(),
+        label,
+        sent_at: now,
+        mutation_age_secs: state.last_mutation_at.map(|at| now - at),
+    };
+    vault.remote().upload_file(&key, &HEARTBEATS.seal(vault.master_key(), &key, &beat)?).await?;
+
+    let objects = vault.remote().list_prefix(SESSIONS_PREFIX).await?;
+    // Horloge de référence : la date d'écriture de notre propre battement
+    let reference = objects.iter().find(|object| object.key == key).and_then(|object| object.last_modified);
+    let mut peers = Vec::new();
+    for object in objects.iter().filter(|object| object.key != key) {
+        if let Some(peer) = read_peer(vault, object, reference, now).await? {
+            peers.push(peer);
+        }
+    }
+    peers.sort_by(|a, b| a.label.cmp(&b.label).then(a.device.cmp(&b.device)));
+
+    state.mode = if peers.iter().any(|peer| peer.mutating) {
+        ConflictMode::Conservative
+    } else {
+        ConflictMode::Normal
+    };
+    state.checked_at = Some(now);
+    save_state(&mut vault.open_index()?, &state)?;
+    Ok(SessionStatus { device, peers, mode: state.mode })
+}
+
+/// Battement d'un autre appareil, s'il est encore en ligne. Un battement abandonné depuis
+/// longtemps est retiré du bucket.
+async fn read_peer(
+    vault: &Vault,
+    object: &RemoteObject,
+    reference: Option<i64>,
+    now: i64,
+) -> Result<Option<PeerSession>, SessionError> {
+    let sealed = match vault.remote().download_file(&object.key).await {
+        Ok(sealed) => sealed,
+        // Session terminée entre la liste et la lecture
+        Err(StorjError::NotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let Ok(beat) = HEARTBEATS.open::<Heartbeat>(vault.master_key(), &object.key, &sealed) else {
+        log::warn!("Ignoring unreadable session heartbeat {}", object.key);
+        return Ok(None);
+    };
+    let age = match (reference, object.last_modified) {
+        (Some(reference), Some(written)) => reference - written,
+        _ => now - beat.sent_at,
+    }
+    .max(0);
+    if age >= STALE_SECS {
+        if let Err(e) = vault.remote().delete_file(&object.key).await {
+            log::warn!("Failed to remove stale session heartbeat {}: {}", object.key, e);
+        }
+        return Ok(None);
+    }
+    if age >= ACTIVE_WINDOW_SECS {
+        return Ok(None);
+    }
+    let mutated_secs_ago = beat.mutation_age_secs.map(|mutation_age| mutation_age.max(0) + age);
+    Ok(Some(PeerSession {
+        device: beat.device,
+        label: beat.label,
+        seen_secs_ago: age,
+        mutated_secs_ago,
+        mutating: mutated_secs_ago.is_some_and(|secs| secs < MUTATION_WINDOW_SECS),
+    }))
+}
+
+/// Termine la session de cet appareil (verrouillage) : son battement est retiré du bucket.
+pub async fn end(vault: &Vault) -> Result<(), SessionError> {
+    let device = journal::device_id(&mut vault.open_index()?)?;
+    match vault.remote().delete_file(&object_key(&device)).await {
+        Ok(()) | Err(StorjError::NotFound) => {}
+        Err(e) => return Err(e.into()),
+    }
+    let mut index = vault.open_index()?;
+    let state = SessionState {
+        mode: ConflictMode::Normal,
+        checked_at: None,
+        ..load_state(&index)?
+    };
+    save_state(&mut index, &state)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::MasterKey;
+
+    #[test]
+    fn heartbeats_are_bound_to_their_device_and_vault() {
+        let master_key = MasterKey::from_vec(vec![5u8; 32]);
+        let key = object_key("aaaa");
+        let beat = Heartbeat { device: "aaaa".to_string(), label: "Laptop".to_string(), sent_at: 1, mutation_age_secs: Some(3) };
+        let sealed = HEARTBEATS.seal(&master_key, &key, &beat).unwrap();
+        assert_eq!(HEARTBEATS.open::<Heartbeat>(&master_key, &key, &sealed).unwrap().label, "Laptop");
+        // Présenté sous la clé d'un autre appareil, ou lu avec une autre MasterKey
+        assert!(HEARTBEATS.open::<Heartbeat>(&master_key, &object_key("bbbb"), &sealed).is_err());
+        assert!(HEARTBEATS.open::<Heartbeat>(&MasterKey::from_vec(vec![6u8; 32]), &key, &sealed).is_err());
+    }
+}
@@ -0,0 +1,240 @@
+//! Assistant de première configuration : création du coffre, configuration du stockage
+//! distant, test de connexion, kit de récupération facultatif puis import initial.
+//!
+//! Chaque étape n'est validée qu'après avoir réussi, et l'état est persisté
+//! (`setup_wizard.json`) : une configuration interrompue reprend à l'étape où elle s'est
+//! arrêtée au lieu de laisser l'application à moitié configurée. Le fichier ne contient
+//! que l'avancement et l'empreinte publique du coffre, lisibles coffre verrouillé.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::export::write_atomic;
+
+/// Fichier de l'assistant, dans le dossier de données de l'application.
+pub const SETUP_FILE: &str = "setup_wizard.json";
+
+#[derive(Debug)]
+pub enum SetupError {
+    /// Étape refusée (`reason` : code stable).
+    Invalid(&'static str),
+    Io(std::io::Error),
+    State(String),
+}
+
+impl fmt::Display for SetupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetupError::Invalid(reason) => write!(f, "Invalid setup step: {}", reason),
+            SetupError::Io(e) => write!(f, "IO error: {}", e),
+            SetupError::State(msg) => write!(f, "Setup state error: {}", msg),
+        }
+    }
+}
+
+impl From<std::io::Error> for SetupError {
+    fn from(e: std::io::Error) -> Self {
+        SetupError::Io(e)
+    }
+}
+
+impl std::error::Error for SetupError {}
+
+/// Étapes de l'assistant, dans l'ordre.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStep {
+    CreateVault,
+    ConfigureBackend,
+    TestConnection,
+    RecoveryKit,
+    InitialImport,
+    Done,
+}
+
+impl SetupStep {
+    fn next(self) -> Self {
+        match self {
+            SetupStep::CreateVault => SetupStep::ConfigureBackend,
+            SetupStep::ConfigureBackend => SetupStep::TestConnection,
+            SetupStep::TestConnection => SetupStep::RecoveryKit,
+            SetupStep::RecoveryKit => SetupStep::InitialImport,
+            SetupStep::InitialImport | SetupStep::Done => SetupStep::Done,
+        }
+    }
+}
+
+/// Avancement de l'assistant, persisté dans [`SETUP_FILE`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupWizard {
+    /// Prochaine étape à réussir.
+    step: SetupStep,
+    /// Empreinte du coffre créé par l'assistant : les étapes suivantes s'y appliquent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vault_fingerprint: Option<String>,
+    /// Kit de récupération exporté (`true`) ou étape passée (`false`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recovery_kit: Option<bool>,
+    /// Fichiers traités par l'import initial en cours.
+    #[serde(default)]
+    imported_files: u64,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl SetupWizard {
+    /// Charge l'état de `data_dir`. Sans fichier, l'assistant commence au début, sauf si un
+    /// coffre existe déjà (`vault_exists`) : il a été configuré avant l'assistant.
+    ///
+    /// Un assistant qui commence est enregistré aussitôt : un arrêt juste après la création
+    /// du coffre ne le fait pas passer pour configuré.
+    pub fn load(data_dir: &Path, vault_exists: bool) -> Result<Self, SetupError> {
+        let path = data_dir.join(SETUP_FILE);
+        if path.exists() {
+            let mut wizard = serde_json::from_slice::<SetupWizard>(&fs::read(&path)?)
+                .map_err(|e| SetupError::State(e.to_string()))?;
+            wizard.path = path;
+            return Ok(wizard);
+        }
+        let wizard = SetupWizard {
+            step: if vault_exists { SetupStep::Done } else { SetupStep::CreateVault },
+            vault_fingerprint: None,
+            recovery_kit: None,
+            imported_files: 0,
+            path,
+        };
+        if !vault_exists {
+            wizard.save()?;
+        }
+        Ok(wizard)
+    }
+
+    pub fn step(&self) -> SetupStep {
+        self.step
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.step == SetupStep::Done
+    }
+
+    pub fn imported_files(&self) -> u64 {
+        self.imported_files
+    }
+
+    /// Vérifie que `step` peut être tentée : l'étape en cours, ou une étape déjà réussie à
+    /// refaire (identifiants à corriger, par exemple). La création du coffre ne se refait
+    /// pas depuis l'assistant, et rien ne se fait une fois la configuration terminée.
+    pub fn begin(&self, step: SetupStep) -> Result<(), SetupError> {
+        if self.is_done() {
+            return Err(SetupError::Invalid("finished"));
+        }
+        if step == SetupStep::Done || step > self.step {
+            return Err(SetupError::Invalid("out_of_order"));
+        }
+        if step == SetupStep::CreateVault && self.step != SetupStep::CreateVault {
+            return Err(SetupError::Invalid("vault_created"));
+        }
+        Ok(())
+    }
+
+    /// Vérifie que le coffre déverrouillé est celui créé par l'assistant.
+    pub fn check_vault(&self, fingerprint: &str) -> Result<(), SetupError> {
+        match &self.vault_fingerprint {
+            Some(expected) if expected != fingerprint => Err(SetupError::Invalid("other_vault")),
+            _ => Ok(()),
+        }
+    }
+
+    /// Valide la création du coffre.
+    pub fn complete_vault(&mut self, fingerprint: &str) -> Result<(), SetupError> {
+        self.begin(SetupStep::CreateVault)?;
+        self.vault_fingerprint = Some(fingerprint.to_string());
+        self.advance(SetupStep::CreateVault)
+    }
+
+    /// Valide l'étape du kit de récupération, exporté ou passé.
+    pub fn complete_recovery_kit(&mut self, exported: bool) -> Result<(), SetupError> {
+        self.begin(SetupStep::RecoveryKit)?;
+        self.recovery_kit = Some(exported);
+        self.advance(SetupStep::RecoveryKit)
+    }
+
+    /// Valide `step` ; l'étape suivante devient la prochaine à réussir. Refaire une étape
+    /// déjà réussie fait donc repasser par les suivantes (nouveau test de connexion après
+    /// un changement d'identifiants).
+    pub fn complete(&mut self, step: SetupStep) -> Result<(), SetupError> {
+        if matches!(step, SetupStep::CreateVault | SetupStep::RecoveryKit) {
+            return Err(SetupError::Invalid("out_of_order"));
+        }
+        self.begin(step)?;
+        self.advance(step)
+    }
+
+    /// Enregistre l'avancement de l'import initial : `count` fichiers traités. Une reprise
+    /// repart du premier fichier ; ceux déjà envoyés, inchangés, ne sont pas renvoyés.
+    pub fn set_imported(&mut self, count: u64) -> Result<(), SetupError> {
+        self.begin(SetupStep::InitialImport)?;
+        self.imported_files = count;
+        self.save()
+    }
+
+    fn advance(&mut self, step: SetupStep) -> Result<(), SetupError> {
+        self.step = step.next();
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), SetupError> {
+        // La sérialisation d'une structure de chaînes ne peut pas échouer
+        let raw = serde_json::to_vec_pretty(self).unwrap_or_default();
+        write_atomic(&self.path, &raw).map_err(|e| SetupError::State(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn steps_run_in_order_and_resume_after_a_restart() {
+        let dir = tempdir().unwrap();
+        let mut wizard = SetupWizard::load(dir.path(), false).unwrap();
+        assert_eq!(wizard.step(), SetupStep::CreateVault);
+        // Coffre créé mais arrêt avant la validation de l'étape : l'assistant n'est pas sauté
+        assert_eq!(SetupWizard::load(dir.path(), true).unwrap().step(), SetupStep::CreateVault);
+        assert!(matches!(wizard.complete(SetupStep::ConfigureBackend), Err(SetupError::Invalid("out_of_order"))));
+
+        wizard.complete_vault("abcd").unwrap();
+        wizard.complete(SetupStep::ConfigureBackend).unwrap();
+        // Interrompu avant le test de connexion : la reprise y revient
+        let mut wizard = SetupWizard::load(dir.path(), true).unwrap();
+        assert_eq!(wizard.step(), SetupStep::TestConnection);
+        assert!(matches!(wizard.check_vault("other"), Err(SetupError::Invalid("other_vault"))));
+        wizard.check_vault("abcd").unwrap();
+        assert!(matches!(wizard.complete_vault("abcd"), Err(SetupError::Invalid("vault_created"))));
+
+        wizard.complete(SetupStep::TestConnection).unwrap();
+        wizard.complete_recovery_kit(false).unwrap();
+        // Identifiants corrigés : la connexion doit être testée de nouveau
+        wizard.complete(SetupStep::ConfigureBackend).unwrap();
+        assert_eq!(wizard.step(), SetupStep::TestConnection);
+        wizard.complete(SetupStep::TestConnection).unwrap();
+        wizard.complete_recovery_kit(true).unwrap();
+
+        wizard.set_imported(3).unwrap();
+        let mut wizard = SetupWizard::load(dir.path(), true).unwrap();
+        assert_eq!(wizard.imported_files(), 3);
+        wizard.complete(SetupStep::InitialImport).unwrap();
+        assert!(wizard.is_done());
+        assert!(matches!(wizard.begin(SetupStep::ConfigureBackend), Err(SetupError::Invalid("finished"))));
+    }
+
+    #[test]
+    fn a_vault_configured_before_the_wizard_skips_it() {
+        let dir = tempdir().unwrap();
+        assert!(SetupWizard::load(dir.path(), true).unwrap().is_done());
+        assert!(!dir.path().join(SETUP_FILE).exists());
+    }
+}
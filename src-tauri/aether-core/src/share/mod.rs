@@ -0,0 +1,286 @@
+//! Liens de partage `aether://share/<jeton>`.
+//!
+//! L'émetteur re-chiffre le fichier avec une clé de partage aléatoire (la MasterKey ne
+//! quitte jamais le coffre) et publie l'enveloppe à une URL HTTPS. Le jeton porte cette
+//! URL, la clé, le nom du fichier et l'empreinte SHA-256 de l'enveloppe : le destinataire
+//! télécharge, vérifie l'empreinte, puis déchiffre.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use zeroize::Zeroizing;
+
+/// Schéma d'URL enregistré auprès du système.
+pub const SCHEME: &str = "aether";
+
+/// Préfixe des liens de partage.
+pub const LINK_PREFIX: &str = "aether://share/";
+
+/// Taille maximale d'une enveloppe téléchargée.
+pub const MAX_SHARE_BYTES: u64 = 512 * 1024 * 1024;
+
+const LINK_VERSION: u8 = 1;
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+const SHARE_AAD: &[u8] = b"aether-drive:share:v1";
+
+#[derive(Debug)]
+pub enum ShareError {
+    /// Jeton illisible ou incomplet.
+    InvalidLink(&'static str),
+    /// L'enveloppe téléchargée ne correspond pas à l'empreinte du lien.
+    DigestMismatch,
+    /// Déchiffrement impossible (clé ou nom modifiés).
+    Decrypt,
+    /// Hôte injoignable : le lien peut être réessayé.
+    Unreachable(String),
+    Fetch(String),
+    TooLarge(u64),
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareError::InvalidLink(reason) => write!(f, "Invalid share link: {}", reason),
+            ShareError::DigestMismatch => write!(f, "Shared payload does not match the link digest"),
+            ShareError::Decrypt => write!(f, "Failed to decrypt shared payload"),
+            ShareError::Unreachable(msg) => write!(f, "Share host unreachable: {}", msg),
+            ShareError::Fetch(msg) => write!(f, "Failed to fetch shared payload: {}", msg),
+            ShareError::TooLarge(size) => write!(f, "Shared payload too large: {} bytes", size),
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+/// Charge utile sérialisée dans le jeton (JSON puis base64url).
+#[derive(Serialize, Deserialize)]
+struct Token {
+    v: u8,
+    url: String,
+    name: String,
+    sha256: String,
+    key: String,
+}
+
+/// Lien de partage décodé.
+pub struct ShareLink {
+    pub url: String,
+    pub name: String,
+    pub sha256: [u8; 32],
+    key: Zeroizing<[u8; KEY_LEN]>,
+}
+
+impl fmt::Debug for ShareLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShareLink")
+            .field("url", &self.url)
+            .field("name", &self.name)
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl ShareLink {
+    /// Décode un lien `aether://share/<jeton>`.
+    pub fn parse(link: &str) -> Result<Self, ShareError> {
+        let token = link
+            .strip_prefix(LINK_PREFIX)
+            .ok_or(ShareError::InvalidLink("not_a_share_link"))?
+            .trim_end_matches('/');
+        let json = Zeroizing::new(
+            URL_SAFE_NO_PAD
+                .decode(token)
+                .map_err(|_| ShareError::InvalidLink("bad_encoding"))?,
+        );
+        let token: Token = serde_json::from_slice(&json).map_err(|_| ShareError::InvalidLink("bad_token"))?;
+        if token.v != LINK_VERSION {
+            return Err(ShareError::InvalidLink("unsupported_version"));
+        }
+        if !token.url.starts_with("https://") {
+            return Err(ShareError::InvalidLink("insecure_url"));
+        }
+        validate_name(&token.name)?;
+
+        let sha256 = hex::decode(&token.sha256)
+            .ok()
+            .and_then(|digest| <[u8; 32]>::try_from(digest).ok())
+            .ok_or(ShareError::InvalidLink("bad_digest"))?;
+        let key_bytes = Zeroizing::new(
+            URL_SAFE_NO_PAD
+                .decode(&token.key)
+                .map_err(|_| ShareError::InvalidLink("bad_key"))?,
+        );
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        if key_bytes.len() != KEY_LEN {
+            return Err(ShareError::InvalidLink("bad_key"));
+        }
+        key.copy_from_slice(&key_bytes);
+
+        Ok(Self {
+            url: token.url,
+            name: token.name,
+            sha256,
+            key,
+        })
+    }
+
+    /// Encode le lien à transmettre au destinataire.
+    pub fn to_link(&self) -> String {
+        let token = Token {
+            v: LINK_VERSION,
+            url: self.url.clone(),
+            name: self.name.clone(),
+            sha256: hex::encode(self.sha256),
+            key: URL_SAFE_NO_PAD.encode(*self.key),
+        };
+        // La sérialisation d'une structure de chaînes ne peut pas échouer
+        let json = Zeroizing::new(serde_json::to_vec(&token).unwrap_or_default());
+        format!("{}{}", LINK_PREFIX, URL_SAFE_NO_PAD.encode(&*json))
+    }
+
+    /// Vérifie l'empreinte de l'enveloppe puis la déchiffre.
+    pub fn open(&self, envelope: &[u8]) -> Result<Zeroizing<Vec<u8>>, ShareError> {
+        if Sha256::digest(envelope)[..] != self.sha256[..] {
+            return Err(ShareError::DigestMismatch);
+        }
+        if envelope.len() < NONCE_LEN {
+            return Err(ShareError::Decrypt);
+        }
+        let (nonce, ciphertext) = envelope.split_at(NONCE_LEN);
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&*self.key));
+        let plaintext = cipher
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &aad(&self.name),
+                },
+            )
+            .map_err(|_| ShareError::Decrypt)?;
+        Ok(Zeroizing::new(plaintext))
+    }
+
+    /// Télécharge l'enveloppe publiée à l'URL du lien.
+    pub async fn fetch(&self) -> Result<Vec<u8>, ShareError> {
+        let response = reqwest::get(&self.url).await.map_err(|e| {
+            if e.is_connect() || e.is_timeout() {
+                ShareError::Unreachable(e.to_string())
+            } else {
+                ShareError::Fetch(e.to_string())
+            }
+        })?;
+        let response = response.error_for_status().map_err(|e| ShareError::Fetch(e.to_string()))?;
+        if let Some(size) = response.content_length().filter(|size| *size > MAX_SHARE_BYTES) {
+            return Err(ShareError::TooLarge(size));
+        }
+        let body = response.bytes().await.map_err(|e| ShareError::Fetch(e.to_string()))?;
+        if body.len() as u64 > MAX_SHARE_BYTES {
+            return Err(ShareError::TooLarge(body.len() as u64));
+        }
+        Ok(body.to_vec())
+    }
+
+    /// Télécharge, vérifie et déchiffre le fichier partagé.
+    pub async fn receive(&self) -> Result<Zeroizing<Vec<u8>>, ShareError> {
+        let envelope = self.fetch().await?;
+        self.open(&envelope)
+    }
+}
+
+/// Chiffre `plaintext` sous une clé de partage neuve.
+///
+/// Renvoie l'enveloppe à publier et le lien correspondant une fois `url` connue.
+pub fn seal(plaintext: &[u8], name: &str, url: &str) -> Result<(Vec<u8>, ShareLink), ShareError> {
+    validate_name(name)?;
+    if !url.starts_with("https://") {
+        return Err(ShareError::InvalidLink("insecure_url"));
+    }
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    OsRng.fill_bytes(&mut *key);
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&*key));
+    let ciphertext = cipher
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: &aad(name),
+            },
+        )
+        .map_err(|_| ShareError::Decrypt)?;
+    let mut envelope = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    envelope.extend_from_slice(&nonce);
+    envelope.extend_from_slice(&ciphertext);
+
+    let link = ShareLink {
+        url: url.to_string(),
+        name: name.to_string(),
+        sha256: Sha256::digest(&envelope).into(),
+        key,
+    };
+    Ok((envelope, link))
+}
+
+/// Le nom sert de nom de fichier à la réception : ni chemin ni nom réservé.
+fn validate_name(name: &str) -> Result<(), ShareError> {
+    if name.trim().is_empty() || name == "." || name == ".." {
+        return Err(ShareError::InvalidLink("bad_name"));
+    }
+    if name.contains(['/', '\\']) || name.chars().any(char::is_control) {
+        return Err(ShareError::InvalidLink("bad_name"));
+    }
+    Ok(())
+}
+
+fn aad(name: &str) -> Vec<u8> {
+    let mut aad = SHARE_AAD.to_vec();
+    aad.extend_from_slice(name.as_bytes());
+    aad
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://example.com/s/abc";
+
+    #[test]
+    fn link_round_trip_and_open() {
+        let (envelope, link) = seal(b"hello", "note.txt", URL).unwrap();
+        let parsed = ShareLink::parse(&link.to_link()).unwrap();
+        assert_eq!(parsed.url, URL);
+        assert_eq!(parsed.name, "note.txt");
+        assert_eq!(&*parsed.open(&envelope).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn tampered_envelope_is_rejected_before_decryption() {
+        let (mut envelope, link) = seal(b"hello", "note.txt", URL).unwrap();
+        envelope[NONCE_LEN] ^= 1;
+        assert!(matches!(link.open(&envelope), Err(ShareError::DigestMismatch)));
+    }
+
+    #[test]
+    fn rejects_malformed_links() {
+        let (_, link) = seal(b"hello", "note.txt", URL).unwrap();
+        let encoded = link.to_link();
+        assert!(matches!(
+            ShareLink::parse(&encoded.replace("aether://share/", "aether://other/")),
+            Err(ShareError::InvalidLink("not_a_share_link"))
+        ));
+        assert!(ShareLink::parse("aether://share/%%%").is_err());
+
+        assert!(seal(b"x", "../etc/passwd", URL).is_err());
+        assert!(seal(b"x", "note.txt", "http://example.com/s/abc").is_err());
+    }
+}
@@ -0,0 +1,211 @@
+//! Instantanés du coffre : l'état complet de l'index à un instant donné, comparable à
+//! l'état actuel et restaurable d'un bloc.
+//!
+//! Un instantané ne copie aucun contenu : il référence les objets distants existants, qui
+//! sont épinglés (jamais purgés) tant qu'un instantané les désigne.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::dry_run::{self, DryRunReport};
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::index::{FileId, FileMetadata, SnapshotInfo, SnapshotRestore};
+use crate::vault::{Vault, VaultError};
+
+const MAX_NAME_CHARS: usize = 60;
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// Opération refusée (`reason` : code stable).
+    Invalid(&'static str),
+    NotFound(i64),
+    Vault(VaultError),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Invalid(reason) => write!(f, "Invalid snapshot operation: {}", reason),
+            SnapshotError::NotFound(id) => write!(f, "Snapshot not found: {}", id),
+            SnapshotError::Vault(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<VaultError> for SnapshotError {
+    fn from(e: VaultError) -> Self {
+        SnapshotError::Vault(e)
+    }
+}
+
+impl From<rusqlite::Error> for SnapshotError {
+    fn from(e: rusqlite::Error) -> Self {
+        SnapshotError::Vault(VaultError::Index(e))
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffEntry {
+    pub file_id: FileId,
+    pub logical_path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MovedEntry {
+    pub file_id: FileId,
+    /// Chemin dans l'instantané.
+    pub from: String,
+    /// Chemin actuel.
+    pub to: String,
+}
+
+/// Écart entre un instantané et l'état actuel, vu depuis l'instantané.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SnapshotDiff {
+    /// Entrées apparues depuis (mises à la corbeille par une restauration).
+    pub added: Vec<DiffEntry>,
+    /// Entrées disparues depuis (remises en place par une restauration).
+    pub removed: Vec<DiffEntry>,
+    /// Entrées déplacées ou renommées depuis.
+    pub moved: Vec<MovedEntry>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty()
+    }
+}
+
+/// Capture l'état actuel du coffre sous un nom unique.
+pub fn create(index: &mut SqlCipherIndex, name: &str) -> Result<SnapshotInfo, SnapshotError> {
+    let name = validate_name(name)?;
+    if index.list_snapshots()?.iter().any(|snapshot| snapshot.name == name) {
+        return Err(SnapshotError::Invalid("duplicate_name"));
+    }
+    let snapshot = index.create_snapshot(&name)?;
+    log::info!("Snapshot {} created with {} entries", snapshot.id, snapshot.file_count);
+    Ok(snapshot)
+}
+
+/// Compare un instantané à l'état actuel de l'index.
+pub fn diff(index: &SqlCipherIndex, snapshot_id: i64) -> Result<SnapshotDiff, SnapshotError> {
+    ensure_exists(index, snapshot_id)?;
+    Ok(compare(&index.snapshot_entries(snapshot_id)?, &index.list_all()?))
+}
+
+/// Ramène le coffre à l'état de l'instantané (voir [`SqlCipherIndex::restore_snapshot`]).
+pub fn restore(index: &mut SqlCipherIndex, snapshot_id: i64) -> Result<SnapshotRestore, SnapshotError> {
+    ensure_exists(index, snapshot_id)?;
+    let report = index.restore_snapshot(snapshot_id)?;
+    log::info!(
+        "Snapshot {} restored: restored={}, trashed={}",
+        snapshot_id, report.restored, report.trashed
+    );
+    Ok(report)
+}
+
+/// Supprime un instantané et les objets distants qu'il était seul à retenir.
+///
+/// Retourne le nombre d'objets supprimés ; un échec est journalisé sans interrompre les
+/// suivants (l'objet reste alors orphelin à distance).
+pub async fn delete(vault: &Vault, snapshot_id: i64) -> Result<usize, SnapshotError> {
+    let released = {
+        let mut index = vault.open_index()?;
+        ensure_exists(&index, snapshot_id)?;
+        index.delete_snapshot(snapshot_id)?
+    };
+    let mut deleted = 0;
+    for file_id in released {
+        match vault.delete_remote(&file_id).await {
+            Ok(()) => deleted += 1,
+            Err(e) => log::warn!("Failed to delete {} released by snapshot {}: {}", file_id, snapshot_id, e),
+        }
+    }
+    Ok(deleted)
+}
+
+/// Ce que supprimerait [`delete`], sans rien modifier.
+pub fn plan_delete(index: &SqlCipherIndex, snapshot_id: i64) -> Result<DryRunReport, SnapshotError> {
+    ensure_exists(index, snapshot_id)?;
+    Ok(dry_run::delete_snapshot(index, snapshot_id)?)
+}
+
+/// Écart entre les entrées d'un instantané et les entrées actuelles.
+pub fn compare(snapshot: &[(FileId, FileMetadata)], current: &[(FileId, FileMetadata)]) -> SnapshotDiff {
+    let before: HashMap<&FileId, &FileMetadata> = snapshot.iter().map(|(id, meta)| (id, meta)).collect();
+    let after: HashMap<&FileId, &FileMetadata> = current.iter().map(|(id, meta)| (id, meta)).collect();
+    let mut diff = SnapshotDiff::default();
+    for (id, meta) in current {
+        match before.get(id) {
+            None => diff.added.push(DiffEntry {
+                file_id: id.clone(),
+                logical_path: meta.logical_path.clone(),
+            }),
+            Some(old) if old.logical_path != meta.logical_path => diff.moved.push(MovedEntry {
+                file_id: id.clone(),
+                from: old.logical_path.clone(),
+                to: meta.logical_path.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (id, meta) in snapshot {
+        if !after.contains_key(id) {
+            diff.removed.push(DiffEntry {
+                file_id: id.clone(),
+                logical_path: meta.logical_path.clone(),
+            });
+        }
+    }
+    diff
+}
+
+fn ensure_exists(index: &SqlCipherIndex, snapshot_id: i64) -> Result<(), SnapshotError> {
+    if index.list_snapshots()?.iter().any(|snapshot| snapshot.id == snapshot_id) {
+        Ok(())
+    } else {
+        Err(SnapshotError::NotFound(snapshot_id))
+    }
+}
+
+fn validate_name(name: &str) -> Result<String, SnapshotError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(SnapshotError::Invalid("empty_name"));
+    }
+    if name.chars().count() > MAX_NAME_CHARS || name.chars().any(char::is_control) {
+        return Err(SnapshotError::Invalid("invalid_name"));
+    }
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, path: &str) -> (FileId, FileMetadata) {
+        (
+            id.to_string(),
+            FileMetadata {
+                logical_path: path.to_string(),
+                encrypted_size: 1,
+            },
+        )
+    }
+
+    #[test]
+    fn compare_reports_added_removed_and_moved_entries() {
+        let snapshot = vec![entry("a", "/a.txt"), entry("b", "/b.txt"), entry("c", "/c.txt")];
+        let current = vec![entry("a", "/a.txt"), entry("b", "/docs/b.txt"), entry("d", "/d.txt")];
+        let diff = compare(&snapshot, &current);
+        assert_eq!(diff.added, vec![DiffEntry { file_id: "d".to_string(), logical_path: "/d.txt".to_string() }]);
+        assert_eq!(diff.removed, vec![DiffEntry { file_id: "c".to_string(), logical_path: "/c.txt".to_string() }]);
+        assert_eq!(diff.moved.len(), 1);
+        assert_eq!((diff.moved[0].from.as_str(), diff.moved[0].to.as_str()), ("/b.txt", "/docs/b.txt"));
+        assert!(compare(&snapshot, &snapshot).is_empty());
+        assert!(matches!(validate_name("  "), Err(SnapshotError::Invalid("empty_name"))));
+    }
+}
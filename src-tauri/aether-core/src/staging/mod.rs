@@ -0,0 +1,288 @@
+use memmap2::MmapMut;
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::fs;
+use flate2::{write::DeflateEncoder, Compression};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cache::BlobCache;
+use crate::crypto::MasterKey;
+use crate::index::FileId;
+use crate::storage::{self, AetherFile, ContentEncoding, StorageError, TAG_LEN};
+
+const STAGED_EXTENSION: &str = "staged";
+/// Taille des lectures successives du fichier source dans [`Staging::encrypt_path`].
+const READ_CHUNK: usize = 1024 * 1024;
+
+/// Zone de transit sur disque des envois.
+///
+/// Le blob Aether est écrit directement depuis le ciphertext (aucune copie sérialisée
+/// en mémoire) puis envoyé en flux depuis le disque : le ciphertext peut être libéré
+/// avant l'envoi. Les fichiers de transit sont déjà chiffrés ; placés dans le dossier
+/// temporaire, ils sont balayés par la passe de reprise après un arrêt brutal.
+#[derive(Debug, Clone)]
+pub struct Staging {
+    dir: PathBuf,
+}
+
+impl Staging {
+    pub fn new(dir: impl AsRef<Path>) -> io::Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Espace libre sur le volume de la zone de transit.
+    pub fn available_space(&self) -> io::Result<u64> {
+        fs2::available_space(&self.dir)
+    }
+
+    /// Écrit le blob sérialisé de `aether_file` sur disque.
+    pub fn write(&self, file_id: &FileId, aether_file: &AetherFile) -> io::Result<StagedBlob> {
+        if file_id.is_empty() || !file_id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid file id"));
+        }
+        let path = self.dir.join(format!("{}.{}", file_id, STAGED_EXTENSION));
+        let staged = StagedBlob {
+            file_id: file_id.clone(),
+            path,
+            len: (AetherFile::HEADER_LEN + aether_file.ciphertext.len()) as u64,
+        };
+        // En cas d'échec, le Drop de `staged` retire l'écriture partielle
+        aether_file.write_to(BufWriter::new(fs::File::create(&staged.path)?))?;
+        Ok(staged)
+    }
+
+    /// Chiffre un fichier local directement dans la zone de transit.
+    ///
+    /// Le blob de destination est projeté en mémoire, rempli par morceaux depuis `source`
+    /// puis chiffré sur place : ni le clair ni le ciphertext ne sont chargés entièrement en
+    /// RAM, ce qui permet d'envoyer des fichiers plus gros que la mémoire disponible.
+    /// Retourne aussi le SHA-256 du contenu en clair (déduplication).
+    ///
+    /// Un contenu à transformer (`encoding`) est d'abord écrit, compressé ou non, à sa place
+    /// dans le blob, puis encadré et chiffré sur place de la même façon.
+    pub fn encrypt_path(
+        &self,
+        master_key: &MasterKey,
+        source: &Path,
+        logical_path: &str,
+        encoding: ContentEncoding,
+    ) -> Result<(StagedBlob, [u8; 32]), StorageError> {
+        if !encoding.is_identity() {
+            return self.encrypt_path_framed(master_key, source, logical_path, encoding);
+        }
+        let io_error = |e: io::Error| StorageError::Io(format!("{}: {}", source.display(), e));
+        let mut input = fs::File::open(source).map_err(io_error)?;
+        let plaintext_len = input.metadata().map_err(io_error)?.len();
+        let body_len = usize::try_from(plaintext_len)
+            .map_err(|_| StorageError::Io(format!("{}: file too large", source.display())))?;
+
+        let mut uuid = [0u8; 16];
+        OsRng.fill_bytes(&mut uuid);
+        let file_id = hex::encode(uuid);
+        let ciphertext_len = plaintext_len + TAG_LEN as u64;
+        let staged = StagedBlob {
+            path: self.dir.join(format!("{}.{}", file_id, STAGED_EXTENSION)),
+            file_id,
+            len: AetherFile::HEADER_LEN as u64 + ciphertext_len,
+        };
+
+        // En cas d'échec, le Drop de `staged` retire le fichier partiel
+        let output = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&staged.path)
+            .map_err(io_error)?;
+        output.set_len(staged.len).map_err(io_error)?;
+        // SAFETY : le fichier vient d'être créé (create_new) dans la zone de transit et
+        // n'est ni ouvert ni tronqué par un autre processus tant que la projection existe.
+        let mut mmap = unsafe { MmapMut::map_mut(&output) }.map_err(io_error)?;
+
+        let body = &mut mmap[AetherFile::HEADER_LEN..AetherFile::HEADER_LEN + body_len];
+        let mut hasher = Sha256::new();
+        for chunk in body.chunks_mut(READ_CHUNK) {
+            // Un fichier tronqué pendant la lecture fait échouer `read_exact`
+            input.read_exact(chunk).map_err(io_error)?;
+            hasher.update(&*chunk);
+        }
+
+        let (header, tag) = storage::encrypt_in_place(master_key, body, logical_path, uuid)?;
+        mmap[..AetherFile::HEADER_LEN].copy_from_slice(&AetherFile::encode_header(&header, ciphertext_len));
+        mmap[AetherFile::HEADER_LEN + body_len..].copy_from_slice(&tag);
+        mmap.flush().map_err(io_error)?;
+        Ok((staged, hasher.finalize().into()))
+    }
+
+    fn encrypt_path_framed(
+        &self,
+        master_key: &MasterKey,
+        source: &Path,
+        logical_path: &str,
+        encoding: ContentEncoding,
+    ) -> Result<(StagedBlob, [u8; 32]), StorageError> {
+        let io_error = |e: io::Error| StorageError::Io(format!("{}: {}", source.display(), e));
+        let mut input = fs::File::open(source).map_err(io_error)?;
+
+        let mut uuid = [0u8; 16];
+        OsRng.fill_bytes(&mut uuid);
+        let file_id = hex::encode(uuid);
+        let mut staged = StagedBlob {
+            path: self.dir.join(format!("{}.{}", file_id, STAGED_EXTENSION)),
+            file_id,
+            len: 0,
+        };
+        let mut output = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&staged.path)
+            .map_err(io_error)?;
+
+        // Contenu (compressé au fil de la lecture) écrit après la place de l'en-tête
+        output.seek(SeekFrom::Start(AetherFile::HEADER_LEN as u64)).map_err(io_error)?;
+        let mut hasher = Sha256::new();
+        let mut chunk = vec![0u8; READ_CHUNK];
+        {
+            let mut writer: Box<dyn Write> = if encoding.compress {
+                Box::new(DeflateEncoder::new(BufWriter::new(&output), Compression::default()))
+            } else {
+                Box::new(BufWriter::new(&output))
+            };
+            loop {
+                let read = input.read(&mut chunk).map_err(io_error)?;
+                if read == 0 {
+                    break;
+                }
+                hasher.update(&chunk[..read]);
+                writer.write_all(&chunk[..read]).map_err(io_error)?;
+            }
+            writer.flush().map_err(io_error)?;
+        }
+        let content_len = output.stream_position().map_err(io_error)? - AetherFile::HEADER_LEN as u64;
+
+        let framed_len = encoding.framed_len(content_len);
+        let body_len = usize::try_from(framed_len)
+            .map_err(|_| StorageError::Io(format!("{}: file too large", source.display())))?;
+        let ciphertext_len = framed_len + TAG_LEN as u64;
+        staged.len = AetherFile::HEADER_LEN as u64 + ciphertext_len;
+        // Les octets ajoutés par `set_len` sont des zéros : le bourrage
+        output.set_len(staged.len).map_err(io_error)?;
+        // SAFETY : le fichier vient d'être créé (create_new) dans la zone de transit et
+        // n'est ni ouvert ni tronqué par un autre processus tant que la projection existe.
+        let mut mmap = unsafe { MmapMut::map_mut(&output) }.map_err(io_error)?;
+        let body = &mut mmap[AetherFile::HEADER_LEN..AetherFile::HEADER_LEN + body_len];
+        body[body_len - storage::TRAILER_LEN..].copy_from_slice(&encoding.trailer(content_len));
+
+        let (header, tag) = storage::encrypt_framed_in_place(master_key, body, logical_path, uuid)?;
+        mmap[..AetherFile::HEADER_LEN].copy_from_slice(&AetherFile::encode_header(&header, ciphertext_len));
+        mmap[AetherFile::HEADER_LEN + body_len..].copy_from_slice(&tag);
+        mmap.flush().map_err(io_error)?;
+        Ok((staged, hasher.finalize().into()))
+    }
+}
+
+/// Blob en transit ; le fichier est supprimé quand la valeur est abandonnée.
+#[derive(Debug)]
+pub struct StagedBlob {
+    file_id: FileId,
+    path: PathBuf,
+    len: u64,
+}
+
+impl StagedBlob {
+    pub fn file_id(&self) -> &FileId {
+        &self.file_id
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Transfère le blob dans le cache (envoi différé) sans le relire en mémoire.
+    pub fn move_into(self, cache: &BlobCache) -> io::Result<()> {
+        cache.adopt(&self.file_id, &self.path)
+    }
+}
+
+impl Drop for StagedBlob {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::MasterKey;
+    use crate::storage;
+    use tempfile::tempdir;
+
+    #[test]
+    fn staged_blob_matches_serialized_file_and_is_cleaned_up() {
+        let dir = tempdir().unwrap();
+        let staging = Staging::new(dir.path().join("tmp")).unwrap();
+        let master_key = MasterKey::from_vec(vec![9u8; 32]);
+        let aether_file = storage::encrypt_file(&master_key, b"large payload", "/big.bin").unwrap();
+        let file_id = hex::encode(aether_file.header.uuid);
+
+        let staged = staging.write(&file_id, &aether_file).unwrap();
+        assert_eq!(fs::read(staged.path()).unwrap(), aether_file.to_bytes());
+        assert_eq!(staged.len(), aether_file.to_bytes().len() as u64);
+
+        let path = staged.path().to_path_buf();
+        drop(staged);
+        assert!(!path.exists());
+
+        // Déplacé dans le cache : le blob survit à la zone de transit
+        let cache = BlobCache::new(dir.path().join("cache")).unwrap();
+        staging.write(&file_id, &aether_file).unwrap().move_into(&cache).unwrap();
+        assert_eq!(cache.get(&file_id).unwrap(), aether_file.to_bytes());
+        assert_eq!(fs::read_dir(dir.path().join("tmp")).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn encrypt_path_streams_a_local_file_into_a_decryptable_blob() {
+        let dir = tempdir().unwrap();
+        let staging = Staging::new(dir.path().join("tmp")).unwrap();
+        let master_key = MasterKey::from_vec(vec![9u8; 32]);
+        let plaintext: Vec<u8> = (0..READ_CHUNK * 2 + 17).map(|i| (i % 251) as u8).collect();
+        let source = dir.path().join("video.mkv");
+        fs::write(&source, &plaintext).unwrap();
+
+        let (staged, content_hash) = staging.encrypt_path(&master_key, &source, "/video.mkv", ContentEncoding::default()).unwrap();
+        let blob = fs::read(staged.path()).unwrap();
+        assert_eq!(staged.len(), blob.len() as u64);
+        assert_eq!(content_hash, <[u8; 32]>::from(Sha256::digest(&plaintext)));
+        let aether_file = AetherFile::from_bytes(&blob).unwrap();
+        assert_eq!(&hex::encode(aether_file.header.uuid), staged.file_id());
+        assert_eq!(storage::decrypt_file(&master_key, &aether_file, "/video.mkv").unwrap(), plaintext);
+
+        // Fichier vide : seul le tag est chiffré
+        fs::write(&source, b"").unwrap();
+        let (empty, _) = staging.encrypt_path(&master_key, &source, "/empty", ContentEncoding::default()).unwrap();
+        let aether_file = AetherFile::from_bytes(&fs::read(empty.path()).unwrap()).unwrap();
+        assert!(storage::decrypt_file(&master_key, &aether_file, "/empty").unwrap().is_empty());
+
+        // Contenu compressé et bourré au fil de la lecture
+        fs::write(&source, &plaintext).unwrap();
+        let encoding = ContentEncoding { compress: true, padding: storage::Padding::Standard };
+        let (framed, framed_hash) = staging.encrypt_path(&master_key, &source, "/video.mkv", encoding).unwrap();
+        assert_eq!(framed_hash, content_hash);
+        assert!(framed.len() < blob.len() as u64);
+        let aether_file = AetherFile::from_bytes(&fs::read(framed.path()).unwrap()).unwrap();
+        assert!(storage::is_framed(&aether_file.header));
+        assert_eq!(storage::decrypt_file(&master_key, &aether_file, "/video.mkv").unwrap(), plaintext);
+    }
+}
@@ -1,13 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::io::{self, Write};
 use zeroize::Zeroizing;
 
 /// En-tête binaire d'un fichier Aether V1
 ///
 /// Structure :
 /// - Magic Number (4 bytes): "AETH"
-/// - Version (1 byte): 0x01
-/// - Cipher ID (1 byte): 0x02 (XChaCha20-Poly1305 + PQ Hybrid)
+/// - Version (1 byte): 0x01 (0x02 : corps encadré, 0x03 : corps par morceaux)
+/// - Cipher ID (1 byte): 0x02 (XChaCha20-Poly1305, FileKey dérivée par HKDF-SHA256)
 /// - UUID (16 bytes): Identifiant unique du fichier
 /// - Salt (32 bytes): Salt pour la dérivation de la FileKey
 /// - Commitment HMAC (32 bytes): HMAC-SHA256 pour vérifier l'intégrité
@@ -55,29 +56,72 @@ impl fmt::Display for AetherError {
 impl std::error::Error for AetherError {}
 
 impl AetherFile {
+    /// Octets ajoutés au clair par le format V1 : en-tête (110) + longueur (8) + tag Poly1305 (16).
+    pub const OVERHEAD: u64 = 110 + 8 + 16;
+
+    /// Taille du contenu en clair d'un fichier V1 de `encrypted_size` octets. Ne vaut pas
+    /// pour les versions 2 et 3 : elle ne sert qu'aux entrées indexées avant le relevé des
+    /// tailles en clair, toutes en V1.
+    pub fn v1_plaintext_len(encrypted_size: u64) -> u64 {
+        encrypted_size.saturating_sub(Self::OVERHEAD)
+    }
+
+    /// Taille de l'en-tête sérialisé, champ de longueur inclus.
+    pub const HEADER_LEN: usize = 110 + 8;
+
+    /// Lit l'en-tête et la longueur du ciphertext depuis le début d'un blob.
+    ///
+    /// Permet de vérifier un objet distant sans le télécharger entièrement.
+    pub fn parse_header(data: &[u8]) -> Result<(AetherHeader, u64), AetherError> {
+        if data.len() < Self::HEADER_LEN {
+            return Err(AetherError::InvalidHeader);
+        }
+        let magic: [u8; 4] = data[0..4].try_into().unwrap();
+        if &magic != b"AETH" {
+            return Err(AetherError::InvalidMagic);
+        }
+        let header = AetherHeader {
+            magic,
+            version: data[4],
+            cipher_id: data[5],
+            uuid: data[6..22].try_into().unwrap(),
+            salt: data[22..54].try_into().unwrap(),
+            commitment_hmac: data[54..86].try_into().unwrap(),
+            nonce: data[86..110].try_into().unwrap(),
+        };
+        let ciphertext_len = u64::from_le_bytes(data[110..118].try_into().unwrap());
+        Ok((header, ciphertext_len))
+    }
+
     /// Sérialise le fichier Aether en format binaire pour le stockage
     ///
     /// Format binaire :
     /// [Magic(4)][Version(1)][CipherID(1)][UUID(16)][Salt(32)][HMAC(32)][Nonce(24)][CiphertextLen(8)][Ciphertext(N)]
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        
-        // En-tête
-        bytes.extend_from_slice(&self.header.magic);
-        bytes.push(self.header.version);
-        bytes.push(self.header.cipher_id);
-        bytes.extend_from_slice(&self.header.uuid);
-        bytes.extend_from_slice(&self.header.salt);
-        bytes.extend_from_slice(&self.header.commitment_hmac);
-        bytes.extend_from_slice(&self.header.nonce);
-        
-        // Longueur du ciphertext (u64 en little-endian)
-        let ciphertext_len = self.ciphertext.len() as u64;
-        bytes.extend_from_slice(&ciphertext_len.to_le_bytes());
-        
-        // Ciphertext
-        bytes.extend_from_slice(self.ciphertext.as_ref());
-        
+        let mut bytes = Vec::with_capacity(Self::HEADER_LEN + self.ciphertext.len());
+        // L'écriture dans un Vec ne peut pas échouer
+        let _ = self.write_to(&mut bytes);
+        bytes
+    }
+
+    /// Écrit le format binaire dans `writer` sans construire de copie sérialisée en mémoire.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&Self::encode_header(&self.header, self.ciphertext.len() as u64))?;
+        writer.write_all(self.ciphertext.as_ref())?;
+        writer.flush()
+    }
+
+    /// En-tête sérialisé suivi de la longueur du ciphertext (u64 en little-endian).
+    pub fn encode_header(header: &AetherHeader, ciphertext_len: u64) -> [u8; Self::HEADER_LEN] {
+        let mut bytes = [0u8; Self::HEADER_LEN];
+        bytes[0..4].copy_from_slice(&header.magic);
+        bytes[4] = header.version;
+        bytes[5] = header.cipher_id;
+        bytes[6..22].copy_from_slice(&header.uuid);
+        bytes[22..54].copy_from_slice(&header.salt);
+        bytes[54..86].copy_from_slice(&header.commitment_hmac);
+        bytes[86..110].copy_from_slice(&header.nonce);
+        bytes[110..118].copy_from_slice(&ciphertext_len.to_le_bytes());
         bytes
     }
 
@@ -185,6 +229,11 @@ mod tests {
         assert_eq!(deserialized.header.commitment_hmac, file.header.commitment_hmac);
         assert_eq!(deserialized.header.nonce, file.header.nonce);
         assert_eq!(deserialized.ciphertext.as_ref() as &[u8], file.ciphertext.as_ref() as &[u8]);
+
+        // L'en-tête seul suffit pour relire UUID et longueur
+        let (header, ciphertext_len) = AetherFile::parse_header(&bytes[..AetherFile::HEADER_LEN]).unwrap();
+        assert_eq!(header.uuid, file.header.uuid);
+        assert_eq!(ciphertext_len, 100);
     }
 }
 
@@ -0,0 +1,244 @@
+//! Format Aether par morceaux (version 3) : chiffrement en flux des fichiers trop gros pour
+//! tenir en mémoire.
+//!
+//! L'en-tête est celui du format V1, son nonce servant de nonce de base. Le corps est une
+//! suite de morceaux de [`CHUNK_LEN`] octets de clair (le dernier plus court, éventuellement
+//! vide), chacun chiffré avec XChaCha20-Poly1305 sous la FileKey et suivi de son tag :
+//!
+//! - nonce du morceau `i` : nonce de base dont les 8 derniers octets sont combinés (XOR)
+//!   avec `i` (u64 LE) ;
+//! - AAD : celle du chemin logique, suivie de `i` (u64 LE) et d'un octet valant 1 pour le
+//!   dernier morceau, 0 sinon.
+//!
+//! Un morceau déplacé, dupliqué ou retiré, y compris en fin de corps, fait donc échouer le
+//! déchiffrement. Le corps n'est ni compressé ni bourré (voir [`super::ContentEncoding`]).
+
+use chacha20poly1305::{aead::{AeadInPlace, KeyInit}, Key, XChaCha20Poly1305, XNonce};
+use rand::{rngs::OsRng, RngCore};
+use std::io::{Read, Write};
+use zeroize::Zeroizing;
+
+use super::{build_aad, build_header, derive_file_key, verify_header, AetherFile, AetherHeader, StorageError};
+use super::{NONCE_LEN, SALT_LEN, TAG_LEN, UUID_LEN};
+use crate::crypto::{CryptoError, FileKey, MasterKey};
+
+/// Version du format dont le corps est découpé en morceaux.
+pub(super) const VERSION_CHUNKED: u8 = 0x03;
+
+/// Octets de clair par morceau.
+pub const CHUNK_LEN: usize = 64 * 1024;
+
+/// Vrai si le corps de ce fichier est découpé en morceaux (version 3 du format).
+pub fn is_chunked(header: &AetherHeader) -> bool {
+    header.version == VERSION_CHUNKED
+}
+
+/// Taille du corps chiffré (tags compris) d'un clair de `plaintext_len` octets.
+pub fn chunked_len(plaintext_len: u64) -> u64 {
+    let chunks = plaintext_len.div_ceil(CHUNK_LEN as u64).max(1);
+    plaintext_len + chunks * TAG_LEN as u64
+}
+
+/// Taille du clair d'un corps chiffré de `body_len` octets (inverse de [`chunked_len`]).
+pub fn chunked_plaintext_len(body_len: u64) -> u64 {
+    let chunks = body_len.div_ceil((CHUNK_LEN + TAG_LEN) as u64).max(1);
+    body_len.saturating_sub(chunks * TAG_LEN as u64)
+}
+
+/// Chiffre en flux `plaintext_len` octets lus dans `reader` et écrit le fichier sérialisé,
+/// en-tête compris, dans `writer`, un morceau à la fois ; retourne l'en-tête.
+///
+/// La taille est inscrite dans l'en-tête avant le corps : un lecteur qui fournit plus ou
+/// moins d'octets fait échouer le chiffrement, et ce qui a déjà été écrit est à jeter.
+pub fn encrypt_stream<R: Read, W: Write>(
+    master_key: &MasterKey,
+    mut reader: R,
+    plaintext_len: u64,
+    logical_path: &str,
+    mut writer: W,
+) -> Result<AetherHeader, StorageError> {
+    let mut uuid = [0u8; UUID_LEN];
+    OsRng.fill_bytes(&mut uuid);
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let file_key = derive_file_key(master_key, &salt)?;
+    let header = build_header(&file_key, uuid, salt, nonce, VERSION_CHUNKED);
+    let chunks = ChunkCipher::new(&file_key, &header, logical_path);
+
+    let io = |e: std::io::Error| StorageError::Io(e.to_string());
+    writer
+        .write_all(&AetherFile::encode_header(&header, chunked_len(plaintext_len)))
+        .map_err(io)?;
+    let mut buffer = Zeroizing::new(vec![0u8; CHUNK_LEN]);
+    let mut remaining = plaintext_len;
+    let mut index = 0u64;
+    loop {
+        let len = remaining.min(CHUNK_LEN as u64) as usize;
+        reader.read_exact(&mut buffer[..len]).map_err(|e| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => StorageError::Io("Source shorter than announced".to_string()),
+            _ => io(e),
+        })?;
+        remaining -= len as u64;
+        let last = remaining == 0;
+        let tag = chunks.seal(index, last, &mut buffer[..len])?;
+        writer.write_all(&buffer[..len]).and_then(|_| writer.write_all(&tag)).map_err(io)?;
+        if last {
+            break;
+        }
+        index += 1;
+    }
+    if reader.read(&mut [0u8; 1]).map_err(io)? != 0 {
+        return Err(StorageError::Io("Source longer than announced".to_string()));
+    }
+    writer.flush().map_err(io)?;
+    Ok(header)
+}
+
+/// Déchiffre en flux vers `writer` le corps d'un fichier par morceaux : `ciphertext_len`
+/// octets lus dans `reader`, positionné après l'en-tête. Retourne la taille du clair.
+///
+/// Chaque morceau est vérifié avant d'être écrit ; en cas d'échec, ce qui a déjà été écrit
+/// est à jeter.
+pub fn decrypt_stream<R: Read, W: Write>(
+    master_key: &MasterKey,
+    header: &AetherHeader,
+    ciphertext_len: u64,
+    mut reader: R,
+    logical_path: &str,
+    mut writer: W,
+) -> Result<u64, StorageError> {
+    if !is_chunked(header) {
+        return Err(StorageError::InvalidFormat("Body is not chunked".to_string()));
+    }
+    let file_key = verify_header(master_key, header)?;
+    let chunks = ChunkCipher::new(&file_key, header, logical_path);
+
+    let io = |e: std::io::Error| StorageError::Io(e.to_string());
+    let mut buffer = Zeroizing::new(vec![0u8; CHUNK_LEN + TAG_LEN]);
+    let mut remaining = ciphertext_len;
+    let mut index = 0u64;
+    let mut plaintext_len = 0u64;
+    loop {
+        let len = remaining.min((CHUNK_LEN + TAG_LEN) as u64) as usize;
+        if len < TAG_LEN {
+            return Err(StorageError::InvalidFormat("Truncated chunk".to_string()));
+        }
+        reader.read_exact(&mut buffer[..len]).map_err(io)?;
+        remaining -= len as u64;
+        let last = remaining == 0;
+        let (body, tag) = buffer[..len].split_at_mut(len - TAG_LEN);
+        chunks.open(index, last, body, tag)?;
+        writer.write_all(body).map_err(io)?;
+        plaintext_len += body.len() as u64;
+        if last {
+            break;
+        }
+        index += 1;
+    }
+    writer.flush().map_err(io)?;
+    Ok(plaintext_len)
+}
+
+/// Chiffrement des morceaux d'un fichier : nonce et AAD propres à chacun.
+struct ChunkCipher {
+    cipher: XChaCha20Poly1305,
+    nonce: [u8; NONCE_LEN],
+    aad: Vec<u8>,
+}
+
+impl ChunkCipher {
+    fn new(file_key: &FileKey, header: &AetherHeader, logical_path: &str) -> Self {
+        Self {
+            cipher: XChaCha20Poly1305::new(Key::from_slice(file_key.as_bytes())),
+            nonce: header.nonce,
+            aad: build_aad(logical_path),
+        }
+    }
+
+    fn nonce(&self, index: u64) -> XNonce {
+        let mut nonce = self.nonce;
+        for (byte, counter) in nonce[NONCE_LEN - 8..].iter_mut().zip(index.to_le_bytes()) {
+            *byte ^= counter;
+        }
+        *XNonce::from_slice(&nonce)
+    }
+
+    fn aad(&self, index: u64, last: bool) -> Vec<u8> {
+        let mut aad = Vec::with_capacity(self.aad.len() + 9);
+        aad.extend_from_slice(&self.aad);
+        aad.extend_from_slice(&index.to_le_bytes());
+        aad.push(last as u8);
+        aad
+    }
+
+    fn seal(&self, index: u64, last: bool, buffer: &mut [u8]) -> Result<[u8; TAG_LEN], StorageError> {
+        let tag = self
+            .cipher
+            .encrypt_in_place_detached(&self.nonce(index), &self.aad(index, last), buffer)
+            .map_err(|e| StorageError::Crypto(CryptoError::from(e)))?;
+        Ok(tag.into())
+    }
+
+    fn open(&self, index: u64, last: bool, buffer: &mut [u8], tag: &[u8]) -> Result<(), StorageError> {
+        self.cipher
+            .decrypt_in_place_detached(&self.nonce(index), &self.aad(index, last), buffer, tag.into())
+            .map_err(|e| StorageError::Crypto(CryptoError::from(e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::decrypt_file;
+
+    fn encrypt(master_key: &MasterKey, plaintext: &[u8], logical_path: &str) -> Vec<u8> {
+        let mut blob = Vec::new();
+        encrypt_stream(master_key, plaintext, plaintext.len() as u64, logical_path, &mut blob).unwrap();
+        blob
+    }
+
+    #[test]
+    fn chunked_files_roundtrip_and_detect_reordering_and_truncation() {
+        let master_key = MasterKey::from_vec(vec![3u8; 32]);
+        for len in [0, 10, CHUNK_LEN, 2 * CHUNK_LEN + 5] {
+            let plaintext: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let blob = encrypt(&master_key, &plaintext, "/big.bin");
+            assert_eq!(blob.len() as u64, AetherFile::HEADER_LEN as u64 + chunked_len(len as u64));
+
+            let aether_file = AetherFile::from_bytes(&blob).unwrap();
+            assert!(is_chunked(&aether_file.header));
+            assert_eq!(decrypt_file(&master_key, &aether_file, "/big.bin").unwrap(), plaintext);
+            assert!(decrypt_file(&master_key, &aether_file, "/other.bin").is_err());
+        }
+
+        let plaintext = vec![9u8; 2 * CHUNK_LEN + 5];
+        let blob = encrypt(&master_key, &plaintext, "/big.bin");
+        let (header, _) = AetherFile::parse_header(&blob).unwrap();
+        let body = &blob[AetherFile::HEADER_LEN..];
+        let chunk = CHUNK_LEN + TAG_LEN;
+
+        // Morceaux intervertis
+        let mut swapped = body[chunk..2 * chunk].to_vec();
+        swapped.extend_from_slice(&body[..chunk]);
+        swapped.extend_from_slice(&body[2 * chunk..]);
+        let mut output = Vec::new();
+        assert!(decrypt_stream(&master_key, &header, swapped.len() as u64, &swapped[..], "/big.bin", &mut output).is_err());
+
+        // Dernier morceau retiré : l'avant-dernier n'a pas été scellé comme le dernier
+        let truncated = &body[..2 * chunk];
+        let result = decrypt_stream(&master_key, &header, truncated.len() as u64, truncated, "/big.bin", Vec::new());
+        assert!(result.is_err());
+
+        let len = decrypt_stream(&master_key, &header, body.len() as u64, body, "/big.bin", Vec::new()).unwrap();
+        assert_eq!(len, plaintext.len() as u64);
+    }
+
+    #[test]
+    fn the_announced_size_must_match_the_source() {
+        let master_key = MasterKey::from_vec(vec![3u8; 32]);
+        assert!(encrypt_stream(&master_key, &b"short"[..], 10, "/a", Vec::new()).is_err());
+        assert!(encrypt_stream(&master_key, &b"longer than announced"[..], 4, "/a", Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+//! Déchiffrement d'un fichier Aether directement sur le disque, sans tenir le clair ni le
+//! chiffré entier en mémoire (restauration de gros fichiers).
+
+use memmap2::MmapMut;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Read};
+use std::path::{Path, PathBuf};
+
+use super::{
+    decrypt_in_place, decrypt_stream, framed_content, inflate_to, is_chunked, is_framed, verify_header, AetherFile,
+    StorageError, TAG_LEN,
+};
+use crate::crypto::MasterKey;
+
+/// Déchiffre vers `dest` le fichier Aether lu dans `reader` (en-tête compris) ; retourne la
+/// taille du clair.
+///
+/// L'en-tête est vérifié avant toute écriture. Le corps est ensuite recopié par blocs dans un
+/// fichier partiel voisin de `dest` :
+/// - corps par morceaux : chaque morceau est vérifié puis déchiffré au fil de la lecture ;
+/// - formats V1 et V2 : le tag unique est vérifié sur le fichier partiel projeté en mémoire,
+///   puis le corps y est déchiffré sur place (un corps encadré est ensuite tronqué ou
+///   décompressé dans un second fichier partiel).
+///
+/// `dest` n'apparaît qu'une fois le contenu authentifié ; en cas d'échec, aucun fichier
+/// partiel ne reste sur le disque.
+pub fn decrypt_file_to_path<R: Read>(
+    master_key: &MasterKey,
+    mut reader: R,
+    logical_path: &str,
+    dest: &Path,
+) -> Result<u64, StorageError> {
+    let mut raw_header = [0u8; AetherFile::HEADER_LEN];
+    reader.read_exact(&mut raw_header).map_err(|_| StorageError::InvalidHeader)?;
+    let (header, ciphertext_len) = AetherFile::parse_header(&raw_header).map_err(|_| StorageError::InvalidHeader)?;
+    verify_header(master_key, &header)?;
+
+    let truncated = || StorageError::InvalidFormat("Truncated body".to_string());
+    let mut body = reader.take(ciphertext_len);
+    let partial = PartialFile::create(dest, ".partial")?;
+    if is_chunked(&header) {
+        let mut output = BufWriter::new(&partial.file);
+        let plaintext_len = decrypt_stream(master_key, &header, ciphertext_len, &mut body, logical_path, &mut output)?;
+        drop(output);
+        partial.persist(dest)?;
+        return Ok(plaintext_len);
+    }
+
+    let body_len = ciphertext_len.checked_sub(TAG_LEN as u64).ok_or_else(truncated)?;
+    if std::io::copy(&mut (&mut body).take(body_len), &mut &partial.file).map_err(io_error)? != body_len {
+        return Err(truncated());
+    }
+    let mut tag = [0u8; TAG_LEN];
+    body.read_exact(&mut tag).map_err(|_| truncated())?;
+
+    let mut plaintext_len = body_len;
+    if body_len == 0 {
+        decrypt_in_place(master_key, &header, &mut [], &tag, logical_path)?;
+    } else {
+        // SAFETY : le fichier partiel vient d'être créé et n'est ouvert que par `partial`
+        // tant que la projection existe.
+        let mut mapped = unsafe { MmapMut::map_mut(&partial.file) }.map_err(io_error)?;
+        decrypt_in_place(master_key, &header, &mut mapped, &tag, logical_path)?;
+        mapped.flush().map_err(io_error)?;
+        if is_framed(&header) {
+            let (content_len, compressed) = framed_content(&mapped)?;
+            if compressed {
+                let inflated = PartialFile::create(dest, ".inflate")?;
+                plaintext_len = inflate_to(&mapped[..content_len], BufWriter::new(&inflated.file))?;
+                drop(mapped);
+                inflated.persist(dest)?;
+                return Ok(plaintext_len);
+            }
+            plaintext_len = content_len as u64;
+        }
+    }
+    partial.file.set_len(plaintext_len).map_err(io_error)?;
+    partial.persist(dest)?;
+    Ok(plaintext_len)
+}
+
+fn io_error(e: std::io::Error) -> StorageError {
+    StorageError::Io(e.to_string())
+}
+
+/// Fichier voisin de la destination, supprimé s'il n'est pas renommé sur elle.
+struct PartialFile {
+    path: PathBuf,
+    file: File,
+    persisted: bool,
+}
+
+impl PartialFile {
+    fn create(dest: &Path, suffix: &str) -> Result<Self, StorageError> {
+        let mut path = dest.as_os_str().to_owned();
+        path.push(suffix);
+        let path = PathBuf::from(path);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .map_err(|e| StorageError::Io(format!("{}: {}", path.display(), e)))?;
+        Ok(Self { path, file, persisted: false })
+    }
+
+    fn persist(mut self, dest: &Path) -> Result<(), StorageError> {
+        self.file.sync_all().map_err(io_error)?;
+        fs::rename(&self.path, dest).map_err(io_error)?;
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{encrypt_file, encrypt_file_encoded, encrypt_stream, ContentEncoding, Padding, CHUNK_LEN};
+    use tempfile::tempdir;
+
+    #[test]
+    fn every_format_decrypts_to_disk_and_failures_leave_nothing_behind() {
+        let dir = tempdir().unwrap();
+        let master_key = MasterKey::from_vec(vec![5u8; 32]);
+        let plaintext = b"restored from a backup ".repeat(4000);
+        let mut chunked = Vec::new();
+        encrypt_stream(&master_key, &plaintext[..], plaintext.len() as u64, "/big.txt", &mut chunked).unwrap();
+        let compressed = ContentEncoding { compress: true, padding: Padding::Standard };
+        let blobs = [
+            encrypt_file(&master_key, &plaintext, "/big.txt").unwrap().to_bytes(),
+            encrypt_file_encoded(&master_key, &plaintext, "/big.txt", compressed).unwrap().to_bytes(),
+            encrypt_file_encoded(&master_key, &plaintext, "/big.txt", ContentEncoding { compress: false, padding: Padding::High })
+                .unwrap()
+                .to_bytes(),
+            chunked,
+        ];
+        assert!(plaintext.len() > CHUNK_LEN);
+        for (i, blob) in blobs.iter().enumerate() {
+            let dest = dir.path().join(format!("out-{}.txt", i));
+            assert_eq!(decrypt_file_to_path(&master_key, &blob[..], "/big.txt", &dest).unwrap(), plaintext.len() as u64);
+            assert_eq!(fs::read(&dest).unwrap(), plaintext);
+        }
+
+        let empty = encrypt_file(&master_key, b"", "/empty").unwrap().to_bytes();
+        let dest = dir.path().join("empty");
+        assert_eq!(decrypt_file_to_path(&master_key, &empty[..], "/empty", &dest).unwrap(), 0);
+
+        // Mauvais chemin, corps altéré ou tronqué : ni destination, ni fichier partiel
+        let dest = dir.path().join("refused.txt");
+        let mut altered = blobs[0].clone();
+        let last = altered.len() - 1;
+        altered[last] ^= 1;
+        assert!(decrypt_file_to_path(&master_key, &blobs[0][..], "/other.txt", &dest).is_err());
+        assert!(decrypt_file_to_path(&master_key, &altered[..], "/big.txt", &dest).is_err());
+        assert!(decrypt_file_to_path(&master_key, &blobs[3][..blobs[3].len() - 10], "/big.txt", &dest).is_err());
+        let names: Vec<_> = fs::read_dir(dir.path()).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(names.len(), blobs.len() + 1);
+        assert!(!dest.exists());
+    }
+}
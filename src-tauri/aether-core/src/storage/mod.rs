@@ -0,0 +1,696 @@
+use crate::crypto::{CryptoError, FileKey, MasterKey};
+use chacha20poly1305::{
+    aead::{Aead, AeadInPlace, KeyInit, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use hkdf::Hkdf;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Sha256, Digest};
+use std::fmt;
+use std::io::{Read, Write};
+use zeroize::Zeroizing;
+
+pub mod aether_format;
+pub use aether_format::{AetherFile, AetherHeader, AetherError};
+pub mod chunked;
+pub use chunked::{chunked_len, decrypt_stream, encrypt_stream, is_chunked, CHUNK_LEN};
+pub mod disk;
+pub use disk::decrypt_file_to_path;
+
+/// Constantes pour le format de fichier Aether (V1)
+const MAGIC_NUMBER: &[u8] = b"AETH";
+const VERSION: u8 = 0x01;
+/// Version dont le corps est encadré (compression, bourrage) : voir [`ContentEncoding`].
+const VERSION_FRAMED: u8 = 0x02;
+const CIPHER_ID: u8 = 0x02;
+const UUID_LEN: usize = 16;
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+/// Longueur du tag Poly1305 ajouté après le ciphertext.
+pub const TAG_LEN: usize = 16;
+const FILE_KEY_INFO: &[u8] = b"aether-drive:file-key:v1";
+/// Fin d'un corps encadré : longueur du contenu (u64 LE) puis drapeaux.
+pub const TRAILER_LEN: usize = 9;
+const FLAG_DEFLATE: u8 = 0x01;
+/// Granularité du bourrage [`Padding::Standard`].
+const PAD_BLOCK: u64 = 4096;
+
+/// Bourrage du contenu avant chiffrement, qui masque la taille exacte du fichier.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Padding {
+    #[default]
+    None,
+    /// Multiple de 4 Kio.
+    Standard,
+    /// Puissance de deux supérieure (au moins 4 Kio) : ne laisse deviner que l'ordre de grandeur.
+    High,
+}
+
+impl Padding {
+    fn padded_len(self, len: u64) -> u64 {
+        match self {
+            Padding::None => len,
+            Padding::Standard => len.div_ceil(PAD_BLOCK) * PAD_BLOCK,
+            Padding::High => len.max(PAD_BLOCK).next_power_of_two(),
+        }
+    }
+}
+
+/// Transformations du contenu avant chiffrement.
+///
+/// Un fichier transformé est écrit en version 2 du format : son corps chiffré est
+/// `contenu (compressé ou non) || zéros de bourrage || fin`, la fin donnant la longueur du
+/// contenu et les drapeaux. Sans transformation, le fichier reste en version 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContentEncoding {
+    /// Compression DEFLATE.
+    pub compress: bool,
+    pub padding: Padding,
+}
+
+impl ContentEncoding {
+    pub fn is_identity(&self) -> bool {
+        !self.compress && self.padding == Padding::None
+    }
+
+    /// Taille du corps encadré (fin comprise) pour un contenu de `content_len` octets.
+    pub fn framed_len(&self, content_len: u64) -> u64 {
+        self.padding.padded_len(content_len + TRAILER_LEN as u64)
+    }
+
+    /// Fin d'un corps encadré.
+    pub fn trailer(&self, content_len: u64) -> [u8; TRAILER_LEN] {
+        let mut trailer = [0u8; TRAILER_LEN];
+        trailer[..8].copy_from_slice(&content_len.to_le_bytes());
+        trailer[8] = if self.compress { FLAG_DEFLATE } else { 0 };
+        trailer
+    }
+}
+
+/// Erreurs du module Storage
+#[derive(Debug)]
+pub enum StorageError {
+    InvalidFormat(String),
+    Crypto(CryptoError),
+    Io(String),
+    InvalidHeader,
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::InvalidFormat(msg) => write!(f, "Invalid file format: {}", msg),
+            StorageError::Crypto(e) => write!(f, "Crypto error: {}", e),
+            StorageError::Io(msg) => write!(f, "IO error: {}", msg),
+            StorageError::InvalidHeader => write!(f, "Invalid Aether file header"),
+        }
+    }
+}
+
+impl From<CryptoError> for StorageError {
+    fn from(e: CryptoError) -> Self {
+        StorageError::Crypto(e)
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+/// Chiffre un fichier selon le format Aether V1
+///
+/// # Arguments
+/// * `master_key` - La MasterKey pour dériver la FileKey
+/// * `plaintext` - Les données en clair à chiffrer
+/// * `logical_path` - Le chemin logique du fichier (utilisé dans l'AAD)
+///
+/// # Returns
+/// Un `AetherFile` contenant l'en-tête et le corps chiffré
+pub fn encrypt_file(
+    master_key: &MasterKey,
+    plaintext: &[u8],
+    logical_path: &str,
+) -> Result<AetherFile, StorageError> {
+    // Génère un UUID unique pour ce fichier
+    let mut uuid = [0u8; UUID_LEN];
+    OsRng.fill_bytes(&mut uuid);
+    encrypt_file_with_uuid(master_key, plaintext, logical_path, uuid)
+}
+
+/// Comme [`encrypt_file`], en conservant l'UUID (et donc la clé d'objet) d'un fichier existant.
+///
+/// Sert à re-lier un contenu à un nouveau chemin logique sans changer d'identité ;
+/// salt et nonce sont tout de même renouvelés.
+pub fn encrypt_file_with_uuid(
+    master_key: &MasterKey,
+    plaintext: &[u8],
+    logical_path: &str,
+    uuid: [u8; UUID_LEN],
+) -> Result<AetherFile, StorageError> {
+    encrypt_file_with_uuid_encoded(master_key, plaintext, logical_path, uuid, ContentEncoding::default())
+}
+
+/// Comme [`encrypt_file`], en transformant d'abord le contenu selon `encoding`.
+pub fn encrypt_file_encoded(
+    master_key: &MasterKey,
+    plaintext: &[u8],
+    logical_path: &str,
+    encoding: ContentEncoding,
+) -> Result<AetherFile, StorageError> {
+    let mut uuid = [0u8; UUID_LEN];
+    OsRng.fill_bytes(&mut uuid);
+    encrypt_file_with_uuid_encoded(master_key, plaintext, logical_path, uuid, encoding)
+}
+
+/// Comme [`encrypt_file_with_uuid`], en transformant d'abord le contenu selon `encoding`.
+pub fn encrypt_file_with_uuid_encoded(
+    master_key: &MasterKey,
+    plaintext: &[u8],
+    logical_path: &str,
+    uuid: [u8; UUID_LEN],
+    encoding: ContentEncoding,
+) -> Result<AetherFile, StorageError> {
+    let mut ciphertext = if encoding.is_identity() {
+        let mut body = Zeroizing::new(Vec::with_capacity(plaintext.len() + TAG_LEN));
+        body.extend_from_slice(plaintext);
+        body
+    } else {
+        frame(plaintext, encoding)?
+    };
+    let (header, tag) = if encoding.is_identity() {
+        encrypt_in_place(master_key, &mut ciphertext, logical_path, uuid)?
+    } else {
+        encrypt_framed_in_place(master_key, &mut ciphertext, logical_path, uuid)?
+    };
+    ciphertext.extend_from_slice(&tag);
+
+    Ok(AetherFile { header, ciphertext })
+}
+
+/// Corps encadré de `plaintext` (voir [`ContentEncoding`]), avec la place du tag réservée.
+fn frame(plaintext: &[u8], encoding: ContentEncoding) -> Result<Zeroizing<Vec<u8>>, StorageError> {
+    let mut compressed = Zeroizing::new(Vec::new());
+    if encoding.compress {
+        let mut encoder = DeflateEncoder::new(&mut *compressed, Compression::default());
+        encoder
+            .write_all(plaintext)
+            .and_then(|_| encoder.finish().map(|_| ()))
+            .map_err(|e| StorageError::Io(format!("Compression failed: {}", e)))?;
+    }
+    let content: &[u8] = if encoding.compress { &compressed } else { plaintext };
+
+    let framed_len = encoding.framed_len(content.len() as u64) as usize;
+    let mut body = Zeroizing::new(Vec::with_capacity(framed_len + TAG_LEN));
+    body.extend_from_slice(content);
+    body.resize(framed_len - TRAILER_LEN, 0);
+    body.extend_from_slice(&encoding.trailer(content.len() as u64));
+    Ok(body)
+}
+
+/// Longueur du contenu et compression d'un corps encadré déchiffré.
+pub fn framed_content(body: &[u8]) -> Result<(usize, bool), StorageError> {
+    let invalid = || StorageError::InvalidFormat("Invalid framed body".to_string());
+    let trailer_start = body.len().checked_sub(TRAILER_LEN).ok_or_else(invalid)?;
+    let trailer = &body[trailer_start..];
+    let content_len = u64::from_le_bytes(trailer[..8].try_into().map_err(|_| invalid())?);
+    let flags = trailer[8];
+    if content_len > trailer_start as u64 || flags & !FLAG_DEFLATE != 0 {
+        return Err(invalid());
+    }
+    Ok((content_len as usize, flags & FLAG_DEFLATE != 0))
+}
+
+/// Contenu d'origine d'un corps encadré déchiffré.
+fn unframe(mut body: Vec<u8>) -> Result<Vec<u8>, StorageError> {
+    let (content_len, compressed) = framed_content(&body)?;
+    if !compressed {
+        body.truncate(content_len);
+        return Ok(body);
+    }
+    let body = Zeroizing::new(body);
+    let mut plaintext = Vec::new();
+    DeflateDecoder::new(&body[..content_len])
+        .read_to_end(&mut plaintext)
+        .map_err(|e| StorageError::InvalidFormat(format!("Invalid compressed content: {}", e)))?;
+    Ok(plaintext)
+}
+
+/// Décompresse le contenu compressé d'un corps encadré dans `output` ; retourne la taille
+/// du clair.
+pub fn inflate_to(content: &[u8], mut output: impl Write) -> Result<u64, StorageError> {
+    std::io::copy(&mut DeflateDecoder::new(content), &mut output)
+        .map_err(|e| StorageError::InvalidFormat(format!("Invalid compressed content: {}", e)))
+}
+
+/// Vrai si le corps de ce fichier est encadré (version 2 du format).
+pub fn is_framed(header: &AetherHeader) -> bool {
+    header.version == VERSION_FRAMED
+}
+
+/// Taille du clair d'après l'en-tête et la longueur du ciphertext ; `None` pour un corps
+/// encadré, dont la longueur du contenu est chiffrée avec lui.
+pub fn plaintext_len(header: &AetherHeader, ciphertext_len: u64) -> Option<u64> {
+    if is_framed(header) {
+        None
+    } else if is_chunked(header) {
+        Some(chunked::chunked_plaintext_len(ciphertext_len))
+    } else {
+        Some(ciphertext_len.saturating_sub(TAG_LEN as u64))
+    }
+}
+
+/// Chiffre `buffer` sur place et retourne l'en-tête et le tag Poly1305 à placer après.
+///
+/// Le ciphertext d'un fichier Aether est `buffer || tag` : sert à chiffrer un fichier
+/// directement dans sa destination (fichier projeté en mémoire) sans copie en clair.
+pub fn encrypt_in_place(
+    master_key: &MasterKey,
+    buffer: &mut [u8],
+    logical_path: &str,
+    uuid: [u8; UUID_LEN],
+) -> Result<(AetherHeader, [u8; TAG_LEN]), StorageError> {
+    // Génère un salt unique pour la dérivation de la FileKey, et un nonce unique
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    seal_in_place(master_key, buffer, logical_path, uuid, salt, nonce, VERSION)
+}
+
+/// Comme [`encrypt_in_place`] pour un corps déjà encadré (version 2, voir [`ContentEncoding`]).
+pub fn encrypt_framed_in_place(
+    master_key: &MasterKey,
+    buffer: &mut [u8],
+    logical_path: &str,
+    uuid: [u8; UUID_LEN],
+) -> Result<(AetherHeader, [u8; TAG_LEN]), StorageError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    seal_in_place(master_key, buffer, logical_path, uuid, salt, nonce, VERSION_FRAMED)
+}
+
+/// Chiffre un fichier avec un UUID, un sel et un nonce imposés : le blob produit est
+/// reproductible octet pour octet (vecteurs de test, voir [`crate::vectors`]).
+///
+/// Ne jamais l'utiliser pour de vraies données : réutiliser un nonce sous la même clé
+/// casse XChaCha20-Poly1305.
+#[cfg(feature = "test-vectors")]
+pub fn encrypt_file_deterministic(
+    master_key: &MasterKey,
+    plaintext: &[u8],
+    logical_path: &str,
+    uuid: [u8; UUID_LEN],
+    salt: [u8; SALT_LEN],
+    nonce: [u8; NONCE_LEN],
+) -> Result<AetherFile, StorageError> {
+    let mut ciphertext = Zeroizing::new(Vec::with_capacity(plaintext.len() + TAG_LEN));
+    ciphertext.extend_from_slice(plaintext);
+    let (header, tag) = seal_in_place(master_key, &mut ciphertext, logical_path, uuid, salt, nonce, VERSION)?;
+    ciphertext.extend_from_slice(&tag);
+    Ok(AetherFile { header, ciphertext })
+}
+
+fn seal_in_place(
+    master_key: &MasterKey,
+    buffer: &mut [u8],
+    logical_path: &str,
+    uuid: [u8; UUID_LEN],
+    salt: [u8; SALT_LEN],
+    nonce_bytes: [u8; NONCE_LEN],
+    version: u8,
+) -> Result<(AetherHeader, [u8; TAG_LEN]), StorageError> {
+    // Dérive la FileKey depuis la MasterKey via HKDF-SHA256
+    let file_key = derive_file_key(master_key, &salt)?;
+    let nonce = XNonce::from_slice(&nonce_bytes);
+
+    // Construit l'AAD (Additional Authenticated Data) avec le chemin logique
+    let aad = build_aad(logical_path);
+
+    // Chiffre avec XChaCha20-Poly1305
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(file_key.as_bytes()));
+    let tag = cipher
+        .encrypt_in_place_detached(nonce, &aad, buffer)
+        .map_err(|e| StorageError::Crypto(CryptoError::from(e)))?;
+
+    Ok((build_header(&file_key, uuid, salt, nonce_bytes, version), tag.into()))
+}
+
+/// Construit l'en-tête, puis le Commitment HMAC (calculé sur Magic + Version + CipherID + UUID + Salt).
+fn build_header(
+    file_key: &FileKey,
+    uuid: [u8; UUID_LEN],
+    salt: [u8; SALT_LEN],
+    nonce: [u8; NONCE_LEN],
+    version: u8,
+) -> AetherHeader {
+    let mut header = AetherHeader {
+        magic: MAGIC_NUMBER.try_into().unwrap(),
+        version,
+        cipher_id: CIPHER_ID,
+        uuid,
+        salt,
+        commitment_hmac: [0u8; 32],
+        nonce,
+    };
+    header.commitment_hmac = compute_commitment(&header, file_key);
+    header
+}
+
+/// Déchiffre un fichier au format Aether V1
+///
+/// # Arguments
+/// * `master_key` - La MasterKey pour dériver la FileKey
+/// * `aether_file` - Le fichier chiffré à déchiffrer
+/// * `logical_path` - Le chemin logique attendu du fichier (vérifié dans l'AAD)
+///
+/// # Returns
+/// Les données en clair
+pub fn decrypt_file(
+    master_key: &MasterKey,
+    aether_file: &AetherFile,
+    logical_path: &str,
+) -> Result<Vec<u8>, StorageError> {
+    if is_chunked(&aether_file.header) {
+        let mut plaintext = Vec::with_capacity(aether_file.ciphertext.len());
+        let ciphertext: &[u8] = &aether_file.ciphertext;
+        decrypt_stream(master_key, &aether_file.header, ciphertext.len() as u64, ciphertext, logical_path, &mut plaintext)?;
+        return Ok(plaintext);
+    }
+    let file_key = verify_header(master_key, &aether_file.header)?;
+
+    // Construit l'AAD avec le chemin logique
+    let aad = build_aad(logical_path);
+
+    // Déchiffre le ciphertext
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(file_key.as_bytes()));
+    let nonce = XNonce::from_slice(&aether_file.header.nonce);
+    let plaintext = cipher
+        .decrypt(
+            nonce,
+            Payload {
+                msg: aether_file.ciphertext.as_ref(),
+                aad: &aad,
+            },
+        )
+        .map_err(|e| StorageError::Crypto(CryptoError::from(e)))?;
+
+    if is_framed(&aether_file.header) {
+        return unframe(plaintext);
+    }
+    Ok(plaintext)
+}
+
+/// Déchiffre sur place le corps d'un fichier Aether (`buffer`, sans le tag).
+///
+/// Pendant de [`encrypt_in_place`] : sert à déchiffrer directement dans le fichier de
+/// destination (projeté en mémoire). Le tag est vérifié avant toute écriture : en cas
+/// d'échec, `buffer` est laissé intact et peut être réessayé sous un autre chemin.
+///
+/// Le corps d'un fichier encadré ([`is_framed`]) reste encadré : voir [`framed_content`].
+/// Celui d'un fichier par morceaux se déchiffre avec [`decrypt_stream`].
+pub fn decrypt_in_place(
+    master_key: &MasterKey,
+    header: &AetherHeader,
+    buffer: &mut [u8],
+    tag: &[u8; TAG_LEN],
+    logical_path: &str,
+) -> Result<(), StorageError> {
+    if is_chunked(header) {
+        return Err(StorageError::InvalidFormat("Chunked body: decrypt it as a stream".to_string()));
+    }
+    let file_key = verify_header(master_key, header)?;
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(file_key.as_bytes()));
+    cipher
+        .decrypt_in_place_detached(
+            XNonce::from_slice(&header.nonce),
+            &build_aad(logical_path),
+            buffer,
+            tag.into(),
+        )
+        .map_err(|e| StorageError::Crypto(CryptoError::from(e)))
+}
+
+/// Vérifie le format et le Commitment HMAC de l'en-tête ; retourne la FileKey.
+fn verify_header(master_key: &MasterKey, header: &AetherHeader) -> Result<FileKey, StorageError> {
+    // Vérifie le Magic Number
+    if header.magic != *MAGIC_NUMBER {
+        return Err(StorageError::InvalidFormat("Invalid magic number".to_string()));
+    }
+
+    // Vérifie la version
+    if header.version != VERSION && header.version != VERSION_FRAMED && header.version != chunked::VERSION_CHUNKED {
+        return Err(StorageError::InvalidFormat(format!(
+            "Unsupported version: 0x{:02x}",
+            header.version
+        )));
+    }
+
+    // Vérifie le Cipher ID
+    if header.cipher_id != CIPHER_ID {
+        return Err(StorageError::InvalidFormat(format!(
+            "Unsupported cipher ID: 0x{:02x}",
+            header.cipher_id
+        )));
+    }
+
+    // Dérive la FileKey et vérifie le Commitment HMAC
+    let file_key = derive_file_key(master_key, &header.salt)?;
+    let computed_hmac = compute_commitment(header, &file_key);
+    
+    if computed_hmac != header.commitment_hmac {
+        return Err(StorageError::InvalidFormat(
+            "HMAC verification failed".to_string(),
+        ));
+    }
+    Ok(file_key)
+}
+
+/// Paramètres de chiffrement d'un fichier, tels que lus dans son en-tête.
+///
+/// Rien de secret : ni clé, ni sel en clair (seulement une empreinte).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EncryptionDetails {
+    pub format_version: u8,
+    pub cipher_id: u8,
+    pub cipher_suite: &'static str,
+    pub key_derivation: &'static str,
+    /// Chaîne `info` HKDF utilisée pour dériver la FileKey.
+    pub key_derivation_info: String,
+    /// 8 premiers octets (hex) du SHA-256 du sel de la FileKey.
+    pub salt_fingerprint: String,
+    /// Le Commitment HMAC de l'en-tête correspond à la MasterKey du coffre.
+    pub commitment_valid: bool,
+    pub nonce_len: usize,
+}
+
+/// Décrit le chiffrement d'un fichier à partir de son en-tête, et vérifie son engagement.
+pub fn encryption_details(master_key: &MasterKey, header: &AetherHeader) -> Result<EncryptionDetails, StorageError> {
+    let cipher_suite = match header.cipher_id {
+        CIPHER_ID => "XChaCha20-Poly1305",
+        other => return Err(StorageError::InvalidFormat(format!("Unsupported cipher ID: 0x{:02x}", other))),
+    };
+    let file_key = derive_file_key(master_key, &header.salt)?;
+    Ok(EncryptionDetails {
+        format_version: header.version,
+        cipher_id: header.cipher_id,
+        cipher_suite,
+        key_derivation: "HKDF-SHA256",
+        key_derivation_info: String::from_utf8_lossy(FILE_KEY_INFO).into_owned(),
+        salt_fingerprint: hex::encode(&Sha256::digest(header.salt)[..8]),
+        commitment_valid: compute_commitment(header, &file_key) == header.commitment_hmac,
+        nonce_len: header.nonce.len(),
+    })
+}
+
+/// Dérive la FileKey d'un fichier depuis la MasterKey et le sel de son en-tête (HKDF-SHA256).
+pub(crate) fn derive_file_key(master_key: &MasterKey, salt: &[u8; SALT_LEN]) -> Result<FileKey, StorageError> {
+    let master_key_array: [u8; 32] = master_key
+        .as_bytes()
+        .try_into()
+        .map_err(|_| StorageError::InvalidFormat("MasterKey length invalid".to_string()))?;
+    let hkdf = Hkdf::<Sha256>::new(Some(salt), &master_key_array);
+    let mut file_key_bytes = Zeroizing::new([0u8; 32]);
+    hkdf.expand(FILE_KEY_INFO, file_key_bytes.as_mut())
+        .map_err(|_| StorageError::Crypto(CryptoError::HkdfLength))?;
+    Ok(FileKey::from_bytes(file_key_bytes.as_slice()))
+}
+
+/// Commitment HMAC de l'en-tête : SHA-256(Magic + Version + CipherID + UUID + Salt + FileKey).
+pub(crate) fn compute_commitment(header: &AetherHeader, file_key: &FileKey) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(header.magic);
+    hasher.update([header.version, header.cipher_id]);
+    hasher.update(header.uuid);
+    hasher.update(header.salt);
+    hasher.update(file_key.as_bytes()); // Utilise la FileKey comme secret HMAC
+    hasher.finalize().into()
+}
+
+/// Construit l'AAD (Additional Authenticated Data) à partir du chemin logique
+pub(crate) fn build_aad(logical_path: &str) -> Vec<u8> {
+    let mut aad = Vec::new();
+    aad.extend_from_slice(b"aether-drive:aad:v1:");
+    aad.extend_from_slice(logical_path.as_bytes());
+    aad
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{CryptoCore, KeyHierarchy, PasswordSecret};
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let core = CryptoCore::default();
+        let password_secret = PasswordSecret::new("test-password-123");
+        let salt = core.random_password_salt();
+        let hierarchy = KeyHierarchy::bootstrap(&password_secret, salt).unwrap();
+        let master_key = hierarchy.master_key();
+
+        let plaintext = b"Hello, Aether Drive! This is a test file.";
+        let logical_path = "/documents/test.txt";
+
+        // Chiffre le fichier
+        let aether_file = encrypt_file(master_key, plaintext, logical_path).unwrap();
+
+        // Vérifie l'en-tête
+        let expected_magic: [u8; 4] = MAGIC_NUMBER.try_into().unwrap();
+        assert_eq!(aether_file.header.magic, expected_magic);
+        assert_eq!(aether_file.header.version, VERSION);
+        assert_eq!(aether_file.header.cipher_id, CIPHER_ID);
+
+        // Déchiffre le fichier
+        let decrypted = decrypt_file(master_key, &aether_file, logical_path).unwrap();
+
+        // Vérifie que le plaintext correspond
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_decrypt_wrong_path_fails() {
+        let core = CryptoCore::default();
+        let password_secret = PasswordSecret::new("test-password-123");
+        let salt = core.random_password_salt();
+        let hierarchy = KeyHierarchy::bootstrap(&password_secret, salt).unwrap();
+        let master_key = hierarchy.master_key();
+
+        let plaintext = b"Hello, Aether Drive!";
+        let logical_path = "/documents/test.txt";
+
+        // Chiffre avec un chemin
+        let aether_file = encrypt_file(master_key, plaintext, logical_path).unwrap();
+
+        // Essaie de déchiffrer avec un chemin différent (doit échouer)
+        let wrong_path = "/documents/different.txt";
+        let result = decrypt_file(master_key, &aether_file, wrong_path);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_decrypt_wrong_master_key_fails() {
+        let core = CryptoCore::default();
+        let password_secret1 = PasswordSecret::new("password-1");
+        let password_secret2 = PasswordSecret::new("password-2");
+        let salt = core.random_password_salt();
+        
+        let hierarchy1 = KeyHierarchy::bootstrap(&password_secret1, salt).unwrap();
+        let hierarchy2 = KeyHierarchy::bootstrap(&password_secret2, salt).unwrap();
+        
+        let master_key1 = hierarchy1.master_key();
+        let master_key2 = hierarchy2.master_key();
+
+        let plaintext = b"Secret data";
+        let logical_path = "/documents/secret.txt";
+
+        // Chiffre avec master_key1
+        let aether_file = encrypt_file(master_key1, plaintext, logical_path).unwrap();
+
+        // Essaie de déchiffrer avec master_key2 (doit échouer)
+        let result = decrypt_file(master_key2, &aether_file, logical_path);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_encryption_details_checks_commitment() {
+        let master_key = MasterKey::from_vec(vec![3u8; 32]);
+        let mut aether_file = encrypt_file(&master_key, b"data", "/a.txt").unwrap();
+
+        let details = encryption_details(&master_key, &aether_file.header).unwrap();
+        assert_eq!(details.cipher_suite, "XChaCha20-Poly1305");
+        assert_eq!(details.key_derivation_info, "aether-drive:file-key:v1");
+        assert_eq!(details.salt_fingerprint.len(), 16);
+        assert!(details.commitment_valid);
+
+        let other_key = MasterKey::from_vec(vec![4u8; 32]);
+        assert!(!encryption_details(&other_key, &aether_file.header).unwrap().commitment_valid);
+        aether_file.header.uuid[0] ^= 1;
+        assert!(!encryption_details(&master_key, &aether_file.header).unwrap().commitment_valid);
+    }
+
+    #[test]
+    fn test_decrypt_in_place_leaves_buffer_untouched_on_failure() {
+        let master_key = MasterKey::from_vec(vec![3u8; 32]);
+        let aether_file = encrypt_file(&master_key, b"streamed content", "/b.txt").unwrap();
+        let (body, tag) = aether_file.ciphertext.split_at(aether_file.ciphertext.len() - TAG_LEN);
+        let tag: [u8; TAG_LEN] = tag.try_into().unwrap();
+
+        let mut buffer = body.to_vec();
+        assert!(decrypt_in_place(&master_key, &aether_file.header, &mut buffer, &tag, "/a.txt").is_err());
+        assert_eq!(buffer, body);
+        decrypt_in_place(&master_key, &aether_file.header, &mut buffer, &tag, "/b.txt").unwrap();
+        assert_eq!(buffer, b"streamed content");
+    }
+
+    #[test]
+    fn test_framed_content_roundtrip() {
+        let master_key = MasterKey::from_vec(vec![3u8; 32]);
+        let plaintext = b"compressible ".repeat(500);
+        let uuid = [7u8; UUID_LEN];
+        for (compress, padding) in [(true, Padding::None), (false, Padding::Standard), (true, Padding::High)] {
+            let encoding = ContentEncoding { compress, padding };
+            let aether_file = encrypt_file_with_uuid_encoded(&master_key, &plaintext, "/c.txt", uuid, encoding).unwrap();
+            assert!(is_framed(&aether_file.header));
+            assert_eq!(decrypt_file(&master_key, &aether_file, "/c.txt").unwrap(), plaintext);
+            let body_len = (aether_file.ciphertext.len() - TAG_LEN) as u64;
+            match padding {
+                Padding::None => assert!(body_len < plaintext.len() as u64),
+                Padding::Standard => assert_eq!(body_len % PAD_BLOCK, 0),
+                Padding::High => assert!(body_len.is_power_of_two()),
+            }
+        }
+        // Sans transformation, le format V1 est inchangé
+        let plain = encrypt_file_encoded(&master_key, b"data", "/d.txt", ContentEncoding::default()).unwrap();
+        assert!(!is_framed(&plain.header));
+        assert_eq!(plain.ciphertext.len(), 4 + TAG_LEN);
+
+        assert!(framed_content(&[0u8; 4]).is_err());
+        let mut lying = vec![0u8; 4];
+        lying.extend_from_slice(&ContentEncoding::default().trailer(100));
+        assert!(framed_content(&lying).is_err());
+    }
+
+    #[test]
+    fn test_plaintext_len_from_header() {
+        let master_key = MasterKey::from_vec(vec![4u8; 32]);
+        for len in [0, 10, CHUNK_LEN, CHUNK_LEN + 1, 3 * CHUNK_LEN + 7] {
+            let plaintext = vec![9u8; len];
+            let v1 = encrypt_file(&master_key, &plaintext, "/e.bin").unwrap().to_bytes();
+            let mut v3 = Vec::new();
+            encrypt_stream(&master_key, &plaintext[..], len as u64, "/e.bin", &mut v3).unwrap();
+            for blob in [&v1, &v3] {
+                let (header, ciphertext_len) = AetherFile::parse_header(blob).unwrap();
+                assert_eq!(plaintext_len(&header, ciphertext_len), Some(len as u64));
+            }
+            assert_eq!(AetherFile::v1_plaintext_len(v1.len() as u64), len as u64);
+        }
+        // Corps encadré : la longueur du contenu n'est connue qu'après déchiffrement
+        let encoding = ContentEncoding { compress: true, padding: Padding::Standard };
+        let v2 = encrypt_file_encoded(&master_key, &b"framed ".repeat(100), "/f.txt", encoding).unwrap();
+        assert_eq!(plaintext_len(&v2.header, v2.ciphertext.len() as u64), None);
+    }
+}
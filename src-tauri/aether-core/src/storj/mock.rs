@@ -0,0 +1,387 @@
+//! Serveur S3 en mémoire pour les tests (feature `mock-s3`).
+//!
+//! Couvre le sous-ensemble de l'API utilisé par [`StorjClient`](super::StorjClient) en
+//! path-style : PUT/GET (avec Range)/HEAD/DELETE d'objet, CopyObject, ListObjectsV2 et
+//! ListMultipartUploads. Aucune signature n'est vérifiée.
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{header, Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+use super::StorjConfig;
+use crate::clock::unix_now;
+
+/// Clé → (contenu, timestamp Unix de l'écriture).
+type Objects = Arc<Mutex<BTreeMap<String, (Vec<u8>, i64)>>>;
+
+/// Clés dont le DELETE est acquitté sans effet.
+type Retained = Arc<Mutex<BTreeSet<String>>>;
+
+/// Clé → (GET servis, plafond au-delà duquel ils échouent).
+type Reads = Arc<Mutex<BTreeMap<String, (usize, Option<usize>)>>>;
+
+/// Bucket S3 en mémoire servi sur 127.0.0.1 ; arrêté en le droppant ou via `stop`.
+pub struct MockS3Server {
+    pub addr: SocketAddr,
+    bucket: String,
+    objects: Objects,
+    retained: Retained,
+    reads: Reads,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockS3Server {
+    /// Démarre un serveur sur un port libre avec un bucket vide.
+    pub async fn start(bucket: &str) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let addr = listener.local_addr()?;
+        let objects: Objects = Arc::default();
+        let retained: Retained = Arc::default();
+        let reads: Reads = Arc::default();
+
+        let (tx, mut rx) = oneshot::channel();
+        let state = Arc::clone(&objects);
+        let retained_state = Arc::clone(&retained);
+        let reads_state = Arc::clone(&reads);
+        let bucket_name = bucket.to_string();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    accepted = listener.accept() => {
+                        let Ok((stream, _)) = accepted else { continue };
+                        let objects = Arc::clone(&state);
+                        let retained = Arc::clone(&retained_state);
+                        let reads = Arc::clone(&reads_state);
+                        let bucket = bucket_name.clone();
+                        tokio::spawn(async move {
+                            let service = service_fn(move |req: Request<hyper::body::Incoming>| {
+                                let objects = Arc::clone(&objects);
+                                let retained = Arc::clone(&retained);
+                                let reads = Arc::clone(&reads);
+                                let bucket = bucket.clone();
+                                async move { Ok::<_, Infallible>(handle(req, &bucket, &objects, &retained, &reads).await) }
+                            });
+                            if let Err(e) = http1::Builder::new()
+                                .serve_connection(TokioIo::new(stream), service)
+                                .await
+                            {
+                                log::debug!("Mock S3 connection closed: {}", e);
+                            }
+                        });
+                    }
+                    _ = &mut rx => break,
+                }
+            }
+        });
+
+        Ok(Self {
+            addr,
+            bucket: bucket.to_string(),
+            objects,
+            retained,
+            reads,
+            shutdown: Some(tx),
+        })
+    }
+
+    /// Configuration client pointant vers ce serveur.
+    pub fn config(&self) -> StorjConfig {
+        StorjConfig::new(
+            "mock-access-key".to_string(),
+            "mock-secret-key".to_string(),
+            format!("http://{}", self.addr),
+            self.bucket.clone(),
+        )
+    }
+
+    /// Clés des objets actuellement stockés, triées.
+    pub fn object_keys(&self) -> Vec<String> {
+        self.objects.lock().map(|o| o.keys().cloned().collect()).unwrap_or_default()
+    }
+
+    pub fn object(&self, key: &str) -> Option<Vec<u8>> {
+        self.objects.lock().ok().and_then(|o| o.get(key).map(|(data, _)| data.clone()))
+    }
+
+    /// Remplace le contenu d'un objet sans passer par le client (simulation d'altération).
+    pub fn put_object(&self, key: &str, data: Vec<u8>) {
+        if let Ok(mut objects) = self.objects.lock() {
+            objects.insert(key.to_string(), (data, unix_now()));
+        }
+    }
+
+    /// Le DELETE de `key` répondra 204 sans supprimer l'objet (suppression non appliquée).
+    pub fn retain_on_delete(&self, key: &str) {
+        if let Ok(mut retained) = self.retained.lock() {
+            retained.insert(key.to_string());
+        }
+    }
+
+    /// Nombre de GET servis pour `key`.
+    pub fn reads(&self, key: &str) -> usize {
+        self.reads.lock().ok().and_then(|reads| reads.get(key).map(|(served, _)| *served)).unwrap_or(0)
+    }
+
+    /// Les GET de `key` échouent dès que `limit` ont été servis (connexion coupée) ;
+    /// `None` lève la limite.
+    pub fn fail_reads_after(&self, key: &str, limit: Option<usize>) {
+        if let Ok(mut reads) = self.reads.lock() {
+            reads.entry(key.to_string()).or_default().1 = limit;
+        }
+    }
+
+    pub fn stop(mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+impl Drop for MockS3Server {
+    fn drop(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+async fn handle(
+    req: Request<hyper::body::Incoming>,
+    bucket: &str,
+    objects: &Objects,
+    retained: &Retained,
+    reads: &Reads,
+) -> Response<Full<Bytes>> {
+    let path = req.uri().path().trim_start_matches('/').to_string();
+    let query = req.uri().query().unwrap_or("").to_string();
+    let (req_bucket, key) = match path.split_once('/') {
+        Some((b, k)) => (b.to_string(), k.to_string()),
+        None => (path.clone(), String::new()),
+    };
+    if req_bucket != bucket {
+        return error(StatusCode::NOT_FOUND, "NoSuchBucket");
+    }
+
+    let method = req.method().clone();
+    let range = req
+        .headers()
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let if_none_match = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    // CopyObject : `x-amz-copy-source: <bucket>/<clé>`
+    let copy_source = req
+        .headers()
+        .get("x-amz-copy-source")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim_start_matches('/').to_string());
+    let chunked = req
+        .headers()
+        .get("x-amz-content-sha256")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("STREAMING-"))
+        || req
+            .headers()
+            .get(header::CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.contains("aws-chunked"));
+    let body = match req.into_body().collect().await {
+        Ok(collected) => collected.to_bytes(),
+        Err(_) => return error(StatusCode::BAD_REQUEST, "IncompleteBody"),
+    };
+    let Ok(mut objects) = objects.lock() else {
+        return error(StatusCode::INTERNAL_SERVER_ERROR, "InternalError");
+    };
+
+    match (method, key.is_empty()) {
+        (Method::GET, true) if query.contains("uploads") => xml(format!(
+            "<ListMultipartUploadsResult><Bucket>{}</Bucket><IsTruncated>false</IsTruncated></ListMultipartUploadsResult>",
+            bucket
+        )),
+        (Method::GET, true) => {
+            let contents: String = objects
+                .iter()
+                .map(|(key, (data, modified))| {
+                    format!(
+                        "<Contents><Key>{}</Key><LastModified>{}</LastModified><Size>{}</Size><ETag>\"{}\"</ETag><StorageClass>STANDARD</StorageClass></Contents>",
+                        key,
+                        iso8601(*modified),
+                        data.len(),
+                        etag(data)
+                    )
+                })
+                .collect();
+            xml(format!(
+                "<ListBucketResult><Name>{}</Name><Prefix></Prefix><KeyCount>{}</KeyCount><MaxKeys>1000</MaxKeys><IsTruncated>false</IsTruncated>{}</ListBucketResult>",
+                bucket,
+                objects.len(),
+                contents
+            ))
+        }
+        (Method::PUT, false) if copy_source.is_some() => {
+            let source = copy_source.as_deref().and_then(|source| source.strip_prefix(&format!("{}/", bucket)));
+            let Some((data, _)) = source.and_then(|source| objects.get(source)).cloned() else {
+                return error(StatusCode::NOT_FOUND, "NoSuchKey");
+            };
+            let now = unix_now();
+            let body = format!(
+                "<CopyObjectResult><LastModified>{}</LastModified><ETag>\"{}\"</ETag></CopyObjectResult>",
+                iso8601(now),
+                etag(&data)
+            );
+            objects.insert(key, (data, now));
+            xml(body)
+        }
+        (Method::PUT, false) => {
+            let data = if chunked { decode_aws_chunked(&body) } else { body.to_vec() };
+            let etag = etag(&data);
+            objects.insert(key, (data, unix_now()));
+            Response::builder()
+                .header(header::ETAG, format!("\"{}\"", etag))
+                .body(Full::new(Bytes::new()))
+                .unwrap_or_default()
+        }
+        (Method::GET, false) => {
+            let Some((data, _)) = objects.get(&key) else {
+                return error(StatusCode::NOT_FOUND, "NoSuchKey");
+            };
+            // Lecture conditionnelle d'un objet inchangé : rien n'est servi
+            if if_none_match.as_deref() == Some(format!("\"{}\"", etag(data)).as_str()) {
+                return Response::builder()
+                    .status(StatusCode::NOT_MODIFIED)
+                    .header(header::ETAG, format!("\"{}\"", etag(data)))
+                    .body(Full::new(Bytes::new()))
+                    .unwrap_or_default();
+            }
+            if let Ok(mut reads) = reads.lock() {
+                let (served, limit) = reads.entry(key.clone()).or_default();
+                if limit.is_some_and(|limit| *served >= limit) {
+                    return error(StatusCode::INTERNAL_SERVER_ERROR, "InternalError");
+                }
+                *served += 1;
+            }
+            match range.as_deref().and_then(|r| parse_range(r, data.len())) {
+                Some((start, end)) => Response::builder()
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, data.len()))
+                    .header(header::CONTENT_LENGTH, end + 1 - start)
+                    .header(header::ETAG, format!("\"{}\"", etag(data)))
+                    .body(Full::new(Bytes::copy_from_slice(&data[start..=end])))
+                    .unwrap_or_default(),
+                None => Response::builder()
+                    .header(header::CONTENT_LENGTH, data.len())
+                    .header(header::ETAG, format!("\"{}\"", etag(data)))
+                    .body(Full::new(Bytes::from(data.clone())))
+                    .unwrap_or_default(),
+            }
+        }
+        (Method::HEAD, false) => match objects.get(&key) {
+            Some((data, _)) => Response::builder()
+                .header(header::CONTENT_LENGTH, data.len())
+                .header(header::ETAG, format!("\"{}\"", etag(data)))
+                .body(Full::new(Bytes::new()))
+                .unwrap_or_default(),
+            None => Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Full::new(Bytes::new()))
+                .unwrap_or_default(),
+        },
+        (Method::DELETE, false) => {
+            if !retained.lock().is_ok_and(|retained| retained.contains(&key)) {
+                objects.remove(&key);
+            }
+            Response::builder()
+                .status(StatusCode::NO_CONTENT)
+                .body(Full::new(Bytes::new()))
+                .unwrap_or_default()
+        }
+        _ => error(StatusCode::NOT_IMPLEMENTED, "NotImplemented"),
+    }
+}
+
+fn xml(body: String) -> Response<Full<Bytes>> {
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(Full::new(Bytes::from(format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>{}",
+            body
+        ))))
+        .unwrap_or_default()
+}
+
+fn error(status: StatusCode, code: &str) -> Response<Full<Bytes>> {
+    let mut response = xml(format!("<Error><Code>{}</Code><Message>{}</Message></Error>", code, code));
+    *response.status_mut() = status;
+    response
+}
+
+/// Timestamp Unix → `2024-05-01T12:00:00.000Z` (format des listings S3).
+fn iso8601(secs: i64) -> String {
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    // Conversion jours → date civile (algorithme de H. Hinnant)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.000Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// ETag factice mais stable : SHA-256 tronqué du contenu.
+fn etag(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(&Sha256::digest(data)[..16])
+}
+
+/// `bytes=a-b` ou `bytes=a-` → bornes inclusives, bornées à la taille de l'objet.
+fn parse_range(range: &str, len: usize) -> Option<(usize, usize)> {
+    let (start, end) = range.strip_prefix("bytes=")?.split_once('-')?;
+    let start: usize = start.parse().ok()?;
+    let end = match end {
+        "" => len.checked_sub(1)?,
+        end => end.parse::<usize>().ok()?.min(len.checked_sub(1)?),
+    };
+    (start <= end).then_some((start, end))
+}
+
+/// Décode un corps `aws-chunked` (`<taille hex>[;ext]\r\n<données>\r\n` … `0\r\n<trailers>`).
+fn decode_aws_chunked(body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(line_end) = rest.windows(2).position(|w| w == b"\r\n") {
+        let line = String::from_utf8_lossy(&rest[..line_end]);
+        let size = usize::from_str_radix(line.split(';').next().unwrap_or("").trim(), 16).unwrap_or(0);
+        rest = &rest[line_end + 2..];
+        if size == 0 || size > rest.len() {
+            break;
+        }
+        out.extend_from_slice(&rest[..size]);
+        rest = rest.get(size + 2..).unwrap_or_default();
+    }
+    out
+}
@@ -0,0 +1,750 @@
+use aws_sdk_s3::Client as S3Client;
+use aws_sdk_s3::config::Config;
+use aws_sdk_s3::primitives::{ByteStream, SdkBody};
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
+use bytes::Bytes;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+#[cfg(feature = "mock-s3")]
+pub mod mock;
+
+// Le module client est défini directement ici pour simplifier
+
+/// Préfixe des objets internes du coffre (sauvegardes de l'index…), qui ne sont pas des
+/// fichiers : [`StorjClient::list_files`] et [`StorjClient::list_objects`] les ignorent.
+pub const RESERVED_PREFIX: &str = ".aether/";
+
+/// Racine des préfixes par coffre : chaque coffre range ses objets sous
+/// `vaults/<empreinte>/`, ce qui permet à plusieurs coffres de partager un bucket.
+pub const VAULTS_PREFIX: &str = "vaults/";
+
+/// Préfixe distant d'un coffre, à partir de son empreinte (voir `MasterKey::fingerprint`).
+pub fn vault_prefix(fingerprint: &str) -> String {
+    format!("{}{}/", VAULTS_PREFIX, fingerprint)
+}
+
+/// Configuration pour le client Storj DCS.
+///
+/// Storj DCS utilise une API compatible S3, donc nous utilisons les identifiants S3 :
+/// - Access Key ID
+/// - Secret Access Key
+/// - Endpoint (ex: https://gateway.storjshare.io)
+/// - Bucket name
+#[derive(Debug, Clone)]
+pub struct StorjConfig {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub endpoint: String,
+    pub bucket_name: String,
+    pub region: String,
+}
+
+impl StorjConfig {
+    pub fn new(
+        access_key_id: String,
+        secret_access_key: String,
+        endpoint: String,
+        bucket_name: String,
+    ) -> Self {
+        Self {
+            access_key_id,
+            secret_access_key,
+            endpoint,
+            bucket_name,
+            region: "us-east-1".to_string(), // Storj utilise généralement us-east-1
+        }
+    }
+}
+
+/// Erreurs du module Storj.
+#[derive(Debug)]
+pub enum StorjError {
+    Config(String),
+    S3(String),
+    Io(String),
+    NotFound,
+    /// Le endpoint est injoignable (pas de réseau, DNS, timeout) : l'opération peut être différée.
+    Unreachable(String),
+    /// Lecture conditionnelle : l'objet porte toujours l'ETag donné.
+    NotModified,
+}
+
+impl fmt::Display for StorjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorjError::Config(msg) => write!(f, "Configuration error: {}", msg),
+            StorjError::S3(msg) => write!(f, "S3/Storj error: {}", msg),
+            StorjError::Io(msg) => write!(f, "IO error: {}", msg),
+            StorjError::NotFound => write!(f, "Object not found"),
+            StorjError::Unreachable(msg) => write!(f, "Storage endpoint unreachable: {}", msg),
+            StorjError::NotModified => write!(f, "Object not modified"),
+        }
+    }
+}
+
+impl std::error::Error for StorjError {}
+
+/// Erreur de transport (requête jamais arrivée au service) plutôt que réponse d'erreur S3.
+fn is_unreachable<E, R>(e: &SdkError<E, R>) -> bool {
+    matches!(e, SdkError::DispatchFailure(_) | SdkError::TimeoutError(_))
+}
+
+/// Objet distant tel que listé par le bucket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteObject {
+    pub key: String,
+    pub size: u64,
+    /// Timestamp Unix (secondes) de la dernière écriture, si le service le fournit.
+    pub last_modified: Option<i64>,
+}
+
+/// Plage d'un objet lue par [`StorjClient::download_range`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeRead {
+    pub data: Vec<u8>,
+    /// Taille totale de l'objet.
+    pub total: u64,
+    /// Version de l'objet lu, si le service la fournit.
+    pub etag: Option<String>,
+}
+
+/// Rappel de progression d'un envoi : octets déjà cédés au transport.
+type UploadProgress = Arc<dyn Fn(u64) + Send + Sync>;
+
+/// Corps d'envoi qui compte les octets lus par le transport.
+struct CountedBody {
+    inner: SdkBody,
+    sent: u64,
+    progress: UploadProgress,
+}
+
+impl http_body::Body for CountedBody {
+    type Data = Bytes;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<http_body::Frame<Bytes>, Self::Error>>> {
+        let this = &mut *self;
+        let frame = std::task::ready!(Pin::new(&mut this.inner).poll_frame(cx));
+        if let Some(data) = frame.as_ref().and_then(|frame| frame.as_ref().ok()).and_then(http_body::Frame::data_ref) {
+            this.sent += data.len() as u64;
+            (this.progress)(this.sent);
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        http_body::Body::is_end_stream(&self.inner)
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        http_body::Body::size_hint(&self.inner)
+    }
+}
+
+/// Instrumente `body` ; il reste rejouable (chaque tentative recompte depuis zéro).
+fn counted(body: ByteStream, progress: UploadProgress) -> ByteStream {
+    ByteStream::new(body.into_inner().map_preserve_contents(move |inner| {
+        SdkBody::from_body_1_x(CountedBody { inner, sent: 0, progress: Arc::clone(&progress) })
+    }))
+}
+
+/// Client Storj pour upload/download de fichiers chiffrés au format Aether.
+pub struct StorjClient {
+    s3_client: S3Client,
+    bucket_name: String,
+    endpoint: String,
+    /// Préfixe appliqué à toutes les clés (vide : racine du bucket).
+    prefix: String,
+}
+
+impl StorjClient {
+    /// Crée un nouveau client Storj à partir d'une configuration.
+    pub async fn new(config: StorjConfig) -> Result<Self, StorjError> {
+        use aws_sdk_s3::config::Credentials;
+        use aws_sdk_s3::config::Region;
+
+        let credentials = Credentials::new(
+            &config.access_key_id,
+            &config.secret_access_key,
+            None,
+            None,
+            "storj",
+        );
+
+        use aws_sdk_s3::config::BehaviorVersion;
+
+        let s3_config = Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .credentials_provider(credentials)
+            .region(Region::new(config.region.clone()))
+            .endpoint_url(&config.endpoint)
+            .force_path_style(true) // Storj nécessite souvent path-style
+            .build();
+
+        let s3_client = S3Client::from_conf(s3_config);
+
+        Ok(Self {
+            s3_client,
+            bucket_name: config.bucket_name,
+            endpoint: config.endpoint,
+            prefix: String::new(),
+        })
+    }
+
+    /// Restreint le client à un préfixe du bucket : les clés reçues et renvoyées lui sont
+    /// relatives, et les objets rangés ailleurs (autres coffres) sont invisibles.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    pub fn bucket_name(&self) -> &str {
+        &self.bucket_name
+    }
+
+    /// Clé complète dans le bucket.
+    fn scoped(&self, object_key: &str) -> String {
+        format!("{}{}", self.prefix, object_key)
+    }
+
+    /// Ramène des objets listés à des clés relatives au préfixe, en écartant les autres.
+    fn unscope(&self, objects: Vec<RemoteObject>) -> Vec<RemoteObject> {
+        objects
+            .into_iter()
+            .filter_map(|obj| {
+                let key = obj.key.strip_prefix(&self.prefix)?.to_string();
+                Some(RemoteObject { key, ..obj })
+            })
+            .collect()
+    }
+
+    /// Déplace sous le préfixe les objets d'avant les préfixes par coffre, rangés à la racine
+    /// du bucket. Seules les clés de `known_keys` sont déplacées : les objets d'autres coffres
+    /// ou applications qui partagent le bucket ne sont pas touchés.
+    ///
+    /// # Returns
+    /// Le nombre d'objets déplacés
+    pub async fn adopt_unprefixed(&self, known_keys: &HashSet<String>) -> Result<usize, StorjError> {
+        if self.prefix.is_empty() {
+            return Ok(0);
+        }
+        let mut adopted = 0;
+        for obj in self.list_raw(None).await? {
+            if obj.key.contains('/') || !known_keys.contains(&obj.key) {
+                continue;
+            }
+            self.copy_object(&obj.key, &self.scoped(&obj.key)).await?;
+            self.delete_object(&obj.key).await?;
+            log::info!("Moved legacy object under vault prefix: key={}", obj.key);
+            adopted += 1;
+        }
+        Ok(adopted)
+    }
+
+    /// Upload un fichier chiffré au format Aether vers Storj.
+    ///
+    /// # Arguments
+    /// * `object_key` - Clé de l'objet dans Storj (généralement l'UUID du fichier)
+    /// * `data` - Données chiffrées au format Aether (bytes)
+    ///
+    /// # Returns
+    /// L'ETag de l'objet uploadé (pour vérification)
+    pub async fn upload_file(
+        &self,
+        object_key: &str,
+        data: &[u8],
+    ) -> Result<String, StorjError> {
+        log::info!("StorjClient::upload_file: bucket={}, key={}, data_len={}", self.bucket_name, object_key, data.len());
+        
+        self.put_object(&self.scoped(object_key), ByteStream::from(data.to_vec())).await
+    }
+
+    /// Comme [`StorjClient::upload_file`], en rapportant à `progress` les octets déjà cédés
+    /// au transport (le compte repart de zéro si le SDK retente l'envoi).
+    pub async fn upload_file_with_progress(
+        &self,
+        object_key: &str,
+        data: &[u8],
+        progress: impl Fn(u64) + Send + Sync + 'static,
+    ) -> Result<String, StorjError> {
+        let body = counted(ByteStream::from(data.to_vec()), Arc::new(progress));
+        self.put_object(&self.scoped(object_key), body).await
+    }
+
+    /// Upload un fichier chiffré lu en flux depuis le disque (jamais chargé entièrement en mémoire).
+    ///
+    /// # Returns
+    /// L'ETag de l'objet uploadé (pour vérification)
+    pub async fn upload_path(&self, object_key: &str, path: &Path) -> Result<String, StorjError> {
+        log::info!("StorjClient::upload_path: bucket={}, key={}", self.bucket_name, object_key);
+        
+        let body = ByteStream::from_path(path)
+            .await
+            .map_err(|e| StorjError::Io(format!("Failed to open staged file: {}", e)))?;
+        self.put_object(&self.scoped(object_key), body).await
+    }
+
+    /// Comme [`StorjClient::upload_path`], en rapportant la progression comme
+    /// [`StorjClient::upload_file_with_progress`].
+    pub async fn upload_path_with_progress(
+        &self,
+        object_key: &str,
+        path: &Path,
+        progress: impl Fn(u64) + Send + Sync + 'static,
+    ) -> Result<String, StorjError> {
+        let body = ByteStream::from_path(path)
+            .await
+            .map_err(|e| StorjError::Io(format!("Failed to open staged file: {}", e)))?;
+        self.put_object(&self.scoped(object_key), counted(body, Arc::new(progress))).await
+    }
+
+    async fn put_object(&self, object_key: &str, body: ByteStream) -> Result<String, StorjError> {
+        let result = self
+            .s3_client
+            .put_object()
+            .bucket(&self.bucket_name)
+            .key(object_key)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| {
+                let error_msg = format!("{}", e);
+                log::error!("StorjClient::put_object failed: {}", error_msg);
+                if is_unreachable(&e) {
+                    return StorjError::Unreachable(error_msg);
+                }
+                // Essaie d'extraire plus de détails de l'erreur
+                let code = e.code();
+                let message = e.message();
+                log::error!("Service error details: code={:?}, message={:?}", code, message);
+                let detailed_msg = if let (Some(code), Some(msg)) = (code, message) {
+                    format!("Failed to upload file: {} (code: {}, message: {})", error_msg, code, msg)
+                } else {
+                    format!("Failed to upload file: {}", error_msg)
+                };
+                StorjError::S3(detailed_msg)
+            })?;
+
+        let etag = result
+            .e_tag()
+            .ok_or_else(|| StorjError::S3("No ETag returned".to_string()))?
+            .to_string();
+
+        log::info!("StorjClient::put_object success: key={}, etag={}", object_key, etag);
+        Ok(etag)
+    }
+
+    /// Copie un objet sous une autre clé, côté serveur (rien ne transite par l'appareil).
+    pub async fn copy_file(&self, from: &str, to: &str) -> Result<(), StorjError> {
+        self.copy_object(&self.scoped(from), &self.scoped(to)).await?;
+        log::info!("StorjClient::copy_file: {} -> {}", from, to);
+        Ok(())
+    }
+
+    /// Copie côté serveur entre deux clés complètes du bucket.
+    async fn copy_object(&self, from: &str, to: &str) -> Result<(), StorjError> {
+        self.s3_client
+            .copy_object()
+            .bucket(&self.bucket_name)
+            .copy_source(format!("{}/{}", self.bucket_name, from))
+            .key(to)
+            .send()
+            .await
+            .map_err(|e| {
+                if is_unreachable(&e) {
+                    StorjError::Unreachable(e.to_string())
+                } else if e.code() == Some("NoSuchKey") || e.raw_response().is_some_and(|r| r.status().as_u16() == 404) {
+                    StorjError::NotFound
+                } else {
+                    StorjError::S3(format!("Failed to copy object: {}", e))
+                }
+            })?;
+        Ok(())
+    }
+
+    /// Download un fichier chiffré depuis Storj.
+    ///
+    /// # Arguments
+    /// * `object_key` - Clé de l'objet dans Storj
+    ///
+    /// # Returns
+    /// Les données chiffrées au format Aether
+    pub async fn download_file(&self, object_key: &str) -> Result<Vec<u8>, StorjError> {
+        Ok(self.get_object(&self.scoped(object_key), None, None).await?.data)
+    }
+
+    /// Télécharge uniquement les `len` premiers octets d'un objet, avec sa taille totale.
+    ///
+    /// # Returns
+    /// `(octets lus, taille totale de l'objet)`
+    pub async fn download_prefix(&self, object_key: &str, len: usize) -> Result<(Vec<u8>, u64), StorjError> {
+        let read = self.get_object(&self.scoped(object_key), Some((0, len as u64)), None).await?;
+        Ok((read.data, read.total))
+    }
+
+    /// Télécharge au plus `len` octets d'un objet à partir de l'octet `start`.
+    pub async fn download_range(&self, object_key: &str, start: u64, len: u64) -> Result<RangeRead, StorjError> {
+        self.get_object(&self.scoped(object_key), Some((start, len)), None).await
+    }
+
+    /// Comme [`StorjClient::download_range`], sauf si l'objet porte encore l'ETag `etag` :
+    /// le service répond alors « 304 Not Modified » sans contenu, et `None` est retourné.
+    pub async fn download_range_if_changed(
+        &self,
+        object_key: &str,
+        start: u64,
+        len: u64,
+        etag: &str,
+    ) -> Result<Option<RangeRead>, StorjError> {
+        match self.get_object(&self.scoped(object_key), Some((start, len)), Some(etag)).await {
+            Err(StorjError::NotModified) => Ok(None),
+            other => other.map(Some),
+        }
+    }
+
+    /// Lit un objet par sa clé complète, en entier ou la plage `(début, longueur)` ; avec
+    /// `if_none_match`, un objet qui porte encore cet ETag n'est pas relu.
+    async fn get_object(
+        &self,
+        key: &str,
+        range: Option<(u64, u64)>,
+        if_none_match: Option<&str>,
+    ) -> Result<RangeRead, StorjError> {
+        let result = self
+            .s3_client
+            .get_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .set_range(range.map(|(start, len)| format!("bytes={}-{}", start, (start + len).saturating_sub(1))))
+            .set_if_none_match(if_none_match.map(str::to_string))
+            .send()
+            .await
+            .map_err(|e| {
+                let error_msg = e.to_string();
+                if e.raw_response().is_some_and(|response| response.status().as_u16() == 304) {
+                    StorjError::NotModified
+                } else if is_unreachable(&e) {
+                    StorjError::Unreachable(error_msg)
+                } else if e.as_service_error().is_some_and(|se| se.is_no_such_key())
+                    || error_msg.contains("NoSuchKey")
+                    || error_msg.contains("404")
+                {
+                    StorjError::NotFound
+                } else {
+                    StorjError::S3(format!("Failed to download file: {}", e))
+                }
+            })?;
+
+        // Content-Range: bytes 0-117/12345 ; à défaut, la réponse contient l'objet entier
+        let total = result
+            .content_range()
+            .and_then(|range| range.rsplit('/').next())
+            .and_then(|total| total.parse::<u64>().ok());
+        let etag = result.e_tag().map(str::to_string);
+        let data = result
+            .body
+            .collect()
+            .await
+            .map_err(|e| StorjError::Io(format!("Failed to read response body: {}", e)))?
+            .into_bytes()
+            .to_vec();
+        let total = total.unwrap_or(data.len() as u64);
+
+        Ok(RangeRead { data, total, etag })
+    }
+
+    /// Supprime un fichier depuis Storj.
+    ///
+    /// # Arguments
+    /// * `object_key` - Clé de l'objet à supprimer
+    pub async fn delete_file(&self, object_key: &str) -> Result<(), StorjError> {
+        self.delete_object(&self.scoped(object_key)).await
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<(), StorjError> {
+        self.s3_client
+            .delete_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| {
+                if is_unreachable(&e) {
+                    StorjError::Unreachable(e.to_string())
+                } else {
+                    StorjError::S3(format!("Failed to delete file: {}", e))
+                }
+            })?;
+
+        Ok(())
+    }
+
+    /// Liste tous les objets du coffre dans le bucket Storj (sous son préfixe).
+    ///
+    /// # Returns
+    /// Liste des clés d'objets (fichiers uniquement, pas les préfixes/dossiers)
+    pub async fn list_files(&self) -> Result<Vec<String>, StorjError> {
+        let result = self
+            .s3_client
+            .list_objects_v2()
+            .bucket(&self.bucket_name)
+            .set_prefix(Some(self.prefix.clone()).filter(|p| !p.is_empty()))
+            .send()
+            .await
+            .map_err(|e| {
+                if is_unreachable(&e) {
+                    StorjError::Unreachable(e.to_string())
+                } else {
+                    StorjError::S3(format!("Failed to list files: {}", e))
+                }
+            })?;
+
+        // Filtre uniquement les objets réels (pas les préfixes/dossiers)
+        // Les objets réels ont une taille > 0 ou sont des fichiers valides
+        // On ignore les objets qui se terminent par "/" (qui sont des préfixes/dossiers)
+        let keys: Vec<String> = result
+            .contents()
+            .iter()
+            .filter_map(|obj| {
+                obj.key().and_then(|k| k.strip_prefix(&self.prefix)).and_then(|k| {
+                    let key_str = k.to_string();
+                    // Ignore les clés qui se terminent par "/" (préfixes/dossiers)
+                    // et ne garde que les fichiers réels
+                    if key_str.ends_with('/') || key_str.starts_with(RESERVED_PREFIX) {
+                        None
+                    } else {
+                        Some(key_str)
+                    }
+                })
+            })
+            .collect();
+
+        Ok(keys)
+    }
+
+    /// Liste les objets du bucket avec leur taille et leur date de dernière modification.
+    ///
+    /// Contrairement à [`StorjClient::list_files`], suit la pagination (plus de 1000 objets).
+    pub async fn list_objects(&self) -> Result<Vec<RemoteObject>, StorjError> {
+        let scope = Some(self.prefix.as_str()).filter(|p| !p.is_empty());
+        let mut objects = self.unscope(self.list_raw(scope).await?);
+        objects.retain(|obj| !obj.key.starts_with(RESERVED_PREFIX));
+        Ok(objects)
+    }
+
+    /// Liste les objets dont la clé commence par `prefix` (objets internes compris).
+    pub async fn list_prefix(&self, prefix: &str) -> Result<Vec<RemoteObject>, StorjError> {
+        let mut objects = self.unscope(self.list_raw(Some(&self.scoped(prefix))).await?);
+        objects.retain(|obj| obj.key.starts_with(prefix));
+        Ok(objects)
+    }
+
+    /// Liste les objets sous une clé complète, sans tenir compte du préfixe du client.
+    async fn list_raw(&self, prefix: Option<&str>) -> Result<Vec<RemoteObject>, StorjError> {
+        let mut objects = Vec::new();
+        let mut continuation: Option<String> = None;
+        loop {
+            let result = self
+                .s3_client
+                .list_objects_v2()
+                .bucket(&self.bucket_name)
+                .set_prefix(prefix.map(str::to_string))
+                .set_continuation_token(continuation.take())
+                .send()
+                .await
+                .map_err(|e| {
+                    if is_unreachable(&e) {
+                        StorjError::Unreachable(e.to_string())
+                    } else {
+                        StorjError::S3(format!("Failed to list objects: {}", e))
+                    }
+                })?;
+
+            objects.extend(result.contents().iter().filter_map(|obj| {
+                let key = obj.key().filter(|k| !k.ends_with('/'))?;
+                Some(RemoteObject {
+                    key: key.to_string(),
+                    size: obj.size().unwrap_or(0).max(0) as u64,
+                    last_modified: obj.last_modified().map(|t| t.secs()),
+                })
+            }));
+
+            match result.next_continuation_token() {
+                Some(token) if result.is_truncated().unwrap_or(false) => continuation = Some(token.to_string()),
+                _ => break,
+            }
+        }
+        Ok(objects)
+    }
+
+    /// Annule les envois multipart restés inachevés sous le préfixe du client (crash pendant un envoi).
+    ///
+    /// # Returns
+    /// Le nombre d'envois annulés
+    pub async fn abort_incomplete_uploads(&self) -> Result<usize, StorjError> {
+        let result = self
+            .s3_client
+            .list_multipart_uploads()
+            .bucket(&self.bucket_name)
+            .set_prefix(Some(self.prefix.clone()).filter(|p| !p.is_empty()))
+            .send()
+            .await
+            .map_err(|e| {
+                if is_unreachable(&e) {
+                    StorjError::Unreachable(e.to_string())
+                } else {
+                    StorjError::S3(format!("Failed to list multipart uploads: {}", e))
+                }
+            })?;
+
+        let mut aborted = 0;
+        for upload in result.uploads() {
+            let (Some(key), Some(upload_id)) = (upload.key(), upload.upload_id()) else {
+                continue;
+            };
+            if !key.starts_with(&self.prefix) {
+                continue;
+            }
+            self.s3_client
+                .abort_multipart_upload()
+                .bucket(&self.bucket_name)
+                .key(key)
+                .upload_id(upload_id)
+                .send()
+                .await
+                .map_err(|e| StorjError::S3(format!("Failed to abort multipart upload: {}", e)))?;
+            log::info!("Aborted incomplete multipart upload: key={}, upload_id={}", key, upload_id);
+            aborted += 1;
+        }
+        Ok(aborted)
+    }
+
+    /// Vérifie que le bucket répond avec ces identifiants (liste d'au plus un objet).
+    pub async fn ping(&self) -> Result<(), StorjError> {
+        self.s3_client
+            .list_objects_v2()
+            .bucket(&self.bucket_name)
+            .max_keys(1)
+            .send()
+            .await
+            .map_err(|e| {
+                if is_unreachable(&e) {
+                    StorjError::Unreachable(format!("{}", e))
+                } else {
+                    StorjError::S3(format!("Failed to reach bucket: {}", e))
+                }
+            })?;
+        Ok(())
+    }
+
+    /// Heure du service (timestamp Unix, secondes), lue dans l'en-tête `Date` d'une requête
+    /// non signée : une horloge locale décalée fait échouer les signatures S3.
+    pub async fn server_time(&self) -> Result<i64, StorjError> {
+        use aws_sdk_s3::primitives::{DateTime, DateTimeFormat};
+
+        let response = reqwest::Client::new()
+            .head(&self.endpoint)
+            .send()
+            .await
+            .map_err(|e| StorjError::Unreachable(e.to_string()))?;
+        let date = response
+            .headers()
+            .get(reqwest::header::DATE)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| StorjError::S3("Missing Date header".to_string()))?;
+        DateTime::from_str(date, DateTimeFormat::HttpDate)
+            .map(|time| time.secs())
+            .map_err(|e| StorjError::S3(format!("Invalid Date header: {}", e)))
+    }
+
+    /// Vérifie si un objet existe dans Storj.
+    ///
+    /// # Arguments
+    /// * `object_key` - Clé de l'objet à vérifier
+    ///
+    /// # Returns
+    /// `true` si l'objet existe, `false` sinon
+    pub async fn file_exists(&self, object_key: &str) -> Result<bool, StorjError> {
+        match self
+            .s3_client
+            .head_object()
+            .bucket(&self.bucket_name)
+            .key(self.scoped(object_key))
+            .send()
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(e) if e.as_service_error().is_some_and(|se| se.is_not_found()) => Ok(false),
+            Err(e) if is_unreachable(&e) => Err(StorjError::Unreachable(e.to_string())),
+            Err(e) => {
+                let error_msg = e.to_string();
+                if error_msg.contains("NotFound") || error_msg.contains("404") {
+                    Ok(false)
+                } else {
+                    Err(StorjError::S3(format!("Failed to check file existence: {}", e)))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Note: Les tests nécessitent des credentials Storj valides.
+    // Pour l'instant, on teste juste que le client peut être créé avec une config valide.
+    #[test]
+    fn test_storj_config() {
+        let config = StorjConfig::new(
+            "test-access-key".to_string(),
+            "test-secret-key".to_string(),
+            "https://gateway.storjshare.io".to_string(),
+            "test-bucket".to_string(),
+        );
+
+        assert_eq!(config.access_key_id, "test-access-key");
+        assert_eq!(config.bucket_name, "test-bucket");
+        assert_eq!(config.region, "us-east-1");
+    }
+
+    #[tokio::test]
+    async fn prefixed_client_scopes_keys_and_listings() {
+        let client = StorjClient::new(StorjConfig::new(
+            "test-access-key".to_string(),
+            "test-secret-key".to_string(),
+            "https://gateway.storjshare.io".to_string(),
+            "test-bucket".to_string(),
+        ))
+        .await
+        .unwrap()
+        .with_prefix(vault_prefix("0123456789abcdef"));
+
+        assert_eq!(client.prefix(), "vaults/0123456789abcdef/");
+        assert_eq!(client.scoped("file-id"), "vaults/0123456789abcdef/file-id");
+        let listed = vec![
+            RemoteObject { key: "vaults/0123456789abcdef/mine".to_string(), size: 1, last_modified: None },
+            RemoteObject { key: "vaults/fedcba9876543210/theirs".to_string(), size: 2, last_modified: None },
+            RemoteObject { key: "legacy".to_string(), size: 3, last_modified: None },
+        ];
+        let keys: Vec<String> = client.unscope(listed).into_iter().map(|obj| obj.key).collect();
+        assert_eq!(keys, vec!["mine".to_string()]);
+    }
+}
+
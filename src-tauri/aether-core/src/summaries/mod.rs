@@ -0,0 +1,250 @@
+//! Résumés lisibles des opérations de fond : ce qu'a fait la dernière synchronisation, ce
+//! qu'a trouvé la dernière vérification d'intégrité.
+//!
+//! Chaque résumé est un bloc de texte structuré (titre puis phrases complètes), en français,
+//! prêt à être annoncé par un lecteur d'écran ou recopié dans un journal : le frontend n'a
+//! pas à le reconstituer depuis les événements bruts.
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::integrity::IntegrityReport;
+
+/// Clé de métadonnées du dernier passage de synchronisation.
+const SYNC_RUN_META_KEY: &str = "last_sync_run";
+
+/// Ce qu'a fait un passage de synchronisation (conservé dans l'index jusqu'au suivant).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncRun {
+    /// Timestamp Unix (secondes) de la fin du passage.
+    pub finished_at: i64,
+    /// Modifications de cet appareil publiées dans le journal.
+    pub pushed: usize,
+    /// Modifications d'autres appareils appliquées depuis le journal.
+    pub applied: usize,
+    pub conflicts: usize,
+    /// Le journal ne suffisait pas : l'index a été comparé à la liste distante complète.
+    pub full_reconcile: bool,
+    /// Objets listés sur le stockage distant (`None` si la liste a échoué).
+    pub remote_count: Option<usize>,
+    /// Entrées retirées de l'index : leur objet distant n'existe plus.
+    pub removed_orphans: usize,
+    /// Cause de l'échec du passage (technique, en anglais).
+    pub error: Option<String>,
+}
+
+pub fn record_sync_run(index: &mut SqlCipherIndex, run: &SyncRun) -> rusqlite::Result<()> {
+    index.put_meta_json(SYNC_RUN_META_KEY, run)
+}
+
+/// Dernier passage enregistré, s'il existe.
+pub fn last_sync_run(index: &SqlCipherIndex) -> rusqlite::Result<Option<SyncRun>> {
+    index.get_meta_json(SYNC_RUN_META_KEY)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryStatus {
+    Success,
+    /// Terminé, avec des points à vérifier.
+    Warning,
+    Failure,
+}
+
+/// Résumé d'une opération.
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationSummary {
+    /// `sync` ou `scrub`.
+    pub operation: &'static str,
+    pub finished_at: i64,
+    pub status: SummaryStatus,
+    /// Phrase d'ensemble, à annoncer en premier.
+    pub title: String,
+    /// Détails, une phrase complète par ligne.
+    pub lines: Vec<String>,
+}
+
+impl OperationSummary {
+    /// Bloc de texte brut : le titre puis une ligne par détail.
+    pub fn to_text(&self) -> String {
+        std::iter::once(self.title.as_str())
+            .chain(self.lines.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+pub fn sync_summary(run: &SyncRun) -> OperationSummary {
+    let mut summary = OperationSummary {
+        operation: "sync",
+        finished_at: run.finished_at,
+        status: SummaryStatus::Success,
+        title: String::new(),
+        lines: Vec::new(),
+    };
+    if let Some(error) = &run.error {
+        summary.status = SummaryStatus::Failure;
+        summary.title = "La synchronisation a échoué.".to_string();
+        summary.lines.push(format!("Détail technique : {}.", error.trim_end_matches('.')));
+        return summary;
+    }
+
+    summary.title = if run.pushed == 0 && run.applied == 0 && run.removed_orphans == 0 {
+        "Synchronisation terminée : tout était à jour.".to_string()
+    } else {
+        format!(
+            "Synchronisation terminée : {} reçue{}, {} envoyée{}.",
+            count(run.applied, "modification", "modifications"),
+            s(run.applied),
+            run.pushed,
+            s(run.pushed)
+        )
+    };
+    if run.applied > 0 {
+        summary.lines.push(format!(
+            "{} d'autres appareils appliquée{} ici.",
+            count(run.applied, "modification", "modifications"),
+            s(run.applied)
+        ));
+    }
+    if run.pushed > 0 {
+        summary.lines.push(format!(
+            "{} de cet appareil publiée{} pour les autres.",
+            count(run.pushed, "modification", "modifications"),
+            s(run.pushed)
+        ));
+    }
+    if run.conflicts > 0 {
+        summary.status = SummaryStatus::Warning;
+        summary.lines.push(format!(
+            "{} avec un autre appareil, à vérifier.",
+            count(run.conflicts, "conflit", "conflits")
+        ));
+    }
+    if run.full_reconcile {
+        let remote = run.remote_count.map_or_else(String::new, |n| format!(" ({})", count(n, "objet", "objets")));
+        summary.lines.push(format!("Index comparé à la liste complète du stockage distant{}.", remote));
+    }
+    if run.removed_orphans > 0 {
+        summary.lines.push(format!(
+            "{} de l'index local : absent{} du stockage distant.",
+            count(run.removed_orphans, "fichier retiré", "fichiers retirés"),
+            s(run.removed_orphans)
+        ));
+    }
+    summary
+}
+
+pub fn scrub_summary(report: &IntegrityReport) -> OperationSummary {
+    let problems = usize::from(!report.merkle_ok) + report.tampered_rows.len() + report.remote_drift.len();
+    let status = if !report.merkle_ok || !report.tampered_rows.is_empty() {
+        SummaryStatus::Failure
+    } else if !report.remote_drift.is_empty() {
+        SummaryStatus::Warning
+    } else {
+        SummaryStatus::Success
+    };
+    let title = if problems == 0 {
+        "Vérification d'intégrité : aucun problème trouvé.".to_string()
+    } else {
+        format!("Vérification d'intégrité : {} trouvé{}.", count(problems, "problème", "problèmes"), s(problems))
+    };
+
+    let mut lines = Vec::new();
+    if !report.merkle_ok {
+        lines.push("L'index local a été modifié en dehors de l'application (empreinte Merkle différente).".to_string());
+    }
+    if !report.tampered_rows.is_empty() {
+        let tampered = report.tampered_rows.len();
+        lines.push(if tampered > 1 {
+            format!("{} entrées de l'index ne correspondent plus à leur signature.", tampered)
+        } else {
+            "1 entrée de l'index ne correspond plus à sa signature.".to_string()
+        });
+    }
+    if report.remote_sampled + report.remote_unchanged == 0 {
+        lines.push("Le stockage distant n'a pas été vérifié : il était injoignable ou vide.".to_string());
+    } else {
+        lines.push(format!(
+            "{} relu{} sur le stockage distant, {} inchangé{} depuis la vérification précédente.",
+            count(report.remote_sampled, "objet", "objets"),
+            s(report.remote_sampled),
+            report.remote_unchanged,
+            s(report.remote_unchanged)
+        ));
+    }
+    if !report.remote_drift.is_empty() {
+        lines.push(format!(
+            "{} : contenu altéré mis en quarantaine, ou objet manquant à réconcilier.",
+            count(report.remote_drift.len(), "objet distant en écart", "objets distants en écart")
+        ));
+    }
+    OperationSummary {
+        operation: "scrub",
+        finished_at: report.checked_at,
+        status,
+        title,
+        lines,
+    }
+}
+
+fn count(n: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", n, if n > 1 { plural } else { singular })
+}
+
+/// Marque du pluriel d'un participe accordé avec `n`.
+fn s(n: usize) -> &'static str {
+    if n > 1 {
+        "s"
+    } else {
+        ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrity::RemoteDrift;
+
+    #[test]
+    fn summaries_describe_the_operation_in_full_sentences() {
+        let idle = sync_summary(&SyncRun { finished_at: 10, remote_count: Some(4), ..SyncRun::default() });
+        assert_eq!(idle.status, SummaryStatus::Success);
+        assert_eq!(idle.to_text(), "Synchronisation terminée : tout était à jour.");
+
+        let busy = sync_summary(&SyncRun {
+            finished_at: 10,
+            pushed: 1,
+            applied: 3,
+            conflicts: 1,
+            full_reconcile: true,
+            remote_count: Some(12),
+            removed_orphans: 2,
+            error: None,
+        });
+        assert_eq!(busy.status, SummaryStatus::Warning);
+        assert_eq!(busy.title, "Synchronisation terminée : 3 modifications reçues, 1 envoyée.");
+        assert_eq!(busy.lines.len(), 5);
+        assert!(busy.lines[3].contains("(12 objets)"));
+
+        let failed = sync_summary(&SyncRun { error: Some("Storj unreachable".to_string()), ..SyncRun::default() });
+        assert_eq!(failed.status, SummaryStatus::Failure);
+        assert_eq!(failed.lines, ["Détail technique : Storj unreachable."]);
+
+        let clean = IntegrityReport { checked_at: 20, merkle_ok: true, remote_sampled: 5, remote_unchanged: 1, ..IntegrityReport::default() };
+        assert_eq!(scrub_summary(&clean).status, SummaryStatus::Success);
+        assert_eq!(scrub_summary(&clean).finished_at, 20);
+
+        let drifted = IntegrityReport {
+            remote_drift: vec![RemoteDrift { file_id: "ab".to_string(), reason: "missing".to_string() }],
+            ..clean
+        };
+        let summary = scrub_summary(&drifted);
+        assert_eq!(summary.status, SummaryStatus::Warning);
+        assert_eq!(summary.title, "Vérification d'intégrité : 1 problème trouvé.");
+        assert_eq!(
+            scrub_summary(&IntegrityReport { merkle_ok: false, ..IntegrityReport::default() }).status,
+            SummaryStatus::Failure
+        );
+    }
+}
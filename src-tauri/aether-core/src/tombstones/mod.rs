@@ -0,0 +1,120 @@
+//! Corbeille visible depuis le bucket.
+//!
+//! La corbeille vit dans l'index local : le journal de synchronisation ne transmet aux autres
+//! appareils que le retrait de l'entrée. Pour eux, l'objet distant devient un fichier vivant
+//! dont ils n'ont pas les métadonnées. Chaque objet mis à la corbeille reçoit donc un témoin
+//! vide sous [`TOMBSTONES_PREFIX`] (même clé que l'objet) : une liste distante sait qu'il est
+//! à la corbeille sans rien télécharger.
+//!
+//! Un témoin est retiré par l'appareil qui l'a posé quand le fichier quitte sa corbeille, ou
+//! par n'importe quel appareil dès que l'objet a disparu du bucket (purge). Il ne sert qu'à
+//! l'affichage : la corbeille de l'appareil auteur fait foi.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+
+use crate::index::sqlcipher::SqlCipherIndex;
+use crate::index::FileId;
+use crate::remote_keys;
+use crate::storj::{StorjClient, StorjError};
+use crate::vault::{Vault, VaultError};
+
+/// Préfixe des témoins de corbeille dans le bucket (sous le préfixe réservé).
+pub const TOMBSTONES_PREFIX: &str = ".aether/trash/";
+
+const STATE_META_KEY: &str = "tombstone_state";
+
+/// Témoins posés par cet appareil, conservés dans l'index.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TombstoneState {
+    published: BTreeSet<FileId>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TombstoneReport {
+    /// Témoins posés pour des objets mis à la corbeille ici.
+    pub written: usize,
+    /// Témoins retirés (fichier restauré ou objet purgé).
+    pub removed: usize,
+}
+
+fn tombstone_key(object_key: &str) -> String {
+    format!("{}{}", TOMBSTONES_PREFIX, object_key)
+}
+
+fn load_state(index: &SqlCipherIndex) -> rusqlite::Result<TombstoneState> {
+    Ok(index.get_meta_json(STATE_META_KEY)?.unwrap_or_default())
+}
+
+fn save_state(index: &mut SqlCipherIndex, state: &TombstoneState) -> rusqlite::Result<()> {
+    index.put_meta_json(STATE_META_KEY, state)
+}
+
+/// Objets distants que seule la corbeille désigne : un objet encore lu par une entrée
+/// vivante (déduplication) ou pas encore envoyé n'a pas de témoin.
+fn trashed_objects(index: &SqlCipherIndex) -> Result<HashSet<FileId>, VaultError> {
+    let links = index.blob_links()?;
+    let object_of = |id: &FileId| links.get(id).cloned().unwrap_or_else(|| id.clone());
+    let live: HashSet<FileId> = index.list_all()?.iter().map(|(id, _)| object_of(id)).collect();
+    let pending = index.pending_upload_ids()?;
+    Ok(index
+        .list_trash()?
+        .iter()
+        .filter(|(_, meta, _)| !meta.logical_path.ends_with('/') && meta.encrypted_size > 0)
+        .map(|(id, _, _)| object_of(id))
+        .filter(|object_key| !live.contains(object_key) && !pending.contains(object_key))
+        .collect())
+}
+
+/// Clés des objets qu'un appareil a mis à la corbeille, d'après les témoins du bucket.
+pub async fn trashed_keys(remote: &StorjClient) -> Result<HashSet<String>, StorjError> {
+    Ok(remote
+        .list_prefix(TOMBSTONES_PREFIX)
+        .await?
+        .into_iter()
+        .filter_map(|object| object.key.strip_prefix(TOMBSTONES_PREFIX).map(str::to_string))
+        .collect())
+}
+
+/// Aligne les témoins du bucket sur la corbeille de cet appareil.
+pub async fn publish(vault: &Vault) -> Result<TombstoneReport, VaultError> {
+    let (trashed, mut state) = {
+        let index = vault.open_index()?;
+        (trashed_objects(&index)?, load_state(&index)?)
+    };
+    let remote = trashed_keys(vault.remote()).await?;
+    let objects: HashSet<String> = vault
+        .remote()
+        .list_objects()
+        .await?
+        .into_iter()
+        .map(|object| remote_keys::normalize(&object.key))
+        .collect();
+
+    let mut report = TombstoneReport::default();
+    for object_key in trashed.iter().filter(|key| objects.contains(*key)) {
+        if !remote.contains(object_key) {
+            vault.remote().upload_file(&tombstone_key(object_key), &[]).await?;
+            report.written += 1;
+        }
+        state.published.insert(object_key.clone());
+    }
+    for object_key in &remote {
+        let ours_and_left = state.published.contains(object_key) && !trashed.contains(object_key);
+        if ours_and_left || !objects.contains(object_key) {
+            match vault.remote().delete_file(&tombstone_key(object_key)).await {
+                Ok(()) | Err(StorjError::NotFound) => report.removed += 1,
+                Err(e) => return Err(e.into()),
+            }
+            state.published.remove(object_key);
+        }
+    }
+    // Témoin retiré par un autre appareil (objet purgé) : plus rien à suivre
+    state.published.retain(|object_key| trashed.contains(object_key));
+    save_state(&mut vault.open_index()?, &state)?;
+
+    if report != TombstoneReport::default() {
+        log::info!("Trash tombstones published: written={}, removed={}", report.written, report.removed);
+    }
+    Ok(report)
+}
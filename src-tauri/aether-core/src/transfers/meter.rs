@@ -0,0 +1,111 @@
+//! Mesure de l'avancement d'un transfert : débit et temps restant, pour l'affichage.
+//!
+//! Les rappels de progression arrivent à chaque morceau lu ou envoyé ; le [`TransferMeter`]
+//! n'en retient qu'une mesure par [`REPORT_INTERVAL`] (la fin est toujours rapportée) et
+//! calcule le débit sur les dernières secondes, pour qu'un transfert qui ralentit voie son
+//! estimation suivre.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Intervalle minimal entre deux mesures rapportées.
+pub const REPORT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Fenêtre sur laquelle le débit est mesuré.
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Mesure d'un transfert à un instant donné.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferRate {
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub bytes_per_second: u64,
+    /// Secondes restantes au débit actuel ; inconnu tant que rien n'a avancé.
+    pub eta_seconds: Option<u64>,
+}
+
+/// Avancement d'un transfert, alimenté par ses rappels de progression.
+#[derive(Debug, Default)]
+pub struct TransferMeter {
+    /// `(instant, octets transférés)`, du plus ancien au plus récent.
+    samples: VecDeque<(Instant, u64)>,
+    last_report: Option<Instant>,
+}
+
+impl TransferMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enregistre `bytes_done` octets sur `bytes_total` ; retourne une mesure si elle est à
+    /// rapporter.
+    pub fn observe(&mut self, bytes_done: u64, bytes_total: u64) -> Option<TransferRate> {
+        self.observe_at(Instant::now(), bytes_done, bytes_total)
+    }
+
+    fn observe_at(&mut self, now: Instant, bytes_done: u64, bytes_total: u64) -> Option<TransferRate> {
+        // Un transfert repris depuis le début (nouvelle tentative) repart d'une mesure vierge
+        if self.samples.back().is_some_and(|(_, done)| *done > bytes_done) {
+            self.samples.clear();
+        }
+        self.samples.push_back((now, bytes_done));
+        // Garde un échantillon au moins aussi ancien que la fenêtre
+        while self.samples.len() > 2 && now.duration_since(self.samples[1].0) >= RATE_WINDOW {
+            self.samples.pop_front();
+        }
+
+        let finished = bytes_done >= bytes_total;
+        if !finished && self.last_report.is_some_and(|last| now.duration_since(last) < REPORT_INTERVAL) {
+            return None;
+        }
+        self.last_report = Some(now);
+
+        let (since, from) = self.samples[0];
+        let elapsed = now.duration_since(since).as_secs_f64();
+        let bytes_per_second = if elapsed > 0.0 {
+            ((bytes_done - from) as f64 / elapsed) as u64
+        } else {
+            0
+        };
+        let eta_seconds = if finished {
+            Some(0)
+        } else {
+            (bytes_per_second > 0).then(|| (bytes_total - bytes_done).div_ceil(bytes_per_second))
+        };
+        Some(TransferRate { bytes_done, bytes_total, bytes_per_second, eta_seconds })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_are_throttled_and_rate_follows_recent_progress() {
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let mut meter = TransferMeter::new();
+
+        let first = meter.observe_at(at(0), 0, 1_000_000).unwrap();
+        assert_eq!((first.bytes_per_second, first.eta_seconds), (0, None));
+        // Trop tôt : rien à rapporter
+        assert!(meter.observe_at(at(100), 50_000, 1_000_000).is_none());
+        let rate = meter.observe_at(at(1_000), 100_000, 1_000_000).unwrap();
+        assert_eq!((rate.bytes_per_second, rate.eta_seconds), (100_000, Some(9)));
+
+        // Au-delà de la fenêtre, seul le débit récent compte
+        meter.observe_at(at(6_000), 600_000, 1_000_000).unwrap();
+        let slowed = meter.observe_at(at(12_000), 700_000, 1_000_000).unwrap();
+        assert_eq!(slowed.bytes_per_second, 100_000 / 6);
+
+        // La fin est toujours rapportée
+        let done = meter.observe_at(at(12_010), 1_000_000, 1_000_000).unwrap();
+        assert_eq!(done.eta_seconds, Some(0));
+
+        // Une nouvelle tentative repart de zéro
+        let retried = meter.observe_at(at(13_000), 10_000, 1_000_000).unwrap();
+        assert_eq!((retried.bytes_per_second, retried.eta_seconds), (0, None));
+    }
+}
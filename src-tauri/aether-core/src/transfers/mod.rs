@@ -0,0 +1,246 @@
+//! Ordonnancement des transferts par classe de priorité.
+//!
+//! Un transfert est soit interactif (aperçu, petit fichier ouvert ou envoyé par
+//! l'utilisateur), soit de masse (synchronisation initiale, règles, file hors ligne). La
+//! [`TransferQueue`] borne le nombre de transferts simultanés et sert toujours les
+//! interactifs en premier : un transfert de masse attend tant qu'un interactif patiente, et
+//! une place reste réservée aux interactifs. Un aperçu n'attend donc jamais derrière un
+//! arriéré de plusieurs gigaoctets.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+pub mod meter;
+
+/// Transferts simultanés par défaut.
+pub const DEFAULT_SLOTS: usize = 4;
+/// En dessous de cette taille, un transfert non étiqueté est interactif.
+pub const SMALL_TRANSFER_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Classe d'un transfert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferPriority {
+    Interactive,
+    Bulk,
+}
+
+impl TransferPriority {
+    /// Classe d'un transfert que l'appelant n'a pas étiqueté, d'après sa taille.
+    pub fn for_size(bytes: u64) -> Self {
+        if bytes <= SMALL_TRANSFER_BYTES {
+            TransferPriority::Interactive
+        } else {
+            TransferPriority::Bulk
+        }
+    }
+}
+
+/// Occupation de la file, pour l'affichage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct QueueStatus {
+    pub slots: usize,
+    pub running_interactive: usize,
+    pub running_bulk: usize,
+    pub waiting_interactive: usize,
+    pub waiting_bulk: usize,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    running_interactive: usize,
+    running_bulk: usize,
+    waiting_interactive: usize,
+    waiting_bulk: usize,
+}
+
+/// File partagée par tous les transferts d'une application.
+#[derive(Debug)]
+pub struct TransferQueue {
+    slots: usize,
+    state: Mutex<QueueState>,
+    released: Notify,
+}
+
+impl Default for TransferQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_SLOTS)
+    }
+}
+
+impl TransferQueue {
+    /// `slots` : transferts simultanés (au moins un).
+    pub fn new(slots: usize) -> Self {
+        Self {
+            slots: slots.max(1),
+            state: Mutex::new(QueueState::default()),
+            released: Notify::new(),
+        }
+    }
+
+    /// Attend une place pour un transfert de classe `priority` ; elle est rendue quand le
+    /// [`TransferPermit`] est abandonné.
+    pub async fn acquire(self: &Arc<Self>, priority: TransferPriority) -> TransferPermit {
+        let mut waiting: Option<Waiting> = None;
+        loop {
+            let released = self.released.notified();
+            tokio::pin!(released);
+            // Inscrit avant la vérification : une place rendue entre-temps n'est pas manquée
+            released.as_mut().enable();
+            {
+                let mut state = self.lock();
+                if self.can_start(&state, priority) {
+                    match priority {
+                        TransferPriority::Interactive => state.running_interactive += 1,
+                        TransferPriority::Bulk => state.running_bulk += 1,
+                    }
+                    if let Some(mut waiting) = waiting.take() {
+                        waiting.leave(&mut state);
+                    }
+                    return TransferPermit { queue: Arc::clone(self), priority };
+                }
+                if waiting.is_none() {
+                    match priority {
+                        TransferPriority::Interactive => state.waiting_interactive += 1,
+                        TransferPriority::Bulk => state.waiting_bulk += 1,
+                    }
+                    waiting = Some(Waiting { queue: Arc::clone(self), priority, left: false });
+                }
+            }
+            released.await;
+        }
+    }
+
+    pub fn status(&self) -> QueueStatus {
+        let state = self.lock();
+        QueueStatus {
+            slots: self.slots,
+            running_interactive: state.running_interactive,
+            running_bulk: state.running_bulk,
+            waiting_interactive: state.waiting_interactive,
+            waiting_bulk: state.waiting_bulk,
+        }
+    }
+
+    fn can_start(&self, state: &QueueState, priority: TransferPriority) -> bool {
+        if state.running_interactive + state.running_bulk >= self.slots {
+            return false;
+        }
+        match priority {
+            TransferPriority::Interactive => true,
+            // Une place reste libre pour les interactifs (sauf file à une seule place)
+            TransferPriority::Bulk => {
+                state.waiting_interactive == 0 && state.running_bulk < (self.slots - 1).max(1)
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        // L'état ne contient que des compteurs : il reste cohérent après une panique
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Inscription d'un transfert en attente ; retirée même si l'attente est abandonnée.
+struct Waiting {
+    queue: Arc<TransferQueue>,
+    priority: TransferPriority,
+    left: bool,
+}
+
+impl Waiting {
+    fn leave(&mut self, state: &mut QueueState) {
+        match self.priority {
+            TransferPriority::Interactive => state.waiting_interactive -= 1,
+            TransferPriority::Bulk => state.waiting_bulk -= 1,
+        }
+        self.left = true;
+    }
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        if self.left {
+            return;
+        }
+        let queue = Arc::clone(&self.queue);
+        self.leave(&mut queue.lock());
+        // Un interactif qui renonce peut débloquer des transferts de masse
+        queue.released.notify_waiters();
+    }
+}
+
+/// Place occupée dans la [`TransferQueue`].
+#[derive(Debug)]
+pub struct TransferPermit {
+    queue: Arc<TransferQueue>,
+    priority: TransferPriority,
+}
+
+impl TransferPermit {
+    pub fn priority(&self) -> TransferPriority {
+        self.priority
+    }
+}
+
+impl Drop for TransferPermit {
+    fn drop(&mut self) {
+        {
+            let mut state = self.queue.lock();
+            match self.priority {
+                TransferPriority::Interactive => state.running_interactive -= 1,
+                TransferPriority::Bulk => state.running_bulk -= 1,
+            }
+        }
+        self.queue.released.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn interactive_transfers_never_wait_behind_bulk() {
+        let queue = Arc::new(TransferQueue::new(2));
+        let bulk = queue.acquire(TransferPriority::Bulk).await;
+        // La seconde place est réservée aux interactifs
+        let waiting_bulk = tokio::spawn({
+            let queue = Arc::clone(&queue);
+            async move { queue.acquire(TransferPriority::Bulk).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(queue.status().waiting_bulk, 1);
+        let interactive = queue.acquire(TransferPriority::Interactive).await;
+        assert_eq!((queue.status().running_interactive, queue.status().running_bulk), (1, 1));
+
+        // Un interactif en attente passe devant le transfert de masse qui attendait déjà
+        let next_interactive = tokio::spawn({
+            let queue = Arc::clone(&queue);
+            async move { queue.acquire(TransferPriority::Interactive).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(bulk);
+        let next_interactive = next_interactive.await.unwrap();
+        assert!(!waiting_bulk.is_finished());
+
+        drop(interactive);
+        drop(next_interactive);
+        assert_eq!(waiting_bulk.await.unwrap().priority(), TransferPriority::Bulk);
+        assert_eq!(queue.status(), QueueStatus { slots: 2, ..QueueStatus::default() });
+    }
+
+    #[tokio::test]
+    async fn abandoned_waits_are_forgotten() {
+        let queue = Arc::new(TransferQueue::new(1));
+        let running = queue.acquire(TransferPriority::Bulk).await;
+        let abandoned = tokio::time::timeout(Duration::from_millis(20), queue.acquire(TransferPriority::Interactive)).await;
+        assert!(abandoned.is_err());
+        assert_eq!(queue.status().waiting_interactive, 0);
+        drop(running);
+        // Sans interactif inscrit, le transfert de masse repart aussitôt
+        drop(queue.acquire(TransferPriority::Bulk).await);
+    }
+}
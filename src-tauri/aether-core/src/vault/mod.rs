@@ -0,0 +1,1773 @@
+use memmap2::MmapMut;
+use rand::{rngs::OsRng, RngCore};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::activity;
+use crate::cache::policy::{self, CachePolicy};
+use crate::cache::BlobCache;
+use crate::crypto::MasterKey;
+use crate::downloads::{self, ResumableDownloads};
+use crate::integrity;
+use crate::index::{cache::IndexCache, path, sqlcipher::SqlCipherIndex, AliasEntry, FileId, FileMetadata, FileVersion, PendingKind};
+use crate::jobs::Job;
+use crate::mime;
+use crate::photos;
+use crate::policies;
+use crate::preflight::{self, PreflightReport, UploadCandidate};
+use crate::quota::{self, QuotaCheck};
+use crate::remote_keys;
+use crate::search::{self, extract, ContentIndex};
+use crate::staging::{StagedBlob, Staging};
+use crate::storage::{self, AetherError, AetherFile, EncryptionDetails, StorageError};
+use crate::storj::{StorjClient, StorjError};
+use crate::transfers::{TransferPermit, TransferPriority, TransferQueue};
+
+/// Anciennes versions conservées par chemin logique ; au-delà, les plus anciennes sont
+/// supprimées du stockage distant (sauf si un instantané les retient).
+pub const VERSION_RETENTION: usize = 5;
+
+/// Étapes d'un renommage de fichier (voir [`Vault::rename_job`]).
+pub const RENAME_STEPS: &[&str] = &["read", "upload", "trash"];
+
+/// Erreurs de la façade Vault.
+#[derive(Debug)]
+pub enum VaultError {
+    Index(rusqlite::Error),
+    Remote(StorjError),
+    Storage(StorageError),
+    Format(AetherError),
+    NotFound(FileId),
+    /// Blob attendu dans le cache local absent (envoi différé impossible à rejouer, ou pas de
+    /// copie intacte pour réparer un objet en quarantaine).
+    CacheMiss(FileId),
+    /// L'envoi ferait dépasser le budget de stockage défini par l'utilisateur.
+    QuotaExceeded { used_bytes: u64, limit_bytes: u64, requested_bytes: u64 },
+    /// Aucun fichier ni dossier à ce chemin logique.
+    PathNotFound(String),
+    /// Le chemin de destination est déjà occupé.
+    AlreadyExists(String),
+    /// Destination refusée (racine, ou dossier déplacé dans lui-même).
+    InvalidPath(&'static str),
+    /// L'objet distant existe toujours après sa suppression.
+    DeleteNotVerified(FileId),
+    /// Opération annulée par l'utilisateur (ce qui avait été fait a été défait).
+    Cancelled,
+    /// L'objet distant a échoué à la vérification : le contenu n'est plus servi tant que
+    /// l'entrée n'a pas été réparée (voir `crate::quarantine`).
+    Quarantined(FileId),
+    /// L'entrée a été modifiée par une autre opération (ou un autre appareil) pendant
+    /// celle-ci : rien n'a été écrasé (voir [`SqlCipherIndex::revision`]).
+    Conflict(FileId),
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VaultError::Index(e) => write!(f, "Index error: {}", e),
+            VaultError::Remote(e) => write!(f, "Remote storage error: {}", e),
+            VaultError::Storage(e) => write!(f, "Storage error: {}", e),
+            VaultError::Format(e) => write!(f, "Invalid Aether file: {}", e),
+            VaultError::NotFound(id) => write!(f, "File not found: {}", id),
+            VaultError::CacheMiss(id) => write!(f, "Cached blob missing for pending upload: {}", id),
+            VaultError::QuotaExceeded { used_bytes, limit_bytes, requested_bytes } => write!(
+                f,
+                "Storage quota exceeded: used={}, requested={}, limit={}",
+                used_bytes, requested_bytes, limit_bytes
+            ),
+            VaultError::PathNotFound(path) => write!(f, "Path not found: {}", path),
+            VaultError::AlreadyExists(path) => write!(f, "Already exists: {}", path),
+            VaultError::InvalidPath(reason) => write!(f, "Invalid path: {}", reason),
+            VaultError::DeleteNotVerified(id) => write!(f, "Remote object still present after deletion: {}", id),
+            VaultError::Cancelled => write!(f, "Operation cancelled"),
+            VaultError::Quarantined(id) => write!(f, "File quarantined after failed verification: {}", id),
+            VaultError::Conflict(id) => write!(f, "File modified concurrently: {}", id),
+        }
+    }
+}
+
+impl std::error::Error for VaultError {}
+
+impl From<rusqlite::Error> for VaultError {
+    fn from(e: rusqlite::Error) -> Self {
+        VaultError::Index(e)
+    }
+}
+
+impl From<StorjError> for VaultError {
+    fn from(e: StorjError) -> Self {
+        VaultError::Remote(e)
+    }
+}
+
+impl From<StorageError> for VaultError {
+    fn from(e: StorageError) -> Self {
+        VaultError::Storage(e)
+    }
+}
+
+impl From<AetherError> for VaultError {
+    fn from(e: AetherError) -> Self {
+        VaultError::Format(e)
+    }
+}
+
+/// Origine d'une mise en quarantaine au téléchargement.
+pub const QUARANTINE_SOURCE: &str = "download";
+
+/// Origine d'une mise en quarantaine à la restauration depuis la corbeille.
+pub const RESTORE_QUARANTINE_SOURCE: &str = "restore";
+
+/// Suffixe du fichier partiel écrit à côté de la destination de [`Vault::save_to`].
+const PARTIAL_SUFFIX: &str = ".aether-part";
+
+/// [`Vault::decrypt_bound`] sur un index donné (copie de l'index d'un clone, par exemple).
+pub(crate) fn decrypt_with_index(
+    index: &SqlCipherIndex,
+    master_key: &MasterKey,
+    file_id: &FileId,
+    aether_file: &AetherFile,
+    logical_path: &str,
+) -> Result<Vec<u8>, VaultError> {
+    let mut result = Err(VaultError::NotFound(file_id.clone()));
+    for aad_path in aad_paths(index, file_id, logical_path)? {
+        result = storage::decrypt_file(master_key, aether_file, &aad_path).map_err(VaultError::from);
+        if result.is_ok() {
+            break;
+        }
+    }
+    result
+}
+
+/// Chemins sous lesquels le contenu de `file_id` a pu être scellé (AAD), dans l'ordre d'essai
+/// de [`Vault::decrypt_bound`].
+fn aad_paths(index: &SqlCipherIndex, file_id: &FileId, logical_path: &str) -> Result<Vec<String>, VaultError> {
+    if let Some((_, aad_path)) = index.blob_link(file_id)? {
+        return Ok(vec![aad_path]);
+    }
+    Ok(index.bound_path(file_id)?.into_iter().chain([logical_path.to_string()]).collect())
+}
+
+/// Échec qui met en cause le contenu lui-même (en-tête invalide, authentification refusée),
+/// par opposition à une erreur d'accès.
+pub fn is_verification_failure(error: &VaultError) -> bool {
+    matches!(
+        error,
+        VaultError::Format(_)
+            | VaultError::Storage(StorageError::Crypto(_) | StorageError::InvalidFormat(_) | StorageError::InvalidHeader)
+    )
+}
+
+/// Résultat d'une passe de synchronisation index ↔ stockage distant.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    pub remote_count: usize,
+    /// Entrées locales dont l'objet distant n'existe plus (retirées de l'index).
+    pub removed_orphans: Vec<FileId>,
+}
+
+/// Résultat d'un renommage de dossier.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FolderRenameReport {
+    /// Entrées de l'index déplacées (dossiers compris).
+    pub moved_entries: usize,
+    /// Fichiers dont le contenu distant reste à re-chiffrer sous le nouveau chemin.
+    pub pending_rebinds: usize,
+}
+
+/// Occupation du cache local et effet de la dernière passe de maintenance.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CacheReport {
+    pub policy: CachePolicy,
+    pub cached_files: usize,
+    pub cached_bytes: u64,
+    pub evicted: usize,
+    pub prewarmed: usize,
+}
+
+/// Résultat de la vérification d'une copie locale (export, sauvegarde restaurée).
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadVerification {
+    pub file_id: FileId,
+    pub logical_path: String,
+    /// L'en-tête distant porte le bon UUID et son engagement correspond à la FileKey re-dérivée.
+    pub header_valid: bool,
+    /// Le contenu distant se déchiffre et s'authentifie sous son chemin.
+    pub content_authentic: bool,
+    /// L'empreinte enregistrée à l'envoi était présente dans l'index.
+    pub hash_recorded: bool,
+    /// L'empreinte enregistrée correspond au contenu distant.
+    pub index_consistent: bool,
+    pub size_matches: bool,
+    pub hash_matches: bool,
+    /// SHA-256 (hex) de la copie locale.
+    pub local_sha256: String,
+    /// Toutes les vérifications ci-dessus ont réussi.
+    pub verified: bool,
+}
+
+/// Élément de la corbeille dont la suppression définitive a échoué (il y reste).
+#[derive(Debug, Clone, Serialize)]
+pub struct PurgeFailure {
+    pub file_id: FileId,
+    pub logical_path: String,
+    /// Code stable : `still_present`, `remote_error`, `index_error` ou `internal_error`.
+    pub reason: &'static str,
+}
+
+/// Résultat du vidage de la corbeille.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PurgeReport {
+    /// Supprimés (ou suppression distante mise en file hors ligne), retirés de la corbeille.
+    pub purged: Vec<FileId>,
+    pub failed: Vec<PurgeFailure>,
+}
+
+/// Résultat du rejeu de la file hors ligne.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FlushReport {
+    pub completed: usize,
+    pub remaining: usize,
+}
+
+/// Résultat d'un envoi.
+#[derive(Debug, Clone, Serialize)]
+pub struct PutReport {
+    pub file_id: FileId,
+    /// Chemin retenu : celui demandé, sauf s'il était déjà occupé par un alias.
+    pub logical_path: String,
+    /// Contenu déjà présent dans le coffre : rien n'a été transféré, l'entrée lit l'objet existant.
+    pub deduplicated: bool,
+}
+
+/// Coffre déverrouillé : MasterKey + index local + stockage distant.
+///
+/// Point d'entrée unique pour les consommateurs du crate. Chaque opération ouvre
+/// l'index le temps nécessaire (la connexion SQLCipher n'est pas `Send`), ce qui
+/// permet de partager un `Vault` entre tâches async.
+pub struct Vault {
+    master_key: MasterKey,
+    index_path: PathBuf,
+    remote: Arc<StorjClient>,
+    cache: Option<BlobCache>,
+    staging: Option<Staging>,
+    downloads: Option<ResumableDownloads>,
+    transfers: Option<Arc<TransferQueue>>,
+    priority: Option<TransferPriority>,
+    index_cache: Option<Arc<IndexCache>>,
+}
+
+/// Blob sérialisé d'un envoi : en mémoire, ou dans la zone de transit.
+enum PutBlob {
+    Memory(Vec<u8>),
+    Staged(StagedBlob),
+}
+
+impl Vault {
+    pub fn new(master_key: MasterKey, index_path: impl AsRef<Path>, remote: Arc<StorjClient>) -> Self {
+        Self {
+            master_key,
+            index_path: index_path.as_ref().to_path_buf(),
+            remote,
+            cache: None,
+            staging: None,
+            downloads: None,
+            transfers: None,
+            priority: None,
+            index_cache: None,
+        }
+    }
+
+    /// Active le cache local : lectures hors ligne et envois différés.
+    pub fn with_cache(mut self, cache: BlobCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Active la zone de transit sur disque : les envois sont lus en flux depuis le disque.
+    pub fn with_staging(mut self, staging: Staging) -> Self {
+        self.staging = Some(staging);
+        self
+    }
+
+    /// Télécharge les gros objets par morceaux, en mémoire comme vers un chemin, en reprenant
+    /// là où un téléchargement interrompu s'est arrêté (voir [`downloads`](crate::downloads)).
+    pub fn with_downloads(mut self, downloads: ResumableDownloads) -> Self {
+        self.downloads = Some(downloads);
+        self
+    }
+
+    /// Fait passer les transferts par une file partagée (voir [`transfers`](crate::transfers)).
+    pub fn with_transfers(mut self, queue: Arc<TransferQueue>) -> Self {
+        self.transfers = Some(queue);
+        self
+    }
+
+    /// Classe des transferts de ce coffre ; sans elle, chaque transfert est classé d'après
+    /// sa nature (taille d'un envoi, lecture demandée, file hors ligne).
+    pub fn with_priority(mut self, priority: TransferPriority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Sert les lectures fréquentes de l'index depuis un cache partagé entre les coffres
+    /// ouverts sur le même index (voir [`IndexCache`]).
+    pub fn with_index_cache(mut self, cache: Arc<IndexCache>) -> Self {
+        self.index_cache = Some(cache);
+        self
+    }
+
+    /// Attend une place dans la file des transferts, s'il y en a une.
+    async fn transfer_slot(&self, default: TransferPriority) -> Option<TransferPermit> {
+        let queue = self.transfers.as_ref()?;
+        Some(queue.acquire(self.priority.unwrap_or(default)).await)
+    }
+
+    pub fn master_key(&self) -> &MasterKey {
+        &self.master_key
+    }
+
+    pub fn remote(&self) -> &StorjClient {
+        &self.remote
+    }
+
+    pub fn index_path(&self) -> &Path {
+        &self.index_path
+    }
+
+    pub fn downloads(&self) -> Option<&ResumableDownloads> {
+        self.downloads.as_ref()
+    }
+
+    pub fn cache(&self) -> Option<&BlobCache> {
+        self.cache.as_ref()
+    }
+
+    pub fn open_index(&self) -> Result<SqlCipherIndex, VaultError> {
+        Ok(SqlCipherIndex::open(&self.index_path, self.master_key.as_bytes())?)
+    }
+
+    /// Vérifie un lot d'envois avant de le commencer (voir [`preflight::check`]).
+    pub fn preflight(&self, candidates: &[UploadCandidate]) -> Result<PreflightReport, VaultError> {
+        Ok(preflight::check(&self.open_index()?, self.staging.as_ref(), candidates)?)
+    }
+
+    /// Liste les entrées de l'index (fichiers et dossiers).
+    pub fn list(&self) -> Result<Vec<(FileId, FileMetadata)>, VaultError> {
+        let index = self.open_index()?;
+        match &self.index_cache {
+            Some(cache) => Ok(cache.list_all(&index)?),
+            None => Ok(index.list_all()?),
+        }
+    }
+
+    /// Chiffre, envoie puis indexe un fichier. Retourne son FileId (UUID hex).
+    ///
+    /// Un fichier déjà présent au même chemin est remplacé : le nouvel objet est envoyé,
+    /// l'index bascule vers lui en une transaction et l'ancien devient une version.
+    ///
+    /// Un contenu déjà présent dans le coffre n'est pas renvoyé : la nouvelle entrée lit
+    /// l'objet existant (voir [`PutReport::deduplicated`]).
+    ///
+    /// Si le stockage distant est injoignable et qu'un cache est configuré, l'envoi
+    /// est mis en file d'attente : le fichier est indexé et apparaît « en attente ».
+    /// L'envoi est refusé s'il ferait dépasser le budget de stockage.
+    pub async fn put(&self, logical_path: &str, plaintext: &[u8]) -> Result<FileId, VaultError> {
+        Ok(self.put_bytes(logical_path, plaintext).await?.file_id)
+    }
+
+    async fn put_bytes(&self, logical_path: &str, plaintext: &[u8]) -> Result<PutReport, VaultError> {
+        let logical_path: &str = &canonical_file_path(logical_path)?;
+        let mut index = self.open_index()?;
+        let mime_type = mime::detect(&plaintext[..plaintext.len().min(mime::SNIFF_LEN)], logical_path);
+        let encoding = policies::effective(&index, logical_path)?.encoding(mime_type, plaintext.len() as u64);
+        let aether_file = storage::encrypt_file_encoded(&self.master_key, plaintext, logical_path, encoding)?;
+        let file_id = hex::encode(aether_file.header.uuid);
+        let encrypted_size = (AetherFile::HEADER_LEN + aether_file.ciphertext.len()) as u64;
+
+        check_quota(&index, encrypted_size)?;
+
+        // Avec une zone de transit, le ciphertext est libéré avant l'envoi (lu en flux depuis le disque)
+        let blob = match &self.staging {
+            Some(staging) => PutBlob::Staged(
+                staging
+                    .write(&file_id, &aether_file)
+                    .map_err(|e| VaultError::Storage(StorageError::Io(e.to_string())))?,
+            ),
+            None => PutBlob::Memory(aether_file.to_bytes()),
+        };
+        drop(aether_file);
+
+        let content_hash: [u8; 32] = Sha256::digest(plaintext).into();
+        let report = self
+            .upload_and_index(&mut index, &file_id, blob, logical_path, encrypted_size, &content_hash)
+            .await?;
+        record_content_type(&mut index, &report.file_id, logical_path, plaintext)?;
+        index.set_plaintext_size(&report.file_id, plaintext.len() as u64)?;
+        self.index_content(&report, || Ok(Cow::Borrowed(plaintext)));
+        Ok(report)
+    }
+
+    /// Comme [`Vault::put`], en lisant le contenu depuis un fichier local.
+    ///
+    /// Avec une zone de transit, le fichier est chiffré par morceaux dans un blob projeté
+    /// en mémoire ([`Staging::encrypt_path`]) : sa taille n'est pas limitée par la RAM.
+    pub async fn put_path(&self, logical_path: &str, source: &Path) -> Result<PutReport, VaultError> {
+        let logical_path: &str = &canonical_file_path(logical_path)?;
+        let Some(staging) = &self.staging else {
+            let plaintext = std::fs::read(source)
+                .map_err(|e| VaultError::Storage(StorageError::Io(format!("{}: {}", source.display(), e))))?;
+            return self.put_bytes(logical_path, &plaintext).await;
+        };
+
+        // Quota vérifié avant de chiffrer : inutile d'écrire des gigaoctets pour rien
+        let mut index = self.open_index()?;
+        let plaintext_len = std::fs::metadata(source)
+            .map_err(|e| VaultError::Storage(StorageError::Io(format!("{}: {}", source.display(), e))))?
+            .len();
+        check_quota(&index, plaintext_len + AetherFile::OVERHEAD)?;
+        let head = read_head(source)?;
+
+        let mime_type = mime::detect(&head[..head.len().min(mime::SNIFF_LEN)], logical_path);
+        let encoding = policies::effective(&index, logical_path)?.encoding(mime_type, plaintext_len);
+        let (staged, content_hash) = staging.encrypt_path(&self.master_key, source, logical_path, encoding)?;
+        let file_id = staged.file_id().clone();
+        let encrypted_size = staged.len();
+        let report = self
+            .upload_and_index(&mut index, &file_id, PutBlob::Staged(staged), logical_path, encrypted_size, &content_hash)
+            .await?;
+        record_content_type(&mut index, &report.file_id, logical_path, &head)?;
+        index.set_plaintext_size(&report.file_id, plaintext_len)?;
+        if plaintext_len <= extract::MAX_SOURCE_BYTES {
+            self.index_content(&report, || std::fs::read(source).map(Cow::Owned));
+        }
+        Ok(report)
+    }
+
+    /// Indexe le texte d'un fichier envoyé quand la recherche dans le contenu est activée ;
+    /// un échec est journalisé sans faire échouer l'envoi.
+    fn index_content<'a>(&self, report: &PutReport, read: impl FnOnce() -> std::io::Result<Cow<'a, [u8]>>) {
+        if !extract::is_extractable(&report.logical_path) {
+            return;
+        }
+        let result = (|| {
+            if !search::enabled(&self.open_index()?)? {
+                return Ok(());
+            }
+            let plaintext = read().map_err(|e| VaultError::Storage(StorageError::Io(e.to_string())))?;
+            let Some(text) = extract::extract_text(&report.logical_path, &plaintext) else {
+                return Ok(());
+            };
+            ContentIndex::open(&self.index_path, self.master_key.as_bytes())?.set_text(&report.file_id, &text)?;
+            Ok::<_, VaultError>(())
+        })();
+        if let Err(e) = result {
+            log::warn!("Failed to index the content of {}: {}", report.logical_path, e);
+        }
+    }
+
+    /// Retire le texte indexé d'un fichier supprimé définitivement.
+    fn forget_content(&self, file_id: &FileId) {
+        if !search::content_db_path(&self.index_path).exists() {
+            return;
+        }
+        let result = ContentIndex::open(&self.index_path, self.master_key.as_bytes())
+            .and_then(|mut content| content.remove(file_id));
+        if let Err(e) = result {
+            log::warn!("Failed to drop the indexed content of {}: {}", file_id, e);
+        }
+    }
+
+    /// Envoie un blob (ou diffère son envoi hors ligne) puis l'indexe.
+    ///
+    /// L'index des empreintes est consulté d'abord : un contenu identique déjà envoyé n'est
+    /// pas transféré de nouveau, et un fichier inchangé au même chemin est laissé tel quel.
+    ///
+    /// L'ancien fichier au même chemin n'est retiré qu'une fois le nouveau référencé : à aucun
+    /// moment l'index ne pointe vers deux fichiers au même chemin, ni vers aucun.
+    ///
+    /// Le blob est envoyé sous `object_key` (l'UUID de son en-tête) ; en mode clés aléatoires
+    /// ([`remote_keys`]), l'entrée reçoit son propre identifiant, lié à l'objet.
+    async fn upload_and_index(
+        &self,
+        index: &mut SqlCipherIndex,
+        object_key: &FileId,
+        blob: PutBlob,
+        logical_path: &str,
+        encrypted_size: u64,
+        content_hash: &[u8; 32],
+    ) -> Result<PutReport, VaultError> {
+        let file_id = &remote_keys::entry_id(index, object_key)?;
+        let (encrypted_size, deduplicated) = match index.find_by_content_hash(content_hash, logical_path)? {
+            Some(existing) if existing.logical_path == logical_path => {
+                log::info!("Unchanged content at {}, keeping {}", logical_path, existing.file_id);
+                return Ok(PutReport {
+                    file_id: existing.file_id,
+                    logical_path: existing.logical_path,
+                    deduplicated: true,
+                });
+            }
+            Some(existing) => {
+                drop(blob);
+                index.link_blob(file_id, &existing.object_key, &existing.aad_path)?;
+                log::info!("Deduplicated {} against object {}", logical_path, existing.object_key);
+                (existing.encrypted_size, true)
+            }
+            None => {
+                self.upload_blob(index, object_key, blob).await?;
+                if file_id != object_key {
+                    index.link_blob(file_id, object_key, logical_path)?;
+                }
+                (encrypted_size, false)
+            }
+        };
+
+        let meta = FileMetadata {
+            logical_path: logical_path.to_string(),
+            encrypted_size,
+        };
+        // Un fichier au même chemin est remplacé (il devient une version) ; un alias ne l'est
+        // pas : le fichier est alors placé à côté
+        let logical_path = match index.files_at_path(logical_path)?.into_iter().find(|id| id != file_id) {
+            Some(previous) => {
+                index.replace_file(&previous, file_id, &meta, Some(content_hash))?;
+                log::info!("Replaced {} with {} at {}", previous, file_id, logical_path);
+                self.retire_versions(logical_path).await;
+                logical_path.to_string()
+            }
+            None => {
+                let placed = index.insert_at_free_path(file_id.clone(), meta)?;
+                index.set_content_hash(file_id, content_hash)?;
+                placed
+            }
+        };
+        Ok(PutReport {
+            file_id: file_id.clone(),
+            logical_path,
+            deduplicated,
+        })
+    }
+
+    async fn upload_blob(&self, index: &mut SqlCipherIndex, file_id: &FileId, blob: PutBlob) -> Result<(), VaultError> {
+        let len = match &blob {
+            PutBlob::Memory(bytes) => bytes.len() as u64,
+            PutBlob::Staged(staged) => staged.len(),
+        };
+        let slot = self.transfer_slot(TransferPriority::for_size(len)).await;
+        let uploaded = match &blob {
+            PutBlob::Memory(bytes) => self.remote.upload_file(file_id, bytes).await,
+            PutBlob::Staged(staged) => self.remote.upload_path(file_id, staged.path()).await,
+        };
+        drop(slot);
+        match uploaded {
+            Ok(etag) => Ok(index.record_etag(file_id, &etag)?),
+            Err(StorjError::Unreachable(msg)) if self.cache.is_some() => {
+                log::warn!("Remote unreachable, deferring upload of {}: {}", file_id, msg);
+                match blob {
+                    PutBlob::Memory(bytes) => self.defer_upload(index, file_id, &bytes),
+                    PutBlob::Staged(staged) => self.defer_staged(index, staged),
+                }
+            }
+            Err(e) => {
+                if let Err(count_error) = activity::record_failed_transfer(index) {
+                    log::warn!("Failed to count failed upload of {}: {}", file_id, count_error);
+                }
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Supprime les versions au-delà de [`VERSION_RETENTION`] ; un échec est journalisé et
+    /// la version reste listée, pour être retirée au prochain remplacement.
+    pub async fn retire_versions(&self, logical_path: &str) {
+        let result = async {
+            let expired: Vec<FileVersion> = self
+                .open_index()?
+                .list_versions(logical_path)?
+                .into_iter()
+                .skip(VERSION_RETENTION)
+                .collect();
+            for version in expired {
+                if !self.open_index()?.is_pinned(&version.id)? {
+                    self.delete_remote(&version.id).await?;
+                }
+                self.open_index()?.remove_version(&version.id)?;
+                self.forget_content(&version.id);
+                log::info!("Retired old version {} of {}", version.id, logical_path);
+            }
+            Ok::<_, VaultError>(())
+        }
+        .await;
+        if let Err(e) = result {
+            log::warn!("Failed to retire old versions of {}: {}", logical_path, e);
+        }
+    }
+
+    /// Versions conservées d'un fichier, de la plus récente à la plus ancienne.
+    pub fn versions(&self, logical_path: &str) -> Result<Vec<FileVersion>, VaultError> {
+        Ok(self.open_index()?.list_versions(logical_path)?)
+    }
+
+    /// Remet une version en place ; le fichier actuel devient une version à son tour.
+    pub fn restore_version(&self, version_id: &FileId) -> Result<FileMetadata, VaultError> {
+        match self.open_index()?.restore_version(version_id) {
+            Err(rusqlite::Error::QueryReturnedNoRows) => Err(VaultError::NotFound(version_id.clone())),
+            other => Ok(other?),
+        }
+    }
+
+    /// Télécharge et déchiffre un fichier indexé.
+    pub async fn get(&self, file_id: &FileId) -> Result<Vec<u8>, VaultError> {
+        let meta = self
+            .open_index()?
+            .get(file_id)?
+            .ok_or_else(|| VaultError::NotFound(file_id.clone()))?;
+        self.get_with_path(file_id, &meta.logical_path).await
+    }
+
+    /// Comme [`Vault::get`], quand le chemin logique est déjà connu de l'appelant.
+    ///
+    /// Le cache local est consulté en premier ; un blob téléchargé y est conservé. Un blob du
+    /// cache qui ne se déchiffre pas est écarté au profit de l'objet distant ; si celui-ci
+    /// échoue à son tour, l'entrée est mise en quarantaine.
+    pub async fn get_with_path(&self, file_id: &FileId, logical_path: &str) -> Result<Vec<u8>, VaultError> {
+        let object_key = self.open_index()?.object_key(file_id)?;
+        let cached = self.cache.as_ref().is_some_and(|c| c.contains(&object_key));
+        let encrypted = self.fetch_blob(file_id).await?;
+        match self.open_blob(file_id, &encrypted, logical_path) {
+            Err(e) if is_verification_failure(&e) && cached => {
+                log::warn!("Cached blob {} failed verification, reading the remote object: {}", object_key, e);
+                if let Some(cache) = &self.cache {
+                    cache.remove(&object_key);
+                }
+                let encrypted = self.fetch_blob(file_id).await?;
+                self.open_blob(file_id, &encrypted, logical_path)
+                    .map_err(|e| self.quarantine_on_failure(file_id, e))
+            }
+            result => result.map_err(|e| self.quarantine_on_failure(file_id, e)),
+        }
+    }
+
+    fn open_blob(&self, file_id: &FileId, encrypted: &[u8], logical_path: &str) -> Result<Vec<u8>, VaultError> {
+        let aether_file = AetherFile::from_bytes(encrypted)?;
+        self.decrypt_bound(file_id, &aether_file, logical_path)
+    }
+
+    /// Met l'entrée en quarantaine si `error` est un échec de vérification du contenu ; le
+    /// blob fautif n'est pas gardé en cache.
+    fn quarantine_on_failure(&self, file_id: &FileId, error: VaultError) -> VaultError {
+        if is_verification_failure(&error) {
+            let object_key = self.open_index().and_then(|index| Ok(index.object_key(file_id)?));
+            if let (Some(cache), Ok(object_key)) = (&self.cache, object_key) {
+                cache.remove(&object_key);
+            }
+            self.quarantine(file_id, &error.to_string())
+        } else {
+            error
+        }
+    }
+
+    /// Compte un transfert échoué pour le résumé d'activité, puis rend l'erreur.
+    fn failed_transfer(&self, error: StorjError) -> VaultError {
+        let counted = self
+            .open_index()
+            .and_then(|mut index| Ok(activity::record_failed_transfer(&mut index)?));
+        if let Err(e) = counted {
+            log::warn!("Failed to count failed transfer: {}", e);
+        }
+        error.into()
+    }
+
+    /// Isole une entrée dont l'objet distant a échoué à la vérification au téléchargement.
+    fn quarantine(&self, file_id: &FileId, reason: &str) -> VaultError {
+        let recorded = self
+            .open_index()
+            .and_then(|mut index| Ok(index.quarantine(file_id, reason, QUARANTINE_SOURCE)?));
+        match recorded {
+            Ok(()) => {
+                log::warn!("Quarantined {} after failed download verification: {}", file_id, reason);
+                VaultError::Quarantined(file_id.clone())
+            }
+            Err(e) => e,
+        }
+    }
+
+    /// Déchiffre sous le chemin lié à l'AAD (chemin de l'envoi d'origine pour une entrée
+    /// dédupliquée, ancien chemin tant que le re-chiffrement d'un dossier renommé est en
+    /// attente), puis sous le chemin logique actuel.
+    ///
+    /// Le dernier essai couvre un arrêt entre l'envoi re-chiffré et la mise à jour de l'index.
+    pub fn decrypt_bound(
+        &self,
+        file_id: &FileId,
+        aether_file: &AetherFile,
+        logical_path: &str,
+    ) -> Result<Vec<u8>, VaultError> {
+        decrypt_with_index(&self.open_index()?, &self.master_key, file_id, aether_file, logical_path)
+    }
+
+    /// Blob chiffré d'un fichier, depuis le cache si possible.
+    ///
+    /// Une entrée dédupliquée lit l'objet de l'envoi dont elle partage le contenu. Une entrée
+    /// en quarantaine est refusée ; un objet distant dont l'en-tête ne vérifie pas l'y place.
+    pub async fn fetch_blob(&self, file_id: &FileId) -> Result<Vec<u8>, VaultError> {
+        self.fetch_blob_with_progress(file_id, |_, _| {}).await
+    }
+
+    /// Comme [`Vault::fetch_blob`] ; `progress` reçoit les octets reçus et la taille de
+    /// l'objet, après chaque morceau quand il est lu par morceaux (voir
+    /// [`Vault::with_downloads`]), sinon une fois l'objet reçu.
+    pub async fn fetch_blob_with_progress(
+        &self,
+        file_id: &FileId,
+        mut progress: impl FnMut(u64, u64),
+    ) -> Result<Vec<u8>, VaultError> {
+        let object_key = {
+            let index = self.open_index()?;
+            if index.quarantine_entry(file_id)?.is_some() {
+                return Err(VaultError::Quarantined(file_id.clone()));
+            }
+            index.object_key(file_id)?
+        };
+        if let Some(blob) = self.cache.as_ref().and_then(|c| c.get(&object_key)) {
+            return Ok(blob);
+        }
+        // Une lecture est demandée par l'utilisateur, sauf étiquette contraire
+        let slot = self.transfer_slot(TransferPriority::Interactive).await;
+        let fetched = match &self.downloads {
+            Some(downloads) => downloads.fetch_with_progress(&self.remote, &object_key, &mut progress).await,
+            None => self.remote.download_file(&object_key).await.inspect(|blob| {
+                progress(blob.len() as u64, blob.len() as u64);
+            }),
+        };
+        drop(slot);
+        let blob = fetched.map_err(|e| self.failed_transfer(e))?;
+        if let Some(reason) = integrity::object_drift(&self.master_key, &object_key, &blob, blob.len() as u64) {
+            return Err(self.quarantine(file_id, &reason));
+        }
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.put(&object_key, &blob) {
+                log::warn!("Failed to cache blob {}: {}", object_key, e);
+            }
+        }
+        Ok(blob)
+    }
+
+    /// Télécharge et déchiffre un fichier directement dans `dest`, sans copie du clair en
+    /// mémoire.
+    ///
+    /// Le corps chiffré est écrit par morceaux dans un fichier voisin (`.aether-part`), puis
+    /// déchiffré sur place une fois complet, tag vérifié, avant d'être renommé en `dest` : un
+    /// contenu qui ne se vérifie pas n'apparaît jamais sous `dest`. Un corps par morceaux est
+    /// déchiffré en flux depuis le fichier voisin. Un téléchargement interrompu reprend comme
+    /// [`Vault::fetch_blob`] (voir [`Vault::with_downloads`]). `progress` reçoit les octets
+    /// reçus et la taille de l'objet. Retourne la taille du fichier en clair.
+    pub async fn save_to(
+        &self,
+        file_id: &FileId,
+        logical_path: &str,
+        dest: &Path,
+        mut progress: impl FnMut(u64, u64),
+    ) -> Result<u64, VaultError> {
+        let (object_key, candidates) = {
+            let index = self.open_index()?;
+            if index.quarantine_entry(file_id)?.is_some() {
+                return Err(VaultError::Quarantined(file_id.clone()));
+            }
+            (index.object_key(file_id)?, aad_paths(&index, file_id, logical_path)?)
+        };
+        let io_error = |e: std::io::Error| VaultError::Storage(StorageError::Io(format!("{}: {}", dest.display(), e)));
+        let mut partial = dest.as_os_str().to_owned();
+        partial.push(PARTIAL_SUFFIX);
+        let mut sink = CiphertextSink::create(PathBuf::from(partial), false).map_err(io_error)?;
+        self.receive_object(file_id, &object_key, &mut sink, &mut progress).await.map_err(|e| match e {
+            ReceiveError::Io(e) => io_error(e),
+            ReceiveError::Vault(e) => e,
+        })?;
+
+        let (header, ciphertext_len) = AetherFile::parse_header(&sink.header)?;
+        if storage::is_chunked(&header) {
+            return self.decrypt_chunked_sink(file_id, &sink, &header, ciphertext_len, &candidates, dest);
+        }
+        let tag: [u8; storage::TAG_LEN] = sink
+            .tag
+            .as_slice()
+            .try_into()
+            .map_err(|_| VaultError::Format(AetherError::InvalidHeader))?;
+        let mut plaintext_len = sink.file.metadata().map_err(io_error)?.len();
+        let mut result = Err(VaultError::NotFound(file_id.clone()));
+        let mut inflated: Option<CiphertextSink> = None;
+        if plaintext_len == 0 {
+            for aad_path in &candidates {
+                result = storage::decrypt_in_place(&self.master_key, &header, &mut [], &tag, aad_path).map_err(VaultError::from);
+                if result.is_ok() {
+                    break;
+                }
+            }
+        } else {
+            // SAFETY : le fichier partiel vient d'être créé (create_new) et n'est ouvert que
+            // par `sink` tant que la projection existe.
+            let mut body = unsafe { MmapMut::map_mut(&sink.file) }.map_err(io_error)?;
+            for aad_path in &candidates {
+                result = storage::decrypt_in_place(&self.master_key, &header, &mut body, &tag, aad_path).map_err(VaultError::from);
+                if result.is_ok() {
+                    break;
+                }
+            }
+            if result.is_ok() {
+                body.flush().map_err(io_error)?;
+                // Corps encadré : le contenu compressé est décompressé dans un second fichier
+                // partiel, un contenu bourré est tronqué à sa longueur
+                if storage::is_framed(&header) {
+                    let (content_len, compressed) = storage::framed_content(&body)?;
+                    plaintext_len = content_len as u64;
+                    if compressed {
+                        let mut inflated_path = sink.path.clone().into_os_string();
+                        inflated_path.push(".inflate");
+                        let output = CiphertextSink::create(PathBuf::from(inflated_path), false).map_err(io_error)?;
+                        plaintext_len = storage::inflate_to(&body[..content_len], &output.file)?;
+                        inflated = Some(output);
+                    }
+                }
+            }
+        }
+        result.map_err(|e| self.quarantine_on_failure(file_id, e))?;
+
+        match inflated {
+            Some(output) => output.persist(dest).map_err(io_error)?,
+            None => {
+                sink.file.set_len(plaintext_len).map_err(io_error)?;
+                sink.persist(dest).map_err(io_error)?;
+            }
+        }
+        Ok(plaintext_len)
+    }
+
+    /// Télécharge l'objet chiffré d'un fichier tel quel dans `dest` (fichier `.aeth` lisible
+    /// par `decrypt_file_to_path` ou une restauration hors ligne), par plages comme
+    /// [`Vault::save_to`]. Retourne la taille de l'objet.
+    pub async fn save_encrypted_to(
+        &self,
+        file_id: &FileId,
+        dest: &Path,
+        mut progress: impl FnMut(u64, u64),
+    ) -> Result<u64, VaultError> {
+        let object_key = {
+            let index = self.open_index()?;
+            if index.quarantine_entry(file_id)?.is_some() {
+                return Err(VaultError::Quarantined(file_id.clone()));
+            }
+            index.object_key(file_id)?
+        };
+        let io_error = |e: std::io::Error| VaultError::Storage(StorageError::Io(format!("{}: {}", dest.display(), e)));
+        let mut partial = dest.as_os_str().to_owned();
+        partial.push(PARTIAL_SUFFIX);
+        let mut sink = CiphertextSink::create(PathBuf::from(partial), true).map_err(io_error)?;
+        self.receive_object(file_id, &object_key, &mut sink, &mut progress).await.map_err(|e| match e {
+            ReceiveError::Io(e) => io_error(e),
+            ReceiveError::Vault(e) => e,
+        })?;
+        let size = sink.received;
+        sink.persist(dest).map_err(io_error)?;
+        Ok(size)
+    }
+
+    /// Remplit `sink` avec l'objet `object_key` : un blob du cache est déjà complet, sinon
+    /// l'objet est lu par plages et son en-tête vérifié dès le premier morceau, avant de tout
+    /// télécharger. Avec des téléchargements reprenables ([`Vault::with_downloads`]), l'objet
+    /// est d'abord reçu dans leur fichier partiel, repris au premier morceau manquant après
+    /// une interruption, puis recopié dans `sink`.
+    async fn receive_object(
+        &self,
+        file_id: &FileId,
+        object_key: &FileId,
+        sink: &mut CiphertextSink,
+        progress: &mut impl FnMut(u64, u64),
+    ) -> Result<(), ReceiveError> {
+        if let Some(blob) = self.cache.as_ref().and_then(|c| c.get(object_key)) {
+            sink.absorb(&blob, blob.len() as u64)?;
+            progress(sink.received, blob.len() as u64);
+            return Ok(());
+        }
+        let _slot = self.transfer_slot(TransferPriority::Interactive).await;
+        if let Some(downloads) = &self.downloads {
+            use std::io::Read;
+            let mut downloaded = downloads
+                .fetch_to_file(&self.remote, object_key, &mut *progress)
+                .await
+                .map_err(|e| self.failed_transfer(e))?;
+            let total = downloaded.len();
+            let mut buffer = vec![0u8; 64 * 1024];
+            loop {
+                let read = downloaded.read(&mut buffer)?;
+                if read == 0 {
+                    break;
+                }
+                sink.absorb(&buffer[..read], total)?;
+            }
+            if let Some(reason) = integrity::object_drift(&self.master_key, object_key, &sink.header, total) {
+                return Err(self.quarantine(file_id, &reason).into());
+            }
+            return Ok(());
+        }
+        loop {
+            let read = self
+                .remote
+                .download_range(object_key, sink.received, downloads::CHUNK_SIZE)
+                .await
+                .map_err(|e| self.failed_transfer(e))?;
+            if read.data.is_empty() && sink.received < read.total {
+                return Err(VaultError::Remote(StorjError::S3(format!("Short read while downloading {}", object_key))).into());
+            }
+            let header_pending = sink.header.len() < AetherFile::HEADER_LEN;
+            sink.absorb(&read.data, read.total)?;
+            if header_pending {
+                if let Some(reason) = integrity::object_drift(&self.master_key, object_key, &sink.header, read.total) {
+                    return Err(self.quarantine(file_id, &reason).into());
+                }
+            }
+            progress(sink.received, read.total);
+            if sink.received >= read.total {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Déchiffre en flux un corps par morceaux reçu dans `sink` (les derniers octets, mis de
+    /// côté comme tag, terminent le dernier morceau) vers un second fichier partiel, renommé
+    /// en `dest` une fois tous les morceaux authentifiés.
+    fn decrypt_chunked_sink(
+        &self,
+        file_id: &FileId,
+        sink: &CiphertextSink,
+        header: &storage::AetherHeader,
+        ciphertext_len: u64,
+        candidates: &[String],
+        dest: &Path,
+    ) -> Result<u64, VaultError> {
+        use std::io::{BufReader, BufWriter, Read, Seek, Write};
+        let io_error = |e: std::io::Error| VaultError::Storage(StorageError::Io(format!("{}: {}", dest.display(), e)));
+        let mut output_path = sink.path.clone().into_os_string();
+        output_path.push(".plain");
+        let output_path = PathBuf::from(output_path);
+        let mut result = Err(VaultError::NotFound(file_id.clone()));
+        for aad_path in candidates {
+            let output = CiphertextSink::create(output_path.clone(), true).map_err(io_error)?;
+            (&sink.file).rewind().map_err(io_error)?;
+            let reader = BufReader::new(&sink.file).chain(sink.tag.as_slice());
+            let mut writer = BufWriter::new(&output.file);
+            let decrypted = storage::decrypt_stream(&self.master_key, header, ciphertext_len, reader, aad_path, &mut writer)
+                .and_then(|len| writer.flush().map(|_| len).map_err(|e| StorageError::Io(e.to_string())));
+            drop(writer);
+            match decrypted {
+                Ok(plaintext_len) => {
+                    output.persist(dest).map_err(io_error)?;
+                    return Ok(plaintext_len);
+                }
+                Err(e) => result = Err(VaultError::from(e)),
+            }
+        }
+        result.map_err(|e| self.quarantine_on_failure(file_id, e))
+    }
+
+    /// Métadonnées d'un fichier de l'index ou de la corbeille : le contenu d'un fichier
+    /// supprimé reste sur Storj jusqu'à la purge, il peut encore être consulté.
+    pub fn find_including_trash(&self, file_id: &FileId) -> Result<FileMetadata, VaultError> {
+        let index = self.open_index()?;
+        let found = match &self.index_cache {
+            Some(cache) => cache.get(&index, file_id)?,
+            None => index.get(file_id)?,
+        };
+        match found {
+            Some(meta) => Ok(meta),
+            None => index.get_trashed(file_id)?.ok_or_else(|| VaultError::NotFound(file_id.clone())),
+        }
+    }
+
+    /// Taille du contenu en clair d'un fichier : celle relevée au chiffrement, sinon celle
+    /// d'un fichier V1 (entrée indexée avant ce relevé).
+    pub fn plaintext_size(&self, file_id: &FileId, meta: &FileMetadata) -> Result<u64, VaultError> {
+        let recorded = self.open_index()?.plaintext_size(file_id)?;
+        Ok(recorded.unwrap_or_else(|| AetherFile::v1_plaintext_len(meta.encrypted_size)))
+    }
+
+    /// Fait apparaître `target_id` au chemin `logical_path` sans dupliquer son contenu.
+    pub fn create_alias(&self, logical_path: &str, target_id: &FileId) -> Result<AliasEntry, VaultError> {
+        if !logical_path.starts_with('/') || logical_path.ends_with('/') {
+            return Err(VaultError::InvalidPath("alias_path"));
+        }
+        let logical_path: &str = &canonical_file_path(logical_path)?;
+        let mut index = self.open_index()?;
+        let target = index.get(target_id)?.ok_or_else(|| VaultError::NotFound(target_id.clone()))?;
+        // Un alias désigne un fichier, pas un dossier
+        if target.encrypted_size == 0 || target.logical_path.ends_with('/') {
+            return Err(VaultError::InvalidPath("alias_target"));
+        }
+        if index.alias_at(logical_path)?.is_some()
+            || index.list_all()?.iter().any(|(_, meta)| meta.logical_path == logical_path)
+        {
+            return Err(VaultError::AlreadyExists(logical_path.to_string()));
+        }
+        let mut raw = [0u8; 16];
+        OsRng.fill_bytes(&mut raw);
+        let alias = index.create_alias(&hex::encode(raw), logical_path, target_id)?;
+        log::info!("Alias created: {} -> {}", alias.logical_path, target_id);
+        Ok(alias)
+    }
+
+    /// Fichier désigné par un alias ; `NotFound` (cible) si elle est à la corbeille ou purgée.
+    pub fn resolve_alias(&self, alias_id: &FileId) -> Result<(FileId, FileMetadata), VaultError> {
+        let index = self.open_index()?;
+        let alias = index.get_alias(alias_id)?.ok_or_else(|| VaultError::NotFound(alias_id.clone()))?;
+        let target = index
+            .get(&alias.target_id)?
+            .ok_or_else(|| VaultError::NotFound(alias.target_id.clone()))?;
+        Ok((alias.target_id, target))
+    }
+
+    /// Supprime un alias ; le fichier désigné n'est pas touché.
+    pub fn remove_alias(&self, alias_id: &FileId) -> Result<(), VaultError> {
+        let mut index = self.open_index()?;
+        if index.get_alias(alias_id)?.is_none() {
+            return Err(VaultError::NotFound(alias_id.clone()));
+        }
+        index.remove_alias(alias_id)?;
+        Ok(())
+    }
+
+    /// Paramètres de chiffrement d'un fichier indexé ou mis à la corbeille.
+    ///
+    /// Seul l'en-tête est lu : depuis le cache si possible, sinon par une requête partielle.
+    pub async fn encryption_details(&self, file_id: &FileId) -> Result<EncryptionDetails, VaultError> {
+        self.find_including_trash(file_id)?;
+        let object_key = self.open_index()?.object_key(file_id)?;
+        let prefix = match self.cache.as_ref().and_then(|c| c.get(&object_key)) {
+            Some(blob) => blob,
+            None => self.remote.download_prefix(&object_key, AetherFile::HEADER_LEN).await?.0,
+        };
+        let (header, _) = AetherFile::parse_header(&prefix)?;
+        Ok(storage::encryption_details(&self.master_key, &header)?)
+    }
+
+    /// Vérifie qu'une copie locale (SHA-256 et taille) est bien le contenu d'un fichier indexé.
+    ///
+    /// L'objet distant est relu : engagement de l'en-tête contrôlé avec la FileKey re-dérivée,
+    /// puis déchiffrement authentifié. L'empreinte attendue est celle enregistrée à l'envoi ;
+    /// pour une entrée antérieure, celle du contenu distant, qui est alors enregistrée.
+    pub async fn verify_download(
+        &self,
+        file_id: &FileId,
+        local_sha256: &[u8; 32],
+        local_size: u64,
+    ) -> Result<DownloadVerification, VaultError> {
+        let meta = self
+            .open_index()?
+            .get(file_id)?
+            .ok_or_else(|| VaultError::NotFound(file_id.clone()))?;
+        let blob = self.fetch_blob(file_id).await?;
+        let aether_file = AetherFile::from_bytes(&blob)?;
+
+        let object_key = self.open_index()?.object_key(file_id)?;
+        let header_valid = hex::encode(aether_file.header.uuid) == object_key
+            && storage::encryption_details(&self.master_key, &aether_file.header)
+                .is_ok_and(|details| details.commitment_valid);
+        let remote_plaintext: Option<([u8; 32], u64)> = if header_valid {
+            self.decrypt_bound(file_id, &aether_file, &meta.logical_path)
+                .ok()
+                .map(|plaintext| (Sha256::digest(&plaintext).into(), plaintext.len() as u64))
+        } else {
+            None
+        };
+        let remote_hash = remote_plaintext.map(|(hash, _)| hash);
+
+        let mut index = self.open_index()?;
+        let recorded = index.content_hash(file_id)?;
+        if let (None, Some(hash)) = (recorded, remote_hash) {
+            index.set_content_hash(file_id, &hash)?;
+        }
+        let expected = recorded.or(remote_hash);
+
+        let content_authentic = remote_hash.is_some();
+        let index_consistent = match (recorded, remote_hash) {
+            (Some(recorded), Some(remote)) => recorded == remote,
+            _ => true,
+        };
+        // Un contenu compressé ou bourré n'a pas la taille qu'indique l'objet chiffré : sans
+        // clair déchiffré, seule la taille relevée au chiffrement ou l'en-tête la donnent
+        let recorded_size = index.plaintext_size(file_id)?;
+        if let (None, Some((_, len))) = (recorded_size, remote_plaintext) {
+            index.set_plaintext_size(file_id, len)?;
+        }
+        let expected_size = remote_plaintext.map(|(_, len)| len).or(recorded_size).or_else(|| {
+            storage::plaintext_len(&aether_file.header, aether_file.ciphertext.len() as u64)
+        });
+        let size_matches = expected_size == Some(local_size);
+        let hash_matches = expected.is_some_and(|hash| hash == *local_sha256);
+
+        Ok(DownloadVerification {
+            file_id: file_id.clone(),
+            logical_path: meta.logical_path,
+            header_valid,
+            content_authentic,
+            hash_recorded: recorded.is_some(),
+            index_consistent,
+            size_matches,
+            hash_matches,
+            local_sha256: hex::encode(local_sha256),
+            verified: header_valid && content_authentic && index_consistent && size_matches && hash_matches,
+        })
+    }
+
+    /// Compte une ouverture (aperçu, téléchargement) pour la politique du cache.
+    pub fn record_access(&self, file_id: &FileId) -> Result<(), VaultError> {
+        self.open_index()?.record_access(file_id)?;
+        Ok(())
+    }
+
+    /// Applique la politique du cache : éviction des blobs froids au-delà de la taille
+    /// maximale, puis pré-chargement des fichiers ouverts souvent.
+    ///
+    /// Les blobs d'envois différés ne sont jamais évincés, ni ceux des dossiers gardés hors
+    /// ligne ([`policies::FolderPolicy::pin_offline`]), qui sont téléchargés d'avance quelle
+    /// que soit leur fréquence. Le pré-chargement s'arrête sans erreur si le stockage distant
+    /// est injoignable.
+    pub async fn maintain_cache(&self) -> Result<CacheReport, VaultError> {
+        let Some(cache) = &self.cache else {
+            return Ok(CacheReport::default());
+        };
+        let io_error = |e: std::io::Error| VaultError::Storage(StorageError::Io(e.to_string()));
+
+        let mut index = self.open_index()?;
+        let policy = policy::load(&index)?;
+        let stats = index.access_stats()?;
+        let mut pinned = index.pending_upload_ids()?;
+        let entries = index.list_all()?;
+        let folder_policies = policies::load(&index)?;
+        let mut offline = Vec::new();
+        for (id, meta) in &entries {
+            if !meta.logical_path.ends_with('/') && folder_policies.resolve(&meta.logical_path).pin_offline {
+                let object_key = index.object_key(id)?;
+                pinned.insert(object_key.clone());
+                offline.push((id.clone(), object_key));
+            }
+        }
+        let indexed: HashMap<FileId, u64> = entries
+            .into_iter()
+            .map(|(id, meta)| (id, meta.encrypted_size))
+            .collect();
+        drop(index);
+
+        let entries = cache.entries().map_err(io_error)?;
+        let evicted = policy::eviction_plan(&entries, &stats, &pinned, policy.max_bytes);
+        for file_id in &evicted {
+            cache.remove(file_id);
+        }
+
+        let remaining = cache.entries().map_err(io_error)?;
+        let cached: HashSet<FileId> = remaining.iter().map(|(id, _)| id.clone()).collect();
+        let cached_bytes = remaining.iter().map(|(_, size)| size).sum();
+        let mut prewarmed = 0;
+        let missing_offline: Vec<FileId> = offline
+            .into_iter()
+            .filter(|(_, object_key)| !cached.contains(object_key))
+            .map(|(id, _)| id)
+            .collect();
+        let frequent = policy::prewarm_candidates(&stats, &indexed, &cached, cached_bytes, &policy)
+            .into_iter()
+            .filter(|id| !missing_offline.contains(id))
+            .collect::<Vec<_>>();
+        for file_id in missing_offline.into_iter().chain(frequent) {
+            match self.fetch_blob(&file_id).await {
+                Ok(_) => prewarmed += 1,
+                Err(VaultError::Remote(StorjError::Unreachable(msg))) => {
+                    log::info!("Remote unreachable, stopping cache prewarm: {}", msg);
+                    break;
+                }
+                Err(e) => log::warn!("Failed to prewarm {}: {}", file_id, e),
+            }
+        }
+
+        let entries = cache.entries().map_err(io_error)?;
+        Ok(CacheReport {
+            policy,
+            cached_files: entries.len(),
+            cached_bytes: entries.iter().map(|(_, size)| size).sum(),
+            evicted: evicted.len(),
+            prewarmed,
+        })
+    }
+
+    /// Vide le cache, hormis les blobs d'envois différés.
+    pub fn clear_cache(&self) -> Result<usize, VaultError> {
+        let Some(cache) = &self.cache else {
+            return Ok(0);
+        };
+        let pinned = self.open_index()?.pending_upload_ids()?;
+        let entries = cache
+            .entries()
+            .map_err(|e| VaultError::Storage(StorageError::Io(e.to_string())))?;
+        let mut removed = 0;
+        for (file_id, _) in entries.into_iter().filter(|(id, _)| !pinned.contains(id)) {
+            cache.remove(&file_id);
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    /// Place un blob dans le cache et enregistre son envoi différé.
+    pub fn defer_upload(
+        &self,
+        index: &mut SqlCipherIndex,
+        file_id: &FileId,
+        blob: &[u8],
+    ) -> Result<(), VaultError> {
+        let cache = self.cache.as_ref().ok_or_else(|| VaultError::CacheMiss(file_id.clone()))?;
+        cache
+            .put(file_id, blob)
+            .map_err(|e| VaultError::Storage(StorageError::Io(e.to_string())))?;
+        index.enqueue_pending(PendingKind::Upload, file_id)?;
+        Ok(())
+    }
+
+    /// Comme [`Vault::defer_upload`], pour un blob déjà écrit dans la zone de transit.
+    fn defer_staged(&self, index: &mut SqlCipherIndex, staged: StagedBlob) -> Result<(), VaultError> {
+        let cache = self.cache.as_ref().ok_or_else(|| VaultError::CacheMiss(staged.file_id().clone()))?;
+        let file_id = staged.file_id().clone();
+        staged
+            .move_into(cache)
+            .map_err(|e| VaultError::Storage(StorageError::Io(e.to_string())))?;
+        index.enqueue_pending(PendingKind::Upload, &file_id)?;
+        Ok(())
+    }
+
+    /// Supprime définitivement l'objet distant, ou diffère la suppression hors ligne.
+    ///
+    /// Un objet dont le contenu est encore lu par une autre entrée (déduplication) est conservé.
+    pub async fn delete_remote(&self, file_id: &FileId) -> Result<(), VaultError> {
+        let mut index = self.open_index()?;
+        if index.object_shared(file_id)? {
+            log::info!("Keeping remote object of {}: still shared by another entry", file_id);
+            return Ok(());
+        }
+        let object_key = index.object_key(file_id)?;
+        // Jamais envoyé : il suffit d'annuler l'envoi en attente
+        if index.pending_upload_ids()?.contains(&object_key) {
+            for op in index.list_pending()?.into_iter().filter(|op| op.file_id == object_key) {
+                index.remove_pending(op.seq)?;
+            }
+            if let Some(cache) = &self.cache {
+                cache.remove(&object_key);
+            }
+            return Ok(());
+        }
+
+        if let Some(cache) = &self.cache {
+            cache.remove(&object_key);
+        }
+        // La suppression n'est tenue pour acquise qu'une fois l'absence de l'objet constatée
+        let deleted = match self.remote.delete_file(&object_key).await {
+            Ok(()) | Err(StorjError::NotFound) => self.remote.file_exists(&object_key).await.map(|exists| !exists),
+            Err(e) => Err(e),
+        };
+        match deleted {
+            Ok(true) => Ok(index.forget_etag(&object_key)?),
+            Ok(false) => Err(VaultError::DeleteNotVerified(file_id.clone())),
+            Err(StorjError::Unreachable(msg)) => {
+                log::warn!("Remote unreachable, deferring deletion of {}: {}", object_key, msg);
+                index.enqueue_pending(PendingKind::Delete, &object_key)?;
+                Ok(())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Rejoue la file hors ligne dans l'ordre. S'arrête dès que le distant est injoignable.
+    pub async fn flush_pending(&self) -> Result<FlushReport, VaultError> {
+        let pending = self.open_index()?.list_pending()?;
+        let mut report = FlushReport {
+            completed: 0,
+            remaining: pending.len(),
+        };
+
+        for op in pending {
+            let result = match op.kind {
+                PendingKind::Upload => {
+                    let blob = self
+                        .cache
+                        .as_ref()
+                        .and_then(|c| c.get(&op.file_id))
+                        .ok_or_else(|| VaultError::CacheMiss(op.file_id.clone()))?;
+                    let _slot = self.transfer_slot(TransferPriority::Bulk).await;
+                    match self.remote.upload_file(&op.file_id, &blob).await {
+                        Ok(etag) => self.open_index().and_then(|mut index| Ok(index.record_etag(&op.file_id, &etag)?)),
+                        Err(e) => Err(e.into()),
+                    }
+                }
+                PendingKind::Delete => match self.remote.delete_file(&op.file_id).await {
+                    Err(StorjError::NotFound) => Ok(()),
+                    other => other.map_err(VaultError::from),
+                },
+                PendingKind::Rebind => self.rebind(&op.file_id).await,
+            };
+            match result {
+                Ok(()) => {
+                    self.open_index()?.remove_pending(op.seq)?;
+                    report.completed += 1;
+                    report.remaining -= 1;
+                }
+                Err(VaultError::Remote(StorjError::Unreachable(_))) => break,
+                Err(VaultError::Remote(e)) if op.kind == PendingKind::Upload => return Err(self.failed_transfer(e)),
+                Err(e) => return Err(e),
+            }
+        }
+
+        if report.completed > 0 {
+            log::info!("Offline queue flushed: completed={}, remaining={}", report.completed, report.remaining);
+        }
+        Ok(report)
+    }
+
+    /// Aligne l'index local sur le contenu du bucket.
+    ///
+    /// Seuls les fichiers sont concernés : les dossiers n'ont pas d'objet distant,
+    /// et les envois encore en attente ne sont évidemment pas des orphelins.
+    pub async fn reconcile(&self) -> Result<SyncReport, VaultError> {
+        let remote: HashSet<String> = self
+            .remote
+            .list_files()
+            .await?
+            .into_iter()
+            .map(|key| remote_keys::normalize(&key))
+            .collect();
+
+        let mut index = self.open_index()?;
+        let pending = index.pending_upload_ids()?;
+        let links = index.blob_links()?;
+        let mut removed_orphans = Vec::new();
+        for (file_id, meta) in index.list_all()? {
+            let is_folder = meta.logical_path.ends_with('/') || meta.encrypted_size == 0;
+            let object_key = links.get(&file_id).unwrap_or(&file_id);
+            if !is_folder && !remote.contains(object_key) && !pending.contains(object_key) {
+                log::info!("Removing orphaned file from local index: {}", file_id);
+                index.remove(&file_id)?;
+                removed_orphans.push(file_id);
+            }
+        }
+
+        Ok(SyncReport {
+            remote_count: remote.len(),
+            removed_orphans,
+        })
+    }
+
+    /// Déplace un fichier dans la corbeille (l'objet distant est conservé).
+    pub fn trash(&self, file_id: &FileId) -> Result<(), VaultError> {
+        let mut index = self.open_index()?;
+        let meta = index
+            .get(file_id)?
+            .ok_or_else(|| VaultError::NotFound(file_id.clone()))?;
+        index.move_to_trash(file_id, &meta)?;
+        Ok(())
+    }
+
+    /// Restaure un fichier de la corbeille. Retourne ses métadonnées.
+    ///
+    /// L'objet distant est d'abord vérifié (voir [`Self::verify_trashed`]) : une entrée dont
+    /// l'objet a disparu reste à la corbeille, en quarantaine.
+    pub async fn restore(&self, file_id: &FileId) -> Result<FileMetadata, VaultError> {
+        self.verify_trashed(std::slice::from_ref(file_id)).await?;
+        match self.open_index()?.restore_from_trash(file_id) {
+            Err(rusqlite::Error::QueryReturnedNoRows) => Err(VaultError::NotFound(file_id.clone())),
+            other => Ok(other?),
+        }
+    }
+
+    /// Vérifie, avant leur restauration, que les objets distants d'entrées de la corbeille
+    /// existent encore et que leur en-tête se lit (format, UUID, engagement, taille indexée).
+    ///
+    /// Chaque entrée en échec est mise en quarantaine, où elle peut être réparée (copie
+    /// locale, nouvelle vérification) ou abandonnée ; `Quarantined` désigne alors la première.
+    /// Les dossiers, fichiers vides et envois en attente n'ont rien à relire ; hors ligne, la
+    /// vérification est sautée et laissée à la vérification d'intégrité périodique.
+    pub async fn verify_trashed(&self, file_ids: &[FileId]) -> Result<(), VaultError> {
+        let mut to_check = Vec::new();
+        {
+            let index = self.open_index()?;
+            let pending = index.pending_upload_ids()?;
+            for file_id in file_ids {
+                let meta = index.get_trashed(file_id)?.ok_or_else(|| VaultError::NotFound(file_id.clone()))?;
+                if meta.logical_path.ends_with('/') || meta.encrypted_size == 0 || pending.contains(file_id) {
+                    continue;
+                }
+                to_check.push((file_id.clone(), index.object_key(file_id)?, meta.encrypted_size));
+            }
+        }
+
+        let mut first_failure = None;
+        for (file_id, object_key, encrypted_size) in to_check {
+            if self.open_index()?.quarantine_entry(&file_id)?.is_some() {
+                first_failure.get_or_insert(file_id);
+                continue;
+            }
+            let reason = match self.remote.download_range(&object_key, 0, AetherFile::HEADER_LEN as u64).await {
+                Ok(read) => integrity::object_drift(&self.master_key, &object_key, &read.data, read.total).or_else(|| {
+                    (read.total != encrypted_size)
+                        .then(|| format!("object size {} differs from indexed size {}", read.total, encrypted_size))
+                }),
+                Err(StorjError::NotFound) => Some(integrity::MISSING_REMOTE_REASON.to_string()),
+                Err(StorjError::Unreachable(msg)) => {
+                    log::info!("Restore skipped remote verification: {}", msg);
+                    break;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if let Some(reason) = reason {
+                let mut index = self.open_index()?;
+                index.forget_etag(&object_key)?;
+                index.quarantine(&file_id, &reason, RESTORE_QUARANTINE_SOURCE)?;
+                log::warn!("Quarantined {} before restore: {}", file_id, reason);
+                first_failure.get_or_insert(file_id);
+            }
+        }
+        match first_failure {
+            Some(file_id) => Err(VaultError::Quarantined(file_id)),
+            None => Ok(()),
+        }
+    }
+
+    /// Supprime définitivement un fichier de la corbeille (objet distant compris, sauf s'il
+    /// est épinglé par un instantané).
+    ///
+    /// La ligne de la corbeille n'est retirée qu'après suppression vérifiée (ou différée).
+    pub async fn purge(&self, file_id: &FileId) -> Result<(), VaultError> {
+        // Un objet retenu par un instantané reste à distance ; il sera supprimé avec
+        // le dernier instantané qui le désigne
+        if !self.open_index()?.is_pinned(file_id)? {
+            self.delete_remote(file_id).await?;
+        }
+        let mut index = self.open_index()?;
+        index.remove_from_trash(file_id)?;
+        // Les alias d'un fichier purgé ne désigneraient plus rien
+        index.remove_aliases_of(file_id)?;
+        self.forget_content(file_id);
+        Ok(())
+    }
+
+    /// Vide la corbeille élément par élément ; un échec laisse l'élément dans la corbeille.
+    pub async fn empty_trash(&self) -> Result<PurgeReport, VaultError> {
+        let mut report = PurgeReport::default();
+        for (file_id, meta, _) in self.open_index()?.list_trash()? {
+            match self.purge(&file_id).await {
+                Ok(()) => report.purged.push(file_id),
+                Err(e) => {
+                    log::warn!("Failed to purge {} from trash: {}", file_id, e);
+                    let reason = match e {
+                        VaultError::DeleteNotVerified(_) => "still_present",
+                        VaultError::Remote(_) => "remote_error",
+                        VaultError::Index(_) => "index_error",
+                        _ => "internal_error",
+                    };
+                    report.failed.push(PurgeFailure {
+                        file_id,
+                        logical_path: meta.logical_path,
+                        reason,
+                    });
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Renomme un dossier et sa sous-arborescence dans une seule transaction de l'index.
+    ///
+    /// Le contenu distant reste lisible sous son ancien chemin ; son re-chiffrement est
+    /// mis en file (`PendingKind::Rebind`) et rejoué par [`Vault::flush_pending`].
+    pub fn rename_folder(&self, old_path: &str, new_path: &str) -> Result<FolderRenameReport, VaultError> {
+        let (old_path, new_path): (&str, &str) = (&path::normalize(old_path), &path::normalize(new_path));
+        if new_path.trim_end_matches('/').len() > 1 {
+            path::validate_file_path(new_path.trim_end_matches('/')).map_err(VaultError::InvalidPath)?;
+        }
+        let old_prefix = format!("{}/", old_path.trim_end_matches('/'));
+        let new_prefix = format!("{}/", new_path.trim_end_matches('/'));
+        if old_prefix == "/" || new_prefix == "/" || !new_prefix.starts_with('/') {
+            return Err(VaultError::InvalidPath("root"));
+        }
+        if new_prefix.starts_with(&old_prefix) {
+            return Err(VaultError::InvalidPath("into_itself"));
+        }
+
+        let mut index = self.open_index()?;
+        let entries = index.list_all()?;
+        let in_subtree = |path: &str| path.starts_with(&old_prefix);
+        let pending_rebinds = entries
+            .iter()
+            .filter(|(_, meta)| in_subtree(&meta.logical_path) && meta.encrypted_size > 0)
+            .count();
+        // Les alias occupent des chemins comme les fichiers (mais n'ont rien à re-chiffrer)
+        let aliases = index.list_aliases()?;
+        let paths: Vec<&str> = entries
+            .iter()
+            .map(|(_, meta)| meta.logical_path.as_str())
+            .chain(aliases.iter().map(|alias| alias.logical_path.as_str()))
+            .collect();
+        if !paths.iter().any(|path| in_subtree(path)) {
+            return Err(VaultError::PathNotFound(old_path.to_string()));
+        }
+        let new_folder = new_prefix.trim_end_matches('/');
+        if paths.iter().any(|path| path.starts_with(&new_prefix) || *path == new_folder) {
+            return Err(VaultError::AlreadyExists(new_prefix));
+        }
+
+        let moved_entries = index.rename_folder(old_path, new_path)?;
+        log::info!(
+            "Folder renamed: {} -> {} (entries={}, rebinds={})",
+            old_prefix, new_prefix, moved_entries, pending_rebinds
+        );
+        Ok(FolderRenameReport {
+            moved_entries,
+            pending_rebinds,
+        })
+    }
+
+    /// Re-chiffre un fichier sous son chemin logique actuel, en conservant son UUID.
+    ///
+    /// L'objet distant est remplacé sur place ; l'ancien lien AAD n'est oublié qu'après l'envoi.
+    async fn rebind(&self, file_id: &FileId) -> Result<(), VaultError> {
+        let (meta, bound_path) = {
+            let index = self.open_index()?;
+            // Un objet partagé (déduplication) reste lié à son chemin d'origine : le
+            // re-chiffrer rendrait les autres entrées illisibles. Une clé aléatoire aussi :
+            // renvoyer l'objet trahirait le renommage
+            if index.blob_link(file_id)?.is_some() || index.object_shared(file_id)? {
+                return Ok(());
+            }
+            match (index.get(file_id)?, index.bound_path(file_id)?) {
+                (Some(meta), Some(bound_path)) => (meta, bound_path),
+                // Supprimé, mis à la corbeille ou déjà re-chiffré entre-temps
+                _ => return Ok(()),
+            }
+        };
+        let encrypted = self.fetch_blob(file_id).await?;
+        let aether_file = AetherFile::from_bytes(&encrypted)?;
+        let plaintext = self.decrypt_bound(file_id, &aether_file, &meta.logical_path)?;
+        let mime_type = mime::detect(&plaintext[..plaintext.len().min(mime::SNIFF_LEN)], &meta.logical_path);
+        let encoding = policies::effective(&self.open_index()?, &meta.logical_path)?.encoding(mime_type, plaintext.len() as u64);
+        let rebound = storage::encrypt_file_with_uuid_encoded(
+            &self.master_key,
+            &plaintext,
+            &meta.logical_path,
+            aether_file.header.uuid,
+            encoding,
+        )?;
+        drop(plaintext);
+        let blob = rebound.to_bytes();
+        let etag = self.remote.upload_file(file_id, &blob).await?;
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.put(file_id, &blob) {
+                log::warn!("Failed to refresh cached blob {}: {}", file_id, e);
+                cache.remove(file_id);
+            }
+        }
+        let mut index = self.open_index()?;
+        index.clear_bound_path(file_id)?;
+        index.record_etag(file_id, &etag)?;
+        log::info!("Rebound {} from {} to {}", file_id, bound_path, meta.logical_path);
+        Ok(())
+    }
+
+    /// Renomme un fichier. Retourne le FileId de la nouvelle version.
+    ///
+    /// La nouvelle entrée réutilise l'objet existant (déduplication, déchiffré sous son chemin
+    /// d'origine) ; sans empreinte connue, le contenu est re-chiffré sous un nouvel UUID puis
+    /// envoyé. L'ancienne version part à la corbeille, d'où elle reste restaurable.
+    pub async fn rename(&self, file_id: &FileId, new_logical_path: &str) -> Result<FileId, VaultError> {
+        self.rename_job(file_id, new_logical_path, &Job::detached(RENAME_STEPS)).await
+    }
+
+    /// Comme [`Vault::rename`], étape par étape ([`RENAME_STEPS`]) et annulable.
+    ///
+    /// Une annulation constatée après l'envoi retire la nouvelle entrée (et son objet, s'il
+    /// n'est pas partagé) : l'ancien fichier n'a pas bougé. Si la destination était occupée,
+    /// l'envoi a remplacé un fichier et le renommage va à son terme. La mise à la corbeille,
+    /// une seule écriture de l'index, n'est pas interrompue.
+    ///
+    /// Si l'entrée est modifiée par ailleurs pendant le renommage, elle reste telle quelle
+    /// et la copie est retirée comme pour une annulation (`Conflict`).
+    pub async fn rename_job(&self, file_id: &FileId, new_logical_path: &str, job: &Job<'_>) -> Result<FileId, VaultError> {
+        let result = self.rename_steps(file_id, new_logical_path, job).await;
+        match &result {
+            Ok(_) => job.completed(),
+            Err(VaultError::Cancelled) => job.cancelled(),
+            Err(_) => job.failed(),
+        }
+        result
+    }
+
+    async fn rename_steps(&self, file_id: &FileId, new_logical_path: &str, job: &Job<'_>) -> Result<FileId, VaultError> {
+        if !job.step(0) {
+            return Err(VaultError::Cancelled);
+        }
+        // L'ancienne entrée ne part à la corbeille que si personne ne l'a touchée entre-temps
+        let revision = self
+            .open_index()?
+            .revision(file_id)?
+            .ok_or_else(|| VaultError::NotFound(file_id.clone()))?;
+        let plaintext = self.get(file_id).await?;
+
+        if !job.step(1) {
+            return Err(VaultError::Cancelled);
+        }
+        let replaces = !self.open_index()?.files_at_path(new_logical_path)?.is_empty();
+        let new_file_id = self.put(new_logical_path, &plaintext).await?;
+        drop(plaintext);
+
+        if !job.step(2) && !replaces {
+            self.delete_remote(&new_file_id).await?;
+            self.open_index()?.remove(&new_file_id)?;
+            log::info!("Rename of {} cancelled: {} rolled back", file_id, new_file_id);
+            return Err(VaultError::Cancelled);
+        }
+        if !self.open_index()?.move_to_trash_if_revision(file_id, revision)? {
+            // Renommé ou modifié ailleurs pendant l'envoi : la copie est retirée, sauf si elle
+            // a remplacé un fichier
+            if !replaces {
+                self.delete_remote(&new_file_id).await?;
+                self.open_index()?.remove(&new_file_id)?;
+            }
+            log::warn!("Rename of {} abandoned: entry modified concurrently", file_id);
+            return Err(VaultError::Conflict(file_id.clone()));
+        }
+        Ok(new_file_id)
+    }
+}
+
+/// Refuse un envoi hors budget ; un seuil d'alerte franchi est seulement journalisé.
+/// Chemin logique d'une écriture : forme canonique (NFC), refusé s'il ne pourrait pas être
+/// restauré sur tous les systèmes. C'est aussi celui auquel le contenu est lié par l'AAD.
+fn canonical_file_path(logical_path: &str) -> Result<String, VaultError> {
+    let normalized = path::normalize(logical_path);
+    path::validate_file_path(&normalized).map_err(VaultError::InvalidPath)?;
+    Ok(normalized)
+}
+
+/// Échec de réception d'un objet : l'écriture locale est rapportée avec le chemin de la
+/// destination par l'appelant.
+enum ReceiveError {
+    Io(std::io::Error),
+    Vault(VaultError),
+}
+
+impl From<std::io::Error> for ReceiveError {
+    fn from(e: std::io::Error) -> Self {
+        ReceiveError::Io(e)
+    }
+}
+
+impl From<VaultError> for ReceiveError {
+    fn from(e: VaultError) -> Self {
+        ReceiveError::Vault(e)
+    }
+}
+
+/// Fichier partiel de [`Vault::save_to`] : reçoit l'objet dans l'ordre, garde l'en-tête et
+/// le tag en mémoire et n'écrit sur disque que le corps chiffré (l'objet entier en mode
+/// `raw`). Supprimé s'il n'est pas renommé en destination.
+struct CiphertextSink {
+    path: PathBuf,
+    file: std::fs::File,
+    header: Vec<u8>,
+    tag: Vec<u8>,
+    received: u64,
+    raw: bool,
+    persisted: bool,
+}
+
+impl CiphertextSink {
+    fn create(path: PathBuf, raw: bool) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+        Ok(Self { path, file, header: Vec::new(), tag: Vec::new(), received: 0, raw, persisted: false })
+    }
+
+    /// Ajoute les octets suivants d'un objet de `total` octets.
+    fn absorb(&mut self, data: &[u8], total: u64) -> std::io::Result<()> {
+        use std::io::Write;
+        if self.raw {
+            let header_end = (AetherFile::HEADER_LEN as u64).saturating_sub(self.received) as usize;
+            self.header.extend_from_slice(&data[..header_end.min(data.len())]);
+            self.file.write_all(data)?;
+            self.received += data.len() as u64;
+            return Ok(());
+        }
+        let body_end = total.saturating_sub(storage::TAG_LEN as u64).max(AetherFile::HEADER_LEN as u64);
+        let offset = |boundary: u64| (boundary.saturating_sub(self.received) as usize).min(data.len());
+        let (header_end, tag_start) = (offset(AetherFile::HEADER_LEN as u64), offset(body_end));
+        self.header.extend_from_slice(&data[..header_end]);
+        self.file.write_all(&data[header_end..tag_start.max(header_end)])?;
+        self.tag.extend_from_slice(&data[tag_start.max(header_end)..]);
+        self.received += data.len() as u64;
+        Ok(())
+    }
+
+    fn persist(mut self, dest: &Path) -> std::io::Result<()> {
+        self.file.sync_all()?;
+        std::fs::rename(&self.path, dest)?;
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for CiphertextSink {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+pub fn check_quota(index: &SqlCipherIndex, requested_bytes: u64) -> Result<QuotaCheck, VaultError> {
+    let verdict = quota::check(index, requested_bytes)?;
+    match verdict {
+        QuotaCheck::Exceeded { used_bytes, limit_bytes } => {
+            Err(VaultError::QuotaExceeded { used_bytes, limit_bytes, requested_bytes })
+        }
+        QuotaCheck::Warning { threshold_percent } => {
+            log::warn!("Storage quota threshold reached: {}%", threshold_percent);
+            Ok(verdict)
+        }
+        QuotaCheck::Within => Ok(verdict),
+    }
+}
+
+/// Enregistre le type MIME d'un fichier et, pour une photo, sa date de prise de vue et ses
+/// dimensions ; `head` est le début du clair (au moins [`photos::exif::HEAD_LEN`] octets si
+/// le fichier est assez long).
+pub fn record_content_type(
+    index: &mut SqlCipherIndex,
+    file_id: &FileId,
+    logical_path: &str,
+    head: &[u8],
+) -> rusqlite::Result<()> {
+    let mime_type = mime::detect(&head[..head.len().min(mime::SNIFF_LEN)], logical_path);
+    index.set_mime_type(file_id, mime_type)?;
+    if mime::Category::of(mime_type) == mime::Category::Images {
+        if let Some(info) = photos::exif::read(head) {
+            index.set_photo_info(file_id, &info)?;
+        }
+    }
+    Ok(())
+}
+
+/// Premiers octets d'un fichier local, pour [`record_content_type`].
+fn read_head(source: &Path) -> Result<Vec<u8>, VaultError> {
+    use std::io::Read;
+    let mut head = Vec::new();
+    std::fs::File::open(source)
+        .and_then(|file| file.take(photos::exif::HEAD_LEN as u64).read_to_end(&mut head))
+        .map_err(|e| VaultError::Storage(StorageError::Io(format!("{}: {}", source.display(), e))))?;
+    Ok(head)
+}
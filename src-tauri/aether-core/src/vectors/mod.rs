@@ -0,0 +1,200 @@
+//! Vecteurs de test du format Aether V1 et de la MKEK.
+//!
+//! Clés, sels et nonces sont figés : la suite produite est identique à chaque génération.
+//! Un client alternatif (mobile, web) la rejoue pour vérifier qu'il dérive les mêmes clés
+//! et produit les mêmes blobs, octet pour octet. Chaque valeur intermédiaire (FileKey, AAD,
+//! engagement) est fournie pour localiser une divergence.
+//!
+//! Disponible uniquement avec la fonctionnalité `test-vectors` : les chemins de chiffrement
+//! à nonce imposé n'existent pas dans l'application. Voir le binaire `aether-vectors`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{mkek, CryptoCore, CryptoError, Kek, MasterKey, PasswordSecret, ARGON2_PARAMS};
+use crate::storage::{self, StorageError};
+
+/// Version de la suite : à incrémenter si sa structure change (pas si le format change,
+/// un nouveau format aurait ses propres vecteurs).
+pub const SUITE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVectors {
+    pub suite: String,
+    pub version: u32,
+    pub kek_derivation: Vec<KekVector>,
+    pub mkek: Vec<MkekVector>,
+    pub aether_files: Vec<AetherFileVector>,
+}
+
+/// Argon2id (v0x13) : mot de passe + sel -> KEK.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KekVector {
+    pub description: String,
+    /// Mot de passe en UTF-8.
+    pub password: String,
+    pub salt: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    pub kek: String,
+}
+
+/// XChaCha20-Poly1305(KEK, nonce, MasterKey, AAD) -> MKEK ; avec l'empreinte publique du coffre.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MkekVector {
+    pub description: String,
+    pub kek: String,
+    pub master_key: String,
+    pub nonce: String,
+    pub aad: String,
+    /// Ciphertext suivi du tag Poly1305.
+    pub payload: String,
+    pub vault_fingerprint: String,
+}
+
+/// Blob Aether V1 complet, avec ses valeurs intermédiaires.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AetherFileVector {
+    pub description: String,
+    pub master_key: String,
+    pub logical_path: String,
+    pub plaintext: String,
+    pub uuid: String,
+    pub salt: String,
+    pub nonce: String,
+    /// HKDF-SHA256(sel, MasterKey, info de format).
+    pub file_key: String,
+    pub aad: String,
+    pub commitment: String,
+    /// Le blob tel que stocké : en-tête, longueur, ciphertext et tag.
+    pub blob: String,
+}
+
+#[derive(Debug)]
+pub enum VectorError {
+    Crypto(CryptoError),
+    Storage(StorageError),
+}
+
+impl std::fmt::Display for VectorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VectorError::Crypto(e) => write!(f, "Crypto error: {}", e),
+            VectorError::Storage(e) => write!(f, "Storage error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for VectorError {}
+
+impl From<CryptoError> for VectorError {
+    fn from(e: CryptoError) -> Self {
+        VectorError::Crypto(e)
+    }
+}
+
+impl From<StorageError> for VectorError {
+    fn from(e: StorageError) -> Self {
+        VectorError::Storage(e)
+    }
+}
+
+/// Octets `start, start + 1, …` : des entrées fixes faciles à recopier ailleurs.
+fn counting<const N: usize>(start: u8) -> [u8; N] {
+    std::array::from_fn(|i| start.wrapping_add(i as u8))
+}
+
+/// Génère la suite complète (une dérivation Argon2id : compter une seconde environ).
+pub fn generate() -> Result<TestVectors, VectorError> {
+    let password = "correct horse battery staple";
+    let password_salt: [u8; 16] = counting(0x10);
+    let kek = CryptoCore::default().derive_kek(&PasswordSecret::new(password), &password_salt)?;
+    let (memory_kib, iterations, parallelism) = ARGON2_PARAMS;
+    let kek_derivation = vec![KekVector {
+        description: "Paramètres Argon2id du coffre".to_string(),
+        password: password.to_string(),
+        salt: hex::encode(password_salt),
+        memory_kib,
+        iterations,
+        parallelism,
+        kek: hex::encode(kek.as_bytes()),
+    }];
+
+    let master_key = MasterKey::from_vec(counting::<32>(0x00).to_vec());
+    let mkek = vec![
+        mkek_vector("MasterKey scellée par la KEK dérivée ci-dessus", &kek, &master_key, counting(0x40))?,
+        mkek_vector(
+            "KEK conservée par l'appareil (sans Argon2)",
+            &Kek::from_bytes(&[0xa5; 32]),
+            &MasterKey::from_vec(vec![0xff; 32]),
+            [0u8; 24],
+        )?,
+    ];
+
+    let binary: Vec<u8> = (0..=255u8).collect();
+    let cases: [(&str, &str, &[u8]); 4] = [
+        ("Fichier vide", "/vide.txt", b""),
+        ("Texte court", "/Documents/notes.txt", b"Hello, Aether Drive!"),
+        ("Chemin Unicode (NFC)", "/Été/café.md", "# Carnet\n\nPremière entrée.\n".as_bytes()),
+        ("Les 256 valeurs d'octet", "/bin/octets.bin", &binary),
+    ];
+    let aether_files = cases
+        .iter()
+        .enumerate()
+        .map(|(i, (description, logical_path, plaintext))| {
+            let seed = 0x60 + 0x10 * i as u8;
+            aether_file_vector(description, &master_key, logical_path, plaintext, seed)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(TestVectors {
+        suite: "aether-drive".to_string(),
+        version: SUITE_VERSION,
+        kek_derivation,
+        mkek,
+        aether_files,
+    })
+}
+
+fn mkek_vector(
+    description: &str,
+    kek: &Kek,
+    master_key: &MasterKey,
+    nonce: [u8; 24],
+) -> Result<MkekVector, VectorError> {
+    let sealed = mkek::encrypt_master_key_with_nonce(kek, master_key, nonce)?;
+    Ok(MkekVector {
+        description: description.to_string(),
+        kek: hex::encode(kek.as_bytes()),
+        master_key: hex::encode(master_key.as_bytes()),
+        nonce: hex::encode(sealed.nonce),
+        aad: hex::encode(mkek::MKEK_AAD),
+        payload: hex::encode(&sealed.payload),
+        vault_fingerprint: master_key.fingerprint(),
+    })
+}
+
+fn aether_file_vector(
+    description: &str,
+    master_key: &MasterKey,
+    logical_path: &str,
+    plaintext: &[u8],
+    seed: u8,
+) -> Result<AetherFileVector, VectorError> {
+    let (uuid, salt, nonce) = (counting::<16>(seed), counting::<32>(seed ^ 0x80), counting::<24>(seed ^ 0x0f));
+    let file = storage::encrypt_file_deterministic(master_key, plaintext, logical_path, uuid, salt, nonce)?;
+    let file_key = storage::derive_file_key(master_key, &salt)?;
+    Ok(AetherFileVector {
+        description: description.to_string(),
+        master_key: hex::encode(master_key.as_bytes()),
+        logical_path: logical_path.to_string(),
+        plaintext: hex::encode(plaintext),
+        uuid: hex::encode(uuid),
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce),
+        file_key: hex::encode(file_key.as_bytes()),
+        aad: hex::encode(storage::build_aad(logical_path)),
+        commitment: hex::encode(storage::compute_commitment(&file.header, &file_key)),
+        blob: hex::encode(file.to_bytes()),
+    })
+}
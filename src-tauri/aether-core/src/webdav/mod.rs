@@ -0,0 +1,431 @@
+use bytes::Bytes;
+use dav_server::davpath::DavPath;
+use dav_server::fakels::FakeLs;
+use dav_server::fs::{
+    DavDirEntry, DavFile, DavFileSystem, DavMetaData, FsError, FsFuture, FsResult, FsStream,
+    OpenOptions, ReadDirMeta,
+};
+use dav_server::{DavHandler, DavMethodSet};
+use futures_util::{stream, FutureExt};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use rand::{rngs::OsRng, RngCore};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::io::SeekFrom;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+use crate::index::{FileId, FileMetadata};
+use crate::storage::AetherFile;
+use crate::vault::Vault;
+
+/// Utilisateur HTTP Basic attendu par le serveur (le mot de passe est aléatoire par session).
+pub const WEBDAV_USER: &str = "aether";
+
+/// Vue arborescente de l'index, recalculée à chaque requête.
+#[derive(Debug, Default)]
+pub struct VaultTree {
+    files: HashMap<String, (FileId, u64)>,
+    /// Dossier → enfants (nom → est un dossier). La racine est "/".
+    dirs: HashMap<String, BTreeMap<String, bool>>,
+}
+
+impl VaultTree {
+    /// `sizes` : tailles en clair relevées au chiffrement ; une entrée sans relevé est en V1.
+    pub fn build(entries: &[(FileId, FileMetadata)], sizes: &HashMap<FileId, u64>) -> Self {
+        let mut tree = VaultTree::default();
+        tree.dirs.insert("/".to_string(), BTreeMap::new());
+        for (file_id, meta) in entries {
+            let is_folder = meta.logical_path.ends_with('/') || meta.encrypted_size == 0;
+            let path = normalize(&meta.logical_path);
+            if path == "/" {
+                continue;
+            }
+            if is_folder {
+                tree.add_dir(&path);
+            } else {
+                tree.add_child(&path, false);
+                let size = sizes
+                    .get(file_id)
+                    .copied()
+                    .unwrap_or_else(|| AetherFile::v1_plaintext_len(meta.encrypted_size));
+                tree.files.insert(path, (file_id.clone(), size));
+            }
+        }
+        tree
+    }
+
+    fn add_dir(&mut self, path: &str) {
+        if self.dirs.contains_key(path) {
+            return;
+        }
+        self.dirs.insert(path.to_string(), BTreeMap::new());
+        self.add_child(path, true);
+    }
+
+    fn add_child(&mut self, path: &str, is_dir: bool) {
+        let (parent, name) = split_parent(path);
+        self.add_dir(&parent);
+        if let Some(children) = self.dirs.get_mut(&parent) {
+            children.insert(name, is_dir);
+        }
+    }
+
+    pub fn file(&self, path: &str) -> Option<&(FileId, u64)> {
+        self.files.get(&normalize(path))
+    }
+
+    pub fn children(&self, path: &str) -> Option<&BTreeMap<String, bool>> {
+        self.dirs.get(&normalize(path))
+    }
+}
+
+/// "/a/b/" → "/a/b" ; "" → "/".
+fn normalize(path: &str) -> String {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        "/".to_string()
+    } else if trimmed.starts_with('/') {
+        trimmed.to_string()
+    } else {
+        format!("/{}", trimmed)
+    }
+}
+
+fn split_parent(path: &str) -> (String, String) {
+    match path.rfind('/') {
+        Some(0) => ("/".to_string(), path[1..].to_string()),
+        Some(i) => (path[..i].to_string(), path[i + 1..].to_string()),
+        None => ("/".to_string(), path.to_string()),
+    }
+}
+
+fn dav_path_str(path: &DavPath) -> FsResult<String> {
+    String::from_utf8(path.as_bytes().to_vec())
+        .map(|p| normalize(&p))
+        .map_err(|_| FsError::NotFound)
+}
+
+#[derive(Debug, Clone)]
+struct VaultMeta {
+    len: u64,
+    is_dir: bool,
+}
+
+impl DavMetaData for VaultMeta {
+    fn len(&self) -> u64 {
+        self.len
+    }
+
+    fn modified(&self) -> FsResult<SystemTime> {
+        // L'index ne conserve pas de date de modification.
+        Ok(SystemTime::UNIX_EPOCH)
+    }
+
+    fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+}
+
+struct VaultDirEntry {
+    name: String,
+    meta: VaultMeta,
+}
+
+impl DavDirEntry for VaultDirEntry {
+    fn name(&self) -> Vec<u8> {
+        self.name.as_bytes().to_vec()
+    }
+
+    fn metadata(&self) -> FsFuture<'_, Box<dyn DavMetaData>> {
+        let meta = self.meta.clone();
+        async move { Ok(Box::new(meta) as Box<dyn DavMetaData>) }.boxed()
+    }
+}
+
+/// Fichier déchiffré servi en lecture seule.
+#[derive(Debug)]
+struct VaultFile {
+    data: Bytes,
+    pos: usize,
+}
+
+impl DavFile for VaultFile {
+    fn metadata(&mut self) -> FsFuture<'_, Box<dyn DavMetaData>> {
+        let meta = VaultMeta {
+            len: self.data.len() as u64,
+            is_dir: false,
+        };
+        async move { Ok(Box::new(meta) as Box<dyn DavMetaData>) }.boxed()
+    }
+
+    fn write_buf(&mut self, _buf: Box<dyn bytes::Buf + Send>) -> FsFuture<'_, ()> {
+        async { Err(FsError::Forbidden) }.boxed()
+    }
+
+    fn write_bytes(&mut self, _buf: Bytes) -> FsFuture<'_, ()> {
+        async { Err(FsError::Forbidden) }.boxed()
+    }
+
+    fn read_bytes(&mut self, count: usize) -> FsFuture<'_, Bytes> {
+        let end = (self.pos + count).min(self.data.len());
+        let chunk = self.data.slice(self.pos..end);
+        self.pos = end;
+        async move { Ok(chunk) }.boxed()
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> FsFuture<'_, u64> {
+        let len = self.data.len() as i64;
+        let target = match pos {
+            SeekFrom::Start(n) => n as i64,
+            SeekFrom::End(n) => len + n,
+            SeekFrom::Current(n) => self.pos as i64 + n,
+        };
+        let result = if (0..=len).contains(&target) {
+            self.pos = target as usize;
+            Ok(target as u64)
+        } else {
+            Err(FsError::GeneralFailure)
+        };
+        async move { result }.boxed()
+    }
+
+    fn flush(&mut self) -> FsFuture<'_, ()> {
+        async { Ok(()) }.boxed()
+    }
+}
+
+/// Système de fichiers WebDAV en lecture seule adossé au coffre.
+#[derive(Clone)]
+struct VaultFs {
+    vault: Arc<Vault>,
+}
+
+impl VaultFs {
+    fn tree(&self) -> FsResult<VaultTree> {
+        let listed = self
+            .vault
+            .list()
+            .and_then(|entries| Ok((entries, self.vault.open_index()?.plaintext_sizes()?)));
+        let (entries, sizes) = listed.map_err(|e| {
+            log::warn!("WebDAV: failed to list index: {}", e);
+            FsError::GeneralFailure
+        })?;
+        Ok(VaultTree::build(&entries, &sizes))
+    }
+}
+
+impl DavFileSystem for VaultFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<'a, Box<dyn DavFile>> {
+        async move {
+            if options.write || options.append || options.create || options.create_new {
+                return Err(FsError::Forbidden);
+            }
+            let path = dav_path_str(path)?;
+            let (file_id, _) = self.tree()?.file(&path).cloned().ok_or(FsError::NotFound)?;
+            let data = self.vault.get_with_path(&file_id, &path).await.map_err(|e| {
+                log::warn!("WebDAV: failed to read {}: {}", file_id, e);
+                FsError::GeneralFailure
+            })?;
+            Ok(Box::new(VaultFile {
+                data: Bytes::from(data),
+                pos: 0,
+            }) as Box<dyn DavFile>)
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        _meta: ReadDirMeta,
+    ) -> FsFuture<'a, FsStream<Box<dyn DavDirEntry>>> {
+        async move {
+            let path = dav_path_str(path)?;
+            let tree = self.tree()?;
+            let children = tree.children(&path).ok_or(FsError::NotFound)?;
+            let entries: Vec<FsResult<Box<dyn DavDirEntry>>> = children
+                .iter()
+                .map(|(name, is_dir)| {
+                    let child = if path == "/" {
+                        format!("/{}", name)
+                    } else {
+                        format!("{}/{}", path, name)
+                    };
+                    let len = if *is_dir {
+                        0
+                    } else {
+                        tree.file(&child).map(|(_, len)| *len).unwrap_or(0)
+                    };
+                    Ok(Box::new(VaultDirEntry {
+                        name: name.clone(),
+                        meta: VaultMeta { len, is_dir: *is_dir },
+                    }) as Box<dyn DavDirEntry>)
+                })
+                .collect();
+            Ok(Box::pin(stream::iter(entries)) as FsStream<Box<dyn DavDirEntry>>)
+        }
+        .boxed()
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<'a, Box<dyn DavMetaData>> {
+        async move {
+            let path = dav_path_str(path)?;
+            let tree = self.tree()?;
+            if tree.children(&path).is_some() {
+                return Ok(Box::new(VaultMeta { len: 0, is_dir: true }) as Box<dyn DavMetaData>);
+            }
+            let (_, len) = tree.file(&path).ok_or(FsError::NotFound)?;
+            Ok(Box::new(VaultMeta { len: *len, is_dir: false }) as Box<dyn DavMetaData>)
+        }
+        .boxed()
+    }
+}
+
+/// Serveur WebDAV en cours d'exécution ; l'arrêter en le droppant ou via `stop`.
+pub struct WebDavServer {
+    pub addr: SocketAddr,
+    pub password: String,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl WebDavServer {
+    /// Démarre le serveur sur 127.0.0.1:`port` (0 = port libre choisi par l'OS).
+    ///
+    /// Le serveur est en lecture seule et protégé par HTTP Basic
+    /// (`WEBDAV_USER` + mot de passe aléatoire renvoyé dans `password`).
+    pub async fn start(vault: Arc<Vault>, port: u16) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+        let addr = listener.local_addr()?;
+
+        let mut secret = [0u8; 16];
+        OsRng.fill_bytes(&mut secret);
+        let password = hex::encode(secret);
+        let expected_auth = Arc::new(basic_auth_header(WEBDAV_USER, &password));
+
+        let handler = DavHandler::builder()
+            .filesystem(Box::new(VaultFs { vault }))
+            .locksystem(FakeLs::new())
+            .methods(DavMethodSet::WEBDAV_RO)
+            .build_handler();
+
+        let (tx, mut rx) = oneshot::channel();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    accepted = listener.accept() => {
+                        let Ok((stream, _)) = accepted else { continue };
+                        let handler = handler.clone();
+                        let expected_auth = Arc::clone(&expected_auth);
+                        tokio::spawn(async move {
+                            let service = service_fn(move |req: hyper::Request<hyper::body::Incoming>| {
+                                let handler = handler.clone();
+                                let expected_auth = Arc::clone(&expected_auth);
+                                async move {
+                                    let authorized = req
+                                        .headers()
+                                        .get(hyper::header::AUTHORIZATION)
+                                        .is_some_and(|v| v.as_bytes() == expected_auth.as_bytes());
+                                    if !authorized {
+                                        return Ok::<_, Infallible>(unauthorized());
+                                    }
+                                    Ok(handler.handle(req).await)
+                                }
+                            });
+                            if let Err(e) = http1::Builder::new()
+                                .serve_connection(TokioIo::new(stream), service)
+                                .await
+                            {
+                                log::debug!("WebDAV connection closed: {}", e);
+                            }
+                        });
+                    }
+                    _ = &mut rx => break,
+                }
+            }
+            log::info!("WebDAV server stopped");
+        });
+
+        log::info!("WebDAV server listening on http://{}", addr);
+        Ok(Self {
+            addr,
+            password,
+            shutdown: Some(tx),
+        })
+    }
+
+    pub fn stop(mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+impl Drop for WebDavServer {
+    fn drop(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+fn unauthorized() -> Response<dav_server::body::Body> {
+    let mut response = Response::new(dav_server::body::Body::empty());
+    *response.status_mut() = StatusCode::UNAUTHORIZED;
+    response.headers_mut().insert(
+        hyper::header::WWW_AUTHENTICATE,
+        hyper::header::HeaderValue::from_static("Basic realm=\"Aether Drive\""),
+    );
+    response
+}
+
+fn basic_auth_header(user: &str, password: &str) -> String {
+    use base64::Engine;
+    format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, password))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, path: &str, size: u64) -> (FileId, FileMetadata) {
+        (
+            id.to_string(),
+            FileMetadata {
+                logical_path: path.to_string(),
+                encrypted_size: size,
+            },
+        )
+    }
+
+    #[test]
+    fn tree_exposes_implicit_and_empty_folders() {
+        let sizes = HashMap::from([("c".to_string(), 4096)]);
+        let tree = VaultTree::build(
+            &[
+                entry("a", "/docs/report.pdf", AetherFile::OVERHEAD + 10),
+                entry("b", "/empty/", 0),
+                entry("c", "/docs/app.log", 300),
+            ],
+            &sizes,
+        );
+
+        let root = tree.children("/").unwrap();
+        assert_eq!(root.get("docs"), Some(&true));
+        assert_eq!(root.get("empty"), Some(&true));
+        assert_eq!(tree.children("/docs/").unwrap().get("report.pdf"), Some(&false));
+        assert_eq!(tree.file("/docs/report.pdf").unwrap(), &("a".to_string(), 10));
+        // Contenu compressé : la taille relevée au chiffrement l'emporte
+        assert_eq!(tree.file("/docs/app.log").unwrap(), &("c".to_string(), 4096));
+        assert!(tree.children("/empty").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,264 @@
+//! Espaces de travail : plusieurs coffres (index, file d'attente, réglages, stockage distant)
+//! sous la même MasterKey, sélectionnables à chaud.
+//!
+//! Le registre (`workspaces.json`) ne contient que des identifiants, des noms et l'empreinte
+//! publique du coffre ; les identifiants du stockage de chaque espace restent scellés dans
+//! le trousseau de l'OS.
+
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::export::write_atomic;
+
+/// Espace créé implicitement, qui reprend les emplacements d'avant les espaces de travail.
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// Fichier du registre, dans le dossier de données de l'application.
+pub const REGISTRY_FILE: &str = "workspaces.json";
+
+/// Dossier des espaces autres que celui par défaut (`workspaces/<id>/index.db`).
+const WORKSPACES_DIR: &str = "workspaces";
+const INDEX_FILE: &str = "index.db";
+const MAX_NAME_CHARS: usize = 60;
+
+#[derive(Debug)]
+pub enum WorkspaceError {
+    /// Opération refusée (`reason` : code stable).
+    Invalid(&'static str),
+    NotFound(String),
+    Io(std::io::Error),
+    Registry(String),
+}
+
+impl fmt::Display for WorkspaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkspaceError::Invalid(reason) => write!(f, "Invalid workspace operation: {}", reason),
+            WorkspaceError::NotFound(id) => write!(f, "Workspace not found: {}", id),
+            WorkspaceError::Io(e) => write!(f, "IO error: {}", e),
+            WorkspaceError::Registry(msg) => write!(f, "Workspace registry error: {}", msg),
+        }
+    }
+}
+
+impl From<std::io::Error> for WorkspaceError {
+    fn from(e: std::io::Error) -> Self {
+        WorkspaceError::Io(e)
+    }
+}
+
+impl std::error::Error for WorkspaceError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+}
+
+/// Liste des espaces et espace actif, persistés dans [`REGISTRY_FILE`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceRegistry {
+    workspaces: Vec<Workspace>,
+    active: String,
+    /// Copie en clair de l'empreinte du coffre (voir [`WorkspaceRegistry::vault_fingerprint`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vault_fingerprint: Option<String>,
+    #[serde(skip)]
+    data_dir: PathBuf,
+}
+
+impl WorkspaceRegistry {
+    /// Charge le registre de `data_dir`, ou le crée avec le seul espace par défaut.
+    pub fn load(data_dir: &Path) -> Result<Self, WorkspaceError> {
+        let path = data_dir.join(REGISTRY_FILE);
+        let mut registry = if path.exists() {
+            let raw = fs::read(&path)?;
+            serde_json::from_slice::<WorkspaceRegistry>(&raw).map_err(|e| WorkspaceError::Registry(e.to_string()))?
+        } else {
+            WorkspaceRegistry {
+                workspaces: vec![Workspace {
+                    id: DEFAULT_WORKSPACE.to_string(),
+                    name: "Personnel".to_string(),
+                }],
+                active: DEFAULT_WORKSPACE.to_string(),
+                vault_fingerprint: None,
+                data_dir: PathBuf::new(),
+            }
+        };
+        registry.data_dir = data_dir.to_path_buf();
+        // Registre modifié à la main : l'espace actif doit exister
+        if registry.get(&registry.active).is_none() {
+            registry.active = DEFAULT_WORKSPACE.to_string();
+        }
+        Ok(registry)
+    }
+
+    pub fn list(&self) -> &[Workspace] {
+        &self.workspaces
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Workspace> {
+        self.workspaces.iter().find(|workspace| workspace.id == id)
+    }
+
+    pub fn active(&self) -> &Workspace {
+        // `load` et `set_active` garantissent que l'espace actif existe
+        self.get(&self.active).unwrap_or(&self.workspaces[0])
+    }
+
+    /// Index de l'espace ; l'espace par défaut garde l'emplacement historique.
+    pub fn index_path(&self, id: &str) -> PathBuf {
+        if id == DEFAULT_WORKSPACE {
+            self.data_dir.join(INDEX_FILE)
+        } else {
+            self.data_dir.join(WORKSPACES_DIR).join(id).join(INDEX_FILE)
+        }
+    }
+
+    /// Empreinte du coffre dont les index de cet appareil dépendent (tous les espaces partagent
+    /// la MasterKey). Lisible sans la clé : elle explique un index que la clé n'ouvre pas.
+    pub fn vault_fingerprint(&self) -> Option<&str> {
+        self.vault_fingerprint.as_deref()
+    }
+
+    pub fn set_vault_fingerprint(&mut self, fingerprint: &str) -> Result<(), WorkspaceError> {
+        if self.vault_fingerprint.as_deref() == Some(fingerprint) {
+            return Ok(());
+        }
+        self.vault_fingerprint = Some(fingerprint.to_string());
+        self.save()
+    }
+
+    pub fn create(&mut self, name: &str) -> Result<Workspace, WorkspaceError> {
+        let name = validate_name(name)?;
+        if self.workspaces.iter().any(|workspace| workspace.name == name) {
+            return Err(WorkspaceError::Invalid("duplicate_name"));
+        }
+        let mut raw = [0u8; 8];
+        OsRng.fill_bytes(&mut raw);
+        let workspace = Workspace {
+            id: hex::encode(raw),
+            name,
+        };
+        self.workspaces.push(workspace.clone());
+        self.save()?;
+        Ok(workspace)
+    }
+
+    pub fn rename(&mut self, id: &str, name: &str) -> Result<Workspace, WorkspaceError> {
+        let name = validate_name(name)?;
+        if self.workspaces.iter().any(|workspace| workspace.name == name && workspace.id != id) {
+            return Err(WorkspaceError::Invalid("duplicate_name"));
+        }
+        let workspace = self
+            .workspaces
+            .iter_mut()
+            .find(|workspace| workspace.id == id)
+            .ok_or_else(|| WorkspaceError::NotFound(id.to_string()))?;
+        workspace.name = name;
+        let renamed = workspace.clone();
+        self.save()?;
+        Ok(renamed)
+    }
+
+    pub fn set_active(&mut self, id: &str) -> Result<Workspace, WorkspaceError> {
+        let workspace = self.get(id).cloned().ok_or_else(|| WorkspaceError::NotFound(id.to_string()))?;
+        self.active = workspace.id.clone();
+        self.save()?;
+        Ok(workspace)
+    }
+
+    /// Retire un espace et supprime ses données locales (index, cache, zone de transit).
+    ///
+    /// Ni l'espace par défaut ni l'espace actif ne peuvent être supprimés. Les objets
+    /// distants ne sont pas touchés.
+    pub fn remove(&mut self, id: &str) -> Result<Workspace, WorkspaceError> {
+        if id == DEFAULT_WORKSPACE {
+            return Err(WorkspaceError::Invalid("default_workspace"));
+        }
+        if id == self.active {
+            return Err(WorkspaceError::Invalid("active_workspace"));
+        }
+        let position = self
+            .workspaces
+            .iter()
+            .position(|workspace| workspace.id == id)
+            .ok_or_else(|| WorkspaceError::NotFound(id.to_string()))?;
+        let removed = self.workspaces.remove(position);
+        self.save()?;
+
+        let dir = self.data_dir.join(WORKSPACES_DIR).join(id);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        Ok(removed)
+    }
+
+    fn save(&self) -> Result<(), WorkspaceError> {
+        // La sérialisation d'une structure de chaînes ne peut pas échouer
+        let raw = serde_json::to_vec_pretty(self).unwrap_or_default();
+        write_atomic(&self.data_dir.join(REGISTRY_FILE), &raw).map_err(|e| WorkspaceError::Registry(e.to_string()))
+    }
+}
+
+fn validate_name(name: &str) -> Result<String, WorkspaceError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(WorkspaceError::Invalid("empty_name"));
+    }
+    if name.chars().count() > MAX_NAME_CHARS || name.chars().any(char::is_control) {
+        return Err(WorkspaceError::Invalid("invalid_name"));
+    }
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn registry_starts_with_the_default_workspace_at_the_legacy_path() {
+        let dir = TempDir::new().unwrap();
+        let registry = WorkspaceRegistry::load(dir.path()).unwrap();
+        assert_eq!(registry.active().id, DEFAULT_WORKSPACE);
+        assert_eq!(registry.index_path(DEFAULT_WORKSPACE), dir.path().join("index.db"));
+    }
+
+    #[test]
+    fn workspaces_are_persisted_switched_and_removed() {
+        let dir = TempDir::new().unwrap();
+        let mut registry = WorkspaceRegistry::load(dir.path()).unwrap();
+        let work = registry.create(" Travail (B2) ").unwrap();
+        assert_eq!(work.name, "Travail (B2)");
+        assert!(matches!(registry.create("Travail (B2)"), Err(WorkspaceError::Invalid("duplicate_name"))));
+        assert_ne!(registry.index_path(&work.id), registry.index_path(DEFAULT_WORKSPACE));
+
+        registry.set_active(&work.id).unwrap();
+        let reloaded = WorkspaceRegistry::load(dir.path()).unwrap();
+        assert_eq!(reloaded.active(), &work);
+        assert_eq!(reloaded.list().len(), 2);
+
+        let index_dir = registry.index_path(&work.id).parent().unwrap().to_path_buf();
+        fs::create_dir_all(&index_dir).unwrap();
+        assert!(matches!(registry.remove(&work.id), Err(WorkspaceError::Invalid("active_workspace"))));
+        assert!(matches!(registry.remove(DEFAULT_WORKSPACE), Err(WorkspaceError::Invalid("default_workspace"))));
+        registry.set_active(DEFAULT_WORKSPACE).unwrap();
+        registry.remove(&work.id).unwrap();
+        assert!(!index_dir.exists());
+        assert_eq!(WorkspaceRegistry::load(dir.path()).unwrap().list().len(), 1);
+    }
+
+    #[test]
+    fn vault_fingerprint_is_persisted_in_the_registry() {
+        let dir = TempDir::new().unwrap();
+        let mut registry = WorkspaceRegistry::load(dir.path()).unwrap();
+        assert_eq!(registry.vault_fingerprint(), None);
+        registry.set_vault_fingerprint("0123456789abcdef").unwrap();
+        let reloaded = WorkspaceRegistry::load(dir.path()).unwrap();
+        assert_eq!(reloaded.vault_fingerprint(), Some("0123456789abcdef"));
+    }
+}
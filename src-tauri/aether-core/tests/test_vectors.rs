@@ -0,0 +1,45 @@
+//! Vecteurs de test publiés (tests/vectors/aether-v1.json, feature `test-vectors`).
+//!
+//! Le fichier est ce que rejouent les autres clients : toute modification du format doit
+//! échouer ici avant d'atteindre un coffre. Régénérer avec
+//! `cargo run -p aether-core --features test-vectors --bin aether-vectors -- --out tests/vectors/aether-v1.json`.
+
+#![cfg(feature = "test-vectors")]
+
+use aether_core::crypto::{mkek, Kek, MasterKey, MkekCiphertext};
+use aether_core::storage::{self, AetherFile};
+use aether_core::vectors::{self, TestVectors};
+
+const PUBLISHED: &str = include_str!("vectors/aether-v1.json");
+
+fn bytes(hex_value: &str) -> Vec<u8> {
+    hex::decode(hex_value).unwrap()
+}
+
+#[test]
+fn published_vectors_match_this_implementation() {
+    let published: TestVectors = serde_json::from_str(PUBLISHED).unwrap();
+    assert_eq!(vectors::generate().unwrap(), published);
+}
+
+#[test]
+fn published_vectors_open_through_the_regular_code_paths() {
+    let published: TestVectors = serde_json::from_str(PUBLISHED).unwrap();
+
+    for vector in &published.mkek {
+        let sealed = MkekCiphertext::new(bytes(&vector.nonce).try_into().unwrap(), bytes(&vector.payload));
+        let master_key = mkek::decrypt_master_key(&Kek::from_bytes(&bytes(&vector.kek)), &sealed).unwrap();
+        assert_eq!(master_key.as_bytes(), bytes(&vector.master_key).as_slice());
+        assert_eq!(master_key.fingerprint(), vector.vault_fingerprint);
+    }
+
+    for vector in &published.aether_files {
+        let master_key = MasterKey::from_vec(bytes(&vector.master_key));
+        let file = AetherFile::from_bytes(&bytes(&vector.blob)).unwrap();
+        assert_eq!(file.header.commitment_hmac.to_vec(), bytes(&vector.commitment));
+        let plaintext = storage::decrypt_file(&master_key, &file, &vector.logical_path).unwrap();
+        assert_eq!(plaintext, bytes(&vector.plaintext), "{}", vector.description);
+        // L'AAD lie le blob à son chemin
+        assert!(storage::decrypt_file(&master_key, &file, "/autre.txt").is_err());
+    }
+}
@@ -0,0 +1,1486 @@
+//! Parcours complets du coffre contre le serveur S3 en mémoire (feature `mock-s3`).
+
+#![cfg(feature = "mock-s3")]
+
+use aether_core::cache::BlobCache;
+use aether_core::crypto::MasterKey;
+use aether_core::export::report::{self, SyncState};
+use aether_core::index::FileId;
+use aether_core::integrity;
+use aether_core::jobs::{CancelToken, Job, JobState};
+use aether_core::quota;
+use aether_core::search::{self, ContentIndex};
+use aether_core::snapshot;
+use aether_core::staging::Staging;
+use aether_core::storj::mock::MockS3Server;
+use aether_core::storj::StorjClient;
+use aether_core::vault::{Vault, VaultError, RENAME_STEPS};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+async fn vault_with_mock(dir: &TempDir) -> (Vault, MockS3Server) {
+    let server = MockS3Server::start("aether-test").await.unwrap();
+    let client = StorjClient::new(server.config()).await.unwrap();
+    let cache = BlobCache::new(dir.path().join("cache")).unwrap();
+    let staging = Staging::new(dir.path().join("tmp")).unwrap();
+    let vault = Vault::new(MasterKey::from_vec(vec![42u8; 32]), dir.path().join("index.db"), Arc::new(client))
+        .with_cache(cache)
+        .with_staging(staging);
+    (vault, server)
+}
+
+#[tokio::test]
+async fn upload_list_rename_trash_restore_purge() {
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+
+    // upload → list
+    let file_id = vault.put("/docs/report.txt", b"quarterly numbers").await.unwrap();
+    assert_eq!(server.object_keys(), vec![file_id.clone()]);
+    assert_eq!(vault.remote().list_files().await.unwrap(), vec![file_id.clone()]);
+    let listed = vault.list().unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].1.logical_path, "/docs/report.txt");
+    assert_eq!(listed[0].1.encrypted_size, server.object(&file_id).unwrap().len() as u64);
+    // Le blob a été envoyé depuis la zone de transit, qui est vide après coup
+    assert_eq!(std::fs::read_dir(dir.path().join("tmp")).unwrap().count(), 0);
+
+    // rename : nouvelle entrée qui réutilise l'objet (contenu identique), l'ancienne part à la corbeille
+    let renamed_id = vault.rename(&file_id, "/docs/report-2024.txt").await.unwrap();
+    assert_ne!(renamed_id, file_id);
+    assert_eq!(vault.get(&renamed_id).await.unwrap(), b"quarterly numbers");
+    let paths: Vec<String> = vault.list().unwrap().into_iter().map(|(_, m)| m.logical_path).collect();
+    assert_eq!(paths, vec!["/docs/report-2024.txt".to_string()]);
+    assert_eq!(server.object_keys(), vec![file_id.clone()]);
+
+    // trash → restore
+    vault.trash(&renamed_id).unwrap();
+    assert!(vault.list().unwrap().is_empty());
+    let restored = vault.restore(&renamed_id).await.unwrap();
+    assert_eq!(restored.logical_path, "/docs/report-2024.txt");
+    assert!(matches!(vault.restore(&renamed_id).await, Err(VaultError::NotFound(_))));
+
+    // purge de l'ancienne version : l'objet, encore lu par le fichier renommé, reste
+    vault.purge(&file_id).await.unwrap();
+    assert_eq!(server.object_keys(), vec![file_id.clone()]);
+    assert!(vault.open_index().unwrap().list_trash().unwrap().is_empty());
+
+    // Les objets restants sont cohérents avec l'index
+    let report = vault.reconcile().await.unwrap();
+    assert_eq!(report.remote_count, 1);
+    assert!(report.removed_orphans.is_empty());
+
+    // Purgé à son tour, le dernier fichier qui le lisait emporte l'objet distant
+    vault.trash(&renamed_id).unwrap();
+    vault.purge(&renamed_id).await.unwrap();
+    assert!(server.object_keys().is_empty());
+}
+
+#[tokio::test]
+async fn reconcile_drops_entries_missing_remotely() {
+    let dir = TempDir::new().unwrap();
+    let (vault, _server) = vault_with_mock(&dir).await;
+
+    let kept = vault.put("/a.txt", b"a").await.unwrap();
+    let lost = vault.put("/b.txt", b"b").await.unwrap();
+    vault.remote().delete_file(&lost).await.unwrap();
+
+    let report = vault.reconcile().await.unwrap();
+    assert_eq!(report.removed_orphans, vec![lost]);
+    assert_eq!(vault.list().unwrap().into_iter().map(|(id, _)| id).collect::<Vec<_>>(), vec![kept]);
+}
+
+#[tokio::test]
+async fn integrity_check_detects_tampered_remote_object() {
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+
+    let file_id = vault.put("/photo.jpg", &[7u8; 512]).await.unwrap();
+    assert!(integrity::check(&vault, 8).await.unwrap().is_clean());
+
+    // Objet tronqué côté serveur : l'en-tête ne correspond plus à la taille réelle
+    let mut blob = server.object(&file_id).unwrap();
+    blob.truncate(blob.len() - 10);
+    server.put_object(&file_id, blob);
+
+    let report = integrity::check(&vault, 8).await.unwrap();
+    assert_eq!(report.remote_sampled, 1);
+    assert_eq!(report.remote_drift.len(), 1);
+    assert_eq!(report.remote_drift[0].file_id, file_id);
+}
+
+#[tokio::test]
+async fn integrity_check_skips_objects_whose_etag_is_unchanged() {
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let uploaded = vault.put("/notes.txt", b"uploaded here").await.unwrap();
+    let foreign = vault.put("/other.txt", b"seen elsewhere").await.unwrap();
+    // ETag inconnu (objet venu d'un autre appareil) : l'en-tête est relu, puis l'ETag retenu
+    vault.open_index().unwrap().forget_etag(&foreign).unwrap();
+
+    let first = integrity::check(&vault, 8).await.unwrap();
+    assert!(first.is_clean());
+    assert_eq!((first.remote_sampled, first.remote_unchanged), (1, 1));
+    assert_eq!((server.reads(&uploaded), server.reads(&foreign)), (0, 1));
+
+    // Plus rien à relire tant que les objets n'ont pas changé
+    let second = integrity::check(&vault, 8).await.unwrap();
+    assert_eq!((second.remote_sampled, second.remote_unchanged), (0, 2));
+    assert_eq!(server.reads(&foreign), 1);
+
+    // Objet remplacé côté serveur : l'ETag ne correspond plus, il est relu et isolé
+    server.put_object(&uploaded, server.object(&foreign).unwrap());
+    let third = integrity::check(&vault, 8).await.unwrap();
+    assert_eq!((third.remote_sampled, third.remote_unchanged), (1, 1));
+    assert_eq!(third.remote_drift.len(), 1);
+    assert!(vault.open_index().unwrap().known_etag(&uploaded).unwrap().is_none());
+}
+
+#[tokio::test]
+async fn uploads_beyond_quota_are_refused_before_reaching_the_bucket() {
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+
+    let first = vault.put("/a.bin", &[1u8; 100]).await.unwrap();
+    let used = quota::used_bytes(&vault.open_index().unwrap()).unwrap();
+    quota::set_limit(&mut vault.open_index().unwrap(), Some(used + 50)).unwrap();
+
+    let refused = vault.put("/b.bin", &[2u8; 100]).await;
+    assert!(matches!(refused, Err(VaultError::QuotaExceeded { .. })));
+    assert_eq!(server.object_keys(), vec![first]);
+    assert_eq!(quota::status(&vault.open_index().unwrap()).unwrap().remaining_bytes, Some(50));
+}
+
+#[tokio::test]
+async fn inventory_report_reflects_remote_state_and_versions() {
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+
+    let old = vault.put("/notes.txt", b"v1").await.unwrap();
+    vault.trash(&old).unwrap();
+    let current = vault.put("/notes.txt", b"version 2").await.unwrap();
+    let lost = vault.put("/lost.bin", &[0u8; 64]).await.unwrap();
+    vault.remote().delete_file(&lost).await.unwrap();
+    let mut blob = server.object(&current).unwrap();
+    blob.push(0);
+    server.put_object(&current, blob);
+
+    let rows = report::build(&vault).await.unwrap();
+    let states: Vec<(&str, SyncState, usize, bool)> = rows
+        .iter()
+        .map(|r| (r.logical_path.as_str(), r.sync_state, r.versions, r.deleted_at.is_some()))
+        .collect();
+    assert_eq!(
+        states,
+        vec![
+            ("/lost.bin", SyncState::MissingRemote, 1, false),
+            ("/notes.txt", SyncState::SizeMismatch, 2, false),
+            ("/notes.txt", SyncState::Synced, 2, true),
+        ]
+    );
+    assert_eq!(rows[1].plaintext_size, 9);
+    assert!(rows[1].uploaded_at.is_some());
+    assert!(rows[0].uploaded_at.is_none());
+}
+
+#[tokio::test]
+async fn folder_rename_rewrites_subtree_and_rebinds_remote_content() {
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+
+    let nested = vault.put("/docs/2024/report.txt", b"numbers").await.unwrap();
+    let sibling = vault.put("/docs-old/readme.txt", b"keep").await.unwrap();
+
+    let report = vault.rename_folder("/docs", "/archive").unwrap();
+    assert_eq!(report.moved_entries, 1);
+    assert_eq!(report.pending_rebinds, 1);
+    let paths: Vec<String> = vault.list().unwrap().into_iter().map(|(_, m)| m.logical_path).collect();
+    assert_eq!(paths, vec!["/archive/2024/report.txt".to_string(), "/docs-old/readme.txt".to_string()]);
+    assert!(vault.open_index().unwrap().verify_integrity().unwrap());
+
+    // Lisible avant comme après le re-chiffrement, sous le même UUID
+    assert_eq!(vault.get(&nested).await.unwrap(), b"numbers");
+    let flushed = vault.flush_pending().await.unwrap();
+    assert_eq!((flushed.completed, flushed.remaining), (1, 0));
+    assert!(vault.open_index().unwrap().bound_path(&nested).unwrap().is_none());
+    assert_eq!(vault.get(&nested).await.unwrap(), b"numbers");
+    assert_eq!(server.object_keys().len(), 2);
+    assert_eq!(vault.get(&sibling).await.unwrap(), b"keep");
+
+    assert!(matches!(vault.rename_folder("/archive", "/archive/inner"), Err(VaultError::InvalidPath(_))));
+    assert!(matches!(vault.rename_folder("/archive", "/docs-old"), Err(VaultError::AlreadyExists(_))));
+    assert!(matches!(vault.rename_folder("/missing", "/elsewhere"), Err(VaultError::PathNotFound(_))));
+}
+
+#[tokio::test]
+async fn empty_trash_keeps_entries_whose_remote_deletion_is_not_verified() {
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+
+    let gone = vault.put("/old/a.txt", b"a").await.unwrap();
+    let stuck = vault.put("/old/b.txt", b"b").await.unwrap();
+    vault.trash(&gone).unwrap();
+    vault.trash(&stuck).unwrap();
+    server.retain_on_delete(&stuck);
+
+    let report = vault.empty_trash().await.unwrap();
+    assert_eq!(report.purged, vec![gone]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].file_id, stuck);
+    assert_eq!(report.failed[0].logical_path, "/old/b.txt");
+    assert_eq!(report.failed[0].reason, "still_present");
+
+    // L'élément non supprimé reste dans la corbeille, son objet distant aussi
+    let trash = vault.open_index().unwrap().list_trash().unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].0, stuck);
+    assert_eq!(server.object_keys(), vec![stuck]);
+}
+
+#[tokio::test]
+async fn verify_download_checks_local_copy_against_index_and_remote() {
+    use sha2::{Digest, Sha256};
+
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let file_id = vault.put("/backup/notes.txt", b"restored content").await.unwrap();
+    let altered = vault.put("/backup/other.txt", b"other content").await.unwrap();
+    let digest = |data: &[u8]| -> [u8; 32] { Sha256::digest(data).into() };
+
+    let ok = vault.verify_download(&file_id, &digest(b"restored content"), 16).await.unwrap();
+    assert!(ok.verified && ok.hash_recorded && ok.header_valid);
+
+    let corrupted = vault.verify_download(&file_id, &digest(b"restored c0ntent"), 16).await.unwrap();
+    assert!(!corrupted.verified);
+    assert!(corrupted.content_authentic && !corrupted.hash_matches);
+
+    // Objet distant altéré : la copie locale, même intacte, ne peut plus être prouvée
+    let mut blob = server.object(&altered).unwrap();
+    let last = blob.len() - 1;
+    blob[last] ^= 1;
+    server.put_object(&altered, blob);
+    let tampered = vault.verify_download(&altered, &digest(b"other content"), 13).await.unwrap();
+    assert!(tampered.header_valid && !tampered.content_authentic && !tampered.verified);
+
+    assert!(matches!(
+        vault.verify_download(&"00".repeat(16), &digest(b""), 0).await,
+        Err(VaultError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn cache_maintenance_evicts_cold_blobs_and_prewarms_frequent_files() {
+    use aether_core::cache::policy::{self, CachePolicy, PREWARM_MIN_HITS};
+
+    let dir = TempDir::new().unwrap();
+    let (vault, _server) = vault_with_mock(&dir).await;
+    let hot = vault.put("/hot.txt", &[1u8; 100]).await.unwrap();
+    let cold = vault.put("/cold.txt", &[2u8; 100]).await.unwrap();
+    vault.get(&hot).await.unwrap();
+    vault.get(&cold).await.unwrap();
+    for _ in 0..PREWARM_MIN_HITS {
+        vault.record_access(&hot).unwrap();
+    }
+
+    // Place pour un seul blob : le blob jamais ouvert part en premier
+    let blob_size = vault.cache().unwrap().entries().unwrap()[0].1;
+    let limit = CachePolicy { max_bytes: blob_size, prewarm_count: 5 };
+    policy::save(&mut vault.open_index().unwrap(), &limit).unwrap();
+    let report = vault.maintain_cache().await.unwrap();
+    assert_eq!((report.evicted, report.prewarmed), (1, 0));
+    assert!(vault.cache().unwrap().contains(&hot));
+    assert!(!vault.cache().unwrap().contains(&cold));
+    assert_eq!(report.cached_bytes, blob_size);
+
+    // Cache vidé : le fichier fréquent revient à la passe suivante
+    assert_eq!(vault.clear_cache().unwrap(), 1);
+    let report = vault.maintain_cache().await.unwrap();
+    assert_eq!((report.evicted, report.prewarmed), (0, 1));
+    assert!(vault.cache().unwrap().contains(&hot));
+}
+
+#[tokio::test]
+async fn rules_import_new_local_files_and_trash_old_ones() {
+    use aether_core::rules::{self, RuleAction};
+
+    let dir = TempDir::new().unwrap();
+    let (vault, _server) = vault_with_mock(&dir).await;
+    let scans = dir.path().join("scans");
+    std::fs::create_dir(&scans).unwrap();
+    std::fs::write(scans.join("a.pdf"), b"scan a").unwrap();
+    std::fs::write(scans.join(".partial"), b"hidden").unwrap();
+    let kept = vault.put("/archive/keep.txt", b"keep").await.unwrap();
+
+    let mut index = vault.open_index().unwrap();
+    let import = RuleAction::ImportFolder {
+        local_dir: scans.to_string_lossy().into_owned(),
+        vault_folder: "tmp-scans".to_string(),
+    };
+    rules::save(&mut index, None, "Scans", true, import).unwrap();
+    let cleanup = RuleAction::AutoTrash { vault_folder: "/tmp-scans".to_string(), older_than_days: 7 };
+    let cleanup = rules::save(&mut index, None, "Nettoyage", true, cleanup).unwrap();
+    drop(index);
+
+    // Un fichier tout juste écrit n'est pas encore stable
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let report = rules::run_all_at(&vault, now).await.unwrap();
+    assert_eq!((report.rules_run, report.uploaded, report.trashed), (2, 0, 0));
+
+    let later = now + rules::STABLE_AFTER_SECS;
+    let report = rules::run_all_at(&vault, later).await.unwrap();
+    assert_eq!((report.uploaded, report.trashed), (1, 0));
+    let (imported, _) = vault
+        .list()
+        .unwrap()
+        .into_iter()
+        .find(|(_, meta)| meta.logical_path == "/tmp-scans/a.pdf")
+        .unwrap();
+    assert_eq!(vault.get(&imported).await.unwrap(), b"scan a");
+
+    // Déjà importé : rien à renvoyer
+    let report = rules::run_all_at(&vault, later).await.unwrap();
+    assert_eq!(report.uploaded, 0);
+
+    // Huit jours plus tard, seul le dossier visé est vidé
+    let report = rules::run_all_at(&vault, later + 8 * 24 * 60 * 60).await.unwrap();
+    assert_eq!(report.trashed, 1);
+    let paths: Vec<String> = vault.list().unwrap().into_iter().map(|(_, m)| m.logical_path).collect();
+    assert_eq!(paths, vec!["/archive/keep.txt".to_string()]);
+    assert!(vault.list().unwrap().iter().any(|(id, _)| *id == kept));
+
+    let stored = rules::list(&vault.open_index().unwrap()).unwrap();
+    let cleanup = stored.iter().find(|rule| rule.id == cleanup.id).unwrap();
+    assert!(cleanup.last_run.is_some() && cleanup.last_error.is_none());
+}
+
+#[tokio::test]
+async fn index_backups_are_hidden_from_listings_pruned_and_restorable() {
+    use aether_core::backup::{self, BackupPolicy};
+
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let file_id = vault.put("/notes.txt", b"notes").await.unwrap();
+    backup::save_policy(&mut vault.open_index().unwrap(), &BackupPolicy { enabled: true, retention: 1 }).unwrap();
+
+    let first = backup::run(&vault).await.unwrap();
+    assert_eq!(first.pruned, 0);
+    // Les sauvegardes ne sont pas des fichiers du coffre
+    assert_eq!(vault.remote().list_files().await.unwrap(), vec![file_id.clone()]);
+    assert_eq!(vault.remote().list_objects().await.unwrap().len(), 1);
+    assert_eq!(server.object_keys().len(), 2);
+    // Rien de nouveau depuis la sauvegarde : pas d'envoi
+    assert!(backup::run_if_due(&vault).await.unwrap().is_none());
+
+    // Une sauvegarde plus récente (horodatée plus tard) évince la première
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let second = backup::run(&vault).await.unwrap();
+    assert_eq!(second.pruned, 1);
+    assert_eq!(backup::list(&vault).await.unwrap(), vec![second.backup.clone()]);
+
+    // Perte de l'index local : la sauvegarde le restaure
+    std::fs::remove_file(vault.index_path()).unwrap();
+    assert!(vault.list().unwrap().is_empty());
+    assert_eq!(backup::restore(&vault, &second.backup.key).await.unwrap(), 1);
+    assert_eq!(vault.get(&file_id).await.unwrap(), b"notes");
+    assert!(matches!(
+        backup::restore(&vault, &file_id).await,
+        Err(backup::BackupError::Invalid("not_a_backup"))
+    ));
+}
+
+#[tokio::test]
+async fn diagnostics_report_a_healthy_vault_and_a_wrong_key() {
+    use aether_core::diagnostics::{self, CheckStatus, DiagnosticsTarget};
+    use aether_core::keychain::KeychainStore;
+
+    let dir = TempDir::new().unwrap();
+    let (vault, _server) = vault_with_mock(&dir).await;
+    vault.put("/notes.txt", b"notes").await.unwrap();
+
+    let report = diagnostics::run(&DiagnosticsTarget {
+        master_key: Some(vault.master_key()),
+        index_path: vault.index_path(),
+        keychain: KeychainStore::storj(),
+        remote: Some(vault.remote()),
+        cache: vault.cache(),
+    })
+    .await;
+    for name in ["database", "merkle", "remote", "clock", "cache"] {
+        assert_eq!(report.status(name), Some(CheckStatus::Ok), "{}", name);
+    }
+
+    // Une mauvaise clé est signalée sans toucher à l'index
+    let wrong_key = MasterKey::from_vec(vec![7u8; 32]);
+    let report = diagnostics::run(&DiagnosticsTarget {
+        master_key: Some(&wrong_key),
+        index_path: vault.index_path(),
+        keychain: KeychainStore::storj(),
+        remote: None,
+        cache: None,
+    })
+    .await;
+    assert_eq!(report.status("database"), Some(CheckStatus::Failed));
+    assert_eq!(report.status("merkle"), Some(CheckStatus::Skipped));
+    assert_eq!(report.status("remote"), Some(CheckStatus::Skipped));
+    assert!(!report.is_healthy());
+    assert_eq!(vault.list().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn trashed_files_can_still_be_previewed_and_inspected() {
+    let dir = TempDir::new().unwrap();
+    let (vault, _server) = vault_with_mock(&dir).await;
+    let file_id = vault.put("/photos/old.txt", b"still on storj").await.unwrap();
+    let details = vault.encryption_details(&file_id).await.unwrap();
+
+    vault.trash(&file_id).unwrap();
+    assert!(matches!(vault.get(&file_id).await, Err(VaultError::NotFound(_))));
+    let meta = vault.find_including_trash(&file_id).unwrap();
+    assert_eq!(meta.logical_path, "/photos/old.txt");
+    assert_eq!(vault.get_with_path(&file_id, &meta.logical_path).await.unwrap(), b"still on storj");
+    assert_eq!(vault.encryption_details(&file_id).await.unwrap(), details);
+
+    vault.purge(&file_id).await.unwrap();
+    assert!(matches!(vault.find_including_trash(&file_id), Err(VaultError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn local_files_are_encrypted_from_disk_and_deduplicated_like_in_memory_uploads() {
+    let dir = TempDir::new().unwrap();
+    let (vault, _server) = vault_with_mock(&dir).await;
+    let plaintext: Vec<u8> = (0..3 * 1024 * 1024 + 5).map(|i| (i % 253) as u8).collect();
+    let source = dir.path().join("archive.tar");
+    std::fs::write(&source, &plaintext).unwrap();
+
+    let file_id = vault.put_path("/backups/archive.tar", &source).await.unwrap().file_id;
+    assert_eq!(vault.get(&file_id).await.unwrap(), plaintext);
+    let index = vault.open_index().unwrap();
+    let meta = index.get(&file_id).unwrap().unwrap();
+    assert_eq!(meta.encrypted_size, plaintext.len() as u64 + aether_core::storage::AetherFile::OVERHEAD);
+
+    // Même empreinte de contenu qu'un envoi depuis la mémoire : la copie réutilise l'objet
+    let copy_id = vault.put("/backups/copy.tar", &plaintext).await.unwrap();
+    assert_eq!(index.content_hash(&file_id).unwrap(), index.content_hash(&copy_id).unwrap());
+    assert_eq!(index.object_key(&copy_id).unwrap(), file_id);
+    // Renvoyer un fichier inchangé au même chemin ne crée ni objet ni version
+    let again = vault.put_path("/backups/archive.tar", &source).await.unwrap();
+    assert!(again.deduplicated);
+    assert_eq!(again.file_id, file_id);
+    assert!(vault.versions("/backups/archive.tar").unwrap().is_empty());
+    // La zone de transit est vidée après l'envoi
+    assert_eq!(std::fs::read_dir(dir.path().join("tmp")).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn aliases_follow_folder_renames_and_disappear_with_their_target() {
+    let dir = TempDir::new().unwrap();
+    let (vault, _server) = vault_with_mock(&dir).await;
+    let file_id = vault.put("/photos/beach.jpg", b"sand").await.unwrap();
+
+    let alias = vault.create_alias("/albums/summer/beach.jpg", &file_id).unwrap();
+    assert!(matches!(
+        vault.create_alias("/photos/beach.jpg", &file_id),
+        Err(VaultError::AlreadyExists(_))
+    ));
+    let (target_id, meta) = vault.resolve_alias(&alias.id).unwrap();
+    assert_eq!(target_id, file_id);
+    assert_eq!(vault.get_with_path(&target_id, &meta.logical_path).await.unwrap(), b"sand");
+
+    // Un dossier qui ne contient que des alias se renomme aussi ; le contenu n'est pas re-chiffré
+    let report = vault.rename_folder("/albums", "/souvenirs").unwrap();
+    assert_eq!((report.moved_entries, report.pending_rebinds), (1, 0));
+    let index = vault.open_index().unwrap();
+    assert_eq!(index.get_alias(&alias.id).unwrap().unwrap().logical_path, "/souvenirs/summer/beach.jpg");
+
+    vault.trash(&file_id).unwrap();
+    assert!(matches!(vault.resolve_alias(&alias.id), Err(VaultError::NotFound(id)) if id == file_id));
+    vault.purge(&file_id).await.unwrap();
+    assert!(index.list_aliases().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn snapshots_pin_purged_objects_until_they_are_deleted() {
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let kept = vault.put("/notes/kept.txt", b"alpha").await.unwrap();
+    let snap = snapshot::create(&mut vault.open_index().unwrap(), "avant").unwrap();
+    let later = vault.put("/notes/later.txt", b"beta").await.unwrap();
+
+    // Purgé, mais retenu à distance par l'instantané
+    vault.trash(&kept).unwrap();
+    vault.purge(&kept).await.unwrap();
+    assert!(server.object(&kept).is_some());
+
+    let diff = snapshot::diff(&vault.open_index().unwrap(), snap.id).unwrap();
+    assert_eq!(diff.added.iter().map(|e| &e.file_id).collect::<Vec<_>>(), vec![&later]);
+    assert_eq!(diff.removed.iter().map(|e| &e.file_id).collect::<Vec<_>>(), vec![&kept]);
+
+    let report = snapshot::restore(&mut vault.open_index().unwrap(), snap.id).unwrap();
+    assert_eq!((report.restored, report.trashed), (1, 1));
+    assert_eq!(vault.get(&kept).await.unwrap(), b"alpha");
+    assert!(snapshot::diff(&vault.open_index().unwrap(), snap.id).unwrap().is_empty());
+
+    // Le dernier instantané supprimé libère les objets qu'il était seul à retenir
+    vault.trash(&kept).unwrap();
+    vault.purge(&kept).await.unwrap();
+    assert_eq!(snapshot::delete(&vault, snap.id).await.unwrap(), 1);
+    assert!(server.object(&kept).is_none());
+    assert!(matches!(snapshot::delete(&vault, snap.id).await, Err(snapshot::SnapshotError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn reuploading_a_path_keeps_the_previous_file_as_a_restorable_version() {
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let first = vault.put("/docs/plan.txt", b"v1").await.unwrap();
+    let second = vault.put("/docs/plan.txt", b"v2").await.unwrap();
+
+    // Une seule entrée visible, l'ancienne devient une version
+    let listed: Vec<FileId> = vault.list().unwrap().into_iter().map(|(id, _)| id).collect();
+    assert_eq!(listed, vec![second.clone()]);
+    let versions = vault.versions("/docs/plan.txt").unwrap();
+    assert_eq!(versions.iter().map(|v| &v.id).collect::<Vec<_>>(), vec![&first]);
+    assert_eq!(versions[0].replaced_by, second);
+
+    vault.restore_version(&first).unwrap();
+    assert_eq!(vault.get(&first).await.unwrap(), b"v1");
+    let versions = vault.versions("/docs/plan.txt").unwrap();
+    assert_eq!(versions.iter().map(|v| &v.id).collect::<Vec<_>>(), vec![&second]);
+    assert!(matches!(vault.restore_version(&first), Err(VaultError::NotFound(_))));
+
+    // Au-delà de la rétention, les versions les plus anciennes sont supprimées du stockage
+    for round in 0..aether_core::vault::VERSION_RETENTION {
+        vault.put("/docs/plan.txt", format!("v{}", round + 3).as_bytes()).await.unwrap();
+    }
+    let versions = vault.versions("/docs/plan.txt").unwrap();
+    assert_eq!(versions.len(), aether_core::vault::VERSION_RETENTION);
+    assert!(versions.iter().all(|v| v.id != second));
+    assert!(server.object(&second).is_none());
+}
+
+#[tokio::test]
+async fn vaults_sharing_a_bucket_only_see_their_own_prefix() {
+    use aether_core::recovery;
+    use aether_core::storj::vault_prefix;
+
+    let dir = TempDir::new().unwrap();
+    let server = MockS3Server::start("aether-test").await.unwrap();
+    let key_a = MasterKey::from_vec(vec![1u8; 32]);
+    let prefix_a = vault_prefix(&key_a.fingerprint());
+
+    // Coffre d'avant les préfixes : ses objets sont à la racine du bucket
+    let root_client = StorjClient::new(server.config()).await.unwrap();
+    let legacy = Vault::new(key_a, dir.path().join("a.db"), Arc::new(root_client));
+    let file_a = legacy.put("/a.txt", b"alpha").await.unwrap();
+    drop(legacy);
+    server.put_object("foreign-object", b"not ours".to_vec());
+
+    let client_a = StorjClient::new(server.config()).await.unwrap().with_prefix(prefix_a.clone());
+    let vault_a = Vault::new(MasterKey::from_vec(vec![1u8; 32]), dir.path().join("a.db"), Arc::new(client_a));
+    let report = recovery::run(&vault_a, &dir.path().join("tmp")).await.unwrap();
+    assert_eq!(report.adopted_objects, 1);
+    assert!(server.object(&format!("{}{}", prefix_a, file_a)).is_some());
+    assert!(server.object(&file_a).is_none());
+    // Déplacé côté serveur : l'objet n'a pas transité par l'appareil
+    assert_eq!(server.reads(&file_a), 0);
+    assert_eq!(vault_a.get(&file_a).await.unwrap(), b"alpha");
+
+    let key_b = MasterKey::from_vec(vec![2u8; 32]);
+    let client_b = StorjClient::new(server.config()).await.unwrap().with_prefix(vault_prefix(&key_b.fingerprint()));
+    let vault_b = Vault::new(key_b, dir.path().join("b.db"), Arc::new(client_b));
+    let file_b = vault_b.put("/b.txt", b"beta").await.unwrap();
+
+    assert_eq!(vault_a.remote().list_files().await.unwrap(), vec![file_a.clone()]);
+    assert_eq!(vault_b.remote().list_files().await.unwrap(), vec![file_b]);
+    let objects: Vec<String> = vault_a.remote().list_objects().await.unwrap().into_iter().map(|o| o.key).collect();
+    assert_eq!(objects, vec![file_a]);
+    assert!(server.object("foreign-object").is_some());
+}
+
+#[tokio::test]
+async fn cancelling_a_rename_after_the_upload_rolls_it_back() {
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let file_id = vault.put("/docs/plan.txt", b"draft").await.unwrap();
+
+    // L'annulation arrive pendant l'envoi : elle est constatée avant la mise à la corbeille
+    let token = CancelToken::default();
+    let seen = std::sync::Mutex::new(Vec::new());
+    let job = Job::new(7, RENAME_STEPS, token.clone(), |progress| {
+        if progress.step == "upload" {
+            token.cancel();
+        }
+        seen.lock().unwrap().push((progress.step, progress.state));
+    });
+    let result = vault.rename_job(&file_id, "/docs/plan-v2.txt", &job).await;
+    drop(job);
+    assert!(matches!(result, Err(VaultError::Cancelled)));
+
+    let paths: Vec<String> = vault.list().unwrap().into_iter().map(|(_, m)| m.logical_path).collect();
+    assert_eq!(paths, vec!["/docs/plan.txt".to_string()]);
+    assert_eq!(vault.get(&file_id).await.unwrap(), b"draft");
+    assert_eq!(server.object_keys(), vec![file_id]);
+    assert_eq!(
+        seen.into_inner().unwrap(),
+        vec![("read", JobState::Running), ("upload", JobState::Running), ("trash", JobState::Cancelled)]
+    );
+}
+
+#[tokio::test]
+async fn content_search_indexes_uploads_once_enabled_and_forgets_purged_files() {
+    let dir = TempDir::new().unwrap();
+    let (vault, _server) = vault_with_mock(&dir).await;
+    let index_path = dir.path().join("index.db");
+    let master_key = [42u8; 32];
+
+    // Avant l'activation, rien n'est extrait
+    vault.put("/avant.txt", b"clause de confidentialite").await.unwrap();
+    assert!(!search::content_db_path(&index_path).exists());
+
+    search::set_enabled(&mut vault.open_index().unwrap(), &index_path, true).unwrap();
+    let local = dir.path().join("bail.md");
+    std::fs::write(&local, "Le preavis est de trois mois").unwrap();
+    let report = vault.put_path("/bail.md", &local).await.unwrap();
+
+    let mut content = ContentIndex::open(&index_path, &master_key).unwrap();
+    let hits = search::search(&vault.open_index().unwrap(), Some(&mut content), "preavis").unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].file_id, report.file_id);
+    assert!(search::search(&vault.open_index().unwrap(), Some(&mut content), "confidentialite").unwrap().is_empty());
+
+    // Le texte d'un fichier purgé est effacé avec lui
+    vault.trash(&report.file_id).unwrap();
+    vault.purge(&report.file_id).await.unwrap();
+    assert_eq!(content.len().unwrap(), 0);
+}
+
+#[tokio::test]
+async fn index_changes_reach_another_device_through_the_sync_journal() {
+    use aether_core::journal;
+
+    let dir = TempDir::new().unwrap();
+    let (laptop, server) = vault_with_mock(&dir).await;
+    let phone = Vault::new(
+        MasterKey::from_vec(vec![42u8; 32]),
+        dir.path().join("phone.db"),
+        Arc::new(StorjClient::new(server.config()).await.unwrap()),
+    );
+
+    let report_id = laptop.put("/docs/report.txt", b"quarterly numbers").await.unwrap();
+    let first = journal::sync(&laptop).await.unwrap();
+    assert_eq!((first.pushed, first.applied), (1, 0));
+    assert!(first.full_reconcile_needed);
+    // Les segments sont des objets internes, absents des listes de fichiers
+    assert_eq!(laptop.remote().list_files().await.unwrap(), vec![report_id.clone()]);
+
+    let received = journal::sync(&phone).await.unwrap();
+    assert_eq!((received.pushed, received.applied), (0, 1));
+    assert_eq!(phone.get(&report_id).await.unwrap(), b"quarterly numbers");
+
+    // Renommé sur le téléphone, mis à la corbeille en même temps sur l'ordinateur
+    let phone_report = phone.rename(&report_id, "/docs/report-2024.txt").await.unwrap();
+    laptop.trash(&report_id).unwrap();
+    assert_eq!(journal::sync(&phone).await.unwrap().pushed, 2);
+    let merged = journal::sync(&laptop).await.unwrap();
+    assert!(!merged.full_reconcile_needed);
+    assert_eq!(merged.conflicts.len(), 1);
+    assert_eq!(merged.conflicts[0].file_id, report_id);
+    let paths: Vec<String> = laptop.list().unwrap().into_iter().map(|(_, meta)| meta.logical_path).collect();
+    assert_eq!(paths, vec!["/docs/report-2024.txt".to_string()]);
+    assert_eq!(laptop.get(&phone_report).await.unwrap(), b"quarterly numbers");
+
+    // La mise à la corbeille de l'ordinateur porte sur une entrée déjà retirée du téléphone
+    assert_eq!(journal::sync(&phone).await.unwrap(), journal::JournalSyncReport::default());
+    assert_eq!(
+        phone.open_index().unwrap().computed_merkle_root().unwrap(),
+        laptop.open_index().unwrap().computed_merkle_root().unwrap()
+    );
+
+    // Chacun a publié sa racine signée : même frontière, même racine
+    let check = journal::roots::check_peers(&laptop).await.unwrap();
+    assert_eq!((check.peers, check.diverged.len(), check.untrusted.len()), (1, 0, 0));
+}
+
+#[tokio::test]
+async fn tampered_objects_are_quarantined_until_repaired() {
+    use aether_core::quarantine;
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+
+    // Contenu altéré côté serveur, sans copie locale : le téléchargement le met en quarantaine
+    let first = vault.put("/docs/plan.txt", b"v1").await.unwrap();
+    let second = vault.put("/docs/plan.txt", b"v2").await.unwrap();
+    vault.clear_cache().unwrap();
+    let mut blob = server.object(&second).unwrap();
+    *blob.last_mut().unwrap() ^= 0xff;
+    server.put_object(&second, blob);
+    assert!(matches!(vault.get(&second).await, Err(VaultError::Quarantined(_))));
+    assert!(matches!(vault.fetch_blob(&second).await, Err(VaultError::Quarantined(_))));
+
+    let listed = quarantine::list(&vault).unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!((listed[0].file_id.as_str(), listed[0].source.as_str()), (second.as_str(), "download"));
+    assert_eq!(listed[0].versions.iter().map(|v| &v.id).collect::<Vec<_>>(), vec![&first]);
+    assert!(!listed[0].local_copy);
+    assert!(!quarantine::recheck(&vault, &second).await.unwrap());
+
+    // Réparation par une version antérieure : l'objet altéré est supprimé
+    quarantine::restore_version(&vault, &second, &first).await.unwrap();
+    assert_eq!(vault.get(&first).await.unwrap(), b"v1");
+    assert!(server.object(&second).is_none());
+    assert!(quarantine::list(&vault).unwrap().is_empty());
+
+    // Objet tronqué repéré par la vérification : la copie du cache le remplace
+    let photo = vault.put("/photo.jpg", &[7u8; 512]).await.unwrap();
+    vault.get(&photo).await.unwrap();
+    let original = server.object(&photo).unwrap();
+    let mut blob = original.clone();
+    blob.truncate(blob.len() - 10);
+    server.put_object(&photo, blob);
+    integrity::check(&vault, 8).await.unwrap();
+    let listed = quarantine::list(&vault).unwrap();
+    assert_eq!((listed[0].source.as_str(), listed[0].local_copy), ("scrub", true));
+    assert!(matches!(vault.get(&photo).await, Err(VaultError::Quarantined(_))));
+    quarantine::restore_local_copy(&vault, &photo).await.unwrap();
+    assert_eq!(server.object(&photo).unwrap(), original);
+    assert_eq!(vault.get(&photo).await.unwrap(), vec![7u8; 512]);
+
+    // Abandon : l'entrée et son objet disparaissent
+    let note = vault.put("/note.txt", b"note").await.unwrap();
+    vault.clear_cache().unwrap();
+    let mut blob = server.object(&note).unwrap();
+    blob[0] ^= 0xff;
+    server.put_object(&note, blob);
+    assert!(matches!(vault.get(&note).await, Err(VaultError::Quarantined(_))));
+    quarantine::discard(&vault, &note).await.unwrap();
+    assert!(vault.find_including_trash(&note).is_err());
+    assert!(server.object(&note).is_none());
+    assert!(quarantine::list(&vault).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn restoring_from_trash_checks_the_remote_object_first() {
+    use aether_core::quarantine;
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+
+    // Objet disparu du bucket : l'entrée reste à la corbeille, en quarantaine
+    let report = vault.put("/docs/report.txt", b"numbers").await.unwrap();
+    vault.get(&report).await.unwrap();
+    let original = server.object(&report).unwrap();
+    vault.trash(&report).unwrap();
+    vault.remote().delete_file(&report).await.unwrap();
+    assert!(matches!(vault.restore(&report).await, Err(VaultError::Quarantined(_))));
+    assert!(vault.list().unwrap().is_empty());
+    let listed = quarantine::list(&vault).unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!((listed[0].source.as_str(), listed[0].trashed), ("restore", true));
+    assert_eq!(listed[0].reason, integrity::MISSING_REMOTE_REASON);
+    assert!(listed[0].local_copy);
+
+    // Réparé depuis la copie locale, il se restaure
+    quarantine::restore_local_copy(&vault, &report).await.unwrap();
+    assert_eq!(server.object(&report).unwrap(), original);
+    assert_eq!(vault.restore(&report).await.unwrap().logical_path, "/docs/report.txt");
+    assert_eq!(vault.get(&report).await.unwrap(), b"numbers");
+
+    // En-tête illisible et pas de copie locale : seul l'abandon reste
+    let note = vault.put("/note.txt", b"note").await.unwrap();
+    vault.clear_cache().unwrap();
+    vault.trash(&note).unwrap();
+    let mut blob = server.object(&note).unwrap();
+    blob[0] ^= 0xff;
+    server.put_object(&note, blob);
+    assert!(matches!(vault.restore(&note).await, Err(VaultError::Quarantined(_))));
+    assert!(!quarantine::list(&vault).unwrap()[0].local_copy);
+    quarantine::discard(&vault, &note).await.unwrap();
+    assert!(vault.find_including_trash(&note).is_err());
+    assert!(quarantine::list(&vault).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn locks_are_shared_between_devices_and_flag_concurrent_edits() {
+    use aether_core::locks::{self, LockError};
+
+    let dir = TempDir::new().unwrap();
+    let (laptop, server) = vault_with_mock(&dir).await;
+    let phone = Vault::new(
+        MasterKey::from_vec(vec![42u8; 32]),
+        dir.path().join("phone.db"),
+        Arc::new(StorjClient::new(server.config()).await.unwrap()),
+    );
+
+    let lock = locks::acquire(&laptop, "/docs/plan.odt", "Alice", None).await.unwrap();
+    assert!(lock.mine);
+    // Les verrous sont des objets internes, absents des listes de fichiers
+    assert!(laptop.remote().list_files().await.unwrap().is_empty());
+
+    let seen = locks::list(&phone).await.unwrap();
+    assert_eq!(seen.len(), 1);
+    assert_eq!((seen[0].holder.as_str(), seen[0].mine), ("Alice", false));
+    assert_eq!(locks::conflicting(&phone, "/docs/plan.odt").await.unwrap().unwrap().device, lock.device);
+    assert!(locks::conflicting(&laptop, "/docs/plan.odt").await.unwrap().is_none());
+    assert!(locks::conflicting(&phone, "/docs/other.odt").await.unwrap().is_none());
+
+    assert!(matches!(locks::acquire(&phone, "/docs/plan.odt", "Bob", None).await, Err(LockError::Held(_))));
+    assert!(matches!(locks::release(&phone, "/docs/plan.odt", false).await, Err(LockError::Held(_))));
+    // Prolongé par son détenteur, puis libéré
+    assert_eq!(locks::acquire(&laptop, "/docs/plan.odt", "Alice", Some(600)).await.unwrap().acquired_at, lock.acquired_at);
+    assert!(locks::release(&laptop, "/docs/plan.odt", false).await.unwrap());
+    assert!(!locks::release(&laptop, "/docs/plan.odt", false).await.unwrap());
+
+    locks::acquire(&phone, "/docs/plan.odt", "Bob", None).await.unwrap();
+    assert!(locks::release(&laptop, "/docs/plan.odt", true).await.unwrap());
+    assert!(locks::list(&laptop).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn heartbeats_reveal_concurrent_sessions_and_make_the_journal_conservative() {
+    use aether_core::index::ConflictMode;
+    use aether_core::{journal, sessions};
+
+    let dir = TempDir::new().unwrap();
+    let (laptop, server) = vault_with_mock(&dir).await;
+    let phone = Vault::new(
+        MasterKey::from_vec(vec![42u8; 32]),
+        dir.path().join("phone.db"),
+        Arc::new(StorjClient::new(server.config()).await.unwrap()),
+    );
+
+    assert!(sessions::heartbeat(&laptop, "Laptop").await.unwrap().peers.is_empty());
+    let quiet = sessions::heartbeat(&phone, "Phone").await.unwrap();
+    assert_eq!(quiet.peers.len(), 1);
+    assert_eq!((quiet.peers[0].label.as_str(), quiet.peers[0].mutating), ("Laptop", false));
+    assert_eq!(quiet.mode, ConflictMode::Normal);
+    // Les battements sont des objets internes, absents des listes de fichiers
+    assert!(laptop.remote().list_files().await.unwrap().is_empty());
+
+    // L'ordinateur modifie le coffre pendant que le téléphone écrit au même chemin
+    laptop.put("/notes.txt", b"from the laptop").await.unwrap();
+    assert_eq!(sessions::heartbeat(&laptop, "Laptop").await.unwrap().mode, ConflictMode::Normal);
+    let busy = sessions::heartbeat(&phone, "Phone").await.unwrap();
+    assert!(busy.peers[0].mutating);
+    assert_eq!(busy.mode, ConflictMode::Conservative);
+    assert_eq!(sessions::conflict_mode(&phone.open_index().unwrap()).unwrap(), ConflictMode::Conservative);
+
+    let phone_id = phone.put("/notes.txt", b"from the phone").await.unwrap();
+    journal::sync(&laptop).await.unwrap();
+    let merged = journal::sync(&phone).await.unwrap();
+    assert_eq!(merged.conflicts.len(), 1);
+    assert_eq!(merged.conflicts[0].kept_as.as_deref(), Some("/notes (2).txt"));
+    let files = phone.list().unwrap();
+    let at = |path: &str| files.iter().find(|(_, meta)| meta.logical_path == path).unwrap().0.clone();
+    assert_eq!(at("/notes.txt"), phone_id);
+    assert_eq!(phone.get(&at("/notes (2).txt")).await.unwrap(), b"from the laptop");
+
+    // Verrouillé : l'ordinateur n'apparaît plus et le mode redevient normal
+    sessions::end(&laptop).await.unwrap();
+    let alone = sessions::heartbeat(&phone, "Phone").await.unwrap();
+    assert!(alone.peers.is_empty());
+    assert_eq!(alone.mode, ConflictMode::Normal);
+}
+
+#[tokio::test]
+async fn vault_clones_are_verified_and_restorable() {
+    use aether_core::backup;
+    use aether_core::clone::{self, CloneError, CloneTarget, CLONE_STEPS};
+
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let report_id = vault.put("/docs/report.txt", b"quarterly numbers").await.unwrap();
+    vault.put("/photos/beach.jpg", b"not really a jpeg").await.unwrap();
+
+    // Vers un dossier : objets, index et rapport ; le clone se vérifie sur ses propres copies
+    let target = CloneTarget::Directory(dir.path().join("drill"));
+    let report = clone::clone_vault(&vault, &target, &Job::detached(CLONE_STEPS)).await.unwrap();
+    assert!(report.is_verified(), "{:?}", report.failures);
+    assert_eq!((report.objects, report.files, report.verified_files), (2, 2, 2));
+    assert!(dir.path().join("drill").join(clone::INDEX_FILE).exists());
+    assert!(dir.path().join("drill").join(clone::REPORT_FILE).exists());
+    assert_eq!(clone::last_report(&vault.open_index().unwrap()).unwrap().unwrap().objects, 2);
+    assert!(matches!(
+        clone::clone_vault(&vault, &target, &Job::detached(CLONE_STEPS)).await,
+        Err(CloneError::Invalid("target_not_empty"))
+    ));
+
+    // Un objet copié altéré fait échouer la vérification, sans erreur
+    let tampered = CloneTarget::Directory(dir.path().join("tampered"));
+    clone::clone_vault(&vault, &tampered, &Job::detached(CLONE_STEPS)).await.unwrap();
+    std::fs::remove_dir_all(dir.path().join("tampered")).unwrap();
+    let intact = server.object(&report_id).unwrap();
+    server.put_object(&report_id, b"garbage".to_vec());
+    let report = clone::clone_vault(&vault, &tampered, &Job::detached(CLONE_STEPS)).await.unwrap();
+    assert!(!report.is_verified());
+    assert_eq!((report.failures.len(), report.failures[0].key.as_str()), (1, report_id.as_str()));
+    server.put_object(&report_id, intact);
+
+    // Vers un autre bucket : l'index y devient une sauvegarde que restaure un nouvel appareil
+    let second = MockS3Server::start("aether-drill").await.unwrap();
+    let client = Arc::new(StorjClient::new(second.config()).await.unwrap());
+    let same = CloneTarget::Bucket(Arc::new(StorjClient::new(server.config()).await.unwrap()));
+    assert!(matches!(
+        clone::clone_vault(&vault, &same, &Job::detached(CLONE_STEPS)).await,
+        Err(CloneError::Invalid("same_as_source"))
+    ));
+    let report = clone::clone_vault(&vault, &CloneTarget::Bucket(client.clone()), &Job::detached(CLONE_STEPS)).await.unwrap();
+    assert!(report.is_verified(), "{:?}", report.failures);
+
+    let restored = Vault::new(MasterKey::from_vec(vec![42u8; 32]), dir.path().join("restored.db"), client);
+    let backups = backup::list(&restored).await.unwrap();
+    assert_eq!(backup::restore(&restored, &backups[0].key).await.unwrap(), 2);
+    assert_eq!(restored.get(&report_id).await.unwrap(), b"quarterly numbers");
+}
+
+#[tokio::test]
+async fn encrypted_archives_pack_each_blob_once_and_open_with_the_vault_password() {
+    use aether_core::crypto::{mkek, CryptoCore, PasswordSecret};
+    use aether_core::export::archive::{self, ArchiveError, ArchiveKey, ArchiveReader};
+    use aether_core::index::FileMetadata;
+
+    let dir = TempDir::new().unwrap();
+    let (vault, _server) = vault_with_mock(&dir).await;
+    vault.put("/docs/report.txt", b"quarterly numbers").await.unwrap();
+    vault.put("/copies/report.txt", b"quarterly numbers").await.unwrap();
+    vault.put("/notes.md", b"# todo").await.unwrap();
+    // Renommé sans re-chiffrement : le blob reste lié à l'ancien chemin
+    vault.rename_folder("/docs", "/archive").unwrap();
+    let folder = FileMetadata { logical_path: "/empty/".to_string(), encrypted_size: 0 };
+    vault.open_index().unwrap().upsert("folder".to_string(), folder).unwrap();
+
+    let password = PasswordSecret::new("correct horse battery staple");
+    let password_salt = [7u8; 16];
+    let kek = CryptoCore::default().derive_kek(&password, &password_salt).unwrap();
+    let key = ArchiveKey { password_salt, mkek: mkek::encrypt_master_key(&kek, vault.master_key()).unwrap() };
+    let dest = dir.path().join("usb").join("vault.aether-archive");
+    let mut steps = 0;
+    let summary = archive::write(&vault, &dest, key.clone(), |_, _, _| steps += 1).await.unwrap();
+    assert_eq!((summary.files, summary.folders, summary.objects, steps), (3, 1, 2, 4));
+    assert!(summary.failed.is_empty());
+    assert!(matches!(
+        archive::write(&vault, &dest, key, |_, _, _| {}).await,
+        Err(ArchiveError::Invalid("destination_exists"))
+    ));
+    // Aucun chemin ni contenu en clair dans l'archive
+    let raw = std::fs::read(&dest).unwrap();
+    assert!(!raw.windows(b"quarterly".len()).any(|w| w == b"quarterly"));
+    assert!(!raw.windows(b"notes.md".len()).any(|w| w == b"notes.md"));
+
+    let mut reader = ArchiveReader::open(&dest).unwrap();
+    assert_eq!(reader.header().vault_fingerprint, vault.master_key().fingerprint());
+    assert!(matches!(reader.unlock(&PasswordSecret::new("wrong")), Err(ArchiveError::Invalid("wrong_password"))));
+    let master_key = reader.unlock(&password).unwrap();
+    let out = dir.path().join("restored");
+    let report = archive::extract(&mut reader, &master_key, &out).unwrap();
+    assert_eq!((report.files, report.folders, report.failed.len()), (3, 1, 0));
+    assert_eq!(std::fs::read(out.join("archive/report.txt")).unwrap(), b"quarterly numbers");
+    assert_eq!(std::fs::read(out.join("copies/report.txt")).unwrap(), b"quarterly numbers");
+    assert_eq!(std::fs::read(out.join("notes.md")).unwrap(), b"# todo");
+    assert!(out.join("empty").is_dir());
+
+    // Une archive tronquée est refusée dès l'ouverture
+    let truncated = dir.path().join("truncated.aether-archive");
+    std::fs::write(&truncated, &raw[..raw.len() - 10]).unwrap();
+    assert!(matches!(ArchiveReader::open(&truncated), Err(ArchiveError::Invalid("truncated"))));
+}
+
+#[tokio::test]
+async fn selected_files_are_handed_off_under_their_own_password() {
+    use aether_core::crypto::PasswordSecret;
+    use aether_core::export::archive::{self, ArchiveError, ArchiveReader, SelectionKey};
+
+    let dir = TempDir::new().unwrap();
+    let (vault, _server) = vault_with_mock(&dir).await;
+    let report = vault.put("/docs/report.txt", b"quarterly numbers").await.unwrap();
+    let notes = vault.put("/notes.md", b"# todo").await.unwrap();
+    vault.put("/private.txt", b"not for you").await.unwrap();
+    vault.rename_folder("/docs", "/shared").unwrap();
+
+    let password = PasswordSecret::new("handoff phrase");
+    assert!(matches!(
+        SelectionKey::new(&PasswordSecret::new("short")),
+        Err(ArchiveError::Invalid("password_too_short"))
+    ));
+    let dest = dir.path().join("handoff.aether-archive");
+    let selection = [report.clone(), notes.clone()];
+    let key = SelectionKey::new(&password).unwrap();
+    let summary = archive::write_selection(&vault, &selection, &dest, key, |_, _, _| {}).await.unwrap();
+    assert_eq!((summary.files, summary.objects, summary.failed.len()), (2, 2, 0));
+
+    // Le lot ne porte pas la clé du coffre et s'ouvre avec le seul mot de passe choisi
+    let mut reader = ArchiveReader::open(&dest).unwrap();
+    assert_ne!(reader.header().vault_fingerprint, vault.master_key().fingerprint());
+    assert!(matches!(reader.unlock(&PasswordSecret::new("wrong")), Err(ArchiveError::Invalid("wrong_password"))));
+    let master_key = reader.unlock(&password).unwrap();
+    let out = dir.path().join("received");
+    let extracted = archive::extract(&mut reader, &master_key, &out).unwrap();
+    assert_eq!((extracted.files, extracted.failed.len()), (2, 0));
+    assert_eq!(std::fs::read(out.join("shared/report.txt")).unwrap(), b"quarterly numbers");
+    assert_eq!(std::fs::read(out.join("notes.md")).unwrap(), b"# todo");
+    assert!(!out.join("private.txt").exists());
+}
+
+#[tokio::test]
+async fn trashed_objects_are_marked_in_the_bucket_for_other_devices() {
+    use aether_core::{journal, tombstones};
+
+    let dir = TempDir::new().unwrap();
+    let (laptop, server) = vault_with_mock(&dir).await;
+    let phone = Vault::new(
+        MasterKey::from_vec(vec![42u8; 32]),
+        dir.path().join("phone.db"),
+        Arc::new(StorjClient::new(server.config()).await.unwrap()),
+    );
+
+    let draft = laptop.put("/docs/draft.txt", b"draft").await.unwrap();
+    let notes = laptop.put("/docs/notes.txt", b"notes").await.unwrap();
+    laptop.trash(&draft).unwrap();
+    laptop.trash(&notes).unwrap();
+    assert_eq!(
+        tombstones::publish(&laptop).await.unwrap(),
+        tombstones::TombstoneReport { written: 2, removed: 0 }
+    );
+    // Témoins internes : la liste des fichiers est inchangée
+    assert_eq!(laptop.remote().list_files().await.unwrap().len(), 2);
+
+    // Le téléphone ne reçoit que le retrait des entrées, mais le bucket dit pourquoi
+    journal::sync(&laptop).await.unwrap();
+    journal::sync(&phone).await.unwrap();
+    assert!(phone.list().unwrap().is_empty());
+    let trashed = tombstones::trashed_keys(phone.remote()).await.unwrap();
+    assert_eq!(trashed, [draft.clone(), notes.clone()].into_iter().collect());
+    assert_eq!(tombstones::publish(&phone).await.unwrap(), tombstones::TombstoneReport::default());
+
+    // Restauré ou purgé : le témoin disparaît
+    laptop.restore(&draft).await.unwrap();
+    laptop.purge(&notes).await.unwrap();
+    assert_eq!(
+        tombstones::publish(&laptop).await.unwrap(),
+        tombstones::TombstoneReport { written: 0, removed: 2 }
+    );
+    assert!(tombstones::trashed_keys(phone.remote()).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn cached_index_reads_see_every_write() {
+    use aether_core::index::cache::IndexCache;
+
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let cache = Arc::new(IndexCache::new());
+    let vault = vault.with_index_cache(Arc::clone(&cache));
+    let paths = |vault: &Vault| vault.list().unwrap().into_iter().map(|(_, m)| m.logical_path).collect::<Vec<_>>();
+
+    let file_id = vault.put("/docs/report.txt", b"quarterly numbers").await.unwrap();
+    assert_eq!(paths(&vault), vec!["/docs/report.txt"]);
+    let renamed = vault.rename(&file_id, "/docs/final.txt").await.unwrap();
+    assert_eq!(paths(&vault), vec!["/docs/final.txt"]);
+    assert_eq!(vault.find_including_trash(&renamed).unwrap().logical_path, "/docs/final.txt");
+
+    // Un autre coffre sur le même index, sans cache, écrit à côté
+    let client = StorjClient::new(server.config()).await.unwrap();
+    let other = Vault::new(MasterKey::from_vec(vec![42u8; 32]), dir.path().join("index.db"), Arc::new(client));
+    other.trash(&renamed).unwrap();
+    assert!(paths(&vault).is_empty());
+    assert_eq!(vault.find_including_trash(&renamed).unwrap().logical_path, "/docs/final.txt");
+}
+
+#[tokio::test]
+async fn interrupted_downloads_resume_from_the_last_verified_chunk() {
+    use aether_core::downloads::ResumableDownloads;
+
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let downloads = ResumableDownloads::new(dir.path().join("downloads")).unwrap().with_chunk_size(1024);
+    let vault = vault.with_downloads(downloads.clone());
+
+    let content: Vec<u8> = (0..5000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let file_id = vault.put("/media/clip.bin", &content).await.unwrap();
+    let total = server.object(&file_id).unwrap().len() as u64;
+    let chunks = total.div_ceil(1024) as usize;
+    vault.clear_cache().unwrap();
+
+    // La connexion tombe après deux morceaux : ils restent sur disque
+    server.fail_reads_after(&file_id, Some(2));
+    assert!(vault.get(&file_id).await.is_err());
+    assert_eq!(downloads.progress(&file_id), Some((2048, total)));
+
+    // La reprise ne redemande que les morceaux manquants
+    server.fail_reads_after(&file_id, None);
+    assert_eq!(vault.get(&file_id).await.unwrap(), content);
+    assert_eq!(server.reads(&file_id), chunks);
+    assert_eq!(downloads.progress(&file_id), None);
+
+    // Un objet remplacé pendant l'interruption est téléchargé de nouveau en entier
+    vault.clear_cache().unwrap();
+    server.fail_reads_after(&file_id, Some(chunks + 1));
+    assert!(vault.get(&file_id).await.is_err());
+    let mut blob = server.object(&file_id).unwrap();
+    *blob.last_mut().unwrap() ^= 0xff;
+    server.put_object(&file_id, blob);
+    server.fail_reads_after(&file_id, None);
+    assert!(matches!(vault.get(&file_id).await, Err(VaultError::Quarantined(_))));
+    assert_eq!(server.reads(&file_id), 2 * chunks + 2);
+}
+
+#[tokio::test]
+async fn transfers_report_their_progress() {
+    use aether_core::downloads::ResumableDownloads;
+    use std::sync::Mutex;
+
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let downloads = ResumableDownloads::new(dir.path().join("downloads")).unwrap().with_chunk_size(1024);
+    let vault = vault.with_downloads(downloads);
+
+    // Envoi : octets cédés au transport, croissants jusqu'à la taille du corps
+    let body: Vec<u8> = (0..300_000u32).map(|i| (i % 241) as u8).collect();
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&sent);
+    vault
+        .remote()
+        .upload_file_with_progress("raw-object", &body, move |bytes| recorder.lock().unwrap().push(bytes))
+        .await
+        .unwrap();
+    assert_eq!(server.object("raw-object").unwrap(), body);
+    let sent = sent.lock().unwrap().clone();
+    assert!(sent.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(sent.last(), Some(&(body.len() as u64)));
+
+    // Téléchargement par morceaux : un rappel par morceau
+    let content: Vec<u8> = (0..5000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let file_id = vault.put("/media/clip.bin", &content).await.unwrap();
+    vault.clear_cache().unwrap();
+    let total = server.object(&file_id).unwrap().len() as u64;
+    let mut received = Vec::new();
+    let blob = vault.fetch_blob_with_progress(&file_id, |done, of| received.push((done, of))).await.unwrap();
+    assert_eq!(blob.len() as u64, total);
+    assert_eq!(received.len() as u64, total.div_ceil(1024));
+    assert_eq!(received.first(), Some(&(1024, total)));
+    assert_eq!(received.last(), Some(&(total, total)));
+}
+
+#[tokio::test]
+async fn files_are_decrypted_straight_into_their_destination() {
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let out = dir.path().join("out");
+    std::fs::create_dir_all(&out).unwrap();
+
+    // Plus gros qu'un morceau : lu par plages, progression à chaque morceau
+    let content: Vec<u8> = (0..9 * 1024 * 1024u32).map(|i| (i % 253) as u8).collect();
+    let video = vault.put("/media/video.bin", &content).await.unwrap();
+    vault.clear_cache().unwrap();
+    let mut steps = Vec::new();
+    let saved = vault
+        .save_to(&video, "/media/video.bin", &out.join("video.bin"), |done, total| steps.push((done, total)))
+        .await
+        .unwrap();
+    assert_eq!(saved, content.len() as u64);
+    assert_eq!(std::fs::read(out.join("video.bin")).unwrap(), content);
+    let total = server.object(&video).unwrap().len() as u64;
+    assert_eq!(steps.len(), 2);
+    assert_eq!(steps.last(), Some(&(total, total)));
+
+    // Depuis le cache, y compris un fichier vide
+    let empty = vault.put("/notes/empty.txt", b"").await.unwrap();
+    vault.fetch_blob(&empty).await.unwrap();
+    let reads = server.reads(&empty);
+    assert_eq!(vault.save_to(&empty, "/notes/empty.txt", &out.join("empty.txt"), |_, _| {}).await.unwrap(), 0);
+    assert!(std::fs::read(out.join("empty.txt")).unwrap().is_empty());
+    assert_eq!(server.reads(&empty), reads);
+
+    // Un contenu altéré n'arrive jamais à destination
+    let notes = vault.put("/notes/plan.txt", b"the plan").await.unwrap();
+    vault.clear_cache().unwrap();
+    let mut blob = server.object(&notes).unwrap();
+    *blob.last_mut().unwrap() ^= 0xff;
+    server.put_object(&notes, blob);
+    let tampered = vault.save_to(&notes, "/notes/plan.txt", &out.join("plan.txt"), |_, _| {}).await;
+    assert!(matches!(tampered, Err(VaultError::Quarantined(_))));
+    let mut left: Vec<_> = std::fs::read_dir(&out).unwrap().map(|e| e.unwrap().file_name()).collect();
+    left.sort();
+    assert_eq!(left, vec!["empty.txt", "video.bin"]);
+}
+
+#[tokio::test]
+async fn interrupted_saves_resume_into_the_same_destination() {
+    use aether_core::downloads::ResumableDownloads;
+
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let downloads = ResumableDownloads::new(dir.path().join("downloads")).unwrap().with_chunk_size(1024);
+    let vault = vault.with_downloads(downloads.clone());
+    let out = dir.path().join("out");
+    std::fs::create_dir_all(&out).unwrap();
+    let content: Vec<u8> = (0..5000u32).map(|i| (i * 11 % 251) as u8).collect();
+    let video = vault.put("/media/video.bin", &content).await.unwrap();
+    let total = server.object(&video).unwrap().len() as u64;
+    let chunks = total.div_ceil(1024) as usize;
+    vault.clear_cache().unwrap();
+    let dest = out.join("video.bin");
+
+    // La connexion tombe après deux morceaux : ils restent parmi les téléchargements partiels
+    server.fail_reads_after(&video, Some(2));
+    assert!(vault.save_to(&video, "/media/video.bin", &dest, |_, _| {}).await.is_err());
+    assert!(std::fs::read_dir(&out).unwrap().next().is_none());
+    assert_eq!(downloads.progress(&video), Some((2048, total)));
+
+    // Annulé après le morceau suivant : il est gardé aussi
+    server.fail_reads_after(&video, Some(3));
+    let (cancel, cancelled) = tokio::sync::oneshot::channel();
+    let mut cancel = Some(cancel);
+    let save = vault.save_to(&video, "/media/video.bin", &dest, |_, _| {
+        if let Some(cancel) = cancel.take() {
+            let _ = cancel.send(());
+        }
+    });
+    tokio::select! {
+        _ = save => panic!("the save should have been cancelled"),
+        _ = cancelled => {}
+    }
+    assert_eq!(downloads.progress(&video), Some((3072, total)));
+    // La requête du morceau suivant, déjà partie, est refusée avant de lever la limite
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    // La reprise ne redemande que les morceaux manquants
+    server.fail_reads_after(&video, None);
+    let mut first = None;
+    let saved = vault
+        .save_to(&video, "/media/video.bin", &dest, |done, _| {
+            first.get_or_insert(done);
+        })
+        .await
+        .unwrap();
+    assert_eq!(saved, content.len() as u64);
+    assert_eq!(std::fs::read(&dest).unwrap(), content);
+    assert_eq!(first, Some(4096));
+    assert_eq!(server.reads(&video), chunks);
+    assert_eq!(downloads.progress(&video), None);
+    assert!(std::fs::read_dir(dir.path().join("downloads")).unwrap().next().is_none());
+    let left: Vec<_> = std::fs::read_dir(&out).unwrap().map(|e| e.unwrap().file_name()).collect();
+    assert_eq!(left, vec!["video.bin"]);
+
+    // Un fichier partiel altéré entre-temps est relu depuis le premier morceau abîmé
+    let raw = out.join("video.aeth");
+    server.fail_reads_after(&video, Some(chunks + 3));
+    assert!(vault.save_encrypted_to(&video, &raw, |_, _| {}).await.is_err());
+    std::fs::write(dir.path().join("downloads").join(format!("{}.download", video)), b"garbage").unwrap();
+    server.fail_reads_after(&video, None);
+    let size = vault.save_encrypted_to(&video, &raw, |_, _| {}).await.unwrap();
+    assert_eq!(std::fs::read(&raw).unwrap(), server.object(&video).unwrap());
+    assert_eq!(server.reads(&video), 2 * chunks + 3);
+    assert_eq!(size, total);
+}
+
+#[tokio::test]
+async fn activity_summary_counts_failures_until_acknowledged() {
+    use aether_core::activity;
+
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let report = vault.put("/docs/report.pdf", b"quarterly numbers").await.unwrap();
+    let notes = vault.put("/notes/plan.txt", b"the plan").await.unwrap();
+    vault.clear_cache().unwrap();
+    assert!(activity::summary(&vault.open_index().unwrap()).unwrap().is_clear());
+
+    // Un téléchargement coupé compte comme transfert échoué
+    server.fail_reads_after(&report, Some(0));
+    assert!(vault.get(&report).await.is_err());
+    // Un objet altéré est mis en quarantaine : avertissement d'intégrité
+    let mut blob = server.object(&notes).unwrap();
+    *blob.last_mut().unwrap() ^= 0xff;
+    server.put_object(&notes, blob);
+    assert!(matches!(vault.get(&notes).await, Err(VaultError::Quarantined(_))));
+    let summary = activity::summary(&vault.open_index().unwrap()).unwrap();
+    assert_eq!((summary.failed_transfers, summary.integrity_warnings), (1, 1));
+
+    // L'acquittement efface l'échec, pas la quarantaine qui attend une réparation
+    activity::acknowledge(&mut vault.open_index().unwrap()).unwrap();
+    let summary = activity::summary(&vault.open_index().unwrap()).unwrap();
+    assert_eq!((summary.failed_transfers, summary.integrity_warnings), (0, 1));
+}
+
+#[tokio::test]
+async fn folder_policies_shape_uploads_cache_and_sync() {
+    use aether_core::journal;
+    use aether_core::policies::{self, FolderPolicy};
+    use aether_core::storage::Padding;
+
+    let dir = TempDir::new().unwrap();
+    let (laptop, server) = vault_with_mock(&dir).await;
+    let phone = Vault::new(
+        MasterKey::from_vec(vec![42u8; 32]),
+        dir.path().join("phone.db"),
+        Arc::new(StorjClient::new(server.config()).await.unwrap()),
+    );
+    {
+        let mut index = laptop.open_index().unwrap();
+        let archive = FolderPolicy { compress: true, padding: Padding::Standard, ..FolderPolicy::default() };
+        policies::set(&mut index, "/archive/", &archive).unwrap();
+        policies::set(&mut index, "/offline/", &FolderPolicy { pin_offline: true, ..FolderPolicy::default() }).unwrap();
+        policies::set(&mut index, "/private/", &FolderPolicy { exclude_from_sync: true, ..FolderPolicy::default() })
+            .unwrap();
+    }
+
+    // Compressé et bourré à l'envoi, intact à la lecture comme à l'enregistrement
+    let log = b"the same line again\n".repeat(1_000);
+    let archived = laptop.put("/archive/app.log", &log).await.unwrap();
+    let stored = laptop.find_including_trash(&archived).unwrap();
+    assert!(stored.encrypted_size < log.len() as u64);
+    assert_eq!(laptop.plaintext_size(&archived, &stored).unwrap(), log.len() as u64);
+    assert_eq!(laptop.get(&archived).await.unwrap(), log);
+    laptop.clear_cache().unwrap();
+    let out = dir.path().join("app.log");
+    assert_eq!(laptop.save_to(&archived, "/archive/app.log", &out, |_, _| {}).await.unwrap(), log.len() as u64);
+    assert_eq!(std::fs::read(&out).unwrap(), log);
+
+    // Un fichier hors ligne est téléchargé d'avance sans avoir jamais été ouvert
+    let map = laptop.put("/offline/map.pdf", &[3u8; 200]).await.unwrap();
+    laptop.clear_cache().unwrap();
+    let report = laptop.maintain_cache().await.unwrap();
+    assert_eq!(report.prewarmed, 1);
+    assert!(laptop.cache().unwrap().contains(&map));
+
+    // Les dossiers exclus ne passent pas par le journal, et les racines concordent quand même
+    let diary = laptop.put("/private/diary.txt", b"dear diary").await.unwrap();
+    journal::sync(&laptop).await.unwrap();
+    journal::sync(&phone).await.unwrap();
+    let phone_ids: Vec<FileId> = phone.list().unwrap().into_iter().map(|(id, _)| id).collect();
+    assert!(phone_ids.contains(&archived) && phone_ids.contains(&map));
+    assert!(!phone_ids.contains(&diary));
+    // La taille en clair suit le journal : elle ne se déduit pas de l'objet compressé
+    assert_eq!(phone.open_index().unwrap().plaintext_size(&archived).unwrap(), Some(log.len() as u64));
+    journal::sync(&laptop).await.unwrap();
+    let check = journal::roots::check_peers(&laptop).await.unwrap();
+    assert_eq!((check.peers, check.diverged.len()), (1, 0));
+}
+
+#[tokio::test]
+async fn random_remote_keys_hide_entry_ids_and_renames_from_the_bucket() {
+    use aether_core::remote_keys;
+    use sha2::{Digest, Sha256};
+
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let legacy = vault.put("/legacy.txt", b"before").await.unwrap();
+    remote_keys::set_enabled(&mut vault.open_index().unwrap(), true).unwrap();
+
+    // L'objet porte une clé sans rapport avec l'entrée ; l'index fait le lien
+    let file_id = vault.put("/docs/report.txt", b"quarterly numbers").await.unwrap();
+    let object_key = vault.open_index().unwrap().object_key(&file_id).unwrap();
+    assert_ne!(object_key, file_id);
+    let mut keys = vec![legacy.clone(), object_key.clone()];
+    keys.sort();
+    assert_eq!(server.object_keys(), keys);
+    assert_eq!(vault.get(&file_id).await.unwrap(), b"quarterly numbers");
+    let digest: [u8; 32] = Sha256::digest(b"quarterly numbers").into();
+    assert!(vault.verify_download(&file_id, &digest, 17).await.unwrap().header_valid);
+
+    // Un renommage de dossier ne touche pas au bucket
+    let stored = server.object(&object_key).unwrap();
+    vault.rename_folder("/docs", "/archive").unwrap();
+    vault.flush_pending().await.unwrap();
+    assert_eq!(server.object(&object_key).unwrap(), stored);
+    assert_eq!(vault.get(&file_id).await.unwrap(), b"quarterly numbers");
+    assert!(vault.reconcile().await.unwrap().removed_orphans.is_empty());
+
+    // La purge emporte l'objet lié
+    vault.trash(&file_id).unwrap();
+    vault.purge(&file_id).await.unwrap();
+    assert_eq!(server.object_keys(), vec![legacy]);
+}
+
+#[tokio::test]
+async fn key_repair_renames_dashed_objects_and_their_links() {
+    use aether_core::remote_keys;
+
+    let dashed = |key: &str| format!("{}-{}-{}-{}-{}", &key[..8], &key[8..12], &key[12..16], &key[16..20], &key[20..]);
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+
+    // Objet envoyé par une ancienne version, sous l'UUID avec tirets
+    let legacy = vault.put("/legacy.txt", b"before").await.unwrap();
+    server.put_object(&dashed(&legacy), server.object(&legacy).unwrap());
+    vault.remote().delete_file(&legacy).await.unwrap();
+
+    // Objet lié (clés aléatoires) dont le lien pointe la clé avec tirets
+    remote_keys::set_enabled(&mut vault.open_index().unwrap(), true).unwrap();
+    let linked = vault.put("/docs/report.txt", b"quarterly numbers").await.unwrap();
+    let (object_key, aad_path) = vault.open_index().unwrap().blob_link(&linked).unwrap().unwrap();
+    server.put_object(&dashed(&object_key), server.object(&object_key).unwrap());
+    vault.remote().delete_file(&object_key).await.unwrap();
+    vault.open_index().unwrap().link_blob(&linked, &dashed(&object_key), &aad_path).unwrap();
+
+    // Reste d'une réparation interrompue, et objet étranger au coffre
+    let copied = vault.put("/copied.txt", b"copied").await.unwrap();
+    let copied_key = vault.open_index().unwrap().object_key(&copied).unwrap();
+    server.put_object(&dashed(&copied_key), server.object(&copied_key).unwrap());
+    server.put_object("notes.txt", b"not ours".to_vec());
+
+    let audit = remote_keys::repair(&vault, false).await.unwrap();
+    assert_eq!((audit.checked, audit.misnamed.len(), audit.duplicates.len(), audit.repaired), (5, 2, 1, 0));
+    assert_eq!(audit.unrecognized, vec!["notes.txt".to_string()]);
+    assert_eq!(server.object_keys().len(), 5);
+
+    let repaired = remote_keys::repair(&vault, true).await.unwrap();
+    assert_eq!(repaired.repaired, 3);
+    assert!(repaired.misnamed.is_empty() && repaired.duplicates.is_empty());
+    let mut keys = vec![legacy.clone(), object_key.clone(), copied_key, "notes.txt".to_string()];
+    keys.sort();
+    assert_eq!(server.object_keys(), keys);
+    assert_eq!(vault.open_index().unwrap().object_key(&linked).unwrap(), object_key);
+
+    vault.clear_cache().unwrap();
+    assert_eq!(vault.get(&legacy).await.unwrap(), b"before");
+    assert_eq!(vault.get(&linked).await.unwrap(), b"quarterly numbers");
+    assert!(vault.reconcile().await.unwrap().removed_orphans.is_empty());
+    assert_eq!(remote_keys::repair(&vault, false).await.unwrap().misnamed.len(), 0);
+}
+
+#[tokio::test]
+async fn downloads_to_disk_keep_the_object_or_decrypt_every_format() {
+    use aether_core::index::FileMetadata;
+    use aether_core::storage::{encrypt_stream, AetherFile, CHUNK_LEN};
+
+    let dir = TempDir::new().unwrap();
+    let (vault, server) = vault_with_mock(&dir).await;
+    let file_id = vault.put("/docs/report.txt", b"quarterly figures").await.unwrap();
+
+    // Objet tel quel : identique à l'objet distant
+    let raw = dir.path().join("report.aeth");
+    let size = vault.save_encrypted_to(&file_id, &raw, |_, _| {}).await.unwrap();
+    assert_eq!(std::fs::read(&raw).unwrap(), server.object(&file_id).unwrap());
+    assert_eq!(size, std::fs::metadata(&raw).unwrap().len());
+
+    // Corps par morceaux, lu par plages (hors cache) : déchiffré en flux
+    let plaintext = b"chunked body ".repeat(CHUNK_LEN / 6);
+    let mut blob = Vec::new();
+    encrypt_stream(&MasterKey::from_vec(vec![42u8; 32]), &plaintext[..], plaintext.len() as u64, "/big.bin", &mut blob).unwrap();
+    let (header, _) = AetherFile::parse_header(&blob).unwrap();
+    let chunked_id = hex::encode(header.uuid);
+    server.put_object(&chunked_id, blob.clone());
+    let metadata = FileMetadata { logical_path: "/big.bin".to_string(), encrypted_size: blob.len() as u64 };
+    vault.open_index().unwrap().upsert(chunked_id.clone(), metadata).unwrap();
+
+    let dest = dir.path().join("big.bin");
+    let mut reported = 0;
+    let size = vault.save_to(&chunked_id, "/big.bin", &dest, |done, _| reported = done).await.unwrap();
+    assert_eq!((size, reported), (plaintext.len() as u64, blob.len() as u64));
+    assert_eq!(std::fs::read(&dest).unwrap(), plaintext);
+
+    // Morceau altéré : ni destination, ni fichier partiel
+    let mut altered = blob;
+    altered[AetherFile::HEADER_LEN + 10] ^= 1;
+    server.put_object(&chunked_id, altered);
+    let refused = dir.path().join("refused.bin");
+    assert!(vault.save_to(&chunked_id, "/big.bin", &refused, |_, _| {}).await.is_err());
+    let leftovers: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .filter_map(|e| e.unwrap().file_name().into_string().ok())
+        .filter(|name| name.starts_with("refused"))
+        .collect();
+    assert!(leftovers.is_empty(), "{:?}", leftovers);
+}
@@ -0,0 +1,714 @@
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::{Map, Value};
+use std::fmt;
+
+use crate::backup::BackupError;
+use crate::bundle::BundleError;
+use crate::clone::CloneError;
+use crate::crypto::CryptoError;
+use crate::delegation::DelegationError;
+use crate::export::archive::ArchiveError;
+use crate::export::ExportError;
+use crate::guest::GuestError;
+use crate::hooks::HookError;
+use crate::index::sqlcipher;
+use crate::journal::JournalError;
+use crate::logging::LoggingError;
+use crate::paging::PageError;
+use crate::preflight::PreflightProblem;
+use crate::index::writer::WriteError;
+use crate::keychain::KeychainError;
+use crate::locks::LockError;
+use crate::rules::RuleError;
+use crate::sessions::SessionError;
+use crate::setup::SetupError;
+use crate::share::ShareError;
+use crate::snapshot::SnapshotError;
+use crate::storage::{AetherError, StorageError};
+use crate::storj::StorjError;
+use crate::telemetry;
+use crate::vault::VaultError;
+use crate::workspace::WorkspaceError;
+
+/// Erreur renvoyée par les commandes Tauri.
+///
+/// Le frontend ne reçoit qu'un code stable et des paramètres (`{ code, params }`)
+/// qu'il traduit lui-même ; le détail technique (en anglais) reste dans les logs.
+#[derive(Debug)]
+pub enum CommandError {
+    /// La MasterKey n'est pas en mémoire : le coffre doit être déverrouillé.
+    VaultLocked,
+    /// Mot de passe incorrect (ou MKEK ne correspondant pas au mot de passe).
+    WrongPassword,
+    /// La base locale existe mais ne s'ouvre pas avec la MasterKey fournie.
+    IndexKeyMismatch,
+    /// La MasterKey déverrouillée n'est pas celle du coffre de cet appareil (empreintes différentes).
+    VaultMismatch { expected: String, actual: String },
+    /// Erreur de l'index SQLCipher.
+    Index(String),
+    FileNotFound { file_id: String },
+    /// L'objet distant a échoué à la vérification : le fichier attend une réparation.
+    FileQuarantined { file_id: String },
+    /// Le fichier a été modifié par une autre opération (ou un autre appareil) entre-temps.
+    FileConflict { file_id: String },
+    PathNotFound { path: String },
+    InvalidFileId { file_id: String },
+    InvalidName { reason: &'static str },
+    /// Indice de mot de passe refusé (trop long, ou contenant le mot de passe).
+    InvalidHint { reason: &'static str },
+    /// Couleur ou icône de dossier refusée.
+    InvalidAppearance { reason: &'static str },
+    AlreadyExists { path: String },
+    /// L'envoi ferait dépasser le budget de stockage.
+    QuotaExceeded { used_bytes: u64, limit_bytes: u64, requested_bytes: u64 },
+    StorjNotConfigured,
+    RemoteNotFound,
+    /// Stockage distant injoignable (réseau coupé) : l'opération peut être différée.
+    Offline(String),
+    Remote(String),
+    InvalidAetherFile(String),
+    EncryptionFailed(String),
+    DecryptionFailed(String),
+    Keychain(String),
+    Io(String),
+    DialogCancelled,
+    DialogTimeout,
+    /// Opération en plusieurs étapes annulée par l'utilisateur (et défaite).
+    Cancelled,
+    /// Commande absente des ensembles de capacités : refusée par défaut.
+    CommandNotAllowed { command: String },
+    /// Commande sensible appelée sans confirmation native préalable (`request_elevation`).
+    ElevationRequired { command: String },
+    /// L'utilisateur a refusé la confirmation native.
+    ElevationDenied,
+    /// Commande destructive : le mot de passe doit être saisi à nouveau.
+    PasswordRequired { command: String },
+    /// Lien de partage illisible, ou aucun lien en attente.
+    InvalidShareLink { reason: &'static str },
+    /// Le fichier partagé ne correspond pas au lien (empreinte ou déchiffrement).
+    ShareVerificationFailed,
+    /// Fichier trop volumineux pour être transmis en un bloc à la webview.
+    PreviewTooLarge { size: u64, limit: u64 },
+    /// Contenu non conforme à son type (ou format non pris en charge) : non transmis à la webview.
+    PreviewRefused { reason: &'static str },
+    /// Définition de règle d'automatisation refusée.
+    InvalidRule { reason: &'static str },
+    RuleNotFound { rule_id: i64 },
+    /// Définition de hook refusée (cible non HTTPS, programme relatif, aucun événement…).
+    InvalidHook { reason: &'static str },
+    HookNotFound { hook_id: i64 },
+    /// Sauvegarde de l'index refusée (autre objet, clé différente, rétention nulle).
+    InvalidBackup { reason: &'static str },
+    /// Clonage du coffre refusé (destination non vide, ou le coffre lui-même).
+    InvalidClone { reason: &'static str },
+    /// Archive chiffrée refusée (destination existante, mot de passe ou enveloppe incorrects).
+    InvalidArchive { reason: &'static str },
+    /// Paquet de configuration illisible ou code de transfert mal formé.
+    InvalidBundle { reason: &'static str },
+    /// Segment du journal de synchronisation illisible (modifié, ou d'un autre coffre).
+    InvalidJournal { reason: &'static str },
+    /// Code de transfert erroné, ou paquet modifié.
+    BundleDecryptFailed,
+    /// Réglages de journalisation refusés (nom de module mal formé).
+    InvalidLogSettings { reason: &'static str },
+    /// Opération refusée sur un espace de travail (nom, espace par défaut ou actif).
+    InvalidWorkspace { reason: &'static str },
+    /// Étape de l'assistant de configuration refusée (hors ordre, autre coffre, terminé).
+    InvalidSetup { reason: &'static str },
+    /// Jeton de continuation d'une liste paginée illisible ou périmé.
+    InvalidCursor,
+    WorkspaceNotFound { id: String },
+    /// Opération refusée sur un instantané du coffre (nom vide ou déjà pris).
+    InvalidSnapshot { reason: &'static str },
+    SnapshotNotFound { snapshot_id: i64 },
+    /// Mode invité refusé (phrase d'accès trop courte, non définie ou illisible).
+    InvalidGuestMode { reason: &'static str },
+    /// Demande de verrou refusée (chemin, nom affiché ou durée).
+    InvalidLock { reason: &'static str },
+    /// Le document est verrouillé par un autre appareil jusqu'à `expires_at`.
+    FileLocked { path: String, holder: String, expires_at: i64 },
+    /// Délégation d'identifiants refusée localement (émetteur non HTTPS, jeton, durée).
+    InvalidDelegation { reason: &'static str },
+    /// L'émetteur d'identifiants a refusé la demande (jeton révoqué, appareil retiré…).
+    DelegationRejected { status: u16 },
+    /// Vérifications préalables d'un envoi en échec ; tous les problèmes relevés.
+    UploadPreflightFailed { problems: Vec<PreflightProblem> },
+    /// Aucune KEK confiée à cet appareil, ou elle ne correspond plus au coffre.
+    DeviceKeyUnavailable,
+    /// Le coffre exige son fichier clé, qui n'a pas été fourni.
+    KeyfileRequired,
+    /// Fichier clé refusé (taille, déjà ajouté, absent, relecture différente).
+    InvalidKeyfile { reason: &'static str },
+    Internal(String),
+}
+
+impl CommandError {
+    /// Code stable exposé au frontend (ne jamais renommer un code existant).
+    pub fn code(&self) -> &'static str {
+        match self {
+            CommandError::VaultLocked => "vault_locked",
+            CommandError::WrongPassword => "wrong_password",
+            CommandError::IndexKeyMismatch => "index_key_mismatch",
+            CommandError::VaultMismatch { .. } => "vault_mismatch",
+            CommandError::Index(_) => "index_error",
+            CommandError::FileNotFound { .. } => "file_not_found",
+            CommandError::FileQuarantined { .. } => "file_quarantined",
+            CommandError::FileConflict { .. } => "file_conflict",
+            CommandError::PathNotFound { .. } => "path_not_found",
+            CommandError::InvalidFileId { .. } => "invalid_file_id",
+            CommandError::InvalidName { .. } => "invalid_name",
+            CommandError::InvalidHint { .. } => "invalid_hint",
+            CommandError::InvalidAppearance { .. } => "invalid_appearance",
+            CommandError::AlreadyExists { .. } => "already_exists",
+            CommandError::QuotaExceeded { .. } => "quota_exceeded",
+            CommandError::StorjNotConfigured => "storj_not_configured",
+            CommandError::RemoteNotFound => "remote_not_found",
+            CommandError::Offline(_) => "offline",
+            CommandError::Remote(_) => "remote_error",
+            CommandError::InvalidAetherFile(_) => "invalid_aether_file",
+            CommandError::EncryptionFailed(_) => "encryption_failed",
+            CommandError::DecryptionFailed(_) => "decryption_failed",
+            CommandError::Keychain(_) => "keychain_error",
+            CommandError::Io(_) => "io_error",
+            CommandError::DialogCancelled => "dialog_cancelled",
+            CommandError::DialogTimeout => "dialog_timeout",
+            CommandError::Cancelled => "cancelled",
+            CommandError::CommandNotAllowed { .. } => "command_not_allowed",
+            CommandError::ElevationRequired { .. } => "elevation_required",
+            CommandError::ElevationDenied => "elevation_denied",
+            CommandError::PasswordRequired { .. } => "password_required",
+            CommandError::InvalidShareLink { .. } => "invalid_share_link",
+            CommandError::ShareVerificationFailed => "share_verification_failed",
+            CommandError::PreviewTooLarge { .. } => "preview_too_large",
+            CommandError::PreviewRefused { .. } => "preview_refused",
+            CommandError::InvalidRule { .. } => "invalid_rule",
+            CommandError::RuleNotFound { .. } => "rule_not_found",
+            CommandError::InvalidHook { .. } => "invalid_hook",
+            CommandError::HookNotFound { .. } => "hook_not_found",
+            CommandError::InvalidBackup { .. } => "invalid_backup",
+            CommandError::InvalidClone { .. } => "invalid_clone",
+            CommandError::InvalidArchive { .. } => "invalid_archive",
+            CommandError::InvalidJournal { .. } => "invalid_journal",
+            CommandError::InvalidLogSettings { .. } => "invalid_log_settings",
+            CommandError::InvalidBundle { .. } => "invalid_bundle",
+            CommandError::BundleDecryptFailed => "bundle_decrypt_failed",
+            CommandError::InvalidWorkspace { .. } => "invalid_workspace",
+            CommandError::InvalidSetup { .. } => "invalid_setup",
+            CommandError::InvalidCursor => "invalid_cursor",
+            CommandError::WorkspaceNotFound { .. } => "workspace_not_found",
+            CommandError::InvalidSnapshot { .. } => "invalid_snapshot",
+            CommandError::SnapshotNotFound { .. } => "snapshot_not_found",
+            CommandError::InvalidGuestMode { .. } => "invalid_guest_mode",
+            CommandError::InvalidLock { .. } => "invalid_lock",
+            CommandError::FileLocked { .. } => "file_locked",
+            CommandError::InvalidDelegation { .. } => "invalid_delegation",
+            CommandError::DelegationRejected { .. } => "delegation_rejected",
+            CommandError::UploadPreflightFailed { .. } => "upload_preflight_failed",
+            CommandError::DeviceKeyUnavailable => "device_key_unavailable",
+            CommandError::KeyfileRequired => "keyfile_required",
+            CommandError::InvalidKeyfile { .. } => "invalid_keyfile",
+            CommandError::Internal(_) => "internal_error",
+        }
+    }
+
+    /// Paramètres destinés à l'interpolation du message traduit.
+    pub fn params(&self) -> Map<String, Value> {
+        let mut params = Map::new();
+        match self {
+            CommandError::FileNotFound { file_id }
+            | CommandError::FileQuarantined { file_id }
+            | CommandError::FileConflict { file_id }
+            | CommandError::InvalidFileId { file_id } => {
+                params.insert("fileId".to_string(), Value::from(file_id.as_str()));
+            }
+            CommandError::PathNotFound { path } | CommandError::AlreadyExists { path } => {
+                params.insert("path".to_string(), Value::from(path.as_str()));
+            }
+            CommandError::InvalidName { reason }
+            | CommandError::InvalidHint { reason }
+            | CommandError::InvalidAppearance { reason }
+            | CommandError::InvalidShareLink { reason }
+            | CommandError::InvalidRule { reason }
+            | CommandError::PreviewRefused { reason }
+            | CommandError::InvalidHook { reason }
+            | CommandError::InvalidBackup { reason }
+            | CommandError::InvalidClone { reason }
+            | CommandError::InvalidArchive { reason }
+            | CommandError::InvalidBundle { reason }
+            | CommandError::InvalidJournal { reason }
+            | CommandError::InvalidLogSettings { reason }
+            | CommandError::InvalidWorkspace { reason }
+            | CommandError::InvalidSetup { reason }
+            | CommandError::InvalidSnapshot { reason }
+            | CommandError::InvalidGuestMode { reason }
+            | CommandError::InvalidLock { reason }
+            | CommandError::InvalidDelegation { reason }
+            | CommandError::InvalidKeyfile { reason } => {
+                params.insert("reason".to_string(), Value::from(*reason));
+            }
+            CommandError::CommandNotAllowed { command }
+            | CommandError::ElevationRequired { command }
+            | CommandError::PasswordRequired { command } => {
+                params.insert("command".to_string(), Value::from(command.as_str()));
+            }
+            CommandError::QuotaExceeded { used_bytes, limit_bytes, requested_bytes } => {
+                params.insert("usedBytes".to_string(), Value::from(*used_bytes));
+                params.insert("limitBytes".to_string(), Value::from(*limit_bytes));
+                params.insert("requestedBytes".to_string(), Value::from(*requested_bytes));
+            }
+            CommandError::PreviewTooLarge { size, limit } => {
+                params.insert("size".to_string(), Value::from(*size));
+                params.insert("limit".to_string(), Value::from(*limit));
+            }
+            CommandError::VaultMismatch { expected, actual } => {
+                params.insert("expected".to_string(), Value::from(expected.as_str()));
+                params.insert("actual".to_string(), Value::from(actual.as_str()));
+            }
+            CommandError::RuleNotFound { rule_id } => {
+                params.insert("ruleId".to_string(), Value::from(*rule_id));
+            }
+            CommandError::HookNotFound { hook_id } => {
+                params.insert("hookId".to_string(), Value::from(*hook_id));
+            }
+            CommandError::WorkspaceNotFound { id } => {
+                params.insert("id".to_string(), Value::from(id.as_str()));
+            }
+            CommandError::SnapshotNotFound { snapshot_id } => {
+                params.insert("snapshotId".to_string(), Value::from(*snapshot_id));
+            }
+            CommandError::FileLocked { path, holder, expires_at } => {
+                params.insert("path".to_string(), Value::from(path.as_str()));
+                params.insert("holder".to_string(), Value::from(holder.as_str()));
+                params.insert("expiresAt".to_string(), Value::from(*expires_at));
+            }
+            CommandError::DelegationRejected { status } => {
+                params.insert("status".to_string(), Value::from(*status));
+            }
+            CommandError::UploadPreflightFailed { problems } => {
+                params.insert("count".to_string(), Value::from(problems.len()));
+                params.insert("problems".to_string(), serde_json::to_value(problems).unwrap_or_default());
+            }
+            _ => {}
+        }
+        params
+    }
+
+    pub fn index(context: &str, e: impl fmt::Display) -> Self {
+        CommandError::Index(format!("{}: {}", context, e))
+    }
+
+    pub fn internal(context: &str, e: impl fmt::Display) -> Self {
+        CommandError::Internal(format!("{}: {}", context, e))
+    }
+
+    pub fn io(context: &str, e: impl fmt::Display) -> Self {
+        CommandError::Io(format!("{}: {}", context, e))
+    }
+
+    pub fn remote(context: &str, e: StorjError) -> Self {
+        match e {
+            StorjError::NotFound => CommandError::RemoteNotFound,
+            StorjError::Unreachable(msg) => CommandError::Offline(format!("{}: {}", context, msg)),
+            other => CommandError::Remote(format!("{}: {}", context, other)),
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::VaultLocked => write!(f, "MasterKey not available. Unlock the vault first."),
+            CommandError::WrongPassword => write!(f, "Wrong password or MKEK"),
+            CommandError::IndexKeyMismatch => {
+                write!(f, "Local index exists but cannot be opened with this MasterKey")
+            }
+            CommandError::VaultMismatch { expected, actual } => {
+                write!(f, "Unlocked vault {} does not match the local vault {}", actual, expected)
+            }
+            CommandError::Index(msg) => write!(f, "Index error: {}", msg),
+            CommandError::FileNotFound { file_id } => write!(f, "File not found: {}", file_id),
+            CommandError::FileQuarantined { file_id } => write!(f, "File quarantined: {}", file_id),
+            CommandError::FileConflict { file_id } => write!(f, "File modified concurrently: {}", file_id),
+            CommandError::PathNotFound { path } => write!(f, "Path not found: {}", path),
+            CommandError::InvalidFileId { file_id } => write!(f, "Invalid file id: {}", file_id),
+            CommandError::InvalidName { reason } => write!(f, "Invalid name: {}", reason),
+            CommandError::InvalidHint { reason } => write!(f, "Invalid password hint: {}", reason),
+            CommandError::InvalidAppearance { reason } => write!(f, "Invalid folder appearance: {}", reason),
+            CommandError::AlreadyExists { path } => write!(f, "Already exists: {}", path),
+            CommandError::QuotaExceeded { used_bytes, limit_bytes, requested_bytes } => write!(
+                f,
+                "Storage quota exceeded: used={}, requested={}, limit={}",
+                used_bytes, requested_bytes, limit_bytes
+            ),
+            CommandError::StorjNotConfigured => {
+                write!(f, "Storj client not configured. Call storj_configure first.")
+            }
+            CommandError::RemoteNotFound => write!(f, "Remote object not found"),
+            CommandError::Offline(msg) => write!(f, "Remote storage unreachable: {}", msg),
+            CommandError::Remote(msg) => write!(f, "Remote storage error: {}", msg),
+            CommandError::InvalidAetherFile(msg) => write!(f, "Invalid Aether file: {}", msg),
+            CommandError::EncryptionFailed(msg) => write!(f, "Encryption failed: {}", msg),
+            CommandError::DecryptionFailed(msg) => write!(f, "Decryption failed: {}", msg),
+            CommandError::Keychain(msg) => write!(f, "Keychain error: {}", msg),
+            CommandError::Io(msg) => write!(f, "IO error: {}", msg),
+            CommandError::DialogCancelled => write!(f, "Dialog cancelled by the user"),
+            CommandError::DialogTimeout => write!(f, "Dialog timed out"),
+            CommandError::Cancelled => write!(f, "Operation cancelled by the user"),
+            CommandError::CommandNotAllowed { command } => write!(f, "Command not allowed: {}", command),
+            CommandError::ElevationRequired { command } => write!(f, "Elevation required for command: {}", command),
+            CommandError::ElevationDenied => write!(f, "Elevation denied by the user"),
+            CommandError::PasswordRequired { command } => write!(f, "Password re-entry required for command: {}", command),
+            CommandError::InvalidShareLink { reason } => write!(f, "Invalid share link: {}", reason),
+            CommandError::ShareVerificationFailed => write!(f, "Shared file failed verification"),
+            CommandError::PreviewTooLarge { size, limit } => {
+                write!(f, "File too large for inline preview: {} bytes (limit {})", size, limit)
+            }
+            CommandError::PreviewRefused { reason } => write!(f, "Preview refused: {}", reason),
+            CommandError::InvalidRule { reason } => write!(f, "Invalid rule: {}", reason),
+            CommandError::RuleNotFound { rule_id } => write!(f, "Rule not found: {}", rule_id),
+            CommandError::InvalidHook { reason } => write!(f, "Invalid hook: {}", reason),
+            CommandError::HookNotFound { hook_id } => write!(f, "Hook not found: {}", hook_id),
+            CommandError::InvalidBackup { reason } => write!(f, "Invalid index backup: {}", reason),
+            CommandError::InvalidClone { reason } => write!(f, "Invalid vault clone: {}", reason),
+            CommandError::InvalidArchive { reason } => write!(f, "Invalid archive: {}", reason),
+            CommandError::InvalidBundle { reason } => write!(f, "Invalid vault bundle: {}", reason),
+            CommandError::BundleDecryptFailed => write!(f, "Failed to open vault bundle"),
+            CommandError::InvalidJournal { reason } => write!(f, "Invalid sync journal segment: {}", reason),
+            CommandError::InvalidLogSettings { reason } => write!(f, "Invalid log settings: {}", reason),
+            CommandError::InvalidWorkspace { reason } => write!(f, "Invalid workspace operation: {}", reason),
+            CommandError::InvalidSetup { reason } => write!(f, "Invalid setup step: {}", reason),
+            CommandError::InvalidCursor => write!(f, "Invalid continuation cursor"),
+            CommandError::WorkspaceNotFound { id } => write!(f, "Workspace not found: {}", id),
+            CommandError::InvalidSnapshot { reason } => write!(f, "Invalid snapshot operation: {}", reason),
+            CommandError::SnapshotNotFound { snapshot_id } => write!(f, "Snapshot not found: {}", snapshot_id),
+            CommandError::InvalidGuestMode { reason } => write!(f, "Invalid guest mode operation: {}", reason),
+            CommandError::InvalidLock { reason } => write!(f, "Invalid lock request: {}", reason),
+            CommandError::FileLocked { path, holder, expires_at } => {
+                write!(f, "File {} locked by {} until {}", path, holder, expires_at)
+            }
+            CommandError::InvalidDelegation { reason } => write!(f, "Invalid credential delegation: {}", reason),
+            CommandError::DelegationRejected { status } => {
+                write!(f, "Credential issuer refused the request: HTTP {}", status)
+            }
+            CommandError::UploadPreflightFailed { problems } => {
+                write!(f, "Upload preflight failed with {} problem(s): {:?}", problems.len(), problems)
+            }
+            CommandError::DeviceKeyUnavailable => write!(f, "No usable KEK stored on this device"),
+            CommandError::KeyfileRequired => write!(f, "Keyfile required to open this vault"),
+            CommandError::InvalidKeyfile { reason } => write!(f, "Invalid keyfile: {}", reason),
+            CommandError::Internal(msg) => write!(f, "Internal error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// Sérialisé en `{ "code": "...", "params": { ... } }`.
+///
+/// C'est le seul point où une erreur quitte le backend : le détail technique
+/// y est journalisé avant d'être retiré de la charge utile.
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        log::warn!("Command error [{}]: {}", self.code(), self);
+        telemetry::record_error(self.code());
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("code", self.code())?;
+        map.serialize_entry("params", &self.params())?;
+        map.end()
+    }
+}
+
+impl From<rusqlite::Error> for CommandError {
+    fn from(e: rusqlite::Error) -> Self {
+        // Base laissée intacte par `SqlCipherIndex::open` : voir les commandes de récupération
+        if sqlcipher::is_key_mismatch(&e) {
+            return CommandError::IndexKeyMismatch;
+        }
+        CommandError::Index(e.to_string())
+    }
+}
+
+impl From<WriteError> for CommandError {
+    fn from(e: WriteError) -> Self {
+        match e {
+            WriteError::Index(e) => e.into(),
+            WriteError::Stopped => CommandError::Internal("Index writer stopped".to_string()),
+        }
+    }
+}
+
+impl From<CryptoError> for CommandError {
+    fn from(e: CryptoError) -> Self {
+        match e {
+            CryptoError::Aead => CommandError::WrongPassword,
+            CryptoError::KeyfileRequired => CommandError::KeyfileRequired,
+            other => CommandError::Internal(other.to_string()),
+        }
+    }
+}
+
+impl From<AetherError> for CommandError {
+    fn from(e: AetherError) -> Self {
+        CommandError::InvalidAetherFile(e.to_string())
+    }
+}
+
+impl From<StorageError> for CommandError {
+    fn from(e: StorageError) -> Self {
+        CommandError::DecryptionFailed(e.to_string())
+    }
+}
+
+impl From<ArchiveError> for CommandError {
+    fn from(e: ArchiveError) -> Self {
+        match e {
+            ArchiveError::Invalid(reason) => CommandError::InvalidArchive { reason },
+            ArchiveError::Io(e) => CommandError::Io(e.to_string()),
+            ArchiveError::Crypto(e) => e.into(),
+            ArchiveError::Export(e) => e.into(),
+            ArchiveError::Vault(e) => e.into(),
+        }
+    }
+}
+
+impl From<ExportError> for CommandError {
+    fn from(e: ExportError) -> Self {
+        match e {
+            ExportError::Io(e) => CommandError::Io(e.to_string()),
+            other => CommandError::Internal(other.to_string()),
+        }
+    }
+}
+
+impl From<VaultError> for CommandError {
+    fn from(e: VaultError) -> Self {
+        match e {
+            VaultError::Index(e) => e.into(),
+            VaultError::Remote(e) => CommandError::remote("Vault remote operation failed", e),
+            VaultError::Storage(e) => e.into(),
+            VaultError::Format(e) => e.into(),
+            VaultError::NotFound(file_id) => CommandError::FileNotFound { file_id },
+            VaultError::CacheMiss(file_id) => CommandError::Internal(format!("Cached blob missing for {}", file_id)),
+            VaultError::QuotaExceeded { used_bytes, limit_bytes, requested_bytes } => {
+                CommandError::QuotaExceeded { used_bytes, limit_bytes, requested_bytes }
+            }
+            VaultError::PathNotFound(path) => CommandError::PathNotFound { path },
+            VaultError::AlreadyExists(path) => CommandError::AlreadyExists { path },
+            VaultError::InvalidPath(reason) => CommandError::InvalidName { reason },
+            VaultError::DeleteNotVerified(file_id) => {
+                CommandError::Remote(format!("Remote object still present after deletion: {}", file_id))
+            }
+            VaultError::Cancelled => CommandError::Cancelled,
+            VaultError::Quarantined(file_id) => CommandError::FileQuarantined { file_id },
+            VaultError::Conflict(file_id) => CommandError::FileConflict { file_id },
+        }
+    }
+}
+
+impl From<ShareError> for CommandError {
+    fn from(e: ShareError) -> Self {
+        match e {
+            ShareError::InvalidLink(reason) => CommandError::InvalidShareLink { reason },
+            ShareError::DigestMismatch | ShareError::Decrypt => CommandError::ShareVerificationFailed,
+            ShareError::Unreachable(msg) => CommandError::Offline(msg),
+            other => CommandError::Remote(other.to_string()),
+        }
+    }
+}
+
+impl From<BackupError> for CommandError {
+    fn from(e: BackupError) -> Self {
+        match e {
+            BackupError::Invalid(reason) => CommandError::InvalidBackup { reason },
+            BackupError::Io(e) => CommandError::Io(e.to_string()),
+            BackupError::Vault(e) => e.into(),
+        }
+    }
+}
+
+impl From<CloneError> for CommandError {
+    fn from(e: CloneError) -> Self {
+        match e {
+            CloneError::Invalid(reason) => CommandError::InvalidClone { reason },
+            CloneError::Io(e) => CommandError::Io(e.to_string()),
+            CloneError::Vault(e) => e.into(),
+        }
+    }
+}
+
+impl From<BundleError> for CommandError {
+    fn from(e: BundleError) -> Self {
+        match e {
+            BundleError::Invalid(reason) => CommandError::InvalidBundle { reason },
+            BundleError::Decrypt => CommandError::BundleDecryptFailed,
+            BundleError::Crypto(e) => e.into(),
+        }
+    }
+}
+
+impl From<LoggingError> for CommandError {
+    fn from(e: LoggingError) -> Self {
+        match e {
+            LoggingError::InvalidModule => CommandError::InvalidLogSettings { reason: "module" },
+            LoggingError::NotInstalled | LoggingError::StoreUnavailable => CommandError::Internal(e.to_string()),
+            LoggingError::Io(msg) => CommandError::Io(msg),
+        }
+    }
+}
+
+impl From<JournalError> for CommandError {
+    fn from(e: JournalError) -> Self {
+        match e {
+            JournalError::Invalid(reason) => CommandError::InvalidJournal { reason },
+            JournalError::Crypto(e) => e.into(),
+            JournalError::Vault(e) => e.into(),
+        }
+    }
+}
+
+impl From<SessionError> for CommandError {
+    fn from(e: SessionError) -> Self {
+        match e {
+            SessionError::Crypto(e) => e.into(),
+            SessionError::Vault(e) => e.into(),
+        }
+    }
+}
+
+impl From<LockError> for CommandError {
+    fn from(e: LockError) -> Self {
+        match e {
+            LockError::Invalid(reason) => CommandError::InvalidLock { reason },
+            LockError::Held(lock) => CommandError::FileLocked {
+                path: lock.logical_path,
+                holder: lock.holder,
+                expires_at: lock.expires_at,
+            },
+            LockError::Crypto(e) => e.into(),
+            LockError::Vault(e) => e.into(),
+        }
+    }
+}
+
+impl From<WorkspaceError> for CommandError {
+    fn from(e: WorkspaceError) -> Self {
+        match e {
+            WorkspaceError::Invalid(reason) => CommandError::InvalidWorkspace { reason },
+            WorkspaceError::NotFound(id) => CommandError::WorkspaceNotFound { id },
+            WorkspaceError::Io(e) => CommandError::Io(e.to_string()),
+            WorkspaceError::Registry(msg) => CommandError::Internal(msg),
+        }
+    }
+}
+
+impl From<SetupError> for CommandError {
+    fn from(e: SetupError) -> Self {
+        match e {
+            SetupError::Invalid(reason) => CommandError::InvalidSetup { reason },
+            SetupError::Io(e) => CommandError::Io(e.to_string()),
+            SetupError::State(msg) => CommandError::Internal(msg),
+        }
+    }
+}
+
+impl From<PageError> for CommandError {
+    fn from(e: PageError) -> Self {
+        match e {
+            PageError::InvalidCursor => CommandError::InvalidCursor,
+        }
+    }
+}
+
+impl From<SnapshotError> for CommandError {
+    fn from(e: SnapshotError) -> Self {
+        match e {
+            SnapshotError::Invalid(reason) => CommandError::InvalidSnapshot { reason },
+            SnapshotError::NotFound(snapshot_id) => CommandError::SnapshotNotFound { snapshot_id },
+            SnapshotError::Vault(e) => e.into(),
+        }
+    }
+}
+
+impl From<GuestError> for CommandError {
+    fn from(e: GuestError) -> Self {
+        match e {
+            GuestError::Invalid(reason) => CommandError::InvalidGuestMode { reason },
+            GuestError::Crypto(e) => e.into(),
+            GuestError::Index(e) => e.into(),
+            GuestError::Io(msg) => CommandError::Io(msg),
+        }
+    }
+}
+
+impl From<RuleError> for CommandError {
+    fn from(e: RuleError) -> Self {
+        match e {
+            RuleError::Invalid(reason) => CommandError::InvalidRule { reason },
+            RuleError::NotFound(rule_id) => CommandError::RuleNotFound { rule_id },
+            RuleError::Index(e) => e.into(),
+        }
+    }
+}
+
+impl From<DelegationError> for CommandError {
+    fn from(e: DelegationError) -> Self {
+        match e {
+            DelegationError::Invalid(reason) => CommandError::InvalidDelegation { reason },
+            DelegationError::Rejected(status) => CommandError::DelegationRejected { status },
+            DelegationError::Unreachable(msg) => CommandError::Offline(format!("Credential issuer: {}", msg)),
+            DelegationError::Issuer(msg) => CommandError::Remote(format!("Credential issuer: {}", msg)),
+        }
+    }
+}
+
+impl From<HookError> for CommandError {
+    fn from(e: HookError) -> Self {
+        match e {
+            HookError::Invalid(reason) => CommandError::InvalidHook { reason },
+            HookError::NotFound(hook_id) => CommandError::HookNotFound { hook_id },
+            HookError::Index(e) => e.into(),
+        }
+    }
+}
+
+impl From<KeychainError> for CommandError {
+    fn from(e: KeychainError) -> Self {
+        CommandError::Keychain(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_and_params_only() {
+        let err = CommandError::FileNotFound {
+            file_id: "abc".to_string(),
+        };
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["code"], "file_not_found");
+        assert_eq!(json["params"]["fileId"], "abc");
+        assert_eq!(json.as_object().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn technical_detail_is_not_serialized() {
+        let err = CommandError::index("Failed to list files", "disk I/O error at /secret/path");
+        let json = serde_json::to_string(&err).unwrap();
+        assert!(!json.contains("/secret/path"));
+        assert!(err.to_string().contains("/secret/path"));
+    }
+
+    #[test]
+    fn remote_not_found_has_dedicated_code() {
+        let err = CommandError::remote("download", StorjError::NotFound);
+        assert_eq!(err.code(), "remote_not_found");
+    }
+
+    #[test]
+    fn unreachable_remote_maps_to_offline() {
+        let err = CommandError::remote("list", StorjError::Unreachable("dispatch failure".to_string()));
+        assert_eq!(err.code(), "offline");
+    }
+}
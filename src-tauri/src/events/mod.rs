@@ -0,0 +1,301 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use crate::hooks::HookEvent;
+use crate::jobs::JobState;
+use crate::transfers::meter::{TransferMeter, TransferRate};
+use crate::{notifications, tray};
+
+/// Nom unique de l'événement Tauri sur lequel le frontend s'abonne.
+///
+/// Toutes les notifications backend → frontend passent par ce canal ; le type
+/// concret est porté par le champ `type` de la charge utile.
+pub const APP_EVENT: &str = "aether://event";
+
+/// Sens d'un transfert vers/depuis le stockage distant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransferDirection {
+    Upload,
+    Download,
+}
+
+/// État de la synchronisation entre l'index local et Storj.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncStatus {
+    Syncing,
+    Idle,
+    Failed,
+}
+
+/// Contrat des événements émis par le backend.
+///
+/// Sérialisé en `{ "type": "transferProgress", "payload": { ... } }`.
+/// Ajouter une variante est rétrocompatible ; renommer un champ ne l'est pas.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "payload", rename_all = "camelCase")]
+pub enum AppEvent {
+    /// Avancement d'un transfert, au plus toutes les 250 ms (la fin est toujours émise).
+    #[serde(rename_all = "camelCase")]
+    TransferProgress {
+        file_id: String,
+        direction: TransferDirection,
+        bytes_done: u64,
+        bytes_total: u64,
+        /// Débit mesuré sur les dernières secondes.
+        bytes_per_second: u64,
+        /// Secondes restantes au débit actuel ; `null` tant que rien n'a avancé.
+        eta_seconds: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    SyncState {
+        status: SyncStatus,
+        remote_count: Option<usize>,
+    },
+    /// L'index local ne passe plus la vérification HMAC / Merkle.
+    #[serde(rename_all = "camelCase")]
+    IntegrityWarning { detail: String },
+    /// La MasterKey a été retirée de la mémoire.
+    VaultLocked,
+    /// Un fichier n'a pas pu prendre `logical_path`, occupé par `existing_file_id` :
+    /// il a été placé à `final_path` (`nom (2).ext`).
+    #[serde(rename_all = "camelCase")]
+    Conflict {
+        logical_path: String,
+        existing_file_id: String,
+        new_file_id: String,
+        final_path: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    TrashPurged { file_ids: Vec<String> },
+    #[serde(rename_all = "camelCase")]
+    ExportProgress {
+        files_done: usize,
+        files_total: usize,
+        logical_path: String,
+    },
+    /// Bilan de la passe de reprise après un arrêt brutal (émis au déverrouillage).
+    #[serde(rename_all = "camelCase")]
+    Recovery {
+        removed_temp_files: usize,
+        aborted_multipart_uploads: usize,
+        resumed_uploads: usize,
+        dropped_uploads: Vec<String>,
+        remaining_pending: usize,
+    },
+    /// Un envoi vient de faire franchir un seuil du budget de stockage.
+    #[serde(rename_all = "camelCase")]
+    QuotaWarning {
+        threshold_percent: u8,
+        used_bytes: u64,
+        limit_bytes: u64,
+    },
+    /// Une mise à la corbeille vient de lui faire dépasser sa part de l'espace utilisé.
+    #[serde(rename_all = "camelCase")]
+    TrashSuggestion {
+        trash_bytes: u64,
+        used_bytes: u64,
+        share_percent: u8,
+    },
+    /// Un lien `aether://share/...` a été ouvert : il attend l'accord de l'utilisateur.
+    #[serde(rename_all = "camelCase")]
+    ShareLinkOpened { name: String },
+    /// Une passe des règles d'automatisation a envoyé, mis à la corbeille ou échoué.
+    #[serde(rename_all = "camelCase")]
+    RulesApplied {
+        uploaded: usize,
+        trashed: usize,
+        failed: usize,
+    },
+    /// Modifications d'autres appareils écartées par le journal de synchronisation : ces
+    /// entrées avaient changé ici entre-temps, la version locale est conservée.
+    #[serde(rename_all = "camelCase")]
+    JournalConflicts { logical_paths: Vec<String> },
+    /// Au déverrouillage, l'index diffère de celui d'autres appareils (identifiants de leur
+    /// journal) au même point de synchronisation : divergence que le journal n'explique pas.
+    #[serde(rename_all = "camelCase")]
+    IndexDiverged { devices: Vec<String> },
+    /// Un document verrouillé par un autre appareil vient d'être modifié ici (le verrou est
+    /// consultatif : la modification est conservée).
+    #[serde(rename_all = "camelCase")]
+    LockedFileEdited {
+        logical_path: String,
+        holder: String,
+        expires_at: i64,
+    },
+    /// Autres appareils (noms affichés) en train de modifier ce coffre ; vide quand ils ont
+    /// cessé. Tant qu'il y en a, le journal garde aussi leurs versions en conflit.
+    #[serde(rename_all = "camelCase")]
+    ConcurrentSessions { devices: Vec<String> },
+    /// L'espace de travail actif a changé : listes et réglages sont à recharger.
+    #[serde(rename_all = "camelCase")]
+    WorkspaceChanged {
+        id: String,
+        name: String,
+        storj_configured: bool,
+    },
+    /// Avancement d'une opération en plusieurs étapes (renommage…), annulable via `job_cancel`.
+    #[serde(rename_all = "camelCase")]
+    JobProgress {
+        job_id: u64,
+        /// Nature de l'opération (`rename`).
+        kind: String,
+        /// Ce sur quoi elle porte, pour l'affichage (chemin logique).
+        label: String,
+        step: String,
+        step_index: usize,
+        step_count: usize,
+        state: JobState,
+    },
+    /// Résumé d'activité du badge d'état, émis quand il change (voir `activity::summary`).
+    #[serde(rename_all = "camelCase")]
+    ActivitySummary {
+        pending_uploads: usize,
+        failed_transfers: usize,
+        conflicts: usize,
+        integrity_warnings: usize,
+    },
+}
+
+impl AppEvent {
+    /// Événement de hook correspondant, s'il y en a un : un transfert compte quand il se
+    /// termine, pas à chaque progression.
+    pub fn hook_event(&self) -> Option<HookEvent> {
+        match self {
+            AppEvent::TransferProgress { direction, bytes_done, bytes_total, .. }
+                if *bytes_total > 0 && bytes_done >= bytes_total =>
+            {
+                Some(match direction {
+                    TransferDirection::Upload => HookEvent::UploadComplete,
+                    TransferDirection::Download => HookEvent::DownloadComplete,
+                })
+            }
+            AppEvent::Conflict { .. } | AppEvent::JournalConflicts { .. } | AppEvent::LockedFileEdited { .. } => {
+                Some(HookEvent::Conflict)
+            }
+            AppEvent::IntegrityWarning { .. } => Some(HookEvent::IntegrityFailure),
+            AppEvent::TrashPurged { .. } => Some(HookEvent::TrashPurged),
+            _ => None,
+        }
+    }
+}
+
+/// Émet l'avancement d'un transfert, limité et complété du débit par un [`TransferMeter`].
+pub struct TransferReporter {
+    app: AppHandle,
+    file_id: String,
+    direction: TransferDirection,
+    meter: Mutex<TransferMeter>,
+}
+
+impl TransferReporter {
+    pub fn new(app: &AppHandle, file_id: &str, direction: TransferDirection) -> Self {
+        Self {
+            app: app.clone(),
+            file_id: file_id.to_string(),
+            direction,
+            meter: Mutex::new(TransferMeter::new()),
+        }
+    }
+
+    /// Rapporte `bytes_done` octets transférés sur `bytes_total` ; n'émet que les mesures
+    /// retenues par le compteur.
+    pub fn report(&self, bytes_done: u64, bytes_total: u64) {
+        let rate = match self.meter.lock() {
+            Ok(mut meter) => meter.observe(bytes_done, bytes_total),
+            Err(_) => return,
+        };
+        if let Some(rate) = rate {
+            emit(&self.app, transfer_progress(&self.file_id, self.direction, rate));
+        }
+    }
+}
+
+fn transfer_progress(file_id: &str, direction: TransferDirection, rate: TransferRate) -> AppEvent {
+    AppEvent::TransferProgress {
+        file_id: file_id.to_string(),
+        direction,
+        bytes_done: rate.bytes_done,
+        bytes_total: rate.bytes_total,
+        bytes_per_second: rate.bytes_per_second,
+        eta_seconds: rate.eta_seconds,
+    }
+}
+
+/// Émet un événement vers toutes les fenêtres (et une notification native si pertinent).
+///
+/// La barre système est mise à jour au passage et les hooks abonnés sont déclenchés.
+///
+/// Un échec d'émission n'interrompt jamais la commande appelante : il est journalisé.
+pub fn emit(app: &AppHandle, event: AppEvent) {
+    if let Err(e) = app.emit(APP_EVENT, &event) {
+        log::warn!("Failed to emit app event {:?}: {}", event, e);
+    }
+    notifications::notify(app, &event);
+    tray::on_event(app, &event);
+    crate::fire_hooks(app, &event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_tagged_with_camel_case_fields() {
+        let rate = TransferRate { bytes_done: 10, bytes_total: 20, bytes_per_second: 5, eta_seconds: Some(2) };
+        let event = transfer_progress("abc", TransferDirection::Upload, rate);
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "transferProgress");
+        assert_eq!(json["payload"]["fileId"], "abc");
+        assert_eq!(json["payload"]["direction"], "upload");
+        assert_eq!(json["payload"]["bytesTotal"], 20);
+        assert_eq!(json["payload"]["bytesPerSecond"], 5);
+        assert_eq!(json["payload"]["etaSeconds"], 2);
+    }
+
+    #[test]
+    fn only_finished_transfers_fire_hooks() {
+        let progress = |bytes_done, bytes_total| AppEvent::TransferProgress {
+            file_id: "abc".to_string(),
+            direction: TransferDirection::Download,
+            bytes_done,
+            bytes_total,
+            bytes_per_second: 0,
+            eta_seconds: None,
+        };
+        assert_eq!(progress(10, 20).hook_event(), None);
+        assert_eq!(progress(0, 0).hook_event(), None);
+        assert_eq!(progress(20, 20).hook_event(), Some(HookEvent::DownloadComplete));
+        assert_eq!(AppEvent::VaultLocked.hook_event(), None);
+        let warning = AppEvent::IntegrityWarning { detail: "hmac".to_string() };
+        assert_eq!(warning.hook_event(), Some(HookEvent::IntegrityFailure));
+    }
+
+    #[test]
+    fn job_progress_carries_step_and_state() {
+        let event = AppEvent::JobProgress {
+            job_id: 3,
+            kind: "rename".to_string(),
+            label: "/docs/b.txt".to_string(),
+            step: "upload".to_string(),
+            step_index: 1,
+            step_count: 3,
+            state: JobState::Cancelled,
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "jobProgress");
+        assert_eq!(json["payload"]["jobId"], 3);
+        assert_eq!(json["payload"]["stepCount"], 3);
+        assert_eq!(json["payload"]["state"], "cancelled");
+        assert_eq!(event.hook_event(), None);
+    }
+
+    #[test]
+    fn unit_variant_has_no_payload() {
+        let json = serde_json::to_value(AppEvent::VaultLocked).unwrap();
+        assert_eq!(json["type"], "vaultLocked");
+        assert!(json.get("payload").is_none());
+    }
+}
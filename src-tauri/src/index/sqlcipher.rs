@@ -1,712 +0,0 @@
-use hkdf::Hkdf;
-use log;
-use rusqlite::{params, Connection, Result as SqliteResult};
-use sha2::{Sha256, Digest};
-use std::path::{Path, PathBuf};
-
-use super::{merkle::MerkleTree, FileId, FileMetadata};
-
-const DB_KEY_INFO: &[u8] = b"aether-drive:sqlcipher-key:v1";
-const HMAC_KEY_INFO: &[u8] = b"aether-drive:index-hmac-key:v1";
-const SCHEMA_VERSION: u32 = 3; // Incrémenté pour ajouter la table trash
-const DB_KEY_LEN: usize = 32;
-const HMAC_LEN: usize = 32;
-
-/// Index local persistant basé sur SQLCipher (AES-256).
-///
-/// La clé de chiffrement de la base est dérivée de la MasterKey via HKDF-SHA256,
-/// garantissant que seul le détenteur de la MasterKey peut accéder à l'index.
-/// Chaque entrée est protégée par un HMAC-SHA256 pour garantir l'intégrité.
-pub struct SqlCipherIndex {
-    conn: Connection,
-    hmac_key: [u8; HMAC_LEN], // Clé HMAC dérivée de la MasterKey
-}
-
-impl SqlCipherIndex {
-    /// Ouvre ou crée une base SQLCipher chiffrée à partir d'une MasterKey.
-    ///
-    /// # Arguments
-    /// * `db_path` - Chemin du fichier SQLite à créer/ouvrir.
-    /// * `master_key` - MasterKey utilisée pour dériver la clé de chiffrement SQLCipher (doit faire exactement 32 octets).
-    pub fn open<P: AsRef<Path>>(db_path: P, master_key: &[u8]) -> SqliteResult<Self> {
-        if master_key.len() != DB_KEY_LEN {
-            log::error!("SqlCipherIndex::open: MasterKey length is {} instead of {}", master_key.len(), DB_KEY_LEN);
-            return Err(rusqlite::Error::InvalidQuery);
-        }
-        let master_key_array: [u8; DB_KEY_LEN] = master_key.try_into().unwrap();
-        // Dérive la clé SQLCipher (32 octets) depuis la MasterKey via HKDF-SHA256.
-        let hkdf = Hkdf::<Sha256>::new(None, &master_key_array);
-        let mut db_key = [0u8; DB_KEY_LEN];
-        hkdf.expand(DB_KEY_INFO, &mut db_key)
-            .map_err(|_| {
-                log::error!("SqlCipherIndex::open: HKDF expansion failed");
-                rusqlite::Error::InvalidQuery
-            })?;
-
-        let db_path_buf: PathBuf = db_path.as_ref().to_path_buf();
-        let key_hex = hex::encode(db_key);
-        log::info!("SqlCipherIndex::open: Opening database at {}", db_path_buf.to_string_lossy());
-
-        // Si le fichier existe, essaie de l'ouvrir avec la clé dérivée.
-        if db_path_buf.exists() {
-            log::info!("SqlCipherIndex::open: Database file exists, attempting to open");
-            match Connection::open(&db_path_buf) {
-                        Ok(test_conn) => {
-                    // Essaie de configurer la clé SQLCipher.
-                    match test_conn.pragma_update(None, "key", &format!("x'{}'", key_hex)) {
-                        Ok(_) => {
-                            // Essaie d'accéder à la table pour vérifier que la base est valide.
-                            // Utilise "SELECT 1" d'abord, puis essaie d'accéder à la table si elle existe.
-                            match test_conn.query_row("SELECT 1", [], |_| Ok(())) {
-                                Ok(_) => {
-                                    // La base répond, vérifie maintenant si la table existe.
-                                    // Si la table n'existe pas, c'est OK (première ouverture).
-                                    // Si elle existe mais qu'on ne peut pas y accéder, la clé est incorrecte.
-                                    let table_exists = test_conn.query_row(
-                                        "SELECT name FROM sqlite_master WHERE type='table' AND name='file_index'",
-                                        [],
-                                        |row| Ok(row.get::<_, String>(0)?)
-                                    ).is_ok();
-                                    
-                                    if table_exists {
-                                        // La table existe, teste l'accès réel.
-                                        match test_conn.query_row("SELECT COUNT(*) FROM file_index", [], |_| Ok(())) {
-                                            Ok(_) => {
-                                                // La base est valide, on peut l'utiliser.
-                                                log::info!("SqlCipherIndex::open: Existing database opened successfully");
-                                                drop(test_conn);
-                                                return Self::open_existing(db_path_buf, key_hex, &master_key_array);
-                                            }
-                                            Err(e) => {
-                                                // La clé ne correspond pas ou la base est corrompue.
-                                                log::warn!("SqlCipherIndex::open: Database key mismatch (table exists but inaccessible): {}, removing file", e);
-                                                drop(test_conn);
-                                                std::fs::remove_file(&db_path_buf).ok();
-                                            }
-                                        }
-                                    } else {
-                                        // La table n'existe pas encore, mais la base est valide.
-                                        // On peut l'utiliser, le schéma sera créé plus tard.
-                                        log::info!("SqlCipherIndex::open: Existing database opened successfully (table will be created)");
-                                        drop(test_conn);
-                                        return Self::open_existing(db_path_buf, key_hex, &master_key_array);
-                                    }
-                                }
-                                Err(e) => {
-                                    // La clé ne correspond pas ou la base est corrompue.
-                                    log::warn!("SqlCipherIndex::open: Database key mismatch or corruption: {}, removing file", e);
-                                    drop(test_conn);
-                                    std::fs::remove_file(&db_path_buf).ok();
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            // Impossible de configurer la clé.
-                            log::warn!("SqlCipherIndex::open: Failed to set SQLCipher key: {}, removing file", e);
-                            drop(test_conn);
-                            std::fs::remove_file(&db_path_buf).ok();
-                        }
-                    }
-                }
-                Err(e) => {
-                    // Impossible d'ouvrir le fichier, on le supprime.
-                    log::warn!("SqlCipherIndex::open: Failed to open database file: {}, removing", e);
-                    std::fs::remove_file(&db_path_buf).ok();
-                }
-            }
-        } else {
-            log::info!("SqlCipherIndex::open: Database file does not exist, will create new one");
-        }
-
-        // Crée une nouvelle base SQLCipher.
-        let conn = Connection::open(&db_path_buf)?;
-        conn.pragma_update(None, "key", &format!("x'{}'", key_hex))?;
-
-        // Crée le schéma si nécessaire (avec migration pour ajouter HMAC si nécessaire).
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS file_index (
-                id TEXT PRIMARY KEY,
-                logical_path TEXT NOT NULL,
-                encrypted_size INTEGER NOT NULL,
-                hmac BLOB NOT NULL
-            )",
-            [],
-        )?;
-        
-        // Crée un index sur logical_path pour accélérer les recherches par chemin
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_file_index_logical_path ON file_index(logical_path)",
-            [],
-        )?;
-        
-        // Crée la table pour stocker le hash Merkle de l'index.
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS index_metadata (
-                key TEXT PRIMARY KEY,
-                value BLOB NOT NULL
-            )",
-            [],
-        )?;
-        
-        // Crée la table pour la corbeille (suppression temporaire).
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS trash (
-                id TEXT PRIMARY KEY,
-                logical_path TEXT NOT NULL,
-                encrypted_size INTEGER NOT NULL,
-                deleted_at INTEGER NOT NULL,
-                hmac BLOB NOT NULL
-            )",
-            [],
-        )?;
-        
-        // Migration : ajoute le champ HMAC si la table existe sans ce champ.
-        let current_version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap_or(0);
-        if current_version < SCHEMA_VERSION {
-            // Essaie d'ajouter le champ HMAC (peut échouer si déjà présent, c'est OK).
-            conn.execute("ALTER TABLE file_index ADD COLUMN hmac BLOB", []).ok();
-            // Crée la table metadata si elle n'existe pas.
-            conn.execute(
-                "CREATE TABLE IF NOT EXISTS index_metadata (
-                    key TEXT PRIMARY KEY,
-                    value BLOB NOT NULL
-                )",
-                [],
-            ).ok();
-            // Crée la table trash si elle n'existe pas (migration vers version 3).
-            conn.execute(
-                "CREATE TABLE IF NOT EXISTS trash (
-                    id TEXT PRIMARY KEY,
-                    logical_path TEXT NOT NULL,
-                    encrypted_size INTEGER NOT NULL,
-                    deleted_at INTEGER NOT NULL,
-                    hmac BLOB NOT NULL
-                )",
-                [],
-            ).ok();
-        }
-
-        // Enregistre la version du schéma.
-        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
-        
-        // Dérive la clé HMAC depuis la MasterKey.
-        let mut hmac_key = [0u8; HMAC_LEN];
-        hkdf.expand(HMAC_KEY_INFO, &mut hmac_key)
-            .map_err(|_| {
-                log::error!("SqlCipherIndex::open: HMAC key HKDF expansion failed");
-                rusqlite::Error::InvalidQuery
-            })?;
-
-        Ok(Self { conn, hmac_key })
-    }
-
-    /// Ouvre une base SQLCipher existante déjà valide.
-    fn open_existing<P: AsRef<Path>>(db_path: P, key_hex: String, master_key: &[u8; DB_KEY_LEN]) -> SqliteResult<Self> {
-        let conn = Connection::open(db_path)?;
-        conn.pragma_update(None, "key", &format!("x'{}'", key_hex))?;
-        // Vérifie que la base est valide en exécutant une requête simple.
-        conn.query_row("SELECT 1", [], |_| Ok(()))?;
-        
-        // Crée le schéma si nécessaire (au cas où la table n'existerait pas encore).
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS file_index (
-                id TEXT PRIMARY KEY,
-                logical_path TEXT NOT NULL,
-                encrypted_size INTEGER NOT NULL,
-                hmac BLOB NOT NULL
-            )",
-            [],
-        )?;
-        
-        // Crée un index sur logical_path pour accélérer les recherches par chemin
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_file_index_logical_path ON file_index(logical_path)",
-            [],
-        )?;
-        
-        // Crée la table pour stocker le hash Merkle de l'index.
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS index_metadata (
-                key TEXT PRIMARY KEY,
-                value BLOB NOT NULL
-            )",
-            [],
-        )?;
-        
-        // Crée la table pour la corbeille (suppression temporaire).
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS trash (
-                id TEXT PRIMARY KEY,
-                logical_path TEXT NOT NULL,
-                encrypted_size INTEGER NOT NULL,
-                deleted_at INTEGER NOT NULL,
-                hmac BLOB NOT NULL
-            )",
-            [],
-        )?;
-        
-        // Migration : ajoute le champ HMAC si nécessaire.
-        let current_version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap_or(0);
-        if current_version < SCHEMA_VERSION {
-            conn.execute("ALTER TABLE file_index ADD COLUMN hmac BLOB", []).ok();
-            conn.execute(
-                "CREATE TABLE IF NOT EXISTS index_metadata (
-                    key TEXT PRIMARY KEY,
-                    value BLOB NOT NULL
-                )",
-                [],
-            ).ok();
-            // Crée la table trash si elle n'existe pas (migration vers version 3).
-            conn.execute(
-                "CREATE TABLE IF NOT EXISTS trash (
-                    id TEXT PRIMARY KEY,
-                    logical_path TEXT NOT NULL,
-                    encrypted_size INTEGER NOT NULL,
-                    deleted_at INTEGER NOT NULL,
-                    hmac BLOB NOT NULL
-                )",
-                [],
-            ).ok();
-        }
-        
-        // Enregistre la version du schéma.
-        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
-        
-        // Dérive la clé HMAC depuis la MasterKey.
-        let hkdf = Hkdf::<Sha256>::new(None, master_key);
-        let mut hmac_key = [0u8; HMAC_LEN];
-        hkdf.expand(HMAC_KEY_INFO, &mut hmac_key)
-            .map_err(|_| rusqlite::Error::InvalidQuery)?;
-        
-        Ok(Self { conn, hmac_key })
-    }
-    
-    /// Calcule le HMAC-SHA256 d'une entrée de l'index.
-    fn compute_hmac(&self, id: &str, logical_path: &str, encrypted_size: u64) -> [u8; HMAC_LEN] {
-        let mut hasher = Sha256::new();
-        hasher.update(id.as_bytes());
-        hasher.update(logical_path.as_bytes());
-        hasher.update(&encrypted_size.to_le_bytes());
-        hasher.update(&self.hmac_key);
-        hasher.finalize().into()
-    }
-
-    pub fn upsert(&mut self, id: FileId, meta: FileMetadata) -> SqliteResult<()> {
-        // Calcule le HMAC de l'entrée.
-        let hmac = self.compute_hmac(&id, &meta.logical_path, meta.encrypted_size);
-        
-        self.conn.execute(
-            "INSERT OR REPLACE INTO file_index (id, logical_path, encrypted_size, hmac) VALUES (?1, ?2, ?3, ?4)",
-            params![id, meta.logical_path, meta.encrypted_size as i64, hmac.as_slice()],
-        )?;
-        
-        // Met à jour le hash Merkle de l'index.
-        self.update_merkle_root()?;
-        
-        Ok(())
-    }
-
-    pub fn get(&self, id: &FileId) -> SqliteResult<Option<FileMetadata>> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT logical_path, encrypted_size, hmac FROM file_index WHERE id = ?1")?;
-        let mut rows = stmt.query_map([id], |row| {
-            let logical_path: String = row.get(0)?;
-            let encrypted_size: i64 = row.get(1)?;
-            let stored_hmac: Vec<u8> = row.get(2)?;
-            
-            // Vérifie le HMAC.
-            let computed_hmac = self.compute_hmac(id, &logical_path, encrypted_size as u64);
-            if stored_hmac != computed_hmac.as_slice() {
-                return Err(rusqlite::Error::InvalidQuery);
-            }
-            
-            Ok(FileMetadata {
-                logical_path,
-                encrypted_size: encrypted_size as u64,
-            })
-        })?;
-
-        match rows.next() {
-            Some(Ok(meta)) => Ok(Some(meta)),
-            Some(Err(e)) => Err(e),
-            None => Ok(None),
-        }
-    }
-
-    pub fn remove(&mut self, id: &FileId) -> SqliteResult<()> {
-        self.conn
-            .execute("DELETE FROM file_index WHERE id = ?1", [id])?;
-        
-        // Met à jour le hash Merkle de l'index.
-        self.update_merkle_root()?;
-        
-        Ok(())
-    }
-
-    /// Déplace un fichier vers la corbeille (suppression temporaire).
-    pub fn move_to_trash(&mut self, id: &FileId, meta: &FileMetadata) -> SqliteResult<()> {
-        // Calcule le HMAC pour la corbeille.
-        let hmac = self.compute_hmac(id, &meta.logical_path, meta.encrypted_size);
-        
-        // Timestamp Unix (secondes depuis epoch).
-        let deleted_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        
-        // Insère dans la corbeille.
-        self.conn.execute(
-            "INSERT OR REPLACE INTO trash (id, logical_path, encrypted_size, deleted_at, hmac) VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![id, meta.logical_path, meta.encrypted_size as i64, deleted_at, hmac.as_slice()],
-        )?;
-        
-        // Supprime de l'index principal.
-        self.conn.execute("DELETE FROM file_index WHERE id = ?1", [id])?;
-        
-        // Met à jour le hash Merkle de l'index.
-        self.update_merkle_root()?;
-        
-        Ok(())
-    }
-
-    /// Restaure un fichier depuis la corbeille vers l'index principal.
-    pub fn restore_from_trash(&mut self, id: &FileId) -> SqliteResult<FileMetadata> {
-        // Récupère les métadonnées depuis la corbeille (on clone les valeurs nécessaires avant de modifier self).
-        let (logical_path, encrypted_size): (String, u64) = {
-            let mut stmt = self.conn.prepare(
-                "SELECT logical_path, encrypted_size, hmac FROM trash WHERE id = ?1"
-            )?;
-            let mut rows = stmt.query_map([id], |row| {
-                let logical_path: String = row.get(0)?;
-                let encrypted_size: i64 = row.get(1)?;
-                let stored_hmac: Vec<u8> = row.get(2)?;
-                
-                // Vérifie le HMAC.
-                let computed_hmac = self.compute_hmac(id, &logical_path, encrypted_size as u64);
-                if stored_hmac != computed_hmac.as_slice() {
-                    return Err(rusqlite::Error::InvalidQuery);
-                }
-                
-                Ok((logical_path, encrypted_size as u64))
-            })?;
-            
-            match rows.next() {
-                Some(Ok((lp, es))) => (lp, es),
-                Some(Err(e)) => return Err(e),
-                None => return Err(rusqlite::Error::QueryReturnedNoRows),
-            }
-        };
-        
-        let meta = FileMetadata {
-            logical_path: logical_path.clone(),
-            encrypted_size,
-        };
-        
-        // Restaure dans l'index principal.
-        let hmac = self.compute_hmac(id, &logical_path, encrypted_size);
-        self.conn.execute(
-            "INSERT OR REPLACE INTO file_index (id, logical_path, encrypted_size, hmac) VALUES (?1, ?2, ?3, ?4)",
-            params![id, logical_path, encrypted_size as i64, hmac.as_slice()],
-        )?;
-        
-        // Supprime de la corbeille.
-        self.conn.execute("DELETE FROM trash WHERE id = ?1", [id])?;
-        
-        // Met à jour le hash Merkle de l'index.
-        self.update_merkle_root()?;
-        
-        Ok(meta)
-    }
-
-    /// Liste tous les fichiers dans la corbeille.
-    pub fn list_trash(&self) -> SqliteResult<Vec<(FileId, FileMetadata, i64)>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, logical_path, encrypted_size, deleted_at, hmac FROM trash ORDER BY deleted_at DESC"
-        )?;
-        let rows = stmt.query_map([], |row| {
-            let id: String = row.get(0)?;
-            let logical_path: String = row.get(1)?;
-            let encrypted_size: i64 = row.get(2)?;
-            let deleted_at: i64 = row.get(3)?;
-            let stored_hmac: Vec<u8> = row.get(4)?;
-            
-            // Vérifie le HMAC.
-            let computed_hmac = self.compute_hmac(&id, &logical_path, encrypted_size as u64);
-            if stored_hmac != computed_hmac.as_slice() {
-                return Err(rusqlite::Error::InvalidQuery);
-            }
-            
-            Ok((
-                id,
-                FileMetadata {
-                    logical_path,
-                    encrypted_size: encrypted_size as u64,
-                },
-                deleted_at,
-            ))
-        })?;
-        
-        let mut result = Vec::new();
-        for row in rows {
-            result.push(row?);
-        }
-        Ok(result)
-    }
-
-    /// Supprime définitivement un fichier de la corbeille.
-    pub fn remove_from_trash(&mut self, id: &FileId) -> SqliteResult<()> {
-        self.conn.execute("DELETE FROM trash WHERE id = ?1", [id])?;
-        Ok(())
-    }
-
-    /// Vide complètement la corbeille.
-    pub fn empty_trash(&mut self) -> SqliteResult<usize> {
-        let count = self.conn.execute("DELETE FROM trash", [])?;
-        Ok(count)
-    }
-
-    pub fn len(&self) -> SqliteResult<usize> {
-        let count: i64 = self
-            .conn
-            .query_row("SELECT COUNT(*) FROM file_index", [], |row| row.get(0))?;
-        Ok(count as usize)
-    }
-
-    pub fn is_empty(&self) -> SqliteResult<bool> {
-        Ok(self.len()? == 0)
-    }
-
-    /// Liste tous les fichiers de l'index avec vérification HMAC.
-    pub fn list_all(&self) -> SqliteResult<Vec<(FileId, FileMetadata)>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, logical_path, encrypted_size, hmac FROM file_index ORDER BY logical_path",
-        )?;
-        let rows = stmt.query_map([], |row| {
-            let id: String = row.get(0)?;
-            let logical_path: String = row.get(1)?;
-            let encrypted_size: i64 = row.get(2)?;
-            let stored_hmac: Vec<u8> = row.get(3)?;
-            
-            // Vérifie le HMAC.
-            let computed_hmac = self.compute_hmac(&id, &logical_path, encrypted_size as u64);
-            if stored_hmac != computed_hmac.as_slice() {
-                return Err(rusqlite::Error::InvalidQuery);
-            }
-            
-            Ok((
-                id,
-                FileMetadata {
-                    logical_path,
-                    encrypted_size: encrypted_size as u64,
-                },
-            ))
-        })?;
-
-        let mut result = Vec::new();
-        for row in rows {
-            result.push(row?);
-        }
-        Ok(result)
-    }
-
-    /// Calcule et met à jour le hash Merkle de l'index.
-    fn update_merkle_root(&mut self) -> SqliteResult<()> {
-        // Récupère toutes les entrées.
-        let entries = self.list_all()?;
-        
-        // Construit un HashMap pour le Merkle Tree.
-        let mut entries_map = std::collections::HashMap::new();
-        for (id, meta) in entries {
-            entries_map.insert(id, meta);
-        }
-        
-        // Construit l'arbre de Merkle.
-        let tree = MerkleTree::build(&entries_map);
-        let root_hash = tree.root_hash();
-        
-        // Stocke le hash Merkle dans la table metadata.
-        self.conn.execute(
-            "INSERT OR REPLACE INTO index_metadata (key, value) VALUES (?1, ?2)",
-            params!["merkle_root", root_hash.as_slice()],
-        )?;
-        
-        Ok(())
-    }
-
-    /// Vérifie l'intégrité globale de l'index en comparant avec le hash Merkle stocké.
-    pub fn verify_integrity(&self) -> SqliteResult<bool> {
-        // Récupère toutes les entrées.
-        let entries = self.list_all()?;
-        
-        // Construit un HashMap pour le Merkle Tree.
-        let mut entries_map = std::collections::HashMap::new();
-        for (id, meta) in entries {
-            entries_map.insert(id, meta);
-        }
-        
-        // Construit l'arbre de Merkle.
-        let tree = MerkleTree::build(&entries_map);
-        let computed_root = tree.root_hash();
-        
-        // Récupère le hash Merkle stocké.
-        let stored_root: Option<Vec<u8>> = self.conn
-            .query_row(
-                "SELECT value FROM index_metadata WHERE key = ?1",
-                ["merkle_root"],
-                |row| row.get(0),
-            )
-            .ok();
-        
-        match stored_root {
-            Some(stored) if stored.len() == 32 => {
-                let stored_array: [u8; 32] = stored.try_into().unwrap();
-                Ok(computed_root == &stored_array)
-            }
-            _ => {
-                // Pas de hash stocké (index vide ou première utilisation).
-                // Si l'index est vide, c'est OK.
-                Ok(entries_map.is_empty())
-            }
-        }
-    }
-
-    /// Retourne le hash Merkle de l'index (ou None si non calculé).
-    pub fn get_merkle_root(&self) -> SqliteResult<Option<[u8; 32]>> {
-        let stored_root: Option<Vec<u8>> = self.conn
-            .query_row(
-                "SELECT value FROM index_metadata WHERE key = ?1",
-                ["merkle_root"],
-                |row| row.get(0),
-            )
-            .ok();
-        
-        match stored_root {
-            Some(stored) if stored.len() == 32 => {
-                Ok(Some(stored.try_into().unwrap()))
-            }
-            _ => Ok(None),
-        }
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
-
-    #[test]
-    fn sqlcipher_index_roundtrip() {
-        let temp_dir = TempDir::new().unwrap();
-        let db_path = temp_dir.path().join("test.db");
-        let master_key: [u8; 32] = [42u8; 32];
-
-        // Crée l'index et insère une entrée.
-        let mut index = SqlCipherIndex::open(&db_path, &master_key).unwrap();
-        let meta = FileMetadata {
-            logical_path: "/test/file.txt".to_string(),
-            encrypted_size: 1024,
-        };
-        index.upsert("file-1".to_string(), meta.clone()).unwrap();
-
-        // Vérifie que l'entrée est présente.
-        let retrieved = index.get(&"file-1".to_string()).unwrap();
-        assert!(retrieved.is_some());
-        let retrieved_meta = retrieved.unwrap();
-        assert_eq!(retrieved_meta.logical_path, meta.logical_path);
-        assert_eq!(retrieved_meta.encrypted_size, meta.encrypted_size);
-
-        // Vérifie que l'index n'est pas vide.
-        assert_eq!(index.len().unwrap(), 1);
-        assert!(!index.is_empty().unwrap());
-
-        // Supprime l'entrée.
-        index.remove(&"file-1".to_string()).unwrap();
-        assert!(index.get(&"file-1".to_string()).unwrap().is_none());
-        assert_eq!(index.len().unwrap(), 0);
-        assert!(index.is_empty().unwrap());
-    }
-
-    #[test]
-    fn sqlcipher_index_persists_across_sessions() {
-        let temp_dir = TempDir::new().unwrap();
-        let db_path = temp_dir.path().join("persist.db");
-        let master_key: [u8; 32] = [99u8; 32];
-
-        // Première session : crée et insère.
-        {
-            let mut index = SqlCipherIndex::open(&db_path, &master_key).unwrap();
-            index
-                .upsert(
-                    "persist-1".to_string(),
-                    FileMetadata {
-                        logical_path: "/persist/test.txt".to_string(),
-                        encrypted_size: 2048,
-                    },
-                )
-                .unwrap();
-        }
-
-        // Deuxième session : rouvre et vérifie.
-        {
-            let index = SqlCipherIndex::open(&db_path, &master_key).unwrap();
-            let retrieved = index.get(&"persist-1".to_string()).unwrap();
-            assert!(retrieved.is_some());
-            assert_eq!(retrieved.unwrap().logical_path, "/persist/test.txt");
-        }
-    }
-
-    #[test]
-    fn sqlcipher_index_merkle_integrity() {
-        let temp_dir = TempDir::new().unwrap();
-        let db_path = temp_dir.path().join("merkle.db");
-        let master_key: [u8; 32] = [77u8; 32];
-
-        // Crée l'index et insère plusieurs entrées.
-        let mut index = SqlCipherIndex::open(&db_path, &master_key).unwrap();
-        index
-            .upsert(
-                "file-1".to_string(),
-                FileMetadata {
-                    logical_path: "/test/file1.txt".to_string(),
-                    encrypted_size: 1024,
-                },
-            )
-            .unwrap();
-        index
-            .upsert(
-                "file-2".to_string(),
-                FileMetadata {
-                    logical_path: "/test/file2.txt".to_string(),
-                    encrypted_size: 2048,
-                },
-            )
-            .unwrap();
-
-        // Vérifie l'intégrité Merkle.
-        assert!(index.verify_integrity().unwrap());
-
-        // Vérifie que le hash Merkle est stocké.
-        let root_hash = index.get_merkle_root().unwrap();
-        assert!(root_hash.is_some());
-        assert_eq!(root_hash.unwrap().len(), 32);
-
-        // Modifie une entrée et vérifie que l'intégrité échoue.
-        // Note: On ne peut pas modifier directement via SQL car le HMAC serait invalide.
-        // Mais on peut tester en recalculant après une modification manuelle.
-        // Pour ce test, on supprime et réinsère avec des données différentes.
-        index.remove(&"file-1".to_string()).unwrap();
-        index
-            .upsert(
-                "file-1".to_string(),
-                FileMetadata {
-                    logical_path: "/test/file1-modified.txt".to_string(),
-                    encrypted_size: 1024,
-                },
-            )
-            .unwrap();
-
-        // L'intégrité doit toujours être valide après la mise à jour.
-        assert!(index.verify_integrity().unwrap());
-    }
-}
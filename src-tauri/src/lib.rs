@@ -1,18 +1,73 @@
-pub mod crypto;
-pub mod index;
-pub mod storage;
-pub mod storj;
+pub mod error;
+pub mod events;
+pub mod logging;
+pub mod notifications;
+pub mod password_hint;
+pub mod permissions;
+pub mod picker;
+pub mod preview;
+pub mod telemetry;
+pub mod tray;
 
-use crate::crypto::{CryptoCore, KeyHierarchy, MasterKey, MkekCiphertext, PasswordSecret};
-use crate::index::{sqlcipher::SqlCipherIndex, FileMetadata};
+pub use aether_core::{activity, backup, bundle, cache, clock, clone, crash, crypto, delegation, diagnostics, downloads, dry_run, export, guest, health, hooks, index, integrity, jobs, journal, keychain, locks, logstore, mime, paging, photos, policies, preflight, quarantine, quota, reauth, recovery, remote_keys, rules, search, sessions, setup, share, snapshot, staging, storage, storj, summaries, tombstones, transfers, vault, webdav, workspace};
+
+use crate::activity::ActivitySummary;
+use crate::backup::{BackupPolicy, BackupReport, BackupState, IndexBackup};
+use crate::cache::policy::CachePolicy;
+use crate::cache::BlobCache;
+use crate::clone::{CloneReport, CloneTarget, CLONE_STEPS};
+use crate::crash::CrashLog;
+use crate::crypto::{CryptoCore, CryptoError, KeyHierarchy, Keyfile, MasterKey, MkekCiphertext, PasswordSecret};
+use crate::downloads::ResumableDownloads;
+use crate::dry_run::DryRunReport;
+use crate::error::CommandError;
+use crate::events::{AppEvent, SyncStatus, TransferDirection, TransferReporter};
+use crate::export::report::{self, ReportFormat};
+use crate::export::{archive, ExportManifest};
+use crate::index::{
+    cache::IndexCache,
+    sqlcipher::{self, IndexKeys, SqlCipherIndex},
+    writer::IndexWriter,
+    AliasEntry, FileId, FileMetadata, FileVersion, FolderAppearance, PendingKind, SnapshotInfo,
+    SnapshotRestore, TrashedFolder,
+};
+use crate::diagnostics::{DiagnosticsReport, DiagnosticsTarget};
+use crate::integrity::{IntegrityReport, IntegrityTimeline};
+use crate::quarantine::QuarantinedFile;
+use crate::jobs::{Job, JobRegistry};
+use crate::guest::GuestEntry;
+use crate::health::HealthReport;
+use crate::hooks::{Hook, HookEvent, HookPayload, HookTarget};
+use crate::snapshot::{DiffEntry, MovedEntry};
+use crate::keychain::{KeychainStore, StorjCredentials};
+use crate::paging::{Page, PageRequest};
+use crate::permissions::Elevations;
+use crate::policies::FolderPolicy;
+use crate::preflight::{PreflightReport, UploadCandidate};
+use crate::preview::{PreviewKind, PreviewPayload, PreviewPlan, PreviewSessions, Vetted};
+use crate::quota::{QuotaCheck, QuotaStatus};
+use crate::rules::{Rule, RuleAction, RulesReport};
+use crate::search::{extract, ContentIndex, SearchHit};
+use crate::setup::{SetupStep, SetupWizard};
+use crate::share::ShareLink;
+use crate::staging::Staging;
 use crate::storage::aether_format::AetherFile;
-use crate::storj::{StorjClient, StorjConfig};
+use crate::storage::{EncryptionDetails, StorageError};
+use crate::storj::{StorjClient, StorjConfig, StorjError};
+use crate::summaries::{OperationSummary, SyncRun};
+use crate::transfers::{TransferPriority, TransferQueue};
+use crate::vault::{CacheReport, DownloadVerification, FlushReport, FolderRenameReport, PurgeReport, Vault, RENAME_STEPS};
+use crate::webdav::{WebDavServer, WEBDAV_USER};
+use crate::workspace::{Workspace, WorkspaceError, WorkspaceRegistry};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio::sync::Mutex as AsyncMutex;
+use tauri::ipc::Channel;
 use tauri::{Manager, State};
+use tauri_plugin_dialog::FilePath;
 use rand::RngCore;
 
 #[derive(Debug, Serialize)]
@@ -26,6 +81,13 @@ pub struct MkekUnlockRequest {
     pub password: String,
     pub password_salt: [u8; 16],
     pub mkek: MkekCiphertext,
+    /// Confie la KEK au trousseau de l'appareil : les déverrouillages suivants se passent
+    /// du mot de passe et d'Argon2 (voir `crypto_unlock_device`).
+    #[serde(default)]
+    pub remember_device: bool,
+    /// Fichier clé (chemin ou URI renvoyé par `keyfile_select`), pour un coffre qui en exige un.
+    #[serde(default)]
+    pub keyfile_path: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -34,6 +96,9 @@ pub struct ChangePasswordRequest {
     pub new_password: String,
     pub old_password_salt: [u8; 16],
     pub old_mkek: MkekCiphertext,
+    /// Fichier clé du coffre ; il reste exigé avec le nouveau mot de passe.
+    #[serde(default)]
+    pub keyfile_path: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -46,30 +111,87 @@ pub struct ChangePasswordResponse {
 struct AppState {
     master_key: Mutex<Option<MasterKey>>,
     storj_client: AsyncMutex<Option<Arc<StorjClient>>>,
+    /// Identifiants délégués du client en cours, renouvelés avant leur expiration
+    /// (`None` avec des clés racines).
+    delegated_credentials: AsyncMutex<Option<StorjCredentials>>,
+    /// Serveur WebDAV local (opt-in), arrêté au verrouillage.
+    webdav: AsyncMutex<Option<WebDavServer>>,
+    /// Passe de reprise à lancer dès que le client Storj est prêt (armée au déverrouillage).
+    recovery_pending: AtomicBool,
+    /// Confirmations natives en attente pour les commandes sensibles.
+    elevations: Elevations,
+    /// Synchronisation suspendue depuis la barre système : les envois partent en file d'attente.
+    sync_paused: AtomicBool,
+    /// Dernier lien de partage ouvert, en attente de l'accord de l'utilisateur.
+    pending_share: Mutex<Option<ShareLink>>,
+    /// Clés de l'index descellées par la phrase d'accès invité (métadonnées seulement).
+    guest_keys: Mutex<Option<IndexKeys>>,
+    /// File d'écriture de l'index de l'espace actif (avec son chemin), démarrée à la demande.
+    index_writer: Mutex<Option<(PathBuf, IndexWriter)>>,
+    /// Cache des lectures fréquentes de l'index de l'espace actif (avec son chemin).
+    index_cache: Mutex<Option<(PathBuf, Arc<IndexCache>)>>,
+    /// Réglages d'un paquet de configuration importé (avec l'empreinte du coffre), appliqués
+    /// au premier déverrouillage de ce coffre.
+    pending_settings: Mutex<Option<(String, Vec<(String, Vec<u8>)>)>>,
+    /// File commune des transferts : les interactifs passent devant les transferts de masse.
+    transfers: Arc<TransferQueue>,
 }
 
-/// Obtient le chemin de la base de données SQLCipher dans le répertoire de données de l'app.
-fn get_db_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+/// Registre des espaces de travail, chargé au démarrage.
+struct Workspaces(Mutex<WorkspaceRegistry>);
+
+/// Obtient le chemin de la base de données SQLCipher de l'espace de travail actif.
+///
+/// Le cache local et la zone de transit sont rangés à côté : ils suivent l'espace actif.
+fn get_db_path(app: &tauri::AppHandle) -> Result<PathBuf, CommandError> {
     let app_data = app
         .path()
         .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    fs::create_dir_all(&app_data).map_err(|e| format!("Failed to create app data dir: {}", e))?;
-    Ok(app_data.join("index.db"))
+        .map_err(|e| CommandError::io("Failed to get app data dir", e))?;
+    fs::create_dir_all(&app_data).map_err(|e| CommandError::io("Failed to create app data dir", e))?;
+    let Some(workspaces) = app.try_state::<Workspaces>() else {
+        return Ok(app_data.join("index.db"));
+    };
+    let db_path = {
+        let registry = workspaces.0.lock().map_err(|e| CommandError::internal("Lock error", e))?;
+        registry.index_path(&registry.active().id)
+    };
+    if let Some(parent) = db_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| CommandError::io("Failed to create workspace dir", e))?;
+    }
+    Ok(db_path)
+}
+
+/// Identifiant de l'espace de travail actif (celui par défaut si le registre est indisponible).
+fn active_workspace_id(app: &tauri::AppHandle) -> String {
+    app.try_state::<Workspaces>()
+        .and_then(|workspaces| workspaces.0.lock().ok().map(|registry| registry.active().id.clone()))
+        .unwrap_or_else(|| workspace::DEFAULT_WORKSPACE.to_string())
+}
+
+fn with_workspaces<T>(
+    app: &tauri::AppHandle,
+    f: impl FnOnce(&mut WorkspaceRegistry) -> Result<T, WorkspaceError>,
+) -> Result<T, CommandError> {
+    let workspaces = app
+        .try_state::<Workspaces>()
+        .ok_or_else(|| CommandError::Internal("Workspace registry unavailable".to_string()))?;
+    let mut registry = workspaces.0.lock().map_err(|e| CommandError::internal("Lock error", e))?;
+    Ok(f(&mut registry)?)
 }
 
 /// Ouvre l'index SQLCipher en utilisant la MasterKey stockée dans l'état global.
 fn open_index_with_state(
     app: &tauri::AppHandle,
     state: &State<'_, AppState>,
-) -> Result<SqlCipherIndex, String> {
+) -> Result<SqlCipherIndex, CommandError> {
     let master_key_guard = state
         .master_key
         .lock()
-        .map_err(|e| format!("Lock error: {}", e))?;
+        .map_err(|e| CommandError::internal("Lock error", e))?;
     let master_key = master_key_guard
         .as_ref()
-        .ok_or_else(|| "MasterKey not available. Unlock the vault first.".to_string())?;
+        .ok_or(CommandError::VaultLocked)?;
 
     let db_path = get_db_path(app)?;
     let master_key_bytes = master_key.as_bytes();
@@ -80,18 +202,216 @@ fn open_index_with_state(
     SqlCipherIndex::open(&db_path, master_key_bytes)
         .map_err(|e| {
             log::error!("open_index_with_state: Failed to open SQLCipher index: {}", e);
-            format!("Failed to open SQLCipher index: {}", e)
+            if sqlcipher::is_key_mismatch(&e) {
+                return CommandError::IndexKeyMismatch;
+            }
+            CommandError::index("Failed to open SQLCipher index", e)
         })
 }
 
+/// File d'écriture unique de l'index actif : les commandes qui modifient l'index y passent
+/// pour ne pas s'entrelacer. Redémarrée si l'espace de travail a changé.
+fn index_writer(app: &tauri::AppHandle, state: &AppState) -> Result<IndexWriter, CommandError> {
+    let db_path = get_db_path(app)?;
+    let mut writer_guard = state
+        .index_writer
+        .lock()
+        .map_err(|e| CommandError::internal("Lock error", e))?;
+    if let Some((path, writer)) = writer_guard.as_ref() {
+        if *path == db_path {
+            return Ok(writer.clone());
+        }
+    }
+    let writer = {
+        let master_key_guard = state
+            .master_key
+            .lock()
+            .map_err(|e| CommandError::internal("Lock error", e))?;
+        let master_key = master_key_guard.as_ref().ok_or(CommandError::VaultLocked)?;
+        IndexWriter::spawn(&db_path, master_key.as_bytes())?
+    };
+    *writer_guard = Some((db_path, writer.clone()));
+    Ok(writer)
+}
+
+/// Cache des lectures de l'index de l'espace actif, remplacé quand l'espace change.
+fn index_cache(app: &tauri::AppHandle, state: &AppState) -> Result<Arc<IndexCache>, CommandError> {
+    let db_path = get_db_path(app)?;
+    let mut cache_guard = state
+        .index_cache
+        .lock()
+        .map_err(|e| CommandError::internal("Lock error", e))?;
+    if let Some((path, cache)) = cache_guard.as_ref() {
+        if *path == db_path {
+            return Ok(Arc::clone(cache));
+        }
+    }
+    let cache = Arc::new(IndexCache::new());
+    *cache_guard = Some((db_path, Arc::clone(&cache)));
+    Ok(cache)
+}
+
+/// Oublie les lectures gardées de l'index : le compteur de modifications d'une base supprimée
+/// ou remplacée repart de zéro et pourrait retomber sur celui des lectures gardées.
+fn forget_index_cache(state: &AppState) {
+    if let Ok(mut cache) = state.index_cache.lock() {
+        *cache = None;
+    }
+}
+
+/// Cache local des blobs chiffrés (lecture hors ligne et envois différés).
+fn open_blob_cache(app: &tauri::AppHandle) -> Result<BlobCache, CommandError> {
+    let db_path = get_db_path(app)?;
+    let cache_dir = db_path.with_file_name("cache");
+    BlobCache::new(cache_dir).map_err(|e| CommandError::io("Failed to open local cache", e))
+}
+
+/// Zone de transit des envois, dans le dossier temporaire balayé par la passe de reprise.
+fn open_staging(app: &tauri::AppHandle) -> Result<Staging, CommandError> {
+    let db_path = get_db_path(app)?;
+    let staging_dir = db_path.with_file_name(recovery::SCRATCH_DIR);
+    Staging::new(staging_dir).map_err(|e| CommandError::io("Failed to open staging area", e))
+}
+
+/// Téléchargements partiels reprenables, conservés d'une session à l'autre.
+fn open_downloads(app: &tauri::AppHandle) -> Result<ResumableDownloads, CommandError> {
+    let db_path = get_db_path(app)?;
+    let downloads_dir = db_path.with_file_name("downloads");
+    ResumableDownloads::new(downloads_dir).map_err(|e| CommandError::io("Failed to open partial downloads", e))
+}
+
+/// Construit la façade [`Vault`] (avec cache local et zone de transit) depuis l'état déverrouillé.
+async fn vault_from_state(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+) -> Result<Vault, CommandError> {
+    // Les identifiants délégués proches de l'expiration sont remplacés avant tout transfert
+    if let Err(e) = renew_credentials(app, state, false).await {
+        log::warn!("Failed to renew delegated storage credentials: {}", e);
+    }
+    let client = {
+        let client_guard = state.storj_client.lock().await;
+        client_guard.clone()
+            .ok_or(CommandError::StorjNotConfigured)?
+    };
+    let master_key = get_master_key_from_state(state.clone())?;
+    Ok(Vault::new(master_key, get_db_path(app)?, client)
+        .with_cache(open_blob_cache(app)?)
+        .with_staging(open_staging(app)?)
+        .with_downloads(open_downloads(app)?)
+        .with_transfers(Arc::clone(&state.transfers))
+        .with_index_cache(index_cache(app, state)?))
+}
+
+/// Compte une ouverture pour la politique du cache ; un échec n'interrompt pas la commande.
+fn note_access(vault: &Vault, file_id: &FileId) {
+    if let Err(e) = vault.record_access(file_id) {
+        log::warn!("Failed to record access to {}: {}", file_id, e);
+    }
+}
+
+/// Déclenche en tâche de fond les hooks abonnés à l'événement, coffre déverrouillé seulement
+/// (leurs définitions sont dans l'index chiffré).
+///
+/// Un hook qui échoue est consigné sur sa définition, sans interrompre l'émetteur.
+pub(crate) fn fire_hooks(app: &tauri::AppHandle, event: &AppEvent) {
+    let Some(hook_event) = event.hook_event() else {
+        return;
+    };
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    if !state.master_key.lock().map(|key| key.is_some()).unwrap_or(false) {
+        return;
+    }
+    let subscribers = match open_index_with_state(app, &state).map(|index| hooks::subscribers(&index, hook_event)) {
+        Ok(Ok(subscribers)) => subscribers,
+        Ok(Err(e)) => {
+            log::warn!("Failed to list hooks: {}", e);
+            return;
+        }
+        Err(e) => {
+            log::warn!("Failed to open index for hooks: {}", e);
+            return;
+        }
+    };
+    if subscribers.is_empty() {
+        return;
+    }
+    let data = serde_json::to_value(event)
+        .ok()
+        .and_then(|mut json| json.get_mut("payload").map(serde_json::Value::take))
+        .unwrap_or(serde_json::Value::Null);
+    let payload = HookPayload::new(hook_event, data);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let results = hooks::deliver_all(&subscribers, &payload).await;
+        let state = app.state::<AppState>();
+        let recorded = open_index_with_state(&app, &state)
+            .and_then(|mut index| hooks::record_results(&mut index, &results).map_err(CommandError::from));
+        if let Err(e) = recorded {
+            log::warn!("Failed to record hook results: {}", e);
+        }
+    });
+}
+
+/// Lance la passe de reprise en tâche de fond si elle est armée (une fois par déverrouillage),
+/// puis compare la racine Merkle locale à celles publiées par les autres appareils.
+///
+/// Appelée dès que le client Storj est configuré : la reprise a besoin du stockage distant.
+fn spawn_recovery_if_pending(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    if !state.recovery_pending.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        let result = async {
+            let vault = vault_from_state(&app, &state).await?.with_priority(TransferPriority::Bulk);
+            let scratch_dir = get_db_path(&app)?.with_file_name(recovery::SCRATCH_DIR);
+            Ok::<_, CommandError>(recovery::run(&vault, &scratch_dir).await?)
+        }
+        .await;
+        match result {
+            Ok(report) => events::emit(&app, AppEvent::Recovery {
+                removed_temp_files: report.removed_temp_files,
+                aborted_multipart_uploads: report.aborted_multipart_uploads,
+                resumed_uploads: report.resumed_uploads,
+                dropped_uploads: report.dropped_uploads,
+                remaining_pending: report.remaining_pending,
+            }),
+            Err(e) => log::warn!("Recovery pass failed: {}", e),
+        }
+
+        let check = async {
+            let vault = vault_from_state(&app, &state).await?;
+            Ok::<_, CommandError>(journal::roots::check_peers(&vault).await?)
+        }
+        .await;
+        match check {
+            Ok(check) if !check.diverged.is_empty() => events::emit(&app, AppEvent::IndexDiverged {
+                devices: check.diverged.into_iter().map(|divergence| divergence.device).collect(),
+            }),
+            Ok(_) => {}
+            Err(e) => log::warn!("Merkle root check failed: {}", e),
+        }
+    });
+}
+
 #[tauri::command]
 fn crypto_bootstrap(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
     password: String,
-) -> Result<MkekBootstrapResponse, String> {
+    hint: Option<String>,
+) -> Result<MkekBootstrapResponse, CommandError> {
     log::info!("Starting crypto_bootstrap");
 
+    // Valide l'indice avant tout : c'est le seul moment où le mot de passe est connu en même temps
+    let hint = password_hint::validate(hint.as_deref(), Some(&password))
+        .map_err(|reason| CommandError::InvalidHint { reason })?;
+
     let core = CryptoCore::default();
     let password_secret = PasswordSecret::new(password);
     let salt = core.random_password_salt();
@@ -99,13 +419,13 @@ fn crypto_bootstrap(
 
     let hierarchy = KeyHierarchy::bootstrap(&password_secret, salt).map_err(|e| {
         log::error!("KeyHierarchy::bootstrap failed: {}", e);
-        e.to_string()
+        CommandError::from(e)
     })?;
     log::info!("KeyHierarchy bootstrapped successfully");
 
     let mkek = hierarchy.seal_master_key().map_err(|e| {
         log::error!("seal_master_key failed: {}", e);
-        e.to_string()
+        CommandError::from(e)
     })?;
     log::info!("Master key sealed into MKEK");
 
@@ -114,14 +434,14 @@ fn crypto_bootstrap(
         log::error!("get_db_path failed: {}", e);
         e
     })?;
-    log::info!("Database path: {}", db_path.to_string_lossy());
+    log::debug!("Database path: {}", db_path.to_string_lossy());
 
     let master_key_bytes = hierarchy.master_key().as_bytes();
     if master_key_bytes.len() != 32 {
-        let err = format!(
+        let err = CommandError::Internal(format!(
             "MasterKey length is {} instead of 32",
             master_key_bytes.len()
-        );
+        ));
         log::error!("{}", err);
         return Err(err);
     }
@@ -136,22 +456,42 @@ fn crypto_bootstrap(
         } else {
             log::info!("Bootstrap: Old database file removed successfully");
         }
+        forget_index_cache(&state);
+        if let Err(e) = search::discard(&db_path) {
+            log::warn!("Bootstrap: Failed to remove the old content index: {}", e);
+        }
     }
 
-    SqlCipherIndex::open(&db_path, master_key_bytes).map_err(|e| {
+    let mut index = SqlCipherIndex::open(&db_path, master_key_bytes).map_err(|e| {
         log::error!("SqlCipherIndex::open failed: {}", e);
-        format!("Failed to open SQLCipher index: {}", e)
+        CommandError::index("Failed to open SQLCipher index", e)
     })?;
     log::info!("SQLCipher index opened successfully");
 
+    // Le nouveau coffre remplace l'ancien : son empreinte est celle attendue au déverrouillage
+    let fingerprint = hierarchy.master_key().fingerprint();
+    index.set_vault_fingerprint(&fingerprint)?;
+    drop(index);
+    if let Err(e) = with_workspaces(&app, |registry| registry.set_vault_fingerprint(&fingerprint)) {
+        log::warn!("Failed to record vault fingerprint in the workspace registry: {}", e);
+    }
+
     // Stocke la MasterKey dans l'état global pour les opérations d'index ultérieures.
     let mut master_key_guard = state
         .master_key
         .lock()
-        .map_err(|e| format!("Lock error: {}", e))?;
+        .map_err(|e| CommandError::internal("Lock error", e))?;
     let master_key_bytes_vec = hierarchy.master_key().as_bytes().to_vec();
     *master_key_guard = Some(crate::crypto::MasterKey::from_vec(master_key_bytes_vec));
+    // Le déverrouillage complet remplace le mode invité
+    if let Ok(mut guest_keys) = state.guest_keys.lock() {
+        *guest_keys = None;
+    }
     log::info!("MasterKey stored in AppState");
+    state.elevations.remember_password(salt, &mkek, None);
+
+    password_hint::save(&hint_path(&app)?, hint.as_deref())
+        .map_err(|e| CommandError::io("Failed to save password hint", e))?;
 
     Ok(MkekBootstrapResponse {
         password_salt: salt,
@@ -159,23 +499,308 @@ fn crypto_bootstrap(
     })
 }
 
+fn hint_path(app: &tauri::AppHandle) -> Result<PathBuf, CommandError> {
+    Ok(get_db_path(app)?.with_file_name(password_hint::HINT_FILE))
+}
+
+/// Indice de mot de passe, lisible coffre verrouillé (écran de déverrouillage)
+#[tauri::command]
+fn get_password_hint(app: tauri::AppHandle) -> Result<Option<String>, CommandError> {
+    password_hint::load(&hint_path(&app)?).map_err(|e| CommandError::io("Failed to read password hint", e))
+}
+
+/// Remplace (ou retire, avec `None`) l'indice de mot de passe ; réservé au coffre déverrouillé
+#[tauri::command]
+fn set_password_hint(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    hint: Option<String>,
+) -> Result<(), CommandError> {
+    get_master_key_from_state(state)?;
+    let hint = password_hint::validate(hint.as_deref(), None)
+        .map_err(|reason| CommandError::InvalidHint { reason })?;
+    password_hint::save(&hint_path(&app)?, hint.as_deref())
+        .map_err(|e| CommandError::io("Failed to save password hint", e))?;
+    log::info!("Password hint {}", if hint.is_some() { "updated" } else { "removed" });
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct GuestModeStatus {
+    /// Une phrase d'accès invité est définie pour l'espace actif.
+    pub enabled: bool,
+    /// Le mode invité est ouvert (métadonnées lisibles, contenu verrouillé).
+    pub active: bool,
+}
+
+#[tauri::command]
+fn guest_mode_status(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<GuestModeStatus, CommandError> {
+    let active = state
+        .guest_keys
+        .lock()
+        .map_err(|e| CommandError::internal("Lock error", e))?
+        .is_some();
+    Ok(GuestModeStatus {
+        enabled: guest::is_enabled(&get_db_path(&app)?),
+        active,
+    })
+}
+
+/// Définit (ou remplace) la phrase d'accès du mode invité ; réservé au coffre déverrouillé.
+#[tauri::command]
+fn guest_mode_enable(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    passphrase: String,
+) -> Result<(), CommandError> {
+    telemetry::record_feature("guest_mode_enable");
+    let master_key = get_master_key_from_state(state)?;
+    guest::enable(&get_db_path(&app)?, &master_key, &PasswordSecret::new(passphrase))?;
+    Ok(())
+}
+
+#[tauri::command]
+fn guest_mode_disable(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), CommandError> {
+    get_master_key_from_state(state)?;
+    guest::disable(&get_db_path(&app)?)?;
+    log::info!("Guest mode disabled");
+    Ok(())
+}
+
+/// Ouvre le mode invité : l'index devient consultable sans que la MasterKey soit dérivée.
+#[tauri::command]
+fn guest_mode_open(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    passphrase: String,
+) -> Result<(), CommandError> {
+    telemetry::record_feature("guest_mode_open");
+    let db_path = get_db_path(&app)?;
+    let keys = guest::unseal(&db_path, &PasswordSecret::new(passphrase))?;
+    // Vérifie que les clés ouvrent bien l'index actuel
+    guest::open_index(&db_path, &keys)?;
+    *state.guest_keys.lock().map_err(|e| CommandError::internal("Lock error", e))? = Some(keys);
+    log::info!("Guest mode opened");
+    Ok(())
+}
+
+#[tauri::command]
+fn guest_mode_close(state: State<'_, AppState>) -> Result<(), CommandError> {
+    *state.guest_keys.lock().map_err(|e| CommandError::internal("Lock error", e))? = None;
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct ContentSearchStatus {
+    pub enabled: bool,
+    /// Fichiers dont le texte est indexé.
+    pub indexed_files: usize,
+}
+
+fn content_search_status_of(app: &tauri::AppHandle, state: &State<'_, AppState>) -> Result<ContentSearchStatus, CommandError> {
+    let enabled = search::enabled(&open_index_with_state(app, state)?)?;
+    let db_path = get_db_path(app)?;
+    let indexed_files = if enabled && search::content_db_path(&db_path).exists() {
+        ContentIndex::open(&db_path, get_master_key_from_state(state.clone())?.as_bytes())?.len()?
+    } else {
+        0
+    };
+    Ok(ContentSearchStatus { enabled, indexed_files })
+}
+
+/// État de la recherche dans le contenu des fichiers.
+#[tauri::command]
+fn content_search_status(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ContentSearchStatus, CommandError> {
+    content_search_status_of(&app, &state)
+}
+
+/// Active la recherche dans le contenu (fichiers envoyés ensuite) ; la désactiver efface le
+/// texte déjà indexé.
+#[tauri::command]
+async fn content_search_set_enabled(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<ContentSearchStatus, CommandError> {
+    log::info!("content_search_set_enabled called: enabled={}", enabled);
+    telemetry::record_feature("content_search");
+    let db_path = get_db_path(&app)?;
+    let writer = index_writer(&app, &state)?;
+    writer
+        .write(move |index| search::set_enabled(index, &db_path, enabled))
+        .await?
+        .map_err(|e| CommandError::io("Failed to update content search", e))?;
+    content_search_status_of(&app, &state)
+}
+
+/// Mode clés distantes aléatoires : les objets envoyés ne portent plus l'UUID de leur entrée.
+#[tauri::command]
+fn remote_keys_status(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<bool, CommandError> {
+    Ok(remote_keys::enabled(&open_index_with_state(&app, &state)?)?)
+}
+
+/// Active ou désactive les clés distantes aléatoires, pour les envois suivants.
+#[tauri::command]
+async fn remote_keys_set_enabled(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<bool, CommandError> {
+    log::info!("remote_keys_set_enabled called: enabled={}", enabled);
+    telemetry::record_feature("remote_keys");
+    let writer = index_writer(&app, &state)?;
+    writer.write(move |index| remote_keys::set_enabled(index, enabled)).await??;
+    Ok(enabled)
+}
+
+/// Objets du bucket rangés sous une clé inattendue (UUID avec tirets des anciennes versions).
+#[tauri::command]
+async fn remote_keys_audit(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<remote_keys::KeyAudit, CommandError> {
+    log::info!("remote_keys_audit called");
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(remote_keys::repair(&vault, false).await?)
+}
+
+/// Renomme côté serveur les objets relevés par `remote_keys_audit` et met l'index à jour.
+#[tauri::command]
+async fn remote_keys_repair(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<remote_keys::KeyAudit, CommandError> {
+    log::info!("remote_keys_repair called");
+    telemetry::record_feature("remote_keys_repair");
+    let vault = vault_from_state(&app, &state).await?;
+    let report = remote_keys::repair(&vault, true).await?;
+    log::info!(
+        "Remote keys repaired: repaired={}, duplicates={}, unrecognized={}",
+        report.repaired,
+        report.duplicates.len(),
+        report.unrecognized.len()
+    );
+    Ok(report)
+}
+
+/// Fichiers dont le nom, ou le contenu si la recherche dans le contenu est activée,
+/// correspond à la requête ; par pages.
+#[tauri::command]
+fn search_files(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    query: String,
+    page: Option<PageRequest>,
+) -> Result<Page<SearchHit>, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    let mut content = if search::enabled(&index)? {
+        Some(ContentIndex::open(&get_db_path(&app)?, get_master_key_from_state(state.clone())?.as_bytes())?)
+    } else {
+        None
+    };
+    let hits = search::search(&index, content.as_mut(), &query)?;
+    Ok(paging::paginate(hits, &page.unwrap_or_default())?)
+}
+
+/// Recherche par chemin dans l'index, en mode invité (aucun contenu n'est accessible).
+#[tauri::command]
+fn guest_mode_search(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    query: String,
+    page: Option<PageRequest>,
+) -> Result<Page<GuestEntry>, CommandError> {
+    let keys = state
+        .guest_keys
+        .lock()
+        .map_err(|e| CommandError::internal("Lock error", e))?
+        .clone()
+        .ok_or(CommandError::VaultLocked)?;
+    let index = guest::open_index(&get_db_path(&app)?, &keys)?;
+    Ok(paging::paginate(guest::search(&index, &query)?, &page.unwrap_or_default())?)
+}
+
 #[tauri::command]
-fn get_index_db_path(app: tauri::AppHandle) -> Result<String, String> {
+fn get_index_db_path(app: tauri::AppHandle) -> Result<String, CommandError> {
     let db_path = get_db_path(&app)?;
     Ok(db_path.to_string_lossy().to_string())
 }
 
 /// Supprime la base de données locale (utile en cas de conflit avec Wayne).
+///
+/// Avec `dry_run`, rien n'est supprimé : la commande décrit les fichiers qui le seraient.
 #[tauri::command]
-fn reset_local_database(app: tauri::AppHandle) -> Result<(), String> {
+fn reset_local_database(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    dry_run: Option<bool>,
+) -> Result<CommandOutcome<()>, CommandError> {
     let db_path = get_db_path(&app)?;
+    if dry_run == Some(true) {
+        return Ok(CommandOutcome::DryRun(dry_run::reset_local(&db_path)));
+    }
     if db_path.exists() {
-        std::fs::remove_file(&db_path).map_err(|e| {
-            format!("Failed to remove database file: {}", e)
-        })?;
+        std::fs::remove_file(&db_path)
+            .map_err(|e| CommandError::io("Failed to remove database file", e))?;
         log::info!("Local database file removed successfully");
     }
-    Ok(())
+    forget_index_cache(&state);
+    // Les clés scellées d'une base supprimée ne serviraient plus à rien, ni son texte indexé
+    guest::disable(&db_path)?;
+    search::discard(&db_path).map_err(|e| CommandError::io("Failed to remove content index", e))?;
+    Ok(CommandOutcome::Applied(()))
+}
+
+/// Récupération d'une base que la MKEK de Wayne n'ouvre pas : déverrouille avec un autre
+/// coffre (celui créé sur cet appareil, par exemple), seulement si la base est bien la sienne.
+#[tauri::command]
+async fn index_open_with_vault(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    req: MkekUnlockRequest,
+) -> Result<(), CommandError> {
+    let (hierarchy, keyfile) = restore_hierarchy(&app, &req).await?;
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Err(CommandError::PathNotFound { path: db_path.to_string_lossy().to_string() });
+    }
+    if !SqlCipherIndex::key_matches(&db_path, hierarchy.master_key().as_bytes())? {
+        return Err(CommandError::IndexKeyMismatch);
+    }
+    state.elevations.remember_password(req.password_salt, &req.mkek, keyfile.as_ref());
+    log::info!("Local index opened with another vault ({})", hierarchy.master_key().fingerprint());
+    finish_unlock(&app, &state, &hierarchy)
+}
+
+/// Met de côté la base que la MKEK reçue n'ouvre pas, puis déverrouille avec une base neuve.
+///
+/// Rien n'est supprimé : l'archive pourra être rouverte avec le coffre auquel elle appartient.
+/// Retourne son chemin, ou `None` si la base s'ouvrait déjà (rien à archiver).
+#[tauri::command]
+async fn index_archive_and_recreate(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    req: MkekUnlockRequest,
+) -> Result<Option<String>, CommandError> {
+    let (hierarchy, keyfile) = restore_hierarchy(&app, &req).await?;
+    let db_path = get_db_path(&app)?;
+    let mut archived = None;
+    if db_path.exists() && !SqlCipherIndex::key_matches(&db_path, hierarchy.master_key().as_bytes())? {
+        let archive = SqlCipherIndex::archive_unreadable(&db_path)
+            .map_err(|e| CommandError::io("Failed to archive local database", e))?;
+        forget_index_cache(&state);
+        // Les clés invité scellées pour l'ancienne base ne l'ouvriraient plus ; son texte
+        // indexé, chiffré sous l'ancienne clé, non plus
+        guest::disable(&db_path)?;
+        search::discard(&db_path).map_err(|e| CommandError::io("Failed to remove content index", e))?;
+        archived = Some(archive.to_string_lossy().to_string());
+    }
+    state.elevations.remember_password(req.password_salt, &req.mkek, keyfile.as_ref());
+    finish_unlock(&app, &state, &hierarchy)?;
+    Ok(archived)
 }
 
 #[derive(Debug, Serialize)]
@@ -186,10 +811,11 @@ pub struct IndexStatus {
 }
 
 #[tauri::command]
-fn get_index_status(app: tauri::AppHandle, req: MkekUnlockRequest) -> Result<IndexStatus, String> {
+fn get_index_status(app: tauri::AppHandle, req: MkekUnlockRequest) -> Result<IndexStatus, CommandError> {
+    let keyfile = unlock_keyfile(&app, &req)?;
     let password_secret = PasswordSecret::new(req.password);
-    let hierarchy = KeyHierarchy::restore(&password_secret, req.password_salt, &req.mkek)
-        .map_err(|e| e.to_string())?;
+    let hierarchy =
+        KeyHierarchy::restore_with_keyfile(&password_secret, req.password_salt, &req.mkek, keyfile.as_ref())?;
 
     let db_path = get_db_path(&app)?;
     let exists = db_path.exists();
@@ -204,11 +830,11 @@ fn get_index_status(app: tauri::AppHandle, req: MkekUnlockRequest) -> Result<Ind
 
     let master_key_bytes = hierarchy.master_key().as_bytes();
     let index = SqlCipherIndex::open(&db_path, master_key_bytes)
-        .map_err(|e| format!("Failed to open SQLCipher index: {}", e))?;
+        .map_err(|e| CommandError::index("Failed to open SQLCipher index", e))?;
 
     let file_count = index
         .len()
-        .map_err(|e| format!("Failed to get index length: {}", e))?;
+        .map_err(|e| CommandError::index("Failed to get index length", e))?;
 
     Ok(IndexStatus {
         db_path: db_path.to_string_lossy().to_string(),
@@ -217,42 +843,182 @@ fn get_index_status(app: tauri::AppHandle, req: MkekUnlockRequest) -> Result<Ind
     })
 }
 
+/// Déverrouille le coffre avec le mot de passe maître.
+///
+/// Argon2 (64 MiB) tourne hors du thread principal : sur mobile, le bloquer figerait
+/// l'interface le temps de la dérivation.
 #[tauri::command]
-fn crypto_unlock(
+async fn crypto_unlock(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
     req: MkekUnlockRequest,
-) -> Result<(), String> {
-    let password_secret = PasswordSecret::new(req.password);
-    let hierarchy = KeyHierarchy::restore(&password_secret, req.password_salt, &req.mkek)
-        .map_err(|e| e.to_string())?;
+) -> Result<(), CommandError> {
+    let (hierarchy, keyfile) = restore_hierarchy(&app, &req).await?;
+    // Mot de passe vérifié : il pourra être redemandé, y compris pour supprimer une base
+    // qui ne correspond pas à cette MKEK.
+    state.elevations.remember_password(req.password_salt, &req.mkek, keyfile.as_ref());
+
+    if req.remember_device {
+        // Un échec n'empêche pas le déverrouillage : le mot de passe restera simplement demandé.
+        // La KEK d'un coffre à fichier clé l'inclut : l'appareil devient lui-même le second facteur
+        match KeychainStore::device_kek().and_then(|store| store.save_kek(hierarchy.kek())) {
+            Ok(()) => log::info!("KEK stored on this device"),
+            Err(e) => log::warn!("Failed to store the KEK on this device: {}", e),
+        }
+    }
+    finish_unlock(&app, &state, &hierarchy)
+}
+
+/// Dérive la KEK (Argon2, hors du thread de la commande) et déchiffre la MKEK reçue, avec
+/// le fichier clé si le coffre en exige un ; ce dernier est retourné pour les confirmations.
+async fn restore_hierarchy(
+    app: &tauri::AppHandle,
+    req: &MkekUnlockRequest,
+) -> Result<(KeyHierarchy, Option<Keyfile>), CommandError> {
+    let keyfile = unlock_keyfile(app, req)?;
+    let password_secret = PasswordSecret::new(req.password.clone());
+    let (password_salt, mkek, mixed) = (req.password_salt, req.mkek.clone(), keyfile.clone());
+    let hierarchy = tokio::task::spawn_blocking(move || {
+        KeyHierarchy::restore_with_keyfile(&password_secret, password_salt, &mkek, mixed.as_ref())
+    })
+    .await
+    .map_err(|e| CommandError::internal("Key derivation task failed", e))??;
+    Ok((hierarchy, keyfile))
+}
+
+/// Fichier clé de la requête, lu seulement si la MKEK en exige un (sans lui :
+/// `keyfile_required`, avant toute dérivation).
+fn unlock_keyfile(app: &tauri::AppHandle, req: &MkekUnlockRequest) -> Result<Option<Keyfile>, CommandError> {
+    if !req.mkek.requires_keyfile() {
+        return Ok(None);
+    }
+    let path = req.keyfile_path.as_deref().ok_or(CommandError::KeyfileRequired)?;
+    read_keyfile(app, path).map(Some)
+}
+
+/// Lit un fichier clé (chemin local ou URI de la plateforme).
+fn read_keyfile(app: &tauri::AppHandle, path: &str) -> Result<Keyfile, CommandError> {
+    picker::open_read(app, &picker::parse_selection(path))
+        .and_then(Keyfile::read_from)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => CommandError::PathNotFound { path: path.to_string() },
+            std::io::ErrorKind::InvalidData => CommandError::InvalidKeyfile { reason: "length" },
+            _ => CommandError::io("Failed to read keyfile", e),
+        })
+}
+
+/// Déverrouille avec la KEK confiée à l'appareil (Keystore Android, Keychain iOS/macOS…).
+///
+/// `password_salt` n'est pas dérivé : il sert aux confirmations par mot de passe de la
+/// session. Une KEK qui n'ouvre plus la MKEK (mot de passe changé ailleurs) est oubliée.
+#[tauri::command]
+fn crypto_unlock_device(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    password_salt: [u8; 16],
+    mkek: MkekCiphertext,
+) -> Result<(), CommandError> {
+    let store = KeychainStore::device_kek()?;
+    let kek = store.load_kek()?.ok_or(CommandError::DeviceKeyUnavailable)?;
+    let hierarchy = match KeyHierarchy::from_kek(kek, &mkek) {
+        Ok(hierarchy) => hierarchy,
+        Err(CryptoError::Aead) => {
+            log::warn!("Stored device KEK no longer opens the MKEK, forgetting it");
+            store.delete()?;
+            return Err(CommandError::DeviceKeyUnavailable);
+        }
+        Err(e) => return Err(e.into()),
+    };
+    // Sans le fichier clé, les confirmations par mot de passe d'un tel coffre échoueront ;
+    // la session n'est pas fraîche (voir `reauth`)
+    state.elevations.remember_envelope(password_salt, &mkek);
+    finish_unlock(&app, &state, &hierarchy)
+}
+
+/// Depuis quand le coffre est ouvert et le mot de passe saisi, et si les exports de clés
+/// passent sans nouvelle saisie.
+#[tauri::command]
+fn session_freshness(state: State<'_, AppState>) -> Result<reauth::FreshnessStatus, CommandError> {
+    state.elevations.freshness()
+}
+
+/// Règle le délai après la dernière saisie du mot de passe au-delà duquel il est redemandé
+/// pour exporter les clés ; retourne le délai retenu (borné, voir `reauth`).
+#[tauri::command]
+async fn reauth_set_window(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    window_secs: u64,
+) -> Result<u64, CommandError> {
+    log::info!("reauth_set_window called: window_secs={}", window_secs);
+    let writer = index_writer(&app, &state)?;
+    let window = writer
+        .write(move |index| reauth::set_window(index, std::time::Duration::from_secs(window_secs)))
+        .await??;
+    state.elevations.set_reauth_window(window);
+    Ok(window.as_secs())
+}
+
+/// Indique si une KEK est confiée à cet appareil.
+#[tauri::command]
+fn device_unlock_status() -> Result<bool, CommandError> {
+    Ok(KeychainStore::device_kek()?.load_kek()?.is_some())
+}
+
+/// Oublie la KEK confiée à cet appareil : le mot de passe sera de nouveau demandé.
+#[tauri::command]
+fn device_unlock_forget() -> Result<(), CommandError> {
+    KeychainStore::device_kek()?.delete()?;
+    log::info!("Device KEK forgotten");
+    Ok(())
+}
 
+/// Partagé par `crypto_unlock` et `crypto_unlock_device` : ouvre l'index et garde la MasterKey.
+fn finish_unlock(app: &tauri::AppHandle, state: &AppState, hierarchy: &KeyHierarchy) -> Result<(), CommandError> {
     // Ouvre l'index SQLCipher existant avec la MasterKey restaurée.
-    let db_path = get_db_path(&app)?;
+    let db_path = get_db_path(app)?;
     let master_key_bytes = hierarchy.master_key().as_bytes();
-    
-    // Vérifie si la base existe avant d'essayer de l'ouvrir
-    let db_exists = db_path.exists();
-    
-    match SqlCipherIndex::open(&db_path, master_key_bytes) {
-        Ok(_) => {
-            // Base ouverte avec succès
+    let fingerprint = hierarchy.master_key().fingerprint();
+    let known_fingerprint = app
+        .try_state::<Workspaces>()
+        .and_then(|workspaces| workspaces.0.lock().ok().and_then(|registry| registry.vault_fingerprint().map(str::to_string)));
+
+    if db_path.exists() {
+        if let Some(expected) = known_fingerprint.clone().filter(|expected| *expected != fingerprint) {
+            log::error!("crypto_unlock: vault fingerprint {} does not match local vault {}", fingerprint, expected);
+            return Err(CommandError::VaultMismatch { expected, actual: fingerprint });
         }
-        Err(e) => {
-            let error_msg = format!("Failed to open SQLCipher index: {}", e);
-            
-            // Si la base existe mais qu'on ne peut pas l'ouvrir, c'est probablement une clé incorrecte
-            if db_exists {
-                return Err(format!(
-                    "{}. La clé de déchiffrement ne correspond pas à la base de données existante. \
-                    Cela peut arriver si tu as créé un nouveau coffre localement mais que tu essaies \
-                    de déverrouiller avec un MKEK d'un ancien coffre depuis Wayne. \
-                    Solution : Supprime la base locale (elle sera recréée) ou utilise le bon MKEK.",
-                    error_msg
-                ));
-            }
-            
-            return Err(error_msg);
+    }
+
+    // Une base que la clé ne déchiffre pas est laissée intacte (voir `index_archive_and_recreate`)
+    let mut index = SqlCipherIndex::open(&db_path, master_key_bytes).map_err(|e| {
+        if sqlcipher::is_key_mismatch(&e) {
+            // Index antérieur à l'empreinte : on sait seulement que la clé ne l'ouvre pas
+            // (nouveau coffre créé localement mais MKEK d'un ancien coffre récupéré depuis Wayne).
+            log::error!("crypto_unlock: existing database cannot be opened with this MasterKey");
+            return CommandError::IndexKeyMismatch;
+        }
+        CommandError::index("Failed to open SQLCipher index", e)
+    })?;
+    match index.vault_fingerprint()? {
+        Some(expected) if expected != fingerprint => {
+            log::error!("crypto_unlock: index belongs to vault {}, not {}", expected, fingerprint);
+            return Err(CommandError::VaultMismatch { expected, actual: fingerprint });
+        }
+        Some(_) => {}
+        None => index.set_vault_fingerprint(&fingerprint)?,
+    }
+    let pending = state.pending_settings.lock().ok().and_then(|mut pending| pending.take());
+    if let Some((_, settings)) = pending.filter(|(bundle_fingerprint, _)| *bundle_fingerprint == fingerprint) {
+        let applied = bundle::apply_settings(&mut index, &settings)?;
+        log::info!("Applied {} settings from the imported vault bundle", applied);
+    }
+    state.elevations.set_reauth_window(reauth::window(&index)?);
+    drop(index);
+    // Copie en clair pour les prochains déverrouillages ; son absence ne bloque rien
+    if known_fingerprint.as_deref() != Some(fingerprint.as_str()) {
+        if let Err(e) = with_workspaces(app, |registry| registry.set_vault_fingerprint(&fingerprint)) {
+            log::warn!("Failed to record vault fingerprint in the workspace registry: {}", e);
         }
     }
 
@@ -261,80 +1027,524 @@ fn crypto_unlock(
     let mut master_key_guard = state
         .master_key
         .lock()
-        .map_err(|e| format!("Lock error: {}", e))?;
+        .map_err(|e| CommandError::internal("Lock error", e))?;
     // Clone la MasterKey pour la stocker (elle sera zeroized à la drop).
     // On doit extraire les bytes et recréer une MasterKey car elle n'implémente pas Clone.
     let master_key_bytes_vec = hierarchy.master_key().as_bytes().to_vec();
     *master_key_guard = Some(crate::crypto::MasterKey::from_vec(master_key_bytes_vec));
 
+    // Le client Storj est retiré au verrouillage : la reprise attend sa reconfiguration.
+    state.recovery_pending.store(true, Ordering::SeqCst);
+
     Ok(())
 }
 
-/// Change le mot de passe sans re-chiffrer les données.
-/// 
-/// Le processus :
-/// 1. Déchiffre le MKEK avec l'ancien mot de passe pour obtenir la MasterKey
-/// 2. Génère un nouveau salt
-/// 3. Dérive une nouvelle KEK avec le nouveau mot de passe
-/// 4. Re-chiffre la MasterKey avec la nouvelle KEK (nouveau MKEK)
-/// 
-/// La MasterKey reste la même, seule la façon de la chiffrer change.
+/// Verrouille le coffre : retire la MasterKey et le client Storj de la mémoire.
+#[tauri::command]
+async fn crypto_lock(app: tauri::AppHandle) -> Result<(), CommandError> {
+    lock_vault(&app).await
+}
+
+/// Partagé par `crypto_lock` et l'action « Verrouiller » de la barre système.
+async fn lock_vault(app: &tauri::AppHandle) -> Result<(), CommandError> {
+    let state = app.state::<AppState>();
+    end_session(app, &state).await;
+    {
+        let mut master_key_guard = state
+            .master_key
+            .lock()
+            .map_err(|e| CommandError::internal("Lock error", e))?;
+        // La MasterKey est zeroized à la drop.
+        *master_key_guard = None;
+    }
+    if let Ok(mut guest_keys) = state.guest_keys.lock() {
+        *guest_keys = None;
+    }
+    // Les écritures déjà en file se terminent, puis la connexion est fermée
+    if let Ok(mut writer) = state.index_writer.lock() {
+        *writer = None;
+    }
+    // Les chemins gardés en mémoire ne survivent pas au verrouillage
+    forget_index_cache(&state);
+    *state.storj_client.lock().await = None;
+    *state.delegated_credentials.lock().await = None;
+    state.recovery_pending.store(false, Ordering::SeqCst);
+    state.elevations.revoke_all();
+    if let Some(server) = state.webdav.lock().await.take() {
+        server.stop();
+    }
+    app.state::<PreviewSessions>().close();
+
+    log::info!("Vault locked");
+    events::emit(app, AppEvent::VaultLocked);
+    Ok(())
+}
+
+/// Change le mot de passe sans re-chiffrer les données.
+/// 
+/// Le processus :
+/// 1. Déchiffre le MKEK avec l'ancien mot de passe (et le fichier clé éventuel) pour obtenir la MasterKey
+/// 2. Génère un nouveau salt
+/// 3. Dérive une nouvelle KEK avec le nouveau mot de passe (le fichier clé reste exigé)
+/// 4. Re-chiffre la MasterKey avec la nouvelle KEK (nouveau MKEK)
+/// 
+/// La MasterKey reste la même, seule la façon de la chiffrer change.
+///
+/// Avec `dry_run`, l'ancien mot de passe et l'indice sont vérifiés sans rien sceller : le
+/// rapport est vide, puisqu'aucune donnée n'est re-chiffrée.
 #[tauri::command]
 fn crypto_change_password(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
     req: ChangePasswordRequest,
-) -> Result<ChangePasswordResponse, String> {
-    use crate::crypto::mkek;
-    
-    log::info!("Starting password change");
+    dry_run: Option<bool>,
+) -> Result<CommandOutcome<ChangePasswordResponse>, CommandError> {
+    log::info!("Starting password change: dry_run={:?}", dry_run);
     
     // Étape 1 : Déchiffre le MKEK avec l'ancien mot de passe pour obtenir la MasterKey
+    let keyfile = match req.keyfile_path.as_deref() {
+        Some(path) if req.old_mkek.requires_keyfile() => Some(read_keyfile(&app, path)?),
+        _ => None,
+    };
     let old_password_secret = PasswordSecret::new(req.old_password);
-    let old_hierarchy = KeyHierarchy::restore(
+    let old_hierarchy = KeyHierarchy::restore_with_keyfile(
         &old_password_secret,
         req.old_password_salt,
         &req.old_mkek,
+        keyfile.as_ref(),
     )
     .map_err(|e| {
         log::error!("Failed to restore hierarchy with old password: {}", e);
-        format!("Ancien mot de passe incorrect: {}", e)
+        CommandError::from(e)
     })?;
     
-    // Récupère la MasterKey (elle reste la même)
-    let master_key = old_hierarchy.master_key();
+    // L'indice existant ne doit pas révéler le nouveau mot de passe
+    let hint = password_hint::load(&hint_path(&app)?)
+        .map_err(|e| CommandError::io("Failed to read password hint", e))?;
+    password_hint::validate(hint.as_deref(), Some(&req.new_password))
+        .map_err(|reason| CommandError::InvalidHint { reason })?;
+    if dry_run == Some(true) {
+        return Ok(CommandOutcome::DryRun(DryRunReport::default()));
+    }
     
     // Étape 2 : Génère un nouveau salt pour le nouveau mot de passe
-    let core = CryptoCore::default();
-    let new_password_salt = core.random_password_salt();
+    let new_password_salt = CryptoCore::default().random_password_salt();
     log::info!("New password salt generated");
     
-    // Étape 3 : Dérive une nouvelle KEK avec le nouveau mot de passe
+    // Étape 3 : Dérive une nouvelle KEK avec le nouveau mot de passe ; la MasterKey reste la même
     let new_password_secret = PasswordSecret::new(req.new_password);
-    let new_kek = core.derive_kek(&new_password_secret, &new_password_salt)
+    let new_hierarchy = old_hierarchy
+        .rekey(&new_password_secret, new_password_salt, keyfile.as_ref())
         .map_err(|e| {
             log::error!("Failed to derive new KEK: {}", e);
-            format!("Erreur lors de la dérivation de la nouvelle clé: {}", e)
+            CommandError::from(e)
         })?;
     
-    // Étape 4 : Re-chiffre la MasterKey avec la nouvelle KEK (nouveau MKEK)
-    let new_mkek = mkek::encrypt_master_key(&new_kek, master_key)
-        .map_err(|e| {
-            log::error!("Failed to encrypt master key with new KEK: {}", e);
-            format!("Erreur lors du chiffrement avec la nouvelle clé: {}", e)
-        })?;
+    // Étape 4 : Re-chiffre la MasterKey avec la nouvelle KEK (nouveau MKEK) ; les confirmations
+    // suivantes demandent le nouveau mot de passe
+    let new_mkek = reseal_master_key(&state, new_password_salt, &new_hierarchy, keyfile.as_ref())?;
     
     log::info!("Password change successful");
-    
-    Ok(ChangePasswordResponse {
+    Ok(CommandOutcome::Applied(ChangePasswordResponse {
         new_password_salt,
         new_mkek,
+    }))
+}
+
+/// Scelle la MasterKey sous la KEK de `hierarchy` (nouvelle MKEK). Une KEK confiée à
+/// l'appareil suit, et les confirmations par mot de passe utilisent désormais cette MKEK.
+fn reseal_master_key(
+    state: &AppState,
+    password_salt: [u8; 16],
+    hierarchy: &KeyHierarchy,
+    keyfile: Option<&Keyfile>,
+) -> Result<MkekCiphertext, CommandError> {
+    let mkek = hierarchy.seal_master_key().map_err(|e| {
+        log::error!("Failed to encrypt master key with new KEK: {}", e);
+        CommandError::internal("Failed to encrypt master key with new KEK", e)
+    })?;
+    let device = KeychainStore::device_kek().and_then(|store| match store.load_kek()? {
+        Some(_) => store.save_kek(hierarchy.kek()),
+        None => Ok(()),
+    });
+    if let Err(e) = device {
+        log::warn!("Failed to update the KEK stored on this device: {}", e);
+    }
+    state.elevations.remember_password(password_salt, &mkek, keyfile);
+    Ok(mkek)
+}
+
+/// Indique si la MKEK exige un fichier clé, pour le demander avant le mot de passe.
+#[tauri::command]
+fn keyfile_required(mkek: MkekCiphertext) -> Result<bool, CommandError> {
+    Ok(mkek.requires_keyfile())
+}
+
+/// Choisit le fichier clé à présenter au déverrouillage ; retourne son chemin (ou URI), à
+/// passer dans `keyfile_path`.
+#[tauri::command]
+async fn keyfile_select(app: tauri::AppHandle) -> Result<String, CommandError> {
+    let file_path = picker::pick_open(&app, "Sélectionner le fichier clé du coffre").await?;
+    Ok(file_path.to_string())
+}
+
+#[derive(Debug, Serialize)]
+pub struct KeyfileEnrollment {
+    /// Emplacement du fichier clé écrit.
+    pub path: String,
+    /// Nouvelles données d'amorçage, à enregistrer à la place des anciennes (localStorage, Wayne).
+    pub password_salt: [u8; 16],
+    pub mkek: MkekCiphertext,
+}
+
+/// Ajoute un fichier clé au coffre déverrouillé : 32 octets aléatoires écrits à l'emplacement
+/// choisi (une clé USB, par exemple), mélangés à la KEK. La MasterKey ne change pas ; la
+/// nouvelle MKEK exige ensuite le mot de passe et le fichier.
+#[tauri::command]
+async fn keyfile_enroll(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    req: MkekUnlockRequest,
+) -> Result<KeyfileEnrollment, CommandError> {
+    log::info!("keyfile_enroll called");
+    if req.mkek.requires_keyfile() {
+        return Err(CommandError::InvalidKeyfile { reason: "already_enrolled" });
+    }
+    let (hierarchy, _) = restore_hierarchy(&app, &req).await?;
+    ensure_unlocked_vault(&state, &hierarchy)?;
+
+    let keyfile = Keyfile::generate();
+    let file_path = picker::pick_save(&app, "Enregistrer le fichier clé du coffre", KEYFILE_NAME).await?;
+    let path = file_path.to_string();
+    let (writer, written) = (app.clone(), keyfile.clone());
+    tokio::task::spawn_blocking(move || picker::write(&writer, &file_path, written.as_bytes()))
+        .await
+        .map_err(|e| CommandError::internal("Write task failed", e))?
+        .map_err(|e| CommandError::io("Failed to write keyfile", e))?;
+    // Relu avant de sceller : un support défaillant ne doit pas rendre le coffre inaccessible
+    if read_keyfile(&app, &path)?.as_bytes() != keyfile.as_bytes() {
+        return Err(CommandError::InvalidKeyfile { reason: "mismatch" });
+    }
+
+    let (password_salt, hierarchy) = rekey_hierarchy(hierarchy, req.password, Some(keyfile.clone())).await?;
+    let mkek = reseal_master_key(&state, password_salt, &hierarchy, Some(&keyfile))?;
+    log::info!("Keyfile enrolled at {}", path);
+    Ok(KeyfileEnrollment { path, password_salt, mkek })
+}
+
+/// Retire le fichier clé : le mot de passe seul déverrouille de nouveau. Le fichier lui-même
+/// n'est pas effacé, il ne sert simplement plus.
+#[tauri::command]
+async fn keyfile_remove(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    req: MkekUnlockRequest,
+) -> Result<MkekBootstrapResponse, CommandError> {
+    log::info!("keyfile_remove called");
+    if !req.mkek.requires_keyfile() {
+        return Err(CommandError::InvalidKeyfile { reason: "not_enrolled" });
+    }
+    let (hierarchy, _) = restore_hierarchy(&app, &req).await?;
+    ensure_unlocked_vault(&state, &hierarchy)?;
+
+    let (password_salt, hierarchy) = rekey_hierarchy(hierarchy, req.password, None).await?;
+    let mkek = reseal_master_key(&state, password_salt, &hierarchy, None)?;
+    log::info!("Keyfile removed");
+    Ok(MkekBootstrapResponse { password_salt, mkek })
+}
+
+/// Nom proposé pour un fichier clé.
+const KEYFILE_NAME: &str = "aether-drive.key";
+
+/// La MKEK reçue doit ouvrir le coffre en mémoire, pas un autre.
+fn ensure_unlocked_vault(state: &State<'_, AppState>, hierarchy: &KeyHierarchy) -> Result<(), CommandError> {
+    let expected = get_master_key_from_state(state.clone())?.fingerprint();
+    let actual = hierarchy.master_key().fingerprint();
+    if expected != actual {
+        return Err(CommandError::VaultMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+/// Même MasterKey sous un nouveau sel, avec ou sans fichier clé (Argon2 hors du thread de la
+/// commande).
+async fn rekey_hierarchy(
+    hierarchy: KeyHierarchy,
+    password: String,
+    keyfile: Option<Keyfile>,
+) -> Result<([u8; 16], KeyHierarchy), CommandError> {
+    let password_salt = CryptoCore::default().random_password_salt();
+    let password = PasswordSecret::new(password);
+    let rekeyed = tokio::task::spawn_blocking(move || hierarchy.rekey(&password, password_salt, keyfile.as_ref()))
+        .await
+        .map_err(|e| CommandError::internal("Key derivation task failed", e))??;
+    Ok((password_salt, rekeyed))
+}
+
+#[derive(Debug, Serialize)]
+pub struct BundleExport {
+    pub path: String,
+    /// Code de transfert à saisir sur le nouvel appareil ; il n'est écrit nulle part.
+    pub transfer_code: String,
+}
+
+/// Exporte la configuration du coffre (sel, MKEK, bucket, réglages) dans un paquet scellé
+/// sous un code de transfert neuf (voir `bundle`).
+#[tauri::command]
+async fn vault_bundle_export(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<BundleExport, CommandError> {
+    log::info!("vault_bundle_export called");
+    telemetry::record_feature("vault_bundle");
+    let master_key = get_master_key_from_state(state.clone())?;
+    let (password_salt, mkek) = state.elevations.password_envelope().ok_or(CommandError::VaultLocked)?;
+    let storj = state.storj_client.lock().await.as_ref().map(|client| bundle::StorjReference {
+        endpoint: client.endpoint().to_string(),
+        bucket_name: client.bucket_name().to_string(),
+    });
+    let contents = bundle::VaultBundle {
+        vault_fingerprint: master_key.fingerprint(),
+        password_salt,
+        mkek,
+        storj,
+        settings: bundle::settings(&open_index_with_state(&app, &state)?)?,
+        exported_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64,
+    };
+
+    let transfer_code = bundle::generate_code();
+    let code = transfer_code.clone();
+    let sealed = tokio::task::spawn_blocking(move || bundle::seal(&contents, &code))
+        .await
+        .map_err(|e| CommandError::internal("Key derivation task failed", e))??;
+
+    let suggested_name = format!("coffre.{}", bundle::FILE_EXTENSION);
+    let file_path = picker::pick_save(&app, "Exporter la configuration du coffre", &suggested_name).await?;
+    let path = file_path.to_string();
+    let writer = app.clone();
+    tokio::task::spawn_blocking(move || picker::write(&writer, &file_path, &sealed))
+        .await
+        .map_err(|e| CommandError::internal("Write task failed", e))?
+        .map_err(|e| CommandError::io("Failed to write vault bundle", e))?;
+    log::info!("Vault bundle exported to {}", path);
+    Ok(BundleExport { path, transfer_code: transfer_code.to_string() })
+}
+
+#[derive(Debug, Serialize)]
+pub struct BundleImport {
+    /// Données d'amorçage (comme `crypto_bootstrap`) : le mot de passe habituel déverrouille.
+    pub password_salt: [u8; 16],
+    pub mkek: MkekCiphertext,
+    pub vault_fingerprint: String,
+    pub storj: Option<bundle::StorjReference>,
+    /// Réglages qui seront appliqués au premier déverrouillage.
+    pub settings: usize,
+}
+
+/// Importe un paquet de configuration exporté depuis un autre appareil.
+#[tauri::command]
+async fn vault_bundle_import(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    transfer_code: String,
+) -> Result<BundleImport, CommandError> {
+    log::info!("vault_bundle_import called");
+    telemetry::record_feature("vault_bundle");
+    let file_path = picker::pick_open(&app, "Importer la configuration du coffre").await?;
+    let reader = app.clone();
+    let sealed = tokio::task::spawn_blocking(move || picker::read(&reader, &file_path))
+        .await
+        .map_err(|e| CommandError::internal("Read task failed", e))?
+        .map_err(|e| CommandError::io("Failed to read vault bundle", e))?;
+    let contents = tokio::task::spawn_blocking(move || bundle::open(&sealed, &transfer_code))
+        .await
+        .map_err(|e| CommandError::internal("Key derivation task failed", e))??;
+
+    // Un espace qui contient déjà un autre coffre n'est pas réaffecté
+    let known_fingerprint = app
+        .try_state::<Workspaces>()
+        .and_then(|workspaces| workspaces.0.lock().ok().and_then(|registry| registry.vault_fingerprint().map(str::to_string)));
+    if get_db_path(&app)?.exists() {
+        if let Some(expected) = known_fingerprint.filter(|expected| *expected != contents.vault_fingerprint) {
+            return Err(CommandError::VaultMismatch { expected, actual: contents.vault_fingerprint });
+        }
+    }
+
+    let settings = contents.settings.len();
+    *state.pending_settings.lock().map_err(|e| CommandError::internal("Lock error", e))? =
+        Some((contents.vault_fingerprint.clone(), contents.settings));
+    log::info!("Vault bundle imported for vault {}", contents.vault_fingerprint);
+    Ok(BundleImport {
+        password_salt: contents.password_salt,
+        mkek: contents.mkek,
+        vault_fingerprint: contents.vault_fingerprint,
+        storj: contents.storj,
+        settings,
     })
 }
 
+/// Assistant de première configuration de cet appareil (voir `setup`).
+fn load_setup_wizard(app: &tauri::AppHandle) -> Result<SetupWizard, CommandError> {
+    let vault_exists = get_db_path(app)?.exists();
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| CommandError::io("Failed to get app data dir", e))?;
+    Ok(SetupWizard::load(&data_dir, vault_exists)?)
+}
+
+/// Assistant prêt pour `step`, sur le coffre qu'il a créé (déverrouillé après une reprise).
+fn begin_setup_step(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    step: SetupStep,
+) -> Result<SetupWizard, CommandError> {
+    let wizard = load_setup_wizard(app)?;
+    wizard.begin(step)?;
+    wizard.check_vault(&get_master_key_from_state(state.clone())?.fingerprint())?;
+    Ok(wizard)
+}
+
+/// État de l'assistant, lisible coffre verrouillé : le frontend reprend à `step`.
+#[tauri::command]
+fn setup_wizard_state(app: tauri::AppHandle) -> Result<SetupWizard, CommandError> {
+    load_setup_wizard(&app)
+}
+
+/// Étape 1 : crée le coffre (comme `crypto_bootstrap`).
+#[tauri::command]
+fn setup_wizard_create_vault(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    password: String,
+    hint: Option<String>,
+) -> Result<MkekBootstrapResponse, CommandError> {
+    log::info!("setup_wizard_create_vault called");
+    let mut wizard = load_setup_wizard(&app)?;
+    wizard.begin(SetupStep::CreateVault)?;
+    let response = crypto_bootstrap(app.clone(), state.clone(), password, hint)?;
+    wizard.complete_vault(&get_master_key_from_state(state)?.fingerprint())?;
+    Ok(response)
+}
+
+/// Étape 2 : configure le client Storj et enregistre les identifiants dans le trousseau.
+/// Refaite pour corriger les identifiants, elle impose un nouveau test de connexion.
+#[tauri::command]
+async fn setup_wizard_configure_backend(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    config: StorjConfigRequest,
+) -> Result<(), CommandError> {
+    log::info!("setup_wizard_configure_backend called: endpoint={}, bucket={}", config.endpoint, config.bucket_name);
+    let mut wizard = begin_setup_step(&app, &state, SetupStep::ConfigureBackend)?;
+    storj_configure(app.clone(), state.clone(), config.clone()).await?;
+    storj_save_credentials(app.clone(), state.clone(), config)?;
+    wizard.complete(SetupStep::ConfigureBackend)?;
+    Ok(())
+}
+
+/// Étape 3 : vérifie que le bucket répond avec ces identifiants.
+#[tauri::command]
+async fn setup_wizard_test_connection(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), CommandError> {
+    log::info!("setup_wizard_test_connection called");
+    let mut wizard = begin_setup_step(&app, &state, SetupStep::TestConnection)?;
+    let client = state.storj_client.lock().await.clone().ok_or(CommandError::StorjNotConfigured)?;
+    client.ping().await.map_err(|e| {
+        log::warn!("Setup connection test failed: {}", e);
+        CommandError::remote("Storage connection test failed", e)
+    })?;
+    wizard.complete(SetupStep::TestConnection)?;
+    Ok(())
+}
+
+/// Étape 4, facultative : exporte le kit de récupération (paquet de configuration, voir
+/// `vault_bundle_export`) ou passe l'étape.
+#[tauri::command]
+async fn setup_wizard_recovery_kit(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    export: bool,
+) -> Result<Option<BundleExport>, CommandError> {
+    log::info!("setup_wizard_recovery_kit called: export={}", export);
+    let mut wizard = begin_setup_step(&app, &state, SetupStep::RecoveryKit)?;
+    let kit = if export {
+        Some(vault_bundle_export(app.clone(), state.clone()).await?)
+    } else {
+        None
+    };
+    wizard.complete_recovery_kit(export)?;
+    Ok(kit)
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetupImportReport {
+    pub files: u64,
+    /// Fichiers déjà présents (import repris) ou au contenu identique à un autre.
+    pub deduplicated: u64,
+}
+
+/// Étape 5 : envoie le contenu d'un dossier local (`local_path`, ou choisi ici), sous un
+/// dossier du même nom ; `skip` termine l'assistant sans import. Interrompu, l'import se
+/// relance tel quel : les fichiers déjà envoyés ne sont pas renvoyés.
+#[tauri::command]
+async fn setup_wizard_import_folder(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    local_path: Option<String>,
+    skip: bool,
+) -> Result<SetupImportReport, CommandError> {
+    log::info!("setup_wizard_import_folder called: local_path={:?}, skip={}", local_path, skip);
+    let mut wizard = begin_setup_step(&app, &state, SetupStep::InitialImport)?;
+    let mut report = SetupImportReport { files: 0, deduplicated: 0 };
+    if !skip {
+        let root = match local_path {
+            Some(path) => PathBuf::from(path),
+            #[cfg(desktop)]
+            None => picker::pick_folders(&app, "Sélectionner un dossier à importer")
+                .await?
+                .into_iter()
+                .find_map(|folder| folder.into_path().ok())
+                .ok_or(CommandError::DialogCancelled)?,
+            // Pas de sélecteur de dossiers sur mobile
+            #[cfg(not(desktop))]
+            None => return Err(CommandError::DialogCancelled),
+        };
+        let files = tokio::task::spawn_blocking(move || picker::walk_folder(&root))
+            .await
+            .map_err(|e| CommandError::internal("Folder scan failed", e))?
+            .map_err(|e| CommandError::io("Failed to read the selected folder", e))?;
+        let vault = vault_from_state(&app, &state).await?;
+        for (source, relative_dir) in files {
+            let Some(name) = source.file_name().and_then(|n| n.to_str()) else {
+                log::warn!("Skipping non UTF-8 file name: {}", source.display());
+                continue;
+            };
+            let logical_path = upload_logical_path(Some(format!("/{}", relative_dir).as_str()), name);
+            let put = vault.put_path(&logical_path, &source).await?;
+            report.files += 1;
+            if put.deduplicated {
+                report.deduplicated += 1;
+            }
+            wizard.set_imported(report.files)?;
+        }
+        log::info!("Setup import done: {} files ({} deduplicated)", report.files, report.deduplicated);
+    }
+    wizard.complete(SetupStep::InitialImport)?;
+    Ok(report)
+}
+
 #[derive(Debug, Serialize)]
 pub struct FileEntry {
     pub id: String,
     pub logical_path: String,
     pub encrypted_size: u64,
+    /// Envoi vers Storj encore en file d'attente (créé hors ligne).
+    pub pending: bool,
+    /// Pour un alias : fichier désigné (`id` est alors celui de l'alias).
+    pub alias_of: Option<FileId>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -348,53 +1558,116 @@ pub struct AddFileRequest {
 }
 
 #[tauri::command]
-fn index_add_file(
+async fn index_add_file(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
     req: AddFileRequest,
-) -> Result<(), String> {
+) -> Result<String, CommandError> {
     log::info!(
         "index_add_file called: file_id={}, logical_path={}, encrypted_size={}",
         req.file_id,
         req.logical_path,
         req.encrypted_size
     );
-    let mut index = open_index_with_state(&app, &state)
-        .map_err(|e| {
-            log::error!("open_index_with_state failed: {}", e);
-            e
-        })?;
+    let writer = index_writer(&app, &state)?;
     let metadata = FileMetadata {
         logical_path: req.logical_path.clone(),
         encrypted_size: req.encrypted_size,
     };
-    index
-        .upsert(req.file_id.clone(), metadata)
+    let file_id = req.file_id.clone();
+    // Un chemin déjà occupé n'est jamais doublé : le fichier est placé à côté
+    let logical_path = writer
+        .write(move |index| index.insert_at_free_path(file_id, metadata))
+        .await?
         .map_err(|e| {
             log::error!("upsert failed: {}", e);
-            format!("Failed to add file to index: {}", e)
+            CommandError::index("Failed to add file to index", e)
         })?;
-    log::info!("File {} successfully added to index", req.file_id);
-    Ok(())
+    log::info!("File {} successfully added to index at {}", req.file_id, logical_path);
+    Ok(logical_path)
 }
 
+/// Fichiers de l'index, par pages.
 #[tauri::command]
 fn index_list_files(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<Vec<FileEntry>, String> {
+    page: Option<PageRequest>,
+) -> Result<Page<FileEntry>, CommandError> {
     let index = open_index_with_state(&app, &state)?;
-    let entries = index
-        .list_all()
-        .map_err(|e| format!("Failed to list files: {}", e))?;
-    Ok(entries
+    let entries = index_cache(&app, &state)?
+        .list_all(&index)
+        .map_err(|e| CommandError::index("Failed to list files", e))?;
+    let pending = index.pending_upload_ids()?;
+    let files = entries
         .into_iter()
         .map(|(id, meta)| FileEntry {
+            pending: pending.contains(&id),
             id,
             logical_path: meta.logical_path,
             encrypted_size: meta.encrypted_size,
+            alias_of: None,
         })
-        .collect())
+        .collect();
+    Ok(paging::paginate(files, &page.unwrap_or_default())?)
+}
+
+#[derive(Debug, Serialize)]
+pub struct CategorizedFile {
+    #[serde(flatten)]
+    pub file: FileEntry,
+    pub mime_type: String,
+}
+
+/// Vue « intelligente » : tous les fichiers du coffre d'une catégorie de type MIME, par pages.
+#[tauri::command]
+fn list_by_category(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    category: mime::Category,
+    page: Option<PageRequest>,
+) -> Result<Page<CategorizedFile>, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    let pending = index.pending_upload_ids()?;
+    let files = mime::list_by_category(&index, category)?
+        .into_iter()
+        .map(|(id, meta, mime_type)| CategorizedFile {
+            file: FileEntry {
+                pending: pending.contains(&id),
+                id,
+                logical_path: meta.logical_path,
+                encrypted_size: meta.encrypted_size,
+                alias_of: None,
+            },
+            mime_type,
+        })
+        .collect();
+    Ok(paging::paginate(files, &page.unwrap_or_default())?)
+}
+
+#[derive(Debug, Serialize)]
+pub struct PhotoTimeline {
+    pub months: Vec<photos::MonthBucket>,
+    /// Mois affiché : celui demandé, ou le plus récent.
+    pub month: Option<String>,
+    pub photos: Vec<photos::TimelinePhoto>,
+}
+
+/// Galerie : mois de prise de vue et photos de l'un d'eux (`AAAA-MM` ou `undated`).
+#[tauri::command]
+fn photos_timeline(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    month_bucket: Option<String>,
+) -> Result<PhotoTimeline, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    let months = photos::months(&index)?;
+    let month = month_bucket.or_else(|| months.first().map(|bucket| bucket.month.clone()));
+    let photos = match &month {
+        Some(month) => photos::timeline(&index, month)?,
+        None => Vec::new(),
+    };
+    Ok(PhotoTimeline { months, month, photos })
 }
 
 /// Représente un dossier dans la hiérarchie
@@ -402,22 +1675,41 @@ fn index_list_files(
 pub struct FolderInfo {
     pub name: String,
     pub path: String,
+    pub color: Option<String>,
+    pub icon: Option<String>,
 }
 
-/// Représente un fichier ou un dossier dans un chemin donné
+/// Page du contenu d'un dossier : ses sous-dossiers puis ses fichiers, triés par chemin
 #[derive(Debug, Serialize)]
 pub struct DirectoryEntry {
     pub files: Vec<FileEntry>,
     pub folders: Vec<FolderInfo>,
+    /// Jeton de la page suivante, absent sur la dernière (voir `paging`).
+    pub next_cursor: Option<String>,
+    /// Fichiers et dossiers du dossier complet.
+    pub total: usize,
+}
+
+/// Élément paginé de [`DirectoryEntry`].
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum DirectoryItem {
+    Folder(FolderInfo),
+    File(FileEntry),
 }
 
-/// Normalise un chemin (supprime les doubles slashes, termine par / si c'est un dossier)
+/// Normalise un chemin : forme canonique de l'index (NFC, `/` initial, sans `/` répétés ;
+/// le `/` final d'un dossier est conservé), voir `index::path`.
 fn normalize_path(path: &str) -> String {
-    let mut normalized = path.replace("//", "/");
-    if !normalized.starts_with('/') {
-        normalized = format!("/{}", normalized);
-    }
-    normalized
+    index::path::normalize(path)
+}
+
+/// Chemin logique d'un fichier reçu du frontend : forme canonique, refusé s'il ne pourrait
+/// pas être restauré partout. Le chiffrement (AAD) et l'index reçoivent ainsi le même chemin.
+fn canonical_file_path(logical_path: &str) -> Result<String, CommandError> {
+    let normalized = normalize_path(logical_path);
+    index::path::validate_file_path(&normalized).map_err(|reason| CommandError::InvalidName { reason })?;
+    Ok(normalized)
 }
 
 /// Vérifie si un chemin est un préfixe d'un autre
@@ -455,7 +1747,132 @@ fn get_parent_path(path: &str) -> String {
 /// Extrait le nom du fichier ou dossier depuis un chemin complet
 fn get_name_from_path(path: &str) -> String {
     let path = path.trim_end_matches('/');
-    path.split('/').last().unwrap_or("").to_string()
+    path.split('/').next_back().unwrap_or("").to_string()
+}
+
+/// Place d'une entrée de l'index dans le dossier listé.
+enum ListedEntry {
+    File(FileEntry),
+    /// Sous-dossier direct (vide, ou contenant l'entrée plus profondément).
+    Folder(String),
+}
+
+/// Classe une entrée de l'index par rapport au dossier `parent_normalized` ; `None` si elle
+/// n'y apparaît pas.
+fn classify_entry(
+    parent_normalized: &str,
+    id: FileId,
+    meta: FileMetadata,
+    pending: &std::collections::HashSet<FileId>,
+) -> Option<ListedEntry> {
+    // IMPORTANT: Ne normalise PAS le chemin pour les dossiers, car normalize_path supprime le slash final
+    // On utilise le chemin original pour détecter les dossiers
+    let original_path = &meta.logical_path;
+    let file_path = normalize_path(original_path);
+
+    // Si le chemin original se termine par / OU si encrypted_size = 0, c'est un dossier vide
+    if original_path.ends_with('/') || meta.encrypted_size == 0 {
+        // Exemple : dossier "/dossier1/" a pour parent "/"
+        let folder_path_clean = original_path.trim_end_matches('/');
+        let folder_parent = if folder_path_clean == "/" || folder_path_clean.is_empty() {
+            "/".to_string()
+        } else {
+            get_parent_path(folder_path_clean)
+        };
+        if normalize_path(&folder_parent) != normalize_path(parent_normalized) {
+            return None;
+        }
+        if get_name_from_path(original_path).is_empty() {
+            log::warn!("⚠️ Folder name is empty for path: {}", original_path);
+            return None;
+        }
+        // Utilise le chemin original qui se termine déjà par /
+        let folder_path = if original_path.ends_with('/') {
+            original_path.clone()
+        } else {
+            format!("{}/", original_path)
+        };
+        return Some(ListedEntry::Folder(folder_path));
+    }
+
+    // Vérifie si le fichier est dans le chemin parent
+    if !is_prefix(parent_normalized, &file_path) {
+        return None;
+    }
+
+    // Extrait le chemin relatif au parent
+    let relative_path = if parent_normalized == "/" {
+        file_path.trim_start_matches('/').to_string()
+    } else {
+        file_path.strip_prefix(parent_normalized)
+            .unwrap_or(&file_path)
+            .trim_start_matches('/')
+            .to_string()
+    };
+
+    // Si le chemin relatif est vide, on skip (ne devrait pas arriver)
+    if relative_path.is_empty() {
+        log::warn!("Empty relative path for file {}", id);
+        return None;
+    }
+
+    // Si le chemin relatif contient un slash, c'est dans un sous-dossier
+    if relative_path.contains('/') {
+        let first_folder = relative_path.split('/').next().unwrap_or("");
+        if first_folder.is_empty() {
+            return None;
+        }
+        let folder_path = if parent_normalized == "/" {
+            format!("/{}", first_folder)
+        } else {
+            format!("{}/{}", parent_normalized, first_folder)
+        };
+        return Some(ListedEntry::Folder(folder_path));
+    }
+
+    // C'est un fichier directement dans le parent
+    Some(ListedEntry::File(FileEntry {
+        pending: pending.contains(&id),
+        id,
+        logical_path: meta.logical_path,
+        encrypted_size: meta.encrypted_size,
+        alias_of: None,
+    }))
+}
+
+/// Comme [`classify_entry`], pour un alias : il apparaît comme un fichier de la taille
+/// de sa cible (0 si la cible est à la corbeille ou purgée).
+fn classify_alias(
+    parent_normalized: &str,
+    alias: AliasEntry,
+    index: &SqlCipherIndex,
+    pending: &std::collections::HashSet<FileId>,
+) -> Result<Option<ListedEntry>, CommandError> {
+    let target_size = index.get(&alias.target_id)?.map(|meta| meta.encrypted_size).unwrap_or(0);
+    // Une taille nulle désigne un dossier pour `classify_entry`
+    let meta = FileMetadata {
+        logical_path: alias.logical_path,
+        encrypted_size: target_size.max(1),
+    };
+    Ok(match classify_entry(parent_normalized, alias.id, meta, pending) {
+        Some(ListedEntry::File(mut file)) => {
+            file.encrypted_size = target_size;
+            file.pending = pending.contains(&alias.target_id);
+            file.alias_of = Some(alias.target_id);
+            Some(ListedEntry::File(file))
+        }
+        other => other,
+    })
+}
+
+fn folder_info(path: String, appearances: &mut std::collections::HashMap<String, FolderAppearance>) -> FolderInfo {
+    let appearance = appearances.remove(&FolderAppearance::folder_key(&path)).unwrap_or_default();
+    FolderInfo {
+        name: get_name_from_path(&path),
+        path,
+        color: appearance.color,
+        icon: appearance.icon,
+    }
 }
 
 #[tauri::command]
@@ -463,154 +1880,250 @@ fn list_files_and_folders(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
     parent_path: Option<String>,
-) -> Result<DirectoryEntry, String> {
+    page: Option<PageRequest>,
+) -> Result<DirectoryEntry, CommandError> {
     let parent = parent_path.as_deref().unwrap_or("/");
     let parent_normalized = normalize_path(parent);
     
-    log::info!("list_files_and_folders called: parent_path={:?}, parent_normalized={}", parent_path, parent_normalized);
+    log::debug!("list_files_and_folders called: parent_path={:?}, parent_normalized={}", parent_path, parent_normalized);
     
     let index = open_index_with_state(&app, &state)?;
-    let entries = index
-        .list_all()
-        .map_err(|e| format!("Failed to list files: {}", e))?;
+    let entries = index_cache(&app, &state)?
+        .list_all(&index)
+        .map_err(|e| CommandError::index("Failed to list files", e))?;
     
-    log::info!("Found {} total entries in index", entries.len());
-    for (id, meta) in &entries {
-        log::info!("  Entry: id={}, path={}, size={}", id, meta.logical_path, meta.encrypted_size);
-    }
+    let pending = index.pending_upload_ids()?;
+    log::debug!("Found {} total entries in index", entries.len());
     
     let mut files = Vec::new();
     let mut folder_paths = std::collections::HashSet::new();
     
     for (id, meta) in entries {
-        // IMPORTANT: Ne normalise PAS le chemin pour les dossiers, car normalize_path supprime le slash final
-        // On utilise le chemin original pour détecter les dossiers
-        let original_path = &meta.logical_path;
-        let file_path = normalize_path(original_path);
-        
-        // Si le chemin original se termine par / OU si encrypted_size = 0, c'est un dossier vide
-        let is_folder = original_path.ends_with('/') || meta.encrypted_size == 0;
-        
-        if is_folder {
-            // C'est un dossier vide
-            // Pour un dossier, on doit vérifier si son parent correspond au parent_normalized
-            // Exemple : dossier "/dossier1/" a pour parent "/"
-            // Utilise le chemin original (qui se termine par /) pour extraire le parent
-            let folder_path_clean = original_path.trim_end_matches('/');
-            let folder_parent = if folder_path_clean == "/" || folder_path_clean.is_empty() {
-                "/".to_string()
-            } else {
-                get_parent_path(folder_path_clean)
-            };
-            
-            log::info!("Checking folder: original_path={}, folder_path_clean={}, folder_parent={}, parent_normalized={}", original_path, folder_path_clean, folder_parent, parent_normalized);
-            
-            // Normalise les deux chemins pour la comparaison
-            let folder_parent_normalized = normalize_path(&folder_parent);
-            let parent_normalized_clean = normalize_path(&parent_normalized);
-            
-            if folder_parent_normalized == parent_normalized_clean {
-                let folder_name = get_name_from_path(original_path);
-                if !folder_name.is_empty() {
-                    // Utilise le chemin original qui se termine déjà par /
-                    let folder_path_normalized = if original_path.ends_with('/') {
-                        original_path.clone()
-                    } else {
-                        format!("{}/", original_path)
-                    };
-                    let folder_path_normalized_clone = folder_path_normalized.clone();
-                    folder_paths.insert(folder_path_normalized);
-                    log::info!("✅ Added empty folder: {} (original_path: {}, normalized: {})", folder_name, original_path, folder_path_normalized_clone);
-                } else {
-                    log::warn!("⚠️ Folder name is empty for path: {}", original_path);
-                }
-            } else {
-                log::info!("⏭️ Folder {} not in parent {} (folder_parent: {})", original_path, parent_normalized, folder_parent);
+        match classify_entry(&parent_normalized, id, meta, &pending) {
+            Some(ListedEntry::File(file)) => files.push(file),
+            Some(ListedEntry::Folder(path)) => {
+                folder_paths.insert(path);
             }
-            continue; // Skip les dossiers dans le traitement des fichiers
-        }
-        
-        // Vérifie si le fichier est dans le chemin parent
-        let is_in_parent = is_prefix(&parent_normalized, &file_path);
-        log::info!("Checking file {} (path: {}): is_in_parent={}", id, file_path, is_in_parent);
-        
-        if !is_in_parent {
-            continue;
-        }
-        
-        // Extrait le chemin relatif au parent
-        let relative_path = if parent_normalized == "/" {
-            file_path.trim_start_matches('/').to_string()
-        } else {
-            file_path.strip_prefix(&parent_normalized)
-                .unwrap_or(&file_path)
-                .trim_start_matches('/')
-                .to_string()
-        };
-        
-        // Si le chemin relatif est vide, on skip (ne devrait pas arriver)
-        if relative_path.is_empty() {
-            log::warn!("Empty relative path for file {}", id);
-            continue;
+            None => {}
         }
-        
-        // Si le chemin relatif contient un slash, c'est dans un sous-dossier
-        if relative_path.contains('/') {
-            // Extrait le nom du premier sous-dossier
-            let first_folder = relative_path.split('/').next().unwrap_or("");
-            if !first_folder.is_empty() {
-                let folder_path = if parent_normalized == "/" {
-                    format!("/{}", first_folder)
-                } else {
-                    format!("{}/{}", parent_normalized, first_folder)
-                };
-                folder_paths.insert(folder_path);
-                log::info!("Added folder: {}", first_folder);
+    }
+    for alias in index.list_aliases()? {
+        match classify_alias(&parent_normalized, alias, &index, &pending)? {
+            Some(ListedEntry::File(file)) => files.push(file),
+            Some(ListedEntry::Folder(path)) => {
+                folder_paths.insert(path);
             }
-        } else {
-            // C'est un fichier directement dans le parent
-            let file_id = id.clone();
-            files.push(FileEntry {
-                id,
-                logical_path: meta.logical_path,
-                encrypted_size: meta.encrypted_size,
-            });
-            log::info!("Added file: {} (relative_path: {})", file_id, relative_path);
+            None => {}
         }
     }
     
-    // Convertit les chemins de dossiers en FolderInfo
-    let folders: Vec<FolderInfo> = folder_paths
+    // Convertit les chemins de dossiers en FolderInfo ; l'ordre est fixe d'une page à l'autre
+    let mut appearances = index.list_folder_appearances()?;
+    let mut folder_paths: Vec<String> = folder_paths.into_iter().collect();
+    folder_paths.sort();
+    files.sort_by(|a, b| a.logical_path.cmp(&b.logical_path));
+    let items: Vec<DirectoryItem> = folder_paths
         .into_iter()
-        .map(|path| FolderInfo {
-            name: get_name_from_path(&path),
-            path: path.clone(),
-        })
+        .map(|path| DirectoryItem::Folder(folder_info(path, &mut appearances)))
+        .chain(files.into_iter().map(DirectoryItem::File))
         .collect();
     
-    log::info!("Returning {} files and {} folders", files.len(), folders.len());
+    let page = paging::paginate(items, &page.unwrap_or_default())?;
+    let mut directory = DirectoryEntry {
+        files: Vec::new(),
+        folders: Vec::new(),
+        next_cursor: page.next_cursor,
+        total: page.total,
+    };
+    for item in page.items {
+        match item {
+            DirectoryItem::Folder(folder) => directory.folders.push(folder),
+            DirectoryItem::File(file) => directory.files.push(file),
+        }
+    }
+    log::info!("Returning {} files and {} folders of {}", directory.files.len(), directory.folders.len(), directory.total);
     
-    Ok(DirectoryEntry { files, folders })
+    Ok(directory)
+}
+
+/// Taille par défaut (et maximale) d'une page de [`list_directory_stream`].
+const LISTING_PAGE_SIZE: usize = 500;
+const LISTING_MAX_PAGE_SIZE: usize = 5_000;
+
+/// Page envoyée sur le canal de [`list_directory_stream`] ; chaque dossier n'apparaît
+/// qu'une fois, dans la page où il est découvert.
+#[derive(Debug, Serialize)]
+pub struct ListingPage {
+    pub files: Vec<FileEntry>,
+    pub folders: Vec<FolderInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListingSummary {
+    pub files: usize,
+    pub folders: usize,
+    /// Le parcours s'est arrêté parce que la webview ne recevait plus les pages.
+    pub interrupted: bool,
+}
+
+/// Liste un dossier par pages, envoyées sur `on_page` au fil du curseur SQL : un dossier de
+/// plusieurs dizaines de milliers d'entrées s'affiche progressivement.
+#[tauri::command]
+async fn list_directory_stream(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    parent_path: Option<String>,
+    page_size: Option<usize>,
+    on_page: Channel<ListingPage>,
+) -> Result<ListingSummary, CommandError> {
+    let parent_normalized = normalize_path(parent_path.as_deref().unwrap_or("/"));
+    let page_size = page_size.unwrap_or(LISTING_PAGE_SIZE).clamp(1, LISTING_MAX_PAGE_SIZE);
+    log::info!("list_directory_stream called: parent={}, page_size={}", parent_normalized, page_size);
+
+    let index = open_index_with_state(&app, &state)?;
+    let cache = index_cache(&app, &state)?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<ListingSummary, CommandError> {
+        let pending = index.pending_upload_ids()?;
+        let mut appearances = index.list_folder_appearances()?;
+        let prefix = if parent_normalized == "/" {
+            parent_normalized.clone()
+        } else {
+            format!("{}/", parent_normalized)
+        };
+
+        let mut seen_folders = std::collections::HashSet::new();
+        let mut page = ListingPage { files: Vec::new(), folders: Vec::new() };
+        let mut summary = ListingSummary { files: 0, folders: 0, interrupted: false };
+        let send = |page: &mut ListingPage, summary: &mut ListingSummary| {
+            summary.files += page.files.len();
+            summary.folders += page.folders.len();
+            let full = std::mem::replace(page, ListingPage { files: Vec::new(), folders: Vec::new() });
+            if on_page.send(full).is_err() {
+                summary.interrupted = true;
+            }
+            !summary.interrupted
+        };
+
+        cache
+            .scan_prefix(&index, &prefix, |id, meta| {
+                match classify_entry(&parent_normalized, id, meta, &pending) {
+                    Some(ListedEntry::File(file)) => page.files.push(file),
+                    Some(ListedEntry::Folder(path)) => {
+                        if seen_folders.insert(path.clone()) {
+                            page.folders.push(folder_info(path, &mut appearances));
+                        }
+                    }
+                    None => {}
+                }
+                page.files.len() + page.folders.len() < page_size || send(&mut page, &mut summary)
+            })
+            .map_err(|e| CommandError::index("Failed to list files", e))?;
+        // Les alias du dossier suivent les fichiers (table distincte, peu nombreux)
+        if !summary.interrupted {
+            let aliases = index.list_aliases()?.into_iter().filter(|alias| alias.logical_path.starts_with(&prefix));
+            for alias in aliases {
+                match classify_alias(&parent_normalized, alias, &index, &pending)? {
+                    Some(ListedEntry::File(file)) => page.files.push(file),
+                    Some(ListedEntry::Folder(path)) => {
+                        if seen_folders.insert(path.clone()) {
+                            page.folders.push(folder_info(path, &mut appearances));
+                        }
+                    }
+                    None => {}
+                }
+            }
+        }
+        if !summary.interrupted && (!page.files.is_empty() || !page.folders.is_empty()) {
+            send(&mut page, &mut summary);
+        }
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| CommandError::internal("Listing task failed", e))?
+}
+
+/// Apparence (couleur, icône) d'un dossier.
+#[tauri::command]
+fn get_folder_appearance(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    folder_path: String,
+) -> Result<FolderAppearance, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(index.get_folder_appearance(&folder_path)?)
+}
+
+/// Définit l'apparence d'un dossier ; sans couleur ni icône, l'apparence par défaut est rétablie.
+#[tauri::command]
+async fn set_folder_appearance(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    folder_path: String,
+    color: Option<String>,
+    icon: Option<String>,
+) -> Result<FolderAppearance, CommandError> {
+    telemetry::record_feature("folder_appearance");
+    let appearance = FolderAppearance { color, icon }
+        .validate()
+        .map_err(|reason| CommandError::InvalidAppearance { reason })?;
+    let writer = index_writer(&app, &state)?;
+    let (path, stored) = (folder_path.clone(), appearance.clone());
+    writer
+        .write(move |index| index.set_folder_appearance(&path, &stored))
+        .await??;
+    log::info!("Folder appearance updated: {}", folder_path);
+    Ok(appearance)
+}
+
+/// Politique (compression, bourrage, hors ligne, synchronisation) enregistrée sur un dossier.
+#[tauri::command]
+fn folder_policy_get(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    folder_path: String,
+) -> Result<FolderPolicy, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(policies::get(&index, &folder_path)?)
+}
+
+/// Définit la politique d'un dossier et de son contenu ; elle vaut pour les envois suivants,
+/// les fichiers déjà envoyés gardent leur encodage.
+#[tauri::command]
+async fn folder_policy_set(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    folder_path: String,
+    policy: FolderPolicy,
+) -> Result<FolderPolicy, CommandError> {
+    telemetry::record_feature("folder_policy");
+    let writer = index_writer(&app, &state)?;
+    let path = folder_path.clone();
+    writer.write(move |index| policies::set(index, &path, &policy)).await??;
+    log::info!("Folder policy updated: {}", folder_path);
+    Ok(policy)
 }
 
 /// Crée un dossier vide dans l'index
 #[tauri::command]
-fn create_folder(
+async fn create_folder(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
     folder_name: String,
     parent_path: Option<String>,
-) -> Result<String, String> {
+) -> Result<String, CommandError> {
+    telemetry::record_feature("create_folder");
     let parent = parent_path.as_deref().unwrap_or("/");
     let parent_normalized = normalize_path(parent);
     
     // Valide le nom du dossier
     if folder_name.is_empty() {
-        return Err("Le nom du dossier ne peut pas être vide".to_string());
+        return Err(CommandError::InvalidName { reason: "empty" });
     }
     if folder_name.contains('/') {
-        return Err("Le nom du dossier ne peut pas contenir de slash".to_string());
+        return Err(CommandError::InvalidName { reason: "contains_slash" });
     }
+    let folder_name = normalize_path(&folder_name).trim_start_matches('/').to_string();
+    index::path::validate_name(&folder_name).map_err(|reason| CommandError::InvalidName { reason })?;
     
     // Génère un UUID pour le dossier (comme pour les fichiers)
     let mut uuid_bytes = [0u8; 16];
@@ -626,27 +2139,31 @@ fn create_folder(
     
     log::info!("Creating folder: {} (path: {}, id: {})", folder_name, folder_path, folder_id);
     
-    // Vérifie si le dossier existe déjà
-    let index_check = open_index_with_state(&app, &state)?;
-    let all_entries = index_check.list_all()
-        .map_err(|e| format!("Failed to check existing folders: {}", e))?;
-    
-    for (_, meta) in all_entries {
-        let existing_path = normalize_path(&meta.logical_path);
-        if existing_path == folder_path || existing_path == folder_path.trim_end_matches('/') {
-            return Err(format!("Un dossier avec le nom '{}' existe déjà", folder_name));
-        }
-    }
-    
-    // Ajoute le dossier dans l'index avec encrypted_size = 0 (indique que c'est un dossier)
-    let mut index = open_index_with_state(&app, &state)?;
+    // Vérifie si le dossier existe déjà puis l'ajoute avec encrypted_size = 0 (indique que
+    // c'est un dossier), dans la même écriture pour qu'un doublon ne puisse pas s'intercaler
+    let writer = index_writer(&app, &state)?;
     let metadata = FileMetadata {
         logical_path: folder_path.clone(),
         encrypted_size: 0, // 0 indique que c'est un dossier vide
     };
-    
-    index.upsert(folder_id.clone(), metadata)
-        .map_err(|e| format!("Failed to create folder in index: {}", e))?;
+    let created = writer
+        .write(move |index| {
+            let exists = index.list_all()?.into_iter().any(|(_, meta)| {
+                let existing_path = normalize_path(&meta.logical_path);
+                existing_path == metadata.logical_path
+                    || existing_path == metadata.logical_path.trim_end_matches('/')
+            });
+            if exists {
+                return Ok(false);
+            }
+            index.upsert(folder_id, metadata)?;
+            Ok::<_, rusqlite::Error>(true)
+        })
+        .await?
+        .map_err(|e| CommandError::index("Failed to create folder in index", e))?;
+    if !created {
+        return Err(CommandError::AlreadyExists { path: folder_path });
+    }
     
     log::info!("Folder created successfully: {}", folder_path);
     
@@ -654,15 +2171,16 @@ fn create_folder(
 }
 
 #[tauri::command]
-fn index_remove_file(
+async fn index_remove_file(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
     file_id: String,
-) -> Result<(), String> {
-    let mut index = open_index_with_state(&app, &state)?;
-    index
-        .remove(&file_id)
-        .map_err(|e| format!("Failed to remove file from index: {}", e))?;
+) -> Result<(), CommandError> {
+    let writer = index_writer(&app, &state)?;
+    writer
+        .write(move |index| index.remove(&file_id))
+        .await?
+        .map_err(|e| CommandError::index("Failed to remove file from index", e))?;
     Ok(())
 }
 
@@ -671,15 +2189,18 @@ fn index_get_file(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
     file_id: String,
-) -> Result<Option<FileEntry>, String> {
+) -> Result<Option<FileEntry>, CommandError> {
     let index = open_index_with_state(&app, &state)?;
-    let metadata = index
-        .get(&file_id)
-        .map_err(|e| format!("Failed to get file from index: {}", e))?;
+    let metadata = index_cache(&app, &state)?
+        .get(&index, &file_id)
+        .map_err(|e| CommandError::index("Failed to get file from index", e))?;
+    let pending = index.pending_upload_ids()?.contains(&file_id);
     Ok(metadata.map(|meta| FileEntry {
         id: file_id,
         logical_path: meta.logical_path,
         encrypted_size: meta.encrypted_size,
+        pending,
+        alias_of: None,
     }))
 }
 
@@ -687,23 +2208,515 @@ fn index_get_file(
 fn index_verify_integrity(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<bool, String> {
+) -> Result<bool, CommandError> {
     let index = open_index_with_state(&app, &state)?;
     let is_valid = index
         .verify_integrity()
-        .map_err(|e| format!("Failed to verify index integrity: {}", e))?;
+        .map_err(|e| CommandError::index("Failed to verify index integrity", e))?;
+    if !is_valid {
+        events::emit(&app, AppEvent::IntegrityWarning {
+            detail: "Local index failed HMAC/Merkle verification".to_string(),
+        });
+    }
     Ok(is_valid)
 }
 
+/// Fréquence à laquelle le planificateur regarde si une vérification est due.
+const INTEGRITY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+/// Vérification complète (index + échantillon distant) ; une dérive déclenche une alerte.
+async fn run_integrity_check(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+) -> Result<IntegrityReport, CommandError> {
+    let vault = vault_from_state(app, state).await?;
+    let report = integrity::check(&vault, integrity::REMOTE_SAMPLE_SIZE).await?;
+    if !report.is_clean() {
+        events::emit(app, AppEvent::IntegrityWarning { detail: report.summary() });
+    }
+    Ok(report)
+}
+
+/// Boucle de fond : relance la vérification quand la précédente date de plus de
+/// [`integrity::CHECK_INTERVAL`]. Ne fait rien tant que le coffre est verrouillé.
+async fn integrity_scheduler(app: tauri::AppHandle) {
+    let mut ticker = tokio::time::interval(INTEGRITY_POLL_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let state = app.state::<AppState>();
+        if state.storj_client.lock().await.is_none() {
+            continue;
+        }
+        let due = match open_index_with_state(&app, &state) {
+            Ok(index) => integrity::last_report(&index)
+                .ok()
+                .flatten()
+                .map_or(true, |report| report.is_stale()),
+            Err(_) => false,
+        };
+        if due {
+            if let Err(e) = run_integrity_check(&app, &state).await {
+                log::warn!("Scheduled integrity check failed: {}", e);
+            }
+        }
+    }
+}
+
+/// Fréquence des passes de maintenance du cache local (éviction, pré-chargement).
+const CACHE_MAINTENANCE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// Boucle de fond appliquant la politique du cache. Ne fait rien tant que le coffre est
+/// verrouillé ou la synchronisation suspendue (le pré-chargement télécharge).
+async fn cache_scheduler(app: tauri::AppHandle) {
+    let mut ticker = tokio::time::interval(CACHE_MAINTENANCE_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let state = app.state::<AppState>();
+        if state.sync_paused.load(Ordering::SeqCst) || state.storj_client.lock().await.is_none() {
+            continue;
+        }
+        let result = async {
+            let vault = vault_from_state(&app, &state).await?;
+            Ok::<_, CommandError>(vault.maintain_cache().await?)
+        }
+        .await;
+        match result {
+            Ok(report) => log::info!(
+                "Cache maintenance: files={}, bytes={}, evicted={}, prewarmed={}",
+                report.cached_files,
+                report.cached_bytes,
+                report.evicted,
+                report.prewarmed
+            ),
+            Err(e) => log::warn!("Cache maintenance failed: {}", e),
+        }
+    }
+}
+
+/// Fréquence d'exécution des règles d'automatisation.
+const RULES_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+/// Exécute les règles actives et signale au frontend ce qu'elles ont changé.
+async fn run_rules(app: &tauri::AppHandle, state: &State<'_, AppState>) -> Result<RulesReport, CommandError> {
+    let vault = vault_from_state(app, state).await?.with_priority(TransferPriority::Bulk);
+    let report = rules::run_all(&vault).await?;
+    if report.uploaded > 0 || report.trashed > 0 || !report.failures.is_empty() {
+        events::emit(
+            app,
+            AppEvent::RulesApplied {
+                uploaded: report.uploaded,
+                trashed: report.trashed,
+                failed: report.failures.len(),
+            },
+        );
+    }
+    Ok(report)
+}
+
+/// Boucle de fond des règles d'automatisation, suspendue avec la synchronisation.
+async fn rules_scheduler(app: tauri::AppHandle) {
+    let mut ticker = tokio::time::interval(RULES_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let state = app.state::<AppState>();
+        if state.sync_paused.load(Ordering::SeqCst) || state.storj_client.lock().await.is_none() {
+            continue;
+        }
+        match run_rules(&app, &state).await {
+            Ok(report) if report.rules_run > 0 => log::info!(
+                "Rules run: rules={}, uploaded={}, trashed={}, failed={}",
+                report.rules_run,
+                report.uploaded,
+                report.trashed,
+                report.failures.len()
+            ),
+            Ok(_) => {}
+            Err(e) => log::warn!("Scheduled rules run failed: {}", e),
+        }
+    }
+}
+
+/// Fréquence de vérification des modifications de l'index à sauvegarder.
+const INDEX_BACKUP_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Boucle de fond des sauvegardes de l'index : envoie une copie après chaque lot de
+/// modifications (voir [`backup::run_if_due`]).
+async fn backup_scheduler(app: tauri::AppHandle) {
+    let mut ticker = tokio::time::interval(INDEX_BACKUP_CHECK_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let state = app.state::<AppState>();
+        if state.sync_paused.load(Ordering::SeqCst) || state.storj_client.lock().await.is_none() {
+            continue;
+        }
+        let result = async {
+            let vault = vault_from_state(&app, &state).await?;
+            Ok::<_, CommandError>(backup::run_if_due(&vault).await?)
+        }
+        .await;
+        match result {
+            Ok(Some(report)) => log::info!("Scheduled index backup: key={}, pruned={}", report.backup.key, report.pruned),
+            Ok(None) => {}
+            Err(e) => log::warn!("Scheduled index backup failed: {}", e),
+        }
+    }
+}
+
+/// Auto-diagnostic pour l'écran « Dépannage » ; fonctionne aussi coffre verrouillé ou
+/// stockage non configuré (les vérifications concernées sont alors sautées).
+#[tauri::command]
+async fn run_diagnostics(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<DiagnosticsReport, CommandError> {
+    log::info!("run_diagnostics called");
+    let master_key = get_master_key_from_state(state.clone()).ok();
+    let remote = state.storj_client.lock().await.clone();
+    let index_path = get_db_path(&app)?;
+    let cache = open_blob_cache(&app).ok();
+
+    let report = diagnostics::run(&DiagnosticsTarget {
+        master_key: master_key.as_ref(),
+        index_path: &index_path,
+        keychain: KeychainStore::storj_for_workspace(&active_workspace_id(&app)),
+        remote: remote.as_deref(),
+        cache: cache.as_ref(),
+    })
+    .await;
+    if !report.is_healthy() {
+        log::warn!("Diagnostics found failing checks");
+    }
+    Ok(report)
+}
+
+/// Dernier rapport de vérification (alerte persistante tant qu'il n'est pas propre).
+#[tauri::command]
+fn integrity_last_report(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Option<IntegrityReport>, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(integrity::last_report(&index)?)
+}
+
+/// Résumé lisible du dernier passage de synchronisation (lecteurs d'écran, journaux).
+#[tauri::command]
+fn summary_last_sync(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Option<OperationSummary>, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(summaries::last_sync_run(&index)?.as_ref().map(summaries::sync_summary))
+}
+
+/// Résumé lisible de la dernière vérification d'intégrité.
+#[tauri::command]
+fn summary_last_scrub(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Option<OperationSummary>, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(integrity::last_report(&index)?.as_ref().map(summaries::scrub_summary))
+}
+
+/// Nombre de racines Merkle renvoyées par défaut par `integrity_timeline`.
+const INTEGRITY_TIMELINE_LIMIT: usize = 50;
+
+/// Chronologie des racines Merkle, pour enquêter sur une modification inattendue de l'index.
+#[tauri::command]
+fn integrity_timeline(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+) -> Result<IntegrityTimeline, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(integrity::timeline(&index, limit.unwrap_or(INTEGRITY_TIMELINE_LIMIT))?)
+}
+
+/// Score de santé du coffre, calculé localement depuis l'index et le dernier rapport d'intégrité.
+#[tauri::command]
+fn vault_health(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<HealthReport, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(health::evaluate(&index)?)
+}
+
+/// Résumé d'activité du badge d'état (envois en attente, échecs, conflits, intégrité).
+#[tauri::command]
+fn activity_summary(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ActivitySummary, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(activity::summary(&index)?)
+}
+
+/// Remet à zéro les échecs et conflits du badge une fois vus ; le badge est rafraîchi aussitôt.
+#[tauri::command]
+async fn activity_acknowledge(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ActivitySummary, CommandError> {
+    let writer = index_writer(&app, &state)?;
+    writer.write(activity::acknowledge).await??;
+    let summary = activity::summary(&open_index_with_state(&app, &state)?)?;
+    events::emit(&app, activity_event(&summary));
+    Ok(summary)
+}
+
+fn activity_event(summary: &ActivitySummary) -> AppEvent {
+    AppEvent::ActivitySummary {
+        pending_uploads: summary.pending_uploads,
+        failed_transfers: summary.failed_transfers,
+        conflicts: summary.conflicts,
+        integrity_warnings: summary.integrity_warnings,
+    }
+}
+
+/// Fréquence de recalcul du résumé d'activité (lecture locale de l'index, peu coûteuse).
+const ACTIVITY_SUMMARY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Boucle de fond tenant le badge d'état à jour : le résumé n'est émis que lorsqu'il change,
+/// et une fois au déverrouillage.
+async fn activity_scheduler(app: tauri::AppHandle) {
+    let mut ticker = tokio::time::interval(ACTIVITY_SUMMARY_INTERVAL);
+    let mut last: Option<ActivitySummary> = None;
+    loop {
+        ticker.tick().await;
+        let state = app.state::<AppState>();
+        if state.storj_client.lock().await.is_none() {
+            last = None;
+            continue;
+        }
+        let summary = match open_index_with_state(&app, &state).map(|index| activity::summary(&index)) {
+            Ok(Ok(summary)) => summary,
+            Ok(Err(e)) => {
+                log::warn!("Activity summary failed: {}", e);
+                continue;
+            }
+            Err(_) => continue,
+        };
+        if last != Some(summary) {
+            events::emit(&app, activity_event(&summary));
+            last = Some(summary);
+        }
+    }
+}
+
+#[tauri::command]
+async fn integrity_check_now(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<IntegrityReport, CommandError> {
+    run_integrity_check(&app, &state).await
+}
+
+/// Fichiers en quarantaine (objet distant altéré) et réparations possibles pour chacun, par pages.
+#[tauri::command]
+async fn quarantine_list(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    page: Option<PageRequest>,
+) -> Result<Page<QuarantinedFile>, CommandError> {
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(paging::paginate(quarantine::list(&vault)?, &page.unwrap_or_default())?)
+}
+
+/// Relit l'objet distant en entier ; la quarantaine est levée s'il vérifie désormais.
+#[tauri::command]
+async fn quarantine_recheck(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_id: String,
+) -> Result<bool, CommandError> {
+    log::info!("quarantine_recheck called: file_id={}", file_id);
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(quarantine::recheck(&vault, &file_id).await?)
+}
+
+/// Remplace un fichier en quarantaine par l'une de ses versions antérieures.
+#[tauri::command]
+async fn quarantine_restore_version(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_id: String,
+    version_id: String,
+) -> Result<FileMetadata, CommandError> {
+    log::info!("quarantine_restore_version called: file_id={}, version_id={}", file_id, version_id);
+    telemetry::record_feature("quarantine_repair");
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(quarantine::restore_version(&vault, &file_id, &version_id).await?)
+}
+
+/// Renvoie vers le stockage distant la copie chiffrée intacte conservée dans le cache local.
+#[tauri::command]
+async fn quarantine_restore_local_copy(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_id: String,
+) -> Result<(), CommandError> {
+    log::info!("quarantine_restore_local_copy called: file_id={}", file_id);
+    telemetry::record_feature("quarantine_repair");
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(quarantine::restore_local_copy(&vault, &file_id).await?)
+}
+
+/// Abandonne un fichier en quarantaine : il est supprimé définitivement, objet distant compris.
+#[tauri::command]
+async fn quarantine_discard(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_id: String,
+) -> Result<(), CommandError> {
+    log::info!("quarantine_discard called: file_id={}", file_id);
+    telemetry::record_feature("quarantine_repair");
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(quarantine::discard(&vault, &file_id).await?)
+}
+
+/// Nom affiché dans les verrous pris sans nom explicite : l'utilisateur du système.
+fn default_lock_holder() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| "Aether Drive".to_string())
+}
+
+/// Signale la modification d'un document verrouillé par un autre appareil. Le verrou est
+/// consultatif : la modification n'est jamais bloquée, et un bucket injoignable ne la
+/// retarde pas davantage.
+async fn warn_if_locked(app: &tauri::AppHandle, vault: &Vault, logical_path: &str) {
+    match locks::conflicting(vault, logical_path).await {
+        Ok(Some(lock)) => {
+            log::warn!("Editing a file locked by device {}: {}", lock.device, logical_path);
+            events::emit(
+                app,
+                AppEvent::LockedFileEdited {
+                    logical_path: lock.logical_path,
+                    holder: lock.holder,
+                    expires_at: lock.expires_at,
+                },
+            );
+        }
+        Ok(None) => {}
+        Err(e) => log::debug!("Lock check skipped for {}: {}", logical_path, e),
+    }
+}
+
+/// Verrous actifs sur les documents du coffre, tous appareils confondus.
+#[tauri::command]
+async fn locks_list(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<Vec<locks::FileLock>, CommandError> {
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(locks::list(&vault).await?)
+}
+
+/// Verrouille (ou prolonge le verrou d')un document pour les autres appareils.
+#[tauri::command]
+async fn lock_acquire(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    logical_path: String,
+    holder: Option<String>,
+    ttl_secs: Option<i64>,
+) -> Result<locks::FileLock, CommandError> {
+    log::info!("lock_acquire called: logical_path={}, ttl_secs={:?}", logical_path, ttl_secs);
+    telemetry::record_feature("file_lock");
+    let vault = vault_from_state(&app, &state).await?;
+    let holder = holder.unwrap_or_else(default_lock_holder);
+    Ok(locks::acquire(&vault, &logical_path, &holder, ttl_secs).await?)
+}
+
+/// Occupation de la file des transferts (interactifs et de masse, en cours et en attente).
+#[tauri::command]
+fn transfers_status(state: State<'_, AppState>) -> transfers::QueueStatus {
+    state.transfers.status()
+}
+
+/// Autres appareils en ligne sur ce coffre ; publie au passage le battement de cet appareil.
+#[tauri::command]
+async fn sessions_status(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<sessions::SessionStatus, CommandError> {
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(sessions::heartbeat(&vault, &default_lock_holder()).await?)
+}
+
+/// Boucle de fond publiant le battement de cet appareil tant que le coffre est déverrouillé.
+/// Signale quand d'autres appareils se mettent à modifier le coffre, puis quand ils cessent.
+async fn session_scheduler(app: tauri::AppHandle) {
+    let mut ticker = tokio::time::interval(sessions::HEARTBEAT_INTERVAL);
+    // Appareils déjà signalés, pour ne pas répéter l'alerte à chaque battement
+    let mut signalled: Vec<String> = Vec::new();
+    loop {
+        ticker.tick().await;
+        let state = app.state::<AppState>();
+        if state.storj_client.lock().await.is_none() {
+            signalled.clear();
+            continue;
+        }
+        let result = async {
+            let vault = vault_from_state(&app, &state).await?;
+            Ok::<_, CommandError>(sessions::heartbeat(&vault, &default_lock_holder()).await?)
+        }
+        .await;
+        match result {
+            Ok(status) => {
+                let devices: Vec<String> =
+                    status.peers.into_iter().filter(|peer| peer.mutating).map(|peer| peer.label).collect();
+                if devices != signalled {
+                    log::info!("Concurrent sessions changed: mutating_peers={}, mode={:?}", devices.len(), status.mode);
+                    events::emit(&app, AppEvent::ConcurrentSessions { devices: devices.clone() });
+                    signalled = devices;
+                }
+            }
+            Err(e) => log::warn!("Session heartbeat failed: {}", e),
+        }
+    }
+}
+
+/// Retire le battement de cet appareil avant le verrouillage, sans le retarder si le
+/// bucket ne répond pas (le battement expire alors de lui-même).
+async fn end_session(app: &tauri::AppHandle, state: &State<'_, AppState>) {
+    if state.storj_client.lock().await.is_none() {
+        return;
+    }
+    let ended = async {
+        let vault = vault_from_state(app, state).await?;
+        Ok::<_, CommandError>(sessions::end(&vault).await?)
+    };
+    match tokio::time::timeout(std::time::Duration::from_secs(5), ended).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => log::warn!("Failed to end session: {}", e),
+        Err(_) => log::warn!("Failed to end session: storage did not answer"),
+    }
+}
+
+/// Libère le verrou de cet appareil sur un document.
+#[tauri::command]
+async fn lock_release(app: tauri::AppHandle, state: State<'_, AppState>, logical_path: String) -> Result<bool, CommandError> {
+    log::info!("lock_release called: logical_path={}", logical_path);
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(locks::release(&vault, &logical_path, false).await?)
+}
+
+/// Libère le verrou d'un autre appareil (perdu, ou verrou oublié).
+#[tauri::command]
+async fn lock_break(app: tauri::AppHandle, state: State<'_, AppState>, logical_path: String) -> Result<bool, CommandError> {
+    log::info!("lock_break called: logical_path={}", logical_path);
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(locks::release(&vault, &logical_path, true).await?)
+}
+
 /// Obtient la MasterKey depuis l'état global (doit être déverrouillée).
-fn get_master_key_from_state(state: State<'_, AppState>) -> Result<MasterKey, String> {
+fn get_master_key_from_state(state: State<'_, AppState>) -> Result<MasterKey, CommandError> {
     let master_key_guard = state
         .master_key
         .lock()
-        .map_err(|e| format!("Lock error: {}", e))?;
+        .map_err(|e| CommandError::internal("Lock error", e))?;
     let master_key = master_key_guard
         .as_ref()
-        .ok_or_else(|| "MasterKey not available. Unlock the vault first.".to_string())?;
+        .ok_or(CommandError::VaultLocked)?;
     
     // Clone la MasterKey pour l'utiliser
     let master_key_bytes = master_key.as_bytes().to_vec();
@@ -724,29 +2737,33 @@ fn storage_encrypt_file(
     state: State<'_, AppState>,
     data: Vec<u8>,
     logical_path: String,
-) -> Result<Vec<u8>, String> {
+) -> Result<Vec<u8>, CommandError> {
     log::info!(
         "storage_encrypt_file called: logical_path={}, data_len={}",
         logical_path,
         data.len()
     );
+    let logical_path = canonical_file_path(&logical_path)?;
     
     let master_key = {
         let master_key_guard = state
             .master_key
             .lock()
-            .map_err(|e| format!("Lock error: {}", e))?;
+            .map_err(|e| CommandError::internal("Lock error", e))?;
         let master_key = master_key_guard
             .as_ref()
-            .ok_or_else(|| "MasterKey not available. Unlock the vault first.".to_string())?;
+            .ok_or(CommandError::VaultLocked)?;
         
         // Clone la MasterKey pour l'utiliser
         let master_key_bytes = master_key.as_bytes().to_vec();
         crate::crypto::MasterKey::from_vec(master_key_bytes)
     };
     
-    let aether_file = crate::storage::encrypt_file(&master_key, &data, &logical_path)
-        .map_err(|e| format!("Failed to encrypt file: {}", e))?;
+    // Compression (selon le type du contenu) et bourrage selon la politique du dossier de destination
+    let mime_type = mime::detect(&data[..data.len().min(mime::SNIFF_LEN)], &logical_path);
+    let encoding = policies::effective(&open_index_with_state(&app, &state)?, &logical_path)?.encoding(mime_type, data.len() as u64);
+    let aether_file = crate::storage::encrypt_file_encoded(&master_key, &data, &logical_path, encoding)
+        .map_err(|e| CommandError::EncryptionFailed(e.to_string()))?;
     
     let serialized = aether_file.to_bytes();
     
@@ -754,12 +2771,7 @@ fn storage_encrypt_file(
     let uuid_hex = hex::encode(aether_file.header.uuid);
     let file_id = uuid_hex.clone();
     
-    log::info!(
-        "File encrypted successfully: serialized_size={}, uuid={:?}, file_id={}",
-        serialized.len(),
-        aether_file.header.uuid,
-        file_id
-    );
+    log::info!("File encrypted successfully: serialized_size={}, file_id={}", serialized.len(), file_id);
     
     // Ajoute automatiquement le fichier à l'index local après chiffrement
     match open_index_with_state(&app, &state) {
@@ -772,6 +2784,12 @@ fn storage_encrypt_file(
             match index.upsert(file_id.clone(), metadata) {
                 Ok(_) => {
                     log::info!("File {} automatically added to local index after encryption", file_id);
+                    // Type et métadonnées des photos lus sur le clair, seul moment où l'application le voit en entier
+                    let recorded = vault::record_content_type(&mut index, &file_id, &logical_path, &data)
+                        .and_then(|()| index.set_plaintext_size(&file_id, data.len() as u64));
+                    if let Err(e) = recorded {
+                        log::warn!("Failed to record the content type and size of {}: {}", file_id, e);
+                    }
                 }
                 Err(e) => {
                     log::warn!("Failed to add file {} to local index after encryption: {}", file_id, e);
@@ -788,25 +2806,102 @@ fn storage_encrypt_file(
     Ok(serialized)
 }
 
+/// Comme `storage_encrypt_file`, en lisant le fichier local `local_path` dans le cœur Rust :
+/// il est chiffré par blocs dans la zone de transit puis placé dans le cache local des
+/// blobs, et seules les informations d'en-tête repassent par l'IPC.
 #[tauri::command]
-fn storage_decrypt_file(
+fn storage_encrypt_file_from_path(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    local_path: String,
+    logical_path: String,
+) -> Result<FileInfo, CommandError> {
+    use std::io::Read;
+    log::info!("storage_encrypt_file_from_path called: local_path={}, logical_path={}", local_path, logical_path);
+    let logical_path = canonical_file_path(&logical_path)?;
+    let source = PathBuf::from(&local_path);
+    if !source.is_file() {
+        return Err(CommandError::PathNotFound { path: local_path });
+    }
+    let master_key = get_master_key_from_state(state.clone())?;
+
+    // Début du fichier : type du contenu (compression) et métadonnées des photos
+    let mut head = Vec::new();
+    std::fs::File::open(&source)
+        .and_then(|file| file.take(photos::exif::HEAD_LEN as u64).read_to_end(&mut head))
+        .map_err(|e| CommandError::io("Failed to read source file", e))?;
+    let plaintext_len = std::fs::metadata(&source)
+        .map_err(|e| CommandError::io("Failed to read source file", e))?
+        .len();
+    let mut index = open_index_with_state(&app, &state)?;
+    let mime_type = mime::detect(&head[..head.len().min(mime::SNIFF_LEN)], &logical_path);
+    let encoding = policies::effective(&index, &logical_path)?.encoding(mime_type, plaintext_len);
+    let (staged, _) = open_staging(&app)?
+        .encrypt_path(&master_key, &source, &logical_path, encoding)
+        .map_err(|e| CommandError::EncryptionFailed(e.to_string()))?;
+
+    let mut raw_header = [0u8; AetherFile::HEADER_LEN];
+    std::fs::File::open(staged.path())
+        .and_then(|mut file| file.read_exact(&mut raw_header))
+        .map_err(|e| CommandError::io("Failed to read encrypted file", e))?;
+    let (header, ciphertext_len) = AetherFile::parse_header(&raw_header)?;
+    let file_id = staged.file_id().clone();
+    let encrypted_size = staged.len();
+    staged
+        .move_into(&open_blob_cache(&app)?)
+        .map_err(|e| CommandError::io("Failed to keep encrypted file in local cache", e))?;
+    log::info!("File encrypted successfully: serialized_size={}, file_id={}", encrypted_size, file_id);
+
+    // Ajoute le fichier à l'index local, comme après `storage_encrypt_file`
+    let metadata = FileMetadata {
+        logical_path: logical_path.clone(),
+        encrypted_size,
+    };
+    match index.upsert(file_id.clone(), metadata) {
+        Ok(_) => {
+            let recorded = vault::record_content_type(&mut index, &file_id, &logical_path, &head)
+                .and_then(|()| index.set_plaintext_size(&file_id, plaintext_len));
+            if let Err(e) = recorded {
+                log::warn!("Failed to record the content type and size of {}: {}", file_id, e);
+            }
+        }
+        Err(e) => log::warn!("Failed to add file {} to local index after encryption: {}", file_id, e),
+    }
+
+    Ok(FileInfo {
+        uuid: header.uuid.to_vec(),
+        version: header.version,
+        cipher_id: header.cipher_id,
+        encrypted_size: ciphertext_len as usize,
+    })
+}
+
+#[tauri::command]
+fn storage_decrypt_file(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
     encrypted_data: Vec<u8>,
     logical_path: String,
-) -> Result<Vec<u8>, String> {
+) -> Result<Vec<u8>, CommandError> {
     log::info!(
         "storage_decrypt_file called: logical_path={}, encrypted_data_len={}",
         logical_path,
         encrypted_data.len()
     );
     
-    let master_key = get_master_key_from_state(state)?;
+    let master_key = get_master_key_from_state(state.clone())?;
     
-    let aether_file = AetherFile::from_bytes(&encrypted_data)
-        .map_err(|e| format!("Failed to parse Aether file: {}", e))?;
+    let aether_file = AetherFile::from_bytes(&encrypted_data)?;
     
-    let plaintext = crate::storage::decrypt_file(&master_key, &aether_file, &logical_path)
-        .map_err(|e| format!("Failed to decrypt file: {}", e))?;
+    // Un fichier d'un dossier renommé reste lié à son ancien chemin tant qu'il n'est pas re-chiffré
+    let file_id = hex::encode(aether_file.header.uuid);
+    let bound_path = open_index_with_state(&app, &state)
+        .ok()
+        .and_then(|index| index.bound_path(&file_id).ok().flatten());
+    let plaintext = match bound_path.and_then(|path| crate::storage::decrypt_file(&master_key, &aether_file, &path).ok()) {
+        Some(plaintext) => plaintext,
+        None => crate::storage::decrypt_file(&master_key, &aether_file, &logical_path)?,
+    };
     
     log::info!("File decrypted successfully: plaintext_len={}", plaintext.len());
     
@@ -814,11 +2909,10 @@ fn storage_decrypt_file(
 }
 
 #[tauri::command]
-fn storage_get_file_info(encrypted_data: Vec<u8>) -> Result<FileInfo, String> {
+fn storage_get_file_info(encrypted_data: Vec<u8>) -> Result<FileInfo, CommandError> {
     log::info!("storage_get_file_info called: encrypted_data_len={}", encrypted_data.len());
     
-    let aether_file = AetherFile::from_bytes(&encrypted_data)
-        .map_err(|e| format!("Failed to parse Aether file: {}", e))?;
+    let aether_file = AetherFile::from_bytes(&encrypted_data)?;
     
     Ok(FileInfo {
         uuid: aether_file.header.uuid.to_vec(),
@@ -838,45 +2932,21 @@ pub struct SelectedFile {
 
 /// Sélectionne un fichier depuis le système de fichiers et retourne son contenu.
 #[tauri::command]
-async fn select_and_read_file(app: tauri::AppHandle) -> Result<SelectedFile, String> {
-    use tauri_plugin_dialog::DialogExt;
-    use tokio::sync::oneshot;
-    
+async fn select_and_read_file(app: tauri::AppHandle) -> Result<SelectedFile, CommandError> {
     log::info!("select_and_read_file called");
     
-    // Utilise un oneshot channel pour recevoir le résultat de manière asynchrone
-    let (tx, rx) = oneshot::channel();
-    
-    // Ouvre le dialogue de sélection de fichier
-    app.dialog()
-        .file()
-        .set_title("Sélectionner un fichier à chiffrer")
-        .pick_file(move |path_opt| {
-            let _ = tx.send(path_opt);
-        });
-    
-    // Attendre le résultat avec timeout
-    let path_opt = tokio::time::timeout(std::time::Duration::from_secs(30), rx)
-        .await
-        .map_err(|_| "Timeout lors de la sélection de fichier".to_string())?
-        .map_err(|_| "Erreur lors de la réception du résultat".to_string())?;
-    
-    let file_path = path_opt.ok_or_else(|| "Aucun fichier sélectionné".to_string())?;
-    // FilePath implémente Display, on peut le convertir en String puis en PathBuf
-    let path_buf = PathBuf::from(file_path.to_string());
-    let path_str = path_buf.to_string_lossy().to_string();
-    let file_name = path_buf
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("fichier")
-        .to_string();
+    let file_path = picker::pick_open(&app, "Sélectionner un fichier à chiffrer").await?;
+    let path_str = file_path.to_string();
+    let file_name = picker::display_name(&file_path);
     
     log::info!("File selected: path={}, name={}", path_str, file_name);
     
-    // Lit le contenu du fichier de manière asynchrone
-    let data = tokio::fs::read(&path_buf)
+    // Lit le contenu du fichier hors du thread asynchrone (URI de la plateforme comprises)
+    let reader = app.clone();
+    let data = tokio::task::spawn_blocking(move || picker::read(&reader, &file_path))
         .await
-        .map_err(|e| format!("Erreur lors de la lecture du fichier: {}", e))?;
+        .map_err(|e| CommandError::internal("Read task failed", e))?
+        .map_err(|e| CommandError::io("Failed to read file", e))?;
     
     let size = data.len();
     log::info!("File read successfully: size={} bytes", size);
@@ -891,7 +2961,7 @@ async fn select_and_read_file(app: tauri::AppHandle) -> Result<SelectedFile, Str
 
 /// Lit un fichier depuis un chemin de fichier (utilisé pour le drag & drop natif).
 #[tauri::command]
-async fn select_and_read_file_from_path(file_path: String) -> Result<SelectedFile, String> {
+async fn select_and_read_file_from_path(file_path: String) -> Result<SelectedFile, CommandError> {
     log::info!("select_and_read_file_from_path called: path={}", file_path);
     
     let path_buf = PathBuf::from(&file_path);
@@ -907,7 +2977,7 @@ async fn select_and_read_file_from_path(file_path: String) -> Result<SelectedFil
     // Lit le contenu du fichier de manière asynchrone
     let data = tokio::fs::read(&path_buf)
         .await
-        .map_err(|e| format!("Erreur lors de la lecture du fichier: {}", e))?;
+        .map_err(|e| CommandError::io("Failed to read file", e))?;
     
     let size = data.len();
     log::info!("File read successfully: size={} bytes", size);
@@ -926,47 +2996,102 @@ async fn save_decrypted_file(
     app: tauri::AppHandle,
     data: Vec<u8>,
     suggested_name: String,
-) -> Result<String, String> {
-    use tauri_plugin_dialog::DialogExt;
-    use tokio::sync::oneshot;
-    
+) -> Result<String, CommandError> {
     log::info!("save_decrypted_file called: suggested_name={}, data_len={}", suggested_name, data.len());
     
-    // Utilise un oneshot channel pour recevoir le résultat de manière asynchrone
-    let (tx, rx) = oneshot::channel();
-    
-    // Ouvre le dialogue de sauvegarde de fichier
-    app.dialog()
-        .file()
-        .set_title("Sauvegarder le fichier déchiffré")
-        .set_file_name(&suggested_name)
-        .save_file(move |path_opt| {
-            let _ = tx.send(path_opt);
-        });
-    
-    // Attendre le résultat avec timeout
-    let path_opt = tokio::time::timeout(std::time::Duration::from_secs(30), rx)
-        .await
-        .map_err(|_| "Timeout lors de la sélection du fichier de sauvegarde".to_string())?
-        .map_err(|_| "Erreur lors de la réception du résultat".to_string())?;
-    
-    let file_path = path_opt.ok_or_else(|| "Aucun fichier sélectionné pour la sauvegarde".to_string())?;
-    let path_buf = PathBuf::from(file_path.to_string());
-    let path_str = path_buf.to_string_lossy().to_string();
+    let file_path = picker::pick_save(&app, "Sauvegarder le fichier déchiffré", &suggested_name).await?;
+    let path_str = file_path.to_string();
     
     log::info!("Saving file to: {}", path_str);
     
-    // Sauvegarde le fichier de manière asynchrone
-    tokio::fs::write(&path_buf, &data)
+    // Sauvegarde le fichier hors du thread asynchrone (URI de la plateforme comprises)
+    let writer = app.clone();
+    tokio::task::spawn_blocking(move || picker::write(&writer, &file_path, &data))
         .await
-        .map_err(|e| format!("Erreur lors de l'écriture du fichier: {}", e))?;
+        .map_err(|e| CommandError::internal("Write task failed", e))?
+        .map_err(|e| CommandError::io("Failed to write file", e))?;
     
     log::info!("File saved successfully: {}", path_str);
     
     Ok(path_str)
 }
 
-#[derive(Debug, Deserialize)]
+/// Télécharge un fichier et le déchiffre directement dans la destination choisie.
+///
+/// La destination est choisie avant le transfert ; le ciphertext est écrit au fil du
+/// téléchargement puis déchiffré sur place (voir `Vault::save_to`), sans copie du clair en
+/// mémoire. Une URI de la plateforme (Android) reçoit une copie en flux du fichier déchiffré
+/// dans le dossier temporaire.
+#[tauri::command]
+async fn download_to_file(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    logical_path: String,
+) -> Result<String, CommandError> {
+    log::info!("download_to_file called: logical_path={}", logical_path);
+
+    let file_id = open_index_with_state(&app, &state)?
+        .list_all()
+        .map_err(|e| CommandError::index("Failed to list files from index", e))?
+        .into_iter()
+        .find(|(_, meta)| meta.logical_path == logical_path)
+        .map(|(file_id, _)| file_id)
+        .ok_or_else(|| CommandError::PathNotFound { path: logical_path.clone() })?;
+    let suggested_name = logical_path.rsplit('/').next().filter(|name| !name.is_empty()).unwrap_or("fichier");
+    let file_path = picker::pick_save(&app, "Sauvegarder le fichier déchiffré", suggested_name).await?;
+    let path_str = file_path.to_string();
+
+    let vault = vault_from_state(&app, &state).await?;
+    let (target, scratch) = match file_path.clone().into_path() {
+        Ok(local) => (local, None),
+        Err(_) => {
+            let scratch_dir = get_db_path(&app)?.with_file_name(recovery::SCRATCH_DIR);
+            std::fs::create_dir_all(&scratch_dir).map_err(|e| CommandError::io("Failed to create scratch directory", e))?;
+            let mut suffix = [0u8; 8];
+            rand::thread_rng().fill_bytes(&mut suffix);
+            let scratch = scratch_dir.join(format!("{}.tmp", hex::encode(suffix)));
+            (scratch.clone(), Some(scratch))
+        }
+    };
+    let reporter = TransferReporter::new(&app, &file_id, TransferDirection::Download);
+    // La fin n'est rapportée qu'une fois le fichier vérifié et écrit à destination
+    let mut total = 0;
+    let progress = |bytes_done: u64, bytes_total: u64| {
+        total = bytes_total;
+        if bytes_done < bytes_total {
+            reporter.report(bytes_done, bytes_total);
+        }
+    };
+    let saved = vault.save_to(&file_id, &logical_path, &target, progress).await;
+    let size = match (saved, scratch) {
+        (Ok(size), None) => size,
+        (saved, Some(scratch)) => {
+            let copied = match saved {
+                Ok(size) => {
+                    let (writer, source) = (app.clone(), scratch.clone());
+                    tokio::task::spawn_blocking(move || picker::write_from(&writer, &file_path, &source))
+                        .await
+                        .map_err(|e| CommandError::internal("Write task failed", e))?
+                        .map_err(|e| CommandError::io("Failed to write file", e))
+                        .map(|_| size)
+                }
+                Err(e) => Err(e.into()),
+            };
+            let _ = std::fs::remove_file(&scratch);
+            copied?
+        }
+        (Err(e), None) => return Err(e.into()),
+    };
+    note_access(&vault, &file_id);
+    telemetry::record_transfer(TransferDirection::Download, size);
+    reporter.report(total, total);
+    tray::push_recent(&app, &logical_path);
+
+    log::info!("File saved successfully: {} ({} bytes)", path_str, size);
+    Ok(path_str)
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct StorjConfigRequest {
     #[serde(rename = "accessKeyId")]
     pub access_key_id: String,
@@ -979,11 +3104,13 @@ pub struct StorjConfigRequest {
 
 #[tauri::command]
 async fn storj_configure(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
     config: StorjConfigRequest,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     log::info!("storj_configure called: endpoint={}, bucket={}", config.endpoint, config.bucket_name);
     
+    let master_key = get_master_key_from_state(state.clone())?;
     let storj_config = StorjConfig::new(
         config.access_key_id,
         config.secret_access_key,
@@ -995,630 +3122,3183 @@ async fn storj_configure(
         .await
         .map_err(|e| {
             log::error!("Failed to create Storj client: {}", e);
-            format!("Failed to create Storj client: {}", e)
-        })?;
+            CommandError::remote("Failed to create Storj client", e)
+        })?
+        .with_prefix(storj::vault_prefix(&master_key.fingerprint()));
     
-    let mut client_guard = state.storj_client.lock().await;
-    *client_guard = Some(Arc::new(client));
+    *state.storj_client.lock().await = Some(Arc::new(client));
+    *state.delegated_credentials.lock().await = None;
     
     log::info!("Storj client configured successfully");
+    spawn_recovery_if_pending(&app);
     Ok(())
 }
 
+/// Enregistre les identifiants Storj dans le trousseau de l'OS (scellés avec la MasterKey).
 #[tauri::command]
-async fn storj_upload_file(
+fn storj_save_credentials(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-    encrypted_data: Vec<u8>,
-    logical_path: String,
-) -> Result<String, String> {
-    log::info!("storj_upload_file called: logical_path={}, data_len={}", logical_path, encrypted_data.len());
-    
-    // Parse le fichier Aether pour obtenir l'UUID
-    let aether_file = AetherFile::from_bytes(&encrypted_data)
-        .map_err(|e| format!("Failed to parse Aether file: {}", e))?;
-    
-    // Utilise l'UUID comme clé d'objet dans Storj
-    let uuid_hex = hex::encode(aether_file.header.uuid);
-    let object_key = format!("{}", uuid_hex);
-    
-    log::info!("Preparing Storj upload: object_key={}, file_id={}", object_key, uuid_hex);
-    
-    // Utilise l'UUID comme FileId dans l'index local
-    let file_id = uuid_hex.clone();
-    
-    let client = {
-        let client_guard = state.storj_client.lock().await;
-        client_guard.clone()
-            .ok_or_else(|| "Storj client not configured. Call storj_configure first.".to_string())?
-    };
-    
-    // Upload vers Storj
-    let etag = client.upload_file(&object_key, &encrypted_data)
-        .await
-        .map_err(|e| {
-            log::error!("Storj upload failed: object_key={}, error={}", object_key, e);
-            format!("Failed to upload file to Storj: {}", e)
-        })?;
-    
-    log::info!("File uploaded successfully to Storj: object_key={}, etag={}", object_key, etag);
-    
-    // Synchronise avec l'index local : ajoute l'entrée après upload réussi
-    let mut index = open_index_with_state(&app, &state)
-        .map_err(|e| {
-            log::error!("Failed to open index for sync: {}", e);
-            format!("Failed to sync with local index: {}", e)
-        })?;
-    
-    let metadata = FileMetadata {
-        logical_path: logical_path.clone(),
-        encrypted_size: encrypted_data.len() as u64,
+    config: StorjConfigRequest,
+) -> Result<(), CommandError> {
+    log::info!("storj_save_credentials called: endpoint={}, bucket={}", config.endpoint, config.bucket_name);
+
+    let master_key = get_master_key_from_state(state)?;
+    let credentials = StorjCredentials {
+        access_key_id: config.access_key_id,
+        secret_access_key: config.secret_access_key,
+        endpoint: config.endpoint,
+        bucket_name: config.bucket_name,
+        expires_at: None,
+        delegation: None,
     };
-    
-    index.upsert(file_id.clone(), metadata)
+
+    KeychainStore::storj_for_workspace(&active_workspace_id(&app))
+        .and_then(|store| store.save(&master_key, &credentials))
         .map_err(|e| {
-            log::error!("Failed to add file to index after Storj upload: {}", e);
-            format!("File uploaded to Storj but failed to sync with local index: {}", e)
+            log::error!("Failed to save Storj credentials to keychain: {}", e);
+            CommandError::from(e)
         })?;
-    
-    log::info!("File synchronized with local index: file_id={}, logical_path={}", file_id, logical_path);
-    Ok(etag)
+
+    log::info!("Storj credentials saved to OS keychain");
+    Ok(())
 }
 
+/// Configure le client Storj depuis le trousseau de l'OS.
+///
+/// Retourne `false` si aucun identifiant n'est enregistré : les clés ne transitent
+/// jamais vers le frontend.
 #[tauri::command]
-async fn storj_download_file(
+async fn storj_load_credentials(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
-    file_uuid: Vec<u8>,
-) -> Result<Vec<u8>, String> {
-    log::info!("storj_download_file called: uuid={:?}", file_uuid);
-    
-    if file_uuid.len() != 16 {
-        return Err("Invalid UUID length".to_string());
-    }
-    
-    // Utilise l'UUID comme clé d'objet dans Storj
-    let uuid_hex = hex::encode(&file_uuid);
-    let object_key = format!("{}", uuid_hex);
-    
-    let client = {
-        let client_guard = state.storj_client.lock().await;
-        client_guard.clone()
-            .ok_or_else(|| "Storj client not configured. Call storj_configure first.".to_string())?
+) -> Result<bool, CommandError> {
+    log::info!("storj_load_credentials called");
+    configure_from_keychain(&app, &state).await
+}
+
+/// Configure le client de l'espace actif depuis le trousseau ; `false` sans identifiants.
+async fn configure_from_keychain(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+) -> Result<bool, CommandError> {
+    let master_key = get_master_key_from_state(state.clone())?;
+
+    let credentials = KeychainStore::storj_for_workspace(&active_workspace_id(app))
+        .and_then(|store| store.load(&master_key))
+        .map_err(|e| {
+            log::error!("Failed to load Storj credentials from keychain: {}", e);
+            CommandError::from(e)
+        })?;
+
+    let Some(credentials) = credentials else {
+        log::info!("No Storj credentials stored in OS keychain");
+        return Ok(false);
     };
-    
-    let data = client.download_file(&object_key)
+
+    let delegated = credentials.delegation.is_some().then(|| credentials.clone());
+    let client = StorjClient::new(credentials.into_config())
         .await
-        .map_err(|e| format!("Failed to download file from Storj: {}", e))?;
-    
-    log::info!("File downloaded successfully from Storj: object_key={}, data_len={}", object_key, data.len());
-    Ok(data)
+        .map_err(|e| CommandError::remote("Failed to create Storj client", e))?
+        .with_prefix(storj::vault_prefix(&master_key.fingerprint()));
+
+    *state.storj_client.lock().await = Some(Arc::new(client));
+    *state.delegated_credentials.lock().await = delegated;
+
+    log::info!("Storj client configured from OS keychain");
+    spawn_recovery_if_pending(app);
+    Ok(true)
+}
+
+/// Supprime les identifiants Storj du trousseau de l'OS.
+#[tauri::command]
+async fn storj_delete_credentials(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), CommandError> {
+    log::info!("storj_delete_credentials called");
+
+    // Vérifie que le coffre est déverrouillé avant toute opération sur le trousseau.
+    get_master_key_from_state(state.clone())?;
+
+    KeychainStore::storj_for_workspace(&active_workspace_id(&app))
+        .and_then(|store| store.delete())?;
+    // Sans quoi le prochain renouvellement les réenregistrerait
+    *state.delegated_credentials.lock().await = None;
+
+    log::info!("Storj credentials removed from OS keychain");
+    Ok(())
 }
 
+/// Nature des identifiants de stockage en cours, sans les clés.
 #[derive(Debug, Serialize)]
-pub struct StorjFileInfo {
-    pub uuid: String,
-    pub logical_path: Option<String>,
-    pub encrypted_size: Option<u64>,
+pub struct CredentialsStatus {
+    pub delegated: bool,
+    pub issuer_url: Option<String>,
+    pub expires_at: Option<i64>,
 }
 
-#[tauri::command]
-async fn storj_list_files(
-    app: tauri::AppHandle,
-    state: State<'_, AppState>,
-) -> Result<Vec<StorjFileInfo>, String> {
-    log::info!("storj_list_files called");
-    
-    let client = {
-        let client_guard = state.storj_client.lock().await;
-        client_guard.clone()
-            .ok_or_else(|| "Storj client not configured. Call storj_configure first.".to_string())?
-    };
-    
-    let keys = client.list_files()
+impl CredentialsStatus {
+    fn of(credentials: Option<&StorjCredentials>) -> Self {
+        let delegation = credentials.and_then(|c| c.delegation.as_ref());
+        Self {
+            delegated: delegation.is_some(),
+            issuer_url: delegation.map(|d| d.issuer_url.clone()),
+            expires_at: credentials.and_then(|c| c.expires_at),
+        }
+    }
+}
+
+/// Fréquence de vérification de l'échéance des identifiants délégués.
+const CREDENTIALS_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+/// Enregistre des identifiants délégués et bascule le client dessus.
+///
+/// Le trousseau passe en premier : l'émetteur peut avoir remplacé le jeton de
+/// renouvellement, qui ne doit pas être perdu si la suite échoue.
+async fn install_delegated_credentials(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    credentials: &StorjCredentials,
+) -> Result<(), CommandError> {
+    let master_key = get_master_key_from_state(state.clone())?;
+    KeychainStore::storj_for_workspace(&active_workspace_id(app))
+        .and_then(|store| store.save(&master_key, credentials))?;
+    let client = StorjClient::new(credentials.clone().into_config())
         .await
-        .map_err(|e| format!("Failed to list files from Storj: {}", e))?;
-    
-    log::info!("Listed {} files from Storj", keys.len());
-    
-    // Normalise les UUIDs Storj (enlève les tirets) pour correspondre au format de l'index local
-    let storj_uuids_normalized: std::collections::HashSet<String> = keys
-        .iter()
-        .map(|uuid| uuid.replace("-", "").to_lowercase())
-        .collect();
-    
-    // Pour chaque UUID, essaie de trouver les métadonnées dans l'index local
-    // Si l'index n'est pas disponible, on retourne juste les UUIDs sans métadonnées
-    let mut files_with_metadata = Vec::new();
-    
-    match open_index_with_state(&app, &state) {
-        Ok(mut index) => {
-            // Nettoyage de l'index local : supprime les fichiers qui n'existent plus dans Storj
-            let all_local_files = index.list_all().ok().unwrap_or_default();
-            log::info!("Local index contains {} files", all_local_files.len());
-            
-            for (file_id, _) in all_local_files {
-                if !storj_uuids_normalized.contains(&file_id) {
-                    log::info!("Removing orphaned file from local index: {}", file_id);
-                    if let Err(e) = index.remove(&file_id) {
-                        log::warn!("Failed to remove orphaned file {}: {}", file_id, e);
-                    }
-                }
-            }
-            
-            // Maintenant, récupère les métadonnées pour chaque fichier Storj
-            for uuid_from_storj in keys {
-                // Normalise l'UUID : enlève les tirets pour correspondre au format de l'index local
-                let uuid_normalized = uuid_from_storj.replace("-", "").to_lowercase();
-                
-                // Essaie de trouver le fichier dans l'index local avec l'UUID normalisé
-                let mut metadata = index.get(&uuid_normalized).ok().flatten();
-                
-                // Si le fichier n'est pas dans l'index local, on skip la synchronisation automatique
-                // pour éviter de télécharger tous les fichiers (très coûteux en bande passante)
-                // L'utilisateur peut forcer une synchronisation manuelle si nécessaire
-                if metadata.is_none() {
-                    log::warn!("⚠️ File {} not found in local index, skipping auto-sync (too expensive). Original UUID: {}", uuid_normalized, uuid_from_storj);
-                    // On continue sans télécharger le fichier pour économiser la bande passante
-                }
-                
-                files_with_metadata.push(StorjFileInfo {
-                    uuid: uuid_from_storj.clone(), // Garde le format original pour l'affichage
-                    logical_path: metadata.as_ref().map(|m| m.logical_path.clone()),
-                    encrypted_size: metadata.as_ref().map(|m| m.encrypted_size),
-                });
-            }
+        .map_err(|e| CommandError::remote("Failed to create Storj client", e))?
+        .with_prefix(storj::vault_prefix(&master_key.fingerprint()));
+    *state.storj_client.lock().await = Some(Arc::new(client));
+    Ok(())
+}
+
+/// Renouvelle les identifiants délégués quand leur échéance approche ; avec `rotate`,
+/// tout de suite, et les précédents sont alors révoqués (un renouvellement les laisse
+/// expirer : des transferts en cours les utilisent peut-être encore).
+///
+/// Sans délégation, ne fait rien. Retourne l'échéance des identifiants en cours.
+async fn renew_credentials(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    rotate: bool,
+) -> Result<Option<i64>, CommandError> {
+    let mut delegated = state.delegated_credentials.lock().await;
+    let Some(current) = delegated.as_ref() else {
+        return Ok(None);
+    };
+    let Some(grant) = current.delegation.as_ref() else {
+        return Ok(None);
+    };
+    if !rotate && !delegation::renewal_due(current, clock::unix_now()) {
+        return Ok(current.expires_at);
+    }
+    let renewed = delegation::issue(grant).await?;
+    install_delegated_credentials(app, state, &renewed).await?;
+    if rotate {
+        let revoking = renewed.delegation.as_ref().unwrap_or(grant);
+        if let Err(e) = delegation::revoke(revoking, &current.access_key_id).await {
+            log::warn!("Failed to revoke previous delegated credentials: {}", e);
         }
-        Err(_) => {
-            // Index non disponible, retourne juste les UUIDs sans métadonnées
-            for uuid in keys {
-                files_with_metadata.push(StorjFileInfo {
-                    uuid,
-                    logical_path: None,
-                    encrypted_size: None,
-                });
-            }
+    }
+    log::info!("Delegated storage credentials renewed: rotate={}, expires_at={:?}", rotate, renewed.expires_at);
+    let expires_at = renewed.expires_at;
+    *delegated = Some(renewed);
+    Ok(expires_at)
+}
+
+/// Boucle de fond renouvelant les identifiants délégués, même sans transfert en cours.
+async fn credentials_scheduler(app: tauri::AppHandle) {
+    let mut ticker = tokio::time::interval(CREDENTIALS_CHECK_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let state = app.state::<AppState>();
+        if let Err(e) = renew_credentials(&app, &state, false).await {
+            log::warn!("Scheduled credential renewal failed: {}", e);
         }
     }
-    
-    Ok(files_with_metadata)
 }
 
 #[tauri::command]
-async fn storj_delete_file(
+async fn storj_credentials_status(state: State<'_, AppState>) -> Result<CredentialsStatus, CommandError> {
+    get_master_key_from_state(state.clone())?;
+    let delegated = state.delegated_credentials.lock().await;
+    Ok(CredentialsStatus::of(delegated.as_ref()))
+}
+
+/// Remplace les identifiants de l'espace actif par des identifiants délégués : limités au
+/// préfixe du coffre, à durée limitée et propres à cet appareil. Les clés racines
+/// enregistrées sont écrasées.
+#[tauri::command]
+async fn storj_enroll_delegation(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-    file_uuid: Vec<u8>,
-) -> Result<(), String> {
-    log::info!("storj_delete_file called: uuid={:?}", file_uuid);
-    
-    if file_uuid.len() != 16 {
-        return Err("Invalid UUID length".to_string());
-    }
-    
-    let uuid_hex = hex::encode(&file_uuid);
-    let file_id = uuid_hex.clone();
-    
-    // Déplace vers la corbeille au lieu de supprimer définitivement
-    // Le fichier reste sur Storj jusqu'à ce qu'on vide la corbeille ou qu'on supprime définitivement
-    let mut index = open_index_with_state(&app, &state)
-        .map_err(|e| {
-            log::error!("Failed to open index for trash: {}", e);
-            format!("Failed to open index: {}", e)
-        })?;
-    
-    // Récupère les métadonnées du fichier avant de le déplacer
-    let metadata = index.get(&file_id)
-        .map_err(|e| format!("Failed to get file metadata: {}", e))?
-        .ok_or_else(|| format!("File not found in index: {}", file_id))?;
-    
-    // Déplace vers la corbeille
-    index.move_to_trash(&file_id, &metadata)
-        .map_err(|e| format!("Failed to move file to trash: {}", e))?;
-    
-    log::info!("File moved to trash: file_id={}, logical_path={}", file_id, metadata.logical_path);
-    Ok(())
+    issuer_url: String,
+    renewal_token: String,
+    ttl_secs: Option<i64>,
+) -> Result<CredentialsStatus, CommandError> {
+    log::info!("storj_enroll_delegation called: ttl_secs={:?}", ttl_secs);
+    let master_key = get_master_key_from_state(state.clone())?;
+    let device_id = journal::device_id(&mut open_index_with_state(&app, &state)?)?;
+    let grant = delegation::Delegation::new(
+        &issuer_url,
+        device_id,
+        &renewal_token,
+        storj::vault_prefix(&master_key.fingerprint()),
+        ttl_secs,
+    )?;
+    let credentials = delegation::issue(&grant).await?;
+    let mut delegated = state.delegated_credentials.lock().await;
+    install_delegated_credentials(&app, &state, &credentials).await?;
+    *delegated = Some(credentials);
+    log::info!("Storage switched to delegated credentials");
+    spawn_recovery_if_pending(&app);
+    Ok(CredentialsStatus::of(delegated.as_ref()))
 }
 
-/// Renomme un fichier (télécharge, déchiffre, re-chiffre avec nouveau chemin, re-upload, met à jour index)
+/// Rotation immédiate des identifiants délégués ; les précédents sont révoqués.
 #[tauri::command]
-async fn rename_file(
+async fn storj_rotate_credentials(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-    old_logical_path: String,
-    new_logical_path: String,
-) -> Result<String, String> {
-    log::info!("rename_file called: old_path={}, new_path={}", old_logical_path, new_logical_path);
-    
-    // Étape 1 : Trouve le fichier dans l'index local par ancien chemin
-    let file_id = {
-        let index = open_index_with_state(&app, &state)
-            .map_err(|e| format!("Failed to open index: {}", e))?;
-        
-        let entries = index.list_all()
-            .map_err(|e| format!("Failed to list files from index: {}", e))?;
-        
-        let (file_id, _metadata) = entries
-            .into_iter()
-            .find(|(_, meta)| meta.logical_path == old_logical_path)
-            .ok_or_else(|| format!("File not found in local index: {}", old_logical_path))?;
-        
-        log::info!("Found file in index: file_id={}, old_logical_path={}", file_id, old_logical_path);
-        file_id
+) -> Result<CredentialsStatus, CommandError> {
+    log::info!("storj_rotate_credentials called");
+    get_master_key_from_state(state.clone())?;
+    if renew_credentials(&app, &state, true).await?.is_none() {
+        return Err(CommandError::InvalidDelegation { reason: "not_delegated" });
+    }
+    let delegated = state.delegated_credentials.lock().await;
+    Ok(CredentialsStatus::of(delegated.as_ref()))
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkspaceList {
+    pub workspaces: Vec<Workspace>,
+    pub active: String,
+}
+
+#[tauri::command]
+fn workspace_list(app: tauri::AppHandle) -> Result<WorkspaceList, CommandError> {
+    with_workspaces(&app, |registry| {
+        Ok(WorkspaceList {
+            workspaces: registry.list().to_vec(),
+            active: registry.active().id.clone(),
+        })
+    })
+}
+
+/// Crée un espace de travail ; ses identifiants de stockage, s'ils sont fournis, sont
+/// scellés dans le trousseau sous son propre nom.
+#[tauri::command]
+fn workspace_create(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+    config: Option<StorjConfigRequest>,
+) -> Result<Workspace, CommandError> {
+    log::info!("workspace_create called: with_storage={}", config.is_some());
+    let master_key = config.as_ref().map(|_| get_master_key_from_state(state)).transpose()?;
+    let workspace = with_workspaces(&app, |registry| registry.create(&name))?;
+    if let (Some(config), Some(master_key)) = (config, master_key) {
+        let credentials = StorjCredentials {
+            access_key_id: config.access_key_id,
+            secret_access_key: config.secret_access_key,
+            endpoint: config.endpoint,
+            bucket_name: config.bucket_name,
+            expires_at: None,
+            delegation: None,
+        };
+        KeychainStore::storj_for_workspace(&workspace.id).and_then(|store| store.save(&master_key, &credentials))?;
+    }
+    Ok(workspace)
+}
+
+#[tauri::command]
+fn workspace_rename(app: tauri::AppHandle, id: String, name: String) -> Result<Workspace, CommandError> {
+    log::info!("workspace_rename called: id={}", id);
+    with_workspaces(&app, |registry| registry.rename(&id, &name))
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkspaceSwitch {
+    pub workspace: Workspace,
+    /// Le stockage de l'espace a été configuré depuis le trousseau.
+    pub storj_configured: bool,
+}
+
+/// Bascule sur un autre espace : le client de stockage, le serveur WebDAV et les aperçus
+/// de l'espace précédent sont fermés, puis le client du nouvel espace est chargé.
+#[tauri::command]
+async fn workspace_switch(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<WorkspaceSwitch, CommandError> {
+    log::info!("workspace_switch called: id={}", id);
+    let workspace = with_workspaces(&app, |registry| registry.set_active(&id))?;
+
+    *state.storj_client.lock().await = None;
+    *state.delegated_credentials.lock().await = None;
+    if let Some(server) = state.webdav.lock().await.take() {
+        server.stop();
+    }
+    app.state::<PreviewSessions>().close();
+
+    let unlocked = state.master_key.lock().map(|guard| guard.is_some()).unwrap_or(false);
+    let storj_configured = if unlocked {
+        // La file d'attente du nouvel espace est reprise dès que son client est prêt
+        state.recovery_pending.store(true, Ordering::SeqCst);
+        configure_from_keychain(&app, &state).await.unwrap_or_else(|e| {
+            log::warn!("Failed to configure storage for workspace {}: {}", workspace.id, e);
+            false
+        })
+    } else {
+        false
     };
+
+    events::emit(
+        &app,
+        AppEvent::WorkspaceChanged {
+            id: workspace.id.clone(),
+            name: workspace.name.clone(),
+            storj_configured,
+        },
+    );
+    Ok(WorkspaceSwitch { workspace, storj_configured })
+}
+
+/// Supprime un espace inactif, ses données locales et ses identifiants de stockage.
+#[tauri::command]
+fn workspace_delete(app: tauri::AppHandle, id: String) -> Result<(), CommandError> {
+    log::info!("workspace_delete called: id={}", id);
+    let removed = with_workspaces(&app, |registry| registry.remove(&id))?;
+    KeychainStore::storj_for_workspace(&removed.id).and_then(|store| store.delete())?;
+    Ok(())
+}
+
+/// ETag renvoyé par `storj_upload_file` quand l'envoi a été mis en file d'attente.
+const PENDING_ETAG: &str = "pending";
+
+#[tauri::command]
+async fn storj_upload_file(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    encrypted_data: Vec<u8>,
+    logical_path: String,
+    priority: Option<TransferPriority>,
+) -> Result<String, CommandError> {
+    log::info!("storj_upload_file called: logical_path={}, data_len={}, priority={:?}", logical_path, encrypted_data.len(), priority);
+    // Même forme que pour `storage_encrypt_file` : le chemin de l'index est celui de l'AAD
+    let logical_path = canonical_file_path(&logical_path)?;
     
-    // Étape 2 : Télécharge le fichier depuis Storj
-    log::info!("Downloading file from Storj: file_id={}", file_id);
-    let encrypted_data = {
-        let file_uuid = hex::decode(&file_id)
-            .map_err(|e| format!("Invalid UUID format in index: {}", e))?;
-        
-        if file_uuid.len() != 16 {
-            return Err(format!("Invalid UUID length in index: expected 16 bytes, got {}", file_uuid.len()));
+    // Parse le fichier Aether pour obtenir l'UUID
+    let aether_file = AetherFile::from_bytes(&encrypted_data)?;
+    let upload = EncryptedUpload::Bytes(&encrypted_data);
+    let (file_id, etag) = upload_encrypted(&app, &state, upload, aether_file.header.uuid, &logical_path, priority).await?;
+    let Some(etag) = etag else {
+        return Ok(PENDING_ETAG.to_string());
+    };
+    index_uploaded_content(&app, &state, &file_id, &logical_path, &aether_file);
+    Ok(etag)
+}
+
+/// Comme `storj_upload_file`, pour un fichier `.aeth` déjà chiffré par ce coffre : il est lu
+/// en flux depuis le disque jusqu'au stockage distant, sans transiter par l'IPC ni être
+/// chargé entièrement en mémoire. Un fichier en clair passe par `upload_local_file`, qui le
+/// chiffre à la volée.
+#[tauri::command]
+async fn storj_upload_from_path(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    local_path: String,
+    logical_path: String,
+    priority: Option<TransferPriority>,
+) -> Result<String, CommandError> {
+    log::info!("storj_upload_from_path called: local_path={}, logical_path={}, priority={:?}", local_path, logical_path, priority);
+    let logical_path = canonical_file_path(&logical_path)?;
+    let source = PathBuf::from(&local_path);
+    let master_key = get_master_key_from_state(state.clone())?;
+
+    // Seul l'en-tête est lu : il doit annoncer exactement la taille du fichier et engager la
+    // MasterKey de ce coffre (un fichier d'un autre coffre ne se déchiffrerait pas)
+    let (header, file_len) = {
+        use std::io::Read;
+        let mut file = std::fs::File::open(&source).map_err(|_| CommandError::PathNotFound { path: local_path.clone() })?;
+        let file_len = file.metadata().map_err(|e| CommandError::io("Failed to read source file", e))?.len();
+        let mut raw_header = [0u8; AetherFile::HEADER_LEN];
+        file.read_exact(&mut raw_header).map_err(|_| StorageError::InvalidHeader)?;
+        let (header, ciphertext_len) = AetherFile::parse_header(&raw_header)?;
+        if AetherFile::HEADER_LEN as u64 + ciphertext_len != file_len {
+            return Err(StorageError::InvalidFormat("File size does not match its header".to_string()).into());
         }
-        
-        let uuid_array: [u8; 16] = file_uuid.try_into()
-            .map_err(|_| "Failed to convert UUID to array".to_string())?;
-        
-        storj_download_file(state.clone(), uuid_array.to_vec()).await?
+        (header, file_len)
+    };
+    if !crate::storage::encryption_details(&master_key, &header)?.commitment_valid {
+        return Err(StorageError::InvalidFormat("File was not encrypted by this vault".to_string()).into());
+    }
+
+    let upload = EncryptedUpload::Path(&source);
+    let (file_id, etag) = upload_encrypted(&app, &state, upload, header.uuid, &logical_path, priority).await?;
+    let Some(etag) = etag else {
+        return Ok(PENDING_ETAG.to_string());
     };
+    // L'extraction du texte déchiffre en mémoire : seulement pour les fichiers qui s'y prêtent
+    if extract::is_extractable(&logical_path) && file_len <= extract::MAX_SOURCE_BYTES {
+        match std::fs::read(&source).map(|raw| AetherFile::from_bytes(&raw)) {
+            Ok(Ok(aether_file)) => index_uploaded_content(&app, &state, &file_id, &logical_path, &aether_file),
+            Ok(Err(e)) => log::warn!("Failed to index the content of {}: {}", logical_path, e),
+            Err(e) => log::warn!("Failed to index the content of {}: {}", logical_path, e),
+        }
+    }
+    Ok(etag)
+}
+
+/// Blob Aether déjà chiffré à envoyer : reçu par l'IPC, ou lu en flux depuis le disque.
+#[derive(Clone, Copy)]
+enum EncryptedUpload<'a> {
+    Bytes(&'a [u8]),
+    Path(&'a std::path::Path),
+}
+
+impl EncryptedUpload<'_> {
+    fn len(self) -> Result<u64, CommandError> {
+        match self {
+            EncryptedUpload::Bytes(data) => Ok(data.len() as u64),
+            EncryptedUpload::Path(path) => Ok(std::fs::metadata(path)
+                .map_err(|e| CommandError::io("Failed to read source file", e))?
+                .len()),
+        }
+    }
+}
+
+/// Envoie un blob chiffré d'UUID `uuid` et l'ajoute à l'index sous `logical_path` ; hors ligne
+/// ou synchronisation suspendue, il est placé dans le cache et l'envoi est différé.
+///
+/// Retourne l'entrée de l'index et l'ETag (`None` si l'envoi a été différé).
+async fn upload_encrypted(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    upload: EncryptedUpload<'_>,
+    uuid: [u8; 16],
+    logical_path: &str,
+    priority: Option<TransferPriority>,
+) -> Result<(FileId, Option<String>), CommandError> {
+    // Utilise l'UUID comme clé d'objet dans Storj
+    let uuid_hex = hex::encode(uuid);
+    let object_key = uuid_hex.to_string();
     
-    log::info!("File downloaded from Storj: size={} bytes", encrypted_data.len());
+    log::info!("Preparing Storj upload: object_key={}, file_id={}", object_key, uuid_hex);
     
-    // Étape 3 : Déchiffre le fichier avec l'ancien logical_path
-    log::info!("Decrypting file with old logical_path: {}", old_logical_path);
-    let plaintext = storage_decrypt_file(state.clone(), encrypted_data.clone(), old_logical_path.clone())
-        .map_err(|e| format!("Failed to decrypt file: {}", e))?;
+    // Utilise l'UUID comme FileId dans l'index local, sauf en mode clés distantes aléatoires
+    let file_id = remote_keys::entry_id(&open_index_with_state(app, state)?, &uuid_hex)?;
     
-    log::info!("File decrypted successfully: plaintext_len={}", plaintext.len());
+    let client = {
+        let client_guard = state.storj_client.lock().await;
+        client_guard.clone()
+            .ok_or(CommandError::StorjNotConfigured)?
+    };
     
-    // Étape 4 : Re-chiffre avec le nouveau logical_path (génère un nouveau UUID)
-    log::info!("Re-encrypting file with new logical_path: {}", new_logical_path);
-    let new_encrypted_data = storage_encrypt_file(app.clone(), state.clone(), plaintext, new_logical_path.clone())
-        .map_err(|e| format!("Failed to re-encrypt file: {}", e))?;
+    let bytes_total = upload.len()?;
     
-    // Récupère le nouveau UUID du fichier re-chiffré
-    let new_file_info = storage_get_file_info(new_encrypted_data.clone())
-        .map_err(|e| format!("Failed to get file info: {}", e))?;
-    let new_uuid_hex = hex::encode(&new_file_info.uuid);
+    // Refuse l'envoi s'il ferait dépasser le budget de stockage
+    let quota_check = vault::check_quota(&open_index_with_state(app, state)?, bytes_total)?;
     
-    log::info!("File re-encrypted successfully: new_uuid={}, new_size={}", new_uuid_hex, new_encrypted_data.len());
+    let reporter = Arc::new(TransferReporter::new(app, &file_id, TransferDirection::Upload));
+    reporter.report(0, bytes_total);
     
-    // Étape 5 : Upload le nouveau fichier vers Storj
-    log::info!("Uploading renamed file to Storj: new_uuid={}", new_uuid_hex);
-    let _upload_result = storj_upload_file(app.clone(), state.clone(), new_encrypted_data, new_logical_path.clone()).await
-        .map_err(|e| format!("Failed to upload renamed file to Storj: {}", e))?;
+    // Upload vers Storj ; hors ligne ou synchronisation suspendue, le blob part dans le cache et l'envoi est différé
+    let result = if state.sync_paused.load(Ordering::SeqCst) {
+        None
+    } else {
+        if let Ok(vault) = vault_from_state(app, state).await {
+            warn_if_locked(app, &vault, logical_path).await;
+        }
+        let priority = priority.unwrap_or_else(|| TransferPriority::for_size(bytes_total));
+        let _slot = state.transfers.acquire(priority).await;
+        // La fin n'est rapportée qu'une fois l'objet confirmé par Storj
+        let sent = Arc::clone(&reporter);
+        let progress = move |bytes_done| {
+            if bytes_done < bytes_total {
+                sent.report(bytes_done, bytes_total);
+            }
+        };
+        Some(match upload {
+            EncryptedUpload::Bytes(data) => client.upload_file_with_progress(&object_key, data, progress).await,
+            EncryptedUpload::Path(path) => client.upload_path_with_progress(&object_key, path, progress).await,
+        })
+    };
+    let etag = match result {
+        None => {
+            log::info!("Sync paused, deferring upload: object_key={}", object_key);
+            defer_upload(app, &object_key, upload)?;
+            None
+        }
+        Some(Ok(etag)) => {
+            log::info!("File uploaded successfully to Storj: object_key={}, etag={}", object_key, etag);
+            telemetry::record_transfer(TransferDirection::Upload, bytes_total);
+            reporter.report(bytes_total, bytes_total);
+            Some(etag)
+        }
+        Some(Err(StorjError::Unreachable(msg))) => {
+            log::warn!("Storj unreachable, deferring upload: object_key={}, error={}", object_key, msg);
+            defer_upload(app, &object_key, upload)?;
+            None
+        }
+        Some(Err(e)) => {
+            log::error!("Storj upload failed: object_key={}, error={}", object_key, e);
+            return Err(CommandError::remote("Failed to upload file to Storj", e));
+        }
+    };
     
-    log::info!("Renamed file uploaded successfully to Storj");
+    // Synchronise avec l'index local : ajoute l'entrée après upload réussi (ou différé)
+    let writer = index_writer(app, state)
+        .inspect_err(|e| log::error!("Failed to open index for sync: {}", e))?;
     
-    // Étape 6 : Supprime l'ancien fichier de Storj
-    log::info!("Deleting old file from Storj: old_uuid={}", file_id);
-    let old_uuid_bytes = hex::decode(&file_id)
-        .map_err(|e| format!("Invalid UUID format: {}", e))?;
-    let old_uuid_array: [u8; 16] = old_uuid_bytes.try_into()
-        .map_err(|_| "Failed to convert UUID to array".to_string())?;
+    let metadata = FileMetadata {
+        logical_path: logical_path.to_string(),
+        encrypted_size: bytes_total,
+    };
     
-    storj_delete_file(app.clone(), state.clone(), old_uuid_array.to_vec()).await
-        .map_err(|e| format!("Failed to delete old file from Storj: {}", e))?;
+    // Un fichier déjà présent au même chemin est remplacé en une transaction : il devient une version.
+    // La recherche, le remplacement et la mise en file forment une seule écriture.
+    let (path, id, key, deferred) = (logical_path.to_string(), file_id.clone(), object_key.clone(), etag.is_none());
+    let previous = writer
+        .write(move |index| {
+            if id != key {
+                index.link_blob(&id, &key, &path)?;
+            }
+            let previous = index.files_at_path(&path)?.into_iter().find(|other| *other != id);
+            match &previous {
+                Some(previous) => index.replace_file(previous, &id, &metadata, None)?,
+                // Chemin pris par un alias : le fichier est placé à côté
+                None => {
+                    index.insert_at_free_path(id.clone(), metadata)?;
+                }
+            }
+            if deferred {
+                index.enqueue_pending(PendingKind::Upload, &key)?;
+            }
+            Ok::<_, rusqlite::Error>(previous)
+        })
+        .await?
+        .map_err(|e| {
+            log::error!("Failed to add file to index after Storj upload: {}", e);
+            CommandError::index("File uploaded to Storj but failed to sync with local index", e)
+        })?;
+    if let Some(previous) = previous {
+        log::info!("Replaced {} with {} at {}", previous, file_id, logical_path);
+        vault_from_state(app, state).await?.retire_versions(logical_path).await;
+    }
     
-    log::info!("Old file deleted successfully from Storj");
+    if let QuotaCheck::Warning { threshold_percent } = quota_check {
+        let status = quota::status(&open_index_with_state(app, state)?)?;
+        events::emit(app, AppEvent::QuotaWarning {
+            threshold_percent,
+            used_bytes: status.used_bytes,
+            limit_bytes: status.limit_bytes.unwrap_or_default(),
+        });
+    }
     
-    // Étape 7 : L'index local a déjà été mis à jour par storage_encrypt_file et storj_upload_file
-    // Mais on doit supprimer l'ancienne entrée de l'index
-    {
-        let mut index = open_index_with_state(&app, &state)
-            .map_err(|e| format!("Failed to open index for cleanup: {}", e))?;
-        
-        index.remove(&file_id)
-            .map_err(|e| format!("Failed to remove old file from index: {}", e))?;
-        
-        log::info!("Old file entry removed from local index");
+    if etag.is_none() {
+        log::info!("Upload queued for later: file_id={}, logical_path={}", file_id, logical_path);
+    } else {
+        log::info!("File synchronized with local index: file_id={}, logical_path={}", file_id, logical_path);
+        tray::push_recent(app, logical_path);
+    }
+    Ok((file_id, etag))
+}
+
+/// Indexe le texte d'un fichier reçu déjà chiffré, si la recherche dans le contenu est activée
+/// (déchiffré localement le temps de l'extraction) ; un échec n'affecte pas l'envoi.
+fn index_uploaded_content(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    file_id: &FileId,
+    logical_path: &str,
+    aether_file: &AetherFile,
+) {
+    if !extract::is_extractable(logical_path) || aether_file.ciphertext.len() as u64 > extract::MAX_SOURCE_BYTES {
+        return;
+    }
+    let result = (|| {
+        if !search::enabled(&open_index_with_state(app, state)?)? {
+            return Ok(());
+        }
+        let master_key = get_master_key_from_state(state.clone())?;
+        let plaintext = zeroize::Zeroizing::new(crate::storage::decrypt_file(&master_key, aether_file, logical_path)?);
+        if let Some(text) = extract::extract_text(logical_path, &plaintext) {
+            ContentIndex::open(&get_db_path(app)?, master_key.as_bytes())?.set_text(file_id, &text)?;
+        }
+        Ok::<_, CommandError>(())
+    })();
+    if let Err(e) = result {
+        log::warn!("Failed to index the content of {}: {}", logical_path, e);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LocalUploadResult {
+    pub logical_path: String,
+    /// Contenu déjà présent dans le coffre : rien n'a été transféré.
+    pub deduplicated: bool,
+}
+
+/// Chiffre et envoie un fichier local lu depuis le disque, sans le charger en mémoire.
+///
+/// Contrairement à `storj_upload_file`, rien ne transite par l'IPC : convient aux fichiers
+/// plus gros que la RAM. Sans `local_path`, un sélecteur de fichier est ouvert.
+#[tauri::command]
+async fn upload_local_file(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    local_path: Option<String>,
+    parent_path: Option<String>,
+    priority: Option<TransferPriority>,
+) -> Result<LocalUploadResult, CommandError> {
+    let (source, name) = match local_path.as_deref().map(picker::parse_selection) {
+        Some(FilePath::Path(source)) => {
+            let name = source
+                .file_name()
+                .and_then(|n| n.to_str())
+                .ok_or_else(|| CommandError::PathNotFound { path: source.to_string_lossy().into_owned() })?
+                .to_string();
+            (picker::LocalCopy::from(source), name)
+        }
+        // URI de la plateforme renvoyée par `select_files_for_upload` (mobile)
+        Some(file_path) => {
+            let name = picker::display_name(&file_path);
+            (picker::local_copy(&app, file_path).await?, name)
+        }
+        None => {
+            let file_path = picker::pick_open(&app, "Sélectionner un fichier à chiffrer").await?;
+            let name = picker::display_name(&file_path);
+            (picker::local_copy(&app, file_path).await?, name)
+        }
+    };
+    let logical_path = upload_logical_path(parent_path.as_deref(), &name);
+    log::info!("upload_local_file called: source={}, logical_path={}", source.path().display(), logical_path);
+
+    // Un fichier déjà présent au même chemin est remplacé ; il reste disponible comme version
+    let mut vault = vault_from_state(&app, &state).await?;
+    if let Some(priority) = priority {
+        vault = vault.with_priority(priority);
+    }
+    let report = vault.preflight(&[UploadCandidate::local(logical_path.clone(), source.path())])?;
+    if !report.is_ok() {
+        return Err(CommandError::UploadPreflightFailed { problems: report.problems });
+    }
+    warn_if_locked(&app, &vault, &logical_path).await;
+    let report = vault.put_path(&logical_path, source.path()).await?;
+    let encrypted_size = vault.open_index()?.get(&report.file_id)?.map(|meta| meta.encrypted_size).unwrap_or_default();
+    // Un envoi dédupliqué n'a rien transféré
+    if !report.deduplicated {
+        telemetry::record_transfer(TransferDirection::Upload, encrypted_size);
+    }
+    log::info!(
+        "Local file uploaded: file_id={}, size={}, deduplicated={}",
+        report.file_id,
+        encrypted_size,
+        report.deduplicated
+    );
+    tray::push_recent(&app, &report.logical_path);
+    Ok(LocalUploadResult {
+        logical_path: report.logical_path,
+        deduplicated: report.deduplicated,
+    })
+}
+
+/// Chemin logique d'un fichier `name` envoyé dans le dossier `parent_path` (racine par défaut).
+fn upload_logical_path(parent_path: Option<&str>, name: &str) -> String {
+    let folder = normalize_path(parent_path.unwrap_or("/"));
+    if folder == "/" {
+        format!("/{}", name)
+    } else {
+        format!("{}/{}", folder.trim_end_matches('/'), name)
+    }
+}
+
+/// Fichier d'un lot à vérifier, tel que le recevra `upload_local_file`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadPlanItem {
+    pub local_path: String,
+    pub parent_path: Option<String>,
+}
+
+/// Vérifie un lot d'envois avant de le commencer : chemins, lisibilité des fichiers, budget
+/// de stockage et place dans la zone de transit. Tous les problèmes sont renvoyés ensemble.
+#[tauri::command]
+async fn upload_preflight(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    items: Vec<UploadPlanItem>,
+) -> Result<PreflightReport, CommandError> {
+    log::info!("upload_preflight called: items={}", items.len());
+    let vault = vault_from_state(&app, &state).await?;
+    let probe_app = app.clone();
+    let candidates = tokio::task::spawn_blocking(move || {
+        items
+            .into_iter()
+            .map(|item| {
+                let file_path = picker::parse_selection(&item.local_path);
+                let logical_path = upload_logical_path(item.parent_path.as_deref(), &picker::display_name(&file_path));
+                match file_path.clone().into_path() {
+                    Ok(path) => UploadCandidate::local(logical_path, &path),
+                    Err(_) => UploadCandidate::opened(logical_path, item.local_path, picker::open_read(&probe_app, &file_path)),
+                }
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| CommandError::internal("Preflight task failed", e))?;
+    let report = vault.preflight(&candidates)?;
+    if !report.is_ok() {
+        log::warn!("Upload preflight found {} problem(s) in {} file(s)", report.problems.len(), report.files);
+    }
+    Ok(report)
+}
+
+#[derive(Debug, Serialize)]
+pub struct UploadSelection {
+    /// Chemin (ou URI de la plateforme) à transmettre à `upload_local_file`.
+    pub local_path: String,
+    /// Sous-dossier à recréer sous le dossier de destination (vide pour un fichier choisi seul).
+    pub relative_dir: String,
+}
+
+/// Sélectionne plusieurs fichiers, ou des dossiers entiers (`directories`), à envoyer.
+///
+/// Seuls les chemins sont retournés : chacun est ensuite chiffré et envoyé en flux par
+/// `upload_local_file`. Les plateformes mobiles n'ayant pas de sélecteur de dossiers,
+/// `directories` y ouvre la sélection de fichiers.
+#[tauri::command]
+async fn select_files_for_upload(
+    app: tauri::AppHandle,
+    directories: bool,
+) -> Result<Vec<UploadSelection>, CommandError> {
+    log::info!("select_files_for_upload called: directories={}", directories);
+
+    #[cfg(desktop)]
+    if directories {
+        let folders = picker::pick_folders(&app, "Sélectionner des dossiers à chiffrer").await?;
+        let roots: Vec<PathBuf> = folders.into_iter().filter_map(|folder| folder.into_path().ok()).collect();
+        let files = tokio::task::spawn_blocking(move || -> std::io::Result<Vec<(PathBuf, String)>> {
+            let mut files = Vec::new();
+            for root in roots {
+                files.extend(picker::walk_folder(&root)?);
+            }
+            Ok(files)
+        })
+        .await
+        .map_err(|e| CommandError::internal("Folder scan failed", e))?
+        .map_err(|e| CommandError::io("Failed to read the selected folder", e))?;
+        log::info!("Folders selected: {} files", files.len());
+        return Ok(files
+            .into_iter()
+            .map(|(path, relative_dir)| UploadSelection {
+                local_path: path.to_string_lossy().into_owned(),
+                relative_dir,
+            })
+            .collect());
+    }
+
+    let files = picker::pick_open_many(&app, "Sélectionner des fichiers à chiffrer").await?;
+    log::info!("Files selected: {}", files.len());
+    Ok(files
+        .into_iter()
+        .map(|path| UploadSelection {
+            local_path: path.to_string(),
+            relative_dir: String::new(),
+        })
+        .collect())
+}
+
+/// Place le blob dans le cache local : `flush_pending` l'enverra plus tard.
+fn defer_upload(app: &tauri::AppHandle, file_id: &FileId, upload: EncryptedUpload<'_>) -> Result<(), CommandError> {
+    let cache = open_blob_cache(app)?;
+    match upload {
+        EncryptedUpload::Bytes(data) => cache.put(file_id, data),
+        EncryptedUpload::Path(path) => cache.copy_from(file_id, path),
+    }
+    .map_err(|e| CommandError::io("Failed to cache blob for deferred upload", e))
+}
+
+#[tauri::command]
+async fn storj_download_file(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_uuid: Vec<u8>,
+) -> Result<Vec<u8>, CommandError> {
+    log::info!("storj_download_file called: uuid={}", hex::encode(&file_uuid));
+    
+    if file_uuid.len() != 16 {
+        return Err(CommandError::InvalidFileId { file_id: hex::encode(&file_uuid) });
     }
     
-    log::info!("✅ File renamed successfully: {} -> {} (old_uuid={}, new_uuid={})", old_logical_path, new_logical_path, file_id, new_uuid_hex);
+    // Utilise l'UUID comme clé d'objet dans Storj (le cache local est consulté d'abord)
+    let uuid_hex = hex::encode(&file_uuid);
+    let vault = vault_from_state(&app, &state).await?;
+    let reporter = TransferReporter::new(&app, &uuid_hex, TransferDirection::Download);
+    // La fin n'est rapportée qu'une fois le blob vérifié
+    let data = vault
+        .fetch_blob_with_progress(&uuid_hex, |bytes_done, bytes_total| {
+            if bytes_done < bytes_total {
+                reporter.report(bytes_done, bytes_total);
+            }
+        })
+        .await?;
+    note_access(&vault, &uuid_hex);
+    
+    log::info!("File downloaded successfully: object_key={}, data_len={}", uuid_hex, data.len());
+    telemetry::record_transfer(TransferDirection::Download, data.len() as u64);
+    reporter.report(data.len() as u64, data.len() as u64);
+    Ok(data)
+}
+
+#[derive(Debug, Serialize)]
+pub struct StorjFileInfo {
+    pub uuid: String,
+    pub logical_path: Option<String>,
+    pub encrypted_size: Option<u64>,
+}
+
+/// Synchronise l'index avec Storj et liste les objets distants, par pages.
+///
+/// Seule la première page synchronise ; les suivantes relisent la liste distante sans
+/// refaire le passage.
+#[tauri::command]
+async fn storj_list_files(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    page: Option<PageRequest>,
+) -> Result<Page<StorjFileInfo>, CommandError> {
+    log::info!("storj_list_files called");
+    let page = page.unwrap_or_default();
+    
+    let client = {
+        let client_guard = state.storj_client.lock().await;
+        client_guard.clone()
+            .ok_or(CommandError::StorjNotConfigured)?
+    };
+    
+    let files = if page.cursor.is_some() {
+        let keys = client
+            .list_files()
+            .await
+            .map_err(|e| CommandError::remote("Failed to list files from Storj", e))?;
+        let trashed = tombstones::trashed_keys(&client).await.unwrap_or_else(|e| {
+            log::warn!("Trash tombstones not listed: {}", e);
+            std::collections::HashSet::new()
+        });
+        remote_file_infos(&app, &state, keys, &trashed)
+    } else {
+        sync_remote_files(&app, &state, &client).await?
+    };
+    Ok(paging::paginate(files, &page)?)
+}
+
+/// Passage de synchronisation de [`storj_list_files`] : journal, puis comparaison complète
+/// de l'index avec la liste distante en repli.
+async fn sync_remote_files(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    client: &StorjClient,
+) -> Result<Vec<StorjFileInfo>, CommandError> {
+    events::emit(app, AppEvent::SyncState { status: SyncStatus::Syncing, remote_count: None });
+    let mut run = SyncRun::default();
+    
+    // Le journal apporte les modifications des autres appareils ; la comparaison complète
+    // de l'index avec la liste distante ne sert plus qu'en repli
+    let journal_in_sync = match vault_from_state(app, state).await {
+        Ok(vault) => match journal::sync(&vault).await {
+            Ok(report) => {
+                log::info!(
+                    "Sync journal: pushed={}, applied={}, conflicts={}",
+                    report.pushed, report.applied, report.conflicts.len()
+                );
+                run.pushed = report.pushed;
+                run.applied = report.applied;
+                run.conflicts = report.conflicts.len();
+                if !report.conflicts.is_empty() {
+                    let logical_paths = report
+                        .conflicts
+                        .into_iter()
+                        .map(|conflict| conflict.logical_path.unwrap_or(conflict.file_id))
+                        .collect();
+                    events::emit(app, AppEvent::JournalConflicts { logical_paths });
+                }
+                !report.full_reconcile_needed
+            }
+            Err(e) => {
+                log::warn!("Sync journal skipped, falling back to full reconciliation: {}", e);
+                false
+            }
+        },
+        Err(_) => false,
+    };
+    
+    // Seules les entrées présentes avant la liste distante peuvent être des orphelins :
+    // un fichier indexé pendant la liste (envoi concurrent) n'y figure pas encore
+    let known_before: std::collections::HashSet<FileId> = match open_index_with_state(app, state) {
+        Ok(index) if !journal_in_sync => index.list_all().unwrap_or_default().into_iter().map(|(file_id, _)| file_id).collect(),
+        _ => std::collections::HashSet::new(),
+    };
+    
+    run.full_reconcile = !journal_in_sync;
+    let keys = match client.list_files().await {
+        Ok(keys) => keys,
+        Err(e) => {
+            events::emit(app, AppEvent::SyncState { status: SyncStatus::Failed, remote_count: None });
+            run.error = Some(e.to_string());
+            record_sync_run(app, state, run).await;
+            return Err(CommandError::remote("Failed to list files from Storj", e));
+        }
+    };
+    
+    log::info!("Listed {} files from Storj", keys.len());
+    let remote_count = keys.len();
+    run.remote_count = Some(remote_count);
+    
+    // Objets mis à la corbeille, ici ou sur un autre appareil : ce ne sont pas des fichiers
+    // vivants dont les métadonnées manqueraient
+    let trashed = match vault_from_state(app, state).await {
+        Ok(vault) => {
+            if let Err(e) = tombstones::publish(&vault).await {
+                log::warn!("Trash tombstones not published: {}", e);
+            }
+            tombstones::trashed_keys(client).await.unwrap_or_else(|e| {
+                log::warn!("Trash tombstones not listed: {}", e);
+                std::collections::HashSet::new()
+            })
+        }
+        Err(_) => std::collections::HashSet::new(),
+    };
+    
+    // Clés au format de l'index local (anciens objets avec tirets : voir `remote_keys_repair`)
+    let storj_uuids_normalized: std::collections::HashSet<String> = keys
+        .iter()
+        .map(|uuid| remote_keys::normalize(uuid))
+        .collect();
+    
+    // Nettoyage de l'index local : supprime les fichiers qui n'existent plus dans Storj
+    // (les envois encore en file d'attente ne sont pas des orphelins)
+    if journal_in_sync {
+        log::info!("Sync journal up to date, orphan sweep skipped");
+    } else if let Ok(writer) = index_writer(app, state) {
+        let swept = writer
+            .write(move |index| {
+                let all_local_files = index.list_all().ok().unwrap_or_default();
+                let pending = index.pending_upload_ids().ok().unwrap_or_default();
+                // Une entrée dédupliquée n'a pas d'objet propre : c'est celui qu'elle partage qui compte
+                let links = index.blob_links().ok().unwrap_or_default();
+                log::info!("Local index contains {} files", all_local_files.len());
+                
+                let mut removed = 0;
+                for (file_id, _) in all_local_files {
+                    if !known_before.contains(&file_id) {
+                        continue;
+                    }
+                    let object_key = links.get(&file_id).unwrap_or(&file_id);
+                    if !storj_uuids_normalized.contains(object_key) && !pending.contains(object_key) {
+                        log::info!("Removing orphaned file from local index: {}", file_id);
+                        match index.remove(&file_id) {
+                            Ok(_) => removed += 1,
+                            Err(e) => log::warn!("Failed to remove orphaned file {}: {}", file_id, e),
+                        }
+                    }
+                }
+                removed
+            })
+            .await;
+        match swept {
+            Ok(removed) => run.removed_orphans = removed,
+            Err(e) => log::warn!("Orphan cleanup skipped: {}", e),
+        }
+    }
     
-    Ok(new_uuid_hex)
+    let files_with_metadata = remote_file_infos(app, state, keys, &trashed);
+    record_sync_run(app, state, run).await;
+    events::emit(app, AppEvent::SyncState { status: SyncStatus::Idle, remote_count: Some(remote_count) });
+    Ok(files_with_metadata)
+}
+
+/// Objets distants de [`storj_list_files`], avec les métadonnées de l'index local quand il
+/// les connaît ; ceux de la corbeille d'un autre appareil sont écartés.
+fn remote_file_infos(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    keys: Vec<String>,
+    trashed: &std::collections::HashSet<String>,
+) -> Vec<StorjFileInfo> {
+    // Pour chaque UUID, essaie de trouver les métadonnées dans l'index local
+    // Si l'index n'est pas disponible, on retourne juste les UUIDs sans métadonnées
+    let mut files_with_metadata = Vec::new();
+    match open_index_with_state(app, state) {
+        Ok(index) => {
+            // Objet à clé aléatoire (voir `remote_keys`) : il est listé sous l'entrée qui le lit
+            let linked: std::collections::HashMap<FileId, FileId> = index
+                .blob_links()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(file_id, _)| index.get(file_id).ok().flatten().is_some())
+                .map(|(file_id, object_key)| (object_key, file_id))
+                .collect();
+            
+            // Maintenant, récupère les métadonnées pour chaque fichier Storj
+            for uuid_from_storj in keys {
+                // Clé au format de l'index local
+                let uuid_normalized = remote_keys::normalize(&uuid_from_storj);
+                let mut uuid_from_storj = uuid_from_storj;
+                
+                // Essaie de trouver le fichier dans l'index local avec l'UUID normalisé
+                let mut metadata = index.get(&uuid_normalized).ok().flatten();
+                if let (None, Some(file_id)) = (&metadata, linked.get(&uuid_normalized)) {
+                    metadata = index.get(file_id).ok().flatten();
+                    uuid_from_storj = file_id.clone();
+                }
+                if metadata.is_none() && trashed.contains(&uuid_normalized) {
+                    log::debug!("File {} is in another device's trash, not listed", uuid_normalized);
+                    continue;
+                }
+                
+                // Si le fichier n'est pas dans l'index local, on skip la synchronisation automatique
+                // pour éviter de télécharger tous les fichiers (très coûteux en bande passante)
+                // L'utilisateur peut forcer une synchronisation manuelle si nécessaire
+                if metadata.is_none() {
+                    log::warn!("⚠️ File {} not found in local index, skipping auto-sync (too expensive). Original UUID: {}", uuid_normalized, uuid_from_storj);
+                    // On continue sans télécharger le fichier pour économiser la bande passante
+                }
+                
+                files_with_metadata.push(StorjFileInfo {
+                    uuid: uuid_from_storj.clone(), // Garde le format original pour l'affichage
+                    logical_path: metadata.as_ref().map(|m| m.logical_path.clone()),
+                    encrypted_size: metadata.as_ref().map(|m| m.encrypted_size),
+                });
+            }
+        }
+        Err(_) => {
+            // Index non disponible, retourne juste les UUIDs sans métadonnées
+            for uuid in keys.into_iter().filter(|uuid| !trashed.contains(&remote_keys::normalize(uuid))) {
+                files_with_metadata.push(StorjFileInfo {
+                    uuid,
+                    logical_path: None,
+                    encrypted_size: None,
+                });
+            }
+        }
+    }
+    
+    files_with_metadata
+}
+
+/// Enregistre ce qu'a fait un passage de synchronisation (voir `summary_last_sync`).
+async fn record_sync_run(app: &tauri::AppHandle, state: &AppState, mut run: SyncRun) {
+    run.finished_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let recorded = match index_writer(app, state) {
+        Ok(writer) => match writer.write(move |index| summaries::record_sync_run(index, &run)).await {
+            Ok(result) => result.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        },
+        Err(e) => Err(e.to_string()),
+    };
+    if let Err(e) = recorded {
+        log::warn!("Failed to record the sync run: {}", e);
+    }
+}
+
+#[tauri::command]
+async fn storj_delete_file(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_uuid: Vec<u8>,
+) -> Result<(), CommandError> {
+    log::info!("storj_delete_file called: uuid={}", hex::encode(&file_uuid));
+    
+    if file_uuid.len() != 16 {
+        return Err(CommandError::InvalidFileId { file_id: hex::encode(&file_uuid) });
+    }
+    
+    let uuid_hex = hex::encode(&file_uuid);
+    let file_id = uuid_hex.clone();
+    
+    // Déplace vers la corbeille au lieu de supprimer définitivement
+    // Le fichier reste sur Storj jusqu'à ce qu'on vide la corbeille ou qu'on supprime définitivement
+    let writer = index_writer(&app, &state)
+        .inspect_err(|e| log::error!("Failed to open index for trash: {}", e))?;
+    
+    // Récupère les métadonnées du fichier puis le déplace vers la corbeille, en une seule écriture
+    let id = file_id.clone();
+    let (metadata, suggestion) = writer
+        .write(move |index| {
+            let Some(metadata) = index.get(&id)? else {
+                return Ok(None);
+            };
+            let before = quota::status(index)?;
+            index.move_to_trash(&id, &metadata)?;
+            let suggestion = trash_suggestion(&before, &quota::status(index)?);
+            Ok::<_, rusqlite::Error>(Some((metadata, suggestion)))
+        })
+        .await?
+        .map_err(|e| CommandError::index("Failed to move file to trash", e))?
+        .ok_or_else(|| CommandError::FileNotFound { file_id: file_id.clone() })?;
+    
+    log::info!("File moved to trash: file_id={}, logical_path={}", file_id, metadata.logical_path);
+    if let Some(suggestion) = suggestion {
+        events::emit(&app, suggestion);
+    }
+    Ok(())
+}
+
+/// Suggestion de vider la corbeille, quand elle vient de dépasser sa part de l'espace utilisé.
+fn trash_suggestion(before: &QuotaStatus, after: &QuotaStatus) -> Option<AppEvent> {
+    quota::trash_suggestion_crossed(before, after).then(|| AppEvent::TrashSuggestion {
+        trash_bytes: after.trash_bytes,
+        used_bytes: after.used_bytes,
+        share_percent: after.trash_share_percent(),
+    })
+}
+
+/// Anciennes versions d'un fichier, conservées quand un envoi l'a remplacé.
+#[tauri::command]
+async fn file_versions(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    logical_path: String,
+) -> Result<Vec<FileVersion>, CommandError> {
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(vault.versions(&logical_path)?)
+}
+
+/// Remet une ancienne version en place ; le fichier actuel devient une version.
+#[tauri::command]
+async fn file_version_restore(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    version_id: String,
+) -> Result<String, CommandError> {
+    log::info!("file_version_restore called: version_id={}", version_id);
+    let vault = vault_from_state(&app, &state).await?;
+    let metadata = vault.restore_version(&version_id)?;
+    Ok(metadata.logical_path)
+}
+
+/// Renomme un fichier (re-chiffré sous le nouveau chemin ; l'ancienne version part à la corbeille)
+///
+/// L'avancement est émis en `jobProgress` ; `job_cancel` interrompt le renommage entre deux étapes.
+#[tauri::command]
+async fn rename_file(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    old_logical_path: String,
+    new_logical_path: String,
+) -> Result<String, CommandError> {
+    log::info!("rename_file called: old_path={}, new_path={}", old_logical_path, new_logical_path);
+    telemetry::record_feature("rename");
+    let old_logical_path = normalize_path(&old_logical_path);
+    let new_logical_path = canonical_file_path(&new_logical_path)?;
+    
+    // Trouve le fichier dans l'index local par ancien chemin ; la destination doit être libre
+    let file_id = {
+        let index = open_index_with_state(&app, &state)?;
+        
+        let entries = index.list_all()
+            .map_err(|e| CommandError::index("Failed to list files from index", e))?;
+        
+        let file_id = entries
+            .iter()
+            .find(|(_, meta)| meta.logical_path == old_logical_path)
+            .map(|(id, _)| id.clone())
+            .ok_or_else(|| CommandError::PathNotFound { path: old_logical_path.clone() })?;
+        if entries.iter().any(|(_, meta)| meta.logical_path == new_logical_path) {
+            return Err(CommandError::AlreadyExists { path: new_logical_path });
+        }
+        
+        log::debug!("Found file in index: file_id={}, old_logical_path={}", file_id, old_logical_path);
+        file_id
+    };
+    
+    let vault = vault_from_state(&app, &state).await?;
+    warn_if_locked(&app, &vault, &old_logical_path).await;
+    let jobs = app.state::<JobRegistry>();
+    let (job_id, token) = jobs.start();
+    let job = Job::new(job_id, RENAME_STEPS, token, |progress| {
+        events::emit(
+            &app,
+            AppEvent::JobProgress {
+                job_id: progress.job_id,
+                kind: "rename".to_string(),
+                label: new_logical_path.clone(),
+                step: progress.step.to_string(),
+                step_index: progress.step_index,
+                step_count: progress.step_count,
+                state: progress.state,
+            },
+        )
+    });
+    let result = vault.rename_job(&file_id, &new_logical_path, &job).await;
+    drop(job);
+    jobs.finish(job_id);
+    let new_file_id = result?;
+    
+    log::info!("✅ File renamed successfully: {} -> {} (old_uuid={}, new_uuid={})", old_logical_path, new_logical_path, file_id, new_file_id);
+    
+    Ok(new_file_id)
+}
+
+/// Demande l'annulation d'une opération en cours (voir `jobProgress`).
+///
+/// Renvoie `false` si le job est déjà terminé ; sinon il s'arrête avant sa prochaine étape.
+#[tauri::command]
+fn job_cancel(jobs: State<'_, JobRegistry>, job_id: u64) -> bool {
+    log::info!("job_cancel called: job_id={}", job_id);
+    jobs.cancel(job_id)
+}
+
+/// Renomme un dossier et toute sa sous-arborescence (une seule transaction de l'index).
+///
+/// Le re-chiffrement des fichiers sous leurs nouveaux chemins part aussitôt en tâche de fond,
+/// sauf synchronisation suspendue ; il est sinon rejoué avec la file hors ligne.
+#[tauri::command]
+async fn rename_folder(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    old_path: String,
+    new_path: String,
+) -> Result<FolderRenameReport, CommandError> {
+    log::info!("rename_folder called: old_path={}, new_path={}", old_path, new_path);
+    telemetry::record_feature("rename_folder");
+
+    let vault = vault_from_state(&app, &state).await?;
+    let report = vault.rename_folder(&normalize_path(&old_path), &normalize_path(&new_path))?;
+    if report.pending_rebinds > 0 && !state.sync_paused.load(Ordering::SeqCst) {
+        tauri::async_runtime::spawn(async move {
+            match vault.flush_pending().await {
+                Ok(flush) => log::info!("Rebind after folder rename: completed={}, remaining={}", flush.completed, flush.remaining),
+                Err(e) => log::warn!("Rebind after folder rename failed: {}", e),
+            }
+        });
+    }
+    Ok(report)
+}
+
+/// Fait apparaître un fichier à un autre chemin du coffre, sans dupliquer son contenu.
+#[tauri::command]
+async fn alias_create(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    target_id: String,
+    logical_path: String,
+) -> Result<AliasEntry, CommandError> {
+    telemetry::record_feature("alias_create");
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(vault.create_alias(&normalize_path(&logical_path), &target_id)?)
+}
+
+/// Fichier désigné par un alias (pour l'ouvrir ou le télécharger).
+#[tauri::command]
+async fn alias_resolve(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    alias_id: String,
+) -> Result<FileEntry, CommandError> {
+    let vault = vault_from_state(&app, &state).await?;
+    let (id, meta) = vault.resolve_alias(&alias_id)?;
+    let pending = vault.open_index()?.pending_upload_ids()?.contains(&id);
+    Ok(FileEntry {
+        id,
+        logical_path: meta.logical_path,
+        encrypted_size: meta.encrypted_size,
+        pending,
+        alias_of: None,
+    })
+}
+
+/// Supprime un alias ; le fichier désigné reste en place.
+#[tauri::command]
+async fn alias_delete(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    alias_id: String,
+) -> Result<(), CommandError> {
+    let vault = vault_from_state(&app, &state).await?;
+    vault.remove_alias(&alias_id)?;
+    log::info!("Alias deleted: {}", alias_id);
+    Ok(())
+}
+
+/// Capture l'état actuel du coffre ; les objets qu'il référence ne seront plus purgés.
+#[tauri::command]
+async fn snapshot_create(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<SnapshotInfo, CommandError> {
+    telemetry::record_feature("snapshot_create");
+    let writer = index_writer(&app, &state)?;
+    Ok(writer.write(move |index| snapshot::create(index, &name)).await??)
+}
+
+#[tauri::command]
+fn snapshot_list(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<SnapshotInfo>, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(index.list_snapshots()?)
+}
+
+/// Changement d'un écart d'instantané, élément paginé de [`SnapshotDiffListing`].
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SnapshotChange {
+    Added(DiffEntry),
+    Removed(DiffEntry),
+    Moved(MovedEntry),
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotDiffListing {
+    #[serde(flatten)]
+    pub page: Page<SnapshotChange>,
+    /// Changements de chaque sorte dans l'écart complet.
+    pub added: usize,
+    pub removed: usize,
+    pub moved: usize,
+}
+
+/// Ce qui a changé depuis un instantané (et serait annulé en le restaurant), par pages :
+/// ajouts, puis suppressions, puis déplacements.
+#[tauri::command]
+fn snapshot_diff(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    snapshot_id: i64,
+    page: Option<PageRequest>,
+) -> Result<SnapshotDiffListing, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    let diff = snapshot::diff(&index, snapshot_id)?;
+    let (added, removed, moved) = (diff.added.len(), diff.removed.len(), diff.moved.len());
+    let changes = diff
+        .added
+        .into_iter()
+        .map(SnapshotChange::Added)
+        .chain(diff.removed.into_iter().map(SnapshotChange::Removed))
+        .chain(diff.moved.into_iter().map(SnapshotChange::Moved))
+        .collect();
+    Ok(SnapshotDiffListing {
+        page: paging::paginate(changes, &page.unwrap_or_default())?,
+        added,
+        removed,
+        moved,
+    })
+}
+
+/// Ramène tout le coffre à l'état d'un instantané ; les fichiers plus récents partent à la
+/// corbeille. Le re-chiffrement des fichiers déplacés depuis part en tâche de fond.
+#[tauri::command]
+async fn snapshot_restore(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    snapshot_id: i64,
+) -> Result<SnapshotRestore, CommandError> {
+    log::info!("snapshot_restore called: snapshot_id={}", snapshot_id);
+    telemetry::record_feature("snapshot_restore");
+
+    let vault = vault_from_state(&app, &state).await?;
+    let report = snapshot::restore(&mut vault.open_index()?, snapshot_id)?;
+    if report.restored > 0 && !state.sync_paused.load(Ordering::SeqCst) {
+        tauri::async_runtime::spawn(async move {
+            match vault.flush_pending().await {
+                Ok(flush) => log::info!("Rebind after snapshot restore: completed={}, remaining={}", flush.completed, flush.remaining),
+                Err(e) => log::warn!("Rebind after snapshot restore failed: {}", e),
+            }
+        });
+    }
+    Ok(report)
+}
+
+/// Supprime un instantané, ainsi que les objets distants qu'il était seul à retenir.
+///
+/// Avec `dry_run`, rien n'est supprimé : la commande décrit ce qui le serait.
+#[tauri::command]
+async fn snapshot_delete(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    snapshot_id: i64,
+    dry_run: Option<bool>,
+) -> Result<CommandOutcome<usize>, CommandError> {
+    if dry_run == Some(true) {
+        let index = open_index_with_state(&app, &state)?;
+        return Ok(CommandOutcome::DryRun(snapshot::plan_delete(&index, snapshot_id)?));
+    }
+    let vault = vault_from_state(&app, &state).await?;
+    let released = snapshot::delete(&vault, snapshot_id).await?;
+    log::info!("Snapshot {} deleted, {} remote objects released", snapshot_id, released);
+    Ok(CommandOutcome::Applied(released))
+}
+
+#[tauri::command]
+async fn storj_download_file_by_path(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    logical_path: String,
+) -> Result<Vec<u8>, CommandError> {
+    log::info!("storj_download_file_by_path called: logical_path={}", logical_path);
+    
+    // Cherche le fichier dans l'index local par chemin logique
+    let file_id = {
+        let index = open_index_with_state(&app, &state)?;
+        
+        // Liste tous les fichiers et trouve celui avec le chemin logique correspondant
+        let entries = index.list_all()
+            .map_err(|e| CommandError::index("Failed to list files from index", e))?;
+        
+        let (file_id, _metadata) = entries
+            .into_iter()
+            .find(|(_, meta)| meta.logical_path == logical_path)
+            .ok_or_else(|| CommandError::PathNotFound { path: logical_path.clone() })?;
+        
+        log::debug!("Found file in index: file_id={}, logical_path={}", file_id, logical_path);
+        file_id
+    };
+    
+    // Convertit le file_id (UUID hex) en bytes pour le download Storj
+    let file_uuid = hex::decode(&file_id)
+        .map_err(|_| CommandError::InvalidFileId { file_id: file_id.clone() })?;
+    
+    if file_uuid.len() != 16 {
+        return Err(CommandError::InvalidFileId { file_id: file_id.clone() });
+    }
+    
+    // Télécharge depuis Storj en utilisant l'UUID
+    let uuid_array: [u8; 16] = file_uuid.try_into()
+        .map_err(|_| CommandError::InvalidFileId { file_id: file_id.clone() })?;
+    
+    let uuid_hex = hex::encode(uuid_array);
+    let vault = vault_from_state(&app, &state).await?;
+    let data = vault.fetch_blob(&uuid_hex).await?;
+    note_access(&vault, &uuid_hex);
+    
+    log::info!("File downloaded successfully from Storj via index lookup: logical_path={}", logical_path);
+    tray::push_recent(&app, &logical_path);
+    Ok(data)
+}
+
+/// Télécharge un fichier directement dans `dest_path`, sans renvoyer son contenu à la
+/// webview : déchiffré (par défaut, voir `Vault::save_to`) ou tel quel avec
+/// `decrypt: false` (fichier `.aeth`). Rien n'apparaît sous `dest_path` avant la fin du
+/// transfert ; une destination existante est refusée. Retourne la taille écrite.
+#[tauri::command]
+async fn storj_download_to_path(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_id: String,
+    dest_path: String,
+    decrypt: Option<bool>,
+) -> Result<u64, CommandError> {
+    log::info!("storj_download_to_path called: file_id={}, dest_path={}, decrypt={:?}", file_id, dest_path, decrypt);
+    let dest = PathBuf::from(&dest_path);
+    if dest.exists() {
+        return Err(CommandError::AlreadyExists { path: dest_path });
+    }
+    if !dest.parent().is_some_and(|parent| parent.as_os_str().is_empty() || parent.is_dir()) {
+        return Err(CommandError::PathNotFound { path: dest_path });
+    }
+
+    let vault = vault_from_state(&app, &state).await?;
+    let logical_path = vault.find_including_trash(&file_id)?.logical_path;
+    let reporter = TransferReporter::new(&app, &file_id, TransferDirection::Download);
+    // La fin n'est rapportée qu'une fois le fichier vérifié et en place
+    let mut total = 0;
+    let progress = |bytes_done: u64, bytes_total: u64| {
+        total = bytes_total;
+        if bytes_done < bytes_total {
+            reporter.report(bytes_done, bytes_total);
+        }
+    };
+    let size = if decrypt.unwrap_or(true) {
+        vault.save_to(&file_id, &logical_path, &dest, progress).await?
+    } else {
+        vault.save_encrypted_to(&file_id, &dest, progress).await?
+    };
+    note_access(&vault, &file_id);
+    telemetry::record_transfer(TransferDirection::Download, size);
+    reporter.report(total, total);
+    tray::push_recent(&app, &logical_path);
+
+    log::info!("File downloaded to {} ({} bytes)", dest_path, size);
+    Ok(size)
+}
+
+/// Algorithmes et paramètres de chiffrement d'un fichier (lus dans son en-tête, rien n'est déchiffré).
+#[tauri::command]
+async fn get_encryption_details(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_id: String,
+) -> Result<EncryptionDetails, CommandError> {
+    log::info!("get_encryption_details called: file_id={}", file_id);
+    telemetry::record_feature("encryption_details");
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(vault.encryption_details(&file_id).await?)
+}
+
+/// Vérifie qu'une copie exportée ou restaurée correspond au fichier du coffre.
+///
+/// Sans `local_path`, la copie est choisie via le dialogue natif. Elle est hachée en flux ;
+/// l'objet distant est relu et authentifié (voir `Vault::verify_download`).
+#[tauri::command]
+async fn verify_download(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_id: String,
+    local_path: Option<String>,
+) -> Result<DownloadVerification, CommandError> {
+    log::info!("verify_download called: file_id={}", file_id);
+    telemetry::record_feature("verify_download");
+
+    let path = match local_path {
+        Some(path) => FilePath::Path(PathBuf::from(path)),
+        None => picker::pick_open(&app, "Sélectionner la copie à vérifier").await?,
+    };
+    let reader = app.clone();
+    let (digest, size) = tokio::task::spawn_blocking(move || -> std::io::Result<([u8; 32], u64)> {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        let size = std::io::copy(&mut picker::open_read(&reader, &path)?, &mut hasher)?;
+        Ok((hasher.finalize().into(), size))
+    })
+    .await
+    .map_err(|e| CommandError::internal("Hashing task failed", e))?
+    .map_err(|e| CommandError::io("Failed to read local copy", e))?;
+
+    let vault = vault_from_state(&app, &state).await?;
+    let report = vault.verify_download(&file_id, &digest, size).await?;
+    log::info!("Download verification for {}: verified={}", file_id, report.verified);
+    Ok(report)
+}
+
+/// Télécharge et déchiffre un fichier pour l'aperçu (retourne les données déchiffrées en mémoire)
+#[tauri::command]
+async fn preview_file(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_id: String,
+) -> Result<Vec<u8>, CommandError> {
+    log::info!("preview_file called: file_id={}", file_id);
+    telemetry::record_feature("preview");
+    
+    // Récupère les métadonnées du fichier (index ou corbeille)
+    let vault = vault_from_state(&app, &state).await?;
+    let metadata = vault.find_including_trash(&file_id)?;
+    
+    // Convertit le file_id (UUID hex) en bytes pour le download Storj
+    let file_uuid = hex::decode(&file_id)
+        .map_err(|_| CommandError::InvalidFileId { file_id: file_id.clone() })?;
+    if file_uuid.len() != 16 {
+        return Err(CommandError::InvalidFileId { file_id: file_id.clone() });
+    }
+    
+    // Au-delà, la webview risquerait de manquer de mémoire : voir `preview_open`
+    let size = vault.plaintext_size(&file_id, &metadata)?;
+    if size > preview::INLINE_MAX_BYTES {
+        return Err(CommandError::PreviewTooLarge { size, limit: preview::INLINE_MAX_BYTES });
+    }
+    let logical_path = metadata.logical_path;
+    
+    // Récupère (cache local, sinon Storj) et déchiffre le fichier ; un contenu qui ne
+    // vérifie pas met le fichier en quarantaine
+    let uuid_hex = hex::encode(&file_uuid);
+    let plaintext = vault.get_with_path(&uuid_hex, &logical_path).await?;
+    note_access(&vault, &uuid_hex);
+    
+    log::info!("File decrypted successfully for preview: size={}", plaintext.len());
+    // Mêmes garde-fous que `preview_open` : seul un contenu conforme à son type est renvoyé
+    let kind = PreviewKind::from_path(&logical_path);
+    if let Vetted::Refused(reason) = preview::vet(kind, &logical_path, &plaintext) {
+        return Err(CommandError::PreviewRefused { reason });
+    }
+    if kind == PreviewKind::Unsupported {
+        return Err(CommandError::PreviewRefused { reason: "unsupported" });
+    }
+    Ok(plaintext)
+}
+
+/// Liste les fichiers de la corbeille, par pages
+#[tauri::command]
+fn list_trash(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    page: Option<PageRequest>,
+) -> Result<TrashListing, CommandError> {
+    log::info!("list_trash called");
+    
+    let index = open_index_with_state(&app, &state)?;
+    let trash_items = index.list_trash()
+        .map_err(|e| CommandError::index("Failed to list trash", e))?;
+    let mut folders = index.trashed_folder_members()?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    
+    let entries: Vec<TrashEntry> = trash_items.into_iter().map(|(id, meta, deleted_at)| {
+        TrashEntry {
+            folder_path: folders.remove(&id),
+            id,
+            logical_path: meta.logical_path,
+            encrypted_size: meta.encrypted_size,
+            deleted_at,
+            age_seconds: now.saturating_sub(deleted_at).max(0),
+        }
+    }).collect();
+    
+    log::debug!("Found {} items in trash", entries.len());
+    Ok(TrashListing {
+        page: paging::paginate(entries, &page.unwrap_or_default())?,
+        total_bytes: quota::status(&index)?.trash_bytes,
+    })
+}
+
+/// Restaure un fichier depuis la corbeille vers l'index principal
+#[tauri::command]
+async fn restore_from_trash(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_id: String,
+) -> Result<String, CommandError> {
+    log::info!("restore_from_trash called: file_id={}", file_id);
+    telemetry::record_feature("trash_restore");
+    
+    // Un objet distant disparu ou illisible laisse l'entrée à la corbeille, en quarantaine
+    let vault = vault_from_state(&app, &state).await?;
+    vault.verify_trashed(std::slice::from_ref(&file_id)).await?;
+    
+    let writer = index_writer(&app, &state)?;
+    let id = file_id.clone();
+    let (metadata, conflict) = writer
+        .write(move |index| {
+            let requested = index.get_trashed(&id)?.map(|meta| meta.logical_path);
+            let metadata = index.restore_from_trash(&id)?;
+            let conflict = match requested {
+                Some(requested) => placement_conflict(index, &id, &requested, &metadata.logical_path)?,
+                None => None,
+            };
+            Ok::<_, rusqlite::Error>((metadata, conflict))
+        })
+        .await?
+        .map_err(|e| CommandError::index("Failed to restore file from trash", e))?;
+    
+    // Un autre fichier a pu prendre ce chemin entre-temps : le fichier restauré est placé à côté
+    if let Some(conflict) = conflict {
+        log::warn!("Restored next to an occupied path: {:?}", conflict);
+        events::emit(&app, conflict);
+    }
+    
+    log::info!("File restored from trash: file_id={}, logical_path={}", file_id, metadata.logical_path);
+    Ok(metadata.logical_path)
+}
+
+/// Événement à émettre quand une entrée n'a pas pu prendre le chemin demandé.
+fn placement_conflict(
+    index: &SqlCipherIndex,
+    file_id: &FileId,
+    requested: &str,
+    placed: &str,
+) -> rusqlite::Result<Option<AppEvent>> {
+    if requested == placed {
+        return Ok(None);
+    }
+    Ok(Some(AppEvent::Conflict {
+        logical_path: requested.to_string(),
+        existing_file_id: index.path_holder(requested, file_id)?.unwrap_or_default(),
+        new_file_id: file_id.clone(),
+        final_path: placed.to_string(),
+    }))
+}
+
+/// Envoie un dossier et tout son contenu à la corbeille ; retourne le nombre d'entrées déplacées.
+///
+/// Avec `dry_run`, rien n'est déplacé : la commande décrit ce qui le serait.
+#[tauri::command]
+async fn trash_folder(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    folder_path: String,
+    dry_run: Option<bool>,
+) -> Result<CommandOutcome<usize>, CommandError> {
+    log::info!("trash_folder called: folder_path={}, dry_run={:?}", folder_path, dry_run);
+    
+    let folder = normalize_path(&folder_path);
+    if folder.trim_end_matches('/').is_empty() {
+        return Err(CommandError::InvalidName { reason: "root" });
+    }
+    if dry_run == Some(true) {
+        let report = dry_run::trash_folder(&open_index_with_state(&app, &state)?, &folder)?;
+        if report.entries == 0 {
+            return Err(CommandError::PathNotFound { path: folder });
+        }
+        return Ok(CommandOutcome::DryRun(report));
+    }
+    telemetry::record_feature("trash_folder");
+    
+    let writer = index_writer(&app, &state)?;
+    let path = folder.clone();
+    let (moved, suggestion) = writer
+        .write(move |index| {
+            let before = quota::status(index)?;
+            let moved = index.move_folder_to_trash(&path)?;
+            Ok::<_, rusqlite::Error>((moved, trash_suggestion(&before, &quota::status(index)?)))
+        })
+        .await?
+        .map_err(|e| CommandError::index("Failed to move folder to trash", e))?;
+    if moved == 0 {
+        return Err(CommandError::PathNotFound { path: folder });
+    }
+    
+    log::info!("Folder moved to trash: folder_path={}, entries={}", folder, moved);
+    if let Some(suggestion) = suggestion {
+        events::emit(&app, suggestion);
+    }
+    Ok(CommandOutcome::Applied(moved))
+}
+
+/// Dossiers de la corbeille ; leurs fichiers figurent aussi dans `list_trash` (avec `folder_path`).
+#[tauri::command]
+fn list_trashed_folders(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    page: Option<PageRequest>,
+) -> Result<Page<TrashedFolder>, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(paging::paginate(index.list_trashed_folders()?, &page.unwrap_or_default())?)
+}
+
+/// Restaure un dossier de la corbeille à son emplacement d'origine, avec tout son contenu
+#[tauri::command]
+async fn restore_folder_from_trash(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    folder_path: String,
+    deleted_at: i64,
+) -> Result<usize, CommandError> {
+    log::info!("restore_folder_from_trash called: folder_path={}, deleted_at={}", folder_path, deleted_at);
+    telemetry::record_feature("trash_restore");
+    
+    // Tout le contenu est vérifié d'abord : rien n'est restauré tant qu'un objet manque
+    let vault = vault_from_state(&app, &state).await?;
+    let members: Vec<FileId> = {
+        let index = vault.open_index()?;
+        let prefix = format!("{}/", folder_path.trim_end_matches('/'));
+        let folders = index.trashed_folder_members()?;
+        index
+            .list_trash()?
+            .into_iter()
+            .filter(|(id, _, at)| *at == deleted_at && folders.get(id) == Some(&prefix))
+            .map(|(id, _, _)| id)
+            .collect()
+    };
+    vault.verify_trashed(&members).await?;
+    
+    let writer = index_writer(&app, &state)?;
+    let (restored, conflicts) = writer
+        .write(move |index| {
+            let requested: std::collections::HashMap<FileId, String> = index
+                .list_trash()?
+                .into_iter()
+                .map(|(id, meta, _)| (id, meta.logical_path))
+                .collect();
+            let restored = index.restore_folder_from_trash(&folder_path, deleted_at)?;
+            let mut conflicts = Vec::new();
+            for (file_id, meta) in &restored {
+                if let Some(requested) = requested.get(file_id) {
+                    conflicts.extend(placement_conflict(index, file_id, requested, &meta.logical_path)?);
+                }
+            }
+            Ok::<_, rusqlite::Error>((restored.len(), conflicts))
+        })
+        .await?
+        .map_err(|e| CommandError::index("Failed to restore folder from trash", e))?;
+    
+    // Comme pour un fichier seul : un fichier dont le chemin a été repris est placé à côté
+    for conflict in conflicts {
+        log::warn!("Restored next to an occupied path: {:?}", conflict);
+        events::emit(&app, conflict);
+    }
+    
+    log::info!("Folder restored from trash: entries={}", restored);
+    Ok(restored)
+}
+
+/// Supprime définitivement un fichier de la corbeille (supprime aussi de Storj)
+///
+/// Avec `dry_run`, rien n'est supprimé : la commande décrit ce qui le serait.
+#[tauri::command]
+async fn permanently_delete_from_trash(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_id: String,
+    dry_run: Option<bool>,
+) -> Result<CommandOutcome<()>, CommandError> {
+    log::info!("permanently_delete_from_trash called: file_id={}, dry_run={:?}", file_id, dry_run);
+    
+    // Convertit le file_id en UUID bytes
+    let file_uuid = hex::decode(&file_id)
+        .map_err(|_| CommandError::InvalidFileId { file_id: file_id.clone() })?;
+    
+    if file_uuid.len() != 16 {
+        return Err(CommandError::InvalidFileId { file_id: file_id.clone() });
+    }
+    
+    let uuid_array: [u8; 16] = file_uuid.try_into()
+        .map_err(|_| CommandError::InvalidFileId { file_id: file_id.clone() })?;
+    
+    // Supprime de Storj (différé si hors ligne) puis de la corbeille
+    let uuid_hex = hex::encode(uuid_array);
+    if dry_run == Some(true) {
+        let index = open_index_with_state(&app, &state)?;
+        return Ok(CommandOutcome::DryRun(dry_run::purge(&index, &uuid_hex)?));
+    }
+    let vault = vault_from_state(&app, &state).await?;
+    vault.purge(&uuid_hex).await?;
+    
+    log::info!("File permanently deleted from trash: file_id={}", file_id);
+    events::emit(&app, AppEvent::TrashPurged { file_ids: vec![file_id] });
+    Ok(CommandOutcome::Applied(()))
+}
+
+/// Vide la corbeille : chaque objet distant est supprimé puis son absence vérifiée.
+///
+/// Un élément dont la suppression échoue reste dans la corbeille et figure dans `failed`.
+///
+/// Avec `dry_run`, rien n'est supprimé : la commande décrit ce qui le serait.
+#[tauri::command]
+async fn empty_trash(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    dry_run: Option<bool>,
+) -> Result<CommandOutcome<PurgeReport>, CommandError> {
+    log::info!("empty_trash called: dry_run={:?}", dry_run);
+    if dry_run == Some(true) {
+        let index = open_index_with_state(&app, &state)?;
+        return Ok(CommandOutcome::DryRun(dry_run::empty_trash(&index)?));
+    }
+    telemetry::record_feature("trash_empty");
+    
+    let vault = vault_from_state(&app, &state).await?;
+    let report = vault.empty_trash().await?;
+    
+    log::info!("Trash emptied: purged={}, failed={}", report.purged.len(), report.failed.len());
+    events::emit(&app, AppEvent::TrashPurged {
+        file_ids: report.purged.clone(),
+    });
+    Ok(CommandOutcome::Applied(report))
+}
+
+/// Résultat d'une commande destructive ou groupée : celui de l'opération, ou ce qu'elle
+/// toucherait quand elle est appelée avec `dry_run`.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum CommandOutcome<T> {
+    Applied(T),
+    DryRun(DryRunReport),
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrashEntry {
+    pub id: String,
+    pub logical_path: String,
+    pub encrypted_size: u64,
+    pub deleted_at: i64, // Timestamp Unix en secondes
+    /// Dossier supprimé avec ce fichier, restauré d'un bloc (absent pour un fichier supprimé seul).
+    pub folder_path: Option<String>,
+    /// Temps passé dans la corbeille, en secondes.
+    pub age_seconds: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrashListing {
+    #[serde(flatten)]
+    pub page: Page<TrashEntry>,
+    /// Octets libérés en vidant la corbeille (les objets partagés avec un fichier vivant restent).
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportSummary {
+    pub exported: usize,
+    pub skipped: usize,
+    pub failed: Vec<String>,
+}
+
+/// Prépare l'aperçu d'un fichier selon son type et sa taille (voir `preview::PreviewPlan`).
+///
+/// Texte : seul le début est transmis. Médias et gros PDF : servis par plages via le
+/// protocole `aether-preview`, le contenu déchiffré restant dans le processus backend.
+#[tauri::command]
+async fn preview_open(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    sessions: State<'_, PreviewSessions>,
+    file_id: String,
+) -> Result<PreviewPayload, CommandError> {
+    log::info!("preview_open called: file_id={}", file_id);
+    telemetry::record_feature("preview");
+
+    let vault = vault_from_state(&app, &state).await?;
+    // Les fichiers de la corbeille restent consultables avant restauration ou purge
+    let meta = vault.find_including_trash(&file_id)?;
+    let kind = PreviewKind::from_path(&meta.logical_path);
+    let size = vault.plaintext_size(&file_id, &meta)?;
+
+    let plan = PreviewPlan::for_file(kind, size);
+    if let PreviewPlan::Unavailable(reason) = plan {
+        log::info!("No preview for {}: {} ({} bytes)", file_id, reason, size);
+        return Ok(PreviewPayload::Unavailable { kind, reason, size });
+    }
+
+    let plaintext = vault.get_with_path(&file_id, &meta.logical_path).await?;
+    note_access(&vault, &file_id);
+    // Rien n'atteint la webview avant d'avoir été comparé au type annoncé
+    let payload = match (preview::vet(kind, &meta.logical_path, &plaintext), plan) {
+        (Vetted::Refused(reason), _) => {
+            log::warn!("Preview refused for {}: {}", file_id, reason);
+            PreviewPayload::Unavailable { kind, reason, size }
+        }
+        (Vetted::AsText, _) => match preview::text_head(&plaintext) {
+            Some((head, truncated)) => PreviewPayload::Text {
+                data: head.to_vec(),
+                truncated,
+                total_size: size,
+            },
+            None => PreviewPayload::Unavailable { kind, reason: "unsupported", size },
+        },
+        (Vetted::Serve { kind, mime }, PreviewPlan::Stream) => PreviewPayload::Stream {
+            kind,
+            url: sessions.open(plaintext, mime),
+            size,
+        },
+        (Vetted::Serve { kind, mime }, _) => PreviewPayload::Inline { kind, mime, data: plaintext },
+    };
+    log::info!("Preview ready for {}: plan={:?}", file_id, plan);
+    Ok(payload)
+}
+
+/// Libère le contenu déchiffré de l'aperçu en flux courant.
+#[tauri::command]
+fn preview_close(sessions: State<'_, PreviewSessions>) {
+    sessions.close();
+}
+
+/// Exporte tout le coffre déchiffré dans `destination_dir` (arborescence des chemins logiques).
+///
+/// Les fichiers sont traités un par un (jamais tout le coffre en mémoire) et chaque
+/// fichier terminé est consigné dans un manifeste : relancer l'export reprend là où il
+/// s'est arrêté. Un fichier en échec n'interrompt pas l'export ; il est listé dans `failed`.
+#[tauri::command]
+async fn export_vault(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    destination_dir: String,
+) -> Result<ExportSummary, CommandError> {
+    log::info!("export_vault called: destination_dir={}", destination_dir);
+    telemetry::record_feature("export");
+
+    let root = PathBuf::from(&destination_dir);
+    fs::create_dir_all(&root).map_err(|e| CommandError::io("Failed to create export directory", e))?;
+    let mut manifest = ExportManifest::load(&root)?;
+
+    let vault = vault_from_state(&app, &state).await?;
+    let entries = vault.list()?;
+
+    let files_total = entries.len();
+    let mut summary = ExportSummary { exported: 0, skipped: 0, failed: Vec::new() };
+
+    for (files_done, (file_id, meta)) in entries.into_iter().enumerate() {
+        let dest = match export::destination_for(&root, &meta.logical_path) {
+            Ok(dest) => dest,
+            Err(e) => {
+                log::warn!("Skipping export of {}: {}", file_id, e);
+                summary.failed.push(meta.logical_path);
+                continue;
+            }
+        };
+
+        if meta.logical_path.ends_with('/') || meta.encrypted_size == 0 {
+            // Dossier (éventuellement vide) : on recrée simplement l'arborescence
+            fs::create_dir_all(&dest).map_err(|e| CommandError::io("Failed to create export folder", e))?;
+        } else if manifest.is_done(&file_id) {
+            summary.skipped += 1;
+        } else {
+            let result = async {
+                let plaintext = vault.get_with_path(&file_id, &meta.logical_path).await?;
+                export::write_atomic(&dest, &plaintext)?;
+                manifest.mark_done(&file_id)?;
+                Ok::<(), CommandError>(())
+            }.await;
+
+            match result {
+                Ok(()) => summary.exported += 1,
+                Err(e) => {
+                    log::warn!("Failed to export {} ({}): {}", file_id, meta.logical_path, e);
+                    summary.failed.push(meta.logical_path.clone());
+                }
+            }
+        }
+
+        events::emit(&app, AppEvent::ExportProgress {
+            files_done: files_done + 1,
+            files_total,
+            logical_path: meta.logical_path,
+        });
+    }
+
+    log::info!(
+        "Vault export finished: exported={}, skipped={}, failed={}",
+        summary.exported,
+        summary.skipped,
+        summary.failed.len()
+    );
+    Ok(summary)
+}
+
+/// Écrit une archive chiffrée du coffre dans `destination_path` (voir `export::archive`).
+///
+/// L'enveloppe de la MasterKey (`password_salt`, `mkek`) est rangée dans l'archive : elle est
+/// d'abord ouverte avec `password`, pour ne jamais produire une archive que ce mot de passe
+/// n'ouvrirait pas. Les fichiers qui ne se déchiffrent pas sont écartés et listés.
+#[tauri::command]
+async fn export_archive(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    destination_path: String,
+    password: String,
+    password_salt: [u8; 16],
+    mkek: MkekCiphertext,
+) -> Result<archive::ArchiveSummary, CommandError> {
+    log::info!("export_archive called: destination_path={}", destination_path);
+    telemetry::record_feature("export_archive");
+
+    let vault = vault_from_state(&app, &state).await?.with_priority(TransferPriority::Bulk);
+    let key = archive::ArchiveKey { password_salt, mkek };
+    let fingerprint = vault.master_key().fingerprint();
+    let checked = key.clone();
+    let password = PasswordSecret::new(password);
+    // Argon2 : hors du runtime async
+    tokio::task::spawn_blocking(move || archive::open_key(&checked, &password, &fingerprint).map(drop))
+        .await
+        .map_err(|e| CommandError::internal("Key check task failed", e))??;
+
+    let summary = archive::write(&vault, &PathBuf::from(&destination_path), key, |files_done, files_total, logical_path| {
+        events::emit(&app, AppEvent::ExportProgress {
+            files_done,
+            files_total,
+            logical_path: logical_path.to_string(),
+        });
+    })
+    .await?;
+    Ok(summary)
+}
+
+/// Écrit dans `destination_path` un lot chiffré des fichiers `file_ids`, à remettre à un tiers
+/// (voir `archive::write_selection`) : il s'ouvre avec `aether-archive` et `password`, choisi
+/// pour l'occasion, sans rien livrer du coffre.
+#[tauri::command]
+async fn export_encrypted_selection(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_ids: Vec<String>,
+    password: String,
+    destination_path: String,
+) -> Result<archive::ArchiveSummary, CommandError> {
+    log::info!("export_encrypted_selection called: files={}, destination_path={}", file_ids.len(), destination_path);
+    telemetry::record_feature("export_selection");
+
+    let vault = vault_from_state(&app, &state).await?.with_priority(TransferPriority::Bulk);
+    let password = PasswordSecret::new(password);
+    // Argon2 : hors du runtime async
+    let key = tokio::task::spawn_blocking(move || archive::SelectionKey::new(&password))
+        .await
+        .map_err(|e| CommandError::internal("Key derivation task failed", e))??;
+
+    let summary = archive::write_selection(&vault, &file_ids, &PathBuf::from(&destination_path), key, |files_done, files_total, logical_path| {
+        events::emit(&app, AppEvent::ExportProgress {
+            files_done,
+            files_total,
+            logical_path: logical_path.to_string(),
+        });
+    })
+    .await?;
+    Ok(summary)
+}
+
+/// Écrit l'inventaire du coffre (chemins, tailles, dates, versions, état de synchro) en CSV ou JSON.
+///
+/// Aucun contenu n'est déchiffré ; retourne le nombre d'entrées écrites.
+#[tauri::command]
+async fn export_report(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    format: String,
+    destination_path: String,
+) -> Result<usize, CommandError> {
+    log::info!("export_report called: format={}", format);
+    telemetry::record_feature("export_report");
+    let format = ReportFormat::parse(&format)
+        .ok_or_else(|| CommandError::Internal(format!("Unsupported report format: {}", format)))?;
+
+    let vault = vault_from_state(&app, &state).await?;
+    let rows = report::build(&vault).await?;
+    export::write_atomic(&PathBuf::from(destination_path), &report::render(&rows, format)?)?;
+    log::info!("Inventory report written: entries={}", rows.len());
+    Ok(rows.len())
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebDavInfo {
+    pub url: String,
+    pub username: String,
+    pub password: String,
+}
+
+/// Démarre le serveur WebDAV local (lecture seule) exposant le coffre déverrouillé.
+///
+/// N'écoute que sur 127.0.0.1 ; les identifiants changent à chaque démarrage.
+#[tauri::command]
+async fn webdav_start(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    port: Option<u16>,
+) -> Result<WebDavInfo, CommandError> {
+    telemetry::record_feature("webdav");
+    let vault = vault_from_state(&app, &state).await?;
+
+    let mut webdav_guard = state.webdav.lock().await;
+    if let Some(previous) = webdav_guard.take() {
+        previous.stop();
+    }
+    let server = WebDavServer::start(Arc::new(vault), port.unwrap_or(0))
+        .await
+        .map_err(|e| CommandError::io("Failed to start WebDAV server", e))?;
+
+    let info = WebDavInfo {
+        url: format!("http://{}/", server.addr),
+        username: WEBDAV_USER.to_string(),
+        password: server.password.clone(),
+    };
+    *webdav_guard = Some(server);
+    log::info!("WebDAV server started: {}", info.url);
+    Ok(info)
+}
+
+#[tauri::command]
+async fn webdav_stop(state: State<'_, AppState>) -> Result<(), CommandError> {
+    if let Some(server) = state.webdav.lock().await.take() {
+        server.stop();
+    }
+    Ok(())
+}
+
+/// Consommation actuelle et budget de stockage (marge restante si un budget est défini)
+#[tauri::command]
+fn quota_status(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<QuotaStatus, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(quota::status(&index)?)
+}
+
+/// Définit le budget de stockage en octets (`None` le retire)
+#[tauri::command]
+async fn quota_set_limit(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    limit_bytes: Option<u64>,
+) -> Result<QuotaStatus, CommandError> {
+    log::info!("quota_set_limit called: limit_bytes={:?}", limit_bytes);
+    let writer = index_writer(&app, &state)?;
+    writer
+        .write(move |index| quota::set_limit(index, limit_bytes))
+        .await??;
+    Ok(quota::status(&open_index_with_state(&app, &state)?)?)
+}
+
+/// Part de l'espace utilisé (en %) au-delà de laquelle vider la corbeille est suggéré
+/// (`0` désactive la suggestion, `None` rétablit la valeur par défaut)
+#[tauri::command]
+async fn quota_set_trash_threshold(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    percent: Option<u8>,
+) -> Result<QuotaStatus, CommandError> {
+    log::info!("quota_set_trash_threshold called: percent={:?}", percent);
+    let writer = index_writer(&app, &state)?;
+    writer
+        .write(move |index| quota::set_trash_suggest_percent(index, percent))
+        .await??;
+    Ok(quota::status(&open_index_with_state(&app, &state)?)?)
+}
+
+/// Occupation du cache local et politique en vigueur (sans passe de maintenance).
+#[tauri::command]
+fn cache_status(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<CacheReport, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    let entries = open_blob_cache(&app)?
+        .entries()
+        .map_err(|e| CommandError::io("Failed to list local cache", e))?;
+    Ok(CacheReport {
+        policy: cache::policy::load(&index)?,
+        cached_files: entries.len(),
+        cached_bytes: entries.iter().map(|(_, size)| size).sum(),
+        ..CacheReport::default()
+    })
+}
+
+/// Enregistre la politique du cache puis l'applique immédiatement.
+#[tauri::command]
+async fn cache_set_policy(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    policy: CachePolicy,
+) -> Result<CacheReport, CommandError> {
+    log::info!("cache_set_policy called: max_bytes={}, prewarm_count={}", policy.max_bytes, policy.prewarm_count);
+    index_writer(&app, &state)?
+        .write(move |index| cache::policy::save(index, &policy))
+        .await??;
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(vault.maintain_cache().await?)
+}
+
+/// Vide le cache local (les envois en attente sont conservés) ; retourne le nombre de blobs retirés.
+#[tauri::command]
+async fn cache_clear(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<usize, CommandError> {
+    log::info!("cache_clear called");
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(vault.clear_cache()?)
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexBackupStatus {
+    pub policy: BackupPolicy,
+    pub state: BackupState,
+}
+
+/// Politique des sauvegardes de l'index et date de la dernière (sans accès réseau).
+#[tauri::command]
+fn index_backup_status(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<IndexBackupStatus, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(IndexBackupStatus {
+        policy: backup::load_policy(&index)?,
+        state: backup::load_state(&index)?,
+    })
+}
+
+#[tauri::command]
+async fn index_backup_set_policy(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    policy: BackupPolicy,
+) -> Result<(), CommandError> {
+    log::info!("index_backup_set_policy called: enabled={}, retention={}", policy.enabled, policy.retention);
+    let writer = index_writer(&app, &state)?;
+    Ok(writer.write(move |index| backup::save_policy(index, &policy)).await??)
+}
+
+/// Sauvegardes présentes dans le bucket, de la plus récente à la plus ancienne.
+#[tauri::command]
+async fn index_backup_list(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<IndexBackup>, CommandError> {
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(backup::list(&vault).await?)
+}
+
+#[tauri::command]
+async fn index_backup_now(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<BackupReport, CommandError> {
+    log::info!("index_backup_now called");
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(backup::run(&vault).await?)
+}
+
+/// Remplace l'index local par une sauvegarde ; retourne son nombre de fichiers.
+#[tauri::command]
+async fn index_backup_restore(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    key: String,
+) -> Result<usize, CommandError> {
+    log::info!("index_backup_restore called: key={}", key);
+    let vault = vault_from_state(&app, &state).await?;
+    let restored = backup::restore(&vault, &key).await?;
+    // Le compteur de modifications de la sauvegarde peut coïncider avec celui de l'index remplacé
+    index_cache(&app, &state)?.invalidate();
+    Ok(restored)
+}
+
+/// Destination d'un clonage. Les identifiants d'un autre bucket ne servent qu'à ce clonage
+/// et ne sont pas enregistrés.
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum CloneTargetRequest {
+    Directory { path: String },
+    Bucket { config: StorjConfigRequest },
+}
+
+/// Copie chaque objet chiffré et l'index vers un second emplacement, puis vérifie que le
+/// clone restaurerait chaque fichier (exercice de reprise après sinistre).
+///
+/// L'avancement est émis en `jobProgress` ; `job_cancel` interrompt le clonage et retire
+/// ce qui avait été copié.
+#[tauri::command]
+async fn vault_clone(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    target: CloneTargetRequest,
+) -> Result<CloneReport, CommandError> {
+    telemetry::record_feature("vault_clone");
+    let vault = vault_from_state(&app, &state).await?;
+    let target = match target {
+        CloneTargetRequest::Directory { path } => CloneTarget::Directory(PathBuf::from(path)),
+        CloneTargetRequest::Bucket { config } => {
+            let client = StorjClient::new(StorjConfig::new(
+                config.access_key_id,
+                config.secret_access_key,
+                config.endpoint,
+                config.bucket_name,
+            ))
+            .await
+            .map_err(|e| CommandError::remote("Failed to create Storj client", e))?
+            .with_prefix(storj::vault_prefix(&vault.master_key().fingerprint()));
+            CloneTarget::Bucket(Arc::new(client))
+        }
+    };
+    let label = target.describe();
+    log::info!("vault_clone called: target={}", label);
+
+    let jobs = app.state::<JobRegistry>();
+    let (job_id, token) = jobs.start();
+    let job = Job::new(job_id, CLONE_STEPS, token, |progress| {
+        events::emit(
+            &app,
+            AppEvent::JobProgress {
+                job_id: progress.job_id,
+                kind: "clone".to_string(),
+                label: label.clone(),
+                step: progress.step.to_string(),
+                step_index: progress.step_index,
+                step_count: progress.step_count,
+                state: progress.state,
+            },
+        )
+    });
+    let result = clone::clone_vault(&vault, &target, &job).await;
+    drop(job);
+    jobs.finish(job_id);
+    Ok(result?)
+}
+
+/// Dernier clonage du coffre et son résultat (sans accès réseau).
+#[tauri::command]
+fn vault_clone_last_report(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Option<CloneReport>, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(clone::last_report(&index)?)
+}
+
+#[tauri::command]
+fn rules_list(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<Rule>, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(rules::list(&index)?)
+}
+
+/// Crée (`id` absent) ou modifie une règle d'automatisation.
+#[tauri::command]
+async fn rules_save(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: Option<i64>,
+    name: String,
+    enabled: bool,
+    action: RuleAction,
+) -> Result<Rule, CommandError> {
+    log::info!("rules_save called: id={:?}, enabled={}", id, enabled);
+    let writer = index_writer(&app, &state)?;
+    Ok(writer.write(move |index| rules::save(index, id, &name, enabled, action)).await??)
+}
+
+#[tauri::command]
+async fn rules_delete(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<(), CommandError> {
+    log::info!("rules_delete called: id={}", id);
+    let writer = index_writer(&app, &state)?;
+    Ok(writer.write(move |index| rules::delete(index, id)).await??)
+}
+
+#[tauri::command]
+fn hooks_list(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<Hook>, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(hooks::list(&index)?)
+}
+
+/// Crée (`id` absent) ou modifie un hook ; un secret de webhook vide est généré.
+#[tauri::command]
+async fn hooks_save(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: Option<i64>,
+    name: String,
+    enabled: bool,
+    events: Vec<HookEvent>,
+    target: HookTarget,
+) -> Result<Hook, CommandError> {
+    log::info!("hooks_save called: id={:?}, enabled={}", id, enabled);
+    let writer = index_writer(&app, &state)?;
+    Ok(writer.write(move |index| hooks::save(index, id, &name, enabled, events, target)).await??)
+}
+
+#[tauri::command]
+async fn hooks_delete(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<(), CommandError> {
+    log::info!("hooks_delete called: id={}", id);
+    let writer = index_writer(&app, &state)?;
+    Ok(writer.write(move |index| hooks::delete(index, id)).await??)
+}
+
+/// Exécute immédiatement les règles actives, sans attendre le planificateur.
+#[tauri::command]
+async fn rules_run_now(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<RulesReport, CommandError> {
+    log::info!("rules_run_now called");
+    run_rules(&app, &state).await
+}
+
+/// Nombre d'opérations distantes en attente (créées hors ligne).
+#[tauri::command]
+fn offline_pending_count(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<usize, CommandError> {
+    let index = open_index_with_state(&app, &state)?;
+    Ok(index.list_pending()?.len())
 }
 
+/// Rejoue la file hors ligne (envois puis suppressions, dans l'ordre de création).
+///
+/// S'arrête sans erreur si le stockage est toujours injoignable (ou la synchronisation
+/// suspendue) : `remaining` l'indique.
 #[tauri::command]
-async fn storj_download_file_by_path(
+async fn offline_flush_queue(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-    logical_path: String,
-) -> Result<Vec<u8>, String> {
-    log::info!("storj_download_file_by_path called: logical_path={}", logical_path);
-    
-    // Cherche le fichier dans l'index local par chemin logique
-    let file_id = {
-        let index = open_index_with_state(&app, &state)
-            .map_err(|e| format!("Failed to open index: {}", e))?;
-        
-        // Liste tous les fichiers et trouve celui avec le chemin logique correspondant
-        let entries = index.list_all()
-            .map_err(|e| format!("Failed to list files from index: {}", e))?;
-        
-        let (file_id, _metadata) = entries
-            .into_iter()
-            .find(|(_, meta)| meta.logical_path == logical_path)
-            .ok_or_else(|| format!("File not found in local index: {}", logical_path))?;
-        
-        log::info!("Found file in index: file_id={}, logical_path={}", file_id, logical_path);
-        file_id
-    };
-    
-    // Convertit le file_id (UUID hex) en bytes pour le download Storj
-    let file_uuid = hex::decode(&file_id)
-        .map_err(|e| format!("Invalid UUID format in index: {}", e))?;
-    
-    if file_uuid.len() != 16 {
-        return Err(format!("Invalid UUID length in index: expected 16 bytes, got {}", file_uuid.len()));
+) -> Result<FlushReport, CommandError> {
+    if state.sync_paused.load(Ordering::SeqCst) {
+        let remaining = open_index_with_state(&app, &state)?.list_pending()?.len();
+        return Ok(FlushReport { completed: 0, remaining });
     }
-    
-    // Télécharge depuis Storj en utilisant l'UUID
-    let uuid_array: [u8; 16] = file_uuid.try_into()
-        .map_err(|_| "Failed to convert UUID to array".to_string())?;
-    
-    // Appelle directement le client Storj
-    let client = {
-        let client_guard = state.storj_client.lock().await;
-        client_guard.clone()
-            .ok_or_else(|| "Storj client not configured. Call storj_configure first.".to_string())?
-    };
-    
-    let uuid_hex = hex::encode(&uuid_array);
-    let object_key = format!("{}", uuid_hex);
-    
-    let data = client.download_file(&object_key)
-        .await
-        .map_err(|e| format!("Failed to download file from Storj: {}", e))?;
-    
-    log::info!("File downloaded successfully from Storj via index lookup: logical_path={}", logical_path);
-    Ok(data)
+    let vault = vault_from_state(&app, &state).await?;
+    Ok(vault.flush_pending().await?)
 }
 
-/// Télécharge et déchiffre un fichier pour l'aperçu (retourne les données déchiffrées en mémoire)
+/// Ouvre le journal de plantages chiffré de l'appareil.
+fn open_crash_log(app: &tauri::AppHandle) -> Result<CrashLog, CommandError> {
+    let dir = get_db_path(app)?.with_file_name(crash::CRASH_DIR);
+    Ok(CrashLog::open(dir)?)
+}
+
+/// Nombre de rapports de plantage enregistrés (l'interface propose alors de les exporter).
 #[tauri::command]
-async fn preview_file(
+fn crash_reports_count(app: tauri::AppHandle) -> Result<usize, CommandError> {
+    open_crash_log(&app)?
+        .count()
+        .map_err(|e| CommandError::io("Failed to list crash reports", e))
+}
+
+/// Exporte les rapports de plantage (déjà expurgés) en clair pour un rapport de bug.
+#[tauri::command]
+fn crash_reports_export(app: tauri::AppHandle, destination_path: String) -> Result<usize, CommandError> {
+    open_crash_log(&app)?
+        .export(&PathBuf::from(destination_path))
+        .map_err(|e| CommandError::io("Failed to export crash reports", e))
+}
+
+#[tauri::command]
+fn crash_reports_clear(app: tauri::AppHandle) -> Result<(), CommandError> {
+    open_crash_log(&app)?
+        .clear()
+        .map_err(|e| CommandError::io("Failed to clear crash reports", e))
+}
+
+/// Réglages de journalisation : masquage, niveau général et niveaux par module.
+#[tauri::command]
+fn logging_get_settings() -> Result<logging::LogSettings, CommandError> {
+    Ok(logging::settings()?)
+}
+
+/// Applique de nouveaux réglages de journalisation, effectifs immédiatement.
+#[tauri::command]
+fn logging_set_settings(settings: logging::LogSettings) -> Result<(), CommandError> {
+    logging::set_settings(settings.clone())?;
+    log::info!(
+        "Log settings updated: privacy={:?}, level={:?}, module_overrides={}",
+        settings.privacy,
+        settings.level,
+        settings.modules.len()
+    );
+    Ok(())
+}
+
+/// Messages du journal chiffré retenus par `query`, du plus récent au plus ancien, par pages.
+#[tauri::command]
+fn logs_query(query: logstore::LogQuery, page: Option<PageRequest>) -> Result<Page<logstore::LogRecord>, CommandError> {
+    let records = logging::store()?
+        .query(&query)
+        .map_err(|e| CommandError::io("Failed to read the log store", e))?;
+    Ok(paging::paginate(records, &page.unwrap_or_default())?)
+}
+
+/// Exporte un paquet de diagnostic (messages expurgés et rapports de plantage) à joindre à
+/// un rapport de bug. Retourne le nombre de messages exportés.
+#[tauri::command]
+fn logs_export_bundle(
     app: tauri::AppHandle,
-    state: State<'_, AppState>,
-    file_id: String,
-) -> Result<Vec<u8>, String> {
-    log::info!("preview_file called: file_id={}", file_id);
-    
-    // Récupère les métadonnées du fichier depuis l'index local
-    let (logical_path, file_uuid_bytes) = {
-        let index = open_index_with_state(&app, &state)?;
-        let metadata = index.get(&file_id)
-            .map_err(|e| format!("Failed to get file metadata: {}", e))?
-            .ok_or_else(|| format!("File not found in index: {}", file_id))?;
-        
-        // Convertit le file_id (UUID hex) en bytes pour le download Storj
-        let file_uuid = hex::decode(&file_id)
-            .map_err(|e| format!("Invalid UUID format: {}", e))?;
-        
-        if file_uuid.len() != 16 {
-            return Err(format!("Invalid UUID length: expected 16 bytes, got {}", file_uuid.len()));
-        }
-        
-        (metadata.logical_path, file_uuid)
-    };
-    
-    // Télécharge le fichier chiffré depuis Storj
-    let client = {
-        let client_guard = state.storj_client.lock().await;
-        client_guard.clone()
-            .ok_or_else(|| "Storj client not configured. Call storj_configure first.".to_string())?
-    };
-    
-    let uuid_hex = hex::encode(&file_uuid_bytes);
-    let object_key = format!("{}", uuid_hex);
-    
-    let encrypted_data = client.download_file(&object_key)
-        .await
-        .map_err(|e| format!("Failed to download file from Storj: {}", e))?;
-    
-    log::info!("File downloaded from Storj for preview: size={}", encrypted_data.len());
-    
-    // Déchiffre le fichier
-    let plaintext = storage_decrypt_file(state.clone(), encrypted_data, logical_path)
-        .map_err(|e| format!("Failed to decrypt file for preview: {}", e))?;
-    
-    log::info!("File decrypted successfully for preview: size={}", plaintext.len());
-    Ok(plaintext)
+    query: logstore::LogQuery,
+    destination_path: String,
+) -> Result<usize, CommandError> {
+    let crash_reports = open_crash_log(&app)?
+        .read_all()
+        .map_err(|e| CommandError::io("Failed to read crash reports", e))?;
+    let count = logging::store()?
+        .export_bundle(&PathBuf::from(destination_path), &query, crash_reports)
+        .map_err(|e| CommandError::io("Failed to export the support bundle", e))?;
+    log::info!("Support bundle exported: {} log record(s)", count);
+    Ok(count)
 }
 
-/// Liste tous les fichiers dans la corbeille
 #[tauri::command]
-fn list_trash(
+fn logs_clear() -> Result<(), CommandError> {
+    logging::store()?
+        .clear()
+        .map_err(|e| CommandError::io("Failed to clear the log store", e))
+}
+
+/// Compteurs de télémétrie tels qu'ils seraient partagés (relecture par l'utilisateur).
+#[tauri::command]
+fn telemetry_get() -> Result<telemetry::Metrics, CommandError> {
+    Ok(telemetry::get().map(|t| t.snapshot()).unwrap_or_default())
+}
+
+/// Active ou désactive la télémétrie (opt-in ; désactiver efface les compteurs).
+#[tauri::command]
+fn telemetry_set_enabled(enabled: bool) -> Result<(), CommandError> {
+    let telemetry = telemetry::get().ok_or_else(|| CommandError::Internal("Telemetry not initialized".to_string()))?;
+    telemetry.set_enabled(enabled);
+    log::info!("Telemetry {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+#[tauri::command]
+fn telemetry_reset() -> Result<(), CommandError> {
+    if let Some(telemetry) = telemetry::get() {
+        telemetry.reset();
+    }
+    Ok(())
+}
+
+/// Exporte les compteurs dans un fichier JSON choisi par l'utilisateur.
+///
+/// Aucun envoi n'est fait par l'application : c'est à l'utilisateur de partager ce fichier.
+#[tauri::command]
+fn telemetry_export(destination_path: String) -> Result<(), CommandError> {
+    let metrics = telemetry::get().map(|t| t.snapshot()).unwrap_or_default();
+    let raw = serde_json::to_vec_pretty(&metrics)
+        .map_err(|e| CommandError::internal("Failed to serialize telemetry", e))?;
+    fs::write(&destination_path, raw).map_err(|e| CommandError::io("Failed to write telemetry export", e))?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct PendingShare {
+    pub name: String,
+}
+
+/// Destination d'un fichier reçu par lien de partage.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ShareTarget {
+    /// Importé (re-chiffré avec la MasterKey) dans ce dossier du coffre.
+    Vault { folder: String },
+    /// Enregistré en clair à l'emplacement choisi dans le dialogue natif.
+    Disk,
+}
+
+/// Lien de partage en attente (ouvert avant que le frontend n'écoute les événements).
+#[tauri::command]
+fn share_pending(state: State<'_, AppState>) -> Result<Option<PendingShare>, CommandError> {
+    let pending = state
+        .pending_share
+        .lock()
+        .map_err(|e| CommandError::internal("Lock error", e))?;
+    Ok(pending.as_ref().map(|link| PendingShare { name: link.name.clone() }))
+}
+
+/// Reçoit le fichier du lien en attente : téléchargé, vérifié puis déchiffré vers `target`.
+///
+/// Renvoie le chemin logique (coffre) ou le chemin sur le disque. En cas d'échec, le lien
+/// reste en attente pour pouvoir réessayer.
+#[tauri::command]
+async fn share_receive(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<Vec<TrashEntry>, String> {
-    log::info!("list_trash called");
-    
-    let index = open_index_with_state(&app, &state)?;
-    let trash_items = index.list_trash()
-        .map_err(|e| format!("Failed to list trash: {}", e))?;
-    
-    let entries: Vec<TrashEntry> = trash_items.into_iter().map(|(id, meta, deleted_at)| {
-        TrashEntry {
-            id,
-            logical_path: meta.logical_path,
-            encrypted_size: meta.encrypted_size,
-            deleted_at,
+    target: ShareTarget,
+) -> Result<String, CommandError> {
+    let link = state
+        .pending_share
+        .lock()
+        .map_err(|e| CommandError::internal("Lock error", e))?
+        .take()
+        .ok_or(CommandError::InvalidShareLink { reason: "no_pending_link" })?;
+    log::info!("share_receive called: name={}, target={:?}", link.name, target);
+
+    let result = receive_share(&app, &state, &link, target).await;
+    if result.is_err() {
+        if let Ok(mut pending) = state.pending_share.lock() {
+            pending.get_or_insert(link);
         }
-    }).collect();
-    
-    log::info!("Found {} items in trash", entries.len());
-    Ok(entries)
+    }
+    result
 }
 
-/// Restaure un fichier depuis la corbeille vers l'index principal
+async fn receive_share(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    link: &ShareLink,
+    target: ShareTarget,
+) -> Result<String, CommandError> {
+    match target {
+        ShareTarget::Vault { folder } => {
+            let folder = normalize_path(&folder);
+            let logical_path = if folder == "/" {
+                format!("/{}", link.name)
+            } else {
+                format!("{}/{}", folder, link.name)
+            };
+            let vault = vault_from_state(app, state).await?;
+            if vault.list()?.iter().any(|(_, meta)| meta.logical_path == logical_path) {
+                return Err(CommandError::AlreadyExists { path: logical_path });
+            }
+            let plaintext = link.receive().await?;
+            let file_id = vault.put(&logical_path, &plaintext).await?;
+            log::info!("Shared file imported: file_id={}, logical_path={}", file_id, logical_path);
+            tray::push_recent(app, &logical_path);
+            Ok(logical_path)
+        }
+        ShareTarget::Disk => {
+            let path = picker::pick_save(app, "Enregistrer le fichier partagé", &link.name).await?;
+            let plaintext = link.receive().await?;
+            picker::write(app, &path, &plaintext).map_err(|e| CommandError::io("Failed to write shared file", e))?;
+            log::info!("Shared file saved to disk: {}", path);
+            Ok(path.to_string())
+        }
+    }
+}
+
+/// Oublie le lien de partage en attente.
 #[tauri::command]
-fn restore_from_trash(
-    app: tauri::AppHandle,
-    state: State<'_, AppState>,
-    file_id: String,
-) -> Result<String, String> {
-    log::info!("restore_from_trash called: file_id={}", file_id);
-    
-    let mut index = open_index_with_state(&app, &state)?;
-    let metadata = index.restore_from_trash(&file_id)
-        .map_err(|e| format!("Failed to restore file from trash: {}", e))?;
-    
-    log::info!("File restored from trash: file_id={}, logical_path={}", file_id, metadata.logical_path);
-    Ok(metadata.logical_path)
+fn share_dismiss(state: State<'_, AppState>) -> Result<(), CommandError> {
+    state
+        .pending_share
+        .lock()
+        .map_err(|e| CommandError::internal("Lock error", e))?
+        .take();
+    Ok(())
 }
 
-/// Supprime définitivement un fichier de la corbeille (supprime aussi de Storj)
+/// Demande à l'utilisateur d'autoriser une commande sensible.
+///
+/// Les commandes destructives exigent une nouvelle saisie du mot de passe (`password`),
+/// vérifiée ici ; les autres, une boîte de dialogue native. L'autorisation vaut pour une
+/// seule exécution de `command`, pendant [`permissions::GRANT_TTL`].
 #[tauri::command]
-async fn permanently_delete_from_trash(
+async fn request_elevation(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-    file_id: String,
-) -> Result<(), String> {
-    log::info!("permanently_delete_from_trash called: file_id={}", file_id);
-    
-    // Convertit le file_id en UUID bytes
-    let file_uuid = hex::decode(&file_id)
-        .map_err(|e| format!("Invalid UUID format: {}", e))?;
-    
-    if file_uuid.len() != 16 {
-        return Err(format!("Invalid UUID length: expected 16 bytes, got {}", file_uuid.len()));
+    command: String,
+    password: Option<String>,
+) -> Result<(), CommandError> {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+    use tokio::sync::oneshot;
+
+    if permissions::capability_of(&command).is_none() {
+        return Err(CommandError::CommandNotAllowed { command });
     }
-    
-    let uuid_array: [u8; 16] = file_uuid.try_into()
-        .map_err(|_| "Failed to convert UUID to array".to_string())?;
-    
-    // Supprime de Storj
-    let client = {
-        let client_guard = state.storj_client.lock().await;
-        client_guard.clone()
-            .ok_or_else(|| "Storj client not configured. Call storj_configure first.".to_string())?
-    };
-    
-    let uuid_hex = hex::encode(&uuid_array);
-    let object_key = format!("{}", uuid_hex);
-    
-    client.delete_file(&object_key)
+    if !permissions::requires_elevation(&command) {
+        return Ok(());
+    }
+    log::info!("request_elevation called: command={}", command);
+
+    if permissions::requires_password(&command) || state.elevations.requires_reauthentication(&command) {
+        let password = password.ok_or_else(|| CommandError::PasswordRequired { command: command.clone() })?;
+        let master_key = get_master_key_from_state(state.clone()).ok();
+        if let Err(e) = state.elevations.verify_password(password, master_key.as_ref()) {
+            log::warn!("Elevation password check failed: command={}", command);
+            return Err(e);
+        }
+        state.elevations.grant(&command);
+        return Ok(());
+    }
+
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .message(permissions::describe(&command))
+        .title("Confirmer l'opération")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom("Confirmer".to_string(), "Annuler".to_string()))
+        .show(move |confirmed| {
+            let _ = tx.send(confirmed);
+        });
+
+    let confirmed = tokio::time::timeout(std::time::Duration::from_secs(120), rx)
         .await
-        .map_err(|e| format!("Failed to delete file from Storj: {}", e))?;
-    
-    log::info!("File deleted from Storj: object_key={}", object_key);
-    
-    // Supprime de la corbeille
-    let mut index = open_index_with_state(&app, &state)?;
-    index.remove_from_trash(&file_id)
-        .map_err(|e| format!("Failed to remove file from trash: {}", e))?;
-    
-    log::info!("File permanently deleted from trash: file_id={}", file_id);
+        .map_err(|_| CommandError::DialogTimeout)?
+        .map_err(|e| CommandError::internal("Dialog channel closed", e))?;
+    if !confirmed {
+        log::info!("Elevation denied: command={}", command);
+        return Err(CommandError::ElevationDenied);
+    }
+    state.elevations.grant(&command);
     Ok(())
 }
 
-/// Vide complètement la corbeille (supprime définitivement tous les fichiers de Storj et de la corbeille)
-#[tauri::command]
-async fn empty_trash(
-    app: tauri::AppHandle,
-    state: State<'_, AppState>,
-) -> Result<usize, String> {
-    log::info!("empty_trash called");
-    
-    // Liste tous les fichiers dans la corbeille
-    let index = open_index_with_state(&app, &state)?;
-    let trash_items = index.list_trash()
-        .map_err(|e| format!("Failed to list trash: {}", e))?;
-    
-    let count = trash_items.len();
-    log::info!("Found {} items in trash to delete permanently", count);
-    
-    // Supprime tous les fichiers de Storj
-    let client = {
-        let client_guard = state.storj_client.lock().await;
-        client_guard.clone()
-            .ok_or_else(|| "Storj client not configured. Call storj_configure first.".to_string())?
+fn show_main_window(app: &tauri::AppHandle) {
+    // Sur mobile, l'OS ramène lui-même l'application au premier plan
+    #[cfg(desktop)]
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    #[cfg(mobile)]
+    let _ = app;
+}
+
+/// Retient un lien de partage ouvert par le système et le signale au frontend.
+///
+/// Rien n'est téléchargé avant que l'utilisateur ne choisisse une destination.
+fn handle_deep_link(app: &tauri::AppHandle, url: &str) {
+    // L'URL porte la clé de partage : elle n'est jamais journalisée
+    let link = match ShareLink::parse(url) {
+        Ok(link) => link,
+        Err(e) => {
+            log::warn!("Ignoring deep link: {}", e);
+            return;
+        }
     };
-    
-    for (file_id, _, _) in &trash_items {
-        let file_uuid = hex::decode(file_id)
-            .map_err(|e| format!("Invalid UUID format: {}", e))?;
-        
-        if file_uuid.len() == 16 {
-            let uuid_array: [u8; 16] = file_uuid.try_into()
-                .map_err(|_| "Failed to convert UUID to array".to_string())?;
-            let uuid_hex = hex::encode(&uuid_array);
-            let object_key = format!("{}", uuid_hex);
-            
-            // Supprime de Storj (ignore les erreurs pour continuer avec les autres fichiers)
-            if let Err(e) = client.delete_file(&object_key).await {
-                log::warn!("Failed to delete file {} from Storj: {}", file_id, e);
+    log::info!("Share link opened: name={}", link.name);
+    let name = link.name.clone();
+    if let Ok(mut pending) = app.state::<AppState>().pending_share.lock() {
+        *pending = Some(link);
+    }
+    show_main_window(app);
+    events::emit(app, AppEvent::ShareLinkOpened { name });
+}
+
+/// Enregistre le schéma `aether://` et traite les liens reçus (au lancement puis en cours d'exécution).
+fn register_deep_links(app: &tauri::AppHandle) {
+    use tauri_plugin_deep_link::DeepLinkExt;
+
+    // macOS enregistre le schéma à l'installation du bundle ; ailleurs, à l'exécution
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        log::warn!("Failed to register {}:// scheme: {}", share::SCHEME, e);
+    }
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        for url in urls {
+            handle_deep_link(app, url.as_str());
+        }
+    }
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            handle_deep_link(&handle, url.as_str());
+        }
+    });
+}
+
+/// Exécute une action du menu de la barre système.
+fn handle_tray_action(app: &tauri::AppHandle, action: tray::TrayAction) {
+    match action {
+        tray::TrayAction::ShowWindow => show_main_window(app),
+        tray::TrayAction::TogglePause => {
+            let state = app.state::<AppState>();
+            let paused = !state.sync_paused.fetch_xor(true, Ordering::SeqCst);
+            log::info!("Sync {} from tray", if paused { "paused" } else { "resumed" });
+            tray::set_paused(app, paused);
+            if paused {
+                return;
             }
+            // À la reprise, la file constituée pendant la pause part aussitôt
+            flush_in_background(app);
+        }
+        tray::TrayAction::LockVault => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = lock_vault(&app).await {
+                    log::warn!("Failed to lock vault from tray: {}", e);
+                }
+            });
         }
+        tray::TrayAction::Quit => app.exit(0),
     }
-    
-    // Vide la corbeille
-    let mut index = open_index_with_state(&app, &state)?;
-    let deleted_count = index.empty_trash()
-        .map_err(|e| format!("Failed to empty trash: {}", e))?;
-    
-    log::info!("Trash emptied: {} items permanently deleted", deleted_count);
-    Ok(deleted_count)
 }
 
-#[derive(Debug, Serialize)]
-pub struct TrashEntry {
-    pub id: String,
-    pub logical_path: String,
-    pub encrypted_size: u64,
-    pub deleted_at: i64, // Timestamp Unix en secondes
+/// Envoie la file hors ligne en tâche de fond (reprise de la synchronisation ou de l'application).
+fn flush_in_background(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        let result = async {
+            let vault = vault_from_state(&app, &state).await?;
+            Ok::<_, CommandError>(vault.flush_pending().await?)
+        }
+        .await;
+        match result {
+            Ok(report) => log::info!("Flushed after resume: completed={}, remaining={}", report.completed, report.remaining),
+            Err(e) => log::warn!("Flush after resume failed: {}", e),
+        }
+    });
+}
+
+/// Au retour au premier plan sur mobile, relance les transferts coupés par la suspension.
+///
+/// L'OS suspend l'application en arrière-plan : un envoi interrompu échoue comme hors
+/// ligne et attend dans la file (blob en cache, opération en attente dans l'index), qui
+/// survit même si le processus est tué entre-temps.
+#[cfg(mobile)]
+fn resume_transfers(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let unlocked = state.master_key.lock().map(|key| key.is_some()).unwrap_or(false);
+    if unlocked && !state.sync_paused.load(Ordering::SeqCst) {
+        flush_in_background(app);
+    }
+}
+
+/// Applique le modèle de permissions avant de transmettre l'appel aux commandes.
+fn permission_gate<R: tauri::Runtime>(
+    handler: impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let command = invoke.message.command().to_string();
+        // Un essai à blanc ne modifie rien : il prépare la confirmation, avant toute élévation
+        let dry_run = matches!(
+            invoke.message.payload(),
+            tauri::ipc::InvokeBody::Json(args) if args.get("dryRun") == Some(&serde_json::Value::Bool(true))
+        );
+        let authorized = invoke.message.webview().state::<AppState>().elevations.authorize_call(&command, dry_run);
+        if let Err(e) = authorized {
+            log::warn!("Command rejected by permission gate: {}", e);
+            invoke.resolver.reject(e);
+            return true;
+        }
+        handler(invoke)
+    }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+    // Une seconde instance (lancée par un lien `aether://`) transmet l'URL à la première puis se ferme
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| show_main_window(app)));
+    builder
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_log::Builder::default().build())
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(AppState {
             master_key: Mutex::new(None),
             storj_client: AsyncMutex::new(None),
+            delegated_credentials: AsyncMutex::new(None),
+            webdav: AsyncMutex::new(None),
+            recovery_pending: AtomicBool::new(false),
+            elevations: Elevations::default(),
+            sync_paused: AtomicBool::new(false),
+            pending_share: Mutex::new(None),
+            guest_keys: Mutex::new(None),
+            index_writer: Mutex::new(None),
+            index_cache: Mutex::new(None),
+            pending_settings: Mutex::new(None),
+            transfers: Arc::new(TransferQueue::default()),
+        })
+        .manage(PreviewSessions::default())
+        .manage(JobRegistry::default())
+        // Aperçus en flux : seul le jeton de la session courante est servi
+        .register_uri_scheme_protocol(preview::PROTOCOL, |ctx, request| {
+            ctx.app_handle().state::<PreviewSessions>().respond(&request)
         })
-        .invoke_handler(tauri::generate_handler![
+        // Avec une icône dans la barre système, fermer la fenêtre la masque : les transferts continuent
+        .on_window_event(|window, event| {
+            #[cfg(desktop)]
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                if window.app_handle().tray_by_id(tray::TRAY_ID).is_some() {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
+            #[cfg(mobile)]
+            let _ = (window, event);
+        })
+        .invoke_handler(permission_gate(tauri::generate_handler![
             crypto_bootstrap,
+            setup_wizard_state,
+            setup_wizard_create_vault,
+            setup_wizard_configure_backend,
+            setup_wizard_test_connection,
+            setup_wizard_recovery_kit,
+            setup_wizard_import_folder,
             crypto_unlock,
+            crypto_unlock_device,
+            device_unlock_status,
+            session_freshness,
+            reauth_set_window,
+            device_unlock_forget,
+            crypto_lock,
             crypto_change_password,
+            keyfile_required,
+            keyfile_select,
+            keyfile_enroll,
+            keyfile_remove,
+            vault_bundle_export,
+            vault_bundle_import,
             get_index_db_path,
             reset_local_database,
+            index_open_with_vault,
+            index_archive_and_recreate,
             get_index_status,
             index_add_file,
             index_list_files,
+            list_by_category,
+            photos_timeline,
             list_files_and_folders,
+            list_directory_stream,
+            get_folder_appearance,
+            set_folder_appearance,
+            folder_policy_get,
+            folder_policy_set,
             create_folder,
             index_remove_file,
             index_get_file,
             index_verify_integrity,
+            integrity_last_report,
+            summary_last_sync,
+            summary_last_scrub,
+            integrity_timeline,
+            vault_health,
+            activity_summary,
+            activity_acknowledge,
+            run_diagnostics,
+            integrity_check_now,
+            quarantine_list,
+            quarantine_recheck,
+            quarantine_restore_version,
+            quarantine_restore_local_copy,
+            quarantine_discard,
+            locks_list,
+            sessions_status,
+            transfers_status,
+            lock_acquire,
+            lock_release,
+            lock_break,
             storage_encrypt_file,
+            storage_encrypt_file_from_path,
             storage_decrypt_file,
             storage_get_file_info,
             storj_configure,
+            storj_save_credentials,
+            storj_load_credentials,
+            storj_delete_credentials,
+            storj_credentials_status,
+            storj_enroll_delegation,
+            storj_rotate_credentials,
+            workspace_list,
+            workspace_create,
+            workspace_rename,
+            workspace_switch,
+            workspace_delete,
             storj_upload_file,
+            storj_upload_from_path,
+            upload_local_file,
+            upload_preflight,
             storj_download_file,
             storj_download_file_by_path,
+            storj_download_to_path,
             storj_list_files,
             storj_delete_file,
             rename_file,
+            job_cancel,
+            file_versions,
+            file_version_restore,
+            rename_folder,
+            alias_create,
+            alias_resolve,
+            alias_delete,
+            snapshot_create,
+            snapshot_list,
+            snapshot_diff,
+            snapshot_restore,
+            snapshot_delete,
             list_trash,
             restore_from_trash,
+            trash_folder,
+            list_trashed_folders,
+            restore_folder_from_trash,
             permanently_delete_from_trash,
             empty_trash,
+            export_vault,
+            export_archive,
+            export_encrypted_selection,
+            export_report,
+            webdav_start,
+            webdav_stop,
+            get_password_hint,
+            set_password_hint,
+            guest_mode_status,
+            guest_mode_enable,
+            guest_mode_disable,
+            guest_mode_open,
+            guest_mode_close,
+            guest_mode_search,
+            content_search_status,
+            content_search_set_enabled,
+            remote_keys_status,
+            remote_keys_set_enabled,
+            remote_keys_audit,
+            remote_keys_repair,
+            search_files,
+            quota_status,
+            quota_set_limit,
+            quota_set_trash_threshold,
+            cache_status,
+            cache_set_policy,
+            cache_clear,
+            index_backup_status,
+            index_backup_set_policy,
+            index_backup_list,
+            index_backup_now,
+            index_backup_restore,
+            vault_clone,
+            vault_clone_last_report,
+            rules_list,
+            rules_save,
+            rules_delete,
+            hooks_list,
+            hooks_save,
+            hooks_delete,
+            rules_run_now,
+            offline_pending_count,
+            offline_flush_queue,
+            logging_get_settings,
+            logging_set_settings,
+            telemetry_get,
+            telemetry_set_enabled,
+            telemetry_reset,
+            telemetry_export,
+            crash_reports_count,
+            crash_reports_export,
+            crash_reports_clear,
+            logs_query,
+            logs_export_bundle,
+            logs_clear,
+            get_encryption_details,
+            verify_download,
             preview_file,
+            preview_open,
+            preview_close,
             select_and_read_file,
+            select_files_for_upload,
             select_and_read_file_from_path,
-            save_decrypted_file
-        ])
-        .setup(|_app| {
+            save_decrypted_file,
+            download_to_file,
+            share_pending,
+            share_receive,
+            share_dismiss,
+            request_elevation
+        ]))
+        .setup(|app| {
+            // Avant tout le reste, pour que rien ne soit journalisé sans masquage
+            if let Err(e) = logging::install(app.handle()) {
+                eprintln!("Logging unavailable: {}", e);
+            }
+            if let Ok(app_data) = app.path().app_data_dir() {
+                let _ = fs::create_dir_all(&app_data);
+                if let Err(e) = keychain::install_platform_store(&app_data) {
+                    log::warn!("Platform key storage unavailable: {}", e);
+                }
+                match WorkspaceRegistry::load(&app_data) {
+                    Ok(registry) => {
+                        app.manage(Workspaces(Mutex::new(registry)));
+                    }
+                    Err(e) => log::warn!("Workspace registry unavailable, using the default workspace: {}", e),
+                }
+                telemetry::init(app_data.join("telemetry.json"));
+                match CrashLog::open(app_data.join(crash::CRASH_DIR)) {
+                    Ok(crash_log) => crash_log.install_panic_hook(),
+                    Err(e) => log::warn!("Crash reports disabled: {}", e),
+                }
+            }
+            if let Err(e) = tray::init(app.handle(), handle_tray_action) {
+                log::warn!("System tray unavailable: {}", e);
+            }
+            register_deep_links(app.handle());
+            tauri::async_runtime::spawn(integrity_scheduler(app.handle().clone()));
+            tauri::async_runtime::spawn(cache_scheduler(app.handle().clone()));
+            tauri::async_runtime::spawn(rules_scheduler(app.handle().clone()));
+            tauri::async_runtime::spawn(backup_scheduler(app.handle().clone()));
+            tauri::async_runtime::spawn(credentials_scheduler(app.handle().clone()));
+            tauri::async_runtime::spawn(session_scheduler(app.handle().clone()));
+            tauri::async_runtime::spawn(activity_scheduler(app.handle().clone()));
             // Les plugins sont initialisés via .plugin() dans le Builder
             // Note: Le drag & drop HTML5 ne fonctionne pas dans Tauri car Tauri intercepte les événements natifs
             // Pour l'instant, on utilise uniquement le sélecteur de fichier
             // Le drag & drop sera implémenté dans une future version quand l'API Tauri sera disponible
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            #[cfg(mobile)]
+            if let tauri::RunEvent::Resumed = event {
+                resume_transfers(app);
+            }
+            #[cfg(desktop)]
+            let _ = (app, event);
+        });
 }
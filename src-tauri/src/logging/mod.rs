@@ -0,0 +1,404 @@
+//! Journalisation de l'application.
+//!
+//! Enveloppe le logger de `tauri-plugin-log` (console + fichiers à rotation) : niveaux par
+//! module réglables à chaud, masquage des chemins et des identifiants tant que l'utilisateur
+//! n'a pas choisi le mode diagnostic, et limitation des messages répétés d'un même endroit.
+//! Les messages admis sont aussi conservés chiffrés dans le [`LogStore`], consultable dans
+//! l'application et exportable en paquet de diagnostic.
+
+use log::{LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind};
+
+use crate::crash;
+use crate::logstore::{self, LogLevel, LogRecord, LogStore};
+
+/// Nom des fichiers de journal (dans le dossier de logs de l'OS).
+pub const LOG_FILE_NAME: &str = "aether-drive";
+/// Taille à partir de laquelle le fichier courant est archivé.
+pub const MAX_FILE_BYTES: u128 = 5 * 1024 * 1024;
+/// Fichiers archivés conservés ; les plus anciens sont supprimés.
+pub const KEPT_FILES: usize = 5;
+/// Messages admis par emplacement (module + ligne) et par fenêtre ; au-delà ils sont comptés.
+pub const RATE_BURST: u32 = 20;
+pub const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Au-delà, les fenêtres expirées sont oubliées.
+const MAX_TRACKED_SITES: usize = 1024;
+
+/// Ce que les journaux peuvent contenir.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Privacy {
+    /// Chemins et identifiants masqués comme dans les rapports de plantage (par défaut).
+    #[default]
+    Redacted,
+    /// Tout est écrit tel quel, pour un diagnostic ponctuel.
+    Full,
+}
+
+/// Niveau de détail d'un module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verbosity {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Verbosity {
+    fn filter(self) -> LevelFilter {
+        match self {
+            Verbosity::Off => LevelFilter::Off,
+            Verbosity::Error => LevelFilter::Error,
+            Verbosity::Warn => LevelFilter::Warn,
+            Verbosity::Info => LevelFilter::Info,
+            Verbosity::Debug => LevelFilter::Debug,
+            Verbosity::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+/// Réglages persistés dans `logging.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogSettings {
+    pub privacy: Privacy,
+    pub level: Verbosity,
+    /// Préfixe de module (`aether_core::vault`) → niveau ; le préfixe le plus long l'emporte.
+    pub modules: BTreeMap<String, Verbosity>,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            privacy: Privacy::Redacted,
+            level: Verbosity::Info,
+            modules: BTreeMap::new(),
+        }
+    }
+}
+
+impl LogSettings {
+    /// Refuse un nom de module qui ne peut désigner aucun module Rust.
+    pub fn validate(&self) -> Result<(), LoggingError> {
+        let valid = |module: &str| {
+            !module.is_empty()
+                && module.split("::").all(|part| {
+                    !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                })
+        };
+        if self.modules.keys().all(|module| valid(module)) {
+            Ok(())
+        } else {
+            Err(LoggingError::InvalidModule)
+        }
+    }
+
+    /// Niveau retenu pour la cible d'un message.
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .filter(|(module, _)| {
+                target == module.as_str()
+                    || target.strip_prefix(module.as_str()).is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.level, |(_, verbosity)| *verbosity)
+            .filter()
+    }
+
+    /// Niveau le plus détaillé demandé, pour que `log` écarte le reste sans nous consulter.
+    fn max_level(&self) -> LevelFilter {
+        self.modules.values().map(|verbosity| verbosity.filter()).fold(self.level.filter(), Ord::max)
+    }
+}
+
+#[derive(Debug)]
+pub enum LoggingError {
+    /// Nom de module vide ou mal formé.
+    InvalidModule,
+    NotInstalled,
+    /// Le journal chiffré n'a pas pu être ouvert (trousseau indisponible).
+    StoreUnavailable,
+    Io(String),
+}
+
+impl fmt::Display for LoggingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoggingError::InvalidModule => write!(f, "Invalid module name in log settings"),
+            LoggingError::NotInstalled => write!(f, "Logger not installed"),
+            LoggingError::StoreUnavailable => write!(f, "Encrypted log store unavailable"),
+            LoggingError::Io(e) => write!(f, "Failed to persist log settings: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LoggingError {}
+
+/// Fenêtre de comptage d'un emplacement.
+struct Window {
+    started: Instant,
+    admitted: u32,
+    suppressed: u32,
+}
+
+/// Limite les messages répétés d'un même emplacement à [`RATE_BURST`] par [`RATE_WINDOW`].
+#[derive(Default)]
+struct RateLimiter {
+    sites: Mutex<HashMap<(String, u32), Window>>,
+}
+
+impl RateLimiter {
+    /// `None` si le message doit être écarté ; sinon le nombre de messages écartés à cet
+    /// emplacement pendant la fenêtre précédente, à signaler avant celui-ci.
+    fn admit(&self, site: (&str, u32), now: Instant) -> Option<u32> {
+        let Ok(mut sites) = self.sites.lock() else {
+            return Some(0);
+        };
+        if sites.len() >= MAX_TRACKED_SITES {
+            sites.retain(|_, window| now.duration_since(window.started) < RATE_WINDOW);
+        }
+        let window = sites.entry((site.0.to_string(), site.1)).or_insert(Window {
+            started: now,
+            admitted: 0,
+            suppressed: 0,
+        });
+        let mut reported = 0;
+        if now.duration_since(window.started) >= RATE_WINDOW {
+            reported = window.suppressed;
+            *window = Window {
+                started: now,
+                admitted: 0,
+                suppressed: 0,
+            };
+        }
+        if window.admitted >= RATE_BURST {
+            window.suppressed += 1;
+            return None;
+        }
+        window.admitted += 1;
+        Some(reported)
+    }
+}
+
+/// Logger installé pour le processus, devant celui du plugin.
+pub struct AppLogger {
+    inner: Box<dyn Log>,
+    settings: RwLock<LogSettings>,
+    path: PathBuf,
+    limiter: RateLimiter,
+    store: Option<LogStore>,
+    /// Un échec d'écriture dans le journal chiffré n'est signalé qu'une fois.
+    store_failed: AtomicBool,
+}
+
+static LOGGER: OnceLock<&'static AppLogger> = OnceLock::new();
+
+impl AppLogger {
+    fn settings(&self) -> LogSettings {
+        self.settings.read().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Transmet au logger du plugin, avec le message réécrit, et l'ajoute au journal chiffré.
+    fn forward(&self, record: &Record, message: &str) {
+        if let Some(store) = &self.store {
+            let entry = LogRecord {
+                timestamp_ms: logstore::unix_millis(),
+                level: log_level(record.level()),
+                module: record.target().to_string(),
+                message: message.to_string(),
+            };
+            // Jamais via `log` : on est déjà dans le logger
+            if let Err(e) = store.append(&entry) {
+                if !self.store_failed.swap(true, Ordering::Relaxed) {
+                    eprintln!("Failed to write to the encrypted log store: {}", e);
+                }
+            }
+        }
+        self.inner.log(
+            &Record::builder()
+                .metadata(record.metadata().clone())
+                .args(format_args!("{}", message))
+                .module_path(record.module_path())
+                .file(record.file())
+                .line(record.line())
+                .build(),
+        );
+    }
+
+    fn apply(&self, settings: LogSettings) -> Result<(), LoggingError> {
+        settings.validate()?;
+        let raw = serde_json::to_vec_pretty(&settings).map_err(|e| LoggingError::Io(e.to_string()))?;
+        fs::write(&self.path, raw).map_err(|e| LoggingError::Io(e.to_string()))?;
+        log::set_max_level(settings.max_level());
+        if let Ok(mut current) = self.settings.write() {
+            *current = settings;
+        }
+        Ok(())
+    }
+}
+
+impl Log for AppLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.settings
+            .read()
+            .is_ok_and(|settings| metadata.level() <= settings.level_for(metadata.target()))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let site = (record.module_path().unwrap_or(record.target()), record.line().unwrap_or(0));
+        let Some(suppressed) = self.limiter.admit(site, Instant::now()) else {
+            return;
+        };
+        if suppressed > 0 {
+            self.forward(record, &format!("{} similar message(s) suppressed", suppressed));
+        }
+        let message = record.args().to_string();
+        match self.settings.read().map(|settings| settings.privacy).unwrap_or_default() {
+            Privacy::Redacted => self.forward(record, &crash::redact(&message)),
+            Privacy::Full => self.forward(record, &message),
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+fn log_level(level: log::Level) -> LogLevel {
+    match level {
+        log::Level::Error => LogLevel::Error,
+        log::Level::Warn => LogLevel::Warn,
+        log::Level::Info => LogLevel::Info,
+        log::Level::Debug => LogLevel::Debug,
+        log::Level::Trace => LogLevel::Trace,
+    }
+}
+
+fn load_settings(path: &Path) -> LogSettings {
+    fs::read(path)
+        .ok()
+        .and_then(|raw| serde_json::from_slice::<LogSettings>(&raw).ok())
+        .filter(|settings| settings.validate().is_ok())
+        .unwrap_or_default()
+}
+
+/// Installe le logger (console + fichier `LOG_FILE_NAME` à rotation) ; à appeler une fois,
+/// au tout début de `setup`.
+pub fn install(app: &tauri::AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let app_data = app.path().app_data_dir()?;
+    fs::create_dir_all(&app_data)?;
+    let path = app_data.join("logging.json");
+    let settings = load_settings(&path);
+
+    // Le plugin laisse tout passer : le tri se fait ici, selon les réglages du moment
+    let (plugin, _, inner) = tauri_plugin_log::Builder::new()
+        .level(LevelFilter::Trace)
+        .clear_targets()
+        .targets([
+            Target::new(TargetKind::Stdout),
+            Target::new(TargetKind::LogDir { file_name: Some(LOG_FILE_NAME.to_string()) }),
+        ])
+        .max_file_size(MAX_FILE_BYTES)
+        .rotation_strategy(RotationStrategy::KeepSome(KEPT_FILES))
+        .split(app)?;
+    app.plugin(plugin)?;
+
+    // Sans trousseau, l'application journalise quand même, sans journal consultable
+    let store = match LogStore::open(app_data.join(logstore::LOG_DIR)) {
+        Ok(store) => Some(store),
+        Err(e) => {
+            eprintln!("Encrypted log store unavailable: {}", e);
+            None
+        }
+    };
+
+    let max_level = settings.max_level();
+    let logger: &'static AppLogger = Box::leak(Box::new(AppLogger {
+        inner,
+        settings: RwLock::new(settings),
+        path,
+        limiter: RateLimiter::default(),
+        store,
+        store_failed: AtomicBool::new(false),
+    }));
+    log::set_logger(logger).map_err(|e| e.to_string())?;
+    log::set_max_level(max_level);
+    let _ = LOGGER.set(logger);
+    Ok(())
+}
+
+pub fn settings() -> Result<LogSettings, LoggingError> {
+    Ok(LOGGER.get().ok_or(LoggingError::NotInstalled)?.settings())
+}
+
+/// Applique et enregistre de nouveaux réglages ; ils valent dès le message suivant.
+pub fn set_settings(settings: LogSettings) -> Result<(), LoggingError> {
+    LOGGER.get().ok_or(LoggingError::NotInstalled)?.apply(settings)
+}
+
+/// Journal chiffré du processus.
+pub fn store() -> Result<&'static LogStore, LoggingError> {
+    LOGGER
+        .get()
+        .ok_or(LoggingError::NotInstalled)?
+        .store
+        .as_ref()
+        .ok_or(LoggingError::StoreUnavailable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_longest_module_prefix_wins() {
+        let settings = LogSettings {
+            level: Verbosity::Warn,
+            modules: [
+                ("aether_core".to_string(), Verbosity::Info),
+                ("aether_core::vault".to_string(), Verbosity::Trace),
+            ]
+            .into(),
+            ..Default::default()
+        };
+        assert_eq!(settings.level_for("aether_core::vault::cache"), LevelFilter::Trace);
+        assert_eq!(settings.level_for("aether_core::index"), LevelFilter::Info);
+        assert_eq!(settings.level_for("aether_core_extra"), LevelFilter::Warn);
+        assert_eq!(settings.max_level(), LevelFilter::Trace);
+        assert!(settings.validate().is_ok());
+
+        let invalid = LogSettings { modules: [("vault::".to_string(), Verbosity::Info)].into(), ..Default::default() };
+        assert!(matches!(invalid.validate(), Err(LoggingError::InvalidModule)));
+    }
+
+    #[test]
+    fn repeated_messages_are_counted_then_reported() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+        for _ in 0..RATE_BURST {
+            assert_eq!(limiter.admit(("app_lib", 10), start), Some(0));
+        }
+        assert_eq!(limiter.admit(("app_lib", 10), start), None);
+        assert_eq!(limiter.admit(("app_lib", 10), start), None);
+        // Un autre emplacement a sa propre fenêtre
+        assert_eq!(limiter.admit(("app_lib", 11), start), Some(0));
+        assert_eq!(limiter.admit(("app_lib", 10), start + RATE_WINDOW), Some(2));
+    }
+}
@@ -0,0 +1,129 @@
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::events::{AppEvent, SyncStatus, TransferDirection};
+
+/// En dessous de ce seuil, un transfert terminé ne mérite pas de notification native.
+pub const LARGE_TRANSFER_THRESHOLD: u64 = 50 * 1024 * 1024;
+
+/// Titre et corps de la notification native associée à un événement, s'il y en a une.
+pub fn notification_for(event: &AppEvent) -> Option<(String, String)> {
+    match event {
+        AppEvent::TransferProgress {
+            direction,
+            bytes_done,
+            bytes_total,
+            ..
+        } if bytes_done == bytes_total && *bytes_total >= LARGE_TRANSFER_THRESHOLD => {
+            let title = match direction {
+                TransferDirection::Upload => "Envoi terminé",
+                TransferDirection::Download => "Téléchargement terminé",
+            };
+            Some((
+                title.to_string(),
+                format!("{:.1} Mo transférés", *bytes_total as f64 / (1024.0 * 1024.0)),
+            ))
+        }
+        AppEvent::SyncState {
+            status: SyncStatus::Failed,
+            ..
+        } => Some((
+            "Synchronisation échouée".to_string(),
+            "Impossible de joindre le stockage distant.".to_string(),
+        )),
+        AppEvent::IntegrityWarning { .. } => Some((
+            "Intégrité de l'index".to_string(),
+            "La vérification de l'index local a échoué.".to_string(),
+        )),
+        AppEvent::TrashPurged { file_ids } if !file_ids.is_empty() => Some((
+            "Corbeille vidée".to_string(),
+            format!("{} fichier(s) supprimé(s) définitivement", file_ids.len()),
+        )),
+        AppEvent::QuotaWarning { threshold_percent, .. } => Some((
+            "Budget de stockage".to_string(),
+            format!("{} % du budget de stockage est utilisé.", threshold_percent),
+        )),
+        AppEvent::TrashSuggestion { share_percent, .. } => Some((
+            "Corbeille volumineuse".to_string(),
+            format!("La corbeille occupe {} % de l'espace utilisé : pensez à la vider.", share_percent),
+        )),
+        AppEvent::JournalConflicts { logical_paths } => Some((
+            "Modifications en conflit".to_string(),
+            format!("{} modification(s) d'un autre appareil écartée(s) : la version locale est conservée.", logical_paths.len()),
+        )),
+        AppEvent::LockedFileEdited { holder, .. } => Some((
+            "Document verrouillé".to_string(),
+            format!("Un document verrouillé par {} sur un autre appareil a été modifié ici.", holder),
+        )),
+        AppEvent::IndexDiverged { devices } => Some((
+            "Index divergent".to_string(),
+            format!("L'index diffère de celui de {} autre(s) appareil(s) synchronisé(s).", devices.len()),
+        )),
+        AppEvent::ConcurrentSessions { devices } if !devices.is_empty() => Some((
+            "Coffre ouvert ailleurs".to_string(),
+            format!("{} modifie aussi ce coffre en ce moment.", devices.join(", ")),
+        )),
+        AppEvent::RulesApplied { failed, .. } if *failed > 0 => Some((
+            "Règles d'automatisation".to_string(),
+            format!("{} règle(s) en échec, voir les paramètres.", failed),
+        )),
+        _ => None,
+    }
+}
+
+/// Affiche une notification native pour l'événement, uniquement si la fenêtre n'a pas le focus.
+pub fn notify(app: &AppHandle, event: &AppEvent) {
+    let Some((title, body)) = notification_for(event) else {
+        return;
+    };
+
+    let focused = app
+        .get_webview_window("main")
+        .and_then(|w| w.is_focused().ok())
+        .unwrap_or(false);
+    if focused {
+        return;
+    }
+
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        log::warn!("Failed to show native notification: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(bytes_done: u64, bytes_total: u64) -> AppEvent {
+        AppEvent::TransferProgress {
+            file_id: "abc".to_string(),
+            direction: TransferDirection::Upload,
+            bytes_done,
+            bytes_total,
+            bytes_per_second: 0,
+            eta_seconds: None,
+        }
+    }
+
+    #[test]
+    fn only_completed_large_transfers_notify() {
+        let large = LARGE_TRANSFER_THRESHOLD;
+        assert!(notification_for(&transfer(large, large)).is_some());
+        assert!(notification_for(&transfer(0, large)).is_none());
+        assert!(notification_for(&transfer(1024, 1024)).is_none());
+    }
+
+    #[test]
+    fn sync_failure_notifies_but_idle_does_not() {
+        let failed = AppEvent::SyncState {
+            status: SyncStatus::Failed,
+            remote_count: None,
+        };
+        let idle = AppEvent::SyncState {
+            status: SyncStatus::Idle,
+            remote_count: Some(3),
+        };
+        assert!(notification_for(&failed).is_some());
+        assert!(notification_for(&idle).is_none());
+    }
+}
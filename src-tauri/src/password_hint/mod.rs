@@ -0,0 +1,72 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Fichier (dans le dossier de données) contenant l'indice, en clair.
+///
+/// Volontairement hors de la hiérarchie de clés : il doit être lisible coffre verrouillé,
+/// et rien n'y est dérivé du mot de passe ni chiffré avec lui.
+pub const HINT_FILE: &str = "password_hint.txt";
+pub const MAX_HINT_CHARS: usize = 100;
+
+/// Nettoie et valide un indice. `None` (ou un indice vide) signifie « pas d'indice ».
+///
+/// Si le mot de passe est connu de l'appelant, un indice qui le contient est refusé.
+pub fn validate(hint: Option<&str>, password: Option<&str>) -> Result<Option<String>, &'static str> {
+    let Some(hint) = hint.map(str::trim).filter(|h| !h.is_empty()) else {
+        return Ok(None);
+    };
+    if hint.chars().count() > MAX_HINT_CHARS {
+        return Err("too_long");
+    }
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        if hint.to_lowercase().contains(&password.to_lowercase()) {
+            return Err("contains_password");
+        }
+    }
+    Ok(Some(hint.to_string()))
+}
+
+pub fn load(path: &Path) -> io::Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(hint) => Ok(Some(hint).filter(|h| !h.is_empty())),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn save(path: &Path, hint: Option<&str>) -> io::Result<()> {
+    match hint {
+        Some(hint) => fs::write(path, hint),
+        None => match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn hint_may_not_reveal_the_password() {
+        assert_eq!(validate(Some("  le chat + année  "), Some("Minou2019")), Ok(Some("le chat + année".to_string())));
+        assert_eq!(validate(Some("c'est minou2019 !"), Some("Minou2019")), Err("contains_password"));
+        assert_eq!(validate(Some(&"x".repeat(MAX_HINT_CHARS + 1)), None), Err("too_long"));
+        assert_eq!(validate(Some("   "), None), Ok(None));
+    }
+
+    #[test]
+    fn hint_roundtrips_and_can_be_removed() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(HINT_FILE);
+        assert_eq!(load(&path).unwrap(), None);
+        save(&path, Some("variante avec majuscule")).unwrap();
+        assert_eq!(load(&path).unwrap().as_deref(), Some("variante avec majuscule"));
+        save(&path, None).unwrap();
+        save(&path, None).unwrap();
+        assert_eq!(load(&path).unwrap(), None);
+    }
+}
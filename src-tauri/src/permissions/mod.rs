@@ -0,0 +1,578 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::crypto::{constant_time_eq, KeyHierarchy, Keyfile, MasterKey, MkekCiphertext, PasswordSecret};
+use crate::error::CommandError;
+use crate::reauth::{Freshness, FreshnessStatus};
+
+/// Durée de validité d'une élévation accordée et non utilisée.
+pub const GRANT_TTL: Duration = Duration::from_secs(60);
+
+/// Ensemble de capacités auquel appartient une commande.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    /// Lecture seule : parcours, aperçu, téléchargement, état.
+    Browse,
+    /// Modifications réversibles (envoi, renommage, corbeille, configuration).
+    Mutate,
+    /// Perte de données irréversible (y compris, à venir, re-chiffrement et migration) :
+    /// le mot de passe est redemandé.
+    Destructive,
+    /// Mot de passe, clés et identifiants.
+    KeyManagement,
+}
+
+/// Commandes exposées au frontend, par capacité. Une commande absente est refusée.
+const COMMANDS: &[(&str, Capability)] = &[
+    ("get_index_db_path", Capability::Browse),
+    ("get_index_status", Capability::Browse),
+    ("index_list_files", Capability::Browse),
+    ("list_by_category", Capability::Browse),
+    ("photos_timeline", Capability::Browse),
+    ("list_files_and_folders", Capability::Browse),
+    ("list_directory_stream", Capability::Browse),
+    ("get_folder_appearance", Capability::Browse),
+    ("folder_policy_get", Capability::Browse),
+    ("index_get_file", Capability::Browse),
+    ("index_verify_integrity", Capability::Browse),
+    ("integrity_last_report", Capability::Browse),
+    ("summary_last_sync", Capability::Browse),
+    ("summary_last_scrub", Capability::Browse),
+    ("integrity_timeline", Capability::Browse),
+    ("vault_health", Capability::Browse),
+    ("activity_summary", Capability::Browse),
+    ("run_diagnostics", Capability::Browse),
+    ("integrity_check_now", Capability::Browse),
+    ("quarantine_list", Capability::Browse),
+    ("vault_clone_last_report", Capability::Browse),
+    ("locks_list", Capability::Browse),
+    ("sessions_status", Capability::Browse),
+    ("transfers_status", Capability::Browse),
+    ("storage_decrypt_file", Capability::Browse),
+    ("storage_get_file_info", Capability::Browse),
+    ("storj_download_file", Capability::Browse),
+    ("storj_download_file_by_path", Capability::Browse),
+    ("storj_list_files", Capability::Browse),
+    ("list_trash", Capability::Browse),
+    ("list_trashed_folders", Capability::Browse),
+    ("get_password_hint", Capability::Browse),
+    ("setup_wizard_state", Capability::Browse),
+    ("quota_status", Capability::Browse),
+    ("offline_pending_count", Capability::Browse),
+    ("telemetry_get", Capability::Browse),
+    ("logging_get_settings", Capability::Browse),
+    ("crash_reports_count", Capability::Browse),
+    ("logs_query", Capability::Browse),
+    ("get_encryption_details", Capability::Browse),
+    ("verify_download", Capability::Browse),
+    ("preview_file", Capability::Browse),
+    ("preview_open", Capability::Browse),
+    ("preview_close", Capability::Browse),
+    ("crypto_lock", Capability::Browse),
+    ("request_elevation", Capability::Browse),
+    ("share_pending", Capability::Browse),
+    ("index_add_file", Capability::Mutate),
+    ("set_folder_appearance", Capability::Mutate),
+    ("folder_policy_set", Capability::Mutate),
+    ("activity_acknowledge", Capability::Mutate),
+    ("create_folder", Capability::Mutate),
+    ("storage_encrypt_file", Capability::Mutate),
+    ("storage_encrypt_file_from_path", Capability::Mutate),
+    ("storj_configure", Capability::Mutate),
+    ("storj_save_credentials", Capability::Mutate),
+    ("storj_load_credentials", Capability::Mutate),
+    ("setup_wizard_configure_backend", Capability::Mutate),
+    ("setup_wizard_test_connection", Capability::Mutate),
+    ("setup_wizard_import_folder", Capability::Mutate),
+    ("storj_upload_file", Capability::Mutate),
+    ("storj_upload_from_path", Capability::Mutate),
+    ("upload_local_file", Capability::Mutate),
+    ("storj_delete_file", Capability::Mutate),
+    ("rename_file", Capability::Mutate),
+    ("job_cancel", Capability::Mutate),
+    ("file_versions", Capability::Browse),
+    ("file_version_restore", Capability::Mutate),
+    ("rename_folder", Capability::Mutate),
+    ("alias_create", Capability::Mutate),
+    ("alias_resolve", Capability::Browse),
+    ("alias_delete", Capability::Mutate),
+    ("snapshot_create", Capability::Mutate),
+    ("snapshot_list", Capability::Browse),
+    ("snapshot_diff", Capability::Browse),
+    ("restore_from_trash", Capability::Mutate),
+    ("trash_folder", Capability::Mutate),
+    ("restore_folder_from_trash", Capability::Mutate),
+    ("export_vault", Capability::Mutate),
+    ("export_archive", Capability::Mutate),
+    ("export_encrypted_selection", Capability::Mutate),
+    ("export_report", Capability::Mutate),
+    ("webdav_start", Capability::Mutate),
+    ("webdav_stop", Capability::Mutate),
+    ("quota_set_limit", Capability::Mutate),
+    ("quota_set_trash_threshold", Capability::Mutate),
+    ("content_search_status", Capability::Browse),
+    ("content_search_set_enabled", Capability::Mutate),
+    ("remote_keys_status", Capability::Browse),
+    ("remote_keys_set_enabled", Capability::Mutate),
+    ("remote_keys_audit", Capability::Browse),
+    ("remote_keys_repair", Capability::Mutate),
+    ("search_files", Capability::Browse),
+    ("cache_status", Capability::Browse),
+    ("rules_list", Capability::Browse),
+    ("upload_preflight", Capability::Browse),
+    ("workspace_list", Capability::Browse),
+    ("index_backup_status", Capability::Browse),
+    ("index_backup_list", Capability::Browse),
+    ("cache_set_policy", Capability::Mutate),
+    ("cache_clear", Capability::Mutate),
+    ("quarantine_recheck", Capability::Mutate),
+    ("quarantine_restore_version", Capability::Mutate),
+    ("quarantine_restore_local_copy", Capability::Mutate),
+    ("lock_acquire", Capability::Mutate),
+    ("lock_release", Capability::Mutate),
+    ("index_backup_set_policy", Capability::Mutate),
+    ("index_backup_now", Capability::Mutate),
+    ("vault_clone", Capability::Mutate),
+    ("rules_save", Capability::Mutate),
+    ("workspace_create", Capability::Mutate),
+    ("workspace_rename", Capability::Mutate),
+    ("workspace_switch", Capability::Mutate),
+    ("rules_delete", Capability::Mutate),
+    ("hooks_list", Capability::Browse),
+    ("hooks_delete", Capability::Mutate),
+    ("rules_run_now", Capability::Mutate),
+    ("offline_flush_queue", Capability::Mutate),
+    ("telemetry_set_enabled", Capability::Mutate),
+    ("logging_set_settings", Capability::Mutate),
+    ("telemetry_reset", Capability::Mutate),
+    ("telemetry_export", Capability::Mutate),
+    ("crash_reports_export", Capability::Mutate),
+    ("crash_reports_clear", Capability::Mutate),
+    ("logs_export_bundle", Capability::Mutate),
+    ("logs_clear", Capability::Mutate),
+    ("select_and_read_file", Capability::Mutate),
+    ("select_files_for_upload", Capability::Mutate),
+    ("select_and_read_file_from_path", Capability::Mutate),
+    ("save_decrypted_file", Capability::Mutate),
+    ("download_to_file", Capability::Mutate),
+    ("storj_download_to_path", Capability::Mutate),
+    ("share_receive", Capability::Mutate),
+    ("share_dismiss", Capability::Mutate),
+    ("reset_local_database", Capability::Destructive),
+    ("index_remove_file", Capability::Destructive),
+    ("permanently_delete_from_trash", Capability::Destructive),
+    ("empty_trash", Capability::Destructive),
+    ("index_backup_restore", Capability::Destructive),
+    ("workspace_delete", Capability::Destructive),
+    ("snapshot_restore", Capability::Destructive),
+    ("snapshot_delete", Capability::Destructive),
+    ("quarantine_discard", Capability::Destructive),
+    ("lock_break", Capability::Destructive),
+    ("crypto_bootstrap", Capability::KeyManagement),
+    ("setup_wizard_create_vault", Capability::KeyManagement),
+    ("crypto_unlock", Capability::KeyManagement),
+    ("crypto_unlock_device", Capability::KeyManagement),
+    ("index_open_with_vault", Capability::KeyManagement),
+    ("index_archive_and_recreate", Capability::KeyManagement),
+    ("device_unlock_status", Capability::Browse),
+    ("device_unlock_forget", Capability::KeyManagement),
+    ("crypto_change_password", Capability::KeyManagement),
+    ("keyfile_required", Capability::Browse),
+    ("keyfile_select", Capability::Browse),
+    ("keyfile_enroll", Capability::KeyManagement),
+    ("keyfile_remove", Capability::KeyManagement),
+    ("storj_delete_credentials", Capability::KeyManagement),
+    ("storj_credentials_status", Capability::Browse),
+    ("storj_enroll_delegation", Capability::KeyManagement),
+    ("storj_rotate_credentials", Capability::KeyManagement),
+    ("set_password_hint", Capability::KeyManagement),
+    ("guest_mode_status", Capability::Browse),
+    ("guest_mode_search", Capability::Browse),
+    ("guest_mode_close", Capability::Mutate),
+    ("guest_mode_enable", Capability::KeyManagement),
+    ("guest_mode_disable", Capability::KeyManagement),
+    ("guest_mode_open", Capability::KeyManagement),
+    // Le paquet transporte la MKEK ; l'import remplace la configuration de l'appareil
+    ("vault_bundle_export", Capability::KeyManagement),
+    ("setup_wizard_recovery_kit", Capability::KeyManagement),
+    ("vault_bundle_import", Capability::KeyManagement),
+    // Un hook exécute un programme local ou envoie des chemins du coffre hors de l'appareil
+    ("hooks_save", Capability::KeyManagement),
+    ("session_freshness", Capability::Browse),
+    ("reauth_set_window", Capability::KeyManagement),
+];
+
+/// Commandes de gestion des clés qui vérifient elles-mêmes le mot de passe reçu en argument :
+/// c'est un facteur plus fort qu'une confirmation, aucune élévation n'est demandée.
+const PASSWORD_VERIFIED: &[&str] = &[
+    "crypto_bootstrap",
+    "setup_wizard_create_vault",
+    "crypto_unlock",
+    "index_open_with_vault",
+    "index_archive_and_recreate",
+    "crypto_change_password",
+    "keyfile_enroll",
+    "keyfile_remove",
+    // Vérifie la phrase d'accès invité en descellant les clés de l'index
+    "guest_mode_open",
+];
+
+/// Commandes qui font sortir la clé du coffre : au-delà de la fenêtre de fraîcheur (voir
+/// `reauth`), leur confirmation est une nouvelle saisie du mot de passe. Celles qui prennent
+/// le mot de passe en argument (changement de mot de passe, fichier clé, archive) sont
+/// fraîches par construction.
+const REAUTH_REQUIRED: &[&str] = &[
+    "vault_bundle_export",
+    "setup_wizard_recovery_kit",
+    // Allonger la fenêtre ne doit pas suffire à s'en passer
+    "reauth_set_window",
+];
+
+/// Commandes qui acceptent `dryRun` : leur essai à blanc ne modifie rien et prépare la
+/// confirmation, il se passe donc d'élévation. Pour toute autre commande, l'argument est
+/// ignoré par Tauri et ne dispense de rien.
+const DRY_RUN_CAPABLE: &[&str] = &[
+    "trash_folder",
+    "empty_trash",
+    "snapshot_delete",
+    "permanently_delete_from_trash",
+    "crypto_change_password",
+    "reset_local_database",
+];
+
+pub fn capability_of(command: &str) -> Option<Capability> {
+    COMMANDS.iter().find(|(name, _)| *name == command).map(|(_, capability)| *capability)
+}
+
+pub fn requires_elevation(command: &str) -> bool {
+    matches!(capability_of(command), Some(Capability::Destructive | Capability::KeyManagement))
+        && !PASSWORD_VERIFIED.contains(&command)
+}
+
+/// Commandes pour lesquelles la confirmation est une nouvelle saisie du mot de passe,
+/// vérifiée côté Rust : une session détournée ne peut pas détruire de données sans lui.
+pub fn requires_password(command: &str) -> bool {
+    capability_of(command) == Some(Capability::Destructive)
+}
+
+/// Libellé présenté dans la boîte de confirmation native.
+pub fn describe(command: &str) -> &'static str {
+    match command {
+        "reset_local_database" => "Supprimer la base locale du coffre (index, corbeille, file hors ligne).",
+        "index_remove_file" => "Retirer définitivement un fichier de l'index local.",
+        "permanently_delete_from_trash" => "Supprimer définitivement un fichier de la corbeille et du stockage distant.",
+        "empty_trash" => "Vider la corbeille : tous ses fichiers seront supprimés définitivement du stockage distant.",
+        "snapshot_restore" => "Restaurer un instantané : les fichiers plus récents seront mis à la corbeille.",
+        "snapshot_delete" => "Supprimer un instantané et les fichiers distants qu'il était seul à conserver.",
+        "crypto_unlock_device" => "Déverrouiller le coffre avec la clé conservée par cet appareil.",
+        "device_unlock_forget" => "Oublier la clé conservée par cet appareil : le mot de passe maître sera de nouveau demandé.",
+        "storj_delete_credentials" => "Effacer les identifiants Storj du trousseau du système.",
+        "storj_enroll_delegation" => "Remplacer les identifiants Storj par des identifiants délégués, à durée limitée.",
+        "storj_rotate_credentials" => "Renouveler les identifiants Storj délégués et révoquer les précédents.",
+        "set_password_hint" => "Modifier l'indice de mot de passe (lisible sans déverrouiller le coffre).",
+        "guest_mode_enable" => "Définir une phrase d'accès invité : elle permettra de lire les noms et tailles des fichiers sans déverrouiller le coffre.",
+        "guest_mode_disable" => "Supprimer la phrase d'accès invité.",
+        "vault_bundle_export" => "Exporter la configuration du coffre pour l'ajouter sur un autre appareil.",
+        "setup_wizard_recovery_kit" => "Exporter le kit de récupération du coffre (configuration scellée sous un code de transfert).",
+        "vault_bundle_import" => "Importer la configuration d'un coffre exportée depuis un autre appareil.",
+        "reauth_set_window" => "Modifier le délai après lequel le mot de passe est redemandé pour exporter les clés du coffre.",
+        "hooks_save" => "Enregistrer un hook : il exécutera un programme local ou enverra les événements du coffre à une adresse externe.",
+        _ => "Exécuter une opération sensible.",
+    }
+}
+
+/// Élévations accordées par confirmation native, à usage unique et de courte durée.
+///
+/// Une page compromise peut appeler n'importe quelle commande, mais pas valider une
+/// boîte de dialogue du système à la place de l'utilisateur.
+#[derive(Default)]
+pub struct Elevations {
+    grants: Mutex<HashMap<String, Instant>>,
+    verifier: Mutex<Option<PasswordVerifier>>,
+    freshness: Mutex<Freshness>,
+}
+
+/// Sel et MKEK du dernier déverrouillage : de quoi vérifier une nouvelle saisie du mot de
+/// passe sans le conserver.
+#[derive(Clone)]
+struct PasswordVerifier {
+    salt: [u8; 16],
+    mkek: MkekCiphertext,
+    /// Fichier clé lu au déverrouillage. Absent après un déverrouillage par l'appareil : un
+    /// coffre à fichier clé ne peut alors plus confirmer par mot de passe (`keyfile_required`).
+    keyfile: Option<Keyfile>,
+}
+
+impl Elevations {
+    /// Retient le sel, la MKEK et le fichier clé avec lesquels le mot de passe vient d'être
+    /// vérifié : la session est fraîche.
+    pub fn remember_password(&self, salt: [u8; 16], mkek: &MkekCiphertext, keyfile: Option<&Keyfile>) {
+        self.remember(PasswordVerifier { salt, mkek: mkek.clone(), keyfile: keyfile.cloned() }, true);
+    }
+
+    /// Comme [`Self::remember_password`] après un déverrouillage sans mot de passe (appareil) :
+    /// la session n'est pas fraîche.
+    pub fn remember_envelope(&self, salt: [u8; 16], mkek: &MkekCiphertext) {
+        self.remember(PasswordVerifier { salt, mkek: mkek.clone(), keyfile: None }, false);
+    }
+
+    fn remember(&self, verifier: PasswordVerifier, with_password: bool) {
+        if let Ok(mut current) = self.verifier.lock() {
+            *current = Some(verifier);
+        }
+        if let Ok(mut freshness) = self.freshness.lock() {
+            freshness.unlocked(Instant::now(), with_password);
+        }
+    }
+
+    /// Fenêtre de fraîcheur lue dans l'index du coffre (voir `reauth::window`).
+    pub fn set_reauth_window(&self, window: Duration) {
+        if let Ok(mut freshness) = self.freshness.lock() {
+            freshness.set_window(window);
+        }
+    }
+
+    pub fn freshness(&self) -> Result<FreshnessStatus, CommandError> {
+        Ok(self
+            .freshness
+            .lock()
+            .map_err(|e| CommandError::internal("Elevation lock error", e))?
+            .status(Instant::now()))
+    }
+
+    /// La confirmation de `command` passe par une nouvelle saisie du mot de passe, la
+    /// dernière étant trop ancienne.
+    pub fn requires_reauthentication(&self, command: &str) -> bool {
+        self.requires_reauthentication_at(command, Instant::now())
+    }
+
+    fn requires_reauthentication_at(&self, command: &str, now: Instant) -> bool {
+        REAUTH_REQUIRED.contains(&command)
+            && !self.freshness.lock().is_ok_and(|freshness| freshness.is_fresh(now))
+    }
+
+    /// Sel et MKEK du dernier déverrouillage (paquet de configuration d'un nouvel appareil).
+    pub fn password_envelope(&self) -> Option<([u8; 16], MkekCiphertext)> {
+        let verifier = self.verifier.lock().ok()?.clone()?;
+        Some((verifier.salt, verifier.mkek))
+    }
+
+    /// Vérifie une nouvelle saisie du mot de passe ; coffre déverrouillé, la MasterKey
+    /// qu'elle ouvre doit en plus être celle en mémoire.
+    pub fn verify_password(&self, password: String, current: Option<&MasterKey>) -> Result<(), CommandError> {
+        let verifier = self
+            .verifier
+            .lock()
+            .map_err(|e| CommandError::internal("Elevation lock error", e))?
+            .clone()
+            .ok_or(CommandError::VaultLocked)?;
+        let hierarchy = KeyHierarchy::restore_with_keyfile(
+            &PasswordSecret::new(password),
+            verifier.salt,
+            &verifier.mkek,
+            verifier.keyfile.as_ref(),
+        )?;
+        match current {
+            Some(current) if !constant_time_eq(current.as_bytes(), hierarchy.master_key().as_bytes()) => {
+                Err(CommandError::WrongPassword)
+            }
+            _ => {
+                if let Ok(mut freshness) = self.freshness.lock() {
+                    freshness.authenticated(Instant::now());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn grant(&self, command: &str) {
+        if let Ok(mut grants) = self.grants.lock() {
+            grants.insert(command.to_string(), Instant::now() + GRANT_TTL);
+        }
+    }
+
+    /// Vérifie qu'une commande peut s'exécuter, en consommant l'élévation éventuelle.
+    pub fn authorize(&self, command: &str) -> Result<(), CommandError> {
+        self.authorize_at(command, Instant::now())
+    }
+
+    /// Comme [`Elevations::authorize`], pour un appel qui demande un essai à blanc (`dry_run`).
+    pub fn authorize_call(&self, command: &str, dry_run: bool) -> Result<(), CommandError> {
+        self.authorize_call_at(command, dry_run, Instant::now())
+    }
+
+    fn authorize_at(&self, command: &str, now: Instant) -> Result<(), CommandError> {
+        self.authorize_call_at(command, false, now)
+    }
+
+    fn authorize_call_at(&self, command: &str, dry_run: bool, now: Instant) -> Result<(), CommandError> {
+        if capability_of(command).is_none() {
+            return Err(CommandError::CommandNotAllowed { command: command.to_string() });
+        }
+        if !requires_elevation(command) || (dry_run && DRY_RUN_CAPABLE.contains(&command)) {
+            return Ok(());
+        }
+        // Une élévation accordée avant l'expiration de la fenêtre ne suffit plus
+        if self.requires_reauthentication_at(command, now) {
+            return Err(CommandError::PasswordRequired { command: command.to_string() });
+        }
+        let mut grants = self
+            .grants
+            .lock()
+            .map_err(|e| CommandError::internal("Elevation lock error", e))?;
+        match grants.remove(command) {
+            Some(expires_at) if now <= expires_at => Ok(()),
+            _ => Err(CommandError::ElevationRequired { command: command.to_string() }),
+        }
+    }
+
+    /// Retire toutes les élévations en attente et oublie la MKEK (verrouillage du coffre).
+    pub fn revoke_all(&self) {
+        if let Ok(mut grants) = self.grants.lock() {
+            grants.clear();
+        }
+        if let Ok(mut verifier) = self.verifier.lock() {
+            *verifier = None;
+        }
+        if let Ok(mut freshness) = self.freshness.lock() {
+            freshness.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capabilities_are_unique_and_elevation_is_scoped() {
+        let mut names: Vec<&str> = COMMANDS.iter().map(|(name, _)| *name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), COMMANDS.len());
+
+        assert!(requires_elevation("empty_trash"));
+        assert!(requires_elevation("set_password_hint"));
+        assert!(!requires_elevation("crypto_unlock"));
+        assert!(!requires_elevation("storj_delete_file"));
+        assert!(requires_password("reset_local_database"));
+        assert!(!requires_password("set_password_hint"));
+        assert!(PASSWORD_VERIFIED.iter().all(|c| capability_of(c) == Some(Capability::KeyManagement)));
+    }
+
+    #[test]
+    fn every_declared_command_has_a_capability() {
+        let source = include_str!("../lib.rs");
+        let mut lines = source.lines();
+        while let Some(line) = lines.next() {
+            if line.trim() != "#[tauri::command]" {
+                continue;
+            }
+            let signature = lines.next().unwrap_or_default();
+            let name = signature
+                .split("fn ")
+                .nth(1)
+                .and_then(|rest| rest.split(['(', '<']).next())
+                .unwrap_or_default();
+            assert!(capability_of(name).is_some(), "command `{}` has no capability", name);
+        }
+    }
+
+    #[test]
+    fn grants_are_single_use_and_expire() {
+        let elevations = Elevations::default();
+        assert!(elevations.authorize("list_trash").is_ok());
+        assert_eq!(elevations.authorize("eval_anything").unwrap_err().code(), "command_not_allowed");
+        assert_eq!(elevations.authorize("empty_trash").unwrap_err().code(), "elevation_required");
+
+        elevations.grant("empty_trash");
+        // L'élévation ne vaut que pour la commande confirmée
+        assert!(elevations.authorize("reset_local_database").is_err());
+        assert!(elevations.authorize("empty_trash").is_ok());
+        assert!(elevations.authorize("empty_trash").is_err());
+
+        elevations.grant("empty_trash");
+        let later = Instant::now() + GRANT_TTL + Duration::from_secs(1);
+        assert!(elevations.authorize_at("empty_trash", later).is_err());
+
+        elevations.grant("empty_trash");
+        elevations.revoke_all();
+        assert!(elevations.authorize("empty_trash").is_err());
+    }
+
+    #[test]
+    fn dry_runs_skip_elevation_only_where_supported() {
+        let elevations = Elevations::default();
+        assert!(DRY_RUN_CAPABLE.iter().all(|c| capability_of(c).is_some()));
+        assert!(elevations.authorize_call("empty_trash", true).is_ok());
+        assert_eq!(elevations.authorize_call("empty_trash", false).unwrap_err().code(), "elevation_required");
+
+        assert!(elevations.authorize_call("reset_local_database", true).is_ok());
+
+        // `dryRun` sur une autre commande ne dispense ni de l'élévation ni de la liste
+        assert_eq!(elevations.authorize_call("vault_bundle_export", true).unwrap_err().code(), "elevation_required");
+        assert_eq!(elevations.authorize_call("eval_anything", true).unwrap_err().code(), "command_not_allowed");
+    }
+
+    #[test]
+    fn key_exports_need_a_recent_password_entry() {
+        let elevations = Elevations::default();
+        let hierarchy = KeyHierarchy::bootstrap(&PasswordSecret::new("correct horse"), [5u8; 16]).unwrap();
+        let mkek = hierarchy.seal_master_key().unwrap();
+
+        // Déverrouillage par l'appareil : une confirmation native ne suffit pas
+        elevations.remember_envelope([5u8; 16], &mkek);
+        assert!(elevations.requires_reauthentication("vault_bundle_export"));
+        assert!(!elevations.requires_reauthentication("set_password_hint"));
+        elevations.grant("vault_bundle_export");
+        assert_eq!(elevations.authorize("vault_bundle_export").unwrap_err().code(), "password_required");
+
+        elevations.remember_password([5u8; 16], &mkek, None);
+        assert!(elevations.freshness().unwrap().fresh);
+        elevations.grant("vault_bundle_export");
+        let later = Instant::now() + crate::reauth::DEFAULT_WINDOW + Duration::from_secs(1);
+        assert_eq!(elevations.authorize_at("vault_bundle_export", later).unwrap_err().code(), "password_required");
+        assert!(elevations.authorize("vault_bundle_export").is_ok());
+
+        elevations.set_reauth_window(Duration::from_secs(3600));
+        assert_eq!(elevations.freshness().unwrap().window_secs, 3600);
+        elevations.revoke_all();
+        assert_eq!(elevations.freshness().unwrap().unlocked_secs, None);
+    }
+
+    #[test]
+    fn password_reentry_must_open_the_unlocked_master_key() {
+        let elevations = Elevations::default();
+        assert_eq!(elevations.verify_password("pw".into(), None).unwrap_err().code(), "vault_locked");
+
+        let salt = [3u8; 16];
+        let hierarchy = KeyHierarchy::bootstrap(&PasswordSecret::new("correct horse"), salt).unwrap();
+        let mkek = hierarchy.seal_master_key().unwrap();
+        let current = MasterKey::from_vec(hierarchy.master_key().as_bytes().to_vec());
+        elevations.remember_password(salt, &mkek, None);
+
+        assert!(elevations.verify_password("correct horse".into(), Some(&current)).is_ok());
+        assert_eq!(
+            elevations.verify_password("wrong".into(), Some(&current)).unwrap_err().code(),
+            "wrong_password"
+        );
+        // Une MKEK valide mais d'un autre coffre ne suffit pas une fois déverrouillé
+        let other = MasterKey::from_vec(vec![9u8; 32]);
+        assert!(elevations.verify_password("correct horse".into(), Some(&other)).is_err());
+
+        // Coffre à fichier clé : le fichier retenu au déverrouillage est nécessaire
+        let keyfile = Keyfile::generate();
+        let mkek = hierarchy.rekey(&PasswordSecret::new("correct horse"), salt, Some(&keyfile)).unwrap().seal_master_key().unwrap();
+        elevations.remember_password(salt, &mkek, None);
+        assert_eq!(
+            elevations.verify_password("correct horse".into(), Some(&current)).unwrap_err().code(),
+            "keyfile_required"
+        );
+        elevations.remember_password(salt, &mkek, Some(&keyfile));
+        assert!(elevations.verify_password("correct horse".into(), Some(&current)).is_ok());
+
+        elevations.revoke_all();
+        assert!(elevations.verify_password("correct horse".into(), Some(&current)).is_err());
+    }
+}
@@ -0,0 +1,282 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rand::RngCore;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, FilePath};
+use tauri_plugin_fs::{FsExt, OpenOptions};
+use tokio::sync::oneshot;
+
+use crate::error::CommandError;
+
+/// Délai laissé à l'utilisateur pour répondre au dialogue.
+const DIALOG_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sous-dossier du cache où sont copiés les documents sans chemin local.
+const IMPORT_DIR: &str = "imports";
+
+/// Ouvre le dialogue natif de sélection de fichier.
+///
+/// Sur bureau, le résultat est un chemin ; sur Android (Storage Access Framework) et iOS
+/// (sélecteur de documents), souvent une URI `content://` ou `file://` qu'il faut lire via
+/// [`open_read`] ou [`local_copy`] plutôt qu'avec `std::fs`.
+pub async fn pick_open(app: &AppHandle, title: &str) -> Result<FilePath, CommandError> {
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .file()
+        .set_title(title)
+        .pick_file(move |path_opt| {
+            let _ = tx.send(path_opt);
+        });
+    wait(rx).await
+}
+
+/// Ouvre le dialogue natif de sélection de plusieurs fichiers (mêmes réserves que [`pick_open`]).
+pub async fn pick_open_many(app: &AppHandle, title: &str) -> Result<Vec<FilePath>, CommandError> {
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .file()
+        .set_title(title)
+        .pick_files(move |paths_opt| {
+            let _ = tx.send(paths_opt);
+        });
+    wait(rx).await
+}
+
+/// Ouvre le dialogue natif de sélection de dossiers ; les plateformes mobiles n'en ont pas.
+#[cfg(desktop)]
+pub async fn pick_folders(app: &AppHandle, title: &str) -> Result<Vec<FilePath>, CommandError> {
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .file()
+        .set_title(title)
+        .pick_folders(move |paths_opt| {
+            let _ = tx.send(paths_opt);
+        });
+    wait(rx).await
+}
+
+/// Ouvre le dialogue natif d'enregistrement ; le résultat s'écrit avec [`write`].
+pub async fn pick_save(app: &AppHandle, title: &str, suggested_name: &str) -> Result<FilePath, CommandError> {
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .file()
+        .set_title(title)
+        .set_file_name(suggested_name)
+        .save_file(move |path_opt| {
+            let _ = tx.send(path_opt);
+        });
+    wait(rx).await
+}
+
+async fn wait<T>(rx: oneshot::Receiver<Option<T>>) -> Result<T, CommandError> {
+    tokio::time::timeout(DIALOG_TIMEOUT, rx)
+        .await
+        .map_err(|_| CommandError::DialogTimeout)?
+        .map_err(|e| CommandError::internal("Dialog channel closed", e))?
+        .ok_or(CommandError::DialogCancelled)
+}
+
+/// Nom affichable du document choisi.
+///
+/// Une URI `content://` ne porte pas toujours le vrai nom : on garde le dernier segment
+/// décodé (`primary:Download/facture.pdf` → `facture.pdf`).
+pub fn display_name(path: &FilePath) -> String {
+    let name = match path {
+        FilePath::Path(path) => path.file_name().map(|n| n.to_string_lossy().into_owned()),
+        FilePath::Url(url) => url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .map(percent_decode)
+            .and_then(|segment| segment.rsplit(|c| c == '/' || c == ':').next().map(str::to_string)),
+    };
+    name.filter(|n| !n.is_empty()).unwrap_or_else(|| "fichier".to_string())
+}
+
+/// Relit un chemin renvoyé au frontend (chemin local ou URI de la plateforme).
+pub fn parse_selection(path: &str) -> FilePath {
+    path.parse().unwrap_or_else(|never: std::convert::Infallible| match never {})
+}
+
+/// Fichiers contenus dans un dossier choisi, récursivement, triés par chemin.
+///
+/// Chaque fichier est accompagné de son dossier relatif, dossier choisi compris
+/// (`/home/a/photos/2024/x.jpg` → `photos/2024`). Les liens symboliques sont ignorés.
+pub fn walk_folder(root: &Path) -> io::Result<Vec<(PathBuf, String)>> {
+    let base = root.parent().unwrap_or(root);
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() {
+                let relative_dir = dir
+                    .strip_prefix(base)
+                    .unwrap_or(&dir)
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.push((entry.path(), relative_dir));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Ouvre le document en lecture, y compris derrière une URI de la plateforme.
+pub fn open_read(app: &AppHandle, path: &FilePath) -> io::Result<fs::File> {
+    let mut options = OpenOptions::new();
+    options.read(true);
+    app.fs().open(path.clone(), options)
+}
+
+/// Lit tout le document choisi.
+pub fn read(app: &AppHandle, path: &FilePath) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    open_read(app, path)?.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Écrit (en remplaçant) le document choisi par [`pick_save`].
+pub fn write(app: &AppHandle, path: &FilePath, data: &[u8]) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    let mut file = app.fs().open(path.clone(), options)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+/// Écrit (en remplaçant) le document choisi par [`pick_save`], copié en flux depuis `source`.
+pub fn write_from(app: &AppHandle, path: &FilePath, source: &Path) -> io::Result<u64> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    let mut file = app.fs().open(path.clone(), options)?;
+    let copied = io::copy(&mut fs::File::open(source)?, &mut file)?;
+    file.sync_all()?;
+    Ok(copied)
+}
+
+/// Chemin local d'un document choisi ; une copie temporaire est supprimée à la libération.
+pub struct LocalCopy {
+    path: PathBuf,
+    temporary: bool,
+}
+
+impl LocalCopy {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl From<PathBuf> for LocalCopy {
+    fn from(path: PathBuf) -> Self {
+        Self { path, temporary: false }
+    }
+}
+
+impl Drop for LocalCopy {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Donne un chemin local lisible en flux (chiffrement, hachage) pour le document choisi.
+///
+/// Un chemin ou une URI `file://` est utilisé tel quel ; une URI `content://` (Android) est
+/// d'abord copiée dans le cache de l'application.
+pub async fn local_copy(app: &AppHandle, path: FilePath) -> Result<LocalCopy, CommandError> {
+    if let Ok(local) = path.clone().into_path() {
+        return Ok(LocalCopy::from(local));
+    }
+    let dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| CommandError::internal("Failed to get cache directory", e))?
+        .join(IMPORT_DIR);
+    let mut suffix = [0u8; 8];
+    rand::thread_rng().fill_bytes(&mut suffix);
+    let copy = LocalCopy {
+        path: dir.join(format!("{}.tmp", hex::encode(suffix))),
+        temporary: true,
+    };
+    let app = app.clone();
+    let target = copy.path.clone();
+    tokio::task::spawn_blocking(move || -> io::Result<u64> {
+        fs::create_dir_all(&dir)?;
+        io::copy(&mut open_read(&app, &path)?, &mut fs::File::create(&target)?)
+    })
+    .await
+    .map_err(|e| CommandError::internal("Copy task failed", e))?
+    .map_err(|e| CommandError::io("Failed to copy the selected document", e))?;
+    Ok(copy)
+}
+
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_name_decodes_the_last_uri_segment() {
+        let path = FilePath::Url(
+            "content://com.android.externalstorage.documents/document/primary%3ADownload%2Ffacture%20mai.pdf"
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(display_name(&path), "facture mai.pdf");
+        assert_eq!(display_name(&FilePath::Path(PathBuf::from("/tmp/rapport.txt"))), "rapport.txt");
+        assert_eq!(display_name(&FilePath::Path(PathBuf::from("/"))), "fichier");
+    }
+
+    #[test]
+    fn walk_folder_keeps_the_chosen_folder_in_relative_dirs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().join("photos");
+        fs::create_dir_all(root.join("2024/juin")).unwrap();
+        fs::write(root.join("a.jpg"), b"a").unwrap();
+        fs::write(root.join("2024/juin/b.jpg"), b"b").unwrap();
+
+        let files: Vec<(String, String)> = walk_folder(&root)
+            .unwrap()
+            .into_iter()
+            .map(|(path, relative_dir)| (path.file_name().unwrap().to_string_lossy().into_owned(), relative_dir))
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                ("b.jpg".to_string(), "photos/2024/juin".to_string()),
+                ("a.jpg".to_string(), "photos".to_string()),
+            ]
+        );
+        assert!(matches!(parse_selection("/tmp/a b.txt"), FilePath::Path(_)));
+        assert!(matches!(parse_selection("content://media/document/12"), FilePath::Url(_)));
+    }
+}
@@ -0,0 +1,359 @@
+use std::sync::{Arc, Mutex};
+
+use rand::RngCore;
+use serde::Serialize;
+use tauri::http::{header, Request, Response, StatusCode};
+use zeroize::Zeroizing;
+
+use crate::mime::{self, Category};
+use crate::photos;
+
+/// Schéma du protocole servant les aperçus en flux (lectures par plages).
+pub const PROTOCOL: &str = "aether-preview";
+
+/// Au-delà, images et PDF ne sont plus transmis en un bloc à la webview.
+pub const INLINE_MAX_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Seul le début d'un fichier texte est affiché.
+pub const TEXT_HEAD_BYTES: usize = 256 * 1024;
+
+/// Le déchiffrement (non découpable, AEAD d'un seul bloc) se fait en mémoire côté backend :
+/// au-delà, aucun aperçu n'est tenté.
+pub const MAX_SOURCE_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Au-delà (largeur × hauteur lues dans l'en-tête), une image n'est pas transmise : une
+/// « bombe de décompression » de quelques Mo occuperait des Go une fois décodée.
+pub const MAX_IMAGE_PIXELS: u64 = 100_000_000;
+
+/// Images affichées telles quelles ; les autres (SVG en tête, qui peut porter du script)
+/// ne sont jamais rendues par la webview.
+const SAFE_IMAGE_TYPES: &[&str] = &["image/jpeg", "image/png", "image/gif", "image/webp", "image/bmp"];
+
+/// Taille maximale d'une réponse du protocole, même pour une plage ouverte (`bytes=N-`).
+const MAX_RANGE_BYTES: u64 = 8 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PreviewKind {
+    Image,
+    Text,
+    Pdf,
+    Video,
+    Audio,
+    Unsupported,
+}
+
+impl PreviewKind {
+    /// Type d'aperçu déduit de l'extension du chemin logique.
+    pub fn from_path(logical_path: &str) -> Self {
+        let name = logical_path.rsplit('/').next().unwrap_or_default();
+        let ext = name.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()).unwrap_or_default();
+        match ext.as_str() {
+            "jpg" | "jpeg" | "png" | "gif" | "webp" | "svg" | "bmp" => PreviewKind::Image,
+            "pdf" => PreviewKind::Pdf,
+            "mp4" | "m4v" | "webm" | "mov" | "ogv" => PreviewKind::Video,
+            "mp3" | "m4a" | "aac" | "wav" | "ogg" | "oga" | "flac" | "opus" => PreviewKind::Audio,
+            "txt" | "md" | "json" | "xml" | "html" | "css" | "js" | "ts" | "tsx" | "jsx" | "py" | "rs" | "go"
+            | "java" | "c" | "cpp" | "h" | "hpp" | "yaml" | "yml" | "ini" | "conf" | "log" | "csv" => {
+                PreviewKind::Text
+            }
+            _ => PreviewKind::Unsupported,
+        }
+    }
+
+}
+
+/// Verdict sur le contenu déchiffré, avant toute transmission à la webview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vetted {
+    /// Contenu conforme à son type, servi sous le type MIME détecté sur ses octets.
+    Serve { kind: PreviewKind, mime: &'static str },
+    /// Format à risque (SVG) : seul son code source est affiché, comme un texte.
+    AsText,
+    /// Pas d'aperçu (`reason` : `mismatch` ou `too_large`).
+    Refused(&'static str),
+}
+
+/// Vérifie que le contenu correspond au type annoncé par l'extension : les octets décident
+/// du type servi, et un fichier déguisé (script nommé `.png`, HTML nommé `.pdf`…) n'atteint
+/// jamais la webview sous un type qu'elle interpréterait.
+pub fn vet(kind: PreviewKind, logical_path: &str, plaintext: &[u8]) -> Vetted {
+    let head = &plaintext[..plaintext.len().min(photos::exif::HEAD_LEN)];
+    let detected = mime::detect(&head[..head.len().min(mime::SNIFF_LEN)], logical_path);
+    match kind {
+        PreviewKind::Image if SAFE_IMAGE_TYPES.contains(&detected) => {
+            let pixels = photos::exif::read(head)
+                .and_then(|info| Some(u64::from(info.width?) * u64::from(info.height?)))
+                .unwrap_or(0);
+            if pixels > MAX_IMAGE_PIXELS {
+                return Vetted::Refused("too_large");
+            }
+            Vetted::Serve { kind, mime: detected }
+        }
+        // SVG et autres images textuelles : le source, jamais le rendu
+        PreviewKind::Image if Category::of(detected) == Category::Documents || detected == "image/svg+xml" => {
+            Vetted::AsText
+        }
+        PreviewKind::Pdf if detected == "application/pdf" => Vetted::Serve { kind, mime: detected },
+        PreviewKind::Video if Category::of(detected) == Category::Video => Vetted::Serve { kind, mime: detected },
+        PreviewKind::Audio if Category::of(detected) == Category::Audio => Vetted::Serve { kind, mime: detected },
+        // Le texte n'est jamais interprété (voir `text_head`)
+        PreviewKind::Text => Vetted::AsText,
+        _ => Vetted::Refused("mismatch"),
+    }
+}
+
+/// Mode de transmission choisi selon le type et la taille en clair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewPlan {
+    /// Contenu complet envoyé à la webview.
+    Inline,
+    /// Seul le début (UTF-8 valide) est envoyé.
+    TextHead,
+    /// Servi par plages via le protocole `aether-preview`.
+    Stream,
+    /// Pas d'aperçu (`reason` : `too_large` ou `unsupported`, voir aussi [`Vetted::Refused`]).
+    Unavailable(&'static str),
+}
+
+impl PreviewPlan {
+    pub fn for_file(kind: PreviewKind, plaintext_size: u64) -> Self {
+        if plaintext_size > MAX_SOURCE_BYTES {
+            return PreviewPlan::Unavailable("too_large");
+        }
+        match kind {
+            PreviewKind::Text => PreviewPlan::TextHead,
+            PreviewKind::Image if plaintext_size <= INLINE_MAX_BYTES => PreviewPlan::Inline,
+            PreviewKind::Image => PreviewPlan::Unavailable("too_large"),
+            PreviewKind::Pdf if plaintext_size <= INLINE_MAX_BYTES => PreviewPlan::Inline,
+            PreviewKind::Pdf | PreviewKind::Video | PreviewKind::Audio => PreviewPlan::Stream,
+            PreviewKind::Unsupported => PreviewPlan::Unavailable("unsupported"),
+        }
+    }
+}
+
+/// Réponse de `preview_open`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum PreviewPayload {
+    Inline {
+        kind: PreviewKind,
+        /// Type détecté sur le contenu (voir [`vet`]), à utiliser tel quel.
+        mime: &'static str,
+        data: Vec<u8>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Text {
+        data: Vec<u8>,
+        truncated: bool,
+        total_size: u64,
+    },
+    Stream {
+        kind: PreviewKind,
+        url: String,
+        size: u64,
+    },
+    Unavailable {
+        kind: PreviewKind,
+        reason: &'static str,
+        size: u64,
+    },
+}
+
+/// Début d'un texte coupé sur une frontière UTF-8 ; `None` si le contenu n'est pas du texte.
+pub fn text_head(data: &[u8]) -> Option<(&[u8], bool)> {
+    let truncated = data.len() > TEXT_HEAD_BYTES;
+    let head = &data[..data.len().min(TEXT_HEAD_BYTES)];
+    match std::str::from_utf8(head) {
+        Ok(_) => Some((head, truncated)),
+        // Caractère multi-octets coupé par la limite : on s'arrête juste avant
+        Err(e) if truncated && e.error_len().is_none() => Some((&head[..e.valid_up_to()], true)),
+        Err(_) => None,
+    }
+}
+
+/// Contenu déchiffré servi par le protocole, identifié par un jeton aléatoire.
+struct Session {
+    token: String,
+    mime: &'static str,
+    data: Arc<Zeroizing<Vec<u8>>>,
+}
+
+/// Aperçu en flux actif ; un seul à la fois, libéré à la fermeture ou au verrouillage.
+#[derive(Default)]
+pub struct PreviewSessions {
+    current: Mutex<Option<Session>>,
+}
+
+impl PreviewSessions {
+    /// Remplace l'aperçu courant ; renvoie l'URL à charger dans la webview.
+    pub fn open(&self, data: Vec<u8>, mime: &'static str) -> String {
+        let mut bytes = [0u8; 16];
+        rand::rngs::OsRng.fill_bytes(&mut bytes);
+        let token = hex::encode(bytes);
+        let url = url_for(&token);
+        if let Ok(mut current) = self.current.lock() {
+            *current = Some(Session {
+                token,
+                mime,
+                data: Arc::new(Zeroizing::new(data)),
+            });
+        }
+        url
+    }
+
+    pub fn close(&self) {
+        if let Ok(mut current) = self.current.lock() {
+            *current = None;
+        }
+    }
+
+    /// Répond à une requête du protocole (`GET /<jeton>` avec ou sans `Range`).
+    pub fn respond(&self, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+        let token = request.uri().path().trim_start_matches('/');
+        let session = self.current.lock().ok().and_then(|current| {
+            current
+                .as_ref()
+                .filter(|session| session.token == token)
+                .map(|session| (session.mime, Arc::clone(&session.data)))
+        });
+        let Some((mime, data)) = session else {
+            return status(StatusCode::NOT_FOUND);
+        };
+
+        let len = data.len() as u64;
+        let range = request.headers().get(header::RANGE).and_then(|v| v.to_str().ok());
+        let (start, end) = match range.map(|r| parse_range(r, len)) {
+            Some(Some(bounds)) => bounds,
+            Some(None) => {
+                return Response::builder()
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+                    .body(Vec::new())
+                    .unwrap_or_default();
+            }
+            None if len <= MAX_RANGE_BYTES => {
+                return Response::builder()
+                    .header(header::CONTENT_TYPE, mime)
+                    .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+                    .header(header::ACCEPT_RANGES, "bytes")
+                    .body(data.to_vec())
+                    .unwrap_or_default();
+            }
+            None => (0, MAX_RANGE_BYTES - 1),
+        };
+        Response::builder()
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_TYPE, mime)
+            .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+            .header(header::ACCEPT_RANGES, "bytes")
+            .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, len))
+            .body(data[start as usize..=end as usize].to_vec())
+            .unwrap_or_default()
+    }
+}
+
+/// Plage `bytes=a-b`, `bytes=a-` ou `bytes=-n` bornée à `len` et à `MAX_RANGE_BYTES`.
+///
+/// Une seule plage est prise en charge ; `None` si elle n'est pas satisfaisable.
+pub fn parse_range(header: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = header.trim().strip_prefix("bytes=")?;
+    let (start, end) = spec.split(',').next()?.trim().split_once('-')?;
+    let (start, end) = match (start.parse::<u64>().ok(), end.parse::<u64>().ok()) {
+        (Some(start), Some(end)) if start <= end => (start, end.min(len.checked_sub(1)?)),
+        (Some(start), None) if end.is_empty() => (start, len.checked_sub(1)?),
+        (None, Some(suffix)) if start.is_empty() && suffix > 0 => (len.saturating_sub(suffix), len.checked_sub(1)?),
+        _ => return None,
+    };
+    if start >= len || start > end {
+        return None;
+    }
+    Some((start, end.min(start + MAX_RANGE_BYTES - 1)))
+}
+
+/// Windows sert les protocoles personnalisés sous `http://<schéma>.localhost`.
+fn url_for(token: &str) -> String {
+    if cfg!(windows) {
+        format!("http://{}.localhost/{}", PROTOCOL, token)
+    } else {
+        format!("{}://localhost/{}", PROTOCOL, token)
+    }
+}
+
+fn status(code: StatusCode) -> Response<Vec<u8>> {
+    Response::builder().status(code).body(Vec::new()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_depends_on_kind_and_size() {
+        let big = INLINE_MAX_BYTES + 1;
+        assert_eq!(PreviewPlan::for_file(PreviewKind::Image, 1024), PreviewPlan::Inline);
+        assert_eq!(PreviewPlan::for_file(PreviewKind::Image, big), PreviewPlan::Unavailable("too_large"));
+        assert_eq!(PreviewPlan::for_file(PreviewKind::Pdf, big), PreviewPlan::Stream);
+        assert_eq!(PreviewPlan::for_file(PreviewKind::Video, 1024), PreviewPlan::Stream);
+        assert_eq!(PreviewPlan::for_file(PreviewKind::Text, big), PreviewPlan::TextHead);
+        assert_eq!(
+            PreviewPlan::for_file(PreviewKind::Video, MAX_SOURCE_BYTES + 1),
+            PreviewPlan::Unavailable("too_large")
+        );
+        assert_eq!(PreviewKind::from_path("/films/Vacances.MP4"), PreviewKind::Video);
+        assert_eq!(PreviewKind::from_path("/archive.tar.gz"), PreviewKind::Unsupported);
+    }
+
+    #[test]
+    fn content_must_match_the_announced_type() {
+        let png = |width: u32, height: u32| {
+            let mut png = b"\x89PNG\r\n\x1A\n\0\0\0\x0DIHDR".to_vec();
+            png.extend_from_slice(&width.to_be_bytes());
+            png.extend_from_slice(&height.to_be_bytes());
+            png.extend_from_slice(&[8, 6, 0, 0, 0, 0, 0, 0, 0]);
+            png
+        };
+        assert_eq!(
+            vet(PreviewKind::Image, "/photo.png", &png(800, 600)),
+            Vetted::Serve { kind: PreviewKind::Image, mime: "image/png" }
+        );
+        // Extension trompeuse : le type servi est celui des octets
+        assert_eq!(
+            vet(PreviewKind::Image, "/photo.jpg", &png(800, 600)),
+            Vetted::Serve { kind: PreviewKind::Image, mime: "image/png" }
+        );
+        assert_eq!(vet(PreviewKind::Image, "/bombe.png", &png(50_000, 50_000)), Vetted::Refused("too_large"));
+
+        let svg = b"<svg xmlns=\"http://www.w3.org/2000/svg\"><script>alert(1)</script></svg>";
+        assert_eq!(vet(PreviewKind::from_path("/logo.svg"), "/logo.svg", svg), Vetted::AsText);
+        assert_eq!(vet(PreviewKind::Pdf, "/facture.pdf", b"<html><script>alert(1)</script>"), Vetted::Refused("mismatch"));
+        assert_eq!(vet(PreviewKind::Pdf, "/facture.pdf", b"%PDF-1.7\n"), Vetted::Serve { kind: PreviewKind::Pdf, mime: "application/pdf" });
+        assert_eq!(vet(PreviewKind::Video, "/film.mp4", b"\x1A\x45\xDF\xA3"), Vetted::Serve { kind: PreviewKind::Video, mime: "video/x-matroska" });
+        assert_eq!(vet(PreviewKind::Audio, "/son.mp3", b"%PDF-1.7"), Vetted::Refused("mismatch"));
+    }
+
+    #[test]
+    fn ranges_are_bounded() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(parse_range("bytes=900-", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=0-5000", 1000), Some((0, 999)));
+        assert_eq!(parse_range("bytes=0-", u64::MAX), Some((0, MAX_RANGE_BYTES - 1)));
+        assert_eq!(parse_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_range("bytes=5-2", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+        assert_eq!(parse_range("bytes=0-0", 0), None);
+    }
+
+    #[test]
+    fn text_head_stops_on_char_boundary() {
+        let mut data = vec![b'a'; TEXT_HEAD_BYTES - 1];
+        data.extend_from_slice("é".as_bytes());
+        let (head, truncated) = text_head(&data).unwrap();
+        assert!(truncated);
+        assert_eq!(head.len(), TEXT_HEAD_BYTES - 1);
+
+        assert_eq!(text_head(b"short"), Some((&b"short"[..], false)));
+        assert!(text_head(&[0xff, 0xfe, 0x00]).is_none());
+    }
+}
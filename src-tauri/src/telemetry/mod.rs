@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::events::TransferDirection;
+
+/// Compteurs agrégés, jamais envoyés automatiquement.
+///
+/// Rien d'identifiant n'y entre : ni chemin, ni FileId, ni taille exacte, ni horodatage
+/// d'événement. L'utilisateur relit et exporte lui-même le fichier s'il souhaite le partager.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metrics {
+    pub enabled: bool,
+    /// `"upload:1-10MB"` → nombre de transferts.
+    pub transfers: BTreeMap<String, u64>,
+    /// Code d'erreur stable (`CommandError::code`) → occurrences.
+    pub errors: BTreeMap<String, u64>,
+    /// Fonctionnalité utilisée → occurrences.
+    pub features: BTreeMap<String, u64>,
+}
+
+/// Compteurs persistés dans un fichier JSON local (`telemetry.json`).
+pub struct Telemetry {
+    path: PathBuf,
+    metrics: Mutex<Metrics>,
+}
+
+static TELEMETRY: OnceLock<Telemetry> = OnceLock::new();
+
+impl Telemetry {
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let metrics = fs::read(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_slice(&raw).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            metrics: Mutex::new(metrics),
+        }
+    }
+
+    pub fn snapshot(&self) -> Metrics {
+        self.metrics.lock().map(|m| m.clone()).unwrap_or_default()
+    }
+
+    /// Active ou désactive la collecte. Désactiver efface les compteurs existants.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.update(|m| {
+            if !enabled {
+                *m = Metrics::default();
+            }
+            m.enabled = enabled;
+        });
+    }
+
+    pub fn reset(&self) {
+        self.update(|m| {
+            *m = Metrics {
+                enabled: m.enabled,
+                ..Default::default()
+            }
+        });
+    }
+
+    fn record(&self, f: impl FnOnce(&mut Metrics)) {
+        self.update(|m| {
+            if m.enabled {
+                f(m);
+            }
+        });
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Metrics)) {
+        let Ok(mut metrics) = self.metrics.lock() else {
+            return;
+        };
+        f(&mut metrics);
+        let result = serde_json::to_vec_pretty(&*metrics)
+            .map_err(|e| e.to_string())
+            .and_then(|raw| fs::write(&self.path, raw).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            log::warn!("Failed to persist telemetry counters: {}", e);
+        }
+    }
+}
+
+/// Tranche de taille (jamais la taille exacte).
+pub fn size_bucket(bytes: u64) -> &'static str {
+    const MB: u64 = 1024 * 1024;
+    match bytes {
+        b if b < MB => "<1MB",
+        b if b < 10 * MB => "1-10MB",
+        b if b < 100 * MB => "10-100MB",
+        b if b < 1024 * MB => "100MB-1GB",
+        _ => ">=1GB",
+    }
+}
+
+/// Initialise la collecte pour le processus (à appeler une fois au démarrage).
+pub fn init(path: impl AsRef<Path>) {
+    let _ = TELEMETRY.set(Telemetry::load(path));
+}
+
+pub fn get() -> Option<&'static Telemetry> {
+    TELEMETRY.get()
+}
+
+pub fn record_transfer(direction: TransferDirection, bytes: u64) {
+    if let Some(telemetry) = get() {
+        let direction = match direction {
+            TransferDirection::Upload => "upload",
+            TransferDirection::Download => "download",
+        };
+        let key = format!("{}:{}", direction, size_bucket(bytes));
+        telemetry.record(|m| *m.transfers.entry(key).or_default() += 1);
+    }
+}
+
+pub fn record_error(code: &str) {
+    if let Some(telemetry) = get() {
+        telemetry.record(|m| *m.errors.entry(code.to_string()).or_default() += 1);
+    }
+}
+
+pub fn record_feature(feature: &'static str) {
+    if let Some(telemetry) = get() {
+        telemetry.record(|m| *m.features.entry(feature.to_string()).or_default() += 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn nothing_is_recorded_until_opted_in() {
+        let dir = tempdir().unwrap();
+        let telemetry = Telemetry::load(dir.path().join("telemetry.json"));
+
+        telemetry.record(|m| *m.errors.entry("io_error".to_string()).or_default() += 1);
+        assert!(telemetry.snapshot().errors.is_empty());
+
+        telemetry.set_enabled(true);
+        telemetry.record(|m| *m.errors.entry("io_error".to_string()).or_default() += 1);
+        assert_eq!(telemetry.snapshot().errors["io_error"], 1);
+
+        // Les compteurs survivent au redémarrage ; les désactiver les efface
+        let reloaded = Telemetry::load(dir.path().join("telemetry.json"));
+        assert_eq!(reloaded.snapshot(), telemetry.snapshot());
+        reloaded.set_enabled(false);
+        assert_eq!(reloaded.snapshot(), Metrics::default());
+    }
+
+    #[test]
+    fn sizes_are_bucketed() {
+        assert_eq!(size_bucket(10), "<1MB");
+        assert_eq!(size_bucket(5 * 1024 * 1024), "1-10MB");
+        assert_eq!(size_bucket(2 * 1024 * 1024 * 1024), ">=1GB");
+    }
+}
@@ -0,0 +1,308 @@
+//! Icône de la barre système (bureau uniquement) ; sur mobile, les fonctions publiques
+//! ne font rien.
+
+use std::collections::{HashMap, VecDeque};
+#[cfg(desktop)]
+use std::sync::Mutex;
+
+#[cfg(desktop)]
+use tauri::menu::{IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+#[cfg(desktop)]
+use tauri::tray::TrayIconBuilder;
+#[cfg(desktop)]
+use tauri::{Manager, Wry};
+use tauri::AppHandle;
+
+use crate::events::{AppEvent, SyncStatus};
+
+/// Identifiant de l'icône de la barre système.
+pub const TRAY_ID: &str = "main";
+
+/// Nombre d'éléments récents listés dans le menu.
+pub const RECENT_ITEMS: usize = 5;
+
+/// Action déclenchée depuis le menu de la barre système.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayAction {
+    ShowWindow,
+    TogglePause,
+    LockVault,
+    Quit,
+}
+
+#[cfg_attr(mobile, allow(dead_code))]
+impl TrayAction {
+    const fn id(self) -> &'static str {
+        match self {
+            TrayAction::ShowWindow => "show",
+            TrayAction::TogglePause => "toggle_pause",
+            TrayAction::LockVault => "lock",
+            TrayAction::Quit => "quit",
+        }
+    }
+
+    /// Un élément récent ouvre simplement la fenêtre principale.
+    fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "show" => Some(TrayAction::ShowWindow),
+            "toggle_pause" => Some(TrayAction::TogglePause),
+            "lock" => Some(TrayAction::LockVault),
+            "quit" => Some(TrayAction::Quit),
+            _ if id.starts_with("recent:") => Some(TrayAction::ShowWindow),
+            _ => None,
+        }
+    }
+}
+
+/// État affiché dans la barre système, alimenté par les événements backend.
+#[derive(Debug, Default)]
+pub struct TrayStatus {
+    sync: Option<SyncStatus>,
+    /// Transferts en cours : `file_id` → (octets transférés, octets totaux).
+    transfers: HashMap<String, (u64, u64)>,
+    recent: VecDeque<String>,
+    paused: bool,
+}
+
+impl TrayStatus {
+    /// Applique un événement ; renvoie `true` si le menu doit être reconstruit.
+    pub fn apply(&mut self, event: &AppEvent) -> bool {
+        match event {
+            AppEvent::TransferProgress {
+                file_id,
+                bytes_done,
+                bytes_total,
+                ..
+            } => {
+                if bytes_done >= bytes_total {
+                    self.transfers.remove(file_id).is_some()
+                } else {
+                    self.transfers.insert(file_id.clone(), (*bytes_done, *bytes_total)).is_none()
+                }
+            }
+            AppEvent::SyncState { status, .. } => {
+                self.sync = Some(*status);
+                true
+            }
+            // Les chemins récents sont des métadonnées du coffre : ils disparaissent au verrouillage
+            AppEvent::VaultLocked => {
+                self.transfers.clear();
+                self.recent.clear();
+                self.sync = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn push_recent(&mut self, logical_path: &str) {
+        self.recent.retain(|path| path != logical_path);
+        self.recent.push_front(logical_path.to_string());
+        self.recent.truncate(RECENT_ITEMS);
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn status_line(&self) -> String {
+        if self.paused {
+            return "Synchronisation suspendue".to_string();
+        }
+        if !self.transfers.is_empty() {
+            let (done, total) = self
+                .transfers
+                .values()
+                .fold((0u64, 0u64), |(d, t), (done, total)| (d + done, t + total));
+            let percent = if total == 0 { 0 } else { done * 100 / total };
+            return format!("{} transfert(s) en cours ({} %)", self.transfers.len(), percent);
+        }
+        match self.sync {
+            Some(SyncStatus::Syncing) => "Synchronisation…".to_string(),
+            Some(SyncStatus::Failed) => "Synchronisation échouée".to_string(),
+            Some(SyncStatus::Idle) | None => "À jour".to_string(),
+        }
+    }
+}
+
+#[cfg(desktop)]
+#[derive(Default)]
+struct TrayState {
+    status: Mutex<TrayStatus>,
+}
+
+/// Crée l'icône de la barre système ; `on_action` reçoit les clics du menu.
+#[cfg(desktop)]
+pub fn init<F>(app: &AppHandle, on_action: F) -> tauri::Result<()>
+where
+    F: Fn(&AppHandle, TrayAction) + Send + Sync + 'static,
+{
+    app.manage(TrayState::default());
+    let menu = build_menu(app, &TrayStatus::default())?;
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip("Aether Drive")
+        .on_menu_event(move |app, event| {
+            if let Some(action) = TrayAction::from_id(event.id().as_ref()) {
+                on_action(app, action);
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    Ok(())
+}
+
+#[cfg(mobile)]
+pub fn init<F>(_app: &AppHandle, _on_action: F) -> tauri::Result<()>
+where
+    F: Fn(&AppHandle, TrayAction) + Send + Sync + 'static,
+{
+    Ok(())
+}
+
+/// Met à jour la barre système pour un événement émis vers le frontend.
+pub fn on_event(app: &AppHandle, event: &AppEvent) {
+    update(app, |status| status.apply(event));
+}
+
+/// Ajoute un fichier envoyé ou téléchargé aux éléments récents.
+pub fn push_recent(app: &AppHandle, logical_path: &str) {
+    update(app, |status| {
+        status.push_recent(logical_path);
+        true
+    });
+}
+
+pub fn set_paused(app: &AppHandle, paused: bool) {
+    update(app, |status| {
+        status.set_paused(paused);
+        true
+    });
+}
+
+/// Sans icône (non initialisée ou plateforme sans barre système), ne fait rien.
+#[cfg(desktop)]
+fn update(app: &AppHandle, change: impl FnOnce(&mut TrayStatus) -> bool) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let Ok(mut status) = state.status.lock() else {
+        return;
+    };
+    if !change(&mut status) {
+        return;
+    }
+    let result = build_menu(app, &status).and_then(|menu| {
+        tray.set_menu(Some(menu))?;
+        tray.set_tooltip(Some(format!("Aether Drive — {}", status.status_line())))
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to refresh tray menu: {}", e);
+    }
+}
+
+#[cfg(mobile)]
+fn update(_app: &AppHandle, _change: impl FnOnce(&mut TrayStatus) -> bool) {}
+
+#[cfg(desktop)]
+fn build_menu(app: &AppHandle, status: &TrayStatus) -> tauri::Result<Menu<Wry>> {
+    let status_item = MenuItem::with_id(app, "status", status.status_line(), false, None::<&str>)?;
+
+    let recent_items = status
+        .recent
+        .iter()
+        .enumerate()
+        .map(|(i, path)| MenuItem::with_id(app, format!("recent:{}", i), path, true, None::<&str>))
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let recent_refs: Vec<&dyn IsMenuItem<Wry>> = recent_items.iter().map(|item| item as &dyn IsMenuItem<Wry>).collect();
+    let recent = Submenu::with_items(app, "Éléments récents", !recent_refs.is_empty(), &recent_refs)?;
+
+    let pause_label = if status.paused {
+        "Reprendre la synchronisation"
+    } else {
+        "Suspendre la synchronisation"
+    };
+    let pause = MenuItem::with_id(app, TrayAction::TogglePause.id(), pause_label, true, None::<&str>)?;
+    let lock = MenuItem::with_id(app, TrayAction::LockVault.id(), "Verrouiller le coffre", true, None::<&str>)?;
+    let show = MenuItem::with_id(app, TrayAction::ShowWindow.id(), "Ouvrir Aether Drive", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, TrayAction::Quit.id(), "Quitter", true, None::<&str>)?;
+
+    Menu::with_items(
+        app,
+        &[
+            &status_item,
+            &PredefinedMenuItem::separator(app)?,
+            &recent,
+            &pause,
+            &lock,
+            &PredefinedMenuItem::separator(app)?,
+            &show,
+            &quit,
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::TransferDirection;
+
+    fn transfer(file_id: &str, bytes_done: u64, bytes_total: u64) -> AppEvent {
+        AppEvent::TransferProgress {
+            file_id: file_id.to_string(),
+            direction: TransferDirection::Upload,
+            bytes_done,
+            bytes_total,
+            bytes_per_second: 0,
+            eta_seconds: None,
+        }
+    }
+
+    #[test]
+    fn status_line_tracks_transfers_and_pause() {
+        let mut status = TrayStatus::default();
+        assert_eq!(status.status_line(), "À jour");
+
+        assert!(status.apply(&transfer("a", 0, 100)));
+        assert!(!status.apply(&transfer("a", 50, 100)));
+        assert!(status.apply(&transfer("b", 0, 100)));
+        assert_eq!(status.status_line(), "2 transfert(s) en cours (25 %)");
+
+        status.apply(&transfer("a", 100, 100));
+        status.apply(&transfer("b", 100, 100));
+        status.apply(&AppEvent::SyncState { status: SyncStatus::Failed, remote_count: None });
+        assert_eq!(status.status_line(), "Synchronisation échouée");
+
+        status.set_paused(true);
+        assert_eq!(status.status_line(), "Synchronisation suspendue");
+    }
+
+    #[test]
+    fn recent_items_are_bounded_and_cleared_on_lock() {
+        let mut status = TrayStatus::default();
+        for i in 0..RECENT_ITEMS + 2 {
+            status.push_recent(&format!("/docs/{}.txt", i));
+        }
+        status.push_recent("/docs/3.txt");
+        assert_eq!(status.recent.len(), RECENT_ITEMS);
+        assert_eq!(status.recent[0], "/docs/3.txt");
+        assert_eq!(status.recent.iter().filter(|p| *p == "/docs/3.txt").count(), 1);
+
+        assert!(status.apply(&AppEvent::VaultLocked));
+        assert!(status.recent.is_empty());
+    }
+
+    #[test]
+    fn menu_ids_round_trip() {
+        for action in [TrayAction::ShowWindow, TrayAction::TogglePause, TrayAction::LockVault, TrayAction::Quit] {
+            assert_eq!(TrayAction::from_id(action.id()), Some(action));
+        }
+        assert_eq!(TrayAction::from_id("recent:2"), Some(TrayAction::ShowWindow));
+        assert_eq!(TrayAction::from_id("status"), None);
+    }
+}
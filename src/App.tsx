@@ -3,6 +3,7 @@ import { LoginPage } from './pages/LoginPage'
 import { UnlockPage } from './pages/UnlockPage'
 import { DashboardPage } from './pages/DashboardPage'
 import { WayneClient } from './wayne_client'
+import { listenAppEvents } from './utils/appEvents'
 import type { KeyEnvelopeDto } from './wayne_dto'
 import './App.css'
 
@@ -74,6 +75,19 @@ function App() {
     }
   }, [wayneBaseUrl])
 
+  // Verrouillage depuis la barre système : retour à l'écran de déverrouillage
+  useEffect(() => {
+    const unlisten = listenAppEvents((event) => {
+      if (event.type === 'vaultLocked') {
+        setIsUnlocked(false)
+        setCurrentPage((page) => (page === 'dashboard' ? 'unlock' : page))
+      }
+    })
+    return () => {
+      unlisten.then((fn) => fn())
+    }
+  }, [])
+
   const handleWayneLoginSuccess = (client: WayneClient, envelopeId: string | null) => {
     setWayneClient(client)
     setWayneEnvelopeId(envelopeId)
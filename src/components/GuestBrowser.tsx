@@ -0,0 +1,117 @@
+import { useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { formatError } from '../utils/commandError'
+import { invokeAllPages } from '../utils/paging'
+import { Card } from './Card'
+import { Button } from './Button'
+import { Input } from './Input'
+import { StatusMessage } from './StatusMessage'
+
+type GuestEntry = {
+  logical_path: string
+  encrypted_size: number
+  is_folder: boolean
+  in_trash: boolean
+}
+
+function formatSize(bytes: number): string {
+  if (bytes < 1024) return `${bytes} B`
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(2)} KB`
+  if (bytes < 1024 * 1024 * 1024) return `${(bytes / (1024 * 1024)).toFixed(2)} MB`
+  return `${(bytes / (1024 * 1024 * 1024)).toFixed(2)} GB`
+}
+
+/**
+ * Mode invité : recherche dans les noms et tailles des fichiers avec la phrase d'accès invité,
+ * sans déverrouiller le coffre. Le contenu des fichiers reste inaccessible.
+ */
+export function GuestBrowser() {
+  const [passphrase, setPassphrase] = useState('')
+  const [isOpen, setIsOpen] = useState(false)
+  const [query, setQuery] = useState('')
+  const [results, setResults] = useState<GuestEntry[] | null>(null)
+  const [status, setStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  const handleOpen = async () => {
+    setStatus(null)
+    try {
+      await invoke('guest_mode_open', { passphrase })
+      setPassphrase('')
+      setIsOpen(true)
+    } catch (e) {
+      setStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleSearch = async () => {
+    setStatus(null)
+    try {
+      setResults((await invokeAllPages<GuestEntry>('guest_mode_search', { query })).items)
+    } catch (e) {
+      setStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleClose = async () => {
+    try {
+      await invoke('guest_mode_close')
+    } finally {
+      setIsOpen(false)
+      setQuery('')
+      setResults(null)
+    }
+  }
+
+  return (
+    <Card title="Mode invité">
+      {!isOpen ? (
+        <>
+          <Input
+            label="Phrase d'accès invité"
+            type="password"
+            value={passphrase}
+            onChange={(e) => setPassphrase(e.target.value)}
+            helperText="Affiche les noms et tailles des fichiers sans déverrouiller le coffre. Le contenu reste chiffré."
+          />
+          <div className="unlock-actions">
+            <Button variant="secondary" onClick={handleOpen} disabled={!passphrase} fullWidth>
+              Consulter sans déverrouiller
+            </Button>
+          </div>
+        </>
+      ) : (
+        <>
+          <Input
+            label="Rechercher un fichier"
+            value={query}
+            onChange={(e) => setQuery(e.target.value)}
+            onKeyDown={(e) => e.key === 'Enter' && handleSearch()}
+            placeholder="Ex. facture 2025"
+          />
+          {results && results.length === 0 && <p>Aucun fichier ne correspond.</p>}
+          {results && results.length > 0 && (
+            <ul>
+              {results.map((entry) => (
+                <li key={`${entry.in_trash ? 'trash' : 'live'}-${entry.logical_path}`}>
+                  {entry.is_folder ? '📁' : '📄'} {entry.logical_path}
+                  {!entry.is_folder && ` · ${formatSize(entry.encrypted_size)}`}
+                  {entry.in_trash && ' · dans la corbeille'}
+                </li>
+              ))}
+            </ul>
+          )}
+          <div className="unlock-actions">
+            <Button variant="primary" onClick={handleSearch} fullWidth>
+              Rechercher
+            </Button>
+            <Button variant="secondary" onClick={handleClose} fullWidth>
+              Quitter le mode invité
+            </Button>
+          </div>
+        </>
+      )}
+
+      {status && <StatusMessage type={status.type} message={status.message} onDismiss={() => setStatus(null)} />}
+    </Card>
+  )
+}
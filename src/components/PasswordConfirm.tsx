@@ -0,0 +1,78 @@
+import { useRef, useState } from 'react'
+import { Card } from './Card'
+import { Button } from './Button'
+import { Input } from './Input'
+import './PasswordConfirm.css'
+
+type PendingRequest = {
+  description: string
+  resolve: (password: string | null) => void
+}
+
+/**
+ * Demande de mot de passe avant une commande destructive.
+ *
+ * `askPassword` affiche la boîte et se résout avec le mot de passe saisi, ou `null` si
+ * l'utilisateur annule ; la vérification elle-même est faite par le backend.
+ */
+export function usePasswordConfirm(): [React.ReactNode, (description: string) => Promise<string | null>] {
+  const [pending, setPending] = useState<PendingRequest | null>(null)
+  const [password, setPassword] = useState('')
+  const pendingRef = useRef<PendingRequest | null>(null)
+
+  const close = (value: string | null) => {
+    pendingRef.current?.resolve(value)
+    pendingRef.current = null
+    setPending(null)
+    setPassword('')
+  }
+
+  const askPassword = (description: string) =>
+    new Promise<string | null>((resolve) => {
+      // Une demande encore ouverte est annulée par la nouvelle
+      pendingRef.current?.resolve(null)
+      pendingRef.current = { description, resolve }
+      setPending(pendingRef.current)
+    })
+
+  const element = pending && (
+    <div
+      className="password-confirm-overlay"
+      onClick={(e) => {
+        // Ne ferme pas la fenêtre (paramètres) au-dessus de laquelle la boîte s'affiche
+        e.stopPropagation()
+        close(null)
+      }}
+    >
+      <div className="password-confirm" onClick={(e) => e.stopPropagation()}>
+        <Card title="Confirmer avec ton mot de passe">
+          <p className="password-confirm-description">{pending.description}</p>
+          <form
+            onSubmit={(e) => {
+              e.preventDefault()
+              if (password) close(password)
+            }}
+          >
+            <Input
+              label="Mot de passe maître"
+              type="password"
+              value={password}
+              onChange={(e) => setPassword(e.target.value)}
+              autoFocus
+            />
+            <div className="password-confirm-actions">
+              <Button type="button" variant="secondary" onClick={() => close(null)}>
+                Annuler
+              </Button>
+              <Button type="submit" variant="primary" disabled={!password}>
+                Confirmer
+              </Button>
+            </div>
+          </form>
+        </Card>
+      </div>
+    </div>
+  )
+
+  return [element, askPassword]
+}
@@ -1,10 +1,15 @@
-import { useState } from 'react'
+import { useEffect, useState } from 'react'
 import { invoke } from '@tauri-apps/api/core'
+import { formatError, isCommandError } from '../utils/commandError'
+import { listenAppEvents } from '../utils/appEvents'
+import { invokeAllPages } from '../utils/paging'
+import { dryRun, invokeElevated, invokeFresh, type DryRunReport } from '../utils/elevation'
 import { WayneClient } from '../wayne_client'
 import { Card } from './Card'
 import { Button } from './Button'
 import { Input } from './Input'
 import { StatusMessage } from './StatusMessage'
+import { usePasswordConfirm } from './PasswordConfirm'
 import './SettingsModal.css'
 
 interface SettingsModalProps {
@@ -21,6 +26,255 @@ type ChangePasswordResponse = {
   }
 }
 
+type MkekEnvelope = {
+  password_salt: number[]
+  mkek: {
+    nonce: number[]
+    payload: number[]
+  }
+}
+
+const BOOTSTRAP_STORAGE_KEY = 'aether_drive_bootstrap_data'
+
+type LogVerbosity = 'off' | 'error' | 'warn' | 'info' | 'debug' | 'trace'
+
+type LogSettings = {
+  privacy: 'redacted' | 'full'
+  level: LogVerbosity
+  modules: Record<string, LogVerbosity>
+}
+
+const LOG_LEVEL_LABELS_FR: Array<[LogVerbosity, string]> = [
+  ['error', 'Erreurs'],
+  ['warn', 'Avertissements'],
+  ['info', 'Normal'],
+  ['debug', 'Détaillé'],
+]
+
+const LOG_VERBOSITIES: LogVerbosity[] = ['off', 'error', 'warn', 'info', 'debug', 'trace']
+
+type LogLevel = Exclude<LogVerbosity, 'off'>
+
+type LogRecord = {
+  timestamp_ms: number
+  level: LogLevel
+  module: string
+  message: string
+}
+
+type LogQuery = {
+  module?: string
+  level?: LogLevel
+  since_ms?: number
+  until_ms?: number
+  limit?: number
+}
+
+// Période consultée, en millisecondes (`null` : tout le journal)
+const LOG_PERIODS: Array<[string, number | null]> = [
+  ['Dernière heure', 60 * 60 * 1000],
+  ['Dernières 24 h', 24 * 60 * 60 * 1000],
+  ['7 derniers jours', 7 * 24 * 60 * 60 * 1000],
+  ['Tout', null],
+]
+
+type TelemetryMetrics = {
+  enabled: boolean
+  transfers: Record<string, number>
+  errors: Record<string, number>
+  features: Record<string, number>
+}
+
+type QuotaStatus = {
+  limit_bytes: number | null
+  used_bytes: number
+  remaining_bytes: number | null
+  // Libéré en vidant la corbeille
+  trash_bytes: number
+  // Part de l'espace utilisé au-delà de laquelle vider la corbeille est suggéré (0 : jamais)
+  trash_suggest_percent: number
+}
+
+type MisnamedObject = { key: string; expected: string }
+
+type KeyAudit = {
+  checked: number
+  misnamed: MisnamedObject[]
+  // Présents aussi sous le nom attendu avec un autre contenu
+  duplicates: MisnamedObject[]
+  unrecognized: string[]
+  repaired: number
+}
+
+type CacheReport = {
+  policy: { max_bytes: number; prewarm_count: number }
+  cached_files: number
+  cached_bytes: number
+  evicted: number
+  prewarmed: number
+}
+
+type RuleAction =
+  | { type: 'import_folder'; local_dir: string; vault_folder: string }
+  | { type: 'auto_trash'; vault_folder: string; older_than_days: number }
+
+type Rule = {
+  id: number
+  name: string
+  enabled: boolean
+  action: RuleAction
+  last_run: number | null
+  last_error: string | null
+}
+
+type RulesReport = {
+  rules_run: number
+  uploaded: number
+  trashed: number
+  failures: Array<{ rule_id: number; name: string; detail: string }>
+}
+
+type HookEvent = 'upload_complete' | 'download_complete' | 'conflict' | 'integrity_failure' | 'trash_purged'
+
+type HookTarget = { type: 'script'; program: string } | { type: 'webhook'; url: string; secret: string }
+
+type Hook = {
+  id: number
+  name: string
+  enabled: boolean
+  events: HookEvent[]
+  target: HookTarget
+  last_fired: number | null
+  last_error: string | null
+}
+
+const HOOK_EVENT_LABELS_FR: Record<HookEvent, string> = {
+  upload_complete: 'Envoi terminé',
+  download_complete: 'Téléchargement terminé',
+  conflict: 'Conflit',
+  integrity_failure: "Échec d'intégrité",
+  trash_purged: 'Corbeille purgée',
+}
+
+type IndexBackupStatus = {
+  policy: { enabled: boolean; retention: number }
+  state: { backed_up_seq: number; last_backup_at: number | null; pending_since: number | null }
+}
+
+type IndexBackup = { key: string; created_at: number; size: number }
+
+type CloneReport = {
+  target: string
+  cloned_at: number
+  objects: number
+  bytes: number
+  files: number
+  verified_files: number
+  pending_files: number
+  failures: Array<{ key: string; reason: string }>
+}
+
+type Workspace = { id: string; name: string }
+
+type CredentialsStatus = { delegated: boolean; issuer_url: string | null; expires_at: number | null }
+
+type DiagnosticCheck = { name: string; status: 'ok' | 'warning' | 'failed' | 'skipped'; detail: string }
+
+const DIAGNOSTIC_LABELS_FR: Record<string, string> = {
+  keychain: 'Trousseau du système',
+  database: 'Index local',
+  merkle: 'Arbre de Merkle',
+  remote: 'Connexion au stockage',
+  clock: 'Horloge',
+  disk: 'Espace disque',
+  cache: 'Cache local',
+}
+
+const DIAGNOSTIC_ICONS: Record<DiagnosticCheck['status'], string> = {
+  ok: '✅',
+  warning: '⚠️',
+  failed: '❌',
+  skipped: '⏭️',
+}
+
+type WorkspaceList = { workspaces: Workspace[]; active: string }
+
+type VaultSnapshot = { id: number; name: string; created_at: number; file_count: number }
+
+type SnapshotDiff = {
+  added: Array<{ file_id: string; logical_path: string }>
+  removed: Array<{ file_id: string; logical_path: string }>
+  moved: Array<{ file_id: string; from: string; to: string }>
+}
+
+// Élément paginé de `snapshot_diff`
+type SnapshotChange =
+  | { kind: 'added' | 'removed'; file_id: string; logical_path: string }
+  | { kind: 'moved'; file_id: string; from: string; to: string }
+
+type MerkleRootRecord = { seq: number; root: string; operation: string; recorded_at: number; chain_valid: boolean }
+
+type IntegrityTimeline = {
+  records: MerkleRootRecord[]
+  current_root: string
+  unexpected_change: boolean
+  broken_chain: boolean
+}
+
+const MERKLE_OPERATION_LABELS_FR: Record<string, string> = {
+  upsert: 'Ajout ou modification',
+  remove: 'Suppression',
+  trash: 'Mise à la corbeille',
+  restore: 'Restauration',
+  rename_folder: 'Renommage de dossier',
+  snapshot_restore: "Restauration d'instantané",
+  replace: 'Remplacement',
+  restore_version: 'Restauration de version',
+}
+
+type QuarantinedFile = {
+  file_id: string
+  logical_path: string | null
+  trashed: boolean
+  is_version: boolean
+  reason: string
+  source: 'scrub' | 'download' | 'restore'
+  detected_at: number
+  versions: Array<{ id: string; replaced_at: number }>
+  local_copy: boolean
+}
+
+const QUARANTINE_SOURCE_LABELS_FR: Record<QuarantinedFile['source'], string> = {
+  scrub: 'vérification',
+  download: 'téléchargement',
+  restore: 'restauration depuis la corbeille',
+}
+
+type HealthStatus = 'good' | 'warning' | 'critical'
+
+type HealthReport = {
+  checked_at: number
+  score: number
+  status: HealthStatus
+  categories: Array<{ name: string; status: HealthStatus; score: number; detail: string }>
+}
+
+const HEALTH_LABELS_FR: Record<string, string> = {
+  integrity: "Vérification d'intégrité",
+  tampered_rows: 'Entrées altérées (HMAC)',
+  orphans: 'Fichiers sans copie distante',
+  backup: "Sauvegardes de l'index",
+  trash: 'Corbeille',
+}
+
+const HEALTH_ICONS: Record<HealthStatus, string> = {
+  good: '✅',
+  warning: '⚠️',
+  critical: '❌',
+}
+
+const GIB = 1024 * 1024 * 1024
+
 export function SettingsModal({ wayneClient, onClose, onPasswordChanged }: SettingsModalProps) {
   const [passwordType, setPasswordType] = useState<'wayne' | 'master'>('wayne')
   
@@ -36,6 +290,1288 @@ export function SettingsModal({ wayneClient, onClose, onPasswordChanged }: Setti
   
   const [isLoading, setIsLoading] = useState(false)
   const [status, setStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+  const [passwordConfirm, askPassword] = usePasswordConfirm()
+
+  // États pour l'export complet du coffre
+  const [exportDir, setExportDir] = useState('')
+  const [isExporting, setIsExporting] = useState(false)
+  const [exportProgress, setExportProgress] = useState<{ done: number; total: number } | null>(null)
+  const [exportStatus, setExportStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+  // Archive chiffrée (archivage à froid)
+  const [archivePath, setArchivePath] = useState('')
+  const [isArchiving, setIsArchiving] = useState(false)
+  const [archiveStatus, setArchiveStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  useEffect(() => {
+    const unlisten = listenAppEvents((event) => {
+      if (event.type === 'exportProgress') {
+        setExportProgress({ done: event.payload.filesDone, total: event.payload.filesTotal })
+      }
+    })
+    return () => {
+      unlisten.then((fn) => fn())
+    }
+  }, [])
+
+  // État du serveur WebDAV local
+  const [webdavInfo, setWebdavInfo] = useState<{ url: string; username: string; password: string } | null>(null)
+  const [webdavError, setWebdavError] = useState<string | null>(null)
+
+  const handleToggleWebdav = async () => {
+    setWebdavError(null)
+    try {
+      if (webdavInfo) {
+        await invoke('webdav_stop')
+        setWebdavInfo(null)
+      } else {
+        setWebdavInfo(await invoke<{ url: string; username: string; password: string }>('webdav_start', { port: null }))
+      }
+    } catch (e) {
+      setWebdavError(formatError(e))
+    }
+  }
+
+  // Score de santé : intégrité, entrées altérées ou orphelines, sauvegardes, corbeille
+  const [health, setHealth] = useState<HealthReport | null>(null)
+
+  const loadHealth = () =>
+    invoke<HealthReport>('vault_health')
+      .then(setHealth)
+      .catch((e) => console.warn('vault_health failed:', e))
+
+  useEffect(() => {
+    loadHealth()
+  }, [])
+
+  // Vérification d'intégrité à la demande (la vérification automatique tourne toutes les 24 h)
+  const [isCheckingIntegrity, setIsCheckingIntegrity] = useState(false)
+  const [integrityStatus, setIntegrityStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  const handleCheckIntegrity = async () => {
+    setIsCheckingIntegrity(true)
+    setIntegrityStatus(null)
+    try {
+      const report = await invoke<{ merkle_ok: boolean; tampered_rows: string[]; remote_sampled: number; remote_unchanged: number; remote_drift: Array<{ file_id: string; reason: string }> }>('integrity_check_now')
+      if (report.merkle_ok && report.tampered_rows.length === 0 && report.remote_drift.length === 0) {
+        setIntegrityStatus({ type: 'success', message: `✅ Index intègre, ${report.remote_sampled} objet(s) distant(s) contrôlé(s), ${report.remote_unchanged} inchangé(s) depuis leur dernier contrôle.` })
+      } else {
+        setIntegrityStatus({
+          type: 'warning',
+          message: `⚠️ ${report.tampered_rows.length} entrée(s) altérée(s), ${report.remote_drift.length} objet(s) distant(s) incohérent(s).`,
+        })
+      }
+      await Promise.all([loadHealth(), loadQuarantine()])
+    } catch (e) {
+      setIntegrityStatus({ type: 'error', message: formatError(e) })
+    } finally {
+      setIsCheckingIntegrity(false)
+    }
+  }
+
+  // Quarantaine : fichiers dont le contenu distant n'a pas passé la vérification, à réparer
+  const [quarantined, setQuarantined] = useState<QuarantinedFile[]>([])
+
+  const loadQuarantine = () =>
+    invokeAllPages<QuarantinedFile>('quarantine_list')
+      .then(({ items }) => setQuarantined(items))
+      .catch((e) => console.warn('quarantine_list failed:', e))
+
+  useEffect(() => {
+    loadQuarantine()
+  }, [])
+
+  const runRepair = async (repair: () => Promise<string>) => {
+    setIntegrityStatus(null)
+    try {
+      setIntegrityStatus({ type: 'success', message: await repair() })
+    } catch (e) {
+      setIntegrityStatus({ type: 'error', message: formatError(e) })
+    }
+    await Promise.all([loadQuarantine(), loadHealth()])
+  }
+
+  const handleRecheckQuarantined = (file: QuarantinedFile) =>
+    runRepair(async () =>
+      (await invoke<boolean>('quarantine_recheck', { fileId: file.file_id }))
+        ? '✅ Le contenu distant est de nouveau valide : quarantaine levée.'
+        : '⚠️ Le contenu distant ne passe toujours pas la vérification.',
+    )
+
+  const handleRestoreQuarantinedVersion = (file: QuarantinedFile, versionId: string) =>
+    runRepair(async () => {
+      await invoke('quarantine_restore_version', { fileId: file.file_id, versionId })
+      return '✅ Version antérieure remise en place, le contenu altéré a été supprimé.'
+    })
+
+  const handleRestoreQuarantinedCopy = (file: QuarantinedFile) =>
+    runRepair(async () => {
+      await invoke('quarantine_restore_local_copy', { fileId: file.file_id })
+      return '✅ Contenu distant réparé depuis la copie locale.'
+    })
+
+  const handleDiscardQuarantined = async (file: QuarantinedFile) => {
+    const password = await askPassword(
+      `Supprimer définitivement « ${file.logical_path ?? file.file_id} ». Son contenu altéré ne peut pas être récupéré.`,
+    )
+    if (password === null) {
+      return
+    }
+    await runRepair(async () => {
+      await invokeElevated('quarantine_discard', { fileId: file.file_id }, password)
+      return '✅ Fichier abandonné et supprimé du stockage.'
+    })
+  }
+
+  // Historique des racines Merkle, pour repérer un changement survenu hors de l'application
+  const [timeline, setTimeline] = useState<IntegrityTimeline | null>(null)
+
+  const handleLoadTimeline = async () => {
+    setIntegrityStatus(null)
+    try {
+      const result = await invoke<IntegrityTimeline>('integrity_timeline')
+      setTimeline(result)
+      if (result.unexpected_change || result.broken_chain) {
+        setIntegrityStatus({
+          type: 'warning',
+          message: result.unexpected_change
+            ? "⚠️ L'index a changé sans qu'aucune opération de l'application ne l'explique (peut-être pendant qu'elle était fermée)."
+            : "⚠️ L'historique des racines a été réécrit.",
+        })
+      }
+    } catch (e) {
+      setIntegrityStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Dépannage : auto-diagnostic du trousseau, de l'index, du stockage, de l'horloge et du disque
+  const [isDiagnosing, setIsDiagnosing] = useState(false)
+  const [diagnostics, setDiagnostics] = useState<DiagnosticCheck[] | null>(null)
+  const [diagnosticsError, setDiagnosticsError] = useState<string | null>(null)
+
+  const handleRunDiagnostics = async () => {
+    setIsDiagnosing(true)
+    setDiagnosticsError(null)
+    try {
+      const report = await invoke<{ checked_at: number; checks: DiagnosticCheck[] }>('run_diagnostics')
+      setDiagnostics(report.checks)
+    } catch (e) {
+      setDiagnosticsError(formatError(e))
+    } finally {
+      setIsDiagnosing(false)
+    }
+  }
+
+  // Indice de mot de passe, affiché sur l'écran de déverrouillage
+  const [passwordHint, setPasswordHint] = useState('')
+  const [hintStatus, setHintStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  useEffect(() => {
+    invoke<string | null>('get_password_hint')
+      .then((hint) => setPasswordHint(hint ?? ''))
+      .catch((e) => console.warn('get_password_hint failed:', e))
+  }, [])
+
+  const handleSaveHint = async () => {
+    try {
+      await invokeElevated('set_password_hint', { hint: passwordHint.trim() || null })
+      setHintStatus({ type: 'success', message: passwordHint.trim() ? '✅ Indice enregistré' : '✅ Indice retiré' })
+    } catch (e) {
+      setHintStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Mode invité : noms et tailles consultables sans la MasterKey, contenu verrouillé
+  const [guestEnabled, setGuestEnabled] = useState(false)
+  const [guestPassphrase, setGuestPassphrase] = useState('')
+  const [guestStatus, setGuestStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  useEffect(() => {
+    invoke<{ enabled: boolean; active: boolean }>('guest_mode_status')
+      .then((status) => setGuestEnabled(status.enabled))
+      .catch((e) => console.warn('guest_mode_status failed:', e))
+  }, [])
+
+  const handleEnableGuestMode = async () => {
+    try {
+      await invokeElevated('guest_mode_enable', { passphrase: guestPassphrase })
+      setGuestPassphrase('')
+      setGuestEnabled(true)
+      setGuestStatus({ type: 'success', message: '✅ Phrase d\'accès invité enregistrée' })
+    } catch (e) {
+      setGuestStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleDisableGuestMode = async () => {
+    try {
+      await invokeElevated('guest_mode_disable')
+      setGuestEnabled(false)
+      setGuestStatus({ type: 'success', message: '✅ Mode invité désactivé' })
+    } catch (e) {
+      setGuestStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Recherche dans le contenu : texte extrait à l'envoi, indexé dans une base chiffrée locale
+  const [contentSearch, setContentSearch] = useState<{ enabled: boolean; indexed_files: number } | null>(null)
+  const [contentSearchStatus, setContentSearchStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  useEffect(() => {
+    invoke<{ enabled: boolean; indexed_files: number }>('content_search_status')
+      .then(setContentSearch)
+      .catch((e) => console.warn('content_search_status failed:', e))
+  }, [])
+
+  const handleToggleContentSearch = async (enabled: boolean) => {
+    try {
+      setContentSearch(await invoke<{ enabled: boolean; indexed_files: number }>('content_search_set_enabled', { enabled }))
+      setContentSearchStatus({
+        type: 'success',
+        message: enabled ? '✅ Recherche dans le contenu activée' : '✅ Recherche dans le contenu désactivée, texte indexé effacé',
+      })
+    } catch (e) {
+      setContentSearchStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Clés distantes aléatoires : les objets du bucket ne portent plus l'identifiant de leur fichier
+  const [remoteKeys, setRemoteKeys] = useState<boolean | null>(null)
+  const [remoteKeysStatus, setRemoteKeysStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  useEffect(() => {
+    invoke<boolean>('remote_keys_status')
+      .then(setRemoteKeys)
+      .catch((e) => console.warn('remote_keys_status failed:', e))
+  }, [])
+
+  const handleToggleRemoteKeys = async (enabled: boolean) => {
+    try {
+      setRemoteKeys(await invoke<boolean>('remote_keys_set_enabled', { enabled }))
+      setRemoteKeysStatus({
+        type: 'success',
+        message: enabled ? '✅ Clés distantes aléatoires activées' : '✅ Clés distantes aléatoires désactivées',
+      })
+    } catch (e) {
+      setRemoteKeysStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Audit des noms d'objets : clés avec tirets laissées par d'anciennes versions
+  const [isRepairingKeys, setIsRepairingKeys] = useState(false)
+
+  const handleRepairRemoteKeys = async () => {
+    setIsRepairingKeys(true)
+    try {
+      const audit = await invoke<KeyAudit>('remote_keys_audit')
+      if (audit.misnamed.length === 0 && audit.duplicates.length === 0) {
+        setRemoteKeysStatus({ type: 'success', message: `✅ ${audit.checked} objet(s) vérifié(s) : tous correctement nommés` })
+        return
+      }
+      const report = await invoke<KeyAudit>('remote_keys_repair')
+      const leftovers = report.misnamed.length + report.duplicates.length
+      setRemoteKeysStatus({
+        type: leftovers > 0 ? 'warning' : 'success',
+        message: leftovers > 0
+          ? `⚠️ ${report.repaired} objet(s) renommé(s), ${leftovers} laissé(s) en place (contenu différent sous le nom attendu)`
+          : `✅ ${report.repaired} objet(s) renommé(s)`,
+      })
+    } catch (e) {
+      setRemoteKeysStatus({ type: 'error', message: formatError(e) })
+    } finally {
+      setIsRepairingKeys(false)
+    }
+  }
+
+  // Nouvel appareil : paquet de configuration scellé sous un code de transfert à usage unique
+  const [bundleExport, setBundleExport] = useState<{ path: string; transfer_code: string } | null>(null)
+  const [isExportingBundle, setIsExportingBundle] = useState(false)
+  const [bundleStatus, setBundleStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  const handleExportBundle = async () => {
+    setIsExportingBundle(true)
+    setBundleExport(null)
+    try {
+      const exported = await invokeFresh<{ path: string; transfer_code: string }>(
+        'vault_bundle_export',
+        undefined,
+        askPassword,
+        'Le mot de passe n\'a pas été saisi récemment : confirme-le pour exporter la configuration du coffre.',
+      )
+      if (!exported) return
+      setBundleExport(exported)
+      setBundleStatus(null)
+    } catch (e) {
+      if (isCommandError(e) && e.code === 'dialog_cancelled') return
+      setBundleStatus({ type: 'error', message: formatError(e) })
+    } finally {
+      setIsExportingBundle(false)
+    }
+  }
+
+  // Session : les exports de clés exigent un mot de passe saisi récemment (vérifié côté Rust)
+  const [freshness, setFreshness] = useState<{
+    unlocked_secs: number | null
+    authenticated_secs: number | null
+    window_secs: number
+    fresh: boolean
+  } | null>(null)
+  const [freshnessStatus, setFreshnessStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  const loadFreshness = () =>
+    invoke<typeof freshness>('session_freshness')
+      .then(setFreshness)
+      .catch((e) => console.warn('session_freshness failed:', e))
+
+  useEffect(() => {
+    loadFreshness()
+  }, [])
+
+  const handleSetReauthWindow = async (minutes: number) => {
+    try {
+      const windowSecs = await invokeFresh<number>(
+        'reauth_set_window',
+        { windowSecs: minutes * 60 },
+        askPassword,
+        'Confirme ton mot de passe pour modifier ce délai.',
+      )
+      if (windowSecs === null) return
+      setFreshnessStatus({ type: 'success', message: `✅ Le mot de passe sera redemandé ${Math.round(windowSecs / 60)} min après sa dernière saisie` })
+      loadFreshness()
+    } catch (e) {
+      if (isCommandError(e) && e.code === 'dialog_cancelled') return
+      setFreshnessStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Déverrouillage par l'appareil : KEK conservée dans le trousseau (Keystore, Keychain…)
+  const [deviceUnlock, setDeviceUnlock] = useState(false)
+  const [deviceStatus, setDeviceStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  useEffect(() => {
+    invoke<boolean>('device_unlock_status')
+      .then(setDeviceUnlock)
+      .catch((e) => console.warn('device_unlock_status failed:', e))
+  }, [])
+
+  // Fichier clé : second facteur mélangé à la KEK ; la nouvelle MKEK remplace l'ancienne
+  // sur Wayne et sur cet appareil
+  const [keyfileEnrolled, setKeyfileEnrolled] = useState<boolean | null>(null)
+  const [keyfilePassword, setKeyfilePassword] = useState('')
+  const [keyfilePath, setKeyfilePath] = useState<string | null>(null)
+  const [isUpdatingKeyfile, setIsUpdatingKeyfile] = useState(false)
+  const [keyfileStatus, setKeyfileStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  const loadEnvelope = async (): Promise<MkekEnvelope> => {
+    if (wayneClient && wayneClient.getAccessToken()) {
+      const { envelope } = await wayneClient.getMyKeyEnvelope()
+      return { password_salt: envelope.password_salt, mkek: envelope.mkek }
+    }
+    const stored = localStorage.getItem(BOOTSTRAP_STORAGE_KEY)
+    if (!stored) {
+      throw new Error('Aucune donnée de bootstrap trouvée pour ce coffre.')
+    }
+    return JSON.parse(stored) as MkekEnvelope
+  }
+
+  const saveEnvelope = async (envelope: MkekEnvelope) => {
+    if (wayneClient && wayneClient.getAccessToken()) {
+      await wayneClient.changePassword({
+        password_type: 'master',
+        new_password_salt: envelope.password_salt,
+        new_mkek: envelope.mkek,
+      })
+    }
+    if (localStorage.getItem(BOOTSTRAP_STORAGE_KEY)) {
+      localStorage.setItem(BOOTSTRAP_STORAGE_KEY, JSON.stringify(envelope))
+    }
+  }
+
+  useEffect(() => {
+    loadEnvelope()
+      .then(({ mkek }) => invoke<boolean>('keyfile_required', { mkek }))
+      .then(setKeyfileEnrolled)
+      .catch((e) => console.warn('keyfile_required failed:', e))
+  }, [])
+
+  const handleSelectKeyfile = async () => {
+    try {
+      setKeyfilePath(await invoke<string>('keyfile_select'))
+    } catch (e) {
+      if (isCommandError(e) && e.code === 'dialog_cancelled') return
+      setKeyfileStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleUpdateKeyfile = async (enroll: boolean) => {
+    if (!keyfilePassword) {
+      setKeyfileStatus({ type: 'error', message: 'Le mot de passe maître est requis.' })
+      return
+    }
+    setIsUpdatingKeyfile(true)
+    setKeyfileStatus(null)
+    try {
+      const current = await loadEnvelope()
+      const req = { password: keyfilePassword, ...current, keyfile_path: keyfilePath }
+      if (enroll) {
+        const result = await invoke<MkekEnvelope & { path: string }>('keyfile_enroll', { req })
+        await saveEnvelope({ password_salt: result.password_salt, mkek: result.mkek })
+        setKeyfileStatus({
+          type: 'success',
+          message: `✅ Fichier clé enregistré (${result.path}). Garde-le hors de cet appareil : sans lui, le mot de passe ne suffit plus à déverrouiller le coffre.`,
+        })
+      } else {
+        const result = await invoke<MkekEnvelope>('keyfile_remove', { req })
+        await saveEnvelope(result)
+        setKeyfileStatus({ type: 'success', message: '✅ Fichier clé retiré : le mot de passe maître suffit de nouveau.' })
+      }
+      setKeyfileEnrolled(enroll)
+      setKeyfilePassword('')
+      setKeyfilePath(null)
+    } catch (e) {
+      if (isCommandError(e) && e.code === 'dialog_cancelled') return
+      setKeyfileStatus({ type: 'error', message: formatError(e) })
+    } finally {
+      setIsUpdatingKeyfile(false)
+    }
+  }
+
+  const handleForgetDevice = async () => {
+    try {
+      await invokeElevated('device_unlock_forget')
+      setDeviceUnlock(false)
+      setDeviceStatus({ type: 'success', message: '✅ Cet appareil ne déverrouille plus le coffre seul' })
+    } catch (e) {
+      setDeviceStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Identifiants délégués : limités au coffre, à durée limitée et renouvelés automatiquement
+  const [credentials, setCredentials] = useState<CredentialsStatus | null>(null)
+  const [issuerUrl, setIssuerUrl] = useState('')
+  const [renewalToken, setRenewalToken] = useState('')
+  const [delegationHours, setDelegationHours] = useState('12')
+  const [isDelegating, setIsDelegating] = useState(false)
+  const [credentialsStatus, setCredentialsStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  useEffect(() => {
+    invoke<CredentialsStatus>('storj_credentials_status')
+      .then(setCredentials)
+      .catch((e) => console.warn('storj_credentials_status failed:', e))
+  }, [])
+
+  const handleEnrollDelegation = async () => {
+    const hours = Number(delegationHours)
+    if (!issuerUrl.trim() || !renewalToken.trim() || !Number.isFinite(hours) || hours <= 0) {
+      setCredentialsStatus({ type: 'warning', message: 'Renseigne l\'émetteur, le jeton et une durée en heures.' })
+      return
+    }
+    setIsDelegating(true)
+    try {
+      setCredentials(await invokeElevated<CredentialsStatus>('storj_enroll_delegation', {
+        issuerUrl: issuerUrl.trim(),
+        renewalToken: renewalToken.trim(),
+        ttlSecs: Math.round(hours * 3600),
+      }))
+      setRenewalToken('')
+      setCredentialsStatus({ type: 'success', message: '✅ Cet appareil utilise désormais des identifiants délégués' })
+    } catch (e) {
+      if (isCommandError(e) && e.code === 'dialog_cancelled') return
+      setCredentialsStatus({ type: 'error', message: formatError(e) })
+    } finally {
+      setIsDelegating(false)
+    }
+  }
+
+  const handleRotateCredentials = async () => {
+    setIsDelegating(true)
+    try {
+      setCredentials(await invokeElevated<CredentialsStatus>('storj_rotate_credentials'))
+      setCredentialsStatus({ type: 'success', message: '✅ Identifiants renouvelés, les précédents sont révoqués' })
+    } catch (e) {
+      if (isCommandError(e) && e.code === 'dialog_cancelled') return
+      setCredentialsStatus({ type: 'error', message: formatError(e) })
+    } finally {
+      setIsDelegating(false)
+    }
+  }
+
+  // Budget de stockage : les envois qui le dépasseraient sont refusés
+  const [quota, setQuota] = useState<QuotaStatus | null>(null)
+  const [quotaLimitGb, setQuotaLimitGb] = useState('')
+  const [trashThreshold, setTrashThreshold] = useState('')
+  const [quotaStatus, setQuotaStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  useEffect(() => {
+    invoke<QuotaStatus>('quota_status')
+      .then((status) => {
+        setQuota(status)
+        setQuotaLimitGb(status.limit_bytes !== null ? String(status.limit_bytes / GIB) : '')
+        setTrashThreshold(String(status.trash_suggest_percent))
+      })
+      .catch((e) => setQuotaStatus({ type: 'error', message: formatError(e) }))
+  }, [])
+
+  const handleSaveQuota = async (remove: boolean) => {
+    const limitGb = Number(quotaLimitGb.replace(',', '.'))
+    if (!remove && (!quotaLimitGb || !Number.isFinite(limitGb) || limitGb <= 0)) {
+      setQuotaStatus({ type: 'error', message: 'Indique un budget en Go supérieur à 0.' })
+      return
+    }
+    try {
+      const status = await invoke<QuotaStatus>('quota_set_limit', { limitBytes: remove ? null : Math.round(limitGb * GIB) })
+      setQuota(status)
+      if (remove) setQuotaLimitGb('')
+      setQuotaStatus({ type: 'success', message: remove ? '✅ Budget retiré' : '✅ Budget enregistré' })
+    } catch (e) {
+      setQuotaStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleSaveTrashThreshold = async () => {
+    const percent = Number(trashThreshold)
+    if (!trashThreshold || !Number.isInteger(percent) || percent < 0 || percent > 100) {
+      setQuotaStatus({ type: 'error', message: 'Indique un pourcentage entier entre 0 et 100.' })
+      return
+    }
+    try {
+      const status = await invoke<QuotaStatus>('quota_set_trash_threshold', { percent })
+      setQuota(status)
+      setQuotaStatus({
+        type: 'success',
+        message: percent === 0 ? '✅ Suggestion de vider la corbeille désactivée' : '✅ Seuil de la corbeille enregistré',
+      })
+    } catch (e) {
+      setQuotaStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Cache local : fichiers récents et fréquents gardés (chiffrés) sur cet ordinateur
+  const [cache, setCache] = useState<CacheReport | null>(null)
+  const [cacheMaxGb, setCacheMaxGb] = useState('')
+  const [cachePrewarm, setCachePrewarm] = useState('')
+  const [cacheStatus, setCacheStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  const applyCacheReport = (report: CacheReport) => {
+    setCache(report)
+    setCacheMaxGb(String(report.policy.max_bytes / GIB))
+    setCachePrewarm(String(report.policy.prewarm_count))
+  }
+
+  useEffect(() => {
+    invoke<CacheReport>('cache_status')
+      .then(applyCacheReport)
+      .catch((e) => setCacheStatus({ type: 'error', message: formatError(e) }))
+  }, [])
+
+  const handleSaveCachePolicy = async () => {
+    const maxGb = Number(cacheMaxGb.replace(',', '.'))
+    const prewarm = Number(cachePrewarm)
+    if (!Number.isFinite(maxGb) || maxGb < 0 || !Number.isInteger(prewarm) || prewarm < 0) {
+      setCacheStatus({ type: 'error', message: 'Indique une taille en Go et un nombre de fichiers positifs.' })
+      return
+    }
+    try {
+      const report = await invoke<CacheReport>('cache_set_policy', {
+        policy: { max_bytes: Math.round(maxGb * GIB), prewarm_count: prewarm },
+      })
+      applyCacheReport(report)
+      setCacheStatus({
+        type: 'success',
+        message: `✅ Politique enregistrée (${report.evicted} fichier(s) retiré(s), ${report.prewarmed} préchargé(s))`,
+      })
+    } catch (e) {
+      setCacheStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleClearCache = async () => {
+    try {
+      const removed = await invoke<number>('cache_clear')
+      applyCacheReport(await invoke<CacheReport>('cache_status'))
+      setCacheStatus({ type: 'success', message: `✅ Cache vidé : ${removed} fichier(s) retiré(s)` })
+    } catch (e) {
+      setCacheStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Sauvegardes de l'index : copie chiffrée envoyée dans le bucket après chaque lot de modifications
+  const [backupStatus, setBackupStatus] = useState<IndexBackupStatus | null>(null)
+  const [backupRetention, setBackupRetention] = useState('')
+  const [backups, setBackups] = useState<IndexBackup[] | null>(null)
+  const [backupMessage, setBackupMessage] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  const loadBackupStatus = () =>
+    invoke<IndexBackupStatus>('index_backup_status')
+      .then((status) => {
+        setBackupStatus(status)
+        setBackupRetention(String(status.policy.retention))
+      })
+      .catch((e) => setBackupMessage({ type: 'error', message: formatError(e) }))
+
+  useEffect(() => {
+    loadBackupStatus()
+  }, [])
+
+  const handleSaveBackupPolicy = async (enabled: boolean) => {
+    const retention = Number(backupRetention)
+    if (!Number.isInteger(retention) || retention < 1) {
+      setBackupMessage({ type: 'error', message: 'Indique un nombre de sauvegardes entier, au moins 1.' })
+      return
+    }
+    try {
+      await invoke('index_backup_set_policy', { policy: { enabled, retention } })
+      await loadBackupStatus()
+      setBackupMessage({ type: 'success', message: '✅ Sauvegardes de l\'index mises à jour' })
+    } catch (e) {
+      setBackupMessage({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleBackupNow = async () => {
+    try {
+      const report = await invoke<{ backup: IndexBackup; pruned: number }>('index_backup_now')
+      await loadBackupStatus()
+      setBackups(null)
+      setBackupMessage({
+        type: 'success',
+        message: `✅ Index sauvegardé (${report.pruned} ancienne(s) sauvegarde(s) supprimée(s))`,
+      })
+    } catch (e) {
+      setBackupMessage({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleListBackups = async () => {
+    try {
+      setBackups(await invoke<IndexBackup[]>('index_backup_list'))
+    } catch (e) {
+      setBackupMessage({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleRestoreBackup = async (backup: IndexBackup) => {
+    const date = new Date(backup.created_at * 1000).toLocaleString()
+    const password = await askPassword(`Remplacer l'index local par la sauvegarde du ${date}. L'index actuel est conservé à côté.`)
+    if (password === null) {
+      return
+    }
+    try {
+      const files = await invokeElevated<number>('index_backup_restore', { key: backup.key }, password)
+      setBackupMessage({ type: 'success', message: `✅ Index restauré : ${files} fichier(s)` })
+    } catch (e) {
+      setBackupMessage({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Exercice de restauration : clone du coffre (objets chiffrés + index) vérifié de bout en bout
+  const [cloneDir, setCloneDir] = useState('')
+  const [cloneEndpoint, setCloneEndpoint] = useState('')
+  const [cloneBucket, setCloneBucket] = useState('')
+  const [cloneAccessKey, setCloneAccessKey] = useState('')
+  const [cloneSecretKey, setCloneSecretKey] = useState('')
+  const [lastClone, setLastClone] = useState<CloneReport | null>(null)
+  const [isCloning, setIsCloning] = useState(false)
+  const [cloneMessage, setCloneMessage] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  useEffect(() => {
+    invoke<CloneReport | null>('vault_clone_last_report')
+      .then(setLastClone)
+      .catch((e) => console.warn('vault_clone_last_report failed:', e))
+  }, [])
+
+  const handleCloneVault = async (kind: 'directory' | 'bucket') => {
+    if (kind === 'directory' && !cloneDir) {
+      setCloneMessage({ type: 'error', message: 'Indique un dossier de destination vide.' })
+      return
+    }
+    if (kind === 'bucket' && (!cloneEndpoint || !cloneBucket || !cloneAccessKey || !cloneSecretKey)) {
+      setCloneMessage({ type: 'error', message: 'Renseigne l\'endpoint, le bucket et les deux clés du bucket de destination.' })
+      return
+    }
+    const target =
+      kind === 'directory'
+        ? { kind, path: cloneDir }
+        : {
+            kind,
+            config: {
+              accessKeyId: cloneAccessKey,
+              secretAccessKey: cloneSecretKey,
+              endpoint: cloneEndpoint,
+              bucketName: cloneBucket,
+            },
+          }
+    setIsCloning(true)
+    setCloneMessage({ type: 'info', message: 'Clonage en cours : chaque fichier est ensuite vérifié depuis la copie...' })
+    try {
+      const report = await invoke<CloneReport>('vault_clone', { target })
+      setLastClone(report)
+      setCloneSecretKey('')
+      if (report.failures.length === 0 && report.verified_files === report.files) {
+        setCloneMessage({
+          type: 'success',
+          message: `✅ Clone vérifié : ${report.verified_files} fichier(s) restaurable(s) depuis ${report.objects} objet(s)`,
+        })
+      } else {
+        setCloneMessage({
+          type: 'warning',
+          message: `⚠️ Le clone ne restaurerait pas tout : ${report.failures.length} échec(s) de vérification`,
+        })
+      }
+    } catch (e) {
+      setCloneMessage({ type: 'error', message: formatError(e) })
+    } finally {
+      setIsCloning(false)
+    }
+  }
+
+  // Espaces de travail : un index, une file d'attente, des réglages et un stockage par espace
+  const [workspaceList, setWorkspaceList] = useState<WorkspaceList | null>(null)
+  const [workspaceName, setWorkspaceName] = useState('')
+  const [workspaceEndpoint, setWorkspaceEndpoint] = useState('')
+  const [workspaceBucket, setWorkspaceBucket] = useState('')
+  const [workspaceAccessKey, setWorkspaceAccessKey] = useState('')
+  const [workspaceSecretKey, setWorkspaceSecretKey] = useState('')
+  const [workspaceStatus, setWorkspaceStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  const loadWorkspaces = () =>
+    invoke<WorkspaceList>('workspace_list')
+      .then(setWorkspaceList)
+      .catch((e) => setWorkspaceStatus({ type: 'error', message: formatError(e) }))
+
+  useEffect(() => {
+    loadWorkspaces()
+  }, [])
+
+  const handleCreateWorkspace = async () => {
+    const storageFields = [workspaceEndpoint, workspaceBucket, workspaceAccessKey, workspaceSecretKey].map((v) => v.trim())
+    if (storageFields.some((v) => v) && storageFields.some((v) => !v)) {
+      setWorkspaceStatus({ type: 'error', message: 'Renseigne tous les champs du stockage, ou aucun.' })
+      return
+    }
+    const [endpoint, bucketName, accessKeyId, secretAccessKey] = storageFields
+    const config = endpoint ? { accessKeyId, secretAccessKey, endpoint, bucketName } : null
+    try {
+      const workspace = await invoke<Workspace>('workspace_create', { name: workspaceName, config })
+      setWorkspaceName('')
+      setWorkspaceEndpoint('')
+      setWorkspaceBucket('')
+      setWorkspaceAccessKey('')
+      setWorkspaceSecretKey('')
+      await loadWorkspaces()
+      setWorkspaceStatus({ type: 'success', message: `✅ Espace « ${workspace.name} » créé` })
+    } catch (e) {
+      setWorkspaceStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleSwitchWorkspace = async (workspace: Workspace) => {
+    try {
+      const result = await invoke<{ workspace: Workspace; storj_configured: boolean }>('workspace_switch', {
+        id: workspace.id,
+      })
+      await loadWorkspaces()
+      setWorkspaceStatus(
+        result.storj_configured
+          ? { type: 'success', message: `✅ Espace « ${workspace.name} » ouvert` }
+          : { type: 'warning', message: `Espace « ${workspace.name} » ouvert, sans stockage configuré` },
+      )
+    } catch (e) {
+      setWorkspaceStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleRenameWorkspace = async (workspace: Workspace) => {
+    // Le champ « Nom » sert aussi au renommage
+    if (!workspaceName.trim()) {
+      setWorkspaceStatus({ type: 'info', message: 'Saisis le nouveau nom dans le champ « Nom », puis clique sur Renommer.' })
+      return
+    }
+    try {
+      const renamed = await invoke<Workspace>('workspace_rename', { id: workspace.id, name: workspaceName })
+      setWorkspaceName('')
+      await loadWorkspaces()
+      setWorkspaceStatus({ type: 'success', message: `✅ Espace renommé en « ${renamed.name} »` })
+    } catch (e) {
+      setWorkspaceStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleDeleteWorkspace = async (workspace: Workspace) => {
+    const password = await askPassword(
+      `Supprimer l'espace « ${workspace.name} » et son index local. Les fichiers de son bucket ne sont pas supprimés.`,
+    )
+    if (password === null) {
+      return
+    }
+    try {
+      await invokeElevated('workspace_delete', { id: workspace.id }, password)
+      await loadWorkspaces()
+      setWorkspaceStatus({ type: 'success', message: `✅ Espace « ${workspace.name} » supprimé` })
+    } catch (e) {
+      setWorkspaceStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Instantanés : l'état complet du coffre à un instant donné, restaurable d'un bloc
+  const [snapshots, setSnapshots] = useState<VaultSnapshot[]>([])
+  const [snapshotName, setSnapshotName] = useState('')
+  const [snapshotDiff, setSnapshotDiff] = useState<{ snapshot: VaultSnapshot; diff: SnapshotDiff } | null>(null)
+  const [snapshotStatus, setSnapshotStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  const loadSnapshots = () =>
+    invoke<VaultSnapshot[]>('snapshot_list')
+      .then(setSnapshots)
+      .catch((e) => setSnapshotStatus({ type: 'error', message: formatError(e) }))
+
+  useEffect(() => {
+    loadSnapshots()
+  }, [])
+
+  const handleCreateSnapshot = async () => {
+    try {
+      const snapshot = await invoke<VaultSnapshot>('snapshot_create', { name: snapshotName })
+      setSnapshotName('')
+      await loadSnapshots()
+      setSnapshotStatus({ type: 'success', message: `✅ Instantané « ${snapshot.name} » créé (${snapshot.file_count} élément(s))` })
+    } catch (e) {
+      setSnapshotStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleDiffSnapshot = async (snapshot: VaultSnapshot) => {
+    try {
+      const { items } = await invokeAllPages<SnapshotChange>('snapshot_diff', { snapshotId: snapshot.id })
+      const diff: SnapshotDiff = { added: [], removed: [], moved: [] }
+      for (const change of items) {
+        if (change.kind === 'moved') {
+          diff.moved.push(change)
+        } else {
+          diff[change.kind].push(change)
+        }
+      }
+      setSnapshotDiff({ snapshot, diff })
+    } catch (e) {
+      setSnapshotStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleRestoreSnapshot = async (snapshot: VaultSnapshot) => {
+    const password = await askPassword(
+      `Ramener tout le coffre à l'instantané « ${snapshot.name} ». Les fichiers ajoutés depuis seront mis à la corbeille.`,
+    )
+    if (password === null) {
+      return
+    }
+    try {
+      const report = await invokeElevated<{ restored: number; trashed: number }>(
+        'snapshot_restore',
+        { snapshotId: snapshot.id },
+        password,
+      )
+      setSnapshotDiff(null)
+      setSnapshotStatus({
+        type: 'success',
+        message: `✅ Instantané restauré : ${report.restored} élément(s) remis en place, ${report.trashed} mis à la corbeille`,
+      })
+    } catch (e) {
+      setSnapshotStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleDeleteSnapshot = async (snapshot: VaultSnapshot) => {
+    let plan: DryRunReport
+    try {
+      plan = await dryRun('snapshot_delete', { snapshotId: snapshot.id })
+    } catch (e) {
+      setSnapshotStatus({ type: 'error', message: formatError(e) })
+      return
+    }
+    const password = await askPassword(
+      `Supprimer l'instantané « ${snapshot.name} ». ${plan.deleted_objects} fichier(s) déjà supprimé(s) qu'il était seul à conserver (${(plan.bytes / (1024 * 1024)).toFixed(1)} Mo) seront effacés du stockage.`,
+    )
+    if (password === null) {
+      return
+    }
+    try {
+      const released = await invokeElevated<number>('snapshot_delete', { snapshotId: snapshot.id }, password)
+      setSnapshotDiff(null)
+      await loadSnapshots()
+      setSnapshotStatus({ type: 'success', message: `✅ Instantané supprimé, ${released} fichier(s) effacé(s) du stockage` })
+    } catch (e) {
+      setSnapshotStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Règles d'automatisation : import d'un dossier local, mise à la corbeille des vieux fichiers
+  const [rules, setRules] = useState<Rule[]>([])
+  const [ruleType, setRuleType] = useState<RuleAction['type']>('import_folder')
+  const [ruleName, setRuleName] = useState('')
+  const [ruleLocalDir, setRuleLocalDir] = useState('')
+  const [ruleVaultFolder, setRuleVaultFolder] = useState('')
+  const [ruleDays, setRuleDays] = useState('30')
+  const [rulesStatus, setRulesStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  const loadRules = () =>
+    invoke<Rule[]>('rules_list')
+      .then(setRules)
+      .catch((e) => setRulesStatus({ type: 'error', message: formatError(e) }))
+
+  useEffect(() => {
+    loadRules()
+  }, [])
+
+  const describeRule = (action: RuleAction) =>
+    action.type === 'import_folder'
+      ? `${action.local_dir} → ${action.vault_folder}`
+      : `Corbeille après ${action.older_than_days} jour(s) dans ${action.vault_folder}`
+
+  const handleAddRule = async () => {
+    let action: RuleAction
+    if (ruleType === 'import_folder') {
+      action = { type: 'import_folder', local_dir: ruleLocalDir.trim(), vault_folder: ruleVaultFolder.trim() || '/' }
+    } else {
+      const days = Number(ruleDays)
+      if (!Number.isInteger(days) || days < 1) {
+        setRulesStatus({ type: 'error', message: 'Indique un nombre de jours entier positif.' })
+        return
+      }
+      action = { type: 'auto_trash', vault_folder: ruleVaultFolder.trim(), older_than_days: days }
+    }
+    try {
+      await invoke<Rule>('rules_save', { id: null, name: ruleName, enabled: true, action })
+      setRuleName('')
+      setRuleLocalDir('')
+      setRuleVaultFolder('')
+      await loadRules()
+      setRulesStatus({ type: 'success', message: '✅ Règle ajoutée' })
+    } catch (e) {
+      setRulesStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleToggleRule = async (rule: Rule) => {
+    try {
+      await invoke<Rule>('rules_save', { id: rule.id, name: rule.name, enabled: !rule.enabled, action: rule.action })
+      await loadRules()
+    } catch (e) {
+      setRulesStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleDeleteRule = async (rule: Rule) => {
+    try {
+      await invoke('rules_delete', { id: rule.id })
+      await loadRules()
+    } catch (e) {
+      setRulesStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleRunRules = async () => {
+    try {
+      const report = await invoke<RulesReport>('rules_run_now')
+      await loadRules()
+      setRulesStatus({
+        type: report.failures.length > 0 ? 'warning' : 'success',
+        message: `${report.failures.length > 0 ? '⚠️' : '✅'} ${report.rules_run} règle(s) exécutée(s) : ${report.uploaded} fichier(s) importé(s), ${report.trashed} mis à la corbeille`,
+      })
+    } catch (e) {
+      setRulesStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Hooks : script local ou webhook HTTPS signé, déclenchés par les événements du coffre
+  const [hooks, setHooks] = useState<Hook[]>([])
+  const [hookType, setHookType] = useState<HookTarget['type']>('webhook')
+  const [hookName, setHookName] = useState('')
+  const [hookTarget, setHookTarget] = useState('')
+  const [hookEvents, setHookEvents] = useState<HookEvent[]>(['upload_complete'])
+  const [hooksStatus, setHooksStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  const loadHooks = () =>
+    invoke<Hook[]>('hooks_list')
+      .then(setHooks)
+      .catch((e) => setHooksStatus({ type: 'error', message: formatError(e) }))
+
+  useEffect(() => {
+    loadHooks()
+  }, [])
+
+  const toggleHookEvent = (event: HookEvent) =>
+    setHookEvents((events) => (events.includes(event) ? events.filter((e) => e !== event) : [...events, event]))
+
+  const handleAddHook = async () => {
+    const target: HookTarget =
+      hookType === 'script'
+        ? { type: 'script', program: hookTarget.trim() }
+        : { type: 'webhook', url: hookTarget.trim(), secret: '' }
+    try {
+      const hook = await invokeElevated<Hook>('hooks_save', {
+        id: null,
+        name: hookName,
+        enabled: true,
+        events: hookEvents,
+        target,
+      })
+      setHookName('')
+      setHookTarget('')
+      await loadHooks()
+      setHooksStatus({
+        type: 'success',
+        message:
+          hook.target.type === 'webhook'
+            ? `✅ Hook ajouté. Secret de signature : ${hook.target.secret}`
+            : '✅ Hook ajouté',
+      })
+    } catch (e) {
+      setHooksStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleToggleHook = async (hook: Hook) => {
+    try {
+      await invokeElevated<Hook>('hooks_save', {
+        id: hook.id,
+        name: hook.name,
+        enabled: !hook.enabled,
+        events: hook.events,
+        target: hook.target,
+      })
+      await loadHooks()
+    } catch (e) {
+      setHooksStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleDeleteHook = async (hook: Hook) => {
+    try {
+      await invoke('hooks_delete', { id: hook.id })
+      await loadHooks()
+    } catch (e) {
+      setHooksStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Journaux : masquage des chemins et identifiants, niveau général et niveaux par module
+  const [logSettings, setLogSettings] = useState<LogSettings | null>(null)
+  const [logModules, setLogModules] = useState('')
+  const [logStatus, setLogStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  useEffect(() => {
+    invoke<LogSettings>('logging_get_settings')
+      .then((settings) => {
+        setLogSettings(settings)
+        setLogModules(Object.entries(settings.modules).map(([module, level]) => `${module}=${level}`).join(', '))
+      })
+      .catch((e) => console.warn('logging_get_settings failed:', e))
+  }, [])
+
+  const saveLogSettings = async (changes: Partial<LogSettings>) => {
+    if (!logSettings) {
+      return
+    }
+    setLogStatus(null)
+    const settings = { ...logSettings, ...changes }
+    try {
+      await invoke('logging_set_settings', { settings })
+      setLogSettings(settings)
+    } catch (e) {
+      setLogStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // `module=niveau` séparés par des virgules ; une entrée mal formée est signalée sans rien enregistrer
+  const handleSaveLogModules = async () => {
+    const modules: Record<string, LogVerbosity> = {}
+    for (const entry of logModules.split(',').map((part) => part.trim()).filter(Boolean)) {
+      const [module, level] = entry.split('=').map((part) => part.trim())
+      if (!module || !LOG_VERBOSITIES.includes(level as LogVerbosity)) {
+        setLogStatus({ type: 'error', message: `Entrée invalide : « ${entry} » (attendu : module=niveau).` })
+        return
+      }
+      modules[module] = level as LogVerbosity
+    }
+    await saveLogSettings({ modules })
+  }
+
+  // Consultation du journal chiffré et paquet de diagnostic
+  const [logFilterModule, setLogFilterModule] = useState('')
+  const [logFilterLevel, setLogFilterLevel] = useState<LogLevel>('warn')
+  const [logPeriod, setLogPeriod] = useState<number | null>(24 * 60 * 60 * 1000)
+  const [logRecords, setLogRecords] = useState<LogRecord[] | null>(null)
+  const [supportBundlePath, setSupportBundlePath] = useState('')
+  const [logViewerStatus, setLogViewerStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  const currentLogQuery = (): LogQuery => ({
+    module: logFilterModule.trim() || undefined,
+    level: logFilterLevel,
+    since_ms: logPeriod === null ? undefined : Date.now() - logPeriod,
+  })
+
+  const handleQueryLogs = async () => {
+    setLogViewerStatus(null)
+    try {
+      setLogRecords((await invokeAllPages<LogRecord>('logs_query', { query: currentLogQuery() })).items)
+    } catch (e) {
+      setLogViewerStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleExportSupportBundle = async () => {
+    try {
+      const exported = await invoke<number>('logs_export_bundle', {
+        query: currentLogQuery(),
+        destinationPath: supportBundlePath,
+      })
+      setLogViewerStatus({
+        type: 'success',
+        message: `✅ Paquet de diagnostic exporté (${exported} message(s)). Joins ce fichier à ton rapport de bug.`,
+      })
+    } catch (e) {
+      setLogViewerStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleClearLogs = async () => {
+    try {
+      await invoke('logs_clear')
+      setLogRecords([])
+      setLogViewerStatus(null)
+    } catch (e) {
+      setLogViewerStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Télémétrie opt-in : compteurs agrégés, relus et exportés localement par l'utilisateur
+  const [telemetry, setTelemetry] = useState<TelemetryMetrics | null>(null)
+  const [telemetryExportPath, setTelemetryExportPath] = useState('')
+  const [telemetryStatus, setTelemetryStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  useEffect(() => {
+    invoke<TelemetryMetrics>('telemetry_get')
+      .then(setTelemetry)
+      .catch((e) => setTelemetryStatus({ type: 'error', message: formatError(e) }))
+  }, [])
+
+  const handleToggleTelemetry = async () => {
+    try {
+      await invoke('telemetry_set_enabled', { enabled: !telemetry?.enabled })
+      setTelemetry(await invoke<TelemetryMetrics>('telemetry_get'))
+    } catch (e) {
+      setTelemetryStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleExportTelemetry = async () => {
+    try {
+      await invoke('telemetry_export', { destinationPath: telemetryExportPath })
+      setTelemetryStatus({ type: 'success', message: `✅ Compteurs exportés dans ${telemetryExportPath}` })
+    } catch (e) {
+      setTelemetryStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Rapports de plantage : chiffrés localement, exportés uniquement à la demande
+  const [crashCount, setCrashCount] = useState(0)
+  const [crashExportPath, setCrashExportPath] = useState('')
+  const [crashStatus, setCrashStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  useEffect(() => {
+    invoke<number>('crash_reports_count')
+      .then(setCrashCount)
+      .catch((e) => console.warn('crash_reports_count failed:', e))
+  }, [])
+
+  const handleExportCrashReports = async () => {
+    try {
+      const exported = await invoke<number>('crash_reports_export', { destinationPath: crashExportPath })
+      setCrashStatus({ type: 'success', message: `✅ ${exported} rapport(s) exporté(s). Joins ce fichier à ton rapport de bug.` })
+    } catch (e) {
+      setCrashStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleClearCrashReports = async () => {
+    try {
+      await invoke('crash_reports_clear')
+      setCrashCount(0)
+      setCrashStatus(null)
+    } catch (e) {
+      setCrashStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  const handleExportVault = async () => {
+    if (!exportDir) {
+      setExportStatus({ type: 'error', message: 'Indique un dossier de destination.' })
+      return
+    }
+    setIsExporting(true)
+    setExportProgress(null)
+    setExportStatus({ type: 'info', message: 'Export en cours...' })
+    try {
+      const summary = await invoke<{ exported: number; skipped: number; failed: string[] }>('export_vault', { destinationDir: exportDir })
+      if (summary.failed.length > 0) {
+        setExportStatus({
+          type: 'warning',
+          message: `⚠️ ${summary.exported} exporté(s), ${summary.skipped} déjà présent(s), ${summary.failed.length} en échec. Relance l'export pour réessayer.`,
+        })
+      } else {
+        setExportStatus({ type: 'success', message: `✅ ${summary.exported} fichier(s) exporté(s), ${summary.skipped} déjà présent(s).` })
+      }
+    } catch (e) {
+      setExportStatus({ type: 'error', message: `Erreur lors de l'export: ${formatError(e)}` })
+    } finally {
+      setIsExporting(false)
+    }
+  }
+
+  const handleExportArchive = async () => {
+    if (!wayneClient || !wayneClient.getAccessToken()) {
+      setArchiveStatus({ type: 'error', message: 'Tu dois être connecté à Wayne : l\'archive embarque l\'enveloppe de ta clé.' })
+      return
+    }
+    const password = await askPassword(
+      'L\'archive s\'ouvrira avec ton mot de passe maître actuel, y compris sans l\'application (aether-archive).',
+    )
+    if (!password) return
+    setIsArchiving(true)
+    setExportProgress(null)
+    setArchiveStatus({ type: 'info', message: 'Archivage en cours...' })
+    try {
+      const { envelope } = await wayneClient.getMyKeyEnvelope()
+      const summary = await invoke<{ files: number; objects: number; bytes: number; failed: string[] }>('export_archive', {
+        destinationPath: archivePath,
+        password,
+        passwordSalt: envelope.password_salt,
+        mkek: { nonce: envelope.mkek.nonce, payload: envelope.mkek.payload },
+      })
+      const size = `${(summary.bytes / (1024 * 1024)).toFixed(1)} Mo`
+      setArchiveStatus(summary.failed.length > 0
+        ? {
+            type: 'warning',
+            message: `⚠️ ${summary.files} fichier(s) archivé(s) (${size}) ; ${summary.failed.length} illisible(s) écarté(s) : ${summary.failed.join(', ')}`,
+          }
+        : { type: 'success', message: `✅ ${summary.files} fichier(s) archivé(s) (${size}), toujours chiffrés.` })
+    } catch (e) {
+      setArchiveStatus({ type: 'error', message: formatError(e) })
+    } finally {
+      setIsArchiving(false)
+    }
+  }
+
+  // État du rapport d'inventaire
+  const [reportPath, setReportPath] = useState('')
+  const [reportStatus, setReportStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string } | null>(null)
+
+  const handleExportReport = async (format: 'csv' | 'json') => {
+    try {
+      const entries = await invoke<number>('export_report', { format, destinationPath: reportPath })
+      setReportStatus({ type: 'success', message: `✅ Inventaire de ${entries} entrée(s) écrit dans ${reportPath}` })
+    } catch (e) {
+      setReportStatus({ type: 'error', message: formatError(e) })
+    }
+  }
 
   const handleChangeWaynePassword = async () => {
     // Validation
@@ -80,7 +1616,7 @@ export function SettingsModal({ wayneClient, onClose, onPasswordChanged }: Setti
         onPasswordChanged()
       }, 2000)
     } catch (e) {
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      const errorMsg = formatError(e)
       setStatus({ type: 'error', message: `Erreur lors du changement de mot de passe Wayne: ${errorMsg}` })
     } finally {
       setIsLoading(false)
@@ -127,6 +1663,7 @@ export function SettingsModal({ wayneClient, onClose, onPasswordChanged }: Setti
             nonce: currentEnvelope.mkek.nonce,
             payload: currentEnvelope.mkek.payload,
           },
+          keyfile_path: keyfilePath,
         },
       })
 
@@ -152,7 +1689,7 @@ export function SettingsModal({ wayneClient, onClose, onPasswordChanged }: Setti
         onClose()
       }, 2000)
     } catch (e) {
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      const errorMsg = formatError(e)
       setStatus({ type: 'error', message: `Erreur lors du changement de mot de passe maître: ${errorMsg}` })
     } finally {
       setIsLoading(false)
@@ -293,6 +1830,14 @@ export function SettingsModal({ wayneClient, onClose, onPasswordChanged }: Setti
                 required
               />
 
+              {keyfileEnrolled && (
+                <div className="settings-modal-actions">
+                  <Button variant="secondary" onClick={handleSelectKeyfile} disabled={isLoading}>
+                    {keyfilePath ? 'Changer de fichier clé' : 'Sélectionner le fichier clé'}
+                  </Button>
+                </div>
+              )}
+
               {status && (
                 <StatusMessage
                   type={status.type}
@@ -320,8 +1865,1278 @@ export function SettingsModal({ wayneClient, onClose, onPasswordChanged }: Setti
               </div>
             </Card>
           )}
+
+          <Card title="Accès WebDAV local">
+            <p className="settings-description">
+              Expose le coffre déverrouillé en lecture seule sur cet ordinateur, pour l'ouvrir depuis ton gestionnaire de fichiers.
+              Le serveur s'arrête automatiquement au verrouillage du coffre.
+            </p>
+
+            {webdavInfo && (
+              <p className="settings-description">
+                Adresse : <code>{webdavInfo.url}</code><br />
+                Utilisateur : <code>{webdavInfo.username}</code><br />
+                Mot de passe : <code>{webdavInfo.password}</code>
+              </p>
+            )}
+
+            {webdavError && (
+              <StatusMessage type="error" message={webdavError} onDismiss={() => setWebdavError(null)} />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button variant={webdavInfo ? 'secondary' : 'primary'} onClick={handleToggleWebdav}>
+                {webdavInfo ? 'Arrêter le serveur' : 'Démarrer le serveur'}
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Santé du coffre">
+            <p className="settings-description">
+              Une note sur 100 qui résume si tes données chiffrées sont réellement récupérables : dernière vérification
+              d'intégrité, entrées altérées, fichiers sans copie distante, fraîcheur des sauvegardes de l'index et
+              taille de la corbeille.
+            </p>
+            {health ? (
+              <>
+                <p className="settings-description">
+                  <strong>
+                    {HEALTH_ICONS[health.status]} {health.score}/100
+                  </strong>
+                </p>
+                {health.categories.map((category) => (
+                  <p key={category.name} className="settings-description">
+                    {HEALTH_ICONS[category.status]} {HEALTH_LABELS_FR[category.name] ?? category.name} · {category.score}/100 ·{' '}
+                    {category.detail}
+                  </p>
+                ))}
+              </>
+            ) : (
+              <p className="settings-description">Score indisponible.</p>
+            )}
+            <div className="settings-modal-actions">
+              <Button variant="secondary" onClick={loadHealth}>
+                Recalculer
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Intégrité du coffre">
+            <p className="settings-description">
+              L'index local et un échantillon des fichiers distants sont vérifiés automatiquement une fois par jour.
+              Tu peux aussi lancer une vérification maintenant.
+            </p>
+
+            {integrityStatus && (
+              <StatusMessage
+                type={integrityStatus.type}
+                message={integrityStatus.message}
+                onDismiss={() => setIntegrityStatus(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button
+                variant="secondary"
+                onClick={handleCheckIntegrity}
+                disabled={isCheckingIntegrity}
+                loading={isCheckingIntegrity}
+              >
+                Vérifier maintenant
+              </Button>
+              <Button variant="secondary" onClick={handleLoadTimeline}>
+                Historique des racines
+              </Button>
+            </div>
+
+            {quarantined.length > 0 && (
+              <p className="settings-description">
+                ⚠️ {quarantined.length} fichier(s) en quarantaine : leur contenu distant n'a pas passé la vérification et
+                n'est plus ouvert tant qu'il n'est pas réparé.
+              </p>
+            )}
+            {quarantined.map((file) => (
+              <div key={file.file_id}>
+                <p className="settings-description">
+                  🚫 <strong>{file.logical_path ?? file.file_id}</strong>
+                  {file.trashed ? ' (corbeille)' : file.is_version ? ' (ancienne version)' : ''} ·{' '}
+                  {QUARANTINE_SOURCE_LABELS_FR[file.source]} du{' '}
+                  {new Date(file.detected_at * 1000).toLocaleString()} · <code>{file.reason}</code>
+                </p>
+                <div className="settings-modal-actions">
+                  <Button variant="secondary" onClick={() => handleRecheckQuarantined(file)}>
+                    Revérifier
+                  </Button>
+                  {file.versions.slice(0, 1).map((version) => (
+                    <Button
+                      key={version.id}
+                      variant="secondary"
+                      onClick={() => handleRestoreQuarantinedVersion(file, version.id)}
+                    >
+                      Version du {new Date(version.replaced_at * 1000).toLocaleDateString()}
+                    </Button>
+                  ))}
+                  {file.local_copy && (
+                    <Button variant="secondary" onClick={() => handleRestoreQuarantinedCopy(file)}>
+                      Réparer depuis la copie locale
+                    </Button>
+                  )}
+                  <Button variant="danger" onClick={() => handleDiscardQuarantined(file)}>
+                    Abandonner
+                  </Button>
+                </div>
+              </div>
+            ))}
+
+            {timeline && timeline.records.length === 0 && (
+              <p className="settings-description">Aucune racine enregistrée pour l'instant.</p>
+            )}
+            {timeline?.records.map((record) => (
+              <p key={record.seq} className="settings-description">
+                {record.chain_valid ? '🔗' : '❌'} {new Date(record.recorded_at * 1000).toLocaleString()} ·{' '}
+                {MERKLE_OPERATION_LABELS_FR[record.operation] ?? record.operation} · <code>{record.root.slice(0, 16)}</code>
+              </p>
+            ))}
+          </Card>
+
+          <Card title="Dépannage">
+            <p className="settings-description">
+              Vérifie l'accès au trousseau, l'index local, la connexion au stockage, l'horloge, l'espace disque et le
+              cache. Joins le résultat à une demande d'aide si quelque chose ne fonctionne pas.
+            </p>
+
+            {diagnostics?.map((check) => (
+              <p key={check.name} className="settings-description">
+                {DIAGNOSTIC_ICONS[check.status]} {DIAGNOSTIC_LABELS_FR[check.name] ?? check.name} · {check.detail}
+              </p>
+            ))}
+
+            {diagnosticsError && (
+              <StatusMessage type="error" message={diagnosticsError} onDismiss={() => setDiagnosticsError(null)} />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button variant="secondary" onClick={handleRunDiagnostics} disabled={isDiagnosing} loading={isDiagnosing}>
+                Lancer le diagnostic
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Indice de mot de passe">
+            <p className="settings-description">
+              Affiché sur l'écran de déverrouillage si tu ne te souviens plus de la variante de ta passphrase.
+              Il est stocké en clair sur cet appareil : il ne doit pas permettre de deviner le mot de passe.
+            </p>
+
+            <Input
+              label="Indice"
+              value={passwordHint}
+              onChange={(e) => setPasswordHint(e.target.value)}
+              maxLength={100}
+              placeholder="Laisse vide pour ne pas avoir d'indice"
+            />
+
+            {hintStatus && (
+              <StatusMessage type={hintStatus.type} message={hintStatus.message} onDismiss={() => setHintStatus(null)} />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button variant="primary" onClick={handleSaveHint}>
+                Enregistrer l'indice
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Mode invité">
+            <p className="settings-description">
+              Sur une machine à laquelle tu ne fais pas confiance, une phrase d'accès distincte du mot de passe maître
+              permet de vérifier qu'un fichier est dans le coffre (noms et tailles) sans jamais dériver la Master Key.
+              Le contenu des fichiers reste verrouillé. {guestEnabled ? 'Une phrase d\'accès est définie.' : ''}
+            </p>
+
+            <Input
+              label="Phrase d'accès invité"
+              type="password"
+              value={guestPassphrase}
+              onChange={(e) => setGuestPassphrase(e.target.value)}
+              placeholder="Au moins 8 caractères"
+              helperText="Choisis-la différente du mot de passe maître."
+            />
+
+            {guestStatus && (
+              <StatusMessage type={guestStatus.type} message={guestStatus.message} onDismiss={() => setGuestStatus(null)} />
+            )}
+
+            <div className="settings-modal-actions">
+              {guestEnabled && (
+                <Button variant="secondary" onClick={handleDisableGuestMode}>
+                  Désactiver
+                </Button>
+              )}
+              <Button variant="primary" onClick={handleEnableGuestMode} disabled={!guestPassphrase}>
+                {guestEnabled ? 'Remplacer la phrase d\'accès' : 'Activer le mode invité'}
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Recherche dans le contenu">
+            <p className="settings-description">
+              Le texte des documents (texte, PDF, Word, Excel, PowerPoint, OpenDocument) est extrait sur cet ordinateur au
+              moment de l'envoi et conservé dans une base locale chiffrée par la Master Key : la recherche trouve alors un
+              fichier par ce qu'il contient. Rien n'est envoyé en clair, et le mode invité n'y a pas accès. Seuls les fichiers
+              envoyés après l'activation sont indexés.
+            </p>
+
+            {contentSearch?.enabled && (
+              <p className="settings-description">
+                {contentSearch.indexed_files} fichier{contentSearch.indexed_files > 1 ? 's' : ''} indexé{contentSearch.indexed_files > 1 ? 's' : ''}
+              </p>
+            )}
+
+            {contentSearchStatus && (
+              <StatusMessage
+                type={contentSearchStatus.type}
+                message={contentSearchStatus.message}
+                onDismiss={() => setContentSearchStatus(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button
+                variant={contentSearch?.enabled ? 'secondary' : 'primary'}
+                onClick={() => handleToggleContentSearch(!contentSearch?.enabled)}
+                disabled={contentSearch === null}
+              >
+                {contentSearch?.enabled ? 'Désactiver et effacer l\'index' : 'Activer la recherche dans le contenu'}
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Noms des objets distants">
+            <p className="settings-description">
+              Par défaut, chaque objet du bucket porte l'identifiant de son fichier. Avec des clés aléatoires, le fournisseur
+              ne peut plus rapprocher les envois d'un même fichier, et un renommage de dossier ne touche plus au bucket : seul
+              l'index chiffré fait le lien. S'applique aux fichiers envoyés ensuite. La vérification renomme, sans les
+              télécharger, les objets laissés sous un ancien format de nom.
+            </p>
+
+            {remoteKeysStatus && (
+              <StatusMessage
+                type={remoteKeysStatus.type}
+                message={remoteKeysStatus.message}
+                onDismiss={() => setRemoteKeysStatus(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button
+                variant={remoteKeys ? 'secondary' : 'primary'}
+                onClick={() => handleToggleRemoteKeys(!remoteKeys)}
+                disabled={remoteKeys === null}
+              >
+                {remoteKeys ? 'Revenir aux clés par fichier' : 'Utiliser des clés aléatoires'}
+              </Button>
+              <Button variant="secondary" onClick={handleRepairRemoteKeys} disabled={isRepairingKeys}>
+                {isRepairingKeys ? 'Vérification...' : 'Vérifier et réparer les noms'}
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Ajouter un appareil">
+            <p className="settings-description">
+              Exporte la configuration du coffre (Master Key chiffrée, bucket, réglages) dans un fichier scellé, puis importe-le
+              depuis l'écran de déverrouillage de l'autre appareil avec le code de transfert affiché ici. Le mot de passe maître
+              reste nécessaire : le fichier seul n'ouvre pas le coffre, et les identifiants Storj n'y figurent pas.
+            </p>
+
+            {bundleExport && (
+              <div className="settings-description">
+                <p>Fichier enregistré : {bundleExport.path}</p>
+                <p>
+                  Code de transfert (affiché une seule fois) :{' '}
+                  <code style={{ fontSize: '1.1rem', letterSpacing: '0.05em', userSelect: 'all' }}>{bundleExport.transfer_code}</code>
+                </p>
+              </div>
+            )}
+
+            {bundleStatus && (
+              <StatusMessage type={bundleStatus.type} message={bundleStatus.message} onDismiss={() => setBundleStatus(null)} />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button variant="primary" onClick={handleExportBundle} loading={isExportingBundle} disabled={isExportingBundle}>
+                Exporter la configuration
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Déverrouillage par l'appareil">
+            <p className="settings-description">
+              {deviceUnlock
+                ? 'Cet appareil conserve la clé de chiffrement du mot de passe dans son trousseau sécurisé : le coffre s\'ouvre sans saisir le mot de passe maître.'
+                : 'Coche « Se souvenir de cet appareil » à l\'écran de déverrouillage pour ne plus saisir le mot de passe maître ici. Pratique sur mobile, où la dérivation du mot de passe est coûteuse.'}
+            </p>
+
+            {deviceStatus && (
+              <StatusMessage type={deviceStatus.type} message={deviceStatus.message} onDismiss={() => setDeviceStatus(null)} />
+            )}
+
+            {deviceUnlock && (
+              <div className="settings-modal-actions">
+                <Button variant="secondary" onClick={handleForgetDevice}>
+                  Oublier cet appareil
+                </Button>
+              </div>
+            )}
+          </Card>
+
+          <Card title="Session">
+            <p className="settings-description">
+              Exporter la configuration ou le kit de récupération du coffre demande un mot de passe saisi récemment, même coffre ouvert.
+              {freshness && freshness.unlocked_secs !== null && (
+                <>
+                  {' '}Coffre ouvert depuis {Math.floor(freshness.unlocked_secs / 60)} min ;{' '}
+                  {freshness.authenticated_secs === null
+                    ? 'mot de passe pas encore saisi pendant cette session (déverrouillage par l\'appareil).'
+                    : `mot de passe saisi il y a ${Math.floor(freshness.authenticated_secs / 60)} min${freshness.fresh ? '' : ', il sera redemandé'}.`}
+                </>
+              )}
+            </p>
+
+            {freshnessStatus && (
+              <StatusMessage type={freshnessStatus.type} message={freshnessStatus.message} onDismiss={() => setFreshnessStatus(null)} />
+            )}
+
+            <label className="settings-description">
+              Redemander le mot de passe après{' '}
+              <select
+                value={freshness ? Math.round(freshness.window_secs / 60) : 15}
+                onChange={(e) => handleSetReauthWindow(Number(e.target.value))}
+                disabled={!freshness}
+              >
+                {[5, 15, 60, 240].map((minutes) => (
+                  <option key={minutes} value={minutes}>
+                    {minutes < 60 ? `${minutes} min` : `${minutes / 60} h`}
+                  </option>
+                ))}
+              </select>
+            </label>
+          </Card>
+
+          <Card title="Fichier clé">
+            <p className="settings-description">
+              {keyfileEnrolled
+                ? 'Ce coffre exige un fichier clé en plus du mot de passe maître. Le retirer rend le mot de passe de nouveau suffisant ; le fichier lui-même n\'est pas effacé.'
+                : 'Un fichier clé (32 octets aléatoires, sur une clé USB par exemple) peut s\'ajouter au mot de passe maître : le coffre ne s\'ouvrira plus sans lui. Un appareil mémorisé continue de déverrouiller seul.'}
+            </p>
+
+            {keyfileStatus && (
+              <StatusMessage type={keyfileStatus.type} message={keyfileStatus.message} onDismiss={() => setKeyfileStatus(null)} />
+            )}
+
+            <Input
+              label="Mot de passe maître"
+              type="password"
+              value={keyfilePassword}
+              onChange={(e) => setKeyfilePassword(e.target.value)}
+              disabled={isUpdatingKeyfile}
+              required
+            />
+
+            <div className="settings-modal-actions">
+              {keyfileEnrolled ? (
+                <>
+                  <Button variant="secondary" onClick={handleSelectKeyfile} disabled={isUpdatingKeyfile}>
+                    {keyfilePath ? 'Changer de fichier clé' : 'Sélectionner le fichier clé'}
+                  </Button>
+                  <Button
+                    variant="primary"
+                    onClick={() => handleUpdateKeyfile(false)}
+                    loading={isUpdatingKeyfile}
+                    disabled={isUpdatingKeyfile || !keyfilePath}
+                  >
+                    Retirer le fichier clé
+                  </Button>
+                </>
+              ) : (
+                <Button
+                  variant="primary"
+                  onClick={() => handleUpdateKeyfile(true)}
+                  loading={isUpdatingKeyfile}
+                  disabled={isUpdatingKeyfile || keyfileEnrolled === null}
+                >
+                  Créer un fichier clé
+                </Button>
+              )}
+            </div>
+          </Card>
+
+          <Card title="Identifiants délégués">
+            <p className="settings-description">
+              {credentials?.delegated
+                ? `Cet appareil utilise des identifiants limités au coffre, délivrés par ${credentials.issuer_url}. Ils sont renouvelés automatiquement avant leur expiration${credentials.expires_at ? ` (actuellement le ${new Date(credentials.expires_at * 1000).toLocaleString()})` : ''}.`
+                : 'Plutôt que de conserver les clés Storj principales, cet appareil peut obtenir d\'un émetteur (par exemple Wayne) des identifiants limités au coffre et à durée limitée, renouvelés automatiquement. Le jeton de renouvellement est propre à l\'appareil et révocable.'}
+            </p>
+
+            {!credentials?.delegated && (
+              <>
+                <Input
+                  label="Émetteur"
+                  value={issuerUrl}
+                  onChange={(e) => setIssuerUrl(e.target.value)}
+                  placeholder="https://wayne.example.com"
+                />
+                <Input
+                  label="Jeton de renouvellement de l'appareil"
+                  type="password"
+                  value={renewalToken}
+                  onChange={(e) => setRenewalToken(e.target.value)}
+                />
+                <Input
+                  label="Durée de validité (heures)"
+                  type="number"
+                  value={delegationHours}
+                  onChange={(e) => setDelegationHours(e.target.value)}
+                />
+              </>
+            )}
+
+            {credentialsStatus && (
+              <StatusMessage type={credentialsStatus.type} message={credentialsStatus.message} onDismiss={() => setCredentialsStatus(null)} />
+            )}
+
+            <div className="settings-modal-actions">
+              {credentials?.delegated ? (
+                <Button variant="secondary" onClick={handleRotateCredentials} loading={isDelegating} disabled={isDelegating}>
+                  Renouveler et révoquer les précédents
+                </Button>
+              ) : (
+                <Button variant="primary" onClick={handleEnrollDelegation} loading={isDelegating} disabled={isDelegating}>
+                  Passer aux identifiants délégués
+                </Button>
+              )}
+            </div>
+          </Card>
+
+          <Card title="Budget de stockage">
+            <p className="settings-description">
+              Fixe une limite à l'espace occupé sur Storj (corbeille et versions précédentes comprises) pour éviter une facture inattendue.
+              Tu es prévenu à 80 % et 95 % du budget ; un envoi qui le dépasserait est refusé.
+            </p>
+
+            {quota && (
+              <p className="settings-description">
+                Utilisé : {(quota.used_bytes / GIB).toFixed(2)} Go
+                {quota.limit_bytes !== null && quota.remaining_bytes !== null
+                  ? ` sur ${(quota.limit_bytes / GIB).toFixed(2)} Go (reste ${(quota.remaining_bytes / GIB).toFixed(2)} Go)`
+                  : ' (aucun budget défini)'}
+                {quota.trash_bytes > 0 && ` · corbeille : ${(quota.trash_bytes / GIB).toFixed(2)} Go`}
+              </p>
+            )}
+
+            <Input
+              label="Budget (Go)"
+              type="number"
+              value={quotaLimitGb}
+              onChange={(e) => setQuotaLimitGb(e.target.value)}
+              placeholder="Ex. 150"
+            />
+
+            {quotaStatus && (
+              <StatusMessage
+                type={quotaStatus.type}
+                message={quotaStatus.message}
+                onDismiss={() => setQuotaStatus(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              {quota?.limit_bytes !== null && quota?.limit_bytes !== undefined && (
+                <Button variant="secondary" onClick={() => handleSaveQuota(true)}>
+                  Retirer le budget
+                </Button>
+              )}
+              <Button variant="primary" onClick={() => handleSaveQuota(false)}>
+                Enregistrer
+              </Button>
+            </div>
+
+            <Input
+              label="Suggérer de vider la corbeille au-delà de (% de l'espace utilisé, 0 pour jamais)"
+              type="number"
+              value={trashThreshold}
+              onChange={(e) => setTrashThreshold(e.target.value)}
+              placeholder="Ex. 20"
+            />
+            <div className="settings-modal-actions">
+              <Button variant="secondary" onClick={handleSaveTrashThreshold}>
+                Enregistrer le seuil
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Cache local">
+            <p className="settings-description">
+              Les fichiers ouverts récemment restent sur cet ordinateur (toujours chiffrés) pour être rouverts
+              instantanément, même hors ligne. Au-delà de la taille choisie, les moins utilisés sont retirés ;
+              les fichiers que tu ouvres souvent sont préchargés. Les envois en attente ne sont jamais retirés.
+            </p>
+
+            {cache && (
+              <p className="settings-description">
+                En cache : {cache.cached_files} fichier(s), {(cache.cached_bytes / GIB).toFixed(2)} Go
+              </p>
+            )}
+
+            <Input
+              label="Taille maximale (Go)"
+              type="number"
+              value={cacheMaxGb}
+              onChange={(e) => setCacheMaxGb(e.target.value)}
+              placeholder="Ex. 2"
+            />
+            <Input
+              label="Fichiers fréquents à précharger"
+              type="number"
+              value={cachePrewarm}
+              onChange={(e) => setCachePrewarm(e.target.value)}
+              placeholder="Ex. 10 (0 pour désactiver)"
+            />
+
+            {cacheStatus && (
+              <StatusMessage
+                type={cacheStatus.type}
+                message={cacheStatus.message}
+                onDismiss={() => setCacheStatus(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button variant="secondary" onClick={handleClearCache}>
+                Vider le cache
+              </Button>
+              <Button variant="primary" onClick={handleSaveCachePolicy}>
+                Enregistrer
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Sauvegardes de l'index">
+            <p className="settings-description">
+              Après chaque lot de modifications (au plus cinq minutes d'attente), une copie chiffrée de l'index est
+              envoyée dans ton bucket. Si cet ordinateur est perdu, la plus récente restaure la liste de tes fichiers.
+            </p>
+
+            {backupStatus && (
+              <p className="settings-description">
+                {backupStatus.policy.enabled ? 'Activées' : 'Désactivées'} · dernière sauvegarde :{' '}
+                {backupStatus.state.last_backup_at
+                  ? new Date(backupStatus.state.last_backup_at * 1000).toLocaleString()
+                  : 'jamais'}
+              </p>
+            )}
+
+            <Input
+              label="Sauvegardes conservées"
+              type="number"
+              value={backupRetention}
+              onChange={(e) => setBackupRetention(e.target.value)}
+              placeholder="Ex. 10"
+            />
+
+            {backups?.map((backup) => (
+              <div key={backup.key} className="settings-description">
+                {new Date(backup.created_at * 1000).toLocaleString()} · {(backup.size / 1024).toFixed(0)} Ko{' '}
+                <Button variant="secondary" onClick={() => handleRestoreBackup(backup)}>
+                  Restaurer
+                </Button>
+              </div>
+            ))}
+            {backups?.length === 0 && <p className="settings-description">Aucune sauvegarde dans le bucket.</p>}
+
+            {backupMessage && (
+              <StatusMessage
+                type={backupMessage.type}
+                message={backupMessage.message}
+                onDismiss={() => setBackupMessage(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button variant="secondary" onClick={handleListBackups}>
+                Voir les sauvegardes
+              </Button>
+              <Button variant="secondary" onClick={handleBackupNow}>
+                Sauvegarder maintenant
+              </Button>
+              <Button variant="secondary" onClick={() => handleSaveBackupPolicy(!(backupStatus?.policy.enabled ?? true))}>
+                {backupStatus?.policy.enabled === false ? 'Activer' : 'Désactiver'}
+              </Button>
+              <Button variant="primary" onClick={() => handleSaveBackupPolicy(backupStatus?.policy.enabled ?? true)}>
+                Enregistrer
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Exercice de restauration">
+            <p className="settings-description">
+              Copie chaque fichier chiffré et l'index vers un dossier vide ou un autre bucket, puis vérifie que chaque
+              fichier se déchiffre depuis cette copie. Rien n'y est écrit en clair ; dans un bucket, l'index copié se
+              restaure comme une sauvegarde.
+            </p>
+
+            {lastClone && (
+              <p className="settings-description">
+                Dernier exercice : {new Date(lastClone.cloned_at * 1000).toLocaleString()} vers {lastClone.target} ·{' '}
+                {lastClone.verified_files}/{lastClone.files} fichier(s) vérifié(s)
+                {lastClone.pending_files > 0 && ` · ${lastClone.pending_files} envoi(s) en attente non copié(s)`}
+              </p>
+            )}
+            {lastClone?.failures.slice(0, 5).map((failure) => (
+              <p key={failure.key} className="settings-description">
+                ❌ {failure.key} : {failure.reason}
+              </p>
+            ))}
+
+            <Input
+              label="Dossier de destination (vide)"
+              value={cloneDir}
+              onChange={(e) => setCloneDir(e.target.value)}
+              placeholder="/Volumes/Disque externe/aether-clone"
+            />
+            <Input label="Endpoint du bucket" value={cloneEndpoint} onChange={(e) => setCloneEndpoint(e.target.value)} />
+            <Input label="Bucket" value={cloneBucket} onChange={(e) => setCloneBucket(e.target.value)} />
+            <Input label="Access Key ID" value={cloneAccessKey} onChange={(e) => setCloneAccessKey(e.target.value)} />
+            <Input
+              label="Secret Access Key"
+              type="password"
+              value={cloneSecretKey}
+              onChange={(e) => setCloneSecretKey(e.target.value)}
+            />
+
+            {cloneMessage && (
+              <StatusMessage type={cloneMessage.type} message={cloneMessage.message} onDismiss={() => setCloneMessage(null)} />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button variant="secondary" onClick={() => handleCloneVault('bucket')} disabled={isCloning}>
+                Cloner vers ce bucket
+              </Button>
+              <Button variant="primary" onClick={() => handleCloneVault('directory')} disabled={isCloning}>
+                Cloner vers ce dossier
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Instantanés du coffre">
+            <p className="settings-description">
+              Un instantané fige la liste complète de tes fichiers et dossiers, sans rien copier : les fichiers qu'il
+              référence ne sont plus supprimés du stockage, même en vidant la corbeille, tant qu'il existe.
+            </p>
+
+            {snapshots.map((snapshot) => (
+              <div key={snapshot.id} className="settings-description">
+                {snapshot.name} · {new Date(snapshot.created_at * 1000).toLocaleString()} · {snapshot.file_count}{' '}
+                élément(s){' '}
+                <Button variant="secondary" onClick={() => handleDiffSnapshot(snapshot)}>
+                  Comparer
+                </Button>{' '}
+                <Button variant="secondary" onClick={() => handleRestoreSnapshot(snapshot)}>
+                  Restaurer
+                </Button>{' '}
+                <Button variant="secondary" onClick={() => handleDeleteSnapshot(snapshot)}>
+                  Supprimer
+                </Button>
+              </div>
+            ))}
+            {snapshots.length === 0 && <p className="settings-description">Aucun instantané.</p>}
+
+            {snapshotDiff && (
+              <div className="settings-description">
+                Depuis « {snapshotDiff.snapshot.name} » :{' '}
+                {snapshotDiff.diff.added.length + snapshotDiff.diff.removed.length + snapshotDiff.diff.moved.length === 0
+                  ? 'aucun changement.'
+                  : `${snapshotDiff.diff.added.length} ajout(s), ${snapshotDiff.diff.removed.length} suppression(s), ${snapshotDiff.diff.moved.length} déplacement(s).`}
+                <ul>
+                  {snapshotDiff.diff.added.map((entry) => (
+                    <li key={`added-${entry.file_id}`}>+ {entry.logical_path}</li>
+                  ))}
+                  {snapshotDiff.diff.removed.map((entry) => (
+                    <li key={`removed-${entry.file_id}`}>− {entry.logical_path}</li>
+                  ))}
+                  {snapshotDiff.diff.moved.map((entry) => (
+                    <li key={`moved-${entry.file_id}`}>
+                      {entry.from} → {entry.to}
+                    </li>
+                  ))}
+                </ul>
+              </div>
+            )}
+
+            <Input
+              label="Nom"
+              value={snapshotName}
+              onChange={(e) => setSnapshotName(e.target.value)}
+              placeholder="Ex. Avant le grand rangement"
+            />
+
+            {snapshotStatus && (
+              <StatusMessage
+                type={snapshotStatus.type}
+                message={snapshotStatus.message}
+                onDismiss={() => setSnapshotStatus(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button variant="primary" onClick={handleCreateSnapshot}>
+                Créer un instantané
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Espaces de travail">
+            <p className="settings-description">
+              Chaque espace a son propre index, sa file d'envoi, ses réglages et son stockage (par exemple un bucket
+              personnel et un bucket professionnel), sous le même mot de passe maître.
+            </p>
+
+            {workspaceList?.workspaces.map((workspace) => (
+              <div key={workspace.id} className="settings-description">
+                {workspace.name}
+                {workspace.id === workspaceList.active ? ' · actif' : ''}{' '}
+                {workspace.id !== workspaceList.active && (
+                  <Button variant="secondary" onClick={() => handleSwitchWorkspace(workspace)}>
+                    Ouvrir
+                  </Button>
+                )}{' '}
+                <Button variant="secondary" onClick={() => handleRenameWorkspace(workspace)}>
+                  Renommer
+                </Button>{' '}
+                {workspace.id !== workspaceList.active && workspace.id !== 'default' && (
+                  <Button variant="secondary" onClick={() => handleDeleteWorkspace(workspace)}>
+                    Supprimer
+                  </Button>
+                )}
+              </div>
+            ))}
+
+            <Input
+              label="Nom"
+              value={workspaceName}
+              onChange={(e) => setWorkspaceName(e.target.value)}
+              placeholder="Ex. Travail"
+            />
+            <Input
+              label="Endpoint S3 (facultatif)"
+              value={workspaceEndpoint}
+              onChange={(e) => setWorkspaceEndpoint(e.target.value)}
+              placeholder="Ex. https://gateway.storjshare.io"
+            />
+            <Input label="Bucket" value={workspaceBucket} onChange={(e) => setWorkspaceBucket(e.target.value)} />
+            <Input label="Access key" value={workspaceAccessKey} onChange={(e) => setWorkspaceAccessKey(e.target.value)} />
+            <Input
+              label="Secret key"
+              type="password"
+              value={workspaceSecretKey}
+              onChange={(e) => setWorkspaceSecretKey(e.target.value)}
+            />
+
+            {workspaceStatus && (
+              <StatusMessage
+                type={workspaceStatus.type}
+                message={workspaceStatus.message}
+                onDismiss={() => setWorkspaceStatus(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button variant="primary" onClick={handleCreateWorkspace}>
+                Créer l'espace
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Règles d'automatisation">
+            <p className="settings-description">
+              Les règles actives s'exécutent toutes les cinq minutes tant que la synchronisation n'est pas suspendue.
+              Un import envoie les nouveaux fichiers d'un dossier local (un fichier modifié remplace l'ancien, qui
+              reste disponible comme version précédente) ; la mise à la corbeille automatique vise les fichiers envoyés il y a plus de N jours.
+            </p>
+
+            {rules.map((rule) => (
+              <div key={rule.id} className="settings-description">
+                <strong>{rule.name}</strong> {rule.enabled ? '' : '(désactivée)'} — {describeRule(rule.action)}
+                {rule.last_run && <> · dernière exécution {new Date(rule.last_run * 1000).toLocaleString()}</>}
+                {rule.last_error && <> · ⚠️ {rule.last_error}</>}
+                <div className="settings-modal-actions">
+                  <Button variant="secondary" onClick={() => handleToggleRule(rule)}>
+                    {rule.enabled ? 'Désactiver' : 'Activer'}
+                  </Button>
+                  <Button variant="secondary" onClick={() => handleDeleteRule(rule)}>
+                    Supprimer
+                  </Button>
+                </div>
+              </div>
+            ))}
+
+            <div className="password-type-selector">
+              <button
+                type="button"
+                className={`password-type-btn ${ruleType === 'import_folder' ? 'active' : ''}`}
+                onClick={() => setRuleType('import_folder')}
+              >
+                Importer un dossier local
+              </button>
+              <button
+                type="button"
+                className={`password-type-btn ${ruleType === 'auto_trash' ? 'active' : ''}`}
+                onClick={() => setRuleType('auto_trash')}
+              >
+                Corbeille automatique
+              </button>
+            </div>
+
+            <Input
+              label="Nom"
+              value={ruleName}
+              onChange={(e) => setRuleName(e.target.value)}
+              placeholder="Ex. Scans du bureau"
+            />
+            {ruleType === 'import_folder' && (
+              <Input
+                label="Dossier local"
+                value={ruleLocalDir}
+                onChange={(e) => setRuleLocalDir(e.target.value)}
+                placeholder="/chemin/vers/scans"
+              />
+            )}
+            <Input
+              label="Dossier du coffre"
+              value={ruleVaultFolder}
+              onChange={(e) => setRuleVaultFolder(e.target.value)}
+              placeholder="/tmp-scans"
+            />
+            {ruleType === 'auto_trash' && (
+              <Input
+                label="Âge minimal (jours)"
+                type="number"
+                value={ruleDays}
+                onChange={(e) => setRuleDays(e.target.value)}
+                placeholder="Ex. 30"
+              />
+            )}
+
+            {rulesStatus && (
+              <StatusMessage
+                type={rulesStatus.type}
+                message={rulesStatus.message}
+                onDismiss={() => setRulesStatus(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button variant="secondary" onClick={handleRunRules} disabled={rules.length === 0}>
+                Exécuter maintenant
+              </Button>
+              <Button variant="primary" onClick={handleAddRule}>
+                Ajouter la règle
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Hooks d'événements">
+            <p className="settings-description">
+              Préviens un script local ou un service externe quand un envoi se termine, qu'un conflit apparaît ou que
+              l'intégrité du coffre est compromise. Un script reçoit l'événement en JSON sur son entrée standard ; un
+              webhook reçoit une requête POST signée (en-tête X-Aether-Signature, HMAC-SHA256 du secret). Les chemins
+              des fichiers font partie de l'événement : ne les envoie qu'à un service de confiance.
+            </p>
+
+            {hooks.map((hook) => (
+              <div key={hook.id} className="settings-description">
+                <strong>{hook.name}</strong> {hook.enabled ? '' : '(désactivé)'} —{' '}
+                {hook.target.type === 'script' ? hook.target.program : hook.target.url} ·{' '}
+                {hook.events.map((event) => HOOK_EVENT_LABELS_FR[event]).join(', ')}
+                {hook.last_fired && <> · dernier envoi {new Date(hook.last_fired * 1000).toLocaleString()}</>}
+                {hook.last_error && <> · ⚠️ {hook.last_error}</>}
+                <div className="settings-modal-actions">
+                  <Button variant="secondary" onClick={() => handleToggleHook(hook)}>
+                    {hook.enabled ? 'Désactiver' : 'Activer'}
+                  </Button>
+                  <Button variant="secondary" onClick={() => handleDeleteHook(hook)}>
+                    Supprimer
+                  </Button>
+                </div>
+              </div>
+            ))}
+
+            <div className="password-type-selector">
+              <button
+                type="button"
+                className={`password-type-btn ${hookType === 'webhook' ? 'active' : ''}`}
+                onClick={() => setHookType('webhook')}
+              >
+                Webhook HTTPS
+              </button>
+              <button
+                type="button"
+                className={`password-type-btn ${hookType === 'script' ? 'active' : ''}`}
+                onClick={() => setHookType('script')}
+              >
+                Script local
+              </button>
+            </div>
+
+            <Input
+              label="Nom"
+              value={hookName}
+              onChange={(e) => setHookName(e.target.value)}
+              placeholder="Ex. Alerte intégrité"
+            />
+            <Input
+              label={hookType === 'script' ? 'Script (chemin absolu)' : 'Adresse du webhook'}
+              value={hookTarget}
+              onChange={(e) => setHookTarget(e.target.value)}
+              placeholder={hookType === 'script' ? '/usr/local/bin/aether-hook' : 'https://exemple.fr/aether'}
+            />
+            {(Object.keys(HOOK_EVENT_LABELS_FR) as HookEvent[]).map((event) => (
+              <label key={event} className="settings-description">
+                <input type="checkbox" checked={hookEvents.includes(event)} onChange={() => toggleHookEvent(event)} />{' '}
+                {HOOK_EVENT_LABELS_FR[event]}
+              </label>
+            ))}
+
+            {hooksStatus && (
+              <StatusMessage
+                type={hooksStatus.type}
+                message={hooksStatus.message}
+                onDismiss={() => setHooksStatus(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button variant="primary" onClick={handleAddHook}>
+                Ajouter le hook
+              </Button>
+            </div>
+          </Card>
+
+          {logSettings && (
+            <Card title="Journaux">
+              <p className="settings-description">
+                Les journaux restent sur cet ordinateur, dans des fichiers remplacés au fil de l'eau. Les chemins et
+                identifiants de fichiers y sont masqués ; le mode diagnostic les inclut, le temps d'enquêter sur un
+                problème.
+              </p>
+
+              <label className="settings-description">
+                <input
+                  type="checkbox"
+                  checked={logSettings.privacy === 'full'}
+                  onChange={() => saveLogSettings({ privacy: logSettings.privacy === 'full' ? 'redacted' : 'full' })}
+                />{' '}
+                Mode diagnostic (chemins et identifiants en clair)
+              </label>
+
+              <div className="settings-modal-actions">
+                {LOG_LEVEL_LABELS_FR.map(([level, label]) => (
+                  <Button
+                    key={level}
+                    variant={logSettings.level === level ? 'primary' : 'secondary'}
+                    onClick={() => saveLogSettings({ level })}
+                  >
+                    {label}
+                  </Button>
+                ))}
+              </div>
+
+              <Input
+                label="Niveaux par module"
+                value={logModules}
+                onChange={(e) => setLogModules(e.target.value)}
+                placeholder="aether_core::vault=debug, aether_core::storj=warn"
+              />
+
+              {logStatus && (
+                <StatusMessage type={logStatus.type} message={logStatus.message} onDismiss={() => setLogStatus(null)} />
+              )}
+
+              <div className="settings-modal-actions">
+                <Button variant="secondary" onClick={handleSaveLogModules}>
+                  Enregistrer les niveaux
+                </Button>
+              </div>
+            </Card>
+          )}
+
+          <Card title="Consulter les journaux">
+            <p className="settings-description">
+              Une copie chiffrée des journaux est conservée sur cet ordinateur. Le paquet de diagnostic reprend les
+              messages affichés et les rapports de plantage, toujours expurgés (aucun chemin, aucune clé), même en mode
+              diagnostic.
+            </p>
+
+            <Input
+              label="Module"
+              value={logFilterModule}
+              onChange={(e) => setLogFilterModule(e.target.value)}
+              placeholder="aether_core::vault"
+            />
+
+            <div className="settings-modal-actions">
+              {LOG_LEVEL_LABELS_FR.map(([level, label]) => (
+                <Button
+                  key={level}
+                  variant={logFilterLevel === level ? 'primary' : 'secondary'}
+                  onClick={() => setLogFilterLevel(level as LogLevel)}
+                >
+                  {label}
+                </Button>
+              ))}
+            </div>
+
+            <div className="settings-modal-actions">
+              {LOG_PERIODS.map(([label, period]) => (
+                <Button
+                  key={label}
+                  variant={logPeriod === period ? 'primary' : 'secondary'}
+                  onClick={() => setLogPeriod(period)}
+                >
+                  {label}
+                </Button>
+              ))}
+            </div>
+
+            {logRecords && (
+              <pre className="settings-description" style={{ maxHeight: '16rem', overflow: 'auto' }}>
+                {logRecords.length === 0
+                  ? 'Aucun message pour ces filtres.'
+                  : logRecords
+                      .map(
+                        (record) =>
+                          `${new Date(record.timestamp_ms).toLocaleString('fr-FR')} ${record.level.toUpperCase()} ${record.module} — ${record.message}`
+                      )
+                      .join('\n')}
+              </pre>
+            )}
+
+            <Input
+              label="Fichier du paquet de diagnostic"
+              value={supportBundlePath}
+              onChange={(e) => setSupportBundlePath(e.target.value)}
+              placeholder="/chemin/vers/diagnostic.json"
+            />
+
+            {logViewerStatus && (
+              <StatusMessage
+                type={logViewerStatus.type}
+                message={logViewerStatus.message}
+                onDismiss={() => setLogViewerStatus(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button variant="secondary" onClick={handleQueryLogs}>
+                Afficher
+              </Button>
+              <Button variant="primary" onClick={handleExportSupportBundle} disabled={!supportBundlePath}>
+                Exporter le paquet de diagnostic
+              </Button>
+              <Button variant="danger" onClick={handleClearLogs}>
+                Effacer
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Statistiques d'utilisation anonymes">
+            <p className="settings-description">
+              Désactivées par défaut. Si tu les actives, seuls des compteurs agrégés sont conservés sur cet ordinateur
+              (tranches de taille des transferts, codes d'erreur, fonctionnalités utilisées) : aucun nom de fichier,
+              aucun identifiant. Rien n'est envoyé automatiquement ; tu peux relire et exporter ces compteurs ci-dessous.
+            </p>
+
+            {telemetry?.enabled && (
+              <pre className="settings-description" style={{ maxHeight: '10rem', overflow: 'auto' }}>
+                {JSON.stringify({ transfers: telemetry.transfers, errors: telemetry.errors, features: telemetry.features }, null, 2)}
+              </pre>
+            )}
+
+            {telemetry?.enabled && (
+              <Input
+                label="Fichier d'export"
+                value={telemetryExportPath}
+                onChange={(e) => setTelemetryExportPath(e.target.value)}
+                placeholder="/chemin/vers/statistiques.json"
+              />
+            )}
+
+            {telemetryStatus && (
+              <StatusMessage
+                type={telemetryStatus.type}
+                message={telemetryStatus.message}
+                onDismiss={() => setTelemetryStatus(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button variant={telemetry?.enabled ? 'secondary' : 'primary'} onClick={handleToggleTelemetry}>
+                {telemetry?.enabled ? 'Désactiver et effacer' : 'Activer'}
+              </Button>
+              {telemetry?.enabled && (
+                <Button variant="secondary" onClick={handleExportTelemetry} disabled={!telemetryExportPath}>
+                  Exporter
+                </Button>
+              )}
+            </div>
+          </Card>
+
+          {crashCount > 0 && (
+            <Card title="Rapports de plantage">
+              <p className="settings-description">
+                L'application a planté {crashCount} fois. Les rapports sont chiffrés sur cet ordinateur et expurgés
+                (aucun chemin, aucune clé). Tu peux les exporter pour les joindre à un rapport de bug.
+              </p>
+
+              <Input
+                label="Fichier d'export"
+                value={crashExportPath}
+                onChange={(e) => setCrashExportPath(e.target.value)}
+                placeholder="/chemin/vers/plantages.txt"
+              />
+
+              {crashStatus && (
+                <StatusMessage
+                  type={crashStatus.type}
+                  message={crashStatus.message}
+                  onDismiss={() => setCrashStatus(null)}
+                />
+              )}
+
+              <div className="settings-modal-actions">
+                <Button variant="secondary" onClick={handleClearCrashReports}>
+                  Supprimer
+                </Button>
+                <Button variant="primary" onClick={handleExportCrashReports} disabled={!crashExportPath}>
+                  Exporter
+                </Button>
+              </div>
+            </Card>
+          )}
+
+          <Card title="Inventaire du coffre">
+            <p className="settings-description">
+              Liste tous les fichiers (corbeille comprise) avec leur taille, leur date d'envoi, leur nombre de versions
+              et leur état de synchronisation. Aucun contenu n'est déchiffré ; le fichier produit n'est pas chiffré.
+            </p>
+
+            <Input
+              label="Fichier du rapport"
+              value={reportPath}
+              onChange={(e) => setReportPath(e.target.value)}
+              placeholder="/chemin/vers/inventaire.csv"
+            />
+
+            {reportStatus && (
+              <StatusMessage
+                type={reportStatus.type}
+                message={reportStatus.message}
+                onDismiss={() => setReportStatus(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button variant="secondary" onClick={() => handleExportReport('json')} disabled={!reportPath}>
+                JSON
+              </Button>
+              <Button variant="primary" onClick={() => handleExportReport('csv')} disabled={!reportPath}>
+                CSV
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Exporter le coffre">
+            <p className="settings-description">
+              Déchiffre tous tes fichiers dans un dossier local, en conservant l'arborescence.
+              Si l'export est interrompu, relance-le avec le même dossier : il reprendra là où il s'est arrêté.
+            </p>
+
+            <Input
+              label="Dossier de destination"
+              value={exportDir}
+              onChange={(e) => setExportDir(e.target.value)}
+              placeholder="/chemin/vers/export"
+              disabled={isExporting}
+            />
+
+            {!isArchiving && exportProgress && (
+              <p className="settings-description">
+                {exportProgress.done} / {exportProgress.total} fichiers traités
+              </p>
+            )}
+
+            {exportStatus && (
+              <StatusMessage
+                type={exportStatus.type}
+                message={exportStatus.message}
+                onDismiss={() => setExportStatus(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button
+                variant="primary"
+                onClick={handleExportVault}
+                disabled={isExporting || !exportDir}
+                loading={isExporting}
+              >
+                Exporter
+              </Button>
+            </div>
+          </Card>
+
+          <Card title="Archive chiffrée">
+            <p className="settings-description">
+              Rassemble tout le coffre dans un seul fichier, sans rien déchiffrer : idéal pour un archivage à froid sur un disque USB.
+              L'archive s'ouvre avec ton mot de passe maître, y compris sans l'application (outil en ligne de commande aether-archive).
+            </p>
+
+            <Input
+              label="Fichier d'archive"
+              value={archivePath}
+              onChange={(e) => setArchivePath(e.target.value)}
+              placeholder="/Volumes/USB/coffre.aether-archive"
+              disabled={isArchiving}
+            />
+
+            {isArchiving && exportProgress && (
+              <p className="settings-description">
+                {exportProgress.done} / {exportProgress.total} fichiers traités
+              </p>
+            )}
+
+            {archiveStatus && (
+              <StatusMessage
+                type={archiveStatus.type}
+                message={archiveStatus.message}
+                onDismiss={() => setArchiveStatus(null)}
+              />
+            )}
+
+            <div className="settings-modal-actions">
+              <Button
+                variant="primary"
+                onClick={handleExportArchive}
+                disabled={isArchiving || isExporting || !archivePath}
+                loading={isArchiving}
+              >
+                Créer l'archive
+              </Button>
+            </div>
+          </Card>
         </div>
       </div>
+      {passwordConfirm}
     </div>
   )
 }
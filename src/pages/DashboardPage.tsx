@@ -1,8 +1,13 @@
 import { useState, useEffect, useRef, useMemo, useCallback } from 'react'
-import { invoke } from '@tauri-apps/api/core'
+import { Channel, invoke } from '@tauri-apps/api/core'
+import { describePreflightProblem, formatError, isCommandError, type PreflightProblem } from '../utils/commandError'
+import { listenAppEvents, type ActivitySummary, type JobState } from '../utils/appEvents'
+import { dryRun, invokeElevated, type DryRunReport } from '../utils/elevation'
+import { invokeAllPages, type Page } from '../utils/paging'
 import { Card } from '../components/Card'
 import { Button } from '../components/Button'
 import { StatusMessage } from '../components/StatusMessage'
+import { usePasswordConfirm } from '../components/PasswordConfirm'
 import { SettingsModal } from '../components/SettingsModal'
 import { WayneClient } from '../wayne_client'
 import './DashboardPage.css'
@@ -14,6 +19,149 @@ interface FileInfo {
   // Métadonnées supplémentaires depuis l'index local
   file_id?: string
   created_at?: string
+  // Envoi vers Storj en file d'attente (créé hors ligne)
+  pending?: boolean
+  // Alias : identifiant de l'alias (uuid et file_id désignent alors le fichier cible)
+  alias_id?: string
+}
+
+// Verrou consultatif d'un document, partagé entre les appareils du coffre
+interface FileLock {
+  logical_path: string
+  device: string
+  holder: string
+  acquired_at: number
+  expires_at: number
+  mine: boolean
+}
+
+interface ListedFile {
+  id: string
+  logical_path: string
+  encrypted_size: number
+  pending?: boolean
+  alias_of?: string | null
+}
+
+// Vue « intelligente » : fichiers de tout le coffre d'une catégorie de type MIME
+type SmartCategory = 'images' | 'documents' | 'video' | 'audio' | 'other'
+
+const SMART_VIEWS: Array<{ category: SmartCategory; label: string }> = [
+  { category: 'images', label: '📷 Photos' },
+  { category: 'documents', label: '📄 Documents' },
+  { category: 'video', label: '🎬 Vidéos' },
+  { category: 'audio', label: '🎵 Audio' },
+  { category: 'other', label: '📁 Autres' },
+]
+
+interface CategorizedFile extends ListedFile {
+  mime_type: string
+}
+
+// Chronologie des photos (date de prise de vue lue avant le chiffrement)
+interface TimelinePhoto {
+  file_id: string
+  logical_path: string
+  encrypted_size: number
+  taken_at: string | null
+  width: number | null
+  height: number | null
+}
+
+interface PhotoTimeline {
+  months: Array<{ month: string; count: number }>
+  month: string | null
+  photos: TimelinePhoto[]
+}
+
+// `AAAA-MM` → « juillet 2023 »
+function formatMonthBucket(month: string): string {
+  if (month === 'undated') return 'Sans date'
+  const [year, monthIndex] = month.split('-').map(Number)
+  return new Date(year, monthIndex - 1, 1).toLocaleDateString('fr-FR', { month: 'long', year: 'numeric' })
+}
+
+interface ListingPage {
+  files: ListedFile[]
+  folders: FolderInfo[]
+}
+
+// Entrée de listage → fichier affiché ; un alias s'ouvre et se télécharge comme sa cible
+function toFileInfo(file: ListedFile): FileInfo {
+  const id = file.alias_of ?? file.id
+  return {
+    uuid: id,
+    logical_path: file.logical_path,
+    encrypted_size: file.encrypted_size,
+    file_id: id,
+    pending: file.pending,
+    alias_id: file.alias_of ? file.id : undefined,
+  }
+}
+
+// Page de `list_files_and_folders` : sous-dossiers puis fichiers, triés par chemin
+interface DirectoryPage extends ListingPage {
+  next_cursor: string | null
+  total: number
+}
+
+// Contenu complet d'un dossier, une page de `list_files_and_folders` à la fois
+async function listDirectory(parentPath: string | null): Promise<ListingPage> {
+  const listing: ListingPage = { files: [], folders: [] }
+  let cursor: string | null = null
+  for (;;) {
+    const page: DirectoryPage = await invoke<DirectoryPage>('list_files_and_folders', { parentPath, page: { cursor } })
+    listing.files.push(...page.files)
+    listing.folders.push(...page.folders)
+    if (!page.next_cursor) {
+      return listing
+    }
+    cursor = page.next_cursor
+  }
+}
+
+// Objet distant de `storj_list_files` (seule la première page synchronise)
+interface RemoteFile {
+  uuid: string
+  logical_path: string | null
+  encrypted_size: number | null
+}
+
+// Étapes des opérations suivies (voir `RENAME_STEPS` côté Rust)
+const JOB_STEP_LABELS: Record<string, string> = {
+  read: 'lecture',
+  upload: 'envoi sous le nouveau nom',
+  trash: "mise à la corbeille de l'ancien",
+}
+
+interface TrashItem {
+  id: string
+  logical_path: string
+  encrypted_size: number
+  deleted_at: number
+  // Dossier supprimé avec ce fichier : il se restaure avec lui
+  folder_path: string | null
+  // Temps passé dans la corbeille, en secondes
+  age_seconds: number
+}
+
+// Résultat de `search_files` ; `snippet` : extrait du contenu, termes trouvés entre [ et ]
+interface SearchHit {
+  file_id: string
+  logical_path: string
+  snippet: string | null
+}
+
+interface TrashListing extends Page<TrashItem> {
+  // Octets libérés en vidant la corbeille
+  total_bytes: number
+}
+
+interface TrashedFolder {
+  path: string
+  deleted_at: number
+  file_count: number
+  encrypted_size: number
 }
 
 interface DashboardPageProps {
@@ -28,6 +176,70 @@ type FileTypeFilter = 'all' | 'images' | 'documents' | 'videos' | 'audio' | 'arc
 interface FolderInfo {
   name: string
   path: string
+  color: string | null
+  icon: string | null
+}
+
+type FolderPolicy = {
+  compress: boolean
+  padding: 'none' | 'standard' | 'high'
+  pin_offline: boolean
+  exclude_from_sync: boolean
+}
+
+const DEFAULT_FOLDER_POLICY: FolderPolicy = { compress: false, padding: 'none', pin_offline: false, exclude_from_sync: false }
+
+type DownloadVerification = {
+  header_valid: boolean
+  content_authentic: boolean
+  index_consistent: boolean
+  size_matches: boolean
+  hash_matches: boolean
+  local_sha256: string
+  verified: boolean
+}
+
+type PreviewKind = 'image' | 'text' | 'pdf' | 'video' | 'audio' | 'unsupported'
+
+// `mismatch` : le contenu ne correspond pas au type annoncé par l'extension
+type PreviewUnavailableReason = 'too_large' | 'unsupported' | 'mismatch'
+
+// Réponse de `preview_open` : contenu complet, début d'un texte, flux par plages ou rien
+type PreviewPayload =
+  | { mode: 'inline'; kind: PreviewKind; mime: string; data: number[] }
+  | { mode: 'text'; data: number[]; truncated: boolean; totalSize: number }
+  | { mode: 'stream'; kind: PreviewKind; url: string; size: number }
+  | { mode: 'unavailable'; kind: PreviewKind; reason: PreviewUnavailableReason; size: number }
+
+type PreviewState =
+  | { type: 'image' | 'pdf'; url: string }
+  | { type: 'video' | 'audio'; url: string }
+  | { type: 'text'; text: string; truncated: boolean; totalSize: number }
+  | { type: 'unsupported'; reason: PreviewUnavailableReason }
+
+type PurgeReport = {
+  purged: string[]
+  failed: { file_id: string; logical_path: string; reason: string }[]
+}
+
+// Ancienne version conservée quand un envoi a remplacé le fichier
+type FileVersion = {
+  id: string
+  logical_path: string
+  encrypted_size: number
+  replaced_by: string
+  replaced_at: number
+}
+
+type EncryptionDetails = {
+  format_version: number
+  cipher_id: number
+  cipher_suite: string
+  key_derivation: string
+  key_derivation_info: string
+  salt_fingerprint: string
+  commitment_valid: boolean
+  nonce_len: number
 }
 
 export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
@@ -48,6 +260,8 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
   const [sortBy, setSortBy] = useState<SortField>('name')
   const [sortOrder, setSortOrder] = useState<SortOrder>('asc')
   const [fileTypeFilter, setFileTypeFilter] = useState<FileTypeFilter>('all')
+  const [smartView, setSmartView] = useState<{ category: SmartCategory; files: FileInfo[] } | null>(null)
+  const [timeline, setTimeline] = useState<PhotoTimeline | null>(null)
   
   // États pour les statistiques
   const [userStats, setUserStats] = useState<{ total_files: number; total_size: number; files_by_type: Record<string, number> } | null>(null)
@@ -55,15 +269,53 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
   const [newFolderName, setNewFolderName] = useState('')
   const [showRenameModal, setShowRenameModal] = useState(false)
   const [fileToRename, setFileToRename] = useState<FileInfo | null>(null)
+  // Fichier dont on crée un alias (réutilise la modale de renommage, saisie du chemin complet)
+  const [fileToAlias, setFileToAlias] = useState<FileInfo | null>(null)
+  const [folderToRename, setFolderToRename] = useState<FolderInfo | null>(null)
   const [newFileName, setNewFileName] = useState('')
+  const [encryptionDetails, setEncryptionDetails] = useState<{ fileName: string; details: EncryptionDetails } | null>(null)
+  const [fileVersions, setFileVersions] = useState<{ logicalPath: string; versions: FileVersion[] } | null>(null)
+  const [folderToStyle, setFolderToStyle] = useState<FolderInfo | null>(null)
+  const [folderColor, setFolderColor] = useState('')
+  const [folderIcon, setFolderIcon] = useState('')
+  const [folderPolicy, setFolderPolicy] = useState<FolderPolicy>(DEFAULT_FOLDER_POLICY)
   const [contextMenu, setContextMenu] = useState<{ x: number; y: number; file: FileInfo } | null>(null)
   const [showTrash, setShowTrash] = useState(false)
-  const [trashItems, setTrashItems] = useState<Array<{ id: string; logical_path: string; encrypted_size: number; deleted_at: number }>>([])
+  const [trashItems, setTrashItems] = useState<TrashItem[]>([])
+  const [trashBytes, setTrashBytes] = useState(0)
+  const [trashedFolders, setTrashedFolders] = useState<TrashedFolder[]>([])
+  // Les fichiers d'un dossier supprimé s'affichent sous le dossier, pas un par un
+  const looseTrashItems = trashItems.filter((item) => !item.folder_path)
+  const visibleTrashedFolders = trashedFolders.filter((folder) =>
+    trashItems.some((item) => item.folder_path === folder.path && item.deleted_at === folder.deleted_at)
+  )
   const [showPreview, setShowPreview] = useState(false)
   const [previewFile, setPreviewFile] = useState<FileInfo | null>(null)
-  const [previewData, setPreviewData] = useState<{ data: Uint8Array; type: 'image' | 'text' | 'pdf' | 'unsupported' } | null>(null)
+  const [previewData, setPreviewData] = useState<PreviewState | null>(null)
   const [isLoadingPreview, setIsLoadingPreview] = useState(false)
   const [previewBlobUrl, setPreviewBlobUrl] = useState<string | null>(null) // Pour nettoyer les Blob URLs
+  const [isOffline, setIsOffline] = useState(false)
+  // Opération en plusieurs étapes en cours (renommage), annulable entre deux étapes
+  const [activeJob, setActiveJob] = useState<{ jobId: number; label: string; step: string; stepIndex: number; stepCount: number; state: JobState } | null>(null)
+  // Alerte d'intégrité persistante (dernière vérification automatique non propre)
+  const [integrityIssue, setIntegrityIssue] = useState<string | null>(null)
+  const [healthScore, setHealthScore] = useState<{ score: number; status: 'good' | 'warning' | 'critical' } | null>(null)
+  // Badge d'état : envois en attente, échecs, conflits, avertissements d'intégrité
+  const [activity, setActivity] = useState<ActivitySummary | null>(null)
+  // Lien de partage `aether://share/...` ouvert, en attente d'une destination
+  const [incomingShare, setIncomingShare] = useState<string | null>(null)
+  // Incrémenté quand une règle d'automatisation modifie le coffre : recharge la liste
+  const [rulesRevision, setRulesRevision] = useState(0)
+  const listingGeneration = useRef(0)
+  const [passwordConfirm, askPassword] = usePasswordConfirm()
+  // Verrous actifs, par chemin logique
+  const [fileLocks, setFileLocks] = useState<Record<string, FileLock>>({})
+  const [isReceivingShare, setIsReceivingShare] = useState(false)
+  // Lot de fichiers à remettre dans une archive chiffrée par son propre mot de passe
+  const [handoffSelection, setHandoffSelection] = useState<FileInfo[]>([])
+  const [handoffPath, setHandoffPath] = useState('')
+  const [handoffPassword, setHandoffPassword] = useState('')
+  const [isExportingHandoff, setIsExportingHandoff] = useState(false)
 
   // Ferme le menu contextuel avec la touche Escape
   useEffect(() => {
@@ -76,24 +328,243 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
     return () => window.removeEventListener('keydown', handleEscape)
   }, [contextMenu])
 
+  // Événements poussés par le backend (remplace le polling des commandes)
+  useEffect(() => {
+    const unlisten = listenAppEvents((event) => {
+      switch (event.type) {
+        case 'integrityWarning':
+          setIntegrityIssue(event.payload.detail)
+          break
+        case 'conflict':
+          setStatus({ type: 'warning', message: `⚠️ « ${event.payload.logicalPath} » existe déjà : le fichier a été placé à « ${event.payload.finalPath} »` })
+          break
+        case 'journalConflicts':
+          setStatus({
+            type: 'warning',
+            message: `⚠️ Modifié sur un autre appareil et ici en même temps, version locale conservée : ${event.payload.logicalPaths.join(', ')}`,
+          })
+          break
+        case 'lockedFileEdited':
+          setStatus({
+            type: 'warning',
+            message: `⚠️ "${event.payload.logicalPath}" est verrouillé par ${event.payload.holder} : ta modification est enregistrée, préviens-le pour éviter un conflit`,
+          })
+          break
+        case 'concurrentSessions':
+          if (event.payload.devices.length > 0) {
+            setStatus({
+              type: 'warning',
+              message: `⚠️ Coffre modifié en même temps sur ${event.payload.devices.join(', ')} : en cas de conflit, les deux versions sont gardées`,
+            })
+          }
+          break
+        case 'indexDiverged':
+          setIntegrityIssue(
+            `index différent de celui des appareils ${event.payload.devices
+              .map((device) => device.slice(0, 8))
+              .join(', ')} au même point de synchronisation`,
+          )
+          break
+        case 'trashPurged': {
+          const purged = new Set(event.payload.fileIds)
+          setTrashItems((items) => items.filter((item) => !purged.has(item.id)))
+          break
+        }
+        case 'vaultLocked':
+          setStatus({ type: 'info', message: '🔒 Coffre verrouillé' })
+          break
+        case 'recovery': {
+          // Reprise après un arrêt brutal : on ne signale que ce qui concerne l'utilisateur
+          const { resumedUploads, droppedUploads } = event.payload
+          if (droppedUploads.length > 0) {
+            setStatus({
+              type: 'warning',
+              message: `⚠️ Envois interrompus perdus, à renvoyer : ${droppedUploads.join(', ')}`,
+            })
+          } else if (resumedUploads > 0) {
+            setStatus({ type: 'success', message: `✅ ${resumedUploads} envoi(s) interrompu(s) repris` })
+          }
+          break
+        }
+        case 'shareLinkOpened':
+          setIncomingShare(event.payload.name)
+          break
+        case 'quotaWarning': {
+          const { thresholdPercent, usedBytes, limitBytes } = event.payload
+          setStatus({
+            type: 'warning',
+            message: `⚠️ ${thresholdPercent} % du budget de stockage utilisé (${formatSize(usedBytes)} / ${formatSize(limitBytes)})`,
+          })
+          break
+        }
+        case 'trashSuggestion': {
+          const { trashBytes, sharePercent } = event.payload
+          setTrashBytes(trashBytes)
+          setStatus({
+            type: 'warning',
+            message: `🗑️ La corbeille occupe ${sharePercent} % de l'espace utilisé (${formatSize(trashBytes)}) : pense à la vider`,
+          })
+          break
+        }
+        case 'rulesApplied': {
+          const { uploaded, trashed, failed } = event.payload
+          if (uploaded > 0 || trashed > 0) {
+            setRulesRevision((revision) => revision + 1)
+          }
+          if (failed > 0) {
+            setStatus({ type: 'warning', message: `⚠️ ${failed} règle(s) d'automatisation en échec (voir les paramètres)` })
+          }
+          break
+        }
+        case 'jobProgress': {
+          const { jobId, label, step, stepIndex, stepCount, state } = event.payload
+          setActiveJob(state === 'running' ? { jobId, label, step, stepIndex, stepCount, state } : null)
+          break
+        }
+        case 'activitySummary':
+          setActivity(event.payload)
+          break
+        case 'workspaceChanged': {
+          const { name, storjConfigured } = event.payload
+          setFiles([])
+          setCurrentPath('/')
+          setStorjConfigured(storjConfigured)
+          setRulesRevision((revision) => revision + 1)
+          setStatus({
+            type: storjConfigured ? 'info' : 'warning',
+            message: storjConfigured
+              ? `Espace de travail « ${name} » ouvert`
+              : `Espace de travail « ${name} » ouvert : configure son stockage pour y accéder`,
+          })
+          break
+        }
+      }
+    })
+    return () => {
+      unlisten.then((fn) => fn())
+    }
+  }, [])
+
+  // Un lien ouvert avant l'affichage du tableau de bord (lancement, coffre verrouillé) reste en attente
+  useEffect(() => {
+    invoke<{ name: string } | null>('share_pending')
+      .then((pending) => setIncomingShare(pending?.name ?? null))
+      .catch((e) => console.warn('share_pending failed:', e))
+  }, [])
+
+  const handleReceiveShare = async (target: { kind: 'vault'; folder: string } | { kind: 'disk' }) => {
+    setIsReceivingShare(true)
+    try {
+      const destination = await invoke<string>('share_receive', { target })
+      setIncomingShare(null)
+      setStatus({ type: 'success', message: `✅ Fichier partagé reçu : ${destination}` })
+      if (target.kind === 'vault') {
+        await loadFiles()
+      }
+    } catch (e) {
+      if (!(isCommandError(e) && e.code === 'dialog_cancelled')) {
+        setStatus({ type: 'error', message: `❌ ${formatError(e)}` })
+      }
+    } finally {
+      setIsReceivingShare(false)
+    }
+  }
+
+  const handleDismissShare = async () => {
+    await invoke('share_dismiss').catch((e) => console.warn('share_dismiss failed:', e))
+    setIncomingShare(null)
+  }
+
+  // Le dernier rapport d'intégrité survit au redémarrage : une dérive reste affichée
+  useEffect(() => {
+    invoke<{ checked_at: number; merkle_ok: boolean; tampered_rows: string[]; remote_drift: Array<{ file_id: string; reason: string }> } | null>('integrity_last_report')
+      .then((report) => {
+        if (report && (!report.merkle_ok || report.tampered_rows.length > 0 || report.remote_drift.length > 0)) {
+          setIntegrityIssue(
+            `${report.tampered_rows.length} entrée(s) altérée(s), ${report.remote_drift.length} objet(s) distant(s) incohérent(s)`,
+          )
+        }
+      })
+      .catch((e) => console.warn('integrity_last_report failed:', e))
+  }, [])
+
+  // Score de santé du coffre, affiché d'un coup d'œil dans l'en-tête (détail dans les paramètres)
+  useEffect(() => {
+    invoke<{ score: number; status: 'good' | 'warning' | 'critical' }>('vault_health')
+      .then(setHealthScore)
+      .catch((e) => console.warn('vault_health failed:', e))
+  }, [showSettings])
+
+  // Premier état du badge ; la suite arrive par les événements `activitySummary`
+  useEffect(() => {
+    invoke<{ pending_uploads: number; failed_transfers: number; conflicts: number; integrity_warnings: number }>('activity_summary')
+      .then((summary) =>
+        setActivity({
+          pendingUploads: summary.pending_uploads,
+          failedTransfers: summary.failed_transfers,
+          conflicts: summary.conflicts,
+          integrityWarnings: summary.integrity_warnings,
+        }),
+      )
+      .catch((e) => console.warn('activity_summary failed:', e))
+  }, [])
+
+  // Échecs et conflits vus : le backend renvoie le badge à jour par événement
+  const acknowledgeActivity = () => {
+    invoke('activity_acknowledge').catch((e) => console.warn('activity_acknowledge failed:', e))
+  }
+
+  // Verrous posés par tous les appareils ; hors ligne, la liste reste vide
+  const loadLocks = useCallback(() => {
+    if (!storjConfigured) return
+    invoke<FileLock[]>('locks_list')
+      .then((locks) => setFileLocks(Object.fromEntries(locks.map((lock) => [lock.logical_path, lock]))))
+      .catch((e) => console.warn('locks_list failed:', e))
+  }, [storjConfigured])
+
+  useEffect(() => {
+    loadLocks()
+  }, [loadLocks, files])
+
+  // Place occupée par la corbeille, affichée à côté des statistiques
+  useEffect(() => {
+    invoke<{ trash_bytes: number }>('quota_status')
+      .then((quota) => setTrashBytes(quota.trash_bytes))
+      .catch((e) => console.warn('quota_status failed:', e))
+  }, [showSettings, files])
+
   // Configuration automatique de Storj au chargement
   useEffect(() => {
     async function loadStorjConfig() {
+      // Les identifiants déjà enregistrés dans le trousseau de l'OS évitent un aller-retour Wayne
+      try {
+        const loaded = await invoke<boolean>('storj_load_credentials')
+        if (loaded) {
+          setStorjConfigured(true)
+          console.log('✅ Storj configuré depuis le trousseau du système')
+          return
+        }
+      } catch (e) {
+        console.warn('⚠️ Trousseau du système indisponible:', e)
+      }
+
       if (wayneClient && wayneClient.getAccessToken()) {
         try {
           const storjConfig = await wayneClient.getMyStorjConfig()
-          await invoke('storj_configure', {
-            config: {
-              accessKeyId: storjConfig.access_key_id,
-              secretAccessKey: storjConfig.secret_access_key,
-              endpoint: storjConfig.endpoint,
-              bucketName: storjConfig.bucket_name,
-            },
+          const config = {
+            accessKeyId: storjConfig.access_key_id,
+            secretAccessKey: storjConfig.secret_access_key,
+            endpoint: storjConfig.endpoint,
+            bucketName: storjConfig.bucket_name,
+          }
+          await invoke('storj_configure', { config })
+          await invoke('storj_save_credentials', { config }).catch((saveError) => {
+            console.warn('⚠️ Impossible d\'enregistrer les identifiants Storj dans le trousseau:', saveError)
           })
           setStorjConfigured(true)
           console.log('✅ Storj configuré automatiquement depuis Wayne')
         } catch (e) {
-          const errorMsg = e instanceof Error ? e.message : String(e)
+          const errorMsg = formatError(e)
           if (errorMsg.includes('Not Found') || errorMsg.includes('404')) {
             try {
               await wayneClient.createStorjBucket()
@@ -126,7 +597,7 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
     if (storjConfigured) {
       loadFiles()
     }
-  }, [storjConfigured, currentPath])
+  }, [storjConfigured, currentPath, rulesRevision])
 
   // Chargement des statistiques utilisateur
   useEffect(() => {
@@ -155,6 +626,32 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
     return () => clearTimeout(timer)
   }, [searchQuery])
 
+  // Fichiers dont le contenu correspond (recherche plein texte, si activée dans les paramètres)
+  const [contentMatches, setContentMatches] = useState<Map<string, string>>(new Map())
+
+  useEffect(() => {
+    const query = debouncedSearchQuery.trim()
+    if (!query) {
+      setContentMatches(new Map())
+      return
+    }
+    let cancelled = false
+    // Résultats classés par pertinence : la première page suffit
+    invoke<Page<SearchHit>>('search_files', { query })
+      .then(({ items: hits }) => {
+        if (cancelled) return
+        setContentMatches(new Map(hits.flatMap((hit) => (hit.snippet ? [[hit.file_id, hit.snippet] as [string, string]] : []))))
+      })
+      .catch((e) => console.warn('search_files failed:', e))
+    return () => {
+      cancelled = true
+    }
+  }, [debouncedSearchQuery])
+
+  function contentSnippet(file: FileInfo): string | undefined {
+    return contentMatches.get(file.file_id ?? file.uuid.replace(/-/g, ''))
+  }
+
   // Note: Le drag & drop HTML5 ne fonctionne pas dans Tauri car Tauri intercepte les événements natifs
   // Pour l'instant, on utilise uniquement le sélecteur de fichier
   // TODO: Implémenter le drag & drop via l'API Tauri native quand elle sera disponible
@@ -179,24 +676,44 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
             await new Promise(resolve => setTimeout(resolve, 1000 * attempts))
           }
           
-          // Étape 1 : Synchronise depuis Storj (cela met à jour l'index local automatiquement)
-          await invoke<Array<{ uuid: string; logical_path: string | null; encrypted_size: number | null }>>('storj_list_files')
+          // Étape 1 : Rejoue la file hors ligne puis synchronise depuis Storj (met à jour l'index local)
+          // Si Storj est injoignable, on continue en mode hors ligne avec l'index local seul
+          try {
+            await invoke('offline_flush_queue')
+            await invoke<Page<RemoteFile>>('storj_list_files')
+            setIsOffline(false)
+          } catch (e) {
+            if (!isCommandError(e) || e.code !== 'offline') throw e
+            setIsOffline(true)
+          }
           
-          // Étape 2 : Utilise la nouvelle commande pour lister les fichiers et dossiers dans le chemin actuel depuis l'index local
-          const directory = await invoke<{ files: Array<{ id: string; logical_path: string; encrypted_size: number }>; folders: FolderInfo[] }>('list_files_and_folders', {
+          // Étape 2 : Liste le dossier depuis l'index local, page par page : un dossier de
+          // plusieurs dizaines de milliers d'entrées s'affiche au fil de l'eau
+          const generation = ++listingGeneration.current
+          let firstPage = true
+          const channel = new Channel<ListingPage>()
+          channel.onmessage = (page) => {
+            // Pages d'un listage remplacé entre-temps (navigation, rechargement)
+            if (generation !== listingGeneration.current) return
+            const pageFiles: FileInfo[] = page.files.map(toFileInfo)
+            if (firstPage) {
+              firstPage = false
+              setFiles(pageFiles)
+              setFolders(page.folders)
+            } else {
+              setFiles((previous) => [...previous, ...pageFiles])
+              setFolders((previous) => [...previous, ...page.folders])
+            }
+          }
+          await invoke<{ files: number; folders: number; interrupted: boolean }>('list_directory_stream', {
             parentPath: currentPath === '/' ? null : currentPath,
+            onPage: channel,
           })
-          
-          // Convertit les fichiers en FileInfo
-          const enrichedFiles: FileInfo[] = directory.files.map((file) => ({
-            uuid: file.id,
-            logical_path: file.logical_path,
-            encrypted_size: file.encrypted_size,
-            file_id: file.id,
-          }))
-          
-          setFiles(enrichedFiles)
-          setFolders(directory.folders)
+          if (firstPage && generation === listingGeneration.current) {
+            // Dossier vide : aucune page reçue
+            setFiles([])
+            setFolders([])
+          }
           
           if (attempts > 1) {
             setStatus({ type: 'success', message: `✅ Fichiers chargés avec succès (tentative ${attempts})` })
@@ -206,7 +723,7 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
           }
           return
         } catch (e) {
-          const errorMsg = e instanceof Error ? e.message : String(e)
+          const errorMsg = formatError(e)
           
           if (attempts >= maxAttempts) {
             // Dernière tentative échouée
@@ -229,12 +746,37 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
   
   // Navigation dans un dossier
   function navigateToFolder(folderPath: string) {
+    setSmartView(null)
     setCurrentPath(folderPath)
   }
+
+  // Galerie groupée par mois de prise de vue ; sans mois, le plus récent
+  async function loadTimeline(monthBucket?: string) {
+    try {
+      setTimeline(await invoke<PhotoTimeline>('photos_timeline', { monthBucket: monthBucket ?? null }))
+    } catch (e) {
+      setStatus({ type: 'error', message: `❌ ${formatError(e)}` })
+    }
+  }
+
+  // Ouvre (ou referme) une vue intelligente à la place du dossier courant
+  async function toggleSmartView(category: SmartCategory) {
+    if (smartView?.category === category) {
+      setSmartView(null)
+      return
+    }
+    try {
+      const { items: entries } = await invokeAllPages<CategorizedFile>('list_by_category', { category })
+      setSmartView({ category, files: entries.map(toFileInfo) })
+    } catch (e) {
+      setStatus({ type: 'error', message: `❌ ${formatError(e)}` })
+    }
+  }
   
   // Navigation vers le dossier parent
   function navigateToParent() {
     if (currentPath === '/') return
+    setSmartView(null)
     const pathParts = currentPath.split('/').filter(p => p)
     pathParts.pop()
     const newPath = pathParts.length === 0 ? '/' : '/' + pathParts.join('/')
@@ -256,12 +798,154 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
     return breadcrumbs
   }
   
+  // Un fichier de la corbeille reste sur Storj : aperçu et détails fonctionnent comme pour les autres
+  function trashItemAsFile(item: TrashItem): FileInfo {
+    return { uuid: item.id, logical_path: item.logical_path, encrypted_size: item.encrypted_size }
+  }
+
   // Renomme un fichier
+  async function showEncryptionDetails(file: FileInfo) {
+    try {
+      const details = await invoke<EncryptionDetails>('get_encryption_details', { fileId: file.uuid || file.file_id })
+      setEncryptionDetails({ fileName: file.logical_path?.split('/').pop() || file.uuid, details })
+    } catch (e) {
+      setStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Liste les versions précédentes d'un fichier
+  async function showFileVersions(file: FileInfo) {
+    if (!file.logical_path) return
+    try {
+      const versions = await invoke<FileVersion[]>('file_versions', { logicalPath: file.logical_path })
+      setFileVersions({ logicalPath: file.logical_path, versions })
+    } catch (e) {
+      setStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Remet une version précédente en place ; le fichier actuel devient une version
+  async function restoreFileVersion(version: FileVersion) {
+    try {
+      await invoke<string>('file_version_restore', { versionId: version.id })
+      setFileVersions(null)
+      setStatus({ type: 'success', message: `✅ Version du ${new Date(version.replaced_at * 1000).toLocaleString('fr-FR')} restaurée` })
+      await loadFiles()
+    } catch (e) {
+      setStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  // Vérifie qu'une copie locale (export, sauvegarde restaurée) est intacte
+  async function verifyLocalCopy(file: FileInfo) {
+    try {
+      const report = await invoke<DownloadVerification>('verify_download', { fileId: file.uuid || file.file_id })
+      if (report.verified) {
+        setStatus({ type: 'success', message: `✅ Copie intacte de "${file.name}" (SHA-256 ${report.local_sha256.slice(0, 16)}…)` })
+      } else if (!report.header_valid || !report.content_authentic || !report.index_consistent) {
+        setStatus({ type: 'error', message: `❌ Le fichier "${file.name}" du coffre est altéré : impossible de prouver la copie` })
+      } else {
+        setStatus({ type: 'error', message: `❌ La copie ne correspond pas à "${file.name}"${report.size_matches ? '' : ' (taille différente)'}` })
+      }
+    } catch (e) {
+      if (isCommandError(e) && e.code === 'dialog_cancelled') return
+      setStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
+  async function toggleLock(file: FileInfo) {
+    if (!file.logical_path) return
+    const lock = fileLocks[file.logical_path]
+    try {
+      if (!lock) {
+        const acquired = await invoke<FileLock>('lock_acquire', { logicalPath: file.logical_path })
+        const until = new Date(acquired.expires_at * 1000).toLocaleString('fr-FR')
+        setStatus({ type: 'success', message: `🔒 "${file.logical_path}" verrouillé jusqu'au ${until}` })
+      } else if (lock.mine) {
+        await invoke('lock_release', { logicalPath: file.logical_path })
+        setStatus({ type: 'success', message: `🔓 "${file.logical_path}" déverrouillé` })
+      } else {
+        const password = await askPassword(
+          `Retirer le verrou de ${lock.holder} sur "${file.logical_path}" : ses modifications en cours pourraient entrer en conflit.`,
+        )
+        if (password === null) return
+        await invokeElevated('lock_break', { logicalPath: file.logical_path }, password)
+        setStatus({ type: 'success', message: `🔓 Verrou de ${lock.holder} retiré` })
+      }
+    } catch (e) {
+      setStatus({ type: 'error', message: formatError(e) })
+    }
+    loadLocks()
+  }
+
+  function toggleHandoff(file: FileInfo) {
+    setHandoffSelection((current) =>
+      current.some((f) => f.uuid === file.uuid) ? current.filter((f) => f.uuid !== file.uuid) : [...current, file],
+    )
+  }
+
+  async function exportHandoff() {
+    if (handoffSelection.length === 0 || !handoffPath.trim()) return
+    setIsExportingHandoff(true)
+    try {
+      const summary = await invoke<{ files: number; bytes: number; failed: string[] }>('export_encrypted_selection', {
+        fileIds: handoffSelection.map((f) => f.uuid),
+        password: handoffPassword,
+        destinationPath: handoffPath.trim(),
+      })
+      setStatus({
+        type: 'success',
+        message: `📦 ${summary.files} fichier(s) exportés (${formatSize(summary.bytes)}) : l'archive s'ouvre avec aether-archive et le mot de passe du lot`,
+      })
+      setHandoffSelection([])
+      setHandoffPassword('')
+    } catch (e) {
+      setStatus({ type: 'error', message: formatError(e) })
+    } finally {
+      setIsExportingHandoff(false)
+    }
+  }
+
+  function openFolderAppearance(folder: FolderInfo) {
+    setFolderToStyle(folder)
+    setFolderColor(folder.color ?? '')
+    setFolderIcon(folder.icon ?? '')
+    setFolderPolicy(DEFAULT_FOLDER_POLICY)
+    invoke<FolderPolicy>('folder_policy_get', { folderPath: folder.path })
+      .then(setFolderPolicy)
+      .catch((e) => setStatus({ type: 'error', message: formatError(e) }))
+  }
+
+  async function saveFolderAppearance(reset: boolean) {
+    if (!folderToStyle) return
+    try {
+      const appearance = await invoke<{ color: string | null; icon: string | null }>('set_folder_appearance', {
+        folderPath: folderToStyle.path,
+        color: reset ? null : folderColor || null,
+        icon: reset ? null : folderIcon || null,
+      })
+      await invoke('folder_policy_set', {
+        folderPath: folderToStyle.path,
+        policy: reset ? DEFAULT_FOLDER_POLICY : folderPolicy,
+      })
+      setFolders((current) =>
+        current.map((folder) => (folder.path === folderToStyle.path ? { ...folder, ...appearance } : folder))
+      )
+      setFolderToStyle(null)
+    } catch (e) {
+      setStatus({ type: 'error', message: formatError(e) })
+    }
+  }
+
   async function handleRename(file: FileInfo) {
     if (!file.logical_path) {
       setStatus({ type: 'error', message: 'Chemin logique non disponible pour ce fichier.' })
       return
     }
+    if (file.alias_id) {
+      setStatus({ type: 'error', message: "Un alias ne se renomme pas : supprime-le puis crée-en un nouveau." })
+      return
+    }
 
     setFileToRename(file)
     const currentName = file.logical_path.split('/').pop() || ''
@@ -269,7 +953,78 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
     setShowRenameModal(true)
   }
 
+  // Alias : le fichier apparaît à un autre emplacement sans dupliquer son contenu chiffré
+  function handleCreateAlias(file: FileInfo) {
+    if (!file.logical_path) {
+      setStatus({ type: 'error', message: 'Chemin logique non disponible pour ce fichier.' })
+      return
+    }
+    setFileToAlias(file)
+    setNewFileName(file.logical_path)
+    setShowRenameModal(true)
+  }
+
+  async function confirmCreateAlias(file: FileInfo) {
+    setIsLoading(true)
+    try {
+      const alias = await invoke<{ logical_path: string }>('alias_create', {
+        targetId: file.uuid,
+        logicalPath: newFileName.trim(),
+      })
+      setStatus({ type: 'success', message: `🔗 Alias créé : "${alias.logical_path}"` })
+      setShowRenameModal(false)
+      setFileToAlias(null)
+      setNewFileName('')
+      await loadFiles()
+    } catch (e) {
+      setStatus({ type: 'error', message: formatError(e) })
+    } finally {
+      setIsLoading(false)
+    }
+  }
+
+  function handleRenameFolder(folder: FolderInfo) {
+    setFolderToRename(folder)
+    setNewFileName(folder.name)
+    setShowRenameModal(true)
+  }
+
+  async function confirmRenameFolder(folder: FolderInfo) {
+    const newName = newFileName.trim()
+    if (!newName || newName.includes('/')) {
+      setStatus({ type: 'error', message: 'Nom de dossier invalide.' })
+      return
+    }
+    const oldPath = folder.path.replace(/\/+$/, '')
+    const newPath = `${oldPath.slice(0, oldPath.lastIndexOf('/'))}/${newName}`
+
+    setIsLoading(true)
+    try {
+      const report = await invoke<{ moved_entries: number; pending_rebinds: number }>('rename_folder', { oldPath, newPath })
+      setStatus({
+        type: 'success',
+        message: `✅ Dossier renommé : "${newName}" (${report.moved_entries} élément(s) déplacé(s))`,
+      })
+      setShowRenameModal(false)
+      setFolderToRename(null)
+      setNewFileName('')
+      await loadFiles()
+    } catch (e) {
+      setStatus({ type: 'error', message: formatError(e) })
+    } finally {
+      setIsLoading(false)
+    }
+  }
+
   async function confirmRename() {
+    if (fileToAlias) {
+      await confirmCreateAlias(fileToAlias)
+      return
+    }
+    if (folderToRename) {
+      await confirmRenameFolder(folderToRename)
+      return
+    }
     if (!fileToRename || !fileToRename.logical_path || !newFileName.trim()) {
       setStatus({ type: 'error', message: 'Nom de fichier invalide.' })
       return
@@ -299,16 +1054,9 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
       // Recharge la liste des fichiers
       console.log('🔄 Rechargement des fichiers après renommage...')
       try {
-        await invoke<Array<{ uuid: string; logical_path: string | null; encrypted_size: number | null }>>('storj_list_files')
-        const directory = await invoke<{ files: Array<{ id: string; logical_path: string; encrypted_size: number }>; folders: FolderInfo[] }>('list_files_and_folders', {
-          parentPath: currentPath === '/' ? null : currentPath,
-        })
-        const enrichedFiles: FileInfo[] = directory.files.map((file) => ({
-          uuid: file.id,
-          logical_path: file.logical_path,
-          encrypted_size: file.encrypted_size,
-          file_id: file.id,
-        }))
+        await invoke<Page<RemoteFile>>('storj_list_files')
+        const directory = await listDirectory(currentPath === '/' ? null : currentPath)
+        const enrichedFiles: FileInfo[] = directory.files.map(toFileInfo)
         setFiles(enrichedFiles)
         setFolders(directory.folders)
         console.log('✅ Fichiers rechargés après renommage')
@@ -327,24 +1075,43 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
         }
       }
     } catch (e) {
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      if (isCommandError(e) && e.code === 'cancelled') {
+        setStatus({ type: 'info', message: 'Renommage annulé : le fichier est resté à sa place.' })
+        return
+      }
+      const errorMsg = formatError(e)
       setStatus({ type: 'error', message: `❌ Erreur lors du renommage: ${errorMsg}` })
     } finally {
       setIsLoading(false)
     }
   }
 
+  // Demande l'arrêt de l'opération en cours ; elle s'interrompt avant sa prochaine étape
+  async function cancelActiveJob() {
+    if (!activeJob) return
+    try {
+      await invoke<boolean>('job_cancel', { jobId: activeJob.jobId })
+    } catch (e) {
+      setStatus({ type: 'error', message: `Impossible d'annuler : ${formatError(e)}` })
+    }
+  }
+
   // Charge la corbeille
   async function loadTrash() {
     setIsLoading(true)
     setStatus(null)
     
     try {
-      const items = await invoke<Array<{ id: string; logical_path: string; encrypted_size: number; deleted_at: number }>>('list_trash')
-      setTrashItems(items)
-      console.log('✅ Corbeille chargée:', items.length, 'éléments')
+      const [listing, folders] = await Promise.all([
+        invokeAllPages<TrashItem, TrashListing>('list_trash'),
+        invokeAllPages<TrashedFolder>('list_trashed_folders'),
+      ])
+      setTrashItems(listing.items)
+      setTrashBytes(listing.total_bytes)
+      setTrashedFolders(folders.items)
+      console.log('✅ Corbeille chargée:', listing.items.length, 'éléments')
     } catch (e) {
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      const errorMsg = formatError(e)
       setStatus({ type: 'error', message: `Erreur lors du chargement de la corbeille: ${errorMsg}` })
     } finally {
       setIsLoading(false)
@@ -372,24 +1139,83 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
         }
       }
     } catch (e) {
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      const errorMsg = formatError(e)
       setStatus({ type: 'error', message: `Erreur lors de la restauration: ${errorMsg}` })
     } finally {
       setIsLoading(false)
     }
   }
 
+  // Restaure un dossier de la corbeille, avec tout son contenu, à son emplacement d'origine
+  async function handleRestoreFolderFromTrash(folder: TrashedFolder) {
+    setIsLoading(true)
+    setStatus({ type: 'info', message: 'Restauration du dossier...' })
+
+    try {
+      const restored = await invoke<number>('restore_folder_from_trash', {
+        folderPath: folder.path,
+        deletedAt: folder.deleted_at,
+      })
+      setStatus({ type: 'success', message: `✅ Dossier restauré (${restored} élément${restored > 1 ? 's' : ''})` })
+      await loadTrash()
+      await loadFiles()
+    } catch (e) {
+      setStatus({ type: 'error', message: `Erreur lors de la restauration: ${formatError(e)}` })
+    } finally {
+      setIsLoading(false)
+    }
+  }
+
+  // Envoie un dossier et tout son contenu à la corbeille
+  async function handleTrashFolder(folder: FolderInfo) {
+    let summary = ''
+    try {
+      const plan = await dryRun('trash_folder', { folderPath: folder.path })
+      summary = ` (${plan.entries} élément(s), ${formatSize(plan.bytes)})`
+    } catch (e) {
+      setStatus({ type: 'error', message: formatError(e) })
+      return
+    }
+    if (!confirm(`Mettre le dossier "${folder.name}" et tout son contenu${summary} à la corbeille ?`)) {
+      return
+    }
+    setIsLoading(true)
+    setStatus({ type: 'info', message: `🗑️ Suppression du dossier "${folder.name}"...` })
+
+    try {
+      await invoke<number>('trash_folder', { folderPath: folder.path })
+      setStatus({ type: 'success', message: `✅ Dossier "${folder.name}" déplacé dans la corbeille` })
+      await loadFiles()
+    } catch (e) {
+      setStatus({ type: 'error', message: formatError(e) })
+    } finally {
+      setIsLoading(false)
+    }
+  }
+
   // Supprime définitivement un fichier de la corbeille
+  // (mot de passe redemandé, vérifié par le backend)
   async function handlePermanentlyDelete(fileId: string, fileName: string) {
-    if (!confirm(`Es-tu sûr de vouloir supprimer définitivement "${fileName}" ? Cette action est irréversible.`)) {
+    let plan: DryRunReport
+    try {
+      plan = await dryRun('permanently_delete_from_trash', { fileId })
+    } catch (e) {
+      setStatus({ type: 'error', message: `Erreur lors de la suppression définitive: ${formatError(e)}` })
+      return
+    }
+    const remote =
+      plan.deleted_objects > 0
+        ? `${formatSize(plan.bytes)} libérés sur le stockage distant`
+        : 'objet distant conservé, encore lu par un autre fichier ou un instantané'
+    const password = await askPassword(`Supprimer définitivement "${fileName}" de la corbeille (${remote}).`)
+    if (password === null) {
       return
     }
-
     setIsLoading(true)
     setStatus({ type: 'info', message: `Suppression définitive de "${fileName}"...` })
     
     try {
-      await invoke('permanently_delete_from_trash', { fileId })
+      await invokeElevated('permanently_delete_from_trash', { fileId }, password)
       setStatus({ type: 'success', message: `✅ Fichier "${fileName}" supprimé définitivement` })
       await loadTrash() // Recharge la corbeille
       
@@ -404,7 +1230,7 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
         }
       }
     } catch (e) {
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      const errorMsg = formatError(e)
       setStatus({ type: 'error', message: `Erreur lors de la suppression définitive: ${errorMsg}` })
     } finally {
       setIsLoading(false)
@@ -412,18 +1238,41 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
   }
 
   // Vide complètement la corbeille
+  // (mot de passe redemandé, vérifié par le backend)
   async function handleEmptyTrash() {
-    if (!confirm(`Es-tu sûr de vouloir vider complètement la corbeille ? Tous les fichiers seront supprimés définitivement. Cette action est irréversible.`)) {
+    let plan: DryRunReport
+    try {
+      plan = await dryRun('empty_trash')
+    } catch (e) {
+      setStatus({ type: 'error', message: `Erreur lors du vidage de la corbeille: ${formatError(e)}` })
+      return
+    }
+    if (plan.entries === 0) {
+      setStatus({ type: 'info', message: 'La corbeille est déjà vide.' })
+      return
+    }
+    const password = await askPassword(
+      `Vider la corbeille : ${plan.entries} élément(s) supprimé(s) définitivement, ${plan.deleted_objects} objet(s) effacé(s) du stockage distant (${formatSize(plan.bytes)} libérés).`,
+    )
+    if (password === null) {
       return
     }
-
     setIsLoading(true)
     setStatus({ type: 'info', message: 'Vidage de la corbeille...' })
     
     try {
-      const count = await invoke<number>('empty_trash')
-      setStatus({ type: 'success', message: `✅ Corbeille vidée : ${count} fichier(s) supprimé(s) définitivement` })
-      await loadTrash() // Recharge la corbeille (devrait être vide maintenant)
+      const report = await invokeElevated<PurgeReport>('empty_trash', undefined, password)
+      if (report.failed.length === 0) {
+        setStatus({ type: 'success', message: `✅ Corbeille vidée : ${report.purged.length} fichier(s) supprimé(s) définitivement` })
+      } else {
+        // Les fichiers en échec restent dans la corbeille : un nouveau vidage les retentera
+        const failedPaths = report.failed.map((f) => f.logical_path).join(', ')
+        setStatus({
+          type: 'error',
+          message: `⚠️ ${report.purged.length} fichier(s) supprimé(s), ${report.failed.length} conservé(s) dans la corbeille (suppression distante non confirmée) : ${failedPaths}`,
+        })
+      }
+      await loadTrash() // Recharge la corbeille (seuls les échecs y restent)
       
       // Met à jour les métadonnées Wayne si nécessaire
       if (wayneClient && wayneClient.getAccessToken()) {
@@ -437,7 +1286,7 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
         }
       }
     } catch (e) {
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      const errorMsg = formatError(e)
       setStatus({ type: 'error', message: `Erreur lors du vidage de la corbeille: ${errorMsg}` })
     } finally {
       setIsLoading(false)
@@ -457,55 +1306,32 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
     setStatus(null)
 
     try {
-      // Télécharge et déchiffre le fichier
-      const decryptedData = await invoke<number[]>('preview_file', {
+      // Le backend choisit le mode selon le type et la taille : les gros fichiers ne sont jamais chargés en entier ici
+      const payload = await invoke<PreviewPayload>('preview_open', {
         fileId: file.uuid || file.file_id,
       })
 
-      // Convertit en Uint8Array
-      const dataArray = new Uint8Array(decryptedData)
-
-      // Détermine le type de fichier
-      const fileName = file.logical_path.split('/').pop() || ''
-      const ext = fileName.split('.').pop()?.toLowerCase() || ''
-      
-      let fileType: 'image' | 'text' | 'pdf' | 'unsupported' = 'unsupported'
-      
-      // Images
-      const imageExts = ['jpg', 'jpeg', 'png', 'gif', 'webp', 'svg', 'bmp']
-      if (imageExts.includes(ext)) {
-        fileType = 'image'
-      }
-      // PDF
-      else if (ext === 'pdf') {
-        fileType = 'pdf'
-      }
-      // Texte
-      else {
-        const textExts = ['txt', 'md', 'json', 'xml', 'html', 'css', 'js', 'ts', 'tsx', 'jsx', 'py', 'rs', 'go', 'java', 'c', 'cpp', 'h', 'hpp', 'yaml', 'yml', 'ini', 'conf', 'log', 'csv']
-        if (textExts.includes(ext)) {
-          // Vérifie si c'est du texte valide UTF-8
-          try {
-            const decoder = new TextDecoder('utf-8', { fatal: true })
-            decoder.decode(dataArray)
-            fileType = 'text'
-          } catch {
-            // Pas du texte UTF-8 valide
-            fileType = 'unsupported'
-          }
-        }
-      }
-
-      setPreviewData({ data: dataArray, type: fileType })
-      
-      // Crée une Blob URL pour les images et PDF
-      if (fileType === 'image' || fileType === 'pdf') {
-        const blob = new Blob([dataArray], { type: fileType === 'image' ? 'image/*' : 'application/pdf' })
+      if (payload.mode === 'text') {
+        setPreviewData({
+          type: 'text',
+          text: new TextDecoder('utf-8').decode(new Uint8Array(payload.data)),
+          truncated: payload.truncated,
+          totalSize: payload.totalSize,
+        })
+      } else if (payload.mode === 'stream') {
+        // Servi par plages via le protocole aether-preview (lecture progressive)
+        setPreviewData({ type: payload.kind === 'pdf' ? 'pdf' : payload.kind === 'audio' ? 'audio' : 'video', url: payload.url })
+      } else if (payload.mode === 'inline' && (payload.kind === 'image' || payload.kind === 'pdf')) {
+        // Type vérifié côté Rust sur le contenu, jamais déduit du nom ici
+        const blob = new Blob([new Uint8Array(payload.data)], { type: payload.mime })
         const blobUrl = URL.createObjectURL(blob)
         setPreviewBlobUrl(blobUrl)
+        setPreviewData({ type: payload.kind, url: blobUrl })
+      } else {
+        setPreviewData({ type: 'unsupported', reason: payload.mode === 'unavailable' ? payload.reason : 'unsupported' })
       }
     } catch (e) {
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      const errorMsg = formatError(e)
       setStatus({ type: 'error', message: `Erreur lors de l'aperçu: ${errorMsg}` })
       setShowPreview(false)
       setPreviewFile(null)
@@ -535,6 +1361,8 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
       URL.revokeObjectURL(previewBlobUrl)
       setPreviewBlobUrl(null)
     }
+    // Libère le contenu déchiffré gardé par le backend pour un aperçu en flux
+    invoke('preview_close').catch(() => {})
     setShowPreview(false)
     setPreviewFile(null)
     setPreviewData(null)
@@ -563,6 +1391,15 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
     }
   }
 
+  // Durée passée dans la corbeille (calculée par le backend)
+  function formatTrashAge(seconds: number): string {
+    const days = Math.floor(seconds / 86400)
+    if (days > 0) return `${days} j`
+    const hours = Math.floor(seconds / 3600)
+    if (hours > 0) return `${hours} h`
+    return `${Math.floor(seconds / 60)} min`
+  }
+
   // Crée un nouveau dossier
   async function handleCreateFolder() {
     if (!newFolderName.trim()) {
@@ -585,15 +1422,8 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
       // Recharge directement depuis l'index local (pas besoin de synchroniser Storj pour un dossier vide)
       console.log('🔄 Rechargement des fichiers après création de dossier...')
       try {
-        const directory = await invoke<{ files: Array<{ id: string; logical_path: string; encrypted_size: number }>; folders: FolderInfo[] }>('list_files_and_folders', {
-          parentPath: currentPath === '/' ? null : currentPath,
-        })
-        const enrichedFiles: FileInfo[] = directory.files.map((file) => ({
-          uuid: file.id,
-          logical_path: file.logical_path,
-          encrypted_size: file.encrypted_size,
-          file_id: file.id,
-        }))
+        const directory = await listDirectory(currentPath === '/' ? null : currentPath)
+        const enrichedFiles: FileInfo[] = directory.files.map(toFileInfo)
         setFiles(enrichedFiles)
         setFolders(directory.folders)
         console.log('✅ Fichiers rechargés après création de dossier:', { files: enrichedFiles.length, folders: directory.folders.length })
@@ -603,7 +1433,7 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
         await loadFiles()
       }
     } catch (e) {
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      const errorMsg = formatError(e)
       setStatus({ type: 'error', message: `Erreur lors de la création du dossier: ${errorMsg}` })
     } finally {
       setIsLoading(false)
@@ -691,16 +1521,9 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
           console.log('🔄 Rechargement des fichiers après upload...')
           try {
             // Synchronise depuis Storj puis recharge depuis l'index local
-            await invoke<Array<{ uuid: string; logical_path: string | null; encrypted_size: number | null }>>('storj_list_files')
-            const directory = await invoke<{ files: Array<{ id: string; logical_path: string; encrypted_size: number }>; folders: FolderInfo[] }>('list_files_and_folders', {
-              parentPath: currentPath === '/' ? null : currentPath,
-            })
-            const enrichedFiles: FileInfo[] = directory.files.map((file) => ({
-              uuid: file.id,
-              logical_path: file.logical_path,
-              encrypted_size: file.encrypted_size,
-              file_id: file.id,
-            }))
+            await invoke<Page<RemoteFile>>('storj_list_files')
+            const directory = await listDirectory(currentPath === '/' ? null : currentPath)
+            const enrichedFiles: FileInfo[] = directory.files.map(toFileInfo)
             setFiles(enrichedFiles)
             setFolders(directory.folders)
             console.log('✅ Fichiers rechargés après upload:', { files: enrichedFiles.length, folders: directory.folders.length })
@@ -711,7 +1534,7 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
           }
           return
         } catch (e) {
-          const errorMsg = e instanceof Error ? e.message : String(e)
+          const errorMsg = formatError(e)
           
           if (attempts >= maxAttempts) {
             // Dernière tentative échouée
@@ -787,6 +1610,98 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
     }
   }
 
+  // Envoi d'un fichier lu directement depuis le disque (fichiers volumineux, rien ne transite par l'IPC)
+  async function handleUploadFromDisk() {
+    if (!storjConfigured) {
+      setStatus({ type: 'error', message: 'Storj n\'est pas configuré. Connecte-toi à Wayne.' })
+      return
+    }
+    setIsUploading(true)
+    try {
+      const result = await invoke<{ logical_path: string; deduplicated: boolean }>('upload_local_file', {
+        localPath: null,
+        parentPath: currentPath === '/' ? null : currentPath,
+      })
+      setStatus({
+        type: 'success',
+        message: result.deduplicated
+          ? `✅ "${result.logical_path}" ajouté sans transfert : contenu identique déjà présent dans le coffre`
+          : `✅ "${result.logical_path}" chiffré et envoyé depuis le disque`,
+      })
+      await loadFiles()
+    } catch (e) {
+      if (!isCommandError(e) || e.code !== 'dialog_cancelled') {
+        setStatus({ type: 'error', message: formatError(e) })
+      }
+    } finally {
+      setIsUploading(false)
+    }
+  }
+
+  // Envoi de plusieurs fichiers, ou de dossiers entiers, lus depuis le disque côté Rust
+  async function handleUploadSelection(directories: boolean) {
+    if (!storjConfigured) {
+      setStatus({ type: 'error', message: 'Storj n\'est pas configuré. Connecte-toi à Wayne.' })
+      return
+    }
+    let selection: Array<{ local_path: string; relative_dir: string }>
+    try {
+      selection = await invoke('select_files_for_upload', { directories })
+    } catch (e) {
+      if (!isCommandError(e) || e.code !== 'dialog_cancelled') {
+        setStatus({ type: 'error', message: formatError(e) })
+      }
+      return
+    }
+    if (selection.length === 0) {
+      setStatus({ type: 'info', message: 'Aucun fichier à envoyer dans la sélection.' })
+      return
+    }
+
+    const base = currentPath === '/' ? '' : currentPath.replace(/\/$/, '')
+    const plan = selection.map((item) => ({
+      localPath: item.local_path,
+      parentPath: item.relative_dir ? `${base}/${item.relative_dir}` : (base || null),
+    }))
+
+    setIsUploading(true)
+    // Tous les problèmes du lot sont signalés avant le premier envoi
+    try {
+      setStatus({ type: 'info', message: `🔎 Vérification de ${selection.length} fichier${selection.length > 1 ? 's' : ''}…` })
+      const report = await invoke<{ problems: PreflightProblem[] }>('upload_preflight', { items: plan })
+      if (report.problems.length > 0) {
+        setIsUploading(false)
+        setStatus({
+          type: 'error',
+          message: `Envoi non commencé, ${report.problems.length} problème(s) : ${report.problems.map(describePreflightProblem).join(' ; ')}`,
+        })
+        return
+      }
+    } catch (e) {
+      setIsUploading(false)
+      setStatus({ type: 'error', message: formatError(e) })
+      return
+    }
+
+    const failed: string[] = []
+    for (const [i, item] of selection.entries()) {
+      const name = item.local_path.split(/[\\/]/).pop() || item.local_path
+      setStatus({ type: 'info', message: `📤 Envoi ${i + 1}/${selection.length} : ${name}` })
+      try {
+        // Import de dossiers : transfert de masse, les aperçus et petits envois passent devant
+        await invoke('upload_local_file', { ...plan[i], priority: directories ? 'bulk' : null })
+      } catch (e) {
+        console.warn('⚠️ Envoi échoué:', item.local_path, e)
+        failed.push(`${name} (${formatError(e)})`)
+      }
+    }
+    setIsUploading(false)
+    await loadFiles()
+    setStatus(failed.length === 0
+      ? { type: 'success', message: `✅ ${selection.length} fichier${selection.length > 1 ? 's' : ''} chiffré${selection.length > 1 ? 's' : ''} et envoyé${selection.length > 1 ? 's' : ''}` }
+      : { type: 'error', message: `${failed.length} envoi(s) en échec : ${failed.join(', ')}` })
+  }
+
   // Sélection de fichier via bouton
   function handleFileSelect() {
     fileInputRef.current?.click()
@@ -803,7 +1718,8 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
     }
   }
 
-  // Téléchargement d'un fichier avec retry
+  // Téléchargement d'un fichier : la destination est choisie d'abord, puis le fichier y est
+  // déchiffré au fil du téléchargement
   async function handleDownload(file: FileInfo) {
     if (!file.logical_path) {
       setStatus({ type: 'error', message: 'Chemin logique non disponible pour ce fichier.' })
@@ -814,60 +1730,39 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
     setStatus(null)
 
     const fileName = file.logical_path.split('/').pop() || 'fichier'
-    let attempts = 0
-    const maxAttempts = 3
-
-    while (attempts < maxAttempts) {
-      try {
-        attempts++
-        
-        if (attempts > 1) {
-          setStatus({ type: 'info', message: `🔄 Nouvelle tentative de téléchargement (${attempts}/${maxAttempts})...` })
-          await new Promise(resolve => setTimeout(resolve, 1000 * attempts))
-        } else {
-          setStatus({ type: 'info', message: `📥 Téléchargement de "${fileName}" depuis Storj...` })
-        }
-
-        // Télécharge depuis Storj
-        const encryptedData = await invoke<number[]>('storj_download_file_by_path', {
-          logicalPath: file.logical_path,
+    const unlisten = await listenAppEvents((event) => {
+      if (event.type === 'transferProgress' && event.payload.direction === 'download' && event.payload.bytesTotal > 0) {
+        const { bytesDone, bytesTotal, bytesPerSecond, etaSeconds } = event.payload
+        const percent = Math.floor((bytesDone * 100) / bytesTotal)
+        const rate = bytesPerSecond > 0 ? `, ${formatSize(bytesPerSecond)}/s` : ''
+        const eta = etaSeconds !== null && bytesDone < bytesTotal ? `, reste ${formatEta(etaSeconds)}` : ''
+        setStatus({
+          type: 'info',
+          message: `📥 Téléchargement de "${fileName}" : ${percent} % (${formatSize(bytesDone)} / ${formatSize(bytesTotal)}${rate}${eta})`,
         })
+      }
+    })
+    try {
+      // Un alias se télécharge et se déchiffre sous le chemin de sa cible
+      let logicalPath = file.logical_path
+      if (file.alias_id) {
+        const target = await invoke<{ logical_path: string }>('alias_resolve', { aliasId: file.alias_id })
+        logicalPath = target.logical_path
+      }
 
-        setStatus({ type: 'info', message: `🔓 Déchiffrement de "${fileName}"...` })
-
-        // Déchiffre le fichier
-        const decrypted = await invoke<number[]>('storage_decrypt_file', {
-          encryptedData: encryptedData,
-          logicalPath: file.logical_path,
-        })
-
-        setStatus({ type: 'info', message: `💾 Sauvegarde de "${fileName}"...` })
-
-        // Sauvegarde le fichier
-        const savedPath = await invoke<string>('save_decrypted_file', {
-          data: decrypted,
-          suggestedName: fileName,
+      const savedPath = await invoke<string>('download_to_file', { logicalPath })
+      setStatus({ type: 'success', message: `✅ Fichier téléchargé : ${savedPath}` })
+    } catch (e) {
+      if (!(isCommandError(e) && e.code === 'dialog_cancelled')) {
+        setStatus({
+          type: 'error',
+          message: `Erreur lors du téléchargement de "${fileName}".\n\n💡 Suggestions :\n• Vérifie ta connexion Internet\n• Le fichier peut être corrompu\n• Réessaie dans quelques instants\n\nErreur : ${formatError(e)}`,
         })
-
-        setStatus({ type: 'success', message: `✅ Fichier téléchargé : ${savedPath}` })
-        return
-      } catch (e) {
-        const errorMsg = e instanceof Error ? e.message : String(e)
-        
-        if (attempts >= maxAttempts) {
-          // Dernière tentative échouée
-          setStatus({ 
-            type: 'error', 
-            message: `Erreur lors du téléchargement de "${fileName}" après ${maxAttempts} tentatives.\n\n💡 Suggestions :\n• Vérifie ta connexion Internet\n• Le fichier peut être corrompu\n• Réessaie dans quelques instants\n\nErreur : ${errorMsg}` 
-          })
-        } else {
-          // Continue avec le retry
-          console.warn(`Tentative ${attempts} de téléchargement échouée, nouvelle tentative...`, errorMsg)
-        }
       }
+    } finally {
+      unlisten()
+      setIsLoading(false)
     }
-    
-    setIsLoading(false)
   }
 
   // Suppression d'un fichier avec retry
@@ -877,6 +1772,18 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
       return
     }
 
+    // Supprimer un alias ne touche pas au fichier désigné
+    if (file.alias_id) {
+      try {
+        await invoke('alias_delete', { aliasId: file.alias_id })
+        setStatus({ type: 'success', message: `✅ Alias "${fileName}" supprimé` })
+        await loadFiles()
+      } catch (e) {
+        setStatus({ type: 'error', message: formatError(e) })
+      }
+      return
+    }
+
     setIsLoading(true)
     setStatus(null)
 
@@ -934,7 +1841,7 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
         await loadFiles()
         return
       } catch (e) {
-        const errorMsg = e instanceof Error ? e.message : String(e)
+        const errorMsg = formatError(e)
         
         if (attempts >= maxAttempts) {
           // Dernière tentative échouée
@@ -981,6 +1888,13 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
     return `${(bytes / (1024 * 1024 * 1024)).toFixed(2)} GB`
   }
 
+  // Formate un temps restant
+  function formatEta(seconds: number): string {
+    if (seconds < 60) return `${seconds} s`
+    if (seconds < 3600) return `${Math.floor(seconds / 60)} min ${String(seconds % 60).padStart(2, '0')} s`
+    return `${Math.floor(seconds / 3600)} h ${String(Math.floor((seconds % 3600) / 60)).padStart(2, '0')} min`
+  }
+
   // Obtient le type de fichier
   function getFileType(fileName: string): string {
     const ext = fileName.split('.').pop()?.toUpperCase() || 'FICHIER'
@@ -1006,14 +1920,14 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
 
   // Filtre et trie les fichiers (memoized pour éviter les recalculs inutiles)
   const filteredAndSortedFiles = useMemo(() => {
-    let result = [...files]
+    let result = [...(smartView ? smartView.files : files)]
 
     // Filtrage par recherche (utilise la version debounced)
     if (debouncedSearchQuery.trim()) {
       const query = debouncedSearchQuery.toLowerCase()
       result = result.filter(file => {
         const fileName = file.logical_path?.split('/').pop() || file.uuid
-        return fileName.toLowerCase().includes(query)
+        return fileName.toLowerCase().includes(query) || contentSnippet(file) !== undefined
       })
     }
 
@@ -1043,7 +1957,10 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
     })
 
     return result
-  }, [files, debouncedSearchQuery, fileTypeFilter, sortBy, sortOrder])
+  }, [files, smartView, debouncedSearchQuery, contentMatches, fileTypeFilter, sortBy, sortOrder])
+
+  // Une vue intelligente couvre tout le coffre : les dossiers n'y ont pas de sens
+  const visibleFolders = smartView ? [] : folders
 
   return (
     <div className="dashboard-page">
@@ -1055,6 +1972,25 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
             <div style={{ display: 'flex', gap: '1rem', marginTop: '0.5rem', fontSize: '0.85rem', color: 'var(--text-secondary, #666)' }}>
               <span>📊 {userStats.total_files} fichier{userStats.total_files > 1 ? 's' : ''}</span>
               <span>💾 {formatSize(userStats.total_size)}</span>
+              {trashBytes > 0 && <span title="Espace libéré en vidant la corbeille">🗑️ {formatSize(trashBytes)}</span>}
+              {healthScore && (
+                <span title="Santé du coffre (détail dans les paramètres)">
+                  {healthScore.status === 'good' ? '🟢' : healthScore.status === 'warning' ? '🟠' : '🔴'} Santé{' '}
+                  {healthScore.score}/100
+                </span>
+              )}
+              {activity && (activity.pendingUploads > 0 || activity.failedTransfers > 0 || activity.conflicts > 0 || activity.integrityWarnings > 0) && (
+                <span
+                  title="Cliquer pour marquer les échecs et conflits comme vus"
+                  onClick={acknowledgeActivity}
+                  style={{ cursor: 'pointer' }}
+                >
+                  {activity.pendingUploads > 0 && `⏳ ${activity.pendingUploads} en attente `}
+                  {activity.failedTransfers > 0 && `❌ ${activity.failedTransfers} échec(s) `}
+                  {activity.conflicts > 0 && `⚠️ ${activity.conflicts} conflit(s) `}
+                  {activity.integrityWarnings > 0 && `🛡️ ${activity.integrityWarnings} alerte(s) d'intégrité`}
+                </span>
+              )}
             </div>
           )}
         </div>
@@ -1097,6 +2033,53 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
         </div>
       </div>
 
+      {integrityIssue && (
+        <StatusMessage
+          type="warning"
+          message={`⚠️ Intégrité du coffre à vérifier (${integrityIssue}). Relance une vérification depuis les paramètres une fois le problème corrigé.`}
+        />
+      )}
+
+      {incomingShare && (
+        <Card title="📨 Fichier partagé">
+          <p>« {incomingShare} » vous a été partagé. Il sera téléchargé, vérifié puis déchiffré.</p>
+          <div style={{ display: 'flex', gap: '0.75rem' }}>
+            <Button
+              onClick={() => handleReceiveShare({ kind: 'vault', folder: currentPath })}
+              loading={isReceivingShare}
+              disabled={isReceivingShare || !storjConfigured}
+            >
+              Importer dans le coffre
+            </Button>
+            <Button variant="secondary" onClick={() => handleReceiveShare({ kind: 'disk' })} disabled={isReceivingShare}>
+              Enregistrer sur le disque
+            </Button>
+            <Button variant="secondary" onClick={handleDismissShare} disabled={isReceivingShare}>
+              Ignorer
+            </Button>
+          </div>
+        </Card>
+      )}
+
+      {activeJob && (
+        <Card title="⏳ Opération en cours">
+          <p>
+            « {activeJob.label.split('/').pop()} » : {JOB_STEP_LABELS[activeJob.step] ?? activeJob.step} (étape{' '}
+            {activeJob.stepIndex + 1}/{activeJob.stepCount})
+          </p>
+          <Button variant="secondary" onClick={cancelActiveJob}>
+            Annuler
+          </Button>
+        </Card>
+      )}
+
+      {isOffline && (
+        <StatusMessage
+          type="warning"
+          message="📴 Mode hors ligne : les fichiers en cache restent consultables, les envois et suppressions seront rejoués à la reconnexion."
+        />
+      )}
+
       {status && (
         <StatusMessage
           type={status.type}
@@ -1160,8 +2143,55 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
               </div>
             )}
           </div>
+          <div style={{ marginTop: '1rem', textAlign: 'center' }}>
+            <Button variant="secondary" onClick={handleUploadFromDisk} disabled={isUploading}>
+              📀 Envoyer un gros fichier depuis le disque
+            </Button>
+            <Button variant="secondary" onClick={() => handleUploadSelection(false)} disabled={isUploading} style={{ marginLeft: '0.5rem' }}>
+              📚 Envoyer plusieurs fichiers
+            </Button>
+            <Button variant="secondary" onClick={() => handleUploadSelection(true)} disabled={isUploading} style={{ marginLeft: '0.5rem' }}>
+              🗂️ Envoyer un dossier
+            </Button>
+          </div>
         </Card>
 
+        {handoffSelection.length > 0 && (
+          <Card title="📦 Lot chiffré à remettre">
+            <p style={{ color: 'var(--text-secondary, #666)', fontSize: '0.9rem', marginTop: 0 }}>
+              {handoffSelection.length} fichier{handoffSelection.length > 1 ? 's' : ''} :{' '}
+              {handoffSelection.map((f) => f.logical_path ?? f.name).join(', ')}
+            </p>
+            <div style={{ display: 'flex', gap: '0.5rem', flexWrap: 'wrap', alignItems: 'center' }}>
+              <input
+                className="input"
+                type="text"
+                placeholder="/Volumes/USB/lot.aether-archive"
+                value={handoffPath}
+                onChange={(e) => setHandoffPath(e.target.value)}
+                style={{ flex: '2 1 16rem' }}
+              />
+              <input
+                className="input"
+                type="password"
+                placeholder="Mot de passe du lot (8 caractères min.)"
+                value={handoffPassword}
+                onChange={(e) => setHandoffPassword(e.target.value)}
+                style={{ flex: '1 1 12rem' }}
+              />
+              <Button
+                onClick={exportHandoff}
+                disabled={isExportingHandoff || !handoffPath.trim() || handoffPassword.length === 0}
+              >
+                {isExportingHandoff ? 'Export en cours...' : '📦 Exporter le lot chiffré'}
+              </Button>
+              <Button variant="secondary" onClick={() => setHandoffSelection([])} disabled={isExportingHandoff}>
+                Vider
+              </Button>
+            </div>
+          </Card>
+        )}
+
         {/* Tableau de fichiers */}
         {showTrash ? (
           <Card title="🗑️ Corbeille">
@@ -1175,7 +2205,9 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
               <>
                 <div style={{ marginBottom: '1rem', display: 'flex', justifyContent: 'space-between', alignItems: 'center' }}>
                   <div style={{ color: 'var(--text-secondary, #666)', fontSize: '0.9rem' }}>
-                    {trashItems.length} fichier{trashItems.length > 1 ? 's' : ''} dans la corbeille
+                    {looseTrashItems.length} fichier{looseTrashItems.length > 1 ? 's' : ''}
+                    {visibleTrashedFolders.length > 0 && ` et ${visibleTrashedFolders.length} dossier${visibleTrashedFolders.length > 1 ? 's' : ''}`} dans la corbeille
+                    {trashBytes > 0 && ` · ${formatSize(trashBytes)} libérés en la vidant`}
                   </div>
                   <Button
                     variant="danger"
@@ -1194,11 +2226,64 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                         <th style={{ textAlign: 'left', padding: '0.75rem', fontWeight: '600', color: 'var(--text-secondary, #666)' }}>Nom</th>
                         <th style={{ textAlign: 'right', padding: '0.75rem', fontWeight: '600', color: 'var(--text-secondary, #666)' }}>Taille</th>
                         <th style={{ textAlign: 'left', padding: '0.75rem', fontWeight: '600', color: 'var(--text-secondary, #666)' }}>Supprimé le</th>
+                        <th style={{ textAlign: 'right', padding: '0.75rem', fontWeight: '600', color: 'var(--text-secondary, #666)' }}>Âge</th>
                         <th style={{ textAlign: 'center', padding: '0.75rem', fontWeight: '600', color: 'var(--text-secondary, #666)' }}>Actions</th>
                       </tr>
                     </thead>
                     <tbody>
-                      {trashItems.map((item) => {
+                      {visibleTrashedFolders.map((folder) => {
+                        const folderName = folder.path.replace(/\/$/, '').split('/').pop() || folder.path
+                        const folderAge = trashItems.find((item) => item.folder_path === folder.path && item.deleted_at === folder.deleted_at)?.age_seconds ?? 0
+                        return (
+                          <tr
+                            key={`${folder.path}@${folder.deleted_at}`}
+                            style={{ borderBottom: '1px solid var(--border, #eee)', transition: 'background 0.2s' }}
+                            onMouseEnter={(e) => e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'}
+                            onMouseLeave={(e) => e.currentTarget.style.background = 'transparent'}
+                          >
+                            <td style={{ padding: '0.75rem', fontSize: '1.5rem' }}>📁</td>
+                            <td style={{ padding: '0.75rem' }}>
+                              <div>
+                                <div style={{ fontWeight: '500' }}>{folderName}</div>
+                                <div style={{ fontSize: '0.85rem', color: 'var(--text-secondary, #666)', marginTop: '0.25rem' }}>
+                                  Dossier · {folder.file_count} fichier{folder.file_count > 1 ? 's' : ''} · {folder.path}
+                                </div>
+                              </div>
+                            </td>
+                            <td style={{ padding: '0.75rem', textAlign: 'right', color: 'var(--text-secondary, #666)' }}>
+                              {formatSize(folder.encrypted_size || 0)}
+                            </td>
+                            <td style={{ padding: '0.75rem', color: 'var(--text-secondary, #666)', fontSize: '0.9rem' }}>
+                              {formatDeletedDate(folder.deleted_at)}
+                            </td>
+                            <td style={{ padding: '0.75rem', textAlign: 'right', color: 'var(--text-secondary, #666)', fontSize: '0.9rem' }}>
+                              {formatTrashAge(folderAge)}
+                            </td>
+                            <td style={{ padding: '0.75rem', textAlign: 'center' }}>
+                              <button
+                                onClick={() => handleRestoreFolderFromTrash(folder)}
+                                disabled={isLoading}
+                                style={{
+                                  background: 'none',
+                                  border: 'none',
+                                  cursor: 'pointer',
+                                  fontSize: '1.2rem',
+                                  padding: '0.5rem',
+                                  borderRadius: '4px',
+                                  transition: 'background 0.2s',
+                                  opacity: isLoading ? 0.5 : 1,
+                                }}
+                                onMouseEnter={(e) => e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'}
+                                onMouseLeave={(e) => e.currentTarget.style.background = 'transparent'}
+                                title="Restaurer le dossier et son contenu"
+                              >
+                                ♻️
+                              </button>
+                            </td>
+                          </tr>
+                        )
+                      })}
+                      {looseTrashItems.map((item) => {
                         const fileName = item.logical_path.split('/').pop() || item.id
                         return (
                           <tr
@@ -1222,8 +2307,49 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                             <td style={{ padding: '0.75rem', color: 'var(--text-secondary, #666)', fontSize: '0.9rem' }}>
                               {formatDeletedDate(item.deleted_at)}
                             </td>
+                            <td style={{ padding: '0.75rem', textAlign: 'right', color: 'var(--text-secondary, #666)', fontSize: '0.9rem' }}>
+                              {formatTrashAge(item.age_seconds)}
+                            </td>
                             <td style={{ padding: '0.75rem', textAlign: 'center' }}>
                               <div style={{ display: 'flex', gap: '0.5rem', justifyContent: 'center' }}>
+                                <button
+                                  onClick={() => handlePreview(trashItemAsFile(item))}
+                                  disabled={isLoading}
+                                  style={{
+                                    background: 'none',
+                                    border: 'none',
+                                    cursor: 'pointer',
+                                    fontSize: '1.2rem',
+                                    padding: '0.5rem',
+                                    borderRadius: '4px',
+                                    transition: 'background 0.2s',
+                                    opacity: isLoading ? 0.5 : 1,
+                                  }}
+                                  onMouseEnter={(e) => e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'}
+                                  onMouseLeave={(e) => e.currentTarget.style.background = 'transparent'}
+                                  title="Aperçu (avant restauration ou suppression)"
+                                >
+                                  👁️
+                                </button>
+                                <button
+                                  onClick={() => showEncryptionDetails(trashItemAsFile(item))}
+                                  disabled={isLoading}
+                                  style={{
+                                    background: 'none',
+                                    border: 'none',
+                                    cursor: 'pointer',
+                                    fontSize: '1.2rem',
+                                    padding: '0.5rem',
+                                    borderRadius: '4px',
+                                    transition: 'background 0.2s',
+                                    opacity: isLoading ? 0.5 : 1,
+                                  }}
+                                  onMouseEnter={(e) => e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'}
+                                  onMouseLeave={(e) => e.currentTarget.style.background = 'transparent'}
+                                  title="Détails du chiffrement"
+                                >
+                                  🔐
+                                </button>
                                 <button
                                   onClick={() => handleRestoreFromTrash(item.id)}
                                   disabled={isLoading}
@@ -1274,6 +2400,57 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
               </>
             )}
           </Card>
+        ) : timeline ? (
+          <Card title="🗓️ Chronologie des photos">
+            <div style={{ display: 'flex', gap: '0.5rem', flexWrap: 'wrap', alignItems: 'center', marginBottom: '1rem' }}>
+              {timeline.months.map((bucket) => (
+                <button
+                  key={bucket.month}
+                  onClick={() => loadTimeline(bucket.month)}
+                  style={{
+                    padding: '0.5rem 0.75rem',
+                    fontSize: '0.85rem',
+                    border: '1px solid var(--border, #ddd)',
+                    borderRadius: '6px',
+                    background: timeline.month === bucket.month ? 'var(--primary, #007bff)' : 'transparent',
+                    color: timeline.month === bucket.month ? 'white' : 'var(--text-primary, #333)',
+                    cursor: 'pointer',
+                  }}
+                >
+                  {formatMonthBucket(bucket.month)} ({bucket.count})
+                </button>
+              ))}
+              <Button variant="secondary" onClick={() => setTimeline(null)} style={{ marginLeft: 'auto' }}>
+                📁 Mes fichiers
+              </Button>
+            </div>
+            {timeline.photos.length === 0 ? (
+              <div className="empty-state" style={{ textAlign: 'center', padding: '3rem', color: 'var(--text-secondary, #666)' }}>
+                <div style={{ fontSize: '3rem', marginBottom: '1rem' }}>📷</div>
+                <p style={{ fontSize: '1.1rem' }}>Aucune photo</p>
+              </div>
+            ) : (
+              <div style={{ display: 'grid', gridTemplateColumns: 'repeat(auto-fill, minmax(180px, 1fr))', gap: '0.75rem' }}>
+                {timeline.photos.map((photo) => (
+                  <div
+                    key={photo.file_id}
+                    onClick={() => handlePreview({ uuid: photo.file_id, file_id: photo.file_id, logical_path: photo.logical_path, encrypted_size: photo.encrypted_size })}
+                    title={photo.logical_path}
+                    style={{ border: '1px solid var(--border, #ddd)', borderRadius: '8px', padding: '0.75rem', cursor: 'pointer' }}
+                  >
+                    <div style={{ fontSize: '2rem', textAlign: 'center' }}>🖼️</div>
+                    <div style={{ fontWeight: 500, overflow: 'hidden', textOverflow: 'ellipsis', whiteSpace: 'nowrap' }}>
+                      {photo.logical_path.split('/').pop()}
+                    </div>
+                    <div style={{ fontSize: '0.8rem', color: 'var(--text-secondary, #666)' }}>
+                      {photo.taken_at ? new Date(photo.taken_at).toLocaleString('fr-FR', { dateStyle: 'medium', timeStyle: 'short' }) : 'Date inconnue'}
+                      {photo.width && photo.height ? ` · ${photo.width}×${photo.height}` : ''}
+                    </div>
+                  </div>
+                ))}
+              </div>
+            )}
+          </Card>
         ) : (
           <Card title="Mes fichiers">
           {/* Bouton créer dossier */}
@@ -1384,6 +2561,44 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
               </Button>
             </div>
 
+            {/* Vues intelligentes : tout le coffre, par type détecté au chiffrement */}
+            <div style={{ display: 'flex', gap: '0.5rem', flexWrap: 'wrap', alignItems: 'center' }}>
+              <span style={{ color: 'var(--text-secondary, #666)', fontSize: '0.9rem', marginRight: '0.25rem' }}>Vues:</span>
+              {SMART_VIEWS.map(({ category, label }) => (
+                <button
+                  key={category}
+                  onClick={() => toggleSmartView(category)}
+                  title={smartView?.category === category ? 'Revenir au dossier' : 'Tous les fichiers du coffre de ce type'}
+                  style={{
+                    padding: '0.5rem 0.75rem',
+                    fontSize: '0.85rem',
+                    border: '1px solid var(--border, #ddd)',
+                    borderRadius: '6px',
+                    background: smartView?.category === category ? 'var(--primary, #007bff)' : 'transparent',
+                    color: smartView?.category === category ? 'white' : 'var(--text-primary, #333)',
+                    cursor: 'pointer',
+                  }}
+                >
+                  {label}
+                </button>
+              ))}
+              <button
+                onClick={() => loadTimeline()}
+                title="Photos groupées par mois de prise de vue"
+                style={{
+                  padding: '0.5rem 0.75rem',
+                  fontSize: '0.85rem',
+                  border: '1px solid var(--border, #ddd)',
+                  borderRadius: '6px',
+                  background: 'transparent',
+                  color: 'var(--text-primary, #333)',
+                  cursor: 'pointer',
+                }}
+              >
+                🗓️ Chronologie
+              </button>
+            </div>
+
             {/* Filtres par type et tri */}
             <div style={{ display: 'flex', gap: '1rem', flexWrap: 'wrap', alignItems: 'center' }}>
               {/* Filtres par type */}
@@ -1464,8 +2679,8 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
           {/* Compteur de résultats */}
           <div style={{ display: 'flex', justifyContent: 'space-between', alignItems: 'center', marginBottom: '1rem' }}>
             <p style={{ color: 'var(--text-secondary, #666)', fontSize: '0.9rem' }}>
-              {folders.length} dossier{folders.length > 1 ? 's' : ''}, {filteredAndSortedFiles.length} fichier{filteredAndSortedFiles.length > 1 ? 's' : ''} 
-              {searchQuery || fileTypeFilter !== 'all' ? ` (sur ${files.length} au total)` : ''}
+              {visibleFolders.length} dossier{visibleFolders.length > 1 ? 's' : ''}, {filteredAndSortedFiles.length} fichier{filteredAndSortedFiles.length > 1 ? 's' : ''} 
+              {searchQuery || fileTypeFilter !== 'all' ? ` (sur ${(smartView ? smartView.files : files).length} au total)` : ''}
             </p>
           </div>
 
@@ -1474,7 +2689,7 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
               type="info"
               message="💡 Connecte-toi à Wayne pour activer le stockage décentralisé."
             />
-          ) : files.length === 0 ? (
+          ) : (smartView ? smartView.files : files).length === 0 ? (
             <div className="empty-state" style={{ textAlign: 'center', padding: '3rem', color: 'var(--text-secondary, #666)' }}>
               <div style={{ fontSize: '3rem', marginBottom: '1rem' }}>📂</div>
               <p style={{ fontSize: '1.1rem', marginBottom: '0.5rem' }}>Aucun fichier</p>
@@ -1511,7 +2726,7 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                 </thead>
                 <tbody>
                   {/* Affiche d'abord les dossiers */}
-                  {folders.map((folder) => (
+                  {visibleFolders.map((folder) => (
                     <tr 
                       key={folder.path} 
                       style={{ borderBottom: '1px solid var(--border, #eee)', transition: 'background 0.2s', cursor: 'pointer' }} 
@@ -1519,7 +2734,9 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                       onMouseLeave={(e) => e.currentTarget.style.background = 'transparent'}
                       onClick={() => navigateToFolder(folder.path)}
                     >
-                      <td style={{ padding: '0.75rem', fontSize: '1.5rem' }}>📁</td>
+                      <td style={{ padding: '0.75rem', fontSize: '1.5rem', borderLeft: `4px solid ${folder.color ?? 'transparent'}` }}>
+                        {folder.icon ?? '📁'}
+                      </td>
                       <td style={{ padding: '0.75rem' }}>
                         <div>
                           <div style={{ fontWeight: '500' }}>{folder.name}</div>
@@ -1552,6 +2769,68 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                         >
                           ➡️
                         </button>
+                        <button
+                          onClick={(e) => {
+                            e.stopPropagation()
+                            openFolderAppearance(folder)
+                          }}
+                          style={{
+                            background: 'none',
+                            border: 'none',
+                            cursor: 'pointer',
+                            fontSize: '1.2rem',
+                            padding: '0.5rem',
+                            borderRadius: '4px',
+                            transition: 'background 0.2s',
+                          }}
+                          onMouseEnter={(e) => e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'}
+                          onMouseLeave={(e) => e.currentTarget.style.background = 'transparent'}
+                          title="Couleur et icône"
+                        >
+                          🎨
+                        </button>
+                        <button
+                          onClick={(e) => {
+                            e.stopPropagation()
+                            handleRenameFolder(folder)
+                          }}
+                          style={{
+                            background: 'none',
+                            border: 'none',
+                            cursor: 'pointer',
+                            fontSize: '1.2rem',
+                            padding: '0.5rem',
+                            borderRadius: '4px',
+                            transition: 'background 0.2s',
+                          }}
+                          onMouseEnter={(e) => e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'}
+                          onMouseLeave={(e) => e.currentTarget.style.background = 'transparent'}
+                          title="Renommer le dossier"
+                        >
+                          ✏️
+                        </button>
+                        <button
+                          onClick={(e) => {
+                            e.stopPropagation()
+                            handleTrashFolder(folder)
+                          }}
+                          disabled={isLoading}
+                          style={{
+                            background: 'none',
+                            border: 'none',
+                            cursor: 'pointer',
+                            fontSize: '1.2rem',
+                            padding: '0.5rem',
+                            borderRadius: '4px',
+                            transition: 'background 0.2s',
+                            color: 'var(--danger, #dc3545)',
+                          }}
+                          onMouseEnter={(e) => e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'}
+                          onMouseLeave={(e) => e.currentTarget.style.background = 'transparent'}
+                          title="Mettre le dossier à la corbeille"
+                        >
+                          🗑️
+                        </button>
                       </td>
                     </tr>
                   ))}
@@ -1573,10 +2852,36 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                         <td style={{ padding: '0.75rem', fontSize: '1.5rem' }}>{getFileIcon(fileName)}</td>
                         <td style={{ padding: '0.75rem' }}>
                           <div>
-                            <div style={{ fontWeight: '500' }}>{fileName}</div>
+                            <div style={{ fontWeight: '500' }}>
+                              {fileName}
+                              {file.alias_id && (
+                                <span
+                                  title="Alias : le contenu est celui d'un fichier situé ailleurs dans le coffre"
+                                  style={{ marginLeft: '0.5rem', fontSize: '0.8rem', color: 'var(--text-secondary, #666)' }}
+                                >
+                                  🔗 alias
+                                </span>
+                              )}
+                              {file.pending && (
+                                <span
+                                  title="Envoi vers Storj en attente de connexion"
+                                  style={{ marginLeft: '0.5rem', fontSize: '0.8rem', color: 'var(--text-secondary, #666)' }}
+                                >
+                                  ⏳ en attente
+                                </span>
+                              )}
+                            </div>
                             <div style={{ fontSize: '0.85rem', color: 'var(--text-secondary, #666)', marginTop: '0.25rem' }}>
                               {getFileType(fileName)}
                             </div>
+                            {debouncedSearchQuery.trim() && contentSnippet(file) && (
+                              <div
+                                title="Trouvé dans le contenu du fichier"
+                                style={{ fontSize: '0.8rem', color: 'var(--text-secondary, #666)', marginTop: '0.25rem', fontStyle: 'italic' }}
+                              >
+                                🔎 {contentSnippet(file)}
+                              </div>
+                            )}
                           </div>
                         </td>
                         <td style={{ padding: '0.75rem', textAlign: 'right', color: 'var(--text-secondary, #666)' }}>
@@ -1685,6 +2990,8 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
         )}
       </div>
 
+      {passwordConfirm}
+
       {showSettings && wayneClient && (
         <SettingsModal
           wayneClient={wayneClient}
@@ -1696,6 +3003,178 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
         />
       )}
       
+      {/* Menu contextuel */}
+      {contextMenu && (
+        <>
+          {/* Overlay pour fermer le menu au clic */}
+          <div
+            style={{
+              position: 'fixed',
+              top: 0,
+              left: 0,
+              width: '100vw',
+              height: '100vh',
+              zIndex: 999,
+            }}
+            onClick={() => setContextMenu(null)}
+            onContextMenu={(e) => {
+              e.preventDefault()
+              setContextMenu(null)
+            }}
+          />
+          {/* Menu contextuel */}
+          <div
+            style={{
+              position: 'fixed',
+              top: contextMenu.y,
+              left: contextMenu.x,
+              background: 'var(--bg-primary, white)',
+              border: '1px solid var(--border, #ddd)',
+              borderRadius: '8px',
+              boxShadow: '0 4px 12px rgba(0, 0, 0, 0.15)',
+              zIndex: 1000,
+              minWidth: '180px',
+              padding: '0.5rem 0',
+            }}
+            onClick={(e) => e.stopPropagation()}
+            onContextMenu={(e) => {
+              e.preventDefault()
+              e.stopPropagation()
+            }}
+          >
+            <button
+              onClick={() => {
+                handleRename(contextMenu.file)
+                setContextMenu(null)
+              }}
+              disabled={isLoading || !contextMenu.file.logical_path}
+              style={{
+                width: '100%',
+                padding: '0.75rem 1rem',
+                background: 'transparent',
+                border: 'none',
+                textAlign: 'left',
+                cursor: isLoading || !contextMenu.file.logical_path ? 'not-allowed' : 'pointer',
+                color: isLoading || !contextMenu.file.logical_path ? 'var(--text-secondary, #999)' : 'var(--text-primary, #333)',
+                fontSize: '0.9rem',
+                display: 'flex',
+                alignItems: 'center',
+                gap: '0.75rem',
+                transition: 'background 0.2s',
+              }}
+              onMouseEnter={(e) => {
+                if (!isLoading && contextMenu.file.logical_path) {
+                  e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'
+                }
+              }}
+              onMouseLeave={(e) => {
+                e.currentTarget.style.background = 'transparent'
+              }}
+            >
+              <span>✏️</span>
+              <span>Renommer</span>
+            </button>
+            <button
+              onClick={() => {
+                handleCreateAlias(contextMenu.file)
+                setContextMenu(null)
+              }}
+              disabled={isLoading || !contextMenu.file.logical_path || !!contextMenu.file.alias_id}
+              style={{
+                width: '100%',
+                padding: '0.75rem 1rem',
+                background: 'transparent',
+                border: 'none',
+                textAlign: 'left',
+                cursor: isLoading || contextMenu.file.alias_id ? 'not-allowed' : 'pointer',
+                color: isLoading || contextMenu.file.alias_id ? 'var(--text-secondary, #999)' : 'var(--text-primary, #333)',
+                fontSize: '0.9rem',
+                display: 'flex',
+                alignItems: 'center',
+                gap: '0.75rem',
+                transition: 'background 0.2s',
+              }}
+              onMouseEnter={(e) => {
+                if (!isLoading && !contextMenu.file.alias_id) {
+                  e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'
+                }
+              }}
+              onMouseLeave={(e) => {
+                e.currentTarget.style.background = 'transparent'
+              }}
+            >
+              <span>🔗</span>
+              <span>Créer un alias</span>
+            </button>
+            <button
+              onClick={() => {
+                handleDownload(contextMenu.file)
+                setContextMenu(null)
+              }}
+              disabled={isLoading || !contextMenu.file.logical_path}
+              style={{
+                width: '100%',
+                padding: '0.75rem 1rem',
+                background: 'transparent',
+                border: 'none',
+                textAlign: 'left',
+                cursor: isLoading || !contextMenu.file.logical_path ? 'not-allowed' : 'pointer',
+                color: isLoading || !contextMenu.file.logical_path ? 'var(--text-secondary, #999)' : 'var(--text-primary, #333)',
+                fontSize: '0.9rem',
+                display: 'flex',
+                alignItems: 'center',
+                gap: '0.75rem',
+                transition: 'background 0.2s',
+              }}
+              onMouseEnter={(e) => {
+                if (!isLoading && contextMenu.file.logical_path) {
+                  e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'
+                }
+              }}
+              onMouseLeave={(e) => {
+                e.currentTarget.style.background = 'transparent'
+              }}
+            >
+              <span>📥</span>
+              <span>Télécharger</span>
+            </button>
+            <div style={{ height: '1px', background: 'var(--border, #ddd)', margin: '0.5rem 0' }} />
+            <button
+              onClick={() => {
+                handleDelete(contextMenu.file)
+                setContextMenu(null)
+              }}
+              disabled={isLoading}
+              style={{
+                width: '100%',
+                padding: '0.75rem 1rem',
+                background: 'transparent',
+                border: 'none',
+                textAlign: 'left',
+                cursor: isLoading ? 'not-allowed' : 'pointer',
+                color: isLoading ? 'var(--text-secondary, #999)' : 'var(--danger, #dc3545)',
+                fontSize: '0.9rem',
+                display: 'flex',
+                alignItems: 'center',
+                gap: '0.75rem',
+                transition: 'background 0.2s',
+              }}
+              onMouseEnter={(e) => {
+                if (!isLoading) {
+                  e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'
+                }
+              }}
+              onMouseLeave={(e) => {
+                e.currentTarget.style.background = 'transparent'
+              }}
+            >
+              <span>🗑️</span>
+              <span>Supprimer</span>
+            </button>
+          </div>
+        </>
+      )}
+
       {/* Menu contextuel */}
       {contextMenu && (
         <>
@@ -1799,21 +3278,19 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
               <span>📥</span>
               <span>Télécharger</span>
             </button>
-            <div style={{ height: '1px', background: 'var(--border, #ddd)', margin: '0.5rem 0' }} />
             <button
               onClick={() => {
-                handleDelete(contextMenu.file)
+                showEncryptionDetails(contextMenu.file)
                 setContextMenu(null)
               }}
-              disabled={isLoading}
               style={{
                 width: '100%',
                 padding: '0.75rem 1rem',
                 background: 'transparent',
                 border: 'none',
                 textAlign: 'left',
-                cursor: isLoading ? 'not-allowed' : 'pointer',
-                color: isLoading ? 'var(--text-secondary, #999)' : 'var(--danger, #dc3545)',
+                cursor: 'pointer',
+                color: 'var(--text-primary, #333)',
                 fontSize: '0.9rem',
                 display: 'flex',
                 alignItems: 'center',
@@ -1821,7 +3298,37 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                 transition: 'background 0.2s',
               }}
               onMouseEnter={(e) => {
-                if (!isLoading) {
+                e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'
+              }}
+              onMouseLeave={(e) => {
+                e.currentTarget.style.background = 'transparent'
+              }}
+            >
+              <span>🔐</span>
+              <span>Chiffrement</span>
+            </button>
+            <button
+              onClick={() => {
+                showFileVersions(contextMenu.file)
+                setContextMenu(null)
+              }}
+              disabled={!contextMenu.file.logical_path}
+              style={{
+                width: '100%',
+                padding: '0.75rem 1rem',
+                background: 'transparent',
+                border: 'none',
+                textAlign: 'left',
+                cursor: contextMenu.file.logical_path ? 'pointer' : 'not-allowed',
+                color: contextMenu.file.logical_path ? 'var(--text-primary, #333)' : 'var(--text-secondary, #999)',
+                fontSize: '0.9rem',
+                display: 'flex',
+                alignItems: 'center',
+                gap: '0.75rem',
+                transition: 'background 0.2s',
+              }}
+              onMouseEnter={(e) => {
+                if (contextMenu.file.logical_path) {
                   e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'
                 }
               }}
@@ -1829,66 +3336,22 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                 e.currentTarget.style.background = 'transparent'
               }}
             >
-              <span>🗑️</span>
-              <span>Supprimer</span>
+              <span>🕘</span>
+              <span>Versions précédentes</span>
             </button>
-          </div>
-        </>
-      )}
-
-      {/* Menu contextuel */}
-      {contextMenu && (
-        <>
-          {/* Overlay pour fermer le menu au clic */}
-          <div
-            style={{
-              position: 'fixed',
-              top: 0,
-              left: 0,
-              width: '100vw',
-              height: '100vh',
-              zIndex: 999,
-            }}
-            onClick={() => setContextMenu(null)}
-            onContextMenu={(e) => {
-              e.preventDefault()
-              setContextMenu(null)
-            }}
-          />
-          {/* Menu contextuel */}
-          <div
-            style={{
-              position: 'fixed',
-              top: contextMenu.y,
-              left: contextMenu.x,
-              background: 'var(--bg-primary, white)',
-              border: '1px solid var(--border, #ddd)',
-              borderRadius: '8px',
-              boxShadow: '0 4px 12px rgba(0, 0, 0, 0.15)',
-              zIndex: 1000,
-              minWidth: '180px',
-              padding: '0.5rem 0',
-            }}
-            onClick={(e) => e.stopPropagation()}
-            onContextMenu={(e) => {
-              e.preventDefault()
-              e.stopPropagation()
-            }}
-          >
             <button
               onClick={() => {
-                handleRename(contextMenu.file)
+                verifyLocalCopy(contextMenu.file)
                 setContextMenu(null)
               }}
-              disabled={isLoading || !contextMenu.file.logical_path}
               style={{
                 width: '100%',
                 padding: '0.75rem 1rem',
                 background: 'transparent',
                 border: 'none',
                 textAlign: 'left',
-                cursor: isLoading || !contextMenu.file.logical_path ? 'not-allowed' : 'pointer',
-                color: isLoading || !contextMenu.file.logical_path ? 'var(--text-secondary, #999)' : 'var(--text-primary, #333)',
+                cursor: 'pointer',
+                color: 'var(--text-primary, #333)',
                 fontSize: '0.9rem',
                 display: 'flex',
                 alignItems: 'center',
@@ -1896,7 +3359,44 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                 transition: 'background 0.2s',
               }}
               onMouseEnter={(e) => {
-                if (!isLoading && contextMenu.file.logical_path) {
+                e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'
+              }}
+              onMouseLeave={(e) => {
+                e.currentTarget.style.background = 'transparent'
+              }}
+            >
+              <span>🧾</span>
+              <span>Vérifier une copie</span>
+            </button>
+            <button
+              onClick={() => {
+                toggleLock(contextMenu.file)
+                setContextMenu(null)
+              }}
+              disabled={!contextMenu.file.logical_path || !storjConfigured}
+              title={
+                contextMenu.file.logical_path && fileLocks[contextMenu.file.logical_path]
+                  ? `Verrouillé par ${fileLocks[contextMenu.file.logical_path].holder} jusqu'au ${new Date(
+                      fileLocks[contextMenu.file.logical_path].expires_at * 1000,
+                    ).toLocaleString('fr-FR')}`
+                  : 'Signaler aux autres appareils que tu modifies ce document'
+              }
+              style={{
+                width: '100%',
+                padding: '0.75rem 1rem',
+                background: 'transparent',
+                border: 'none',
+                textAlign: 'left',
+                cursor: contextMenu.file.logical_path && storjConfigured ? 'pointer' : 'not-allowed',
+                color: contextMenu.file.logical_path && storjConfigured ? 'var(--text-primary, #333)' : 'var(--text-secondary, #999)',
+                fontSize: '0.9rem',
+                display: 'flex',
+                alignItems: 'center',
+                gap: '0.75rem',
+                transition: 'background 0.2s',
+              }}
+              onMouseEnter={(e) => {
+                if (contextMenu.file.logical_path && storjConfigured) {
                   e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'
                 }
               }}
@@ -1904,23 +3404,30 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                 e.currentTarget.style.background = 'transparent'
               }}
             >
-              <span>✏️</span>
-              <span>Renommer</span>
+              <span>{contextMenu.file.logical_path && fileLocks[contextMenu.file.logical_path] ? '🔓' : '🔒'}</span>
+              <span>
+                {!contextMenu.file.logical_path || !fileLocks[contextMenu.file.logical_path]
+                  ? 'Verrouiller'
+                  : fileLocks[contextMenu.file.logical_path].mine
+                    ? 'Déverrouiller'
+                    : `Retirer le verrou de ${fileLocks[contextMenu.file.logical_path].holder}`}
+              </span>
             </button>
             <button
               onClick={() => {
-                handleDownload(contextMenu.file)
+                toggleHandoff(contextMenu.file)
                 setContextMenu(null)
               }}
-              disabled={isLoading || !contextMenu.file.logical_path}
+              disabled={!contextMenu.file.logical_path || !!contextMenu.file.alias_id}
+              title="Regrouper des fichiers dans une archive chiffrée par un mot de passe propre au lot"
               style={{
                 width: '100%',
                 padding: '0.75rem 1rem',
                 background: 'transparent',
                 border: 'none',
                 textAlign: 'left',
-                cursor: isLoading || !contextMenu.file.logical_path ? 'not-allowed' : 'pointer',
-                color: isLoading || !contextMenu.file.logical_path ? 'var(--text-secondary, #999)' : 'var(--text-primary, #333)',
+                cursor: contextMenu.file.logical_path && !contextMenu.file.alias_id ? 'pointer' : 'not-allowed',
+                color: contextMenu.file.logical_path && !contextMenu.file.alias_id ? 'var(--text-primary, #333)' : 'var(--text-secondary, #999)',
                 fontSize: '0.9rem',
                 display: 'flex',
                 alignItems: 'center',
@@ -1928,7 +3435,7 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                 transition: 'background 0.2s',
               }}
               onMouseEnter={(e) => {
-                if (!isLoading && contextMenu.file.logical_path) {
+                if (contextMenu.file.logical_path && !contextMenu.file.alias_id) {
                   e.currentTarget.style.background = 'var(--bg-secondary, #f5f5f5)'
                 }
               }}
@@ -1936,8 +3443,12 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                 e.currentTarget.style.background = 'transparent'
               }}
             >
-              <span>📥</span>
-              <span>Télécharger</span>
+              <span>📦</span>
+              <span>
+                {handoffSelection.some((f) => f.uuid === contextMenu.file.uuid)
+                  ? 'Retirer du lot chiffré'
+                  : 'Ajouter au lot chiffré'}
+              </span>
             </button>
             <div style={{ height: '1px', background: 'var(--border, #ddd)', margin: '0.5rem 0' }} />
             <button
@@ -1976,8 +3487,243 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
         </>
       )}
       
+      {/* Détails de chiffrement d'un fichier */}
+      {encryptionDetails && (
+        <div
+          style={{
+            position: 'fixed',
+            top: 0,
+            left: 0,
+            width: '100vw',
+            height: '100vh',
+            background: 'rgba(0, 0, 0, 0.5)',
+            display: 'flex',
+            justifyContent: 'center',
+            alignItems: 'center',
+            zIndex: 1000,
+          }}
+          onClick={() => setEncryptionDetails(null)}
+        >
+          <div
+            style={{
+              background: 'var(--bg-primary, white)',
+              padding: '2rem',
+              borderRadius: '12px',
+              boxShadow: '0 4px 20px rgba(0, 0, 0, 0.2)',
+              width: '90%',
+              maxWidth: '480px',
+              color: 'var(--text-primary, #333)',
+            }}
+            onClick={(e) => e.stopPropagation()}
+          >
+            <h2 style={{ marginTop: 0, marginBottom: '1.5rem', color: 'var(--text-primary, #333)' }}>
+              Chiffrement de « {encryptionDetails.fileName} »
+            </h2>
+            <table style={{ width: '100%', fontSize: '0.9rem', marginBottom: '1rem' }}>
+              <tbody>
+                {[
+                  ['Format', `Aether V${encryptionDetails.details.format_version}`],
+                  ['Algorithme', `${encryptionDetails.details.cipher_suite} (0x${encryptionDetails.details.cipher_id.toString(16).padStart(2, '0')})`],
+                  ['Nonce', `${encryptionDetails.details.nonce_len} octets, unique par fichier`],
+                  ['Dérivation de clé', encryptionDetails.details.key_derivation],
+                  ['Contexte de dérivation', encryptionDetails.details.key_derivation_info],
+                  ['Empreinte du sel', encryptionDetails.details.salt_fingerprint],
+                  ['Engagement de clé', encryptionDetails.details.commitment_valid ? '✅ Vérifié' : '❌ Invalide'],
+                ].map(([label, value]) => (
+                  <tr key={label}>
+                    <td style={{ padding: '0.35rem 0', color: 'var(--text-secondary, #666)' }}>{label}</td>
+                    <td style={{ padding: '0.35rem 0', fontFamily: 'monospace', textAlign: 'right' }}>{value}</td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+            <div style={{ display: 'flex', justifyContent: 'flex-end' }}>
+              <Button variant="secondary" onClick={() => setEncryptionDetails(null)}>
+                Fermer
+              </Button>
+            </div>
+          </div>
+        </div>
+      )}
+
+      {/* Versions précédentes d'un fichier */}
+      {fileVersions && (
+        <div
+          style={{
+            position: 'fixed',
+            top: 0,
+            left: 0,
+            width: '100vw',
+            height: '100vh',
+            background: 'rgba(0, 0, 0, 0.5)',
+            display: 'flex',
+            justifyContent: 'center',
+            alignItems: 'center',
+            zIndex: 1000,
+          }}
+          onClick={() => setFileVersions(null)}
+        >
+          <div
+            style={{
+              background: 'var(--bg-primary, white)',
+              padding: '2rem',
+              borderRadius: '12px',
+              boxShadow: '0 4px 20px rgba(0, 0, 0, 0.2)',
+              width: '90%',
+              maxWidth: '480px',
+              color: 'var(--text-primary, #333)',
+            }}
+            onClick={(e) => e.stopPropagation()}
+          >
+            <h2 style={{ marginTop: 0, marginBottom: '1.5rem', color: 'var(--text-primary, #333)' }}>
+              Versions de « {fileVersions.logicalPath.split('/').pop()} »
+            </h2>
+            {fileVersions.versions.length === 0 ? (
+              <p style={{ color: 'var(--text-secondary, #666)', fontSize: '0.9rem' }}>
+                Aucune version précédente : ce fichier n'a jamais été remplacé.
+              </p>
+            ) : (
+              <table style={{ width: '100%', fontSize: '0.9rem', marginBottom: '1rem' }}>
+                <tbody>
+                  {fileVersions.versions.map((version) => (
+                    <tr key={version.id}>
+                      <td style={{ padding: '0.35rem 0', color: 'var(--text-secondary, #666)' }}>
+                        Remplacée le {new Date(version.replaced_at * 1000).toLocaleString('fr-FR')}
+                      </td>
+                      <td style={{ padding: '0.35rem 0', fontFamily: 'monospace', textAlign: 'right' }}>
+                        {formatSize(version.encrypted_size)}
+                      </td>
+                      <td style={{ padding: '0.35rem 0 0.35rem 0.75rem', textAlign: 'right' }}>
+                        <Button variant="secondary" onClick={() => restoreFileVersion(version)} disabled={isLoading}>
+                          Restaurer
+                        </Button>
+                      </td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+            )}
+            <div style={{ display: 'flex', justifyContent: 'flex-end' }}>
+              <Button variant="secondary" onClick={() => setFileVersions(null)}>
+                Fermer
+              </Button>
+            </div>
+          </div>
+        </div>
+      )}
+
+      {/* Modal d'apparence de dossier */}
+      {folderToStyle && (
+        <div
+          style={{
+            position: 'fixed',
+            top: 0,
+            left: 0,
+            width: '100vw',
+            height: '100vh',
+            background: 'rgba(0, 0, 0, 0.5)',
+            display: 'flex',
+            justifyContent: 'center',
+            alignItems: 'center',
+            zIndex: 1000,
+          }}
+          onClick={() => setFolderToStyle(null)}
+        >
+          <div
+            style={{
+              background: 'var(--bg-primary, white)',
+              padding: '2rem',
+              borderRadius: '12px',
+              boxShadow: '0 4px 20px rgba(0, 0, 0, 0.2)',
+              width: '90%',
+              maxWidth: '400px',
+              color: 'var(--text-primary, #333)',
+            }}
+            onClick={(e) => e.stopPropagation()}
+          >
+            <h2 style={{ marginTop: 0, marginBottom: '1.5rem', color: 'var(--text-primary, #333)' }}>
+              Apparence et politique de « {folderToStyle.name} »
+            </h2>
+            <div style={{ display: 'flex', gap: '0.75rem', alignItems: 'center', marginBottom: '1rem' }}>
+              <input
+                type="color"
+                value={folderColor || '#4a90d9'}
+                onChange={(e) => setFolderColor(e.target.value)}
+                title="Couleur"
+                style={{ width: '3rem', height: '2.5rem', border: 'none', background: 'none' }}
+              />
+              <input
+                type="text"
+                placeholder="Icône (emoji)"
+                value={folderIcon}
+                onChange={(e) => setFolderIcon(e.target.value)}
+                style={{
+                  flex: 1,
+                  padding: '0.75rem',
+                  fontSize: '1rem',
+                  border: '1px solid var(--border, #ddd)',
+                  borderRadius: '8px',
+                }}
+              />
+            </div>
+            <h3 style={{ marginTop: 0, marginBottom: '0.75rem', fontSize: '1rem' }}>Politique du dossier</h3>
+            <p style={{ marginTop: 0, fontSize: '0.85rem', color: 'var(--text-secondary, #666)' }}>
+              S'applique aux sous-dossiers sans politique propre, et aux envois suivants.
+            </p>
+            <div style={{ display: 'flex', flexDirection: 'column', gap: '0.5rem', marginBottom: '1.5rem' }}>
+              <label>
+                <input
+                  type="checkbox"
+                  checked={folderPolicy.compress}
+                  onChange={() => setFolderPolicy({ ...folderPolicy, compress: !folderPolicy.compress })}
+                />{' '}
+                Compresser aussi les petits fichiers et les formats inconnus
+                <span style={{ display: 'block', fontSize: '0.8rem', color: 'var(--text-secondary, #666)' }}>
+                  Le texte et les documents bruts sont compressés d'office ; photos, vidéos et archives jamais.
+                </span>
+              </label>
+              <label>
+                Masquer la taille des fichiers{' '}
+                <select
+                  value={folderPolicy.padding}
+                  onChange={(e) => setFolderPolicy({ ...folderPolicy, padding: e.target.value as FolderPolicy['padding'] })}
+                >
+                  <option value="none">Non</option>
+                  <option value="standard">Par blocs de 4 Kio</option>
+                  <option value="high">Puissance de deux supérieure</option>
+                </select>
+              </label>
+              <label>
+                <input
+                  type="checkbox"
+                  checked={folderPolicy.pin_offline}
+                  onChange={() => setFolderPolicy({ ...folderPolicy, pin_offline: !folderPolicy.pin_offline })}
+                />{' '}
+                Toujours disponible hors ligne
+              </label>
+              <label>
+                <input
+                  type="checkbox"
+                  checked={folderPolicy.exclude_from_sync}
+                  onChange={() => setFolderPolicy({ ...folderPolicy, exclude_from_sync: !folderPolicy.exclude_from_sync })}
+                />{' '}
+                Ne pas synchroniser avec mes autres appareils
+              </label>
+            </div>
+            <div style={{ display: 'flex', justifyContent: 'flex-end', gap: '0.75rem' }}>
+              <Button variant="secondary" onClick={() => saveFolderAppearance(true)}>
+                Par défaut
+              </Button>
+              <Button variant="primary" onClick={() => saveFolderAppearance(false)}>
+                Enregistrer
+              </Button>
+            </div>
+          </div>
+        </div>
+      )}
+
       {/* Modal de renommage de fichier */}
-      {showRenameModal && fileToRename && (
+      {showRenameModal && (fileToRename || folderToRename || fileToAlias) && (
         <div
           style={{
             position: 'fixed',
@@ -1994,6 +3740,8 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
           onClick={() => {
             setShowRenameModal(false)
             setFileToRename(null)
+            setFolderToRename(null)
+            setFileToAlias(null)
             setNewFileName('')
           }}
         >
@@ -2010,11 +3758,13 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
             onClick={(e) => e.stopPropagation()}
           >
             <h2 style={{ marginTop: 0, marginBottom: '1.5rem', color: 'var(--text-primary, #333)' }}>
-              Renommer le fichier
+              {fileToAlias ? 'Créer un alias' : folderToRename ? 'Renommer le dossier' : 'Renommer le fichier'}
             </h2>
             <input
               type="text"
-              placeholder="Nouveau nom du fichier"
+              placeholder={
+                fileToAlias ? "Chemin de l'alias (ex. /Photos/copie.jpg)" : folderToRename ? 'Nouveau nom du dossier' : 'Nouveau nom du fichier'
+              }
               value={newFileName}
               onChange={(e) => setNewFileName(e.target.value)}
               onKeyDown={(e) => {
@@ -2023,6 +3773,8 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                 } else if (e.key === 'Escape') {
                   setShowRenameModal(false)
                   setFileToRename(null)
+                  setFolderToRename(null)
+                  setFileToAlias(null)
                   setNewFileName('')
                 }
               }}
@@ -2042,13 +3794,15 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                 onClick={() => {
                   setShowRenameModal(false)
                   setFileToRename(null)
+                  setFolderToRename(null)
+                  setFileToAlias(null)
                   setNewFileName('')
                 }}
               >
                 Annuler
               </Button>
               <Button variant="primary" onClick={confirmRename} disabled={isLoading || !newFileName.trim()}>
-                Renommer
+                {fileToAlias ? 'Créer' : 'Renommer'}
               </Button>
             </div>
           </div>
@@ -2220,9 +3974,9 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                 </div>
               ) : previewData ? (
                 <>
-                  {previewData.type === 'image' && previewBlobUrl && (
+                  {previewData.type === 'image' && (
                     <img
-                      src={previewBlobUrl}
+                      src={previewData.url}
                       alt={previewFile.logical_path?.split('/').pop() || 'Aperçu'}
                       style={{
                         maxWidth: '100%',
@@ -2231,6 +3985,11 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                       }}
                     />
                   )}
+                  {previewData.type === 'text' && previewData.truncated && (
+                    <p style={{ fontSize: '0.85rem', color: 'var(--text-secondary, #666)', marginTop: 0 }}>
+                      Aperçu limité au début du fichier ({formatSize(previewData.totalSize)} au total).
+                    </p>
+                  )}
                   {previewData.type === 'text' && (
                     <pre
                       style={{
@@ -2245,12 +4004,18 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                         wordWrap: 'break-word',
                       }}
                     >
-                      {new TextDecoder('utf-8').decode(previewData.data)}
+                      {previewData.text}
                     </pre>
                   )}
-                  {previewData.type === 'pdf' && previewBlobUrl && (
+                  {previewData.type === 'video' && (
+                    <video src={previewData.url} controls style={{ maxWidth: '100%', maxHeight: '70vh' }} />
+                  )}
+                  {previewData.type === 'audio' && (
+                    <audio src={previewData.url} controls style={{ width: '100%' }} />
+                  )}
+                  {previewData.type === 'pdf' && (
                     <iframe
-                      src={previewBlobUrl}
+                      src={previewData.url}
                       style={{
                         width: '100%',
                         height: '70vh',
@@ -2264,7 +4029,11 @@ export function DashboardPage({ wayneClient, onLogout }: DashboardPageProps) {
                       <div style={{ fontSize: '3rem', marginBottom: '1rem' }}>📄</div>
                       <p style={{ fontSize: '1.1rem', marginBottom: '0.5rem' }}>Aperçu non disponible</p>
                       <p style={{ fontSize: '0.9rem', marginBottom: '1.5rem' }}>
-                        Ce type de fichier ne peut pas être prévisualisé.
+                        {previewData.reason === 'too_large'
+                          ? 'Ce fichier est trop volumineux pour être prévisualisé.'
+                          : previewData.reason === 'mismatch'
+                            ? 'Le contenu de ce fichier ne correspond pas à son type : il n\'est pas affiché par sécurité.'
+                            : 'Ce type de fichier ne peut pas être prévisualisé.'}
                       </p>
                       {previewFile.logical_path && (
                         <button
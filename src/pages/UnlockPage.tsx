@@ -1,11 +1,15 @@
-import { useState } from 'react'
+import { useEffect, useState } from 'react'
 import { invoke } from '@tauri-apps/api/core'
+import { formatError, isCommandError } from '../utils/commandError'
+import { dryRun, invokeElevated, type DryRunReport } from '../utils/elevation'
 import { WayneClient } from '../wayne_client'
 import type { KeyEnvelopeDto } from '../wayne_dto'
 import { Card } from '../components/Card'
 import { Button } from '../components/Button'
 import { Input } from '../components/Input'
 import { StatusMessage } from '../components/StatusMessage'
+import { usePasswordConfirm } from '../components/PasswordConfirm'
+import { GuestBrowser } from '../components/GuestBrowser'
 import './UnlockPage.css'
 
 type MkekBootstrapResponse = {
@@ -31,8 +35,56 @@ const STORAGE_KEY = 'aether_drive_bootstrap_data'
 export function UnlockPage({ wayneClient, useWayne, onBootstrap, onUnlock, onGoToLogin, onDisableWayne, hasWayneEnvelopeId }: UnlockPageProps) {
   const [password, setPassword] = useState('')
   const [isLoading, setIsLoading] = useState(false)
-  const [status, setStatus] = useState<{ type: 'success' | 'error' | 'warning' | 'info'; message: string; isKeyMismatch?: boolean } | null>(null)
+  const [status, setStatus] = useState<{
+    type: 'success' | 'error' | 'warning' | 'info'
+    message: string
+    isKeyMismatch?: boolean
+    // MKEK reçue de Wayne, que la base locale ne reconnaît pas (pour l'archiver et repartir de zéro)
+    wayneMkek?: MkekBootstrapResponse
+  } | null>(null)
   const [isBootstrapMode, setIsBootstrapMode] = useState(false)
+  const [hint, setHint] = useState('')
+  const [storedHint, setStoredHint] = useState<string | null | undefined>(undefined)
+  const [rememberDevice, setRememberDevice] = useState(false)
+  const [hasDeviceKey, setHasDeviceKey] = useState(false)
+  const [transferCode, setTransferCode] = useState('')
+  // Fichier clé : demandé quand le coffre en exige un, en plus du mot de passe
+  const [needsKeyfile, setNeedsKeyfile] = useState(false)
+  const [keyfilePath, setKeyfilePath] = useState<string | null>(null)
+
+  useEffect(() => {
+    const stored = localStorage.getItem(STORAGE_KEY)
+    if (!stored) return
+    const { mkek } = JSON.parse(stored) as MkekBootstrapResponse
+    invoke<boolean>('keyfile_required', { mkek })
+      .then(setNeedsKeyfile)
+      .catch((e) => console.warn('keyfile_required failed:', e))
+  }, [])
+
+  const handleSelectKeyfile = async () => {
+    try {
+      setKeyfilePath(await invoke<string>('keyfile_select'))
+    } catch (e) {
+      if (!isCommandError(e) || e.code !== 'dialog_cancelled') {
+        setStatus({ type: 'error', message: formatError(e) })
+      }
+    }
+  }
+
+  useEffect(() => {
+    invoke<boolean>('device_unlock_status')
+      .then(setHasDeviceKey)
+      .catch((e) => console.warn('device_unlock_status failed:', e))
+  }, [])
+  const [passwordConfirm, askPassword] = usePasswordConfirm()
+
+  const handleShowHint = async () => {
+    try {
+      setStoredHint(await invoke<string | null>('get_password_hint'))
+    } catch (e) {
+      setStatus({ type: 'error', message: formatError(e) })
+    }
+  }
 
   const handleBootstrap = async () => {
     if (!password) {
@@ -52,7 +104,7 @@ export function UnlockPage({ wayneClient, useWayne, onBootstrap, onUnlock, onGoT
     setStatus(null)
 
     try {
-      const result = await invoke<MkekBootstrapResponse>('crypto_bootstrap', { password })
+      const result = await invoke<MkekBootstrapResponse>('crypto_bootstrap', { password, hint: hint || null })
 
       // Si Wayne est activé, sauvegarde le MKEK sur Wayne
       if (useWayne && wayneClient && wayneClient.getAccessToken()) {
@@ -74,7 +126,7 @@ export function UnlockPage({ wayneClient, useWayne, onBootstrap, onUnlock, onGoT
             message: `✅ Coffre initialisé et MKEK sauvegardé sur Wayne (ID: ${saveResponse.envelope_id}).`,
           })
         } catch (wayneError) {
-          const wayneErrorMsg = wayneError instanceof Error ? wayneError.message : String(wayneError)
+          const wayneErrorMsg = formatError(wayneError)
           setStatus({
             type: 'warning',
             message: `⚠️ Coffre initialisé localement mais échec de sauvegarde sur Wayne: ${wayneErrorMsg}. Les données sont sauvegardées localement.`,
@@ -92,7 +144,7 @@ export function UnlockPage({ wayneClient, useWayne, onBootstrap, onUnlock, onGoT
 
       onBootstrap(result)
     } catch (e) {
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      const errorMsg = formatError(e)
       setStatus({ type: 'error', message: `Erreur lors du bootstrap cryptographique: ${errorMsg}` })
     } finally {
       setIsLoading(false)
@@ -131,6 +183,8 @@ export function UnlockPage({ wayneClient, useWayne, onBootstrap, onUnlock, onGoT
                 nonce: mkekData.mkek.nonce,
                 payload: mkekData.mkek.payload,
               },
+              remember_device: rememberDevice,
+              keyfile_path: keyfilePath,
             },
           })
 
@@ -139,18 +193,25 @@ export function UnlockPage({ wayneClient, useWayne, onBootstrap, onUnlock, onGoT
             message: '✅ Coffre déverrouillé avec succès (MKEK récupéré depuis Wayne).',
           })
         } catch (envelopeError) {
-          const envelopeErrorMsg = envelopeError instanceof Error ? envelopeError.message : String(envelopeError)
+          if (isCommandError(envelopeError) && envelopeError.code === 'keyfile_required') {
+            setNeedsKeyfile(true)
+          }
+          const envelopeErrorMsg = formatError(envelopeError)
           
           // Détecte le cas spécifique d'une clé qui ne correspond pas
           let errorType: 'error' | 'warning' = 'error'
           let errorMessage = envelopeErrorMsg
           
           // Détecte le cas spécifique d'une clé qui ne correspond pas
-          const isKeyMismatch = envelopeErrorMsg.includes('clé de déchiffrement ne correspond pas') || 
-                                envelopeErrorMsg.includes('file is not a database') ||
-                                envelopeErrorMsg.includes('base de données locale ne correspond pas')
+          const isKeyMismatch =
+            isCommandError(envelopeError) &&
+            (envelopeError.code === 'index_key_mismatch' || envelopeError.code === 'vault_mismatch')
           
-          if (isKeyMismatch) {
+          if (isKeyMismatch && envelopeError.code === 'vault_mismatch') {
+            // Empreintes connues des deux côtés : on sait précisément quel coffre est lequel
+            errorType = 'warning'
+            errorMessage = `⚠️ Conflit détecté : le MKEK de Wayne ouvre le coffre ${envelopeError.params.actual}, alors que la base locale appartient au coffre ${envelopeError.params.expected}. Tu peux supprimer la base locale et réinitialiser avec Wayne.`
+          } else if (isKeyMismatch) {
             errorType = 'warning'
             errorMessage = '⚠️ Conflit détecté : La base de données locale ne correspond pas au MKEK de Wayne. Cela arrive si tu as créé un nouveau coffre localement. Tu peux supprimer la base locale et réinitialiser avec Wayne.'
           }
@@ -159,6 +220,7 @@ export function UnlockPage({ wayneClient, useWayne, onBootstrap, onUnlock, onGoT
             type: errorType,
             message: `Erreur lors de la récupération du MKEK depuis Wayne: ${errorMessage}`,
             isKeyMismatch: isKeyMismatch, // Flag pour afficher le bouton
+            wayneMkek: isKeyMismatch ? mkekData : undefined,
           })
           setIsLoading(false)
           return
@@ -184,6 +246,8 @@ export function UnlockPage({ wayneClient, useWayne, onBootstrap, onUnlock, onGoT
               nonce: mkekData.mkek.nonce,
               payload: mkekData.mkek.payload,
             },
+            remember_device: rememberDevice,
+            keyfile_path: keyfilePath,
           },
         })
 
@@ -195,13 +259,126 @@ export function UnlockPage({ wayneClient, useWayne, onBootstrap, onUnlock, onGoT
 
       onUnlock()
     } catch (e) {
-      const errorMsg = e instanceof Error ? e.message : String(e)
+      if (isCommandError(e) && e.code === 'keyfile_required') {
+        setNeedsKeyfile(true)
+      }
+      const errorMsg = formatError(e)
       setStatus({ type: 'error', message: `Erreur lors du déverrouillage: ${errorMsg}` })
     } finally {
       setIsLoading(false)
     }
   }
 
+  // Récupération : la base locale appartient peut-être au coffre créé sur cet appareil
+  const handleOpenWithLocalVault = async () => {
+    const stored = localStorage.getItem(STORAGE_KEY)
+    if (!stored) {
+      setStatus({ type: 'error', message: 'Aucun coffre local connu sur cet appareil.' })
+      return
+    }
+    const mkekData = JSON.parse(stored) as MkekBootstrapResponse
+    setIsLoading(true)
+    try {
+      await invoke('index_open_with_vault', {
+        req: { password, password_salt: mkekData.password_salt, mkek: mkekData.mkek, remember_device: false, keyfile_path: keyfilePath },
+      })
+      setStatus({ type: 'success', message: '✅ Base locale ouverte avec le coffre de cet appareil.' })
+      onUnlock()
+    } catch (e) {
+      setStatus({ type: 'error', message: `La base locale n'appartient pas non plus au coffre de cet appareil : ${formatError(e)}` })
+    } finally {
+      setIsLoading(false)
+    }
+  }
+
+  // Récupération : la base illisible est mise de côté (pas supprimée), une base neuve est créée
+  const handleArchiveAndRecreate = async (mkekData: MkekBootstrapResponse) => {
+    setIsLoading(true)
+    try {
+      const archived = await invoke<string | null>('index_archive_and_recreate', {
+        req: { password, password_salt: mkekData.password_salt, mkek: mkekData.mkek, remember_device: false, keyfile_path: keyfilePath },
+      })
+      setStatus({
+        type: 'success',
+        message: archived
+          ? `✅ Ancienne base mise de côté (${archived}) ; coffre déverrouillé avec une base neuve.`
+          : '✅ Coffre déverrouillé.',
+      })
+      onUnlock()
+    } catch (e) {
+      setStatus({ type: 'error', message: `Erreur lors de l'archivage de la base: ${formatError(e)}` })
+    } finally {
+      setIsLoading(false)
+    }
+  }
+
+  // Déverrouillage par la KEK conservée dans le trousseau de l'appareil : ni mot de passe ni Argon2
+  const handleDeviceUnlock = async () => {
+    setIsLoading(true)
+    setStatus(null)
+
+    try {
+      let mkekData: MkekBootstrapResponse
+      if (useWayne && wayneClient && wayneClient.getAccessToken()) {
+        const { envelope } = await wayneClient.getMyKeyEnvelope()
+        mkekData = { password_salt: envelope.password_salt, mkek: envelope.mkek }
+      } else {
+        const stored = localStorage.getItem(STORAGE_KEY)
+        if (!stored) {
+          setStatus({ type: 'error', message: 'Aucune donnée de bootstrap trouvée localement. Initialise d\'abord un coffre.' })
+          return
+        }
+        mkekData = JSON.parse(stored) as MkekBootstrapResponse
+      }
+
+      await invokeElevated('crypto_unlock_device', {
+        passwordSalt: mkekData.password_salt,
+        mkek: { nonce: mkekData.mkek.nonce, payload: mkekData.mkek.payload },
+      })
+      onUnlock()
+    } catch (e) {
+      if (isCommandError(e) && e.code === 'device_key_unavailable') {
+        setHasDeviceKey(false)
+      }
+      setStatus({ type: 'error', message: `Erreur lors du déverrouillage: ${formatError(e)}` })
+    } finally {
+      setIsLoading(false)
+    }
+  }
+
+  // Ajout d'un appareil : le paquet exporté ailleurs apporte le sel et la MKEK ; le mot de passe
+  // maître reste nécessaire pour déverrouiller
+  const handleImportBundle = async () => {
+    setIsLoading(true)
+    setStatus(null)
+    try {
+      const imported = await invokeElevated<MkekBootstrapResponse & {
+        vault_fingerprint: string
+        storj: { endpoint: string; bucket_name: string } | null
+        settings: number
+      }>('vault_bundle_import', { transferCode })
+      localStorage.setItem(
+        STORAGE_KEY,
+        JSON.stringify({ password_salt: imported.password_salt, mkek: imported.mkek }),
+      )
+      setTransferCode('')
+      setIsBootstrapMode(false)
+      setStatus({
+        type: 'success',
+        message: `✅ Configuration importée (coffre ${imported.vault_fingerprint.slice(0, 8)}${
+          imported.storj ? `, bucket ${imported.storj.bucket_name}` : ''
+        }, ${imported.settings} réglage(s)). Entre le mot de passe maître pour déverrouiller.`,
+      })
+    } catch (e) {
+      if (isCommandError(e) && e.code === 'dialog_cancelled') {
+        return
+      }
+      setStatus({ type: 'error', message: `Erreur lors de l'import: ${formatError(e)}` })
+    } finally {
+      setIsLoading(false)
+    }
+  }
+
   return (
     <div className="unlock-page">
       <div className="unlock-container">
@@ -245,6 +422,35 @@ export function UnlockPage({ wayneClient, useWayne, onBootstrap, onUnlock, onGoT
             }
           />
 
+          {!isBootstrapMode && needsKeyfile && (
+            <div style={{ display: 'flex', gap: '0.5rem', alignItems: 'center', marginBottom: '1rem' }}>
+              <Button variant="secondary" onClick={handleSelectKeyfile} disabled={isLoading}>
+                {keyfilePath ? 'Changer de fichier clé' : 'Sélectionner le fichier clé'}
+              </Button>
+              <span>{keyfilePath ? `🔑 ${keyfilePath.split(/[\\/]/).pop()}` : 'Ce coffre exige son fichier clé.'}</span>
+            </div>
+          )}
+
+          {isBootstrapMode && (
+            <Input
+              label="Indice (facultatif)"
+              value={hint}
+              onChange={(e) => setHint(e.target.value)}
+              placeholder="Ex. la variante avec l'année"
+              disabled={isLoading}
+              maxLength={100}
+              helperText="Stocké en clair sur cet appareil et affiché sur l'écran de déverrouillage. Il ne doit pas permettre de deviner le mot de passe."
+            />
+          )}
+
+          {!isBootstrapMode && storedHint !== undefined && (
+            <StatusMessage
+              type="info"
+              message={storedHint ? `💡 Indice : ${storedHint}` : 'Aucun indice enregistré pour ce coffre.'}
+              onDismiss={() => setStoredHint(undefined)}
+            />
+          )}
+
           {useWayne && wayneClient && wayneClient.getAccessToken() && (
             <StatusMessage
               type="info"
@@ -307,19 +513,49 @@ export function UnlockPage({ wayneClient, useWayne, onBootstrap, onUnlock, onGoT
               message={status.message}
               onDismiss={() => setStatus(null)}
             >
+              {status.type === 'warning' && status.isKeyMismatch && (
+                <div className="unlock-recovery">
+                  <Button variant="secondary" onClick={handleOpenWithLocalVault} disabled={isLoading} style={{ marginTop: '0.5rem' }}>
+                    Ouvrir avec le coffre de cet appareil
+                  </Button>
+                  {status.wayneMkek && (
+                    <Button
+                      variant="secondary"
+                      onClick={() => status.wayneMkek && handleArchiveAndRecreate(status.wayneMkek)}
+                      disabled={isLoading}
+                      style={{ marginTop: '0.5rem' }}
+                    >
+                      Mettre la base de côté et repartir de zéro
+                    </Button>
+                  )}
+                </div>
+              )}
               {status.type === 'warning' && status.isKeyMismatch && (
                 <Button
                   variant="secondary"
                   onClick={async () => {
+                    let plan: DryRunReport
+                    try {
+                      plan = await dryRun('reset_local_database')
+                    } catch (e) {
+                      setStatus({ type: 'error', message: `Erreur lors de la suppression de la base: ${formatError(e)}` })
+                      return
+                    }
+                    const confirmPassword = await askPassword(
+                      `Supprimer la base locale du coffre (index, corbeille, file hors ligne) : ${plan.entries} fichier(s), ${(plan.bytes / (1024 * 1024)).toFixed(1)} Mo.`,
+                    )
+                    if (confirmPassword === null) {
+                      return
+                    }
                     try {
-                      await invoke('reset_local_database')
+                      await invokeElevated('reset_local_database', undefined, confirmPassword)
                       setStatus({
                         type: 'info',
                         message: '✅ Base de données locale supprimée. Tu peux maintenant réinitialiser le coffre avec Wayne.',
                       })
                       setIsBootstrapMode(true)
                     } catch (e) {
-                      const errorMsg = e instanceof Error ? e.message : String(e)
+                      const errorMsg = formatError(e)
                       setStatus({
                         type: 'error',
                         message: `Erreur lors de la suppression de la base: ${errorMsg}`,
@@ -335,6 +571,18 @@ export function UnlockPage({ wayneClient, useWayne, onBootstrap, onUnlock, onGoT
             </StatusMessage>
           )}
 
+          {!isBootstrapMode && (
+            <label className="unlock-remember">
+              <input
+                type="checkbox"
+                checked={rememberDevice}
+                onChange={(e) => setRememberDevice(e.target.checked)}
+                disabled={isLoading}
+              />
+              <span>Se souvenir de cet appareil (la clé reste dans son trousseau sécurisé)</span>
+            </label>
+          )}
+
           <div className="unlock-actions">
             {isBootstrapMode ? (
               <Button
@@ -357,13 +605,41 @@ export function UnlockPage({ wayneClient, useWayne, onBootstrap, onUnlock, onGoT
                 Déverrouiller le coffre
               </Button>
             )}
+            {!isBootstrapMode && hasDeviceKey && (
+              <Button variant="secondary" onClick={handleDeviceUnlock} disabled={isLoading} fullWidth>
+                Déverrouiller avec cet appareil
+              </Button>
+            )}
+            {!isBootstrapMode && (
+              <Button variant="secondary" onClick={handleShowHint} disabled={isLoading} fullWidth>
+                Mot de passe oublié ? Afficher l'indice
+              </Button>
+            )}
+          </div>
+
+          <div className="unlock-import">
+            <Input
+              label="Ajouter cet appareil à un coffre existant"
+              value={transferCode}
+              onChange={(e) => setTransferCode(e.target.value)}
+              placeholder="ABCD-EFGH-JKLM-NPQR-STUV"
+              disabled={isLoading}
+              autoComplete="off"
+              helperText="Code affiché sur l'autre appareil lors de l'export de la configuration (Paramètres → Ajouter un appareil)."
+            />
+            <Button variant="secondary" onClick={handleImportBundle} disabled={isLoading || !transferCode.trim()} fullWidth>
+              Importer depuis un autre appareil
+            </Button>
           </div>
         </Card>
 
+        {!isBootstrapMode && <GuestBrowser />}
+
         <p className="unlock-footer">
           Le mot de passe maître ne quitte jamais ton appareil. Seul le MKEK chiffré est synchronisé avec Wayne.
         </p>
       </div>
+      {passwordConfirm}
     </div>
   )
 }
@@ -0,0 +1,74 @@
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+
+/**
+ * Contrat des événements backend → frontend (miroir de `src-tauri/src/events`).
+ * Tous passent par un seul canal ; le champ `type` discrimine la charge utile.
+ */
+export const APP_EVENT = 'aether://event'
+
+export type TransferDirection = 'upload' | 'download'
+export type SyncStatus = 'syncing' | 'idle' | 'failed'
+export type JobState = 'running' | 'cancelled' | 'completed' | 'failed'
+
+/** Compteurs du badge d'état (voir la commande `activity_summary`). */
+export interface ActivitySummary {
+  pendingUploads: number
+  failedTransfers: number
+  conflicts: number
+  integrityWarnings: number
+}
+
+export type AppEvent =
+  | {
+      type: 'transferProgress'
+      payload: {
+        fileId: string
+        direction: TransferDirection
+        bytesDone: number
+        bytesTotal: number
+        bytesPerSecond: number
+        etaSeconds: number | null
+      }
+    }
+  | { type: 'syncState'; payload: { status: SyncStatus; remoteCount: number | null } }
+  | { type: 'integrityWarning'; payload: { detail: string } }
+  | { type: 'vaultLocked' }
+  | { type: 'conflict'; payload: { logicalPath: string; existingFileId: string; newFileId: string; finalPath: string } }
+  | { type: 'trashPurged'; payload: { fileIds: string[] } }
+  | { type: 'exportProgress'; payload: { filesDone: number; filesTotal: number; logicalPath: string } }
+  | {
+      type: 'recovery'
+      payload: {
+        removedTempFiles: number
+        abortedMultipartUploads: number
+        resumedUploads: number
+        droppedUploads: string[]
+        remainingPending: number
+      }
+    }
+  | { type: 'quotaWarning'; payload: { thresholdPercent: number; usedBytes: number; limitBytes: number } }
+  | { type: 'trashSuggestion'; payload: { trashBytes: number; usedBytes: number; sharePercent: number } }
+  | { type: 'shareLinkOpened'; payload: { name: string } }
+  | { type: 'rulesApplied'; payload: { uploaded: number; trashed: number; failed: number } }
+  | { type: 'journalConflicts'; payload: { logicalPaths: string[] } }
+  | { type: 'indexDiverged'; payload: { devices: string[] } }
+  | { type: 'lockedFileEdited'; payload: { logicalPath: string; holder: string; expiresAt: number } }
+  | { type: 'concurrentSessions'; payload: { devices: string[] } }
+  | { type: 'workspaceChanged'; payload: { id: string; name: string; storjConfigured: boolean } }
+  | {
+      type: 'jobProgress'
+      payload: {
+        jobId: number
+        kind: string
+        label: string
+        step: string
+        stepIndex: number
+        stepCount: number
+        state: JobState
+      }
+    }
+  | { type: 'activitySummary'; payload: ActivitySummary }
+
+export function listenAppEvents(handler: (event: AppEvent) => void): Promise<UnlistenFn> {
+  return listen<AppEvent>(APP_EVENT, (e) => handler(e.payload))
+}
@@ -0,0 +1,271 @@
+/**
+ * Erreurs structurées renvoyées par les commandes Tauri ({ code, params }).
+ * Le backend ne transmet jamais de message : la traduction se fait ici.
+ */
+
+export interface CommandError {
+  code: string
+  params: Record<string, string | number>
+}
+
+const MESSAGES_FR: Record<string, string> = {
+  vault_locked: 'Le coffre est verrouillé. Déverrouille-le d\'abord.',
+  wrong_password: 'Mot de passe incorrect.',
+  index_key_mismatch: 'La base de données locale ne correspond pas à ce coffre.',
+  vault_mismatch: 'Ce mot de passe ouvre le coffre {actual}, mais cet appareil contient le coffre {expected}.',
+  index_error: 'Erreur de l\'index local.',
+  file_not_found: 'Fichier introuvable ({fileId}).',
+  file_quarantined: 'Ce fichier est en quarantaine : son contenu distant n\'a pas passé la vérification. Répare-le depuis les paramètres (Intégrité).',
+  file_conflict: 'Ce fichier vient d\'être modifié ailleurs : rien n\'a été écrasé. Recharge la liste et recommence.',
+  path_not_found: 'Aucun fichier à l\'emplacement {path}.',
+  invalid_file_id: 'Identifiant de fichier invalide ({fileId}).',
+  invalid_name: 'Nom invalide.',
+  invalid_hint: 'Indice invalide.',
+  invalid_appearance: 'Apparence de dossier invalide.',
+  already_exists: '« {path} » existe déjà.',
+  quota_exceeded: 'Budget de stockage dépassé : cet envoi ne rentre pas dans la limite fixée dans les paramètres.',
+  storj_not_configured: 'Le stockage distant n\'est pas configuré.',
+  remote_not_found: 'Le fichier est introuvable sur le stockage distant.',
+  offline: 'Stockage distant injoignable : l\'opération sera reprise une fois la connexion rétablie.',
+  remote_error: 'Erreur du stockage distant.',
+  invalid_aether_file: 'Le fichier n\'est pas un fichier Aether valide.',
+  encryption_failed: 'Le chiffrement a échoué.',
+  decryption_failed: 'Le déchiffrement a échoué.',
+  keychain_error: 'Le trousseau du système est inaccessible.',
+  io_error: 'Erreur de lecture ou d\'écriture sur le disque.',
+  dialog_cancelled: 'Aucun fichier sélectionné.',
+  dialog_timeout: 'La sélection de fichier a expiré.',
+  cancelled: 'Opération annulée.',
+  command_not_allowed: 'Commande non autorisée : {command}.',
+  elevation_required: 'Cette opération doit être confirmée dans la fenêtre du système.',
+  elevation_denied: 'Opération annulée.',
+  password_required: 'Saisis ton mot de passe pour confirmer cette opération.',
+  invalid_share_link: 'Lien de partage invalide.',
+  share_verification_failed: 'Le fichier partagé a été modifié ou le lien est incorrect.',
+  preview_too_large: 'Fichier trop volumineux pour un aperçu direct : téléchargez-le pour l\'ouvrir.',
+  preview_refused: 'Aperçu refusé : le contenu ne correspond pas au type du fichier.',
+  invalid_rule: 'Règle invalide.',
+  rule_not_found: 'Cette règle n\'existe plus.',
+  invalid_hook: 'Hook invalide.',
+  hook_not_found: 'Ce hook n\'existe plus.',
+  invalid_backup: 'Sauvegarde de l\'index invalide.',
+  invalid_clone: 'Clonage du coffre impossible.',
+  invalid_archive: 'Archive chiffrée invalide.',
+  invalid_log_settings: 'Réglages de journalisation invalides : nom de module mal formé.',
+  invalid_journal: 'Journal de synchronisation illisible (modifié, ou d\'un autre coffre).',
+  invalid_bundle: 'Paquet de configuration invalide ou code de transfert mal saisi.',
+  bundle_decrypt_failed: 'Code de transfert incorrect, ou paquet de configuration modifié.',
+  invalid_workspace: 'Opération impossible sur cet espace de travail.',
+  invalid_setup: 'Cette étape de la configuration n\'est pas disponible.',
+  invalid_cursor: 'La liste a changé pendant son chargement : rechargez-la.',
+  workspace_not_found: 'Cet espace de travail n\'existe plus.',
+  invalid_snapshot: 'Opération impossible sur cet instantané.',
+  snapshot_not_found: 'Cet instantané n\'existe plus.',
+  invalid_guest_mode: 'Mode invité indisponible.',
+  invalid_lock: 'Verrou impossible : chemin, nom affiché ou durée invalide.',
+  file_locked: '« {path} » est verrouillé par {holder} sur un autre appareil.',
+  invalid_delegation: 'Délégation impossible : l\'émetteur doit être en HTTPS, le jeton valide et la durée entre 15 minutes et 7 jours.',
+  upload_preflight_failed: 'Envoi refusé avant de commencer : {count} problème(s) relevé(s).',
+  delegation_rejected: 'L\'émetteur d\'identifiants a refusé la demande (HTTP {status}) : le jeton de cet appareil a peut-être été révoqué.',
+  device_key_unavailable: 'Cet appareil ne peut plus déverrouiller le coffre seul. Saisis le mot de passe maître.',
+  keyfile_required: 'Ce coffre exige son fichier clé en plus du mot de passe maître : sélectionne-le.',
+  invalid_keyfile: 'Fichier clé refusé.',
+  internal_error: 'Erreur interne.',
+}
+
+const INVALID_NAME_REASONS_FR: Record<string, string> = {
+  empty: 'Le nom ne peut pas être vide.',
+  contains_slash: 'Le nom ne peut pas contenir de slash.',
+  alias_path: "Le chemin de l'alias doit commencer par / et désigner un fichier.",
+  alias_target: "Un alias ne peut désigner qu'un fichier, pas un dossier.",
+  root: 'La racine du coffre ne peut être ni déplacée ni supprimée.',
+  not_a_file_path: 'Le chemin doit désigner un fichier.',
+  path_too_long: 'Le chemin est trop long (1024 octets au plus).',
+  empty_name: 'Le chemin contient un nom vide.',
+  relative_name: 'Les noms « . » et « .. » sont interdits.',
+  name_too_long: 'Le nom est trop long (255 octets au plus).',
+  control_character: 'Le nom ne peut pas contenir de caractère de contrôle.',
+  reserved_character: 'Le nom ne peut pas contenir \\ : * ? " < > |.',
+  trailing_dot_or_space: 'Le nom ne peut pas se terminer par un point ou une espace.',
+  reserved_name: 'Ce nom est réservé par Windows (CON, AUX, NUL…).',
+}
+
+const INVALID_HINT_REASONS_FR: Record<string, string> = {
+  too_long: 'L\'indice ne peut pas dépasser 100 caractères.',
+  contains_password: 'L\'indice ne doit pas contenir le mot de passe.',
+}
+
+const INVALID_APPEARANCE_REASONS_FR: Record<string, string> = {
+  invalid_color: 'La couleur doit être au format #rrggbb.',
+  invalid_icon: 'L\'icône doit être un emoji ou un pictogramme de 8 caractères au plus, sans espace.',
+}
+
+const INVALID_SHARE_LINK_REASONS_FR: Record<string, string> = {
+  no_pending_link: 'Aucun lien de partage en attente.',
+  insecure_url: 'Le lien de partage ne pointe pas vers une adresse sécurisée (HTTPS).',
+  unsupported_version: 'Ce lien de partage a été créé par une version plus récente d\'Aether Drive.',
+}
+
+const INVALID_RULE_REASONS_FR: Record<string, string> = {
+  empty_name: 'Donne un nom à la règle.',
+  name_too_long: 'Le nom de la règle ne peut pas dépasser 80 caractères.',
+  relative_local_dir: 'Le dossier local doit être un chemin absolu.',
+  root_folder: 'La mise à la corbeille automatique ne peut pas viser la racine du coffre.',
+  invalid_age: 'L\'âge doit être compris entre 1 et 3650 jours.',
+}
+
+const INVALID_HOOK_REASONS_FR: Record<string, string> = {
+  empty_name: 'Donne un nom au hook.',
+  name_too_long: 'Le nom du hook ne peut pas dépasser 80 caractères.',
+  no_events: 'Choisis au moins un événement.',
+  relative_program: 'Le script doit être désigné par un chemin absolu.',
+  invalid_url: 'L\'adresse du webhook est invalide.',
+  insecure_url: 'Le webhook doit utiliser une adresse HTTPS.',
+}
+
+const INVALID_CLONE_REASONS_FR: Record<string, string> = {
+  target_not_empty: 'La destination du clone doit être vide.',
+  same_as_source: 'Le clone doit aller dans un autre bucket (ou un autre préfixe) que le coffre.',
+}
+
+const INVALID_ARCHIVE_REASONS_FR: Record<string, string> = {
+  destination_exists: 'Un fichier existe déjà à cet emplacement : choisis un autre nom d\'archive.',
+  wrong_password: 'Mot de passe maître incorrect.',
+  key_mismatch: 'L\'enveloppe de clé ne correspond pas à ce coffre.',
+  password_too_short: 'Choisis un mot de passe d\'au moins 8 caractères pour le lot.',
+  empty_selection: 'Sélectionne au moins un fichier à exporter.',
+}
+
+const INVALID_BACKUP_REASONS_FR: Record<string, string> = {
+  not_a_backup: 'Cet objet n\'est pas une sauvegarde de l\'index.',
+  key_mismatch: 'Cette sauvegarde appartient à un autre coffre (clé différente).',
+  zero_retention: 'Conserve au moins une sauvegarde.',
+}
+
+const INVALID_WORKSPACE_REASONS_FR: Record<string, string> = {
+  empty_name: 'Donne un nom à l\'espace de travail.',
+  invalid_name: 'Le nom de l\'espace ne peut pas dépasser 60 caractères ni contenir de caractères de contrôle.',
+  duplicate_name: 'Un espace de travail porte déjà ce nom.',
+  default_workspace: 'L\'espace de travail par défaut ne peut pas être supprimé.',
+  active_workspace: 'Ouvre un autre espace avant de supprimer celui-ci.',
+}
+
+const INVALID_SETUP_REASONS_FR: Record<string, string> = {
+  out_of_order: 'Termine d\'abord les étapes précédentes de la configuration.',
+  vault_created: 'Le coffre est déjà créé : reprends la configuration à l\'étape suivante.',
+  other_vault: 'Le coffre déverrouillé n\'est pas celui créé par la configuration en cours.',
+  finished: 'La configuration de cet appareil est déjà terminée.',
+}
+
+const INVALID_SNAPSHOT_REASONS_FR: Record<string, string> = {
+  empty_name: 'Donne un nom à l\'instantané.',
+  invalid_name: 'Le nom de l\'instantané ne peut pas dépasser 60 caractères ni contenir de caractères de contrôle.',
+  duplicate_name: 'Un instantané porte déjà ce nom.',
+}
+
+const INVALID_KEYFILE_REASONS_FR: Record<string, string> = {
+  length: 'Ce fichier n\'est pas un fichier clé : il doit faire exactement 32 octets.',
+  already_enrolled: 'Ce coffre a déjà un fichier clé.',
+  not_enrolled: 'Ce coffre n\'a pas de fichier clé.',
+  mismatch: 'Le fichier clé relu ne correspond pas à celui écrit : choisis un autre support.',
+}
+
+const INVALID_GUEST_MODE_REASONS_FR: Record<string, string> = {
+  passphrase_too_short: 'La phrase d\'accès invité doit compter au moins 8 caractères.',
+  not_enabled: 'Aucune phrase d\'accès invité n\'est définie pour ce coffre.',
+  corrupted_keys: 'Les clés du mode invité sont illisibles : redéfinis la phrase d\'accès depuis les paramètres.',
+}
+
+const PREFLIGHT_PATH_REASONS_FR: Record<string, string> = {
+  not_a_file_path: 'chemin de fichier invalide',
+  path_too_long: 'chemin trop long (1024 octets au plus)',
+  empty_name: 'nom vide',
+  relative_name: 'nom « . » ou « .. » interdit',
+  name_too_long: 'nom trop long (255 octets au plus)',
+  control_character: 'caractère de contrôle dans le nom',
+  reserved_character: 'caractère réservé (\\ : * ? " < > |)',
+  trailing_dot_or_space: 'nom terminé par un point ou une espace',
+  reserved_name: 'nom réservé par Windows (CON, AUX, NUL…)',
+}
+
+export type PreflightProblem =
+  | { kind: 'invalid_path'; logical_path: string; reason: string }
+  | { kind: 'unreadable'; source: string; detail: string }
+  | { kind: 'quota_exceeded'; requested_bytes: number; used_bytes: number; limit_bytes: number }
+  | { kind: 'insufficient_disk_space'; required_bytes: number; available_bytes: number }
+
+const toMb = (bytes: number) => (bytes / (1024 * 1024)).toFixed(1)
+
+/** Décrit un problème relevé par les vérifications préalables d'un envoi. */
+export function describePreflightProblem(problem: PreflightProblem): string {
+  switch (problem.kind) {
+    case 'invalid_path':
+      return `« ${problem.logical_path} » : ${PREFLIGHT_PATH_REASONS_FR[problem.reason] ?? 'chemin refusé'}`
+    case 'unreadable':
+      return `« ${problem.source} » illisible (${problem.detail})`
+    case 'quota_exceeded':
+      return `budget de stockage dépassé : ${toMb(problem.requested_bytes)} Mo à envoyer, ${toMb(Math.max(0, problem.limit_bytes - problem.used_bytes))} Mo disponibles`
+    case 'insufficient_disk_space':
+      return `espace disque insuffisant pour chiffrer : ${toMb(problem.required_bytes)} Mo nécessaires, ${toMb(problem.available_bytes)} Mo libres`
+  }
+}
+
+export function isCommandError(e: unknown): e is CommandError {
+  return typeof e === 'object' && e !== null && typeof (e as CommandError).code === 'string'
+}
+
+/**
+ * Convertit n'importe quelle erreur (commande Tauri, Error JS, chaîne) en message lisible.
+ */
+export function formatError(e: unknown): string {
+  if (isCommandError(e)) {
+    if (e.code === 'invalid_name' && typeof e.params?.reason === 'string') {
+      return INVALID_NAME_REASONS_FR[e.params.reason] ?? MESSAGES_FR.invalid_name
+    }
+    if (e.code === 'invalid_hint' && typeof e.params?.reason === 'string') {
+      return INVALID_HINT_REASONS_FR[e.params.reason] ?? MESSAGES_FR.invalid_hint
+    }
+    if (e.code === 'invalid_appearance' && typeof e.params?.reason === 'string') {
+      return INVALID_APPEARANCE_REASONS_FR[e.params.reason] ?? MESSAGES_FR.invalid_appearance
+    }
+    if (e.code === 'invalid_share_link' && typeof e.params?.reason === 'string') {
+      return INVALID_SHARE_LINK_REASONS_FR[e.params.reason] ?? MESSAGES_FR.invalid_share_link
+    }
+    if (e.code === 'invalid_rule' && typeof e.params?.reason === 'string') {
+      return INVALID_RULE_REASONS_FR[e.params.reason] ?? MESSAGES_FR.invalid_rule
+    }
+    if (e.code === 'invalid_hook' && typeof e.params?.reason === 'string') {
+      return INVALID_HOOK_REASONS_FR[e.params.reason] ?? MESSAGES_FR.invalid_hook
+    }
+    if (e.code === 'invalid_backup' && typeof e.params?.reason === 'string') {
+      return INVALID_BACKUP_REASONS_FR[e.params.reason] ?? MESSAGES_FR.invalid_backup
+    }
+    if (e.code === 'invalid_clone' && typeof e.params?.reason === 'string') {
+      return INVALID_CLONE_REASONS_FR[e.params.reason] ?? MESSAGES_FR.invalid_clone
+    }
+    if (e.code === 'invalid_archive' && typeof e.params?.reason === 'string') {
+      return INVALID_ARCHIVE_REASONS_FR[e.params.reason] ?? MESSAGES_FR.invalid_archive
+    }
+    if (e.code === 'invalid_workspace' && typeof e.params?.reason === 'string') {
+      return INVALID_WORKSPACE_REASONS_FR[e.params.reason] ?? MESSAGES_FR.invalid_workspace
+    }
+    if (e.code === 'invalid_setup' && typeof e.params?.reason === 'string') {
+      return INVALID_SETUP_REASONS_FR[e.params.reason] ?? MESSAGES_FR.invalid_setup
+    }
+    if (e.code === 'invalid_guest_mode' && typeof e.params?.reason === 'string') {
+      return INVALID_GUEST_MODE_REASONS_FR[e.params.reason] ?? MESSAGES_FR.invalid_guest_mode
+    }
+    if (e.code === 'invalid_snapshot' && typeof e.params?.reason === 'string') {
+      return INVALID_SNAPSHOT_REASONS_FR[e.params.reason] ?? MESSAGES_FR.invalid_snapshot
+    }
+    if (e.code === 'invalid_keyfile' && typeof e.params?.reason === 'string') {
+      return INVALID_KEYFILE_REASONS_FR[e.params.reason] ?? MESSAGES_FR.invalid_keyfile
+    }
+    if (e.code === 'upload_preflight_failed' && Array.isArray(e.params?.problems)) {
+      return `Envoi refusé : ${(e.params.problems as unknown as PreflightProblem[]).map(describePreflightProblem).join(' ; ')}`
+    }
+    const template = MESSAGES_FR[e.code] ?? `Erreur inattendue (${e.code}).`
+    return template.replace(/\{(\w+)\}/g, (_, key: string) => String(e.params?.[key] ?? ''))
+  }
+  return e instanceof Error ? e.message : String(e)
+}
@@ -0,0 +1,51 @@
+import { invoke, type InvokeArgs } from '@tauri-apps/api/core'
+import { isCommandError } from './commandError'
+
+/**
+ * Appelle une commande sensible (destructive ou gestion des clés).
+ *
+ * Les commandes destructives exigent le mot de passe, saisi à nouveau et vérifié par le
+ * backend (sinon rejet `password_required` ou `wrong_password`) ; les autres affichent
+ * d'abord une confirmation native (sinon rejet `elevation_denied`).
+ */
+export async function invokeElevated<T>(command: string, args?: InvokeArgs, password?: string): Promise<T> {
+  await invoke('request_elevation', { command, password: password ?? null })
+  return invoke<T>(command, args)
+}
+
+/** Ce qu'une commande appelée avec `dryRun` toucherait. */
+export type DryRunReport = {
+  entries: number
+  paths: string[]
+  deleted_objects: number
+  bytes: number
+}
+
+/**
+ * Essai à blanc d'une commande destructive ou groupée : rien n'est modifié, donc aucune
+ * élévation n'est demandée. Sert à détailler la confirmation avant l'appel réel.
+ */
+export function dryRun(command: string, args?: Record<string, unknown>): Promise<DryRunReport> {
+  return invoke<DryRunReport>(command, { ...args, dryRun: true })
+}
+
+/**
+ * Comme `invokeElevated`, pour une commande qui fait sortir la clé du coffre : si le mot de
+ * passe n'a pas été saisi récemment (rejet `password_required`), il est redemandé avec
+ * `askPassword` puis l'appel est refait. Retourne `null` si l'utilisateur annule.
+ */
+export async function invokeFresh<T>(
+  command: string,
+  args: InvokeArgs | undefined,
+  askPassword: (description: string) => Promise<string | null>,
+  description: string,
+): Promise<T | null> {
+  try {
+    return await invokeElevated<T>(command, args)
+  } catch (e) {
+    if (!isCommandError(e) || e.code !== 'password_required') throw e
+  }
+  const password = await askPassword(description)
+  if (!password) return null
+  return invokeElevated<T>(command, args, password)
+}
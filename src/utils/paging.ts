@@ -0,0 +1,33 @@
+/**
+ * Listes paginées renvoyées par le backend (voir `aether_core::paging`)
+ */
+
+import { invoke } from '@tauri-apps/api/core'
+
+export interface Page<T> {
+  items: T[]
+  // Jeton de la page suivante, absent (null) sur la dernière
+  next_cursor: string | null
+  // Éléments de la liste complète
+  total: number
+}
+
+/**
+ * Charge toutes les pages d'une commande paginée, une réponse IPC bornée à la fois.
+ * Retourne la dernière page, dont `items` contient les éléments de toutes les pages.
+ */
+export async function invokeAllPages<T, P extends Page<T> = Page<T>>(
+  command: string,
+  args: Record<string, unknown> = {},
+): Promise<P> {
+  const items: T[] = []
+  let cursor: string | null = null
+  for (;;) {
+    const page: P = await invoke<P>(command, { ...args, page: { cursor } })
+    items.push(...page.items)
+    if (!page.next_cursor) {
+      return { ...page, items }
+    }
+    cursor = page.next_cursor
+  }
+}